@@ -0,0 +1,254 @@
+// benchmarks for hot paths, run with `cargo bench --features testkit`, add the parallel
+//  feature for the scaling of the parallel paths
+use criterion::{criterion_group, criterion_main, Criterion};
+use nbroutes_util::bounds::bounding_circles;
+use nbroutes_util::coord::{Coord, Locatable};
+use nbroutes_util::engine_response::{
+    parse_osrm_table_response, parse_osrm_table_response_owned, parse_valhalla_route_response,
+    parse_valhalla_route_response_owned,
+};
+use nbroutes_util::matrix_view::MatrixData;
+use nbroutes_util::polygon_store::PolygonStore;
+#[cfg(feature = "parallel")]
+use nbroutes_util::polygon_store::DEFAULT_PARALLEL_CHUNK_SIZE;
+use nbroutes_util::polyline;
+use nbroutes_util::prepared_polygon::prepare_polygons;
+use nbroutes_util::testkit::{
+    circle_polygon, grid_coords, random_coords, ring_polygon, AreaBuilder,
+};
+use nbroutes_util::{find_area, find_area_with_bounds};
+use std::collections::HashMap;
+use std::time::Duration;
+
+// bench measures f under name
+fn bench<R, F: FnMut() -> R>(c: &mut Criterion, name: &str, mut f: F) {
+    c.bench_function(name, |b| b.iter(&mut f));
+}
+
+fn bench_find_area(c: &mut Criterion) {
+    // 5 areas shaped as rings around the same center, coordinates only fall in the last one
+    let center = Coord::new(1.3, 103.8);
+    let mut polygons = HashMap::new();
+    let mut areas = vec![];
+    for i in 0..5 {
+        let name = format!("ring-{}", i);
+        let inner = 0.1 + i as f64 * 0.2;
+        polygons.insert(
+            name.clone(),
+            prepare_polygons(vec![ring_polygon(&center, inner + 0.2, inner)]),
+        );
+        areas.push(AreaBuilder::new(name.as_str(), "4w").build());
+    }
+    let coords = grid_coords(&Coord::new(2.3, 104.8), 25, 40, 0.001);
+    assert_eq!(coords.len(), 1000);
+
+    bench(c, "find_area/5_rings/1000_coords", || {
+        find_area(&None, &coords, &polygons, &areas, false, None, false).unwrap()
+    });
+    bench(c, "find_area/5_rings/1000_coords/outlier", || {
+        find_area(&None, &coords, &polygons, &areas, true, None, false).unwrap()
+    });
+
+    // 50 areas with dense borders spread around the globe, coordinates only fall in the last one
+    let mut polygons = HashMap::new();
+    let mut areas = vec![];
+    for i in 0..50 {
+        let name = format!("area-{}", i);
+        let center = Coord::new(
+            -60.0 + (i / 10) as f64 * 30.0,
+            -170.0 + (i % 10) as f64 * 35.0,
+        );
+        polygons.insert(
+            name.clone(),
+            prepare_polygons(vec![circle_polygon(&center, 5.0, 2000)]),
+        );
+        areas.push(AreaBuilder::new(name.as_str(), "4w").build());
+    }
+    let bounds = bounding_circles(&polygons);
+    let coords = random_coords(&Coord::new(60.0, 145.0), 3.0, 1000, 11);
+
+    // with tolerate_outlier every coordinate is checked against every area
+    bench(c, "find_area/50_areas/1000_coords/outlier", || {
+        find_area(&None, &coords, &polygons, &areas, true, None, false).unwrap()
+    });
+    bench(c, "find_area/50_areas/1000_coords/outlier/bounds", || {
+        find_area_with_bounds(
+            &None,
+            &coords,
+            &polygons,
+            Some(&bounds),
+            &areas,
+            true,
+            None,
+            false,
+        )
+        .unwrap()
+    });
+}
+
+fn bench_is_in_polygons(c: &mut Criterion) {
+    let center = Coord::new(1.3, 103.8);
+    let polygons = prepare_polygons(vec![ring_polygon(&center, 1.0, 0.5)]);
+    let coords = random_coords(&center, 1.2, 1000, 42);
+
+    bench(c, "is_in_polygons/ring/1000_coords", || {
+        coords
+            .iter()
+            .filter(|c| c.is_in_polygons(&polygons))
+            .count()
+    });
+}
+
+fn bench_polygon_store(c: &mut Criterion) {
+    // 10 areas with dense borders next to each other, 100k coordinates spread over all of them
+    let mut polygons = HashMap::new();
+    let mut areas = vec![];
+    for i in 0..10 {
+        let name = format!("area-{}", i);
+        let center = Coord::new(1.0 + (i / 5) as f64 * 2.0, 100.0 + (i % 5) as f64 * 2.0);
+        polygons.insert(
+            name.clone(),
+            prepare_polygons(vec![circle_polygon(&center, 1.0, 1000)]),
+        );
+        areas.push(AreaBuilder::new(name.as_str(), "4w").build());
+    }
+    let store = PolygonStore::load(&areas, polygons);
+    let coords = random_coords(&Coord::new(2.0, 104.0), 3.0, 100_000, 13);
+
+    bench(c, "polygon_store/areas_of/100k_coords", || {
+        store.areas_of(&coords, &areas)
+    });
+    // scaling with the number of threads of the pool
+    #[cfg(feature = "parallel")]
+    for threads in [1, 2, 4, 8] {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+        bench(
+            c,
+            &format!(
+                "polygon_store/areas_of_parallel/100k_coords/{}_threads",
+                threads
+            ),
+            || {
+                pool.install(|| {
+                    store.areas_of_parallel(&coords, &areas, DEFAULT_PARALLEL_CHUNK_SIZE)
+                })
+            },
+        );
+    }
+}
+
+fn bench_polyline(c: &mut Criterion) {
+    let coords = random_coords(&Coord::new(1.3, 103.8), 0.5, 1000, 7);
+    let encoded = polyline::encode(&coords, polyline::PRECISION_POLYLINE6);
+
+    bench(c, "polyline/decode/1000_coords", || {
+        polyline::decode(encoded.as_str(), polyline::PRECISION_POLYLINE6).unwrap()
+    });
+    bench(c, "polyline/encode/1000_coords", || {
+        polyline::encode(&coords, polyline::PRECISION_POLYLINE6)
+    });
+}
+
+fn bench_matrix_binary_encode(c: &mut Criterion) {
+    let size = 1000;
+    let mut data = MatrixData::new(size, size);
+    for i in 0..size * size {
+        data.durations[i] = Some((i % 3600) as f64);
+        data.distances[i] = Some((i % 100000) as f64);
+    }
+    let output = data.to_output(false);
+
+    bench(c, "matrix/binary_encode/1000x1000", || {
+        output.binary_encode()
+    });
+}
+
+fn bench_engine_response(c: &mut Criterion) {
+    // a valhalla trip of 2000 maneuvers along a 20001 points shape
+    let coords = random_coords(&Coord::new(1.3, 103.8), 0.5, 20001, 3);
+    let maneuvers: Vec<serde_json::Value> = (0..2000)
+        .map(|i| {
+            serde_json::json!({
+                "type": if i == 0 { 1 } else { 10 },
+                "instruction": format!("Turn right onto Street {}.", i),
+                "verbal_pre_transition_instruction": format!("Turn right onto Street {}.", i),
+                "street_names": [format!("Street {}", i)],
+                "time": 10.0,
+                "length": 0.1,
+                "cost": 12.0,
+                "begin_shape_index": i * 10,
+                "end_shape_index": (i + 1) * 10,
+                "bearing_before": 0,
+                "bearing_after": 90,
+                "travel_mode": "drive",
+                "travel_type": "car",
+            })
+        })
+        .collect();
+    let valhalla = serde_json::json!({
+        "trip": {
+            "locations": [{"lat": 1.3, "lon": 103.8, "type": "break"}, {"lat": 1.4, "lon": 103.9, "type": "break"}],
+            "legs": [{
+                "maneuvers": maneuvers,
+                "summary": {"length": 200.0, "time": 20000.0},
+                "shape": polyline::encode(&coords, polyline::PRECISION_POLYLINE6),
+                "shape_attributes": {"time": vec![1.0; 20000], "length": vec![0.01; 20000], "speed": vec![36.0; 20000]},
+            }],
+            "summary": {"length": 200.0, "time": 20000.0, "cost": 24000.0},
+            "units": "kilometers",
+            "status": 0,
+            "status_message": "Found route between points",
+        },
+    })
+    .to_string();
+
+    bench(c, "engine_response/valhalla/2000_maneuvers/owned", || {
+        parse_valhalla_route_response_owned(valhalla.as_str(), polyline::PRECISION_POLYLINE)
+            .unwrap()
+    });
+    bench(
+        c,
+        "engine_response/valhalla/2000_maneuvers/borrowed",
+        || parse_valhalla_route_response(valhalla.as_str(), polyline::PRECISION_POLYLINE).unwrap(),
+    );
+
+    // an osrm table of 300x300 with its waypoints
+    let size = 300;
+    let waypoints: Vec<serde_json::Value> = (0..size)
+        .map(|i| serde_json::json!({"hint": format!("hint-{}", i), "distance": 1.5, "name": format!("Street {}", i), "location": [103.8, 1.3]}))
+        .collect();
+    let row: Vec<f64> = (0..size).map(|i| i as f64 * 1.5).collect();
+    let osrm = serde_json::json!({
+        "code": "Ok",
+        "durations": vec![row.clone(); size],
+        "distances": vec![row; size],
+        "sources": waypoints,
+        "destinations": waypoints,
+    })
+    .to_string();
+
+    bench(c, "engine_response/osrm_table/300x300/owned", || {
+        parse_osrm_table_response_owned(osrm.as_str()).unwrap()
+    });
+    bench(c, "engine_response/osrm_table/300x300/borrowed", || {
+        parse_osrm_table_response(osrm.as_str()).unwrap()
+    });
+}
+
+// `cargo bench --features testkit -- <filter>` only runs the benchmarks matching the filter
+criterion_group! {
+    name = benches;
+    config = Criterion::default()
+        .warm_up_time(Duration::from_millis(500))
+        .measurement_time(Duration::from_secs(2));
+    targets = bench_find_area,
+        bench_is_in_polygons,
+        bench_polygon_store,
+        bench_polyline,
+        bench_matrix_binary_encode,
+        bench_engine_response
+}
+criterion_main!(benches);
@@ -0,0 +1,317 @@
+use crate::coord::{Coord, Locatable};
+use crate::def::RouteT;
+use crate::polyline;
+use crate::util::straight_distance;
+use std::collections::HashSet;
+
+// precision used to quantize coordinates when comparing geometries, ~1m
+const OVERLAP_QUANTIZE_FACTOR: f64 = 100000.0;
+
+pub trait AlternativeRoute {
+    fn duration(&self) -> f64;
+    fn distance(&self) -> f64;
+    fn geometry(&self) -> Option<&str>;
+    fn has_toll(&self) -> bool;
+}
+
+impl<L> AlternativeRoute for RouteT<L> {
+    fn duration(&self) -> f64 {
+        self.duration
+    }
+    fn distance(&self) -> f64 {
+        self.distance
+    }
+    fn geometry(&self) -> Option<&str> {
+        self.geometry.as_deref()
+    }
+    // routes without toll_cost were not checked against the toll table and count as toll free
+    fn has_toll(&self) -> bool {
+        self.toll_cost
+            .as_ref()
+            .map(|v| v.crossings > 0)
+            .unwrap_or(false)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AlternativesOptions {
+    // max number of routes to keep
+    pub altcount: usize,
+    // routes sharing more than this ratio of their length with a better route are dropped
+    pub max_overlap: f64,
+    pub duration_weight: f64,
+    pub distance_weight: f64,
+    // score added to routes with tolls
+    pub toll_penalty: f64,
+    // precision of the encoded route geometries
+    pub precision: u32,
+}
+
+impl Default for AlternativesOptions {
+    fn default() -> Self {
+        AlternativesOptions {
+            altcount: 3,
+            max_overlap: 0.9,
+            duration_weight: 1.0,
+            distance_weight: 0.0,
+            toll_penalty: 0.0,
+            precision: polyline::PRECISION_POLYLINE6,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DropReason {
+    // overlaps with the kept route at `kept_index` of the input
+    Duplicate { kept_index: usize, overlap: f64 },
+    // exceeds altcount
+    Truncated,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DroppedAlternative {
+    // index in the input routes
+    pub index: usize,
+    pub score: f64,
+    pub reason: DropReason,
+}
+
+#[derive(Debug)]
+pub struct RankedAlternatives<T> {
+    pub routes: Vec<T>,
+    // index in the input routes for each kept route
+    pub kept_indices: Vec<usize>,
+    pub dropped: Vec<DroppedAlternative>,
+}
+
+struct Candidate {
+    index: usize,
+    score: f64,
+    segments: HashSet<(i64, i64, i64, i64)>,
+    length: f64,
+    segment_lengths: Vec<((i64, i64, i64, i64), f64)>,
+}
+
+fn quantize(c: &Coord) -> (i64, i64) {
+    (
+        (c.lat() * OVERLAP_QUANTIZE_FACTOR).round() as i64,
+        (c.lng() * OVERLAP_QUANTIZE_FACTOR).round() as i64,
+    )
+}
+
+fn candidate<T: AlternativeRoute>(
+    index: usize,
+    route: &T,
+    opts: &AlternativesOptions,
+) -> Candidate {
+    let mut score =
+        route.duration() * opts.duration_weight + route.distance() * opts.distance_weight;
+    if route.has_toll() {
+        score += opts.toll_penalty;
+    }
+
+    let coords = route
+        .geometry()
+        .and_then(|g| polyline::decode(g, opts.precision).ok())
+        .unwrap_or_default();
+    let mut segments = HashSet::new();
+    let mut segment_lengths = vec![];
+    let mut length = 0.0;
+    for pair in coords.windows(2) {
+        let (a, b) = (quantize(&pair[0]), quantize(&pair[1]));
+        if a == b {
+            continue;
+        }
+        let key = (a.0, a.1, b.0, b.1);
+        let l = straight_distance(pair[0].lat(), pair[0].lng(), pair[1].lat(), pair[1].lng());
+        segments.insert(key);
+        segment_lengths.push((key, l));
+        length += l;
+    }
+
+    Candidate {
+        index,
+        score,
+        segments,
+        length,
+        segment_lengths,
+    }
+}
+
+// ratio of `candidate`'s length that is shared with `kept`
+fn overlap(candidate: &Candidate, kept: &Candidate) -> f64 {
+    if candidate.length <= 0.0 {
+        return 0.0;
+    }
+    let shared: f64 = candidate
+        .segment_lengths
+        .iter()
+        .filter(|(key, _)| kept.segments.contains(key))
+        .map(|(_, l)| l)
+        .sum();
+    shared / candidate.length
+}
+
+// geometry_overlap returns the ratio of the length of `a` that is shared with `b`
+pub fn geometry_overlap<T: AlternativeRoute>(a: &T, b: &T, precision: u32) -> f64 {
+    let opts = AlternativesOptions {
+        precision,
+        ..Default::default()
+    };
+    overlap(&candidate(0, a, &opts), &candidate(1, b, &opts))
+}
+
+// rank_alternatives orders routes by score (lower is better), drops routes overlapping
+//  too much with a better one and truncates to altcount
+pub fn rank_alternatives<T: AlternativeRoute>(
+    routes: Vec<T>,
+    opts: &AlternativesOptions,
+) -> RankedAlternatives<T> {
+    let mut candidates: Vec<Candidate> = routes
+        .iter()
+        .enumerate()
+        .map(|(idx, r)| candidate(idx, r, opts))
+        .collect();
+    candidates.sort_by(|a, b| {
+        a.score
+            .partial_cmp(&b.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let mut kept: Vec<Candidate> = vec![];
+    let mut dropped = vec![];
+    for c in candidates {
+        let duplicate = kept
+            .iter()
+            .map(|k| (k.index, overlap(&c, k)))
+            .find(|(_, o)| *o > opts.max_overlap);
+        if let Some((kept_index, overlap)) = duplicate {
+            dropped.push(DroppedAlternative {
+                index: c.index,
+                score: c.score,
+                reason: DropReason::Duplicate {
+                    kept_index,
+                    overlap,
+                },
+            });
+            continue;
+        }
+        if kept.len() >= opts.altcount {
+            dropped.push(DroppedAlternative {
+                index: c.index,
+                score: c.score,
+                reason: DropReason::Truncated,
+            });
+            continue;
+        }
+        kept.push(c);
+    }
+
+    let kept_indices: Vec<usize> = kept.iter().map(|c| c.index).collect();
+    let mut slots: Vec<Option<T>> = routes.into_iter().map(Some).collect();
+    let routes = kept_indices
+        .iter()
+        .map(|idx| slots[*idx].take().unwrap())
+        .collect();
+
+    RankedAlternatives {
+        routes,
+        kept_indices,
+        dropped,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::def::Route;
+    use crate::toll::TollCost;
+
+    struct TestRoute {
+        duration: f64,
+        geometry: String,
+    }
+
+    impl AlternativeRoute for TestRoute {
+        fn duration(&self) -> f64 {
+            self.duration
+        }
+        fn distance(&self) -> f64 {
+            0.0
+        }
+        fn geometry(&self) -> Option<&str> {
+            Some(self.geometry.as_str())
+        }
+        fn has_toll(&self) -> bool {
+            false
+        }
+    }
+
+    fn route(duration: f64, coords: &[(f64, f64)]) -> TestRoute {
+        let coords: Vec<Coord> = coords
+            .iter()
+            .map(|(lat, lng)| Coord::new(*lat, *lng))
+            .collect();
+        TestRoute {
+            duration,
+            geometry: polyline::encode(&coords, polyline::PRECISION_POLYLINE6),
+        }
+    }
+
+    #[test]
+    fn test_rank_alternatives() {
+        let routes = vec![
+            route(120.0, &[(1.0, 103.0), (1.0, 103.01), (1.0, 103.02)]),
+            route(100.0, &[(1.0, 103.0), (1.01, 103.01), (1.0, 103.02)]),
+            // same as the first one except the last few meters
+            route(118.0, &[(1.0, 103.0), (1.0, 103.01), (1.0, 103.0201)]),
+            route(200.0, &[(1.0, 103.0), (0.99, 103.01), (1.0, 103.02)]),
+        ];
+        let opts = AlternativesOptions {
+            altcount: 2,
+            max_overlap: 0.4,
+            ..Default::default()
+        };
+        let r = rank_alternatives(routes, &opts);
+        assert_eq!(r.kept_indices, vec![1, 2]);
+        assert_eq!(r.routes[0].duration, 100.0);
+        assert_eq!(r.dropped.len(), 2);
+        assert_eq!(r.dropped[0].index, 0);
+        match &r.dropped[0].reason {
+            DropReason::Duplicate {
+                kept_index,
+                overlap,
+            } => {
+                assert_eq!(*kept_index, 2);
+                assert!(*overlap > 0.4);
+            }
+            _ => panic!("unexpected drop reason"),
+        }
+        assert_eq!(r.dropped[1].index, 3);
+        assert_eq!(r.dropped[1].reason, DropReason::Truncated);
+    }
+
+    #[test]
+    fn test_toll_penalty() {
+        let a = Coord::new(1.0, 103.0);
+        let (b, c) = (Coord::new(1.1, 103.0), Coord::new(1.0, 103.15));
+        let mut tolled = Route::straight_line_fallback(&a, &b, 10.0, 6);
+        tolled.toll_cost = Some(TollCost {
+            amount: 2.5,
+            currency: "SGD".to_string(),
+            crossings: 1,
+        });
+        assert!(tolled.has_toll());
+        let free = Route::straight_line_fallback(&a, &c, 10.0, 6);
+        assert!(!free.has_toll());
+
+        let opts = AlternativesOptions {
+            toll_penalty: 1000.0,
+            ..Default::default()
+        };
+        let r = rank_alternatives(vec![tolled.clone(), free.clone()], &opts);
+        assert_eq!(r.kept_indices, vec![1, 0]);
+        let r = rank_alternatives(vec![tolled, free], &Default::default());
+        assert_eq!(r.kept_indices, vec![0, 1]);
+    }
+}
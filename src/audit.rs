@@ -0,0 +1,376 @@
+// audit trail of road restriction (RRT) updates. every update path builds an AuditEvent from
+//  its input and outcome and hands it to the configured sinks, so that closures, speeds and
+//  dimensions changes can be traced back to the key (and the cid of its token) which made
+//  them. api keys are masked in events, the inputs are recorded without them
+use crate::def::{
+    UpdateRRTDimensionInput, UpdateRRTFixedSpeedInput, UpdateRRTSegmentInput, UpdateRRTSimpleInput,
+    UpdateRRTSimpleOutput,
+};
+use crate::mdm_io::ObjectStore;
+use crate::privacy::mask_key;
+use crate::{instance_id, Result};
+use chrono::{DateTime, Utc};
+use jwks_client::jwt::Jwt;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AuditActor {
+    // masked api key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+}
+
+impl AuditActor {
+    pub fn new(key: Option<&str>) -> AuditActor {
+        AuditActor {
+            key: key.map(mask_key),
+            ..Default::default()
+        }
+    }
+
+    // with_claims takes the cid and sub claims of the verified token of the request
+    pub fn with_claims(mut self, jwt: &Jwt) -> AuditActor {
+        self.cid = jwt.payload().get_str("cid").map(|v| v.to_string());
+        self.sub = jwt.payload().sub().map(|v| v.to_string());
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RrtUpdate {
+    Simple {
+        from_way_id: u64,
+        via_node_id: u64,
+        to_way_id: u64,
+        status: i32,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        opening_hours: Option<String>,
+    },
+    Segment {
+        segment: String,
+        status: i32,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        opening_hours: Option<String>,
+    },
+    Dimension {
+        way_id: u64,
+        segment: String,
+        height: f64,
+        length: f64,
+        weight: f64,
+        width: f64,
+        is_remove: bool,
+    },
+    FixedSpeed {
+        way_id: u64,
+        segment: String,
+        speed: i32,
+        from_ts: u64,
+        to_ts: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        opening_hours: Option<String>,
+    },
+}
+
+impl RrtUpdate {
+    // affected_ways are the ids of the ways the update applies to, segments given by
+    //  coordinates only have none
+    pub fn affected_ways(&self) -> Vec<u64> {
+        match self {
+            RrtUpdate::Simple {
+                from_way_id,
+                to_way_id,
+                ..
+            } => {
+                if from_way_id == to_way_id {
+                    vec![*from_way_id]
+                } else {
+                    vec![*from_way_id, *to_way_id]
+                }
+            }
+            RrtUpdate::Segment { .. } => vec![],
+            RrtUpdate::Dimension { way_id, .. } => vec![*way_id],
+            RrtUpdate::FixedSpeed { way_id, .. } => vec![*way_id],
+        }
+    }
+}
+
+impl From<&UpdateRRTSimpleInput> for RrtUpdate {
+    fn from(input: &UpdateRRTSimpleInput) -> Self {
+        RrtUpdate::Simple {
+            from_way_id: input.from_way_id,
+            via_node_id: input.via_node_id,
+            to_way_id: input.to_way_id,
+            status: input.status,
+            opening_hours: input.opening_hours.clone(),
+        }
+    }
+}
+
+impl From<&UpdateRRTSegmentInput> for RrtUpdate {
+    fn from(input: &UpdateRRTSegmentInput) -> Self {
+        RrtUpdate::Segment {
+            segment: input.segment.clone(),
+            status: input.status,
+            opening_hours: input.opening_hours.clone(),
+        }
+    }
+}
+
+impl From<&UpdateRRTDimensionInput> for RrtUpdate {
+    fn from(input: &UpdateRRTDimensionInput) -> Self {
+        RrtUpdate::Dimension {
+            way_id: input.way_id,
+            segment: input.segment.clone(),
+            height: input.height,
+            length: input.length,
+            weight: input.weight,
+            width: input.width,
+            is_remove: input.is_remove,
+        }
+    }
+}
+
+impl From<&UpdateRRTFixedSpeedInput> for RrtUpdate {
+    fn from(input: &UpdateRRTFixedSpeedInput) -> Self {
+        RrtUpdate::FixedSpeed {
+            way_id: input.way_id,
+            segment: input.segment.clone(),
+            speed: input.speed,
+            from_ts: input.from_ts,
+            to_ts: input.to_ts,
+            opening_hours: input.opening_hours.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AuditEvent {
+    pub actor: AuditActor,
+    pub update: RrtUpdate,
+    // unix timestamp in milliseconds
+    pub timestamp: u64,
+    pub affected_ways: Vec<u64>,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl AuditEvent {
+    pub fn new(actor: AuditActor, update: RrtUpdate, output: &UpdateRRTSimpleOutput) -> AuditEvent {
+        AuditEvent {
+            actor,
+            affected_ways: update.affected_ways(),
+            update,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as u64,
+            success: output.success,
+            error: output.error_msg.clone(),
+        }
+    }
+}
+
+pub type AuditFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+pub trait AuditSink: Sync {
+    fn record<'a>(&'a self, event: &'a AuditEvent) -> AuditFuture<'a, ()>;
+}
+
+// LogSink writes events as json lines to the log
+#[derive(Clone, Debug, Default)]
+pub struct LogSink;
+
+impl AuditSink for LogSink {
+    fn record<'a>(&'a self, event: &'a AuditEvent) -> AuditFuture<'a, ()> {
+        Box::pin(async move {
+            info!("rrt audit: {}", serde_json::to_string(event)?);
+            Ok(())
+        })
+    }
+}
+
+static EVENT_SEQ: AtomicU64 = AtomicU64::new(0);
+
+// ObjectStoreSink appends events to a store, e.g. gcs, as one object per event at
+//  `<prefix>/<yyyy-mm-dd>/<timestamp>-<instance>-<seq>.json` since objects can not be appended
+//  to. the instance keeps the replicas sharing the prefix from overwriting each other's events
+pub struct ObjectStoreSink<S: ObjectStore> {
+    pub store: S,
+    pub prefix: String,
+    pub instance: String,
+}
+
+impl<S: ObjectStore> ObjectStoreSink<S> {
+    pub fn new(store: S, prefix: &str) -> ObjectStoreSink<S> {
+        ObjectStoreSink {
+            store,
+            prefix: prefix.trim_end_matches('/').to_string(),
+            instance: instance_id().to_string(),
+        }
+    }
+
+    // with_instance replaces the generated instance id, e.g. by a stable replica name
+    pub fn with_instance(mut self, instance: &str) -> ObjectStoreSink<S> {
+        self.instance = instance.to_string();
+        self
+    }
+
+    pub fn event_path(&self, event: &AuditEvent) -> String {
+        format!(
+            "{}/{}/{}-{}-{}.json",
+            self.prefix,
+            DateTime::<Utc>::from(UNIX_EPOCH + Duration::from_millis(event.timestamp))
+                .format("%Y-%m-%d"),
+            event.timestamp,
+            self.instance,
+            EVENT_SEQ.fetch_add(1, Ordering::SeqCst)
+        )
+    }
+}
+
+impl<S: ObjectStore> AuditSink for ObjectStoreSink<S> {
+    fn record<'a>(&'a self, event: &'a AuditEvent) -> AuditFuture<'a, ()> {
+        Box::pin(async move {
+            let path = self.event_path(event);
+            self.store.put(&path, serde_json::to_vec(event)?).await
+        })
+    }
+}
+
+// HttpSink posts events as json to an audit service
+pub struct HttpSink {
+    client: reqwest::Client,
+    url: String,
+    timeout: Duration,
+}
+
+impl HttpSink {
+    pub fn new(url: &str, timeout: Duration) -> HttpSink {
+        HttpSink {
+            client: reqwest::Client::new(),
+            url: url.to_string(),
+            timeout,
+        }
+    }
+}
+
+impl AuditSink for HttpSink {
+    fn record<'a>(&'a self, event: &'a AuditEvent) -> AuditFuture<'a, ()> {
+        Box::pin(async move {
+            let response = self
+                .client
+                .post(&self.url)
+                .timeout(self.timeout)
+                .header("Content-Type", "application/json")
+                .body(serde_json::to_vec(event)?)
+                .send()
+                .await?;
+            if !response.status().is_success() {
+                bail!("audit service responded {}", response.status());
+            }
+            Ok(())
+        })
+    }
+}
+
+// record hands event to every sink. failures are logged and do not fail the update, which has
+//  already been applied
+pub async fn record(sinks: &[&dyn AuditSink], event: &AuditEvent) {
+    for sink in sinks.iter() {
+        if let Err(e) = sink.record(event).await {
+            warn!("failed to record rrt audit event: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mdm_io::MemoryStore;
+    use serde_json::json;
+
+    fn output(error: Option<&str>) -> UpdateRRTSimpleOutput {
+        UpdateRRTSimpleOutput {
+            success: error.is_none(),
+            error_msg: error.map(|v| v.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_audit_event() {
+        let input: UpdateRRTSimpleInput = serde_json::from_value(json!({
+            "from_way_id": 1,
+            "from_way_nodes": "10,11",
+            "via_node_id": 11,
+            "via_node": "1.3,103.8",
+            "to_way_id": 2,
+            "to_way_nodes": "11,12",
+            "status": 1,
+            "opening_hours": null,
+            "key": "abcdef123456",
+        }))
+        .unwrap();
+        let actor = AuditActor::new(input.key.as_deref());
+        let event = AuditEvent::new(actor, (&input).into(), &output(None));
+        assert_eq!(event.affected_ways, vec![1, 2]);
+        assert!(event.success);
+
+        let v = serde_json::to_value(&event).unwrap();
+        assert_eq!(v["update"]["type"], "simple");
+        assert!(!v.to_string().contains("abcdef123456"));
+        assert_eq!(v["actor"]["key"], mask_key("abcdef123456"));
+
+        let input: UpdateRRTSegmentInput = serde_json::from_value(json!({
+            "segment": "1.3,103.8;1.31,103.81",
+            "status": 0,
+            "opening_hours": null,
+            "key": null,
+        }))
+        .unwrap();
+        let event = AuditEvent::new(
+            AuditActor::new(None),
+            (&input).into(),
+            &output(Some("no such segment")),
+        );
+        assert!(event.affected_ways.is_empty());
+        assert_eq!(event.error.as_deref(), Some("no such segment"));
+    }
+
+    #[actix_rt::test]
+    async fn test_object_store_sink() {
+        let sink = ObjectStoreSink::new(MemoryStore::default(), "gs://audit/rrt/");
+        assert_eq!(sink.instance, instance_id());
+        let sink = sink.with_instance("nbroutes-0");
+        let update = RrtUpdate::Dimension {
+            way_id: 7,
+            segment: "1.3,103.8;1.31,103.81".to_string(),
+            height: 4.5,
+            length: 0.0,
+            weight: 0.0,
+            width: 0.0,
+            is_remove: false,
+        };
+        let mut event = AuditEvent::new(AuditActor::new(Some("key")), update, &output(None));
+        event.timestamp = 1600000000000;
+        let seq = EVENT_SEQ.load(Ordering::SeqCst);
+        record(&[&LogSink, &sink], &event).await;
+        let path = format!(
+            "gs://audit/rrt/2020-09-13/1600000000000-nbroutes-0-{}.json",
+            seq
+        );
+        let stored: AuditEvent =
+            serde_json::from_slice(&sink.store.get(&path).await.unwrap()).unwrap();
+        assert_eq!(stored, event);
+    }
+}
@@ -0,0 +1,167 @@
+// compatibility of `avoid` values with each engine and mode, since datasets do not all carry
+//  the classes needed to avoid a feature. the matrix is configured per engine, with the modes
+//  of the engine falling back to the "" entry. engines without an entry accept any value
+use crate::def::Engine;
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+// values of avoid that mean nothing to avoid
+const AVOID_NONE: &[&str] = &["", "none"];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnsupportedAvoid {
+    pub engine: String,
+    pub mode: String,
+    pub avoid: String,
+}
+
+impl fmt::Display for UnsupportedAvoid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "avoid={} is not supported for mode {} with {}",
+            self.avoid, self.mode, self.engine
+        )
+    }
+}
+
+impl Error for UnsupportedAvoid {}
+
+pub fn engine_name(engine: &Engine) -> &'static str {
+    match engine {
+        Engine::OSRM => "osrm",
+        Engine::Valhalla => "valhalla",
+    }
+}
+
+// split_avoids splits the avoid of a request, dropping the values that mean nothing to avoid
+pub fn split_avoids(avoid: &str) -> Vec<&str> {
+    avoid
+        .split('|')
+        .map(|v| v.trim())
+        .filter(|v| !AVOID_NONE.contains(v))
+        .collect()
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AvoidCompatibility {
+    // engine => mode => supported avoid values
+    #[serde(flatten)]
+    pub engines: HashMap<String, HashMap<String, Vec<String>>>,
+}
+
+impl AvoidCompatibility {
+    pub fn from_yaml(content: &str) -> Result<AvoidCompatibility> {
+        Ok(serde_yaml::from_str(content)?)
+    }
+
+    // with_supported sets the supported values of a mode, "" for all modes of the engine
+    pub fn with_supported(mut self, engine: &Engine, mode: &str, avoids: &[&str]) -> Self {
+        self.engines
+            .entry(engine_name(engine).to_string())
+            .or_default()
+            .insert(
+                mode.to_string(),
+                avoids.iter().map(|v| v.to_string()).collect(),
+            );
+        self
+    }
+
+    // supported returns the supported values, None when any value is accepted
+    pub fn supported(&self, engine: &Engine, mode: &str) -> Option<&Vec<String>> {
+        let modes = self.engines.get(engine_name(engine))?;
+        modes.get(mode).or_else(|| modes.get(""))
+    }
+
+    pub fn is_supported(&self, engine: &Engine, mode: &str, avoid: &str) -> bool {
+        self.supported(engine, mode)
+            .map(|v| v.iter().any(|s| s == avoid))
+            .unwrap_or(true)
+    }
+
+    // check_avoids fails on the first value that is not supported
+    pub fn check_avoids(
+        &self,
+        engine: &Engine,
+        mode: &str,
+        avoids: &[&str],
+    ) -> std::result::Result<(), UnsupportedAvoid> {
+        match avoids
+            .iter()
+            .find(|a| !AVOID_NONE.contains(a) && !self.is_supported(engine, mode, a))
+        {
+            Some(a) => Err(UnsupportedAvoid {
+                engine: engine_name(engine).to_string(),
+                mode: mode.to_string(),
+                avoid: a.to_string(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    // retain_supported drops the values that are not supported, and returns the kept values
+    //  with a warning for each dropped one, to be added to the warning of the response
+    pub fn retain_supported<'a>(
+        &self,
+        engine: &Engine,
+        mode: &str,
+        avoids: &[&'a str],
+    ) -> (Vec<&'a str>, Vec<String>) {
+        let mut kept = vec![];
+        let mut warnings = vec![];
+        for a in avoids.iter().filter(|a| !AVOID_NONE.contains(a)) {
+            if let Err(e) = self.check_avoids(engine, mode, &[a]) {
+                warn!("dropping unsupported avoid value: {}", e);
+                warnings.push(format!("{}, ignored", e));
+                continue;
+            }
+            kept.push(*a);
+        }
+        (kept, warnings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_avoids() {
+        let matrix = AvoidCompatibility::from_yaml(
+            r#"
+osrm:
+  "": [toll, highway, ferry]
+  2w: [toll, highway]
+"#,
+        )
+        .unwrap();
+        assert!(matrix
+            .check_avoids(&Engine::OSRM, "4w", &["toll", "ferry"])
+            .is_ok());
+        assert_eq!(
+            matrix
+                .check_avoids(&Engine::OSRM, "2w", &["toll", "ferry"])
+                .err()
+                .unwrap()
+                .to_string(),
+            "avoid=ferry is not supported for mode 2w with osrm"
+        );
+        assert!(matrix.check_avoids(&Engine::OSRM, "2w", &["none"]).is_ok());
+        // engines without an entry accept any value
+        assert!(matrix
+            .check_avoids(&Engine::Valhalla, "2w", &["ferry", "uturn"])
+            .is_ok());
+
+        let matrix = matrix.with_supported(&Engine::Valhalla, "", &["toll"]);
+        let (kept, warnings) =
+            matrix.retain_supported(&Engine::Valhalla, "4w", &split_avoids("toll| ferry|none"));
+        assert_eq!(kept, vec!["toll"]);
+        assert_eq!(
+            warnings,
+            vec!["avoid=ferry is not supported for mode 4w with valhalla, ignored"]
+        );
+    }
+}
@@ -0,0 +1,263 @@
+// load balancing of engine calls over the replicas of an area. weighted round robin spreads
+//  calls in proportion to the weights (smooth, so a heavy replica is not picked in bursts),
+//  least outstanding picks the replica with the fewest calls in flight relative to its
+//  weight. with circuit breakers, replicas whose breaker rejects calls are skipped and the
+//  outcome reported with done feeds the breaker of the replica. a lease dropped without done
+//  is released as a failed call
+use crate::circuit_breaker::{CircuitBreakerError, CircuitBreakers, CircuitPermit};
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::sync::{Arc, Mutex};
+
+fn default_weight() -> u32 {
+    1
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Endpoint {
+    pub host: String,
+    // an endpoint with weight 0 is never picked
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EndpointSet {
+    pub name: String,
+    pub endpoints: Vec<Endpoint>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Strategy {
+    #[serde(rename = "weighted_round_robin")]
+    WeightedRoundRobin,
+    #[serde(rename = "least_outstanding")]
+    LeastOutstanding,
+}
+
+pub struct Balancer {
+    strategy: Strategy,
+    breakers: Option<Arc<CircuitBreakers>>,
+    // current weights of the smooth round robin by set name, then host
+    current: Mutex<HashMap<String, HashMap<String, i64>>>,
+    // calls in flight by host
+    outstanding: Mutex<HashMap<String, u64>>,
+}
+
+impl Balancer {
+    pub fn new(strategy: Strategy) -> Balancer {
+        Balancer {
+            strategy,
+            breakers: None,
+            current: Mutex::new(HashMap::new()),
+            outstanding: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // with_breakers skips the endpoints whose breaker rejects calls
+    pub fn with_breakers(mut self, breakers: Arc<CircuitBreakers>) -> Balancer {
+        self.breakers = Some(breakers);
+        self
+    }
+
+    // pick returns the lease of the endpoint of the set to call, released with done. fails with
+    //  an Open circuit breaker error for the set when no endpoint is available
+    pub fn pick(&self, set: &EndpointSet) -> Result<EndpointLease<'_>> {
+        let mut candidates: Vec<&Endpoint> =
+            set.endpoints.iter().filter(|e| e.weight > 0).collect();
+        while !candidates.is_empty() {
+            let idx = match self.strategy {
+                Strategy::WeightedRoundRobin => self.pick_round_robin(&set.name, &candidates),
+                Strategy::LeastOutstanding => self.pick_least_outstanding(&candidates),
+            };
+            let endpoint = candidates.remove(idx);
+            let permit = match self.breakers.as_ref() {
+                Some(breakers) => match breakers.get(&endpoint.host).acquire() {
+                    Ok(v) => Some(v),
+                    Err(_) => continue,
+                },
+                None => None,
+            };
+            *self
+                .outstanding
+                .lock()
+                .unwrap()
+                .entry(endpoint.host.clone())
+                .or_insert(0) += 1;
+            return Ok(EndpointLease {
+                balancer: self,
+                endpoint: endpoint.clone(),
+                permit,
+                released: false,
+            });
+        }
+        Err(Box::new(CircuitBreakerError::Open {
+            endpoint: set.name.clone(),
+        }))
+    }
+
+    fn release(&self, host: &str) {
+        if let Some(v) = self.outstanding.lock().unwrap().get_mut(host) {
+            *v = v.saturating_sub(1);
+        }
+    }
+
+    pub fn outstanding(&self, host: &str) -> u64 {
+        self.outstanding
+            .lock()
+            .unwrap()
+            .get(host)
+            .cloned()
+            .unwrap_or(0)
+    }
+
+    fn pick_round_robin(&self, set_name: &str, candidates: &[&Endpoint]) -> usize {
+        let mut current = self.current.lock().unwrap();
+        let weights = current.entry(set_name.to_string()).or_default();
+        let total: i64 = candidates.iter().map(|e| e.weight as i64).sum();
+        let mut best = 0;
+        let mut best_weight = i64::MIN;
+        for (idx, e) in candidates.iter().enumerate() {
+            let w = weights.entry(e.host.clone()).or_insert(0);
+            *w += e.weight as i64;
+            if *w > best_weight {
+                best = idx;
+                best_weight = *w;
+            }
+        }
+        if let Some(w) = weights.get_mut(&candidates[best].host) {
+            *w -= total;
+        }
+        best
+    }
+
+    fn pick_least_outstanding(&self, candidates: &[&Endpoint]) -> usize {
+        let outstanding = self.outstanding.lock().unwrap();
+        let mut best = 0;
+        let mut best_load = f64::MAX;
+        for (idx, e) in candidates.iter().enumerate() {
+            let load = outstanding.get(&e.host).cloned().unwrap_or(0) as f64 / e.weight as f64;
+            if load < best_load {
+                best = idx;
+                best_load = load;
+            }
+        }
+        best
+    }
+}
+
+// EndpointLease is an endpoint returned by pick, counted as outstanding until released
+pub struct EndpointLease<'a> {
+    balancer: &'a Balancer,
+    endpoint: Endpoint,
+    permit: Option<CircuitPermit>,
+    released: bool,
+}
+
+impl<'a> EndpointLease<'a> {
+    pub fn endpoint(&self) -> &Endpoint {
+        &self.endpoint
+    }
+
+    // done releases the endpoint and reports the outcome of the call
+    pub fn done(mut self, success: bool) {
+        self.released = true;
+        self.balancer.release(&self.endpoint.host);
+        if let Some(permit) = self.permit.take() {
+            permit.record(success);
+        }
+    }
+}
+
+impl<'a> Deref for EndpointLease<'a> {
+    type Target = Endpoint;
+
+    fn deref(&self) -> &Endpoint {
+        &self.endpoint
+    }
+}
+
+impl<'a> Drop for EndpointLease<'a> {
+    // the permit records a failure when dropped
+    fn drop(&mut self) {
+        if !self.released {
+            self.balancer.release(&self.endpoint.host);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit_breaker::{is_circuit_open, CircuitBreakerConfig};
+
+    fn set(weights: &[u32]) -> EndpointSet {
+        EndpointSet {
+            name: "singapore-4w".to_string(),
+            endpoints: weights
+                .iter()
+                .enumerate()
+                .map(|(idx, w)| Endpoint {
+                    host: format!("osrm-{}", idx),
+                    weight: *w,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_weighted_round_robin() {
+        let b = Balancer::new(Strategy::WeightedRoundRobin);
+        let s = set(&[2, 1, 0]);
+        let leases: Vec<EndpointLease> = (0..6).map(|_| b.pick(&s).unwrap()).collect();
+        let hosts: Vec<&str> = leases.iter().map(|l| l.host.as_str()).collect();
+        assert_eq!(
+            hosts,
+            vec!["osrm-0", "osrm-1", "osrm-0", "osrm-0", "osrm-1", "osrm-0"]
+        );
+        assert_eq!(b.outstanding("osrm-0"), 4);
+        drop(leases);
+        assert_eq!(b.outstanding("osrm-0"), 0);
+    }
+
+    #[test]
+    fn test_least_outstanding() {
+        let b = Balancer::new(Strategy::LeastOutstanding);
+        let s = set(&[1, 2]);
+        let first = b.pick(&s).unwrap();
+        assert_eq!(first.host, "osrm-0");
+        let second = b.pick(&s).unwrap();
+        assert_eq!(second.host, "osrm-1");
+        let third = b.pick(&s).unwrap();
+        assert_eq!(third.host, "osrm-1");
+        first.done(true);
+        assert_eq!(b.pick(&s).unwrap().host, "osrm-0");
+    }
+
+    #[test]
+    fn test_pick_with_breakers() {
+        let breakers = Arc::new(CircuitBreakers::new(CircuitBreakerConfig {
+            min_requests: 1,
+            ..Default::default()
+        }));
+        let b = Balancer::new(Strategy::WeightedRoundRobin).with_breakers(breakers);
+        let s = set(&[1, 1]);
+        b.pick(&s).unwrap().done(false);
+        for _ in 0..3 {
+            let lease = b.pick(&s).unwrap();
+            assert_eq!(lease.host, "osrm-1");
+            lease.done(true);
+        }
+        let err = b.pick(&set(&[1])).err().unwrap();
+        assert!(is_circuit_open(err.as_ref()));
+
+        // leases dropped without outcome are failures
+        for _ in 0..3 {
+            drop(b.pick(&s).unwrap());
+        }
+        assert_eq!(b.outstanding("osrm-1"), 0);
+        assert!(b.pick(&s).is_err());
+    }
+}
@@ -0,0 +1,133 @@
+// bearings of requests, `degree,range;degree,range;...` with one item per coordinate in the
+//  order of the request: origins then destinations for matrices, origin, waypoints then
+//  destination for directions. empty items leave a coordinate unconstrained
+use crate::util::split_positional_list;
+use crate::Result;
+
+pub const MAX_BEARING_DEGREE: u16 = 360;
+pub const MAX_BEARING_RANGE: u16 = 180;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bearing {
+    // clockwise from true north
+    pub degree: u16,
+    // allowed deviation on both sides of degree
+    pub range: u16,
+}
+
+impl Bearing {
+    pub fn parse(input: &str) -> Result<Bearing> {
+        let parts: Vec<&str> = input.split(',').map(|v| v.trim()).collect();
+        if parts.len() != 2 {
+            bail!("expected degree,range");
+        }
+        let degree: u16 = parts[0]
+            .parse()
+            .map_err(|_| format!("degree '{}' is not a number", parts[0]))?;
+        let range: u16 = parts[1]
+            .parse()
+            .map_err(|_| format!("range '{}' is not a number", parts[1]))?;
+        if degree > MAX_BEARING_DEGREE {
+            bail!("degree {} is above {}", degree, MAX_BEARING_DEGREE);
+        }
+        if range > MAX_BEARING_RANGE {
+            bail!("range {} is above {}", range, MAX_BEARING_RANGE);
+        }
+        Ok(Bearing { degree, range })
+    }
+}
+
+// parse_bearings parses the bearings of expected coordinates. errors give the 0-based position
+//  of the offending item
+pub fn parse_bearings(input: &str, expected: usize) -> Result<Vec<Option<Bearing>>> {
+    let items = split_positional_list(input, ';');
+    if items.len() > expected {
+        bail!(
+            "bearings count {} does not match coordinates count {}: bearing #{} ('{}') has no coordinate",
+            items.len(),
+            expected,
+            expected,
+            items[expected]
+        );
+    }
+    if items.len() < expected {
+        bail!(
+            "bearings count {} does not match coordinates count {}: bearing #{} is missing, use an empty item to skip a coordinate",
+            items.len(),
+            expected,
+            items.len()
+        );
+    }
+    items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            if item.is_empty() {
+                return Ok(None);
+            }
+            Bearing::parse(item)
+                .map(Some)
+                .map_err(|e| format!("bearing #{} ('{}') is invalid: {}", i, item, e).into())
+        })
+        .collect()
+}
+
+pub fn encode_bearings(bearings: &[Option<Bearing>]) -> String {
+    let items: Vec<String> = bearings
+        .iter()
+        .map(|b| match b {
+            Some(b) => format!("{},{}", b.degree, b.range),
+            None => "".to_string(),
+        })
+        .collect();
+    items.join(";")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bearings() {
+        let bearings = parse_bearings("0,20;;90, 45", 3).unwrap();
+        assert_eq!(
+            bearings,
+            vec![
+                Some(Bearing {
+                    degree: 0,
+                    range: 20
+                }),
+                None,
+                Some(Bearing {
+                    degree: 90,
+                    range: 45
+                }),
+            ]
+        );
+        assert_eq!(encode_bearings(&bearings), "0,20;;90,45");
+
+        let message = |input: &str, expected: usize| {
+            parse_bearings(input, expected).err().unwrap().to_string()
+        };
+        assert_eq!(
+            message("0,20;90,45", 1),
+            "bearings count 2 does not match coordinates count 1: bearing #1 ('90,45') has no coordinate"
+        );
+        assert_eq!(
+            message("0,20", 2),
+            "bearings count 1 does not match coordinates count 2: bearing #1 is missing, use an empty item to skip a coordinate"
+        );
+        assert_eq!(
+            message(";0,20,5", 2),
+            "bearing #1 ('0,20,5') is invalid: expected degree,range"
+        );
+        assert_eq!(
+            message("0,200;", 2),
+            "bearing #0 ('0,200') is invalid: range 200 is above 180"
+        );
+        assert_eq!(
+            message("north,20;", 2),
+            "bearing #0 ('north,20') is invalid: degree 'north' is not a number"
+        );
+    }
+}
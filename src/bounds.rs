@@ -0,0 +1,145 @@
+use crate::coord::Locatable;
+use crate::util::straight_distance;
+use geo::prelude::BoundingRect;
+use geo::Polygon;
+use std::borrow::Borrow;
+use std::collections::HashMap;
+
+// margin added to the radius to absorb the difference between the planar polygons and the
+//  haversine distance
+const RADIUS_MARGIN_RATIO: f64 = 0.01;
+const RADIUS_MARGIN_METERS: f64 = 10.0;
+
+// BoundingCircle is a circle containing all the polygons of an area, used to discard distant
+//  areas before running point-in-polygon
+#[derive(Clone, Debug, PartialEq)]
+pub struct BoundingCircle {
+    pub lat: f64,
+    pub lng: f64,
+    pub radius_meters: f64,
+}
+
+impl BoundingCircle {
+    // from_polygons centers the circle on the bounding rect of the polygons, returns None
+    //  when the polygons have no points
+    pub fn from_polygons<P: Borrow<Polygon<f64>>>(polygons: &[P]) -> Option<BoundingCircle> {
+        let mut rects = polygons.iter().filter_map(|p| p.borrow().bounding_rect());
+        let first = rects.next()?;
+        let (mut min, mut max) = (first.min(), first.max());
+        for r in rects {
+            min.x = min.x.min(r.min().x);
+            min.y = min.y.min(r.min().y);
+            max.x = max.x.max(r.max().x);
+            max.y = max.y.max(r.max().y);
+        }
+        let lat = (min.y + max.y) / 2.0;
+        let lng = (min.x + max.x) / 2.0;
+        // holes are inside the exterior, only its vertices can be the farthest points
+        let radius = polygons
+            .iter()
+            .flat_map(|p| p.borrow().exterior().0.iter())
+            .map(|c| straight_distance(lat, lng, c.y, c.x))
+            .fold(0.0_f64, f64::max);
+        Some(BoundingCircle {
+            lat,
+            lng,
+            radius_meters: radius * (1.0 + RADIUS_MARGIN_RATIO) + RADIUS_MARGIN_METERS,
+        })
+    }
+
+    // may_contain is false when the coordinate can not be in the polygons of the circle
+    pub fn may_contain<T: Locatable>(&self, c: &T) -> bool {
+        straight_distance(self.lat, self.lng, c.lat(), c.lng()) <= self.radius_meters
+    }
+}
+
+pub fn bounding_circles<P: Borrow<Polygon<f64>>>(
+    polygons: &HashMap<String, Vec<P>>,
+) -> HashMap<String, BoundingCircle> {
+    polygons
+        .iter()
+        .filter_map(|(name, ps)| BoundingCircle::from_polygons(ps).map(|c| (name.clone(), c)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coord::Coord;
+    use crate::prepared_polygon::prepare_polygons;
+    use crate::testkit::{random_coords, ring_polygon, square_polygon, AreaBuilder, Rng};
+    use crate::{find_area, find_area_with_bounds};
+
+    #[test]
+    fn test_bounding_circle() {
+        assert_eq!(BoundingCircle::from_polygons::<Polygon<f64>>(&[]), None);
+
+        let center = Coord::new(60.0, 10.0);
+        let polygons = vec![
+            ring_polygon(&center, 1.0, 0.5),
+            square_polygon(&Coord::new(62.0, 12.0), 0.2),
+        ];
+        let circle = BoundingCircle::from_polygons(&polygons).unwrap();
+        let polygons = prepare_polygons(polygons);
+        assert!((circle.lat - 60.6).abs() < 1e-9);
+        assert!((circle.lng - 10.6).abs() < 1e-9);
+
+        // never discards a coordinate inside the polygons
+        for c in random_coords(&Coord::new(60.6, 10.6), 2.0, 5000, 3) {
+            if c.is_in_polygons(&polygons) {
+                assert!(circle.may_contain(&c));
+            }
+        }
+        assert!(!circle.may_contain(&Coord::new(1.3, 103.8)));
+    }
+
+    #[test]
+    fn test_find_area_with_bounds() {
+        let mut polygons = HashMap::new();
+        let mut areas = vec![];
+        for i in 0..10 {
+            let name = format!("area-{}", i);
+            let center = Coord::new(-40.0 + i as f64 * 8.0, -100.0 + i as f64 * 20.0);
+            polygons.insert(name.clone(), prepare_polygons(vec![square_polygon(&center, 2.0)]));
+            areas.push(AreaBuilder::new(name.as_str(), "4w").build());
+        }
+        let bounds = bounding_circles(&polygons);
+        assert_eq!(bounds.len(), 10);
+
+        let mut rng = Rng::new(5);
+        for _ in 0..200 {
+            let area = rng.range_usize(0, 10);
+            let center = Coord::new(-40.0 + area as f64 * 8.0, -100.0 + area as f64 * 20.0);
+            let coords = random_coords(&center, 2.5, 3, rng.next_u64());
+            for tolerate_outlier in [false, true].iter() {
+                let expected = find_area(
+                    &None,
+                    &coords,
+                    &polygons,
+                    &areas,
+                    *tolerate_outlier,
+                    None,
+                    false,
+                );
+                let actual = find_area_with_bounds(
+                    &None,
+                    &coords,
+                    &polygons,
+                    Some(&bounds),
+                    &areas,
+                    *tolerate_outlier,
+                    None,
+                    false,
+                );
+                match (expected, actual) {
+                    (Ok(e), Ok(a)) => {
+                        assert_eq!(e.0.name, a.0.name);
+                        assert_eq!(e.2, a.2);
+                    }
+                    (Err(e), Err(a)) => assert_eq!(e.to_string(), a.to_string()),
+                    (e, a) => panic!("{:?} != {:?}", e.map(|v| v.2), a.map(|v| v.2)),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,221 @@
+use crate::def::{DirectionsOutput, Route, ValhallaDirectionsOutput, ValhallaRoute};
+use serde::Serialize;
+use std::io;
+
+// optional fields dropped to fit a budget, in the order they are dropped
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TrimStage {
+    DebugInfo,
+    Annotations,
+    GeometryFull,
+}
+
+pub const TRIM_STAGES: [TrimStage; 3] = [
+    TrimStage::DebugInfo,
+    TrimStage::Annotations,
+    TrimStage::GeometryFull,
+];
+
+impl TrimStage {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TrimStage::DebugInfo => "debug_info",
+            TrimStage::Annotations => "annotations",
+            TrimStage::GeometryFull => "geometry_full",
+        }
+    }
+}
+
+pub trait Trimmable {
+    // drops the fields of the stage, returns true if anything was dropped
+    fn trim(&mut self, stage: TrimStage) -> bool;
+}
+
+impl Trimmable for Route {
+    fn trim(&mut self, stage: TrimStage) -> bool {
+        match stage {
+            TrimStage::DebugInfo => {
+                self.raw_duration.take().is_some() | self.predicted_duration.take().is_some()
+            }
+            TrimStage::Annotations => {
+                let mut trimmed = false;
+                for leg in self.legs.iter_mut().flatten() {
+                    trimmed |= leg.annotation.take().is_some();
+                }
+                trimmed
+            }
+            TrimStage::GeometryFull => {
+                self.geometry_full.take().is_some() | self.distance_full.take().is_some()
+            }
+        }
+    }
+}
+
+impl Trimmable for ValhallaRoute {
+    fn trim(&mut self, stage: TrimStage) -> bool {
+        match stage {
+            TrimStage::DebugInfo => {
+                self.debug_info.take().is_some()
+                    | self.raw_duration.take().is_some()
+                    | self.predicted_duration.take().is_some()
+            }
+            TrimStage::Annotations => {
+                let mut trimmed = false;
+                for leg in self.legs.iter_mut().flatten() {
+                    trimmed |= leg.annotation.take().is_some();
+                }
+                trimmed
+            }
+            TrimStage::GeometryFull => {
+                self.geometry_full.take().is_some() | self.distance_full.take().is_some()
+            }
+        }
+    }
+}
+
+// counts written bytes without keeping them
+struct CountingWriter {
+    count: usize,
+}
+
+impl io::Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.count += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// estimate_size returns the size in bytes of the serialized json
+pub fn estimate_size<T: Serialize>(value: &T) -> usize {
+    let mut w = CountingWriter { count: 0 };
+    match serde_json::to_writer(&mut w, value) {
+        Ok(_) => w.count,
+        Err(e) => {
+            warn!("failed to estimate response size due to {:?}", e);
+            0
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ResponseBudget {
+    pub max_bytes: usize,
+}
+
+impl ResponseBudget {
+    pub fn new(max_bytes: usize) -> ResponseBudget {
+        ResponseBudget { max_bytes }
+    }
+
+    // fit_directions_output drops optional route fields until the output fits the budget,
+    //  returns the stages applied
+    pub fn fit_directions_output(&self, output: &mut DirectionsOutput) -> Vec<TrimStage> {
+        let stages = self.fit(output, |o, stage| trim_routes(&mut o.routes, stage));
+        record_warnings(&mut output.warning, &stages);
+        stages
+    }
+
+    pub fn fit_valhalla_directions_output(
+        &self,
+        output: &mut ValhallaDirectionsOutput,
+    ) -> Vec<TrimStage> {
+        let stages = self.fit(output, |o, stage| trim_routes(&mut o.routes, stage));
+        record_warnings(&mut output.warning, &stages);
+        stages
+    }
+
+    fn fit<T, F>(&self, output: &mut T, trim: F) -> Vec<TrimStage>
+    where
+        T: Serialize,
+        F: Fn(&mut T, TrimStage) -> bool,
+    {
+        let mut stages = vec![];
+        for stage in TRIM_STAGES.iter() {
+            if estimate_size(output) <= self.max_bytes {
+                break;
+            }
+            if trim(output, *stage) {
+                stages.push(*stage);
+            }
+        }
+        stages
+    }
+}
+
+fn trim_routes<T: Trimmable>(routes: &mut [T], stage: TrimStage) -> bool {
+    let mut trimmed = false;
+    for route in routes.iter_mut() {
+        trimmed |= route.trim(stage);
+    }
+    trimmed
+}
+
+fn record_warnings(warning: &mut Option<Vec<String>>, stages: &[TrimStage]) {
+    for stage in stages {
+        warning.get_or_insert_with(Vec::new).push(format!(
+            "{} removed to fit response size limit",
+            stage.name()
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn output() -> DirectionsOutput {
+        serde_json::from_value(json!({
+            "status": "Ok",
+            "routes": [{
+                "geometry": "_p~iF~ps|U_ulLnnqC_mqNvxq`@",
+                "geometry_full": "_p~iF~ps|U_ulLnnqC_mqNvxq`@_p~iF~ps|U_ulLnnqC_mqNvxq`@",
+                "distance": 100.0,
+                "duration": 10.0,
+                "raw_duration": 12.0,
+                "legs": [{
+                    "distance": {"value": 100},
+                    "duration": {"value": 10},
+                    "annotation": {
+                        "duration": [1.0, 2.0, 3.0, 4.0],
+                        "distance": [10.0, 20.0, 30.0, 40.0],
+                        "speed": [10.0, 10.0, 10.0, 10.0],
+                        "weight": [1.0, 2.0, 3.0, 4.0],
+                        "nodes": [1, 2, 3, 4, 5],
+                        "datasources": [0, 0, 0, 0],
+                        "metadata": null,
+                        "turn_penalty": [],
+                    },
+                }],
+            }],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_fit_directions_output() {
+        let mut o = output();
+        let size = estimate_size(&o);
+        assert_eq!(size, serde_json::to_vec(&o).unwrap().len());
+
+        let stages = ResponseBudget::new(size).fit_directions_output(&mut o);
+        assert!(stages.is_empty());
+        assert!(o.warning.is_none());
+
+        let stages = ResponseBudget::new(size - 10).fit_directions_output(&mut o);
+        assert_eq!(stages, vec![TrimStage::DebugInfo]);
+        assert!(o.routes[0].raw_duration.is_none());
+        assert!(o.routes[0].legs.as_ref().unwrap()[0].annotation.is_some());
+        assert_eq!(o.warning.as_ref().unwrap().len(), 1);
+
+        let mut o = output();
+        let stages = ResponseBudget::new(0).fit_directions_output(&mut o);
+        assert_eq!(stages.len(), 3);
+        assert!(o.routes[0].geometry_full.is_none());
+        assert!(o.routes[0].geometry.is_some());
+    }
+}
@@ -0,0 +1,531 @@
+// circuit breakers stop sending traffic to an engine endpoint that keeps failing. a closed
+//  breaker counts outcomes over a tumbling window and opens when the failure rate of the
+//  window reaches the threshold. an open breaker rejects calls until open_duration elapsed,
+//  then turns half-open and lets probe_budget calls through: the breaker closes once all of
+//  them succeeded and opens again on the first failure, or when a probe is still in flight
+//  after probe_timeout. acquired calls hold a CircuitPermit which counts as a failure when it
+//  is dropped without an outcome (cancelled future, lost hedge), so probes are never leaked
+use crate::statsd::{MetricType, RegisterMetricInput, TrackCountInput, TypedTrackInput};
+use crate::Result;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub const METRIC_CIRCUIT_BREAKER_TRANSITIONS: &str = "circuit_breaker_transitions";
+pub const METRIC_CIRCUIT_BREAKER_REJECTIONS: &str = "circuit_breaker_rejections";
+pub const LABELNAME_ENDPOINT: &str = "endpoint";
+pub const LABELNAME_STATE: &str = "state";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CircuitBreakerError {
+    // the breaker of `endpoint` is open or out of probes
+    Open { endpoint: String },
+}
+
+impl fmt::Display for CircuitBreakerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CircuitBreakerError::Open { endpoint } => {
+                write!(f, "circuit breaker open for {}", endpoint)
+            }
+        }
+    }
+}
+
+impl Error for CircuitBreakerError {}
+
+// is_circuit_open tells if an error returned by this crate is caused by an open breaker
+pub fn is_circuit_open(err: &(dyn Error + Send + Sync + 'static)) -> bool {
+    matches!(
+        err.downcast_ref::<CircuitBreakerError>(),
+        Some(CircuitBreakerError::Open { .. })
+    )
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+impl CircuitState {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CircuitState::Closed => "closed",
+            CircuitState::Open => "open",
+            CircuitState::HalfOpen => "half_open",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CircuitBreakerConfig {
+    pub window: Duration,
+    // the breaker never opens on windows with fewer calls
+    pub min_requests: u32,
+    // ratio of failed calls in [0, 1] opening the breaker
+    pub failure_rate: f64,
+    pub open_duration: Duration,
+    // calls let through while half-open
+    pub probe_budget: u32,
+    // the breaker opens again when a probe did not report its outcome within this delay
+    pub probe_timeout: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        CircuitBreakerConfig {
+            window: Duration::from_secs(10),
+            min_requests: 20,
+            failure_rate: 0.5,
+            open_duration: Duration::from_secs(30),
+            probe_budget: 3,
+            probe_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Inner {
+    state: CircuitState,
+    window_start: Instant,
+    successes: u32,
+    failures: u32,
+    opened_at: Instant,
+    // probes let through and probes succeeded while half-open
+    probes: u32,
+    probe_successes: u32,
+    // time the last probe was let through
+    probed_at: Instant,
+}
+
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    endpoint: String,
+    config: CircuitBreakerConfig,
+    inner: Mutex<Inner>,
+    sender: Option<SyncSender<TypedTrackInput>>,
+}
+
+impl CircuitBreaker {
+    pub fn new(endpoint: &str, config: CircuitBreakerConfig) -> CircuitBreaker {
+        let now = Instant::now();
+        CircuitBreaker {
+            endpoint: endpoint.to_string(),
+            config,
+            inner: Mutex::new(Inner {
+                state: CircuitState::Closed,
+                window_start: now,
+                successes: 0,
+                failures: 0,
+                opened_at: now,
+                probes: 0,
+                probe_successes: 0,
+                probed_at: now,
+            }),
+            sender: None,
+        }
+    }
+
+    // with_sender tracks state transitions and rejections with the statsd collector
+    pub fn with_sender(mut self, sender: SyncSender<TypedTrackInput>) -> CircuitBreaker {
+        self.sender = Some(sender);
+        self
+    }
+
+    pub fn endpoint(&self) -> &str {
+        self.endpoint.as_str()
+    }
+
+    pub fn state(&self) -> CircuitState {
+        self.state_at(Instant::now())
+    }
+
+    // acquire returns an Open error when the call must not be sent, else the permit of the
+    //  call reporting its outcome
+    pub fn acquire(self: &Arc<Self>) -> Result<CircuitPermit> {
+        self.acquire_at(Instant::now())?;
+        Ok(CircuitPermit {
+            breaker: self.clone(),
+            recorded: false,
+        })
+    }
+
+    // run acquires the breaker and records the outcome of `fut`, dropping the returned future
+    //  before it completes records a failure
+    pub async fn run<F, T>(self: &Arc<Self>, fut: F) -> Result<T>
+    where
+        F: Future<Output = Result<T>>,
+    {
+        let permit = self.acquire()?;
+        let r = fut.await;
+        permit.record(r.is_ok());
+        r
+    }
+
+    fn state_at(&self, now: Instant) -> CircuitState {
+        let mut inner = self.inner.lock().unwrap();
+        self.refresh(&mut inner, now);
+        inner.state
+    }
+
+    fn acquire_at(&self, now: Instant) -> Result<()> {
+        let mut inner = self.inner.lock().unwrap();
+        self.refresh(&mut inner, now);
+        let allowed = match inner.state {
+            CircuitState::Closed => true,
+            CircuitState::Open => false,
+            CircuitState::HalfOpen => {
+                if inner.probes < self.config.probe_budget {
+                    inner.probes += 1;
+                    inner.probed_at = now;
+                    true
+                } else {
+                    false
+                }
+            }
+        };
+        if allowed {
+            return Ok(());
+        }
+        self.track(METRIC_CIRCUIT_BREAKER_REJECTIONS, inner.state);
+        Err(Box::new(CircuitBreakerError::Open {
+            endpoint: self.endpoint.clone(),
+        }))
+    }
+
+    fn record_at(&self, now: Instant, success: bool) {
+        let mut inner = self.inner.lock().unwrap();
+        self.refresh(&mut inner, now);
+        match inner.state {
+            CircuitState::Closed => {
+                if success {
+                    inner.successes += 1;
+                } else {
+                    inner.failures += 1;
+                }
+                let total = inner.successes + inner.failures;
+                if total >= self.config.min_requests
+                    && inner.failures as f64 >= self.config.failure_rate * total as f64
+                {
+                    self.transition(&mut inner, CircuitState::Open, now);
+                }
+            }
+            CircuitState::HalfOpen => {
+                if !success {
+                    self.transition(&mut inner, CircuitState::Open, now);
+                    return;
+                }
+                inner.probe_successes += 1;
+                if inner.probe_successes >= self.config.probe_budget {
+                    self.transition(&mut inner, CircuitState::Closed, now);
+                }
+            }
+            // outcomes of calls acquired before the breaker opened
+            CircuitState::Open => {}
+        }
+    }
+
+    // refresh applies the transitions due to elapsed time
+    fn refresh(&self, inner: &mut Inner, now: Instant) {
+        match inner.state {
+            CircuitState::Closed => {
+                if now.saturating_duration_since(inner.window_start) >= self.config.window {
+                    inner.window_start = now;
+                    inner.successes = 0;
+                    inner.failures = 0;
+                }
+            }
+            CircuitState::Open => {
+                if now.saturating_duration_since(inner.opened_at) >= self.config.open_duration {
+                    self.transition(inner, CircuitState::HalfOpen, now);
+                }
+            }
+            CircuitState::HalfOpen => {
+                let in_flight = inner.probes > inner.probe_successes;
+                if in_flight
+                    && now.saturating_duration_since(inner.probed_at) >= self.config.probe_timeout
+                {
+                    self.transition(inner, CircuitState::Open, now);
+                }
+            }
+        }
+    }
+
+    fn transition(&self, inner: &mut Inner, state: CircuitState, now: Instant) {
+        info!(
+            "circuit breaker of {} goes from {} to {}",
+            self.endpoint,
+            inner.state.name(),
+            state.name()
+        );
+        inner.state = state;
+        inner.window_start = now;
+        inner.successes = 0;
+        inner.failures = 0;
+        inner.probes = 0;
+        inner.probe_successes = 0;
+        if state == CircuitState::Open {
+            inner.opened_at = now;
+        }
+        self.track(METRIC_CIRCUIT_BREAKER_TRANSITIONS, state);
+    }
+
+    fn track(&self, metric_name: &str, state: CircuitState) {
+        if let Some(sender) = self.sender.as_ref() {
+            let mut labels = HashMap::new();
+            labels.insert(LABELNAME_ENDPOINT.to_string(), self.endpoint.clone());
+            labels.insert(LABELNAME_STATE.to_string(), state.name().to_string());
+            let r = sender.try_send(TypedTrackInput::Counter(TrackCountInput {
+                metric_name: metric_name.to_string(),
+                count: 1.0,
+                labels,
+            }));
+            if let Err(e) = r {
+                warn!("failed to track {} due to {:?}", metric_name, e);
+            }
+        }
+    }
+}
+
+// CircuitPermit is a call let through by a breaker. the outcome is reported with success or
+//  failure, a permit dropped without outcome counts as a failure
+#[derive(Debug)]
+pub struct CircuitPermit {
+    breaker: Arc<CircuitBreaker>,
+    recorded: bool,
+}
+
+impl CircuitPermit {
+    pub fn endpoint(&self) -> &str {
+        self.breaker.endpoint()
+    }
+
+    pub fn success(self) {
+        self.record(true)
+    }
+
+    pub fn failure(self) {
+        self.record(false)
+    }
+
+    pub fn record(mut self, success: bool) {
+        self.recorded = true;
+        self.breaker.record_at(Instant::now(), success);
+    }
+}
+
+impl Drop for CircuitPermit {
+    fn drop(&mut self) {
+        if !self.recorded {
+            self.breaker.record_at(Instant::now(), false);
+        }
+    }
+}
+
+pub fn circuit_breaker_metrics() -> Vec<RegisterMetricInput> {
+    let labels = vec![LABELNAME_ENDPOINT.to_string(), LABELNAME_STATE.to_string()];
+    vec![
+        RegisterMetricInput {
+            metric_type: MetricType::Counter,
+            metric_name: METRIC_CIRCUIT_BREAKER_TRANSITIONS.to_string(),
+            metric_desc: "number of circuit breaker transitions by new state".to_string(),
+            labels: labels.clone(),
+        },
+        RegisterMetricInput {
+            metric_type: MetricType::Counter,
+            metric_name: METRIC_CIRCUIT_BREAKER_REJECTIONS.to_string(),
+            metric_desc: "number of calls rejected by circuit breakers".to_string(),
+            labels,
+        },
+    ]
+}
+
+// CircuitBreakers holds one breaker per endpoint, created on first use with the same config
+pub struct CircuitBreakers {
+    config: CircuitBreakerConfig,
+    breakers: Mutex<HashMap<String, Arc<CircuitBreaker>>>,
+    sender: Option<SyncSender<TypedTrackInput>>,
+}
+
+impl CircuitBreakers {
+    pub fn new(config: CircuitBreakerConfig) -> CircuitBreakers {
+        CircuitBreakers {
+            config,
+            breakers: Mutex::new(HashMap::new()),
+            sender: None,
+        }
+    }
+
+    pub fn with_sender(mut self, sender: SyncSender<TypedTrackInput>) -> CircuitBreakers {
+        self.sender = Some(sender);
+        self
+    }
+
+    pub fn get(&self, endpoint: &str) -> Arc<CircuitBreaker> {
+        let mut breakers = self.breakers.lock().unwrap();
+        breakers
+            .entry(endpoint.to_string())
+            .or_insert_with(|| {
+                let mut breaker = CircuitBreaker::new(endpoint, self.config.clone());
+                if let Some(sender) = self.sender.as_ref() {
+                    breaker = breaker.with_sender(sender.clone());
+                }
+                Arc::new(breaker)
+            })
+            .clone()
+    }
+
+    // states returns the state of every known endpoint
+    pub fn states(&self) -> HashMap<String, CircuitState> {
+        let breakers: Vec<Arc<CircuitBreaker>> =
+            self.breakers.lock().unwrap().values().cloned().collect();
+        breakers
+            .iter()
+            .map(|b| (b.endpoint.clone(), b.state()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::sync_channel;
+
+    fn config() -> CircuitBreakerConfig {
+        CircuitBreakerConfig {
+            window: Duration::from_secs(10),
+            min_requests: 4,
+            failure_rate: 0.5,
+            open_duration: Duration::from_secs(30),
+            probe_budget: 2,
+            probe_timeout: Duration::from_secs(5),
+        }
+    }
+
+    #[test]
+    fn test_circuit_breaker() {
+        let (tx, rx) = sync_channel(100);
+        let b = CircuitBreaker::new("http://osrm-0:5000", config()).with_sender(tx);
+        let t0 = Instant::now();
+
+        // failures of an elapsed window are forgotten
+        b.record_at(t0, false);
+        b.record_at(t0, false);
+        b.record_at(t0 + Duration::from_secs(11), true);
+        assert_eq!(
+            b.state_at(t0 + Duration::from_secs(11)),
+            CircuitState::Closed
+        );
+
+        let t1 = t0 + Duration::from_secs(12);
+        b.record_at(t1, true);
+        b.record_at(t1, false);
+        assert_eq!(b.state_at(t1), CircuitState::Closed);
+        b.record_at(t1, false);
+        assert_eq!(b.state_at(t1), CircuitState::Open);
+        let err = b.acquire_at(t1).err().unwrap();
+        assert!(is_circuit_open(err.as_ref()));
+        assert_eq!(
+            err.to_string(),
+            "circuit breaker open for http://osrm-0:5000"
+        );
+
+        // probe fails
+        let t2 = t1 + Duration::from_secs(30);
+        assert_eq!(b.state_at(t2), CircuitState::HalfOpen);
+        assert!(b.acquire_at(t2).is_ok());
+        b.record_at(t2, false);
+        assert_eq!(b.state_at(t2), CircuitState::Open);
+
+        // probes succeed, calls beyond the budget are rejected meanwhile
+        let t3 = t2 + Duration::from_secs(30);
+        assert!(b.acquire_at(t3).is_ok());
+        assert!(b.acquire_at(t3).is_ok());
+        assert!(b.acquire_at(t3).is_err());
+        b.record_at(t3, true);
+        assert_eq!(b.state_at(t3), CircuitState::HalfOpen);
+        b.record_at(t3, true);
+        assert_eq!(b.state_at(t3), CircuitState::Closed);
+
+        let tracked: Vec<TypedTrackInput> = rx.try_iter().collect();
+        // open, half_open, open, half_open, closed and 2 rejections
+        assert_eq!(tracked.len(), 7);
+    }
+
+    #[test]
+    fn test_probe_timeout() {
+        let b = CircuitBreaker::new(
+            "a",
+            CircuitBreakerConfig {
+                min_requests: 1,
+                ..config()
+            },
+        );
+        let t0 = Instant::now();
+        b.record_at(t0, false);
+        let t1 = t0 + Duration::from_secs(30);
+        assert!(b.acquire_at(t1).is_ok());
+        assert!(b.acquire_at(t1).is_ok());
+        b.record_at(t1, true);
+        // the second probe never reports
+        assert_eq!(
+            b.state_at(t1 + Duration::from_secs(4)),
+            CircuitState::HalfOpen
+        );
+        assert_eq!(b.state_at(t1 + Duration::from_secs(5)), CircuitState::Open);
+
+        // half-open without probes does not time out
+        let t2 = t1 + Duration::from_secs(60);
+        assert_eq!(b.state_at(t2), CircuitState::HalfOpen);
+    }
+
+    #[test]
+    fn test_dropped_permit() {
+        let new = || {
+            Arc::new(CircuitBreaker::new(
+                "a",
+                CircuitBreakerConfig {
+                    min_requests: 1,
+                    ..config()
+                },
+            ))
+        };
+        let b = new();
+        b.acquire().unwrap().success();
+        assert_eq!(b.state(), CircuitState::Closed);
+        drop(b.acquire().unwrap());
+        assert_eq!(b.state(), CircuitState::Open);
+
+        // the future of run is cancelled after acquiring
+        let b = new();
+        let mut fut = Box::pin(b.run(std::future::pending::<Result<()>>()));
+        let waker = futures::task::noop_waker();
+        let mut cx = std::task::Context::from_waker(&waker);
+        assert!(fut.as_mut().poll(&mut cx).is_pending());
+        drop(fut);
+        assert_eq!(b.state(), CircuitState::Open);
+    }
+
+    #[actix_rt::test]
+    async fn test_circuit_breakers_run() {
+        let breakers = CircuitBreakers::new(CircuitBreakerConfig {
+            min_requests: 1,
+            ..config()
+        });
+        let b = breakers.get("a");
+        assert_eq!(b.run(async { Ok(1) }).await.unwrap(), 1);
+        assert!(b
+            .run::<_, ()>(async { bail!("engine down") })
+            .await
+            .is_err());
+        assert!(breakers.get("a").acquire().is_err());
+        assert!(breakers.get("b").acquire().is_ok());
+        assert_eq!(breakers.states().get("a"), Some(&CircuitState::Open));
+    }
+}
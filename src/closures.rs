@@ -0,0 +1,435 @@
+// warnings for the closures a route goes through. the engines may ignore closures (when the
+//  route was computed without the RRT updates, or when no other road is left), so the route is
+//  checked against the closures of the RRT segment updates: a closure is on the route when the
+//  points of its segment are near the geometry in the order of the segment, which is the closed
+//  direction, and it is warned about when the route reaches it during its opening_hours, the
+//  time of arrival being interpolated from the duration of the route
+use crate::coord::{cumulative_distances, project_on_path, Coord};
+use crate::def::{RouteT, UpdateRRTSegmentInput};
+use crate::polyline;
+use crate::warnings::Warning;
+use crate::Result;
+use chrono::{DateTime, Datelike, FixedOffset, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fmt;
+
+// points of a closed segment farther than this from the geometry are not on the route
+const MAX_CLOSURE_DISTANCE_METERS: f64 = 15.0;
+// status of the RRT updates reopening a segment, other statuses close it
+pub const RRT_STATUS_OPEN: i32 = 0;
+
+const WEEKDAYS: [&str; 7] = ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"];
+const MINUTES_PER_DAY: u32 = 24 * 60;
+
+#[derive(Clone, Debug, PartialEq)]
+struct OpeningRule {
+    // indexed from monday
+    weekdays: [bool; 7],
+    // [start, end) minutes of the day, a range ending before it starts goes past midnight
+    ranges: Vec<(u32, u32)>,
+}
+
+impl OpeningRule {
+    fn covers(&self, weekday: usize, minute: u32) -> bool {
+        self.ranges.iter().any(|(start, end)| {
+            if start < end {
+                self.weekdays[weekday] && *start <= minute && minute < *end
+            } else {
+                (self.weekdays[weekday] && minute >= *start)
+                    || (self.weekdays[(weekday + 6) % 7] && minute < *end)
+            }
+        })
+    }
+}
+
+// OpeningHours are the opening_hours of the RRT updates, the times their restriction applies,
+//  in the subset of the OSM syntax made of `;` separated rules of weekdays and time ranges, e.g.
+//  `Mo-Fr 07:00-09:30,17:00-19:00; Sa 10:00-12:00`. rules without weekdays apply every day,
+//  rules without time ranges the whole day, and `24/7` always
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct OpeningHours {
+    raw: String,
+    rules: Vec<OpeningRule>,
+}
+
+fn parse_weekday(name: &str, input: &str) -> Result<usize> {
+    match WEEKDAYS.iter().position(|d| *d == name) {
+        Some(v) => Ok(v),
+        None => bail!("invalid weekday '{}' in opening_hours '{}'", name, input),
+    }
+}
+
+fn parse_minute(item: &str, input: &str) -> Result<u32> {
+    let minute = item.split_once(':').and_then(|(h, m)| {
+        let (h, m) = (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?);
+        Some(h * 60 + m).filter(|v| m < 60 && *v <= MINUTES_PER_DAY)
+    });
+    match minute {
+        Some(v) => Ok(v),
+        None => bail!("invalid time '{}' in opening_hours '{}'", item, input),
+    }
+}
+
+impl OpeningHours {
+    pub fn parse(input: &str) -> Result<OpeningHours> {
+        let mut rules = vec![];
+        for rule in input.split(';').map(|v| v.trim()).filter(|v| !v.is_empty()) {
+            if rule == "24/7" {
+                rules.push(OpeningRule {
+                    weekdays: [true; 7],
+                    ranges: vec![(0, MINUTES_PER_DAY)],
+                });
+                continue;
+            }
+            let (days, times) = match rule.find(|c: char| c.is_ascii_digit()) {
+                Some(idx) => (rule[..idx].trim(), rule[idx..].trim()),
+                None => (rule, ""),
+            };
+            let mut weekdays = [days.is_empty(); 7];
+            for item in days.split(',').map(|v| v.trim()).filter(|v| !v.is_empty()) {
+                let (from, to) = match item.split_once('-') {
+                    Some((from, to)) => (parse_weekday(from, input)?, parse_weekday(to, input)?),
+                    None => {
+                        let day = parse_weekday(item, input)?;
+                        (day, day)
+                    }
+                };
+                let mut day = from;
+                loop {
+                    weekdays[day] = true;
+                    if day == to {
+                        break;
+                    }
+                    day = (day + 1) % 7;
+                }
+            }
+            let mut ranges = vec![];
+            for item in times.split(',').map(|v| v.trim()).filter(|v| !v.is_empty()) {
+                match item.split_once('-') {
+                    Some((start, end)) => {
+                        ranges.push((parse_minute(start, input)?, parse_minute(end, input)?))
+                    }
+                    None => bail!("invalid time range '{}' in opening_hours '{}'", item, input),
+                }
+            }
+            if ranges.is_empty() {
+                ranges.push((0, MINUTES_PER_DAY));
+            }
+            rules.push(OpeningRule { weekdays, ranges });
+        }
+        if rules.is_empty() {
+            bail!("empty opening_hours");
+        }
+        Ok(OpeningHours {
+            raw: input.trim().to_string(),
+            rules,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+
+    // is_active tells if the hours contain the local time of ts in timezone, in hours from UTC
+    pub fn is_active(&self, ts: i64, timezone: f64) -> bool {
+        let offset = FixedOffset::east_opt((timezone * 3600.0) as i32)
+            .unwrap_or_else(|| FixedOffset::east_opt(0).unwrap());
+        let local = match DateTime::<Utc>::from_timestamp(ts, 0) {
+            Some(v) => v.with_timezone(&offset),
+            None => return false,
+        };
+        let weekday = local.weekday().num_days_from_monday() as usize;
+        let minute = local.hour() * 60 + local.minute();
+        self.rules.iter().any(|r| r.covers(weekday, minute))
+    }
+}
+
+impl TryFrom<String> for OpeningHours {
+    type Error = String;
+
+    fn try_from(v: String) -> std::result::Result<Self, Self::Error> {
+        OpeningHours::parse(&v).map_err(|e| e.to_string())
+    }
+}
+
+impl From<OpeningHours> for String {
+    fn from(v: OpeningHours) -> Self {
+        v.raw
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Closure {
+    // the segment of the RRT update, which identifies the closure
+    pub id: String,
+    // points of the closed segment, in the closed direction
+    pub segment: Vec<Coord>,
+    // None when the segment is closed at all times
+    pub opening_hours: Option<OpeningHours>,
+}
+
+impl Closure {
+    // from_update builds the closure of an RRT segment update, None when the update reopens the
+    //  segment. segment points are `lat,lng` separated by `|` or `;`
+    pub fn from_update(input: &UpdateRRTSegmentInput) -> Result<Option<Closure>> {
+        if input.status == RRT_STATUS_OPEN {
+            return Ok(None);
+        }
+        let segment = Coord::coords(&input.segment.replace(';', "|"))?;
+        if segment.len() < 2 {
+            bail!("closed segment '{}' has less than 2 points", input.segment);
+        }
+        let opening_hours = match input.opening_hours.as_deref().map(|v| v.trim()) {
+            Some(v) if !v.is_empty() => Some(OpeningHours::parse(v)?),
+            _ => None,
+        };
+        Ok(Some(Closure {
+            id: input.segment.clone(),
+            segment,
+            opening_hours,
+        }))
+    }
+
+    pub fn is_active(&self, ts: i64, timezone: f64) -> bool {
+        self.opening_hours
+            .as_ref()
+            .map(|v| v.is_active(ts, timezone))
+            .unwrap_or(true)
+    }
+}
+
+// ClosureSet holds the closures known from the RRT segment updates, built by the services
+//  keeping them
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ClosureSet {
+    pub closures: Vec<Closure>,
+}
+
+impl ClosureSet {
+    pub fn new() -> ClosureSet {
+        ClosureSet::default()
+    }
+
+    pub fn push(&mut self, closure: Closure) {
+        self.closures.push(closure);
+    }
+
+    // apply replaces the closure of the segment of input, or removes it when input reopens it
+    pub fn apply(&mut self, input: &UpdateRRTSegmentInput) -> Result<()> {
+        let closure = Closure::from_update(input)?;
+        self.closures.retain(|c| c.id != input.segment);
+        if let Some(v) = closure {
+            self.closures.push(v);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ClosureWarning {
+    pub closure_id: String,
+    // distances along the route where the closed segment starts and ends, in meters
+    pub start_offset: f64,
+    pub end_offset: f64,
+    // time the route reaches the closure
+    pub arrival_ts: i64,
+    // opening_hours of the closure, None when it is closed at all times
+    pub opening_hours: Option<String>,
+}
+
+impl fmt::Display for ClosureWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "route goes through closure {} from {:.0} to {:.0} meters",
+            self.closure_id, self.start_offset, self.end_offset
+        )?;
+        match self.opening_hours.as_ref() {
+            Some(v) => write!(f, ", closed during {}", v),
+            None => write!(f, ", closed at all times"),
+        }
+    }
+}
+
+impl From<ClosureWarning> for Warning {
+    fn from(v: ClosureWarning) -> Self {
+        Warning::Closure(v)
+    }
+}
+
+// closure_offsets returns the distances along geometry where segment starts and ends, None when
+//  the route does not go along segment in its direction
+fn closure_offsets(geometry: &[Coord], offsets: &[f64], segment: &[Coord]) -> Option<(f64, f64)> {
+    let mut from_segment = 0;
+    let mut r: Option<(f64, f64)> = None;
+    for c in segment {
+        let (i, ratio, distance) = project_on_path(geometry, c, from_segment)?;
+        if distance > MAX_CLOSURE_DISTANCE_METERS {
+            return None;
+        }
+        from_segment = i;
+        let offset = offsets[i] + ratio * (offsets[i + 1] - offsets[i]);
+        r = Some(match r {
+            Some((start, end)) if offset >= end => (start, offset),
+            Some(_) => return None,
+            None => (offset, offset),
+        });
+    }
+    r.filter(|(start, end)| end > start)
+}
+
+// annotate_closures returns the closures of closure_set the route goes through in their closed
+//  direction while they are active, for a departure at departure_ts, sorted by offset.
+//  opening_hours are in the local time of timezone, in hours from UTC. geometry is decoded with
+//  precision, routes without geometry have no warnings
+pub fn annotate_closures<L>(
+    route: &RouteT<L>,
+    closure_set: &ClosureSet,
+    departure_ts: i64,
+    timezone: f64,
+    precision: u32,
+) -> Result<Vec<ClosureWarning>> {
+    let geometry = match route.geometry.as_ref() {
+        Some(v) => polyline::decode(v, precision)?,
+        None => return Ok(vec![]),
+    };
+    if geometry.len() < 2 {
+        return Ok(vec![]);
+    }
+    let offsets = cumulative_distances(&geometry);
+    let total = offsets[offsets.len() - 1];
+    let mut r = vec![];
+    for closure in closure_set.closures.iter() {
+        let (start_offset, end_offset) =
+            match closure_offsets(&geometry, &offsets, &closure.segment) {
+                Some(v) => v,
+                None => continue,
+            };
+        let elapsed = if total > 0.0 {
+            route.duration * start_offset / total
+        } else {
+            0.0
+        };
+        let arrival_ts = departure_ts + elapsed.round() as i64;
+        if closure.is_active(arrival_ts, timezone) {
+            r.push(ClosureWarning {
+                closure_id: closure.id.clone(),
+                start_offset,
+                end_offset,
+                arrival_ts,
+                opening_hours: closure
+                    .opening_hours
+                    .as_ref()
+                    .map(|v| v.as_str().to_string()),
+            });
+        }
+    }
+    r.sort_by(|a, b| a.start_offset.partial_cmp(&b.start_offset).unwrap());
+    Ok(r)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::def::Route;
+    use crate::warnings::Warnings;
+
+    // 2020-09-14 00:00:00 UTC, a monday
+    const MONDAY: i64 = 1600041600;
+
+    fn update(segment: &str, status: i32, opening_hours: Option<&str>) -> UpdateRRTSegmentInput {
+        UpdateRRTSegmentInput {
+            segment: segment.to_string(),
+            status,
+            opening_hours: opening_hours.map(|v| v.to_string()),
+            key: None,
+        }
+    }
+
+    #[test]
+    fn test_opening_hours() {
+        let hours = OpeningHours::parse("Mo-Fr 07:00-09:30,17:00-19:00; Sa 22:00-02:00").unwrap();
+        let at = |day: i64, h: i64, m: i64| MONDAY + day * 86400 + h * 3600 + m * 60;
+        assert!(hours.is_active(at(0, 7, 0), 0.0));
+        assert!(!hours.is_active(at(0, 9, 30), 0.0));
+        assert!(hours.is_active(at(4, 18, 0), 0.0));
+        assert!(!hours.is_active(at(5, 18, 0), 0.0));
+        // past midnight of saturday
+        assert!(hours.is_active(at(6, 1, 0), 0.0));
+        assert!(!hours.is_active(at(0, 1, 0), 0.0));
+        // 23:00 UTC on sunday is 07:00 on monday in UTC+8
+        assert!(hours.is_active(at(6, 23, 0), 8.0));
+
+        let weekend = OpeningHours::parse("Sa,Su").unwrap();
+        assert!(weekend.is_active(at(6, 12, 0), 0.0));
+        assert!(!weekend.is_active(at(0, 12, 0), 0.0));
+        assert!(OpeningHours::parse("24/7").unwrap().is_active(MONDAY, 0.0));
+        assert!(OpeningHours::parse("Fr-Mo").unwrap().is_active(MONDAY, 0.0));
+        assert!(OpeningHours::parse("Xy 07:00-08:00").is_err());
+        assert!(OpeningHours::parse("Mo 25:00-26:00").is_err());
+        assert!(OpeningHours::parse(" ; ").is_err());
+    }
+
+    #[test]
+    fn test_closure_set() {
+        let mut closures = ClosureSet::new();
+        closures
+            .apply(&update("0.0,0.05|0.0,0.051", 1, Some("Mo 08:00-10:00")))
+            .unwrap();
+        closures
+            .apply(&update("0.0,0.02;0.0,0.03", 1, None))
+            .unwrap();
+        assert_eq!(closures.closures.len(), 2);
+        assert_eq!(closures.closures[1].segment.len(), 2);
+        closures
+            .apply(&update("0.0,0.05|0.0,0.051", 1, None))
+            .unwrap();
+        assert_eq!(closures.closures.len(), 2);
+        assert!(closures.closures[1].opening_hours.is_none());
+        closures
+            .apply(&update("0.0,0.05|0.0,0.051", RRT_STATUS_OPEN, None))
+            .unwrap();
+        assert_eq!(closures.closures.len(), 1);
+        assert!(closures.apply(&update("0.0,0.05", 1, None)).is_err());
+        assert!(closures
+            .apply(&update("0.0,0.05|0.0,0.06", 1, Some("soon")))
+            .is_err());
+    }
+
+    #[test]
+    fn test_annotate_closures() {
+        // ~11.1 km east along the equator in 1000 seconds
+        let route =
+            Route::straight_line_fallback(&Coord::new(0.0, 0.0), &Coord::new(0.0, 0.1), 11.12, 5);
+        let mut closures = ClosureSet::new();
+        let mut close = |segment: &str, opening_hours: Option<&str>| {
+            closures.apply(&update(segment, 1, opening_hours)).unwrap()
+        };
+        // reached ~500 seconds after departure, eastbound
+        close("0.0,0.05|0.0,0.051", Some("Mo 00:05-00:10"));
+        close("0.0,0.02|0.0,0.03", Some("Tu"));
+        close("0.0,0.0|0.0,0.001", None);
+        // westbound only
+        close("0.0,0.081|0.0,0.08", None);
+        // ends ~1.1 km north of the route
+        close("0.0,0.05|0.01,0.06", None);
+
+        let r = annotate_closures(&route, &closures, MONDAY, 0.0, 5).unwrap();
+        let ids: Vec<&str> = r.iter().map(|v| v.closure_id.as_str()).collect();
+        assert_eq!(ids, vec!["0.0,0.0|0.0,0.001", "0.0,0.05|0.0,0.051"]);
+        assert_eq!(r[1].opening_hours.as_deref(), Some("Mo 00:05-00:10"));
+        assert!((r[1].start_offset - 5560.0).abs() < 10.0);
+        assert!((r[1].end_offset - r[1].start_offset - 111.2).abs() < 1.0);
+        assert!((r[1].arrival_ts - MONDAY - 500).abs() <= 1);
+        // an hour later only the permanent closure is active
+        let r2 = annotate_closures(&route, &closures, MONDAY + 3600, 0.0, 5).unwrap();
+        assert_eq!(r2.len(), 1);
+
+        let mut warnings = Warnings::new();
+        warnings.extend(r);
+        assert!(warnings.to_vec()[1].starts_with("route goes through closure 0.0,0.05|0.0,0.051"));
+        assert!(warnings.to_vec()[1].ends_with("closed during Mo 00:05-00:10"));
+    }
+}
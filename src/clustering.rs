@@ -0,0 +1,403 @@
+// geometric clustering of coordinates for the clustering service: k-means with a seeded
+//  k-means++ initialization, so the same input and seed always give the same clusters, and
+//  DBSCAN for density based clusters. distances are haversine in meters, centroids are the
+//  mean of the coordinates which is fine at the scale of a city
+use crate::coord::Coord;
+use crate::def::{Cluster, ClusteringJob, ClusteringOutput, ClusteringPostInput, Location};
+use crate::util::straight_distance;
+use crate::Result;
+
+pub const DEFAULT_MAX_ITERATIONS: usize = 100;
+
+fn distance(a: &Coord, b: &Coord) -> f64 {
+    straight_distance(a.lat, a.lng, b.lat, b.lng)
+}
+
+fn mean(coords: &[&Coord]) -> Option<Coord> {
+    if coords.is_empty() {
+        return None;
+    }
+    let n = coords.len() as f64;
+    Some(Coord::new(
+        coords.iter().map(|c| c.lat).sum::<f64>() / n,
+        coords.iter().map(|c| c.lng).sum::<f64>() / n,
+    ))
+}
+
+// splitmix64, enough for seeding without pulling a random crate
+struct SeededRng(u64);
+
+impl SeededRng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // uniform in [0, 1)
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Clustering {
+    // cluster of each coordinate, None for the noise of DBSCAN
+    pub labels: Vec<Option<usize>>,
+    pub centroids: Vec<Coord>,
+}
+
+impl Clustering {
+    pub fn members(&self, cluster: usize) -> Vec<usize> {
+        self.labels
+            .iter()
+            .enumerate()
+            .filter(|(_, l)| **l == Some(cluster))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    // to_output converts the clusters of the coordinates of jobs, jobs[i] being at coords[i].
+    //  noise is left out of the output
+    pub fn to_output(&self, jobs: &[ClusteringJob]) -> ClusteringOutput {
+        let dimensions = jobs
+            .iter()
+            .filter_map(|j| j.demand.as_ref().map(|d| d.len()))
+            .max()
+            .unwrap_or(0);
+        let clusters = self
+            .centroids
+            .iter()
+            .enumerate()
+            .map(|(c, centroid)| {
+                let members = self.members(c);
+                let mut load = vec![0; dimensions];
+                for job in members.iter().filter_map(|i| jobs.get(*i)) {
+                    for (l, d) in load.iter_mut().zip(job.demand.iter().flatten()) {
+                        *l += d;
+                    }
+                }
+                Cluster {
+                    centroid: Location {
+                        latitude: centroid.lat,
+                        longitude: centroid.lng,
+                    },
+                    job_ids: members
+                        .iter()
+                        .filter_map(|i| jobs.get(*i).map(|j| j.id))
+                        .collect(),
+                    load,
+                }
+            })
+            .collect();
+        ClusteringOutput { clusters }
+    }
+}
+
+// job_coords returns the coordinate of each job of the input, in the order of jobs
+pub fn job_coords(input: &ClusteringPostInput) -> Result<Vec<Coord>> {
+    input
+        .jobs
+        .iter()
+        .map(|j| match input.locations.get(j.location_index) {
+            Some(l) => Ok(Coord::coord(l)?),
+            None => bail!(
+                "job {}: location_index {} is out of the {} locations",
+                j.id,
+                j.location_index,
+                input.locations.len()
+            ),
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug)]
+pub struct KMeansOptions {
+    pub k: usize,
+    pub max_iterations: usize,
+    pub seed: u64,
+}
+
+impl KMeansOptions {
+    pub fn new(k: usize) -> Self {
+        KMeansOptions {
+            k,
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+            seed: 0,
+        }
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+}
+
+fn nearest(coord: &Coord, centroids: &[Coord]) -> (usize, f64) {
+    centroids
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (i, distance(coord, c)))
+        .fold(
+            (0, f64::INFINITY),
+            |best, v| if v.1 < best.1 { v } else { best },
+        )
+}
+
+// k-means++: each next centroid is drawn with a probability proportional to the squared
+//  distance to the nearest centroid already drawn
+fn initial_centroids(coords: &[Coord], k: usize, rng: &mut SeededRng) -> Vec<Coord> {
+    let mut centroids = vec![coords[(rng.next_u64() % coords.len() as u64) as usize].clone()];
+    while centroids.len() < k {
+        let weights: Vec<f64> = coords
+            .iter()
+            .map(|c| nearest(c, &centroids).1.powi(2))
+            .collect();
+        let total: f64 = weights.iter().sum();
+        let mut target = rng.next_f64() * total;
+        let mut picked = coords.len() - 1;
+        for (i, w) in weights.iter().enumerate() {
+            if target < *w {
+                picked = i;
+                break;
+            }
+            target -= w;
+        }
+        centroids.push(coords[picked].clone());
+    }
+    centroids
+}
+
+pub fn kmeans(coords: &[Coord], options: &KMeansOptions) -> Result<Clustering> {
+    if options.k == 0 || options.k > coords.len() {
+        bail!(
+            "k must be between 1 and the {} coordinates, got {}",
+            coords.len(),
+            options.k
+        );
+    }
+    let mut rng = SeededRng(options.seed);
+    let mut centroids = initial_centroids(coords, options.k, &mut rng);
+    let mut labels: Vec<Option<usize>> = vec![None; coords.len()];
+    for _ in 0..options.max_iterations {
+        let assigned: Vec<Option<usize>> = coords
+            .iter()
+            .map(|c| Some(nearest(c, &centroids).0))
+            .collect();
+        if assigned == labels {
+            break;
+        }
+        labels = assigned;
+        // an empty cluster keeps its centroid
+        for (i, centroid) in centroids.iter_mut().enumerate() {
+            let members: Vec<&Coord> = coords
+                .iter()
+                .zip(labels.iter())
+                .filter(|(_, l)| **l == Some(i))
+                .map(|(c, _)| c)
+                .collect();
+            if let Some(m) = mean(&members) {
+                *centroid = m;
+            }
+        }
+    }
+    Ok(Clustering { labels, centroids })
+}
+
+// dbscan groups the coordinates having at least min_points coordinates, themselves included,
+//  within eps meters. coordinates reachable from no such group are noise
+pub fn dbscan(coords: &[Coord], eps: f64, min_points: usize) -> Clustering {
+    let neighbors = |i: usize| -> Vec<usize> {
+        (0..coords.len())
+            .filter(|j| distance(&coords[i], &coords[*j]) <= eps)
+            .collect()
+    };
+    let mut labels: Vec<Option<usize>> = vec![None; coords.len()];
+    let mut visited = vec![false; coords.len()];
+    let mut count = 0;
+    for i in 0..coords.len() {
+        if visited[i] {
+            continue;
+        }
+        visited[i] = true;
+        let mut queue = neighbors(i);
+        if queue.len() < min_points {
+            continue;
+        }
+        labels[i] = Some(count);
+        while let Some(j) = queue.pop() {
+            if labels[j].is_none() {
+                labels[j] = Some(count);
+            }
+            if visited[j] {
+                continue;
+            }
+            visited[j] = true;
+            let expanded = neighbors(j);
+            if expanded.len() >= min_points {
+                queue.extend(expanded);
+            }
+        }
+        count += 1;
+    }
+    let centroids = (0..count)
+        .filter_map(|c| {
+            let members: Vec<&Coord> = coords
+                .iter()
+                .zip(labels.iter())
+                .filter(|(_, l)| **l == Some(c))
+                .map(|(c, _)| c)
+                .collect();
+            mean(&members)
+        })
+        .collect();
+    Clustering { labels, centroids }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClusterQuality {
+    // sum of the squared distances to the centroid, in square meters
+    pub inertia: f64,
+    // mean and max distance to the centroid, in meters
+    pub mean_distance: f64,
+    pub max_distance: f64,
+    // mean silhouette in [-1, 1], None with less than 2 clusters
+    pub silhouette: Option<f64>,
+    pub noise: usize,
+}
+
+pub fn quality(coords: &[Coord], clustering: &Clustering) -> ClusterQuality {
+    let clustered: Vec<(usize, usize)> = clustering
+        .labels
+        .iter()
+        .enumerate()
+        .filter_map(|(i, l)| l.map(|l| (i, l)))
+        .collect();
+    let distances: Vec<f64> = clustered
+        .iter()
+        .map(|(i, l)| distance(&coords[*i], &clustering.centroids[*l]))
+        .collect();
+    let mean_distance = if distances.is_empty() {
+        0.0
+    } else {
+        distances.iter().sum::<f64>() / distances.len() as f64
+    };
+
+    let silhouette = if clustering.centroids.len() < 2 {
+        None
+    } else {
+        let scores: Vec<f64> = clustered
+            .iter()
+            .map(|(i, l)| {
+                // mean distance from i to the members of each cluster
+                let mut sums = vec![(0.0, 0usize); clustering.centroids.len()];
+                for (j, m) in clustered.iter().filter(|(j, _)| j != i) {
+                    sums[*m].0 += distance(&coords[*i], &coords[*j]);
+                    sums[*m].1 += 1;
+                }
+                if sums[*l].1 == 0 {
+                    return 0.0;
+                }
+                let a = sums[*l].0 / sums[*l].1 as f64;
+                let b = sums
+                    .iter()
+                    .enumerate()
+                    .filter(|(m, s)| m != l && s.1 > 0)
+                    .map(|(_, s)| s.0 / s.1 as f64)
+                    .fold(f64::INFINITY, f64::min);
+                if !b.is_finite() || a.max(b) == 0.0 {
+                    return 0.0;
+                }
+                (b - a) / a.max(b)
+            })
+            .collect();
+        Some(scores.iter().sum::<f64>() / scores.len().max(1) as f64)
+    };
+
+    ClusterQuality {
+        inertia: distances.iter().map(|d| d * d).sum(),
+        mean_distance,
+        max_distance: distances.iter().cloned().fold(0.0, f64::max),
+        silhouette,
+        noise: clustering.labels.len() - clustered.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // two groups of 3 about 10km apart, and one far away point
+    fn coords() -> Vec<Coord> {
+        vec![
+            Coord::new(1.300, 103.800),
+            Coord::new(1.301, 103.801),
+            Coord::new(1.302, 103.800),
+            Coord::new(1.390, 103.800),
+            Coord::new(1.391, 103.801),
+            Coord::new(1.392, 103.800),
+            Coord::new(1.800, 104.500),
+        ]
+    }
+
+    #[test]
+    fn test_kmeans() {
+        let coords = coords();
+        let options = KMeansOptions::new(3).with_seed(42);
+        let clustering = kmeans(&coords, &options).unwrap();
+        assert_eq!(clustering, kmeans(&coords, &options).unwrap());
+        let labels: Vec<usize> = clustering.labels.iter().map(|l| l.unwrap()).collect();
+        assert_eq!(labels[0], labels[1]);
+        assert_eq!(labels[1], labels[2]);
+        assert_eq!(labels[3], labels[4]);
+        assert_ne!(labels[0], labels[3]);
+        assert_ne!(labels[6], labels[0]);
+        assert_ne!(labels[6], labels[3]);
+
+        let q = quality(&coords, &clustering);
+        // the far away point is alone in its cluster and scores 0
+        assert!(q.silhouette.unwrap() > 0.8);
+        assert!(q.max_distance < 200.0);
+        assert_eq!(q.noise, 0);
+        assert!(kmeans(&coords, &KMeansOptions::new(8)).is_err());
+    }
+
+    #[test]
+    fn test_dbscan() {
+        let coords = coords();
+        let clustering = dbscan(&coords, 500.0, 3);
+        assert_eq!(
+            clustering.labels,
+            vec![Some(0), Some(0), Some(0), Some(1), Some(1), Some(1), None]
+        );
+        assert_eq!(clustering.centroids.len(), 2);
+        assert_eq!(quality(&coords, &clustering).noise, 1);
+    }
+
+    #[test]
+    fn test_to_output() {
+        let input: ClusteringPostInput = serde_json::from_value(json!({
+            "locations": ["1.300,103.800", "1.301,103.801", "1.390,103.800"],
+            "jobs": [
+                {"id": 10, "location_index": 0, "demand": [1, 2]},
+                {"id": 11, "location_index": 1, "demand": [3, 4]},
+                {"id": 12, "location_index": 2},
+            ],
+        }))
+        .unwrap();
+        let coords = job_coords(&input).unwrap();
+        let clustering = dbscan(&coords, 500.0, 2);
+        let output = clustering.to_output(&input.jobs);
+        assert_eq!(output.clusters.len(), 1);
+        assert_eq!(output.clusters[0].job_ids, vec![10, 11]);
+        assert_eq!(output.clusters[0].load, vec![4, 6]);
+    }
+}
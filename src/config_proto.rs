@@ -0,0 +1,465 @@
+// converters between the serde area configs and their protobuf messages, used to ship binary
+//  config snapshots between services
+use crate::intern::Name;
+use crate::protos::{
+    AreaFlexiblePB, AreaPB, BoolMapPB, BordersPB, DaysAheadDaySettingPB, DaysAheadSettingPB,
+    DaysAheadSlotSettingPB, ModeBoolMapPB, ModeSettingMapPB, RecurringDayDefinitionPB,
+    RecurringDaySettingPB, RecurringSettingPB, SettingMapPB, StringListMapPB, StringListPB,
+    TimeDependantSettingPB, UInt32ListPB,
+};
+use crate::util::{Area, AreaFlexible};
+use crate::{
+    Borders, DaysAheadDaySetting, DaysAheadSettting, DaysAheadSlotSetting, RecurringDayDefinition,
+    RecurringDaySetting, RecurringSetting, Result, SlotTime, TimeDependantSetting,
+};
+use protobuf::{Message, RepeatedField};
+use std::collections::{BTreeMap, HashMap};
+
+type ModeBoolMap = BTreeMap<String, BTreeMap<String, bool>>;
+type ModeSettingMap = BTreeMap<String, BTreeMap<String, TimeDependantSetting>>;
+
+fn string_list_to_pb(values: &[String]) -> StringListPB {
+    let mut r = StringListPB::new();
+    r.set_values(RepeatedField::from_vec(values.to_vec()));
+    r
+}
+
+fn string_list_map_to_pb(values: &BTreeMap<String, Vec<String>>) -> StringListMapPB {
+    let mut r = StringListMapPB::new();
+    for (k, v) in values {
+        r.mut_values().insert(k.clone(), string_list_to_pb(v));
+    }
+    r
+}
+
+fn string_list_map_from_pb(pb: &StringListMapPB) -> BTreeMap<String, Vec<String>> {
+    pb.get_values()
+        .iter()
+        .map(|(k, v)| (k.clone(), v.get_values().to_vec()))
+        .collect()
+}
+
+fn mode_bool_map_to_pb(values: &ModeBoolMap) -> ModeBoolMapPB {
+    let mut r = ModeBoolMapPB::new();
+    for (mode, contexts) in values {
+        let mut m = BoolMapPB::new();
+        m.set_values(contexts.clone().into_iter().collect());
+        r.mut_values().insert(mode.clone(), m);
+    }
+    r
+}
+
+fn mode_bool_map_from_pb(pb: &ModeBoolMapPB) -> ModeBoolMap {
+    pb.get_values()
+        .iter()
+        .map(|(mode, m)| (mode.clone(), m.get_values().clone().into_iter().collect()))
+        .collect()
+}
+
+fn mode_setting_map_to_pb(values: &ModeSettingMap) -> ModeSettingMapPB {
+    let mut r = ModeSettingMapPB::new();
+    for (mode, settings) in values {
+        let mut m = SettingMapPB::new();
+        for (ctx, setting) in settings {
+            m.mut_values().insert(ctx.clone(), setting.into());
+        }
+        r.mut_values().insert(mode.clone(), m);
+    }
+    r
+}
+
+fn mode_setting_map_from_pb(pb: &ModeSettingMapPB) -> ModeSettingMap {
+    pb.get_values()
+        .iter()
+        .map(|(mode, m)| {
+            let settings = m
+                .get_values()
+                .iter()
+                .map(|(ctx, setting)| (ctx.clone(), setting.into()))
+                .collect();
+            (mode.clone(), settings)
+        })
+        .collect()
+}
+
+fn slot_to_pb(slot: &DaysAheadSlotSetting) -> DaysAheadSlotSettingPB {
+    let mut r = DaysAheadSlotSettingPB::new();
+    r.set_id(slot.id.clone());
+    r.set_range(slot.range.iter().map(|t| t.minutes() / 60).collect());
+    r.set_range_times(slot.range.iter().map(|t| t.minutes()).collect());
+    if let Some(v) = slot.range_minutes.as_ref() {
+        r.set_range_minutes(v.clone());
+    }
+    r
+}
+
+// slot_from_pb reads range_times when set, the whole hours of range otherwise
+fn slot_from_pb(pb: &DaysAheadSlotSettingPB) -> DaysAheadSlotSetting {
+    let range = if pb.get_range_times().is_empty() {
+        pb.get_range()
+            .iter()
+            .map(|h| SlotTime::from_hour(*h))
+            .collect()
+    } else {
+        pb.get_range_times().iter().map(|m| SlotTime(*m)).collect()
+    };
+    DaysAheadSlotSetting {
+        id: pb.get_id().to_string(),
+        range,
+        range_minutes: Some(pb.get_range_minutes().to_vec()).filter(|v| !v.is_empty()),
+    }
+}
+
+fn days_ahead_to_pb(setting: &DaysAheadSettting) -> DaysAheadSettingPB {
+    let mut r = DaysAheadSettingPB::new();
+    r.set_timezone(setting.timezone);
+    for day in setting.days.iter() {
+        let mut d = DaysAheadDaySettingPB::new();
+        d.set_prefix(day.prefix.clone());
+        d.set_slots(day.slots.iter().map(slot_to_pb).collect());
+        r.mut_days().push(d);
+    }
+    r
+}
+
+fn days_ahead_from_pb(pb: &DaysAheadSettingPB) -> DaysAheadSettting {
+    DaysAheadSettting {
+        timezone: pb.get_timezone(),
+        days: pb
+            .get_days()
+            .iter()
+            .map(|d| DaysAheadDaySetting {
+                prefix: d.get_prefix().to_string(),
+                slots: d.get_slots().iter().map(slot_from_pb).collect(),
+            })
+            .collect(),
+    }
+}
+
+fn day_definition_to_pb(day: &RecurringDayDefinition) -> RecurringDayDefinitionPB {
+    let mut r = RecurringDayDefinitionPB::new();
+    r.set_day_type(day.day_type.clone());
+    if let Some(v) = day.date_value.as_ref() {
+        r.set_date_value(string_list_to_pb(v));
+    }
+    if let Some(v) = day.weekday_value.as_ref() {
+        let mut l = UInt32ListPB::new();
+        l.set_values(v.clone());
+        r.set_weekday_value(l);
+    }
+    r
+}
+
+fn day_definition_from_pb(pb: &RecurringDayDefinitionPB) -> RecurringDayDefinition {
+    RecurringDayDefinition {
+        day_type: pb.get_day_type().to_string(),
+        date_value: if pb.has_date_value() {
+            Some(pb.get_date_value().get_values().to_vec())
+        } else {
+            None
+        },
+        weekday_value: if pb.has_weekday_value() {
+            Some(pb.get_weekday_value().get_values().to_vec())
+        } else {
+            None
+        },
+    }
+}
+
+fn recurring_to_pb(setting: &RecurringSetting) -> RecurringSettingPB {
+    let mut r = RecurringSettingPB::new();
+    r.set_timezone(setting.timezone);
+    for day in setting.days.iter() {
+        let mut d = RecurringDaySettingPB::new();
+        d.set_name(day.name.clone());
+        d.set_prefix(day.prefix.clone());
+        d.set_days(day.days.iter().map(day_definition_to_pb).collect());
+        d.set_slots(day.slots.iter().map(slot_to_pb).collect());
+        r.mut_days().push(d);
+    }
+    r
+}
+
+fn recurring_from_pb(pb: &RecurringSettingPB) -> RecurringSetting {
+    RecurringSetting {
+        timezone: pb.get_timezone(),
+        days: pb
+            .get_days()
+            .iter()
+            .map(|d| RecurringDaySetting {
+                name: d.get_name().to_string(),
+                prefix: d.get_prefix().to_string(),
+                days: d.get_days().iter().map(day_definition_from_pb).collect(),
+                slots: d.get_slots().iter().map(slot_from_pb).collect(),
+            })
+            .collect(),
+    }
+}
+
+impl From<&TimeDependantSetting> for TimeDependantSettingPB {
+    fn from(setting: &TimeDependantSetting) -> Self {
+        let mut r = TimeDependantSettingPB::new();
+        r.set_setting_type(setting.setting_type.clone());
+        if let Some(v) = setting.days_ahead_setting.as_ref() {
+            r.set_days_ahead_setting(days_ahead_to_pb(v));
+        }
+        if let Some(v) = setting.recurring_setting.as_ref() {
+            r.set_recurring_setting(recurring_to_pb(v));
+        }
+        r
+    }
+}
+
+impl From<&TimeDependantSettingPB> for TimeDependantSetting {
+    fn from(pb: &TimeDependantSettingPB) -> Self {
+        TimeDependantSetting {
+            setting_type: pb.get_setting_type().to_string(),
+            days_ahead_setting: if pb.has_days_ahead_setting() {
+                Some(days_ahead_from_pb(pb.get_days_ahead_setting()))
+            } else {
+                None
+            },
+            recurring_setting: if pb.has_recurring_setting() {
+                Some(recurring_from_pb(pb.get_recurring_setting()))
+            } else {
+                None
+            },
+        }
+    }
+}
+
+fn mappings_to_pb(mappings: &BTreeMap<String, Name>) -> HashMap<String, String> {
+    mappings
+        .iter()
+        .map(|(k, v)| (k.clone(), v.to_string()))
+        .collect()
+}
+
+fn mappings_from_pb(mappings: &HashMap<String, String>) -> BTreeMap<String, Name> {
+    mappings
+        .iter()
+        .map(|(k, v)| (k.clone(), v.into()))
+        .collect()
+}
+
+impl From<&AreaFlexible> for AreaFlexiblePB {
+    fn from(flexible: &AreaFlexible) -> Self {
+        let mut r = AreaFlexiblePB::new();
+        r.set_default_service(flexible.default_service.to_string());
+        r.set_mappings(mappings_to_pb(&flexible.mappings));
+        if let Some(v) = flexible.allowed_context.as_ref() {
+            r.set_allowed_context(string_list_map_to_pb(v));
+        }
+        if let Some(v) = flexible.time_dependant.as_ref() {
+            r.set_time_dependant(mode_bool_map_to_pb(v));
+        }
+        if let Some(v) = flexible.time_dependant_settings.as_ref() {
+            r.set_time_dependant_settings(mode_setting_map_to_pb(v));
+        }
+        r
+    }
+}
+
+impl From<&AreaFlexiblePB> for AreaFlexible {
+    fn from(pb: &AreaFlexiblePB) -> Self {
+        AreaFlexible {
+            default_service: pb.get_default_service().into(),
+            mappings: mappings_from_pb(pb.get_mappings()),
+            allowed_context: if pb.has_allowed_context() {
+                Some(string_list_map_from_pb(pb.get_allowed_context()))
+            } else {
+                None
+            },
+            time_dependant: if pb.has_time_dependant() {
+                Some(mode_bool_map_from_pb(pb.get_time_dependant()))
+            } else {
+                None
+            },
+            time_dependant_settings: if pb.has_time_dependant_settings() {
+                Some(mode_setting_map_from_pb(pb.get_time_dependant_settings()))
+            } else {
+                None
+            },
+        }
+    }
+}
+
+impl From<&Area> for AreaPB {
+    fn from(area: &Area) -> Self {
+        let mut r = AreaPB::new();
+        r.set_name(area.name.to_string());
+        r.set_default_service(area.default_service.to_string());
+        r.set_mappings(mappings_to_pb(&area.mappings));
+        if let Some(v) = area.allowed_context.as_ref() {
+            r.set_allowed_context(string_list_map_to_pb(v));
+        }
+        if let Some(v) = area.time_dependant.as_ref() {
+            r.set_time_dependant(mode_bool_map_to_pb(v));
+        }
+        if let Some(v) = area.flexible.as_ref() {
+            r.set_flexible(mode_bool_map_to_pb(v));
+        }
+        if let Some(v) = area.time_dependant_settings.as_ref() {
+            r.set_time_dependant_settings(mode_setting_map_to_pb(v));
+        }
+        if let Some(v) = area.flexible_setting.as_ref() {
+            r.set_flexible_setting(v.into());
+        }
+        if let Some(v) = area.log_coord_precision {
+            r.set_log_coord_precision(v);
+        }
+        if let Some(v) = area.simplify_tolerance_meters {
+            r.set_simplify_tolerance_meters(v);
+        }
+        r
+    }
+}
+
+impl From<&AreaPB> for Area {
+    fn from(pb: &AreaPB) -> Self {
+        Area {
+            name: pb.get_name().into(),
+            default_service: pb.get_default_service().into(),
+            mappings: mappings_from_pb(pb.get_mappings()),
+            allowed_context: if pb.has_allowed_context() {
+                Some(string_list_map_from_pb(pb.get_allowed_context()))
+            } else {
+                None
+            },
+            time_dependant: if pb.has_time_dependant() {
+                Some(mode_bool_map_from_pb(pb.get_time_dependant()))
+            } else {
+                None
+            },
+            flexible: if pb.has_flexible() {
+                Some(mode_bool_map_from_pb(pb.get_flexible()))
+            } else {
+                None
+            },
+            time_dependant_settings: if pb.has_time_dependant_settings() {
+                Some(mode_setting_map_from_pb(pb.get_time_dependant_settings()))
+            } else {
+                None
+            },
+            flexible_setting: if pb.has_flexible_setting() {
+                Some(pb.get_flexible_setting().into())
+            } else {
+                None
+            },
+            log_coord_precision: if pb.has_log_coord_precision() {
+                Some(pb.get_log_coord_precision())
+            } else {
+                None
+            },
+            simplify_tolerance_meters: if pb.has_simplify_tolerance_meters() {
+                Some(pb.get_simplify_tolerance_meters())
+            } else {
+                None
+            },
+        }
+    }
+}
+
+impl From<&Borders> for BordersPB {
+    fn from(borders: &Borders) -> Self {
+        let mut r = BordersPB::new();
+        r.set_area_list(borders.area_list.iter().map(AreaPB::from).collect());
+        r
+    }
+}
+
+impl From<&BordersPB> for Borders {
+    fn from(pb: &BordersPB) -> Self {
+        Borders {
+            area_list: pb.get_area_list().iter().map(Area::from).collect(),
+        }
+    }
+}
+
+// encode_borders serializes borders, including the fetched time dependant settings
+pub fn encode_borders(borders: &Borders) -> Result<Vec<u8>> {
+    Ok(BordersPB::from(borders).write_to_bytes()?)
+}
+
+pub fn decode_borders(data: &[u8]) -> Result<Borders> {
+    let pb = BordersPB::parse_from_bytes(data)?;
+    Ok(Borders::from(&pb))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::AreaBuilder;
+
+    fn time_dependant_setting() -> TimeDependantSetting {
+        let slots = vec![
+            DaysAheadSlotSetting {
+                id: "morning".to_string(),
+                range: vec![6.into(), SlotTime::parse("10:30").unwrap()],
+                range_minutes: None,
+            },
+            DaysAheadSlotSetting {
+                id: "evening".to_string(),
+                range: vec![],
+                range_minutes: Some(vec![1050, 1200]),
+            },
+        ];
+        TimeDependantSetting {
+            setting_type: "recurring".to_string(),
+            days_ahead_setting: Some(DaysAheadSettting {
+                timezone: 8.0,
+                days: vec![DaysAheadDaySetting {
+                    prefix: "d0".to_string(),
+                    slots: slots.clone(),
+                }],
+            }),
+            recurring_setting: Some(RecurringSetting {
+                timezone: -5.5,
+                days: vec![RecurringDaySetting {
+                    name: "weekend".to_string(),
+                    prefix: "we".to_string(),
+                    days: vec![
+                        RecurringDayDefinition {
+                            day_type: "weekday".to_string(),
+                            date_value: None,
+                            weekday_value: Some(vec![5, 6]),
+                        },
+                        RecurringDayDefinition {
+                            day_type: "date".to_string(),
+                            date_value: Some(vec![]),
+                            weekday_value: None,
+                        },
+                    ],
+                    slots,
+                }],
+            }),
+        }
+    }
+
+    #[test]
+    fn test_borders_round_trip() {
+        let mut area = AreaBuilder::new("singapore", "4w")
+            .mapping("car", "4w")
+            .allowed_context("4w", "truck")
+            .time_dependant("4w", "")
+            .flexible("4w", &[("bike", "2w")])
+            .flexible_allowed_context("4w", "bus")
+            .log_coord_precision(3)
+            .simplify_tolerance_meters(25.0)
+            .build();
+        let mut settings = BTreeMap::new();
+        settings.insert("".to_string(), time_dependant_setting());
+        let mut mode_settings = BTreeMap::new();
+        mode_settings.insert("4w".to_string(), settings);
+        area.time_dependant_settings = Some(mode_settings);
+        let borders = Borders {
+            area_list: vec![area, AreaBuilder::new("jakarta", "2w").build()],
+        };
+
+        let decoded = decode_borders(&encode_borders(&borders).unwrap()).unwrap();
+        // none of the structs implement PartialEq, their debug output covers all fields
+        assert_eq!(format!("{:?}", decoded), format!("{:?}", borders));
+
+        assert!(decode_borders(&[0xff, 0xff]).is_err());
+    }
+}
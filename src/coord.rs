@@ -1,14 +1,19 @@
-use crate::util::Area;
+use crate::def::{ConfigCoord, Coordinate, Location};
+use crate::error::{NbroutesError, NbroutesResult};
+use crate::prepared_polygon::PreparedPolygon;
+use crate::util::{split_list, split_list_spans, straight_distance, Area, EARTH_RADIUS_METER};
 use crate::Result;
 use geo::algorithm::contains::Contains;
-use geo::prelude::BoundingRect;
 use geo::{Point, Polygon};
+use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
 use std::collections::HashMap;
+use std::ops::Range;
 
-#[derive(Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Coord {
-    lat: f64,
-    lng: f64,
+    pub lat: f64,
+    pub lng: f64,
 }
 
 impl Coord {
@@ -16,27 +21,33 @@ impl Coord {
         Self { lat, lng }
     }
 
-    pub fn coord(input: &str) -> Result<Coord> {
+    pub fn coord(input: &str) -> NbroutesResult<Coord> {
+        let invalid = |reason: &str| NbroutesError::InvalidCoordinate {
+            input: input.to_string(),
+            reason: reason.to_string(),
+        };
+        let parse = |v: &str| v.trim().parse::<f64>().map_err(|e| invalid(&e.to_string()));
         let items: Vec<&str> = input.split(",").collect();
         let point = match items.len() {
             2 => Coord {
-                lat: items[0].trim().parse::<f64>()?,
-                lng: items[1].trim().parse::<f64>()?,
+                lat: parse(items[0])?,
+                lng: parse(items[1])?,
             },
-            _ => bail!("need 2 float for coordinate"),
+            _ => return Err(invalid("need 2 float for coordinate")),
         };
         if point.lat.is_nan() || point.lng.is_nan() {
-            bail!("lat lng cannot be nan")
+            return Err(invalid("lat lng cannot be nan"));
+        }
+        if point.lat.is_infinite() || point.lng.is_infinite() {
+            return Err(invalid("lat lng cannot be infinite"));
         }
 
         Ok(point)
     }
 
     pub fn coords(input: &str) -> Result<Vec<Coord>> {
-        let input = input.trim().trim_matches('|').trim();
         let mut r: Vec<Coord> = Vec::new();
-        let items = input.split("|");
-        for item in items {
+        for item in split_list(input)? {
             r.push(Coord::coord(item)?);
         }
         Ok(r)
@@ -52,6 +63,61 @@ impl Coord {
     }
 }
 
+// ParsedCoords keeps the byte range of each coordinate in the raw input so that errors
+//  and warnings can point back to what the user sent
+#[derive(Debug, Clone)]
+pub struct ParsedCoords {
+    pub coords: Vec<Coord>,
+    pub raw_spans: Vec<Range<usize>>,
+}
+
+impl ParsedCoords {
+    // parse parses a `|` separated list of coordinates, name is used in error messages,
+    //  e.g. `destination #7 ('12.3,abc') is invalid`
+    pub fn parse(input: &str, name: &str) -> Result<ParsedCoords> {
+        let raw_spans = match split_list_spans(input) {
+            Ok(v) => v,
+            Err(e) => bail!("invalid {} list: {}", name, e),
+        };
+        let mut coords = Vec::with_capacity(raw_spans.len());
+        for (idx, span) in raw_spans.iter().enumerate() {
+            let raw = &input[span.clone()];
+            match Coord::coord(raw) {
+                Ok(c) => coords.push(c),
+                Err(_) => bail!("{} #{} ('{}') is invalid", name, idx, raw),
+            }
+        }
+        Ok(ParsedCoords { coords, raw_spans })
+    }
+
+    // raw returns the text of the coordinate at idx in input, which must be the parsed input
+    pub fn raw<'a>(&self, input: &'a str, idx: usize) -> Option<&'a str> {
+        self.raw_spans
+            .get(idx)
+            .and_then(|span| input.get(span.clone()))
+    }
+}
+
+// geo points are (x, y), i.e. (lng, lat)
+impl From<Point<f64>> for Coord {
+    fn from(p: Point<f64>) -> Self {
+        Coord::new(p.y(), p.x())
+    }
+}
+
+impl From<Coord> for Point<f64> {
+    fn from(c: Coord) -> Self {
+        Point::new(c.lng, c.lat)
+    }
+}
+
+// (lat, lng), same order as Coord::new
+impl From<(f64, f64)> for Coord {
+    fn from(v: (f64, f64)) -> Self {
+        Coord::new(v.0, v.1)
+    }
+}
+
 impl Locatable for Coord {
     fn lat(&self) -> f64 {
         self.lat
@@ -61,12 +127,48 @@ impl Locatable for Coord {
     }
 }
 
+impl Locatable for Location {
+    fn lat(&self) -> f64 {
+        self.latitude
+    }
+    fn lng(&self) -> f64 {
+        self.longitude
+    }
+}
+
+impl Locatable for Coordinate {
+    fn lat(&self) -> f64 {
+        self.latitude
+    }
+    fn lng(&self) -> f64 {
+        self.longitude
+    }
+}
+
+impl Locatable for ConfigCoord {
+    fn lat(&self) -> f64 {
+        self.lat
+    }
+    fn lng(&self) -> f64 {
+        self.lng
+    }
+}
+
+impl Locatable for Point<f64> {
+    fn lat(&self) -> f64 {
+        self.y()
+    }
+    fn lng(&self) -> f64 {
+        self.x()
+    }
+}
+
 pub trait Locatable {
     fn lat(&self) -> f64;
     fn lng(&self) -> f64;
     fn locate<'a>(
         &self,
-        area_polygons: &HashMap<String, Vec<Polygon<f64>>>,
+        area_polygons: &HashMap<String, Vec<PreparedPolygon>>,
         selected_areas: &'a Vec<Area>,
     ) -> Result<&'a Area> {
         let p = Point::<f64>::new(self.lng(), self.lat());
@@ -87,22 +189,370 @@ pub trait Locatable {
         bail!(format!("area not found for {},{}", self.lat(), self.lng()))
     }
 
-    fn is_in_polygons<'a>(&self, polygons: &Vec<Polygon<f64>>) -> bool {
+    fn is_in_polygons(&self, polygons: &[PreparedPolygon]) -> bool {
         let p = Point::<f64>::new(self.lng(), self.lat());
-        for v in polygons {
-            let brect = v.bounding_rect().unwrap();
-            if p.x() < brect.min().x
-                || p.x() > brect.max().x
-                || p.y() < brect.min().y
-                || p.y() > brect.max().y
-            {
-                continue;
+        polygons.iter().any(|v| v.contains(&p))
+    }
+}
+
+fn segment_length<T: Locatable>(a: &T, b: &T) -> f64 {
+    straight_distance(a.lat(), a.lng(), b.lat(), b.lng())
+}
+
+// path_length returns the length in meters of the path going through coords
+pub fn path_length<T: Locatable>(coords: &[T]) -> f64 {
+    coords
+        .windows(2)
+        .map(|w| segment_length(&w[0], &w[1]))
+        .sum()
+}
+
+// cumulative_distances returns the distance in meters from the first coordinate to each
+//  coordinate along the path, the first one being 0
+pub fn cumulative_distances<T: Locatable>(coords: &[T]) -> Vec<f64> {
+    let mut r = Vec::with_capacity(coords.len());
+    let mut total = 0.0;
+    for (idx, c) in coords.iter().enumerate() {
+        if idx > 0 {
+            total += segment_length(&coords[idx - 1], c);
+        }
+        r.push(total);
+    }
+    r
+}
+
+// point_along returns the point at meters from the start of the path, interpolated linearly
+//  within a segment. meters out of the path are clamped to its ends, None for an empty path
+pub fn point_along<T: Locatable>(coords: &[T], meters: f64) -> Option<Coord> {
+    let first = coords.first()?;
+    if meters <= 0.0 {
+        return Some(Coord::new(first.lat(), first.lng()));
+    }
+    let mut remaining = meters;
+    for w in coords.windows(2) {
+        let l = segment_length(&w[0], &w[1]);
+        if remaining <= l && l > 0.0 {
+            let ratio = remaining / l;
+            return Some(Coord::new(
+                w[0].lat() + (w[1].lat() - w[0].lat()) * ratio,
+                w[0].lng() + (w[1].lng() - w[0].lng()) * ratio,
+            ));
+        }
+        remaining -= l;
+    }
+    let last = coords.last()?;
+    Some(Coord::new(last.lat(), last.lng()))
+}
+
+// project_on_path returns the segment of the path nearest to c, the ratio along the segment of
+//  the projection of c and its distance to c in meters. segments before from_segment are
+//  skipped, so that the points of a trace are projected in order. None when there is no
+//  segment to project on
+pub fn project_on_path<T: Locatable, C: Locatable>(
+    coords: &[T],
+    c: &C,
+    from_segment: usize,
+) -> Option<(usize, f64, f64)> {
+    let scale = c.lat().to_radians().cos();
+    let (px, py) = (c.lng() * scale, c.lat());
+    let mut best: Option<(f64, usize, f64, f64, f64)> = None;
+    for (i, w) in coords.windows(2).enumerate().skip(from_segment) {
+        let (ax, ay) = (w[0].lng() * scale, w[0].lat());
+        let (bx, by) = (w[1].lng() * scale, w[1].lat());
+        let (dx, dy) = (bx - ax, by - ay);
+        let len2 = dx * dx + dy * dy;
+        let t = if len2 > 0.0 {
+            (((px - ax) * dx + (py - ay) * dy) / len2).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let (x, y) = (ax + t * dx, ay + t * dy);
+        let d2 = (x - px) * (x - px) + (y - py) * (y - py);
+        if best.map(|b| d2 < b.0).unwrap_or(true) {
+            best = Some((d2, i, t, x, y));
+        }
+    }
+    let (_, i, t, x, y) = best?;
+    let distance = segment_length(&Coord::new(c.lat(), c.lng()), &Coord::new(y, x / scale));
+    Some((i, t, distance))
+}
+
+// nearest_point_on_area returns the point of the polygons nearest to c and its distance in
+//  meters, c itself when it is inside. segments are projected on a local equirectangular
+//  plane, which is accurate enough for the short distances points are nudged by
+pub fn nearest_point_on_area<T: Locatable, P: Borrow<Polygon<f64>>>(
+    c: &T,
+    polygons: &[P],
+) -> Option<(Coord, f64)> {
+    if polygons.is_empty() {
+        return None;
+    }
+    if polygons
+        .iter()
+        .any(|p| p.borrow().contains(&Point::new(c.lng(), c.lat())))
+    {
+        return Some((Coord::new(c.lat(), c.lng()), 0.0));
+    }
+    let scale = c.lat().to_radians().cos();
+    let (px, py) = (c.lng() * scale, c.lat());
+    let mut best: Option<(f64, f64, f64)> = None;
+    for polygon in polygons.iter().map(|p| p.borrow()) {
+        let rings = std::iter::once(polygon.exterior()).chain(polygon.interiors().iter());
+        for ring in rings {
+            for w in ring.0.windows(2) {
+                let (ax, ay) = (w[0].x * scale, w[0].y);
+                let (bx, by) = (w[1].x * scale, w[1].y);
+                let (dx, dy) = (bx - ax, by - ay);
+                let len2 = dx * dx + dy * dy;
+                let t = if len2 > 0.0 {
+                    (((px - ax) * dx + (py - ay) * dy) / len2).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                let (x, y) = (ax + t * dx, ay + t * dy);
+                let d2 = (x - px) * (x - px) + (y - py) * (y - py);
+                if best.map(|b| d2 < b.0).unwrap_or(true) {
+                    best = Some((d2, x, y));
+                }
             }
-            if v.contains(&p) {
-                return true;
+        }
+    }
+    let (_, x, y) = best?;
+    let nearest = Coord::new(y, x / scale);
+    let distance = segment_length(&Coord::new(c.lat(), c.lng()), &nearest);
+    Some((nearest, distance))
+}
+
+// densify_great_circle returns points from `from` to `to` (both included) along the great
+//  circle, spaced by at most step_m meters
+pub fn densify_great_circle<T: Locatable>(from: &T, to: &T, step_m: f64) -> Vec<Coord> {
+    let start = Coord::new(from.lat(), from.lng());
+    let end = Coord::new(to.lat(), to.lng());
+    let distance = segment_length(from, to);
+    let angle = distance / EARTH_RADIUS_METER;
+    // antipodal points have no single great circle
+    if step_m <= 0.0 || distance <= step_m || angle.sin().abs() < 1e-12 {
+        return vec![start, end];
+    }
+    let (lat1, lng1) = (from.lat().to_radians(), from.lng().to_radians());
+    let (lat2, lng2) = (to.lat().to_radians(), to.lng().to_radians());
+    let n = (distance / step_m).ceil() as usize;
+    let mut r = Vec::with_capacity(n + 1);
+    r.push(start);
+    for i in 1..n {
+        let f = i as f64 / n as f64;
+        let a = ((1.0 - f) * angle).sin() / angle.sin();
+        let b = (f * angle).sin() / angle.sin();
+        let x = a * lat1.cos() * lng1.cos() + b * lat2.cos() * lng2.cos();
+        let y = a * lat1.cos() * lng1.sin() + b * lat2.cos() * lng2.sin();
+        let z = a * lat1.sin() + b * lat2.sin();
+        r.push(Coord::new(
+            z.atan2((x * x + y * y).sqrt()).to_degrees(),
+            y.atan2(x).to_degrees(),
+        ));
+    }
+    r.push(end);
+    r
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::{polygons_map, ring_polygon, square_polygon, AreaBuilder};
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_nearest_point_on_area() {
+        let center = Coord::new(1.3, 103.8);
+        let polygons = vec![ring_polygon(&center, 0.1, 0.05)];
+        assert_eq!(nearest_point_on_area::<_, Polygon<f64>>(&center, &[]), None);
+
+        let inside = Coord::new(1.3, 103.88);
+        assert_eq!(
+            nearest_point_on_area(&inside, &polygons),
+            Some((inside.clone(), 0.0))
+        );
+        // ~30m east of the area
+        let offshore = Coord::new(1.32, 103.90027);
+        let (nearest, distance) = nearest_point_on_area(&offshore, &polygons).unwrap();
+        assert!((nearest.lat - 1.32).abs() < 1e-9);
+        assert!((nearest.lng - 103.9).abs() < 1e-9);
+        assert!((distance - 30.0).abs() < 1.0);
+        // in the hole, the inner ring is the nearest
+        let (nearest, _) = nearest_point_on_area(&Coord::new(1.3, 103.84), &polygons).unwrap();
+        assert!((nearest.lng - 103.85).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_densify_great_circle() {
+        let from = Coord::new(1.3, 103.8);
+        let to = Coord::new(1.3, 103.801);
+        assert_eq!(
+            densify_great_circle(&from, &to, 1000.0),
+            vec![from.clone(), to]
+        );
+
+        // singapore to london
+        let to = Coord::new(51.5, -0.1);
+        let total = path_length(&[from.clone(), to.clone()]);
+        let points = densify_great_circle(&from, &to, 50_000.0);
+        assert_eq!(points.len(), (total / 50_000.0).ceil() as usize + 1);
+        assert_eq!(points.first(), Some(&from));
+        assert_eq!(points.last(), Some(&to));
+        // points on the great circle don't make the path longer
+        assert!((path_length(&points) - total).abs() < 1.0);
+        for w in points.windows(2) {
+            assert!(path_length(w) <= 50_000.0 + 1.0);
+        }
+    }
+
+    #[test]
+    fn test_parsed_coords() {
+        let input = " 1.3,103.8 |2.5, 104.1| 12.3,abc |";
+        let err = ParsedCoords::parse(input, "destination").unwrap_err();
+        assert_eq!(err.to_string(), "destination #2 ('12.3,abc') is invalid");
+        let err = ParsedCoords::parse("1,2||3,4", "waypoint").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid waypoint list: empty item at index 1"
+        );
+
+        let input = " 1.3,103.8 |2.5, 104.1|";
+        let parsed = ParsedCoords::parse(input, "origin").unwrap();
+        assert_eq!(
+            parsed.coords,
+            vec![Coord::new(1.3, 103.8), Coord::new(2.5, 104.1)]
+        );
+        assert_eq!(parsed.raw(input, 0), Some("1.3,103.8"));
+        assert_eq!(parsed.raw(input, 1), Some("2.5, 104.1"));
+        assert_eq!(parsed.raw(input, 2), None);
+        assert_eq!(parsed.coords, Coord::coords(input).unwrap());
+    }
+
+    #[test]
+    fn test_locatable_shapes() {
+        let center = Coord::new(1.3, 103.8);
+        let polygons = polygons_map(vec![("singapore", vec![square_polygon(&center, 0.5)])]);
+        let areas = vec![AreaBuilder::new("singapore", "4w").build()];
+        let location = Location {
+            latitude: 1.3,
+            longitude: 103.8,
+        };
+        let coordinate = Coordinate {
+            latitude: 1.3,
+            longitude: 103.8,
+            name: None,
+        };
+        let config_coord = ConfigCoord {
+            lat: 1.3,
+            lng: 103.8,
+        };
+        let point = Point::new(103.8, 1.3);
+        let shapes: Vec<&dyn Locatable> =
+            vec![&center, &location, &coordinate, &config_coord, &point];
+        for c in shapes {
+            assert_eq!((c.lat(), c.lng()), (1.3, 103.8));
+            assert_eq!(c.locate(&polygons, &areas).unwrap().name, "singapore");
+            assert!(c.is_in_polygons(&polygons["singapore"]));
+        }
+        assert!(Location {
+            latitude: 5.0,
+            longitude: 103.8
+        }
+        .locate(&polygons, &areas)
+        .is_err());
+    }
+
+    #[test]
+    fn test_path_length() {
+        let empty: Vec<Coord> = vec![];
+        assert_eq!(path_length(&empty), 0.0);
+        assert!(cumulative_distances(&empty).is_empty());
+        assert_eq!(point_along(&empty, 10.0), None);
+
+        let coords = vec![
+            Coord::new(0.0, 0.0),
+            Coord::new(0.0, 0.01),
+            Coord::new(0.0, 0.01),
+            Coord::new(0.01, 0.01),
+        ];
+        let length = path_length(&coords);
+        // ~1112m per 0.01 degree at the equator
+        assert!((length - 2.0 * 1112.0).abs() < 2.0);
+        let cumulative = cumulative_distances(&coords);
+        assert_eq!(cumulative.len(), 4);
+        assert_eq!(cumulative[0], 0.0);
+        assert_eq!(cumulative[1], cumulative[2]);
+        assert_eq!(cumulative[3], length);
+
+        assert_eq!(point_along(&coords, -1.0), Some(coords[0].clone()));
+        assert_eq!(point_along(&coords, length + 1.0), Some(coords[3].clone()));
+        let middle = point_along(&coords, cumulative[1] / 2.0).unwrap();
+        assert!((middle.lng - 0.005).abs() < 1e-9 && middle.lat == 0.0);
+        let p = point_along(&coords, cumulative[1] + 100.0).unwrap();
+        assert!((cumulative_distances(&[coords[1].clone(), p])[1] - 100.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_coord_conversions() {
+        let c = Coord::new(1.3, 103.8);
+        let p: Point<f64> = c.clone().into();
+        assert_eq!((p.x(), p.y()), (103.8, 1.3));
+        assert_eq!(Coord::from(p), c);
+        assert_eq!(Coord::from((1.3, 103.8)), c);
+
+        let json = serde_json::to_string(&c).unwrap();
+        assert_eq!(json, r#"{"lat":1.3,"lng":103.8}"#);
+        assert_eq!(serde_json::from_str::<Coord>(&json).unwrap(), c);
+    }
+
+    proptest! {
+        #[test]
+        fn test_coords_round_trip(
+            points in prop::collection::vec((-90.0f64..90.0, -180.0f64..180.0), 1..20),
+            spaced: bool,
+            trailing: bool,
+            padded: bool,
+        ) {
+            let coords: Vec<Coord> =
+                points.iter().map(|(lat, lng)| Coord::new(*lat, *lng)).collect();
+            let mut input = Coord::coords_to_str(coords.iter().collect());
+            // whitespace and separators variants should not change the result
+            if spaced {
+                input = input.replace("|", " |\t");
+            }
+            if trailing {
+                input.push('|');
+            }
+            if padded {
+                input = format!(" \n{}  ", input);
+            }
+            let parsed = Coord::coords(input.as_str()).unwrap();
+            prop_assert_eq!(parsed.len(), coords.len());
+            for (a, b) in parsed.iter().zip(coords.iter()) {
+                prop_assert_eq!(a.lat(), b.lat());
+                prop_assert_eq!(a.lng(), b.lng());
             }
         }
 
-        return false;
+        #[test]
+        fn test_coords_fuzz(input in "[019.,+| \teEnaif-]{0,30}") {
+            // must never panic, and parsed values are always finite
+            if let Ok(coords) = Coord::coords(input.as_str()) {
+                prop_assert!(!coords.is_empty());
+                prop_assert!(coords
+                    .iter()
+                    .all(|c| c.lat().is_finite() && c.lng().is_finite()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_coords_errors() {
+        assert!(Coord::coords("1,2||3,4").is_err());
+        assert!(Coord::coords("|").is_err());
+        assert!(Coord::coords("").is_err());
+        assert!(Coord::coords("1,inf").is_err());
+        assert!(Coord::coords("1,NaN").is_err());
     }
 }
@@ -0,0 +1,141 @@
+use crate::error::NbroutesError;
+use crate::Result;
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeadlineError {
+    // the request deadline was exceeded while running `operation`
+    TimedOut { operation: String },
+}
+
+impl fmt::Display for DeadlineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DeadlineError::TimedOut { operation } => {
+                write!(f, "request deadline exceeded during {}", operation)
+            }
+        }
+    }
+}
+
+impl Error for DeadlineError {}
+
+// is_timed_out tells if an error returned by this crate is caused by an exceeded deadline
+pub fn is_timed_out(err: &(dyn Error + Send + Sync + 'static)) -> bool {
+    matches!(
+        err.downcast_ref::<DeadlineError>(),
+        Some(DeadlineError::TimedOut { .. })
+    ) || matches!(
+        err.downcast_ref::<NbroutesError>(),
+        Some(NbroutesError::Deadline(DeadlineError::TimedOut { .. }))
+    )
+}
+
+// Deadline is the point in time a request has to be answered by
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Deadline {
+    at: Instant,
+}
+
+impl Deadline {
+    pub fn at(at: Instant) -> Deadline {
+        Deadline { at }
+    }
+
+    pub fn after(timeout: Duration) -> Deadline {
+        Deadline {
+            at: Instant::now() + timeout,
+        }
+    }
+
+    pub fn instant(&self) -> Instant {
+        self.at
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.at.saturating_duration_since(Instant::now())
+    }
+
+    pub fn is_expired(&self) -> bool {
+        Instant::now() >= self.at
+    }
+
+    // check returns a TimedOut error if the deadline is already exceeded
+    pub fn check(&self, operation: &str) -> Result<()> {
+        if self.is_expired() {
+            return Err(Box::new(DeadlineError::TimedOut {
+                operation: operation.to_string(),
+            }));
+        }
+        Ok(())
+    }
+
+    // run awaits `fut` until the deadline, returns a TimedOut error when exceeded
+    pub async fn run<F, T>(&self, operation: &str, fut: F) -> Result<T>
+    where
+        F: Future<Output = T>,
+    {
+        self.check(operation)?;
+        match tokio::time::timeout(self.remaining(), fut).await {
+            Ok(v) => Ok(v),
+            Err(_) => Err(Box::new(DeadlineError::TimedOut {
+                operation: operation.to_string(),
+            })),
+        }
+    }
+}
+
+// check_deadline is a no-op when no deadline is given
+pub fn check_deadline(deadline: Option<&Deadline>, operation: &str) -> Result<()> {
+    match deadline {
+        Some(d) => d.check(operation),
+        None => Ok(()),
+    }
+}
+
+// run_with_deadline awaits `fut` without limit when no deadline is given
+pub async fn run_with_deadline<F, T>(
+    deadline: Option<&Deadline>,
+    operation: &str,
+    fut: F,
+) -> Result<T>
+where
+    F: Future<Output = T>,
+{
+    match deadline {
+        Some(d) => d.run(operation, fut).await,
+        None => Ok(fut.await),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[actix_rt::test]
+    async fn test_deadline() {
+        let d = Deadline::after(Duration::from_millis(50));
+        assert!(!d.is_expired());
+        assert!(d.check("test").is_ok());
+        assert_eq!(d.run("fast", async { 1 }).await.unwrap(), 1);
+
+        let r = d
+            .run("slow", tokio::time::sleep(Duration::from_millis(500)))
+            .await;
+        let err = r.err().unwrap();
+        assert!(is_timed_out(err.as_ref()));
+        assert_eq!(
+            err.to_string(),
+            "request deadline exceeded during slow".to_string()
+        );
+        assert!(d.is_expired());
+        assert!(check_deadline(Some(&d), "test").is_err());
+        assert!(check_deadline(None, "test").is_ok());
+
+        let err: Box<dyn Error + Send + Sync> = "other".into();
+        assert!(!is_timed_out(err.as_ref()));
+    }
+}
@@ -1,15 +1,83 @@
 #![allow(non_snake_case)]
+use crate::coord::{densify_great_circle, path_length, Coord};
+use crate::elevation::ElevationProfile;
+use crate::geofence::Crossing;
+use crate::matrix_binary::{to_binary_value, BinaryMatrixHeader, BinaryMatrixVersion};
+use crate::polygon_check::{normalize_polygons, LoadReport, NormalizeOptions};
+use crate::polyline;
+use crate::privacy::MaskedKey;
+use crate::speed_compliance::SpeedViolation;
+use crate::toll::TollCost;
+use crate::travel_mode::TravelMode;
 use crate::util::straight_distance;
+use crate::Result;
 use byteorder::{ByteOrder, LittleEndian};
 use geo::{LineString, Polygon};
 use paperclip::actix::Apiv2Schema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+// named_schemas declares a struct shared by the types of several engines, whose schemas keep
+//  the names they had as separate structs: the derive would name them after the generic type,
+//  e.g. `RouteT<LegT<Annotation>>`. the schema is derived on a private twin of the struct and
+//  registered under the given name for each type argument
+macro_rules! named_schemas {
+    (
+        $(#[$attr:meta])*
+        pub struct $name:ident<$param:ident> as $twin:ident { $($fields:tt)* }
+        $($arg:ty => $schema:literal),* $(,)?
+    ) => {
+        #[derive(Serialize, Deserialize, Debug, Clone)]
+        $(#[$attr])*
+        pub struct $name<$param> { $($fields)* }
+
+        #[derive(Serialize, Apiv2Schema)]
+        #[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+        #[allow(dead_code)]
+        struct $twin<$param> { $($fields)* }
+
+        $(
+            impl paperclip::v2::schema::Apiv2Schema for $name<$arg> {
+                fn name() -> Option<String> {
+                    Some($schema.to_string())
+                }
+
+                fn description() -> &'static str {
+                    <$twin<$arg> as paperclip::v2::schema::Apiv2Schema>::description()
+                }
+
+                fn raw_schema() -> paperclip::v2::models::DefaultSchemaRaw {
+                    let mut schema =
+                        <$twin<$arg> as paperclip::v2::schema::Apiv2Schema>::raw_schema();
+                    schema.name = Self::name();
+                    schema
+                }
+            }
+
+            impl paperclip::actix::OperationModifier for $name<$arg> {}
+
+            #[cfg(feature = "json-schema")]
+            impl schemars::JsonSchema for $name<$arg> {
+                fn schema_name() -> String {
+                    $schema.to_string()
+                }
+
+                fn json_schema(
+                    gen: &mut schemars::gen::SchemaGenerator,
+                ) -> schemars::schema::Schema {
+                    <$twin<$arg> as schemars::JsonSchema>::json_schema(gen)
+                }
+            }
+        )*
+    };
+}
 
 pub const STATUS_OK: &str = "Ok";
 pub const STATUS_FAILED: &str = "Failed";
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum EngineError {
     InputFailedToParseJsonRequest,
     InputTryAPostOrGetRequestInstead,
@@ -102,6 +170,7 @@ pub enum EngineError {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum AdaptError {
     OutputRouteFailed,
     OutputInvalidOption,
@@ -155,6 +224,7 @@ impl ToString for AdaptError {
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum ValhallaError {
     NotImplemented,
     InvalidUrl,
@@ -176,6 +246,7 @@ pub enum ValhallaError {
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum OsrmError {
     TooBig,
     NotImplemented,
@@ -189,7 +260,8 @@ pub enum OsrmError {
     UnknownError,
 }
 
-#[derive(Serialize, Deserialize, Clone, Apiv2Schema, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Debug, Apiv2Schema, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum Engine {
     #[serde(rename = "osrm")]
     OSRM,
@@ -197,7 +269,8 @@ pub enum Engine {
     Valhalla,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Apiv2Schema)]
+#[derive(Serialize, Deserialize, Clone, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ISOChroneValhallaInput {
     #[doc = "mode of service.\n\nValues:`car`.\n\nDefault: `\"4w\"`"]
     pub mode: Option<String>,
@@ -215,7 +288,26 @@ pub struct ISOChroneValhallaInput {
     pub key: Option<String>,
 }
 
+// the key is masked in logs
+impl fmt::Debug for ISOChroneValhallaInput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ISOChroneValhallaInput")
+            .field("mode", &self.mode)
+            .field("coordinates", &self.coordinates)
+            .field("contours_meters", &self.contours_meters)
+            .field("contours_minutes", &self.contours_minutes)
+            .field("contours_colors", &self.contours_colors)
+            .field("polygons", &self.polygons)
+            .field("denoise", &self.denoise)
+            .field("generalize", &self.generalize)
+            .field("departure_time", &self.departure_time)
+            .field("key", &MaskedKey(&self.key))
+            .finish()
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ISOChroneValhallaOutput {
     pub features: Vec<ISOChroneFeature>,
     #[serde(rename = "type")]
@@ -226,6 +318,7 @@ pub struct ISOChroneValhallaOutput {
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ISOChroneFeature {
     pub properties: ISOChroneProperty,
     pub geometry: ISOChroneGeometry,
@@ -233,6 +326,7 @@ pub struct ISOChroneFeature {
     pub r#type: String,
 }
 #[derive(Debug, Serialize, Deserialize, Clone, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ISOChroneGeometry {
     pub coordinates: ISOChroneGeometryCoordinates,
     #[serde(rename = "type")]
@@ -240,6 +334,7 @@ pub struct ISOChroneGeometry {
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 #[serde(untagged)]
 pub enum ISOChroneGeometryCoordinates {
     Linestring(Vec<Vec<f64>>),
@@ -247,6 +342,7 @@ pub enum ISOChroneGeometryCoordinates {
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ISOChroneProperty {
     pub fill: String,
     #[serde(rename = "fillOpacity")]
@@ -260,6 +356,7 @@ pub struct ISOChroneProperty {
 }
 
 #[derive(Serialize, Deserialize, Clone, Apiv2Schema, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum GeometryInput {
     #[serde(rename = "polyline")]
     Polyline,
@@ -269,7 +366,8 @@ pub enum GeometryInput {
     GeoJSON,
 }
 
-#[derive(Serialize, Deserialize, Clone, Apiv2Schema)]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Apiv2Schema, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum OverviewInput {
     #[serde(rename = "full")]
     Full,
@@ -279,7 +377,74 @@ pub enum OverviewInput {
     False,
 }
 
+impl OverviewInput {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OverviewInput::Full => "full",
+            OverviewInput::Simplified => "simplified",
+            OverviewInput::False => "false",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Apiv2Schema, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub enum RouteType {
+    #[serde(rename = "fastest")]
+    Fastest,
+    #[serde(rename = "shortest")]
+    Shortest,
+}
+
+impl RouteType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RouteType::Fastest => "fastest",
+            RouteType::Shortest => "shortest",
+        }
+    }
+}
+
+// TripSource is the coordinate the trip of an optimization starts at
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Apiv2Schema, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub enum TripSource {
+    #[serde(rename = "any")]
+    Any,
+    #[serde(rename = "first")]
+    First,
+}
+
+impl TripSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TripSource::Any => "any",
+            TripSource::First => "first",
+        }
+    }
+}
+
+// TripDestination is the coordinate the trip of an optimization ends at
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Apiv2Schema, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub enum TripDestination {
+    #[serde(rename = "any")]
+    Any,
+    #[serde(rename = "last")]
+    Last,
+}
+
+impl TripDestination {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TripDestination::Any => "any",
+            TripDestination::Last => "last",
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum GeoJSONType {
     Point,
     MultiPoint,
@@ -294,6 +459,7 @@ pub enum GeoJSONType {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct GeoJSONLineString {
     #[serde(rename = "type")]
     pub geojson_type: GeoJSONType,
@@ -302,6 +468,7 @@ pub struct GeoJSONLineString {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct GeoJSONMultiLineString {
     #[serde(rename = "type")]
     pub geojson_type: GeoJSONType,
@@ -310,6 +477,7 @@ pub struct GeoJSONMultiLineString {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 #[serde(untagged)]
 pub enum GeoJSONObject {
     LineString(GeoJSONLineString),
@@ -317,6 +485,7 @@ pub enum GeoJSONObject {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct GeoJSONFeature {
     #[serde(rename = "type")]
     pub geojson_type: GeoJSONType,
@@ -325,18 +494,21 @@ pub struct GeoJSONFeature {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct Locations {
     pub id: u64,
     pub location: String,
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct LocationsV2 {
     pub id: u64,
     pub location: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct Job {
     pub id: u64,
     pub location_index: i32,
@@ -351,6 +523,7 @@ pub struct Job {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct Vehicle {
     pub id: u64,
     pub start_index: Option<u64>,
@@ -367,29 +540,34 @@ pub struct Vehicle {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct VehicleCosts {
     pub fixed: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct OptimizationOptions {
     pub objective: Option<ObjectiveOption>,
     pub constraint: Option<OptimizationConstraint>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ObjectiveOption {
     pub travel_cost: Option<String>,
     pub minimise_num_depots: Option<bool>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct OptimizationConstraint {
     pub max_vehicle_overtime: Option<u64>,
     pub max_visit_lateness: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct Depot {
     pub id: u64,
     pub location_index: u64,
@@ -397,6 +575,7 @@ pub struct Depot {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct Break {
     pub id: u64,
     pub time_windows: Vec<Vec<i64>>,
@@ -405,6 +584,7 @@ pub struct Break {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct Shipment {
     pub pickup: ShipmentStep,
     pub delivery: ShipmentStep,
@@ -414,6 +594,7 @@ pub struct Shipment {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ShipmentStep {
     pub id: u64,
     pub location_index: u64,
@@ -423,6 +604,7 @@ pub struct ShipmentStep {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct VRoomResult {
     pub code: u8,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -433,6 +615,7 @@ pub struct VRoomResult {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct Unassigned {
     pub id: u64,
     #[serde(rename = "type")]
@@ -441,6 +624,7 @@ pub struct Unassigned {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct VRoomRoute {
     pub vehicle: Option<u64>,
     pub cost: Option<u64>,
@@ -459,6 +643,7 @@ pub struct VRoomRoute {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct VRoomStep {
     #[serde(rename = "type")]
     pub step_type: Option<String>,
@@ -476,6 +661,7 @@ pub struct VRoomStep {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct Summary {
     pub cost: Option<u64>,
     pub routes: Option<u64>,
@@ -492,6 +678,7 @@ pub struct Summary {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct Violation {
     pub cause: Option<String>,
     pub duration: Option<f64>,
@@ -499,16 +686,19 @@ pub struct Violation {
 
 // wrapper type to keep consistent with python api
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct IntValue {
     pub value: i64,
 }
 
 #[derive(Deserialize, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct KeyInput {
     pub key: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct UpdateRRTSimpleInput {
     pub from_way_id: u64,
     pub from_way_nodes: String,
@@ -523,6 +713,7 @@ pub struct UpdateRRTSimpleInput {
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct UpdateRRTSegmentInput {
     pub segment: String,
     pub status: i32,
@@ -532,6 +723,7 @@ pub struct UpdateRRTSegmentInput {
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct UpdateRRTDimensionInput {
     pub way_id: u64,
     pub segment: String,
@@ -545,6 +737,7 @@ pub struct UpdateRRTDimensionInput {
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct UpdateRRTFixedSpeedInput {
     pub way_id: u64,
     pub segment: String, // 路段纬经度, 传入顺序决定方向
@@ -557,6 +750,7 @@ pub struct UpdateRRTFixedSpeedInput {
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct UpdateRRTSimpleOutput {
     pub success: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -564,15 +758,16 @@ pub struct UpdateRRTSimpleOutput {
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct OptimizationInput {
     #[doc = "A semicolon-separated list of {lat},{lng}.\n\nFormat: `lat0,lng0|lat1,lng1|...`.\n\nRegex: (^[\\d\\.\\-]+,[\\d\\.\\-]+(\\|[\\d\\.\\-]+,[\\d\\.\\-]+)*$)"]
     pub coordinates: String,
     #[doc = "mode of service.\n\nValues:`car`.\n\nDefault: `\"car\"`"]
     pub mode: Option<String>,
     #[doc = "The coordinate at which to start the returned route.\n\nValues: `any|first`.\n\nDefault: `first`"]
-    pub source: Option<String>,
+    pub source: Option<TripSource>,
     #[doc = "Specify the destination coordinate of the returned route.\n\nValues: `any|last`.\n\nDefault: `any`"]
-    pub destination: Option<String>,
+    pub destination: Option<TripDestination>,
     #[doc = "Indicates whether the returned route is roundtrip.\n\nDefault: `true`"]
     pub roundtrip: Option<bool>,
     #[doc = "Indicates whether the return geometry.\n\nDefault: `false`"]
@@ -584,7 +779,20 @@ pub struct OptimizationInput {
     pub approaches: Option<String>,
 }
 
+impl OptimizationInput {
+    // source_string and destination_string keep the string forms for callers not migrated to
+    //  the enums yet
+    pub fn source_string(&self) -> Option<String> {
+        self.source.map(|v| v.as_str().to_string())
+    }
+
+    pub fn destination_string(&self) -> Option<String> {
+        self.destination.map(|v| v.as_str().to_string())
+    }
+}
+
 #[derive(Serialize, Deserialize, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct NavigatingInput {
     #[doc = "geometry input, if this is given, other params will not be considered except `original_shape_type` & `lang` & `key`."]
     pub original_shape: Option<String>,
@@ -603,7 +811,7 @@ pub struct NavigatingInput {
     #[doc = "location(s) of waypoint(s) along the trip.\n\nFormat: `lat0,lng0|lat1,lng1|...`.\n\nRegex: (^[\\d\\.\\-]+,[\\d\\.\\-]+(\\|[\\d\\.\\-]+,[\\d\\.\\-]+)*$)"]
     pub waypoints: Option<String>,
     #[doc = "mode of service.\n\nValues:`car|auto|bike|escooter|4w|2w...`.\n\nDefault: `\"\"`"]
-    pub mode: Option<String>,
+    pub mode: Option<TravelMode>,
     #[doc = "Indicates the truck size in CM, only valid when mode=6w. \n\nFormat: `height,width,length`."]
     pub truck_size: Option<String>,
     #[doc = "Indicates the truck weight including trailers and shipped goods in KG, only valid when mode=6w."]
@@ -629,7 +837,7 @@ pub struct NavigatingInput {
     #[doc = "Limits the search to segments with given bearing in degrees towards true north in clockwise direction. \n\nFormat: `degree,range;degree,range...`. Default:`\"\"`"]
     pub bearings: Option<String>,
     #[doc = "using shortest route when route_type=shortest."]
-    pub route_type: Option<String>,
+    pub route_type: Option<RouteType>,
     #[doc = "road info objects to include in response.\n\nFormat: `type1|type2,...`.\n\nDefault:`\"\"`"]
     pub road_info: Option<String>,
     #[doc = "help reroute, the history trace will be added to snap"]
@@ -641,7 +849,15 @@ pub struct NavigatingInput {
     pub cross_border: Option<bool>,
 }
 
+impl NavigatingInput {
+    // route_type_string keeps the string form for callers not migrated to RouteType yet
+    pub fn route_type_string(&self) -> Option<String> {
+        self.route_type.map(|v| v.as_str().to_string())
+    }
+}
+
 #[derive(Serialize, Deserialize, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct TravelledRawLocation {
     pub bearing: Option<f64>,
     pub accuracy: Option<f64>,
@@ -650,6 +866,7 @@ pub struct TravelledRawLocation {
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct NavigatingOutput {
     #[doc = "`Ok` for success."]
     pub status: String,
@@ -663,6 +880,7 @@ pub struct NavigatingOutput {
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct NavigatingProctorOutput {
     #[doc = "`Ok` for success."]
     pub status: String,
@@ -683,12 +901,14 @@ pub struct NavigatingProctorOutput {
 }
 
 #[derive(Debug, Serialize, Deserialize, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ProctorRouteResult {
     pub code: Option<String>,
     pub message: Option<String>,
     pub routes: Vec<ProctorRoute>,
 }
 #[derive(Debug, Serialize, Deserialize, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ProctorRoute {
     pub duration: f64,
     pub distance: f64,
@@ -702,6 +922,7 @@ pub struct ProctorRoute {
 }
 
 #[derive(Debug, Serialize, Deserialize, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ProctorLeg {
     pub annotation: Option<Annotation>,
     pub duration: f64,
@@ -712,6 +933,7 @@ pub struct ProctorLeg {
 }
 
 #[derive(Debug, Serialize, Deserialize, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ProctorStep {
     pub distance: f64,
     pub duration: f64,
@@ -731,6 +953,7 @@ pub struct ProctorStep {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ProctorManeuver {
     pub bearing_before: i32,
     pub bearing_after: i32,
@@ -741,6 +964,7 @@ pub struct ProctorManeuver {
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ValhallaDirectionsInput {
     #[doc = "{{location_of_origin}}\n\nFormat: `lat,lng`.\n\nRegex: ^[\\d\\.\\-]+,[\\d\\.\\-]+$"]
     pub origin: String,
@@ -751,7 +975,7 @@ pub struct ValhallaDirectionsInput {
     #[doc = "enable to include `steps` in response.\n\nDefault: `false`"]
     pub steps: Option<bool>,
     #[doc = "mode of service.\n\nValues:`car|auto|bike|escooter|4w|2w...`.\n\nDefault: `\"\"`"]
-    pub mode: Option<String>,
+    pub mode: Option<TravelMode>,
     #[doc = "departure time, conflict with arrive_time.\n\nFormat: `unix timestamp`.\n\nUnit: `seconds`.\n\nDefault: `0`"]
     pub departure_time: Option<i64>,
     #[doc = "arrive time, conflict with departure_time.\n\nFormat: `unix timestamp`.\n\nUnit: `seconds`.\n\nDefault: `0`"]
@@ -781,7 +1005,7 @@ pub struct ValhallaDirectionsInput {
     #[doc = "Indicates the truck weight including trailers and shipped goods in KG, only valid when mode=6w."]
     pub truck_weight: Option<i32>,
     #[doc = "using shortest route when route_type=shortest."]
-    pub route_type: Option<String>,
+    pub route_type: Option<RouteType>,
     #[doc = "road info objects to include in response.\n\nFormat: `type1|type2,...`.\n\nDefault:`\"\"`"]
     pub road_info: Option<String>,
     pub truck_axle_count: Option<u32>,
@@ -789,9 +1013,19 @@ pub struct ValhallaDirectionsInput {
     pub truck_axle_load: Option<f64>,
     pub cross_border: Option<bool>,
     pub hazmat_type: Option<String>,
+    #[doc = "enable to include the estimated CO2 emissions in response.\n\nDefault: `false`"]
+    pub emissions: Option<bool>,
+}
+
+impl ValhallaDirectionsInput {
+    // route_type_string keeps the string form for callers not migrated to RouteType yet
+    pub fn route_type_string(&self) -> Option<String> {
+        self.route_type.map(|v| v.as_str().to_string())
+    }
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema,Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct DirectionsInput {
     #[doc = "{{location_of_origin}}\n\nFormat: `lat,lng`.\n\nRegex: ^[\\d\\.\\-]+,[\\d\\.\\-]+$"]
     pub origin: String,
@@ -802,7 +1036,7 @@ pub struct DirectionsInput {
     #[doc = "enable to include `steps` in response.\n\nDefault: `false`"]
     pub steps: Option<bool>,
     #[doc = "mode of service.\n\nValues:`car|auto|bike|escooter|4w|2w...`.\n\nDefault: `\"\"`"]
-    pub mode: Option<String>,
+    pub mode: Option<TravelMode>,
     #[doc = "departure time.\n\nFormat: `unix timestamp`.\n\nUnit: `seconds`.\n\nDefault: `0`"]
     pub departure_time: Option<i64>,
     #[doc = "unique session id for trip identification.\n\nNote: Help to reuse cached trip characteritics when set. \n\nDefault: `\"\"`"]
@@ -832,16 +1066,19 @@ pub struct DirectionsInput {
     #[doc = "Limits the search to segments with given bearing in degrees towards true north in clockwise direction. \n\nFormat: `degree,range;degree,range...`. Default:`\"\"`"]
     pub bearings: Option<String>,
     pub snap_avoid: Option<String>,
+    #[doc = "enable to include the estimated CO2 emissions in response.\n\nDefault: `false`"]
+    pub emissions: Option<bool>,
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct PostTripRouteInput {
     #[doc = "location(s) of waypoint(s) along the trip.\n\nFormat:`lat0,lng0|lat1,lng1|...`\n\nRegex: ^[\\d\\.\\-]+,[\\d\\.\\-]+(\\|[\\d\\.\\-]+,[\\d\\.\\-]+)*$"]
     pub waypoints: String,
     #[doc = "unix timestamp of each `waypoints`.\n\nUnit: `seconds`\n\nFormat: `ts0|ts1|...`\n\nRegex: ^[\\d]+(\\|[\\d]+)*$"]
     pub timestamps: Option<String>,
     #[doc = "mode of service.\n\nValues:`car|auto|bike|escooter|4w|2w...`.\n\nDefault: \"\""]
-    pub mode: Option<String>,
+    pub mode: Option<TravelMode>,
     #[doc = "`deprecated`"]
     pub context: Option<String>,
     #[doc = "apikey for authentication.\n\nDefault: `\"\"`"]
@@ -855,6 +1092,7 @@ pub struct PostTripRouteInput {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct PostTripRouteOutput {
     #[doc = "`Ok` for success."]
     pub status: String,
@@ -869,6 +1107,7 @@ pub struct PostTripRouteOutput {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct OptimizationOutput {
     #[doc = "`Ok` for success."]
     pub code: String,
@@ -879,6 +1118,7 @@ pub struct OptimizationOutput {
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct OptimizationPostInput {
     pub key: Option<String>,
     pub description: Option<String>,
@@ -891,7 +1131,8 @@ pub struct OptimizationPostInput {
     pub depots: Option<Vec<Depot>>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[derive(Serialize, Deserialize, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct OptimizationV2PostInput {
     pub key: Option<String>,
     pub description: Option<String>,
@@ -903,17 +1144,37 @@ pub struct OptimizationV2PostInput {
     pub depots: Option<Vec<Depot>>,
 }
 
+// the key is masked in logs
+impl fmt::Debug for OptimizationV2PostInput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("OptimizationV2PostInput")
+            .field("key", &MaskedKey(&self.key))
+            .field("description", &self.description)
+            .field("locations", &self.locations)
+            .field("jobs", &self.jobs)
+            .field("vehicles", &self.vehicles)
+            .field("shipments", &self.shipments)
+            .field("options", &self.options)
+            .field("depots", &self.depots)
+            .finish()
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct OptimizationV2RoutingOptions {
     pub mode: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct OptimizationV2Options {
     pub routing: Option<OptimizationV2RoutingOptions>,
+    pub constraint: Option<OptimizationConstraint>,
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct OptimizationPostOutput {
     pub id: String,
     pub message: String,
@@ -921,17 +1182,20 @@ pub struct OptimizationPostOutput {
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct OptimizationGetInput {
     pub key: Option<String>,
     pub id: String,
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct OptimizationGetOutput {
     pub result: VRoomResult,
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct OptimizationWaypoint {
     pub name: String,
     pub location: Location,
@@ -940,6 +1204,7 @@ pub struct OptimizationWaypoint {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct OptimizationTrip {
     pub geometry: String,
     pub legs: Vec<OptimizationLeg>,
@@ -950,6 +1215,7 @@ pub struct OptimizationTrip {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct OptimizationLeg {
     pub distance: f64,
     pub duration: f64,
@@ -959,6 +1225,7 @@ pub struct OptimizationLeg {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct OptimizationStep {
     pub distance: f64,
     pub duration: f64,
@@ -968,6 +1235,7 @@ pub struct OptimizationStep {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct DirectionsOutput {
     #[doc = "`Ok` for success."]
     pub status: String,
@@ -982,11 +1250,18 @@ pub struct DirectionsOutput {
     pub mode: Option<String>,
     #[doc = "`routes` calculated."]
     pub routes: Vec<Route>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[doc = "input coordinates snapped to the road network, in the order of the input."]
+    pub waypoints: Option<Vec<Waypoint>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub country_code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[doc = "time spent in each stage of the request.\n\nNote: debug only."]
+    pub server_timings: Option<Vec<ServerTiming>>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ValhallaDirectionsOutput {
     #[doc = "`Ok` for success."]
     pub status: String,
@@ -1006,6 +1281,7 @@ pub struct ValhallaDirectionsOutput {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct DirectionsTableOutput {
     #[doc = "`Ok` for success."]
     pub status: String,
@@ -1016,6 +1292,7 @@ pub struct DirectionsTableOutput {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct SpecialObject {
     #[serde(rename = "ID")]
     pub id: String,
@@ -1024,6 +1301,7 @@ pub struct SpecialObject {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct MeteredRoute {
     #[doc = "encoded geometry value in `polyline` or `polyline6`.\n\nFormat: [Link: Polyline](https://developers.google.com/maps/documentation/utilities/polylinealgorithm)"]
     pub geometry: String,
@@ -1031,89 +1309,152 @@ pub struct MeteredRoute {
     pub distance: f64,
 }
 
-#[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
-pub struct Route {
-    #[doc = "encoded geometry value in `polyline` or `polyline6`.\n\nFormat: [Link: Polyline](https://developers.google.com/maps/documentation/utilities/polylinealgorithm)"]
-    pub geometry: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[doc = "`Debug Only!` encoded geometry value in `polyline` or `polyline6`.\n\nNote: might contains `raw` geometry before filtering.\n\nFormat: [Link: Polyline](https://developers.google.com/maps/documentation/utilities/polylinealgorithm)"]
-    pub geometry_full: Option<String>,
-    #[doc = "route driving distance.\n\nUnit: `meters`"]
-    pub distance: f64,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub distance_full: Option<f64>,
-    #[doc = "route driving duration.\n\nUnit: `seconds`"]
-    pub duration: f64,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[doc = "route weight.\n\n"]
-    pub weight: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[doc = "start location of route"]
-    pub start_location: Option<Location>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[doc = "end location of route"]
-    pub end_location: Option<Location>,
-    #[doc = "legs of route.\n\nNote: `waypoints` split `route` into `legs`"]
-    pub legs: Option<Vec<Leg>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[doc = "route driving duration before adjusting.\n\nNote: debug only."]
-    pub raw_duration: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[doc = "route driving duration after adjusting.\n\nNote: debug only."]
-    pub predicted_duration: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub geojson: Option<GeoJSONFeature>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub confidence: Option<f64>,
+named_schemas! {
+    // RouteT holds the fields of the routes of all engines, L being the type of their legs.
+    //  fields only set by some engines are optional and skipped when not set: road_info and
+    //  debug_info used to be valhalla only and confidence osrm only, they are now part of the
+    //  schemas of both
+    pub struct RouteT<L> as RouteSchema {
+        #[doc = "encoded geometry value in `polyline` or `polyline6`.\n\nFormat: [Link: Polyline](https://developers.google.com/maps/documentation/utilities/polylinealgorithm)"]
+        pub geometry: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[doc = "`Debug Only!` encoded geometry value in `polyline` or `polyline6`.\n\nNote: might contains `raw` geometry before filtering.\n\nFormat: [Link: Polyline](https://developers.google.com/maps/documentation/utilities/polylinealgorithm)"]
+        pub geometry_full: Option<String>,
+        #[doc = "route driving distance.\n\nUnit: `meters`"]
+        pub distance: f64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub distance_full: Option<f64>,
+        #[doc = "route driving duration.\n\nUnit: `seconds`"]
+        pub duration: f64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[doc = "route weight.\n\n"]
+        pub weight: Option<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[doc = "start location of route"]
+        pub start_location: Option<Location>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[doc = "end location of route"]
+        pub end_location: Option<Location>,
+        #[doc = "legs of route.\n\nNote: `waypoints` split `route` into `legs`"]
+        pub legs: Option<Vec<L>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[doc = "route driving duration before adjusting.\n\nNote: debug only."]
+        pub raw_duration: Option<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[doc = "route driving duration after adjusting.\n\nNote: debug only."]
+        pub predicted_duration: Option<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub geojson: Option<GeoJSONFeature>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub confidence: Option<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[doc = "`road info objects crossed along the trip.`"]
+        pub road_info: Option<RoadInfo>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[doc = "`debug related information.`"]
+        pub debug_info: Option<DebugInfo>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[doc = "`geofences entered and exited along the route, in the order they are entered.`"]
+        pub geofence_crossings: Option<Vec<Crossing>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[doc = "`estimated toll cost of the route.`"]
+        pub toll_cost: Option<TollCost>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[doc = "estimated CO2 emissions of the route.\n\nUnit: `grams`"]
+        pub co2_emissions: Option<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[doc = "`elevation profile of the route.`"]
+        pub elevation: Option<ElevationProfile>,
+
+    }
+    Leg => "Route",
+    ValhallaLeg => "ValhallaRoute",
+}
+
+pub type Route = RouteT<Leg>;
+pub type ValhallaRoute = RouteT<ValhallaLeg>;
+
+// max distance between 2 points of the geometry of straight line fallback routes
+const FALLBACK_GEOMETRY_STEP_METERS: f64 = 1000.0;
+
+impl Route {
+    // straight_line_fallback builds a route following the great circle between origin and
+    //  destination when no engine could route them, speed is in meters per second
+    pub fn straight_line_fallback(
+        origin: &Coord,
+        destination: &Coord,
+        speed: f64,
+        precision: u32,
+    ) -> Route {
+        let coords = densify_great_circle(origin, destination, FALLBACK_GEOMETRY_STEP_METERS);
+        let distance = path_length(&coords).round();
+        let duration = if speed > 0.0 {
+            (distance / speed).round()
+        } else {
+            0.0
+        };
+        let start_location = Location {
+            latitude: origin.lat,
+            longitude: origin.lng,
+        };
+        let end_location = Location {
+            latitude: destination.lat,
+            longitude: destination.lng,
+        };
+        Route {
+            geometry: Some(polyline::encode(&coords, precision)),
+            geometry_full: None,
+            distance,
+            distance_full: None,
+            duration,
+            weight: None,
+            start_location: Some(start_location.clone()),
+            end_location: Some(end_location.clone()),
+            legs: Some(vec![Leg {
+                distance: IntValue {
+                    value: distance as i64,
+                },
+                duration: IntValue {
+                    value: duration as i64,
+                },
+                raw_duration: None,
+                start_location: Some(start_location),
+                end_location: Some(end_location),
+                steps: None,
+                annotation: None,
+            }]),
+            raw_duration: None,
+            predicted_duration: None,
+            geojson: None,
+            confidence: None,
+            road_info: None,
+            debug_info: None,
+            geofence_crossings: None,
+            toll_cost: None,
+            co2_emissions: None,
+            elevation: None,
+        }
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
-pub struct ValhallaRoute {
-    #[doc = "encoded geometry value in `polyline` or `polyline6`.\n\nFormat: [Link: Polyline](https://developers.google.com/maps/documentation/utilities/polylinealgorithm)"]
-    pub geometry: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[doc = "`Debug Only!` encoded geometry value in `polyline` or `polyline6`.\n\nNote: might contains `raw` geometry before filtering.\n\nFormat: [Link: Polyline](https://developers.google.com/maps/documentation/utilities/polylinealgorithm)"]
-    pub geometry_full: Option<String>,
-    #[doc = "route driving distance.\n\nUnit: `meters`"]
-    pub distance: f64,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub distance_full: Option<f64>,
-    #[doc = "route driving duration.\n\nUnit: `seconds`"]
-    pub duration: f64,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[doc = "route weight.\n\n"]
-    pub weight: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[doc = "start location of route"]
-    pub start_location: Option<Location>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[doc = "end location of route"]
-    pub end_location: Option<Location>,
-    #[doc = "legs of route.\n\nNote: `waypoints` split `route` into `legs`"]
-    pub legs: Option<Vec<ValhallaLeg>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[doc = "route driving duration before adjusting.\n\nNote: debug only."]
-    pub raw_duration: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[doc = "route driving duration after adjusting.\n\nNote: debug only."]
-    pub predicted_duration: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub geojson: Option<GeoJSONFeature>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[doc = "`road info objects crossed along the trip.`"]
-    pub road_info: Option<RoadInfo>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[doc = "`debug related information.`"]
-    pub debug_info: Option<DebugInfo>,
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct ServerTiming {
+    #[doc = "stage of the request, e.g. `area_lookup`, `engine` or `post_processing`."]
+    pub name: String,
+    #[doc = "time spent in the stage.\n\nUnit: `milliseconds`"]
+    pub duration_ms: f64,
 }
 
 #[derive(Default, Debug, Clone, Serialize, Apiv2Schema, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct DebugInfo {
     pub node_info: Vec<NodeInfo>,
     pub edge_info: Vec<EdgeInfo>,
 }
 
 #[derive(Default, Debug, Clone, Serialize, Apiv2Schema, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct EdgeInfo {
     pub lanes: Vec<String>,
     pub length: i64,
@@ -1135,6 +1476,7 @@ pub struct EdgeInfo {
 }
 
 #[derive(Default, Debug, Clone, Serialize, Apiv2Schema, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct Classification {
     pub link: bool,
     pub internal: bool,
@@ -1145,6 +1487,7 @@ pub struct Classification {
 }
 
 #[derive(Default, Debug, Clone, Serialize, Apiv2Schema, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct AccessRestriction {
     pub part_of_complex_restriction: bool,
     pub end_restriction: HashMap<String, bool>,
@@ -1154,6 +1497,7 @@ pub struct AccessRestriction {
 }
 
 #[derive(Default, Debug, Clone, Serialize, Apiv2Schema, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct GeoAttributes {
     pub curvature: i64,
     pub max_down_slope: f64,
@@ -1163,6 +1507,7 @@ pub struct GeoAttributes {
 }
 
 #[derive(Default, Debug, Clone, Serialize, Apiv2Schema, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct RawSpeed {
     pub predicted: bool,
     pub constrained_flow: i64,
@@ -1171,12 +1516,30 @@ pub struct RawSpeed {
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct Location {
     pub latitude: f64,
     pub longitude: f64,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct Waypoint {
+    #[doc = "location the input coordinate snapped to."]
+    pub location: Location,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[doc = "name of the street the coordinate snapped to."]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[doc = "distance of the snapped location from the input coordinate.\n\nUnit: `meters`"]
+    pub distance: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[doc = "engine hint of the snapped location, to snap the coordinate faster in later requests."]
+    pub hint: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct Annotation {
     pub duration: Vec<f64>,
     pub distance: Vec<f64>,
@@ -1189,73 +1552,63 @@ pub struct Annotation {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct MetaData {
     pub datasource_names: Vec<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
-pub struct Leg {
-    #[doc = "leg driving distance.\n\nUnit: `meters`"]
-    pub distance: IntValue,
-    #[doc = "leg driving duration.\n\nUnit: `seconds`"]
-    pub duration: IntValue,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[doc = "leg driving duration before adjusting.\n\nNote: debug only."]
-    pub raw_duration: Option<IntValue>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[doc = "start location of `leg`"]
-    pub start_location: Option<Location>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[doc = "end location of `leg`"]
-    pub end_location: Option<Location>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[doc = "`steps` of `leg`"]
-    pub steps: Option<Vec<Step>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[doc = "`deprecated`"]
-    pub annotation: Option<Annotation>,
-}
+named_schemas! {
+    // LegT holds the fields of the legs of all engines, A being the type of their annotation
+    pub struct LegT<A> as LegSchema {
+        #[doc = "leg driving distance.\n\nUnit: `meters`"]
+        pub distance: IntValue,
+        #[doc = "leg driving duration.\n\nUnit: `seconds`"]
+        pub duration: IntValue,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[doc = "leg driving duration before adjusting.\n\nNote: debug only."]
+        pub raw_duration: Option<IntValue>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[doc = "start location of `leg`"]
+        pub start_location: Option<Location>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[doc = "end location of `leg`"]
+        pub end_location: Option<Location>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[doc = "`steps` of `leg`"]
+        pub steps: Option<Vec<Step>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[doc = "`deprecated`"]
+        pub annotation: Option<A>,
 
-#[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
-pub struct ValhallaLeg {
-    #[doc = "leg driving distance.\n\nUnit: `meters`"]
-    pub distance: IntValue,
-    #[doc = "leg driving duration.\n\nUnit: `seconds`"]
-    pub duration: IntValue,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[doc = "leg driving duration before adjusting.\n\nNote: debug only."]
-    pub raw_duration: Option<IntValue>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[doc = "start location of `leg`"]
-    pub start_location: Option<Location>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[doc = "end location of `leg`"]
-    pub end_location: Option<Location>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[doc = "`steps` of `leg`"]
-    pub steps: Option<Vec<Step>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[doc = "`deprecated`"]
-    pub annotation: Option<ValhallaAnnotation>,
+    }
+    Annotation => "Leg",
+    ValhallaAnnotation => "ValhallaLeg",
 }
 
+pub type Leg = LegT<Annotation>;
+pub type ValhallaLeg = LegT<ValhallaAnnotation>;
+
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct SnapNodeInfo {
     pub max_speed: Option<Vec<SnapNodeInfoItem>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct SnapNodeInfoItem {
     pub index: u64,
     pub value: f64,
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct RoadInfo {
     pub max_speed: Option<Vec<RoadSegInfo>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct RoadSegInfo {
     pub offset: u64,
     pub length: u64,
@@ -1263,6 +1616,7 @@ pub struct RoadSegInfo {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ValhallaAnnotation {
     pub seg_info: Vec<SegInfo>,
     pub node_info: Vec<NodeInfo>,
@@ -1275,6 +1629,7 @@ pub struct ValhallaAnnotation {
 }
 
 #[derive(Debug, Serialize, Deserialize, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct SegInfo {
     pub weight: f64,
     pub duration: f64,
@@ -1283,6 +1638,7 @@ pub struct SegInfo {
 }
 
 #[derive(Debug, Serialize, Deserialize, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct NodeInfo {
     pub turn_weight: f64,
     pub turn_duration: f64,
@@ -1290,6 +1646,7 @@ pub struct NodeInfo {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct RoadShieldType {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[doc = "A Label identfying the inscription on the road shield, such as containing the road number."]
@@ -1301,6 +1658,7 @@ pub struct RoadShieldType {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct Step {
     pub geometry: Option<String>,
     pub start_location: Location,
@@ -1338,6 +1696,7 @@ pub struct Step {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct Intersection {
     pub location: Coordinate,
     pub bearings: Vec<i32>,
@@ -1349,12 +1708,14 @@ pub struct Intersection {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct Lane {
     pub indications: Vec<String>,
     pub valid: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct VoiceInstruction {
     pub distance_along_geometry: i32,
     pub unit: String,
@@ -1362,6 +1723,7 @@ pub struct VoiceInstruction {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct Maneuver {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub instruction: Option<String>,
@@ -1379,6 +1741,7 @@ pub struct Maneuver {
 }
 
 #[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct Coordinate {
     pub latitude: f64,
     pub longitude: f64,
@@ -1387,6 +1750,7 @@ pub struct Coordinate {
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct GetNearbyInput {
     #[doc = "location of origin\n\nFormat: `lat,lng`\n\nRegex: ^[\\d\\.\\-]+,[\\d\\.\\-]+$"]
     pub currentlocation: String,
@@ -1401,6 +1765,7 @@ pub struct GetNearbyInput {
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct GetNearbyOutput {
     #[doc = "`Ok` for success."]
     pub status: String,
@@ -1418,6 +1783,7 @@ pub struct GetNearbyOutput {
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct NearbyResult {
     pub id: String,
     #[doc = "result location."]
@@ -1429,13 +1795,14 @@ pub struct NearbyResult {
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ValhallaMatrixInput {
     #[doc = "locations of origins \n\nFormat: lat0,lng0|lat1,lng1|...\n\nRegex: ^[\\d\\.\\-]+,[\\d\\.\\-]+(\\|[\\d\\.\\-]+,[\\d\\.\\-]+)*$"]
     pub origins: String,
     #[doc = "locations of destinations\n\nFormat: lat0,lng0|lat1,lng1|...\n\nRegex: ^[\\d\\.\\-]+,[\\d\\.\\-]+(\\|[\\d\\.\\-]+,[\\d\\.\\-]+)*$"]
     pub destinations: String,
     #[doc = "mode of service.\n\nValues:`car|auto|bike|escooter|4w|2w...`.\n\nDefault: `\"\"`"]
-    pub mode: Option<String>,
+    pub mode: Option<TravelMode>,
     #[doc = "departure time, conflict with arrive_time.\n\nFormat: `unix timestamp`.\n\nUnit: `seconds`.\n\nDefault: `0`"]
     pub departure_time: Option<i64>,
     #[doc = "arrive time, conflict with departure_time.\n\nFormat: `unix timestamp`.\n\nUnit: `seconds`.\n\nDefault: `0`"]
@@ -1453,7 +1820,7 @@ pub struct ValhallaMatrixInput {
     // NOTE: for mdm use, control all destinations's approach
     pub destinations_approach: Option<String>,
     #[doc = "using shortest route when route_type=shortest."]
-    pub route_type: Option<String>,
+    pub route_type: Option<RouteType>,
     #[doc = "Indicates the truck size in CM, only valid when mode=6w. \n\nFormat: `height,width,length`."]
     pub truck_size: Option<String>,
     #[doc = "Indicates the truck weight including trailers and shipped goods in KG, only valid when mode=6w."]
@@ -1463,16 +1830,26 @@ pub struct ValhallaMatrixInput {
     pub truck_axle_load: Option<f64>,
     pub hazmat_type: Option<String>,
     pub cross_border: Option<bool>,
+    #[doc = "enable to include the estimated CO2 emissions in response.\n\nDefault: `false`"]
+    pub emissions: Option<bool>,
+}
+
+impl ValhallaMatrixInput {
+    // route_type_string keeps the string form for callers not migrated to RouteType yet
+    pub fn route_type_string(&self) -> Option<String> {
+        self.route_type.map(|v| v.as_str().to_string())
+    }
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct MatrixInput {
     #[doc = "locations of origins \n\nFormat: lat0,lng0|lat1,lng1|...\n\nRegex: ^[\\d\\.\\-]+,[\\d\\.\\-]+(\\|[\\d\\.\\-]+,[\\d\\.\\-]+)*$"]
     pub origins: String,
     #[doc = "locations of destinations\n\nFormat: lat0,lng0|lat1,lng1|...\n\nRegex: ^[\\d\\.\\-]+,[\\d\\.\\-]+(\\|[\\d\\.\\-]+,[\\d\\.\\-]+)*$"]
     pub destinations: String,
     #[doc = "mode of service.\n\nValues:`car|auto|bike|escooter|4w|2w...`.\n\nDefault: `\"\"`"]
-    pub mode: Option<String>,
+    pub mode: Option<TravelMode>,
     #[doc = "departure time.\n\nFormat: `unix timestamp`.\n\nUnit: `seconds`.\n\nDefault: `0`"]
     pub departure_time: Option<i64>,
     #[doc = "apikey for authentication.\n\nDefault: `\"\"`"]
@@ -1496,9 +1873,12 @@ pub struct MatrixInput {
     #[doc = "won't work with osrm, just for extract"]
     pub snap_avoid: Option<String>,
     pub route_failed_prompt: Option<bool>,
+    #[doc = "enable to include the estimated CO2 emissions in response.\n\nDefault: `false`"]
+    pub emissions: Option<bool>,
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ValhallaMassiveDistanceMatrixInput {
     pub matrix_input: ValhallaMatrixInput,
     pub task_id: String,
@@ -1507,6 +1887,7 @@ pub struct ValhallaMassiveDistanceMatrixInput {
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct MassiveDistanceMatrixInput {
     pub matrix_input: MatrixInput,
     pub task_id: String,
@@ -1515,6 +1896,7 @@ pub struct MassiveDistanceMatrixInput {
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct MassiveDistanceMatrixOutput {
     #[doc = "`Ok` for success."]
     pub status: String,
@@ -1524,6 +1906,7 @@ pub struct MassiveDistanceMatrixOutput {
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct MassiveDistanceMatrixStatusInput {
     pub task_id: String,
     pub chunk_id: String,
@@ -1532,11 +1915,24 @@ pub struct MassiveDistanceMatrixStatusInput {
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct MassiveDistanceMatrixStatusOutput {
     pub status: MassiveDistanceMatrixStatus,
+    #[doc = "expiring URL to download the result from the storage directly"]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result_url: Option<SignedUrl>,
+}
+
+#[derive(Serialize, Deserialize, Apiv2Schema, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct SignedUrl {
+    pub url: String,
+    #[doc = "expiration time of the url.\n\nFormat: `unix timestamp`.\n\nUnit: `seconds`"]
+    pub expires_at: i64,
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct MassiveDistanceMatrixStatus {
     pub task_id: String,
     pub chunk_id: String,
@@ -1545,9 +1941,19 @@ pub struct MassiveDistanceMatrixStatus {
     pub output: Option<MatrixOutput>,
     #[serde(skip_serializing)]
     pub start_time: i64,
+    #[doc = "number of failed attempts of the chunk"]
+    #[serde(default)]
+    pub attempts: u32,
+    #[doc = "error of the last failed attempt"]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    #[doc = "time after which the chunk can be submitted again.\n\nFormat: `unix timestamp`.\n\nUnit: `milliseconds`"]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_retry_at: Option<i64>,
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum MassiveDistanceMatrixStatusEnum {
     Running = 1,
     Failed,
@@ -1556,18 +1962,20 @@ pub enum MassiveDistanceMatrixStatusEnum {
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct MassiveMatrixInput {
     #[doc = "locations of origins \n\nFormat: lat0,lng0|lat1,lng1|...\n\nRegex: ^[\\d\\.\\-]+,[\\d\\.\\-]+(\\|[\\d\\.\\-]+,[\\d\\.\\-]+)*$"]
     pub origins: String,
     #[doc = "locations of destinations\n\nFormat: lat0,lng0|lat1,lng1|...\n\nRegex: ^[\\d\\.\\-]+,[\\d\\.\\-]+(\\|[\\d\\.\\-]+,[\\d\\.\\-]+)*$"]
     pub destinations: Option<String>,
     #[doc = "mode of service.\n\nValues:`car|auto|bike|escooter|4w|2w...`.\n\nDefault: `\"\"`"]
-    pub mode: Option<String>,
+    pub mode: Option<TravelMode>,
     #[doc = "area of service.\n\nValues:`usa|...`.\n\nDefault: `\"\"`"]
     pub area: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct MatrixOutput {
     #[doc = "`Ok` for success."]
     pub status: String,
@@ -1576,23 +1984,77 @@ pub struct MatrixOutput {
     pub warning: Option<Vec<String>>,
     #[doc = "matrix output.\n\nNote: each row in following format\n\nRow[i]: `Element`(o[i]d[0]),`Element`(o[i]d[1]),`Element`(o[i]d[2])..."]
     pub rows: Vec<Row>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[doc = "time spent in each stage of the request.\n\nNote: debug only."]
+    pub server_timings: Option<Vec<ServerTiming>>,
 }
 
 impl MatrixOutput {
     pub fn binary_encode(&self) -> Vec<u8> {
+        self.binary_encode_version(BinaryMatrixVersion::V1)
+    }
+
+    // binary_encode_version writes the layout of version, version 2 is opt-in until all the
+    //  readers of the binary matrices decode it
+    pub fn binary_encode_version(&self, version: BinaryMatrixVersion) -> Vec<u8> {
         let mut res: Vec<u8> = Vec::new();
         // add header
-        let header = encode(self.rows.len() as u32, self.rows[0].elements.len() as u32);
-        res.extend_from_slice(&header);
+        // see matrix_binary for the layout
+        let cols = self.rows.first().map(|r| r.elements.len()).unwrap_or(0);
+        let header = BinaryMatrixHeader {
+            version,
+            rows: self.rows.len() as u32,
+            cols: cols as u32,
+        };
+        res.extend_from_slice(&header.encode());
 
         for row in self.rows.iter() {
             for e in row.elements.iter() {
-                let chunk = encode(e.duration.value as u32, e.distance.value as u32);
+                let chunk = encode(
+                    to_binary_value(e.duration.value),
+                    to_binary_value(e.distance.value),
+                );
                 res.extend_from_slice(&chunk);
             }
         }
         res
     }
+
+    // page returns rows [offset, offset + limit) of the matrix
+    pub fn page(&self, rows_offset: usize, rows_limit: usize) -> MatrixOutputPage {
+        let start = rows_offset.min(self.rows.len());
+        let end = start.saturating_add(rows_limit).min(self.rows.len());
+        MatrixOutputPage {
+            status: self.status.clone(),
+            warning: self.warning.clone(),
+            rows_offset: start,
+            rows_limit,
+            total_rows: self.rows.len(),
+            rows: self.rows[start..end].to_vec(),
+            server_timings: self.server_timings.clone(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct MatrixOutputPage {
+    #[doc = "`Ok` for success."]
+    pub status: String,
+    #[serde(rename = "warning", skip_serializing_if = "Option::is_none")]
+    #[doc = "warning when facing unexpected behaviour"]
+    pub warning: Option<Vec<String>>,
+    #[doc = "index of the first row of this page in the full matrix."]
+    pub rows_offset: usize,
+    #[doc = "max number of rows in a page."]
+    pub rows_limit: usize,
+    #[doc = "number of rows in the full matrix."]
+    pub total_rows: usize,
+    #[doc = "matrix rows of this page.\n\nNote: `rows[i]` is the row of origin `rows_offset + i`"]
+    pub rows: Vec<Row>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[doc = "time spent in each stage of the request.\n\nNote: debug only."]
+    pub server_timings: Option<Vec<ServerTiming>>,
 }
 
 pub fn encode(duration: u32, distance: u32) -> [u8; 8] {
@@ -1603,6 +2065,7 @@ pub fn encode(duration: u32, distance: u32) -> [u8; 8] {
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct MatrixConciseOutput {
     #[doc = "`Ok` for success."]
     pub status: String,
@@ -1622,12 +2085,14 @@ d(xy) distance for origins[x] to dest[y]\n
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct Row {
     #[doc = "`elements` for a particular row|origin"]
     pub elements: Vec<Element>,
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct Element {
     #[doc = "traveling duration between origin and destination.\n\nUnit: `seconds`"]
     pub duration: IntValue,
@@ -1639,22 +2104,29 @@ pub struct Element {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[doc = "traveling duration after adjust.\n\nUnit: `seconds`\n\nNote: debug only"]
     pub predicted_duration: Option<IntValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[doc = "estimated CO2 emissions between origin and destination.\n\nUnit: `grams`"]
+    pub co2_emissions: Option<IntValue>,
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct IsochroneInput {
     pub center: String,
     pub resolution: Option<i32>,
+    #[doc = "travel times of the contours, at most 4.\n\nUnit: `minutes`, at most 120\n\nFormat: `t0|t1|...`"]
     pub times: Option<String>,
+    #[doc = "travel distances of the contours, at most 4.\n\nUnit: `meters`, at most 100000\n\nFormat: `d0|d1|...`"]
     pub distances: Option<String>,
     pub strokes: Option<String>,
     pub opacities: Option<String>, // range: [0, 1], 0 for transparent
-    pub mode: Option<String>,
+    pub mode: Option<TravelMode>,
     pub departure_time: Option<i64>,
     pub key: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct IsochroneOutput {
     pub status: String,
     pub polylines: Vec<String>,
@@ -1665,6 +2137,7 @@ pub struct IsochroneOutput {
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct SnapInput {
     #[doc = "`locations` to perform `snap2roads`\n\nFormat: `lat0,lng0|lat1,lng1|...`\n\nRegex: ^[\\d\\.\\-]+,[\\d\\.\\-]+(\\|[\\d\\.\\-]+,[\\d\\.\\-]+)*$"]
     pub path: String,
@@ -1678,7 +2151,7 @@ pub struct SnapInput {
     pub context: Option<String>,
     #[doc = "enable to ignore location not found in service boundary.\n\nNote: enable this to ignore outliers, otherwise an error will be thrown.\n\nDefault: `false`"]
     pub tolerate_outlier: Option<bool>,
-    pub mode: Option<String>,
+    pub mode: Option<TravelMode>,
     pub avoid: Option<String>,
     pub approaches: Option<String>,
     #[doc = "only supports for polyline and geojson"]
@@ -1692,6 +2165,7 @@ pub struct SnapInput {
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema, Debug)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ValhallaSnapOutput {
     #[doc = "`Ok` for success."]
     pub status: String,
@@ -1721,6 +2195,7 @@ pub struct ValhallaSnapOutput {
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema, Debug)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct SnapOutput {
     #[doc = "`Ok` for success."]
     pub status: String,
@@ -1741,6 +2216,9 @@ pub struct SnapOutput {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[doc = "`snap node info objects crossed along the snap trip.`"]
     pub snap_node_info: Option<Vec<Option<SnapNodeInfo>>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[doc = "`ranges of each geometry driven over the max speed, from the timestamps of the snapped points.`"]
+    pub speed_violations: Option<Vec<Option<Vec<SpeedViolation>>>>,
     #[doc = "legs of route.\n\nNote: `waypoints` split `route` into `legs`"]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub legs: Option<Vec<Leg>>,
@@ -1750,9 +2228,13 @@ pub struct SnapOutput {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub routes: Option<Vec<Route>>,
     pub country_code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[doc = "time spent in each stage of the request.\n\nNote: debug only."]
+    pub server_timings: Option<Vec<ServerTiming>>,
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema, Debug)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct SnappedPoint {
     pub location: Location,
     #[serde(rename = "originalIndex")]
@@ -1801,31 +2283,147 @@ pub struct ConfigCluster {
 }
 
 #[derive(Serialize, Deserialize, Apiv2Schema)]
-pub struct ClusteringPostInputPartial {
-    pub options: Option<ClusteringOptionPartial>,
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct ClusteringPostInput {
+    #[doc = "apikey for authentication.\n\nDefault: `\"\"`"]
+    pub key: Option<String>,
+    pub description: Option<String>,
+    pub options: Option<ClusteringOption>,
+    #[doc = "locations referred by `location_index` of jobs.\n\nFormat: `lat,lng`"]
     pub locations: Vec<String>,
-    pub jobs: Vec<ClusteringJobPartial>,
+    pub jobs: Vec<ClusteringJob>,
 }
 
-#[derive(Serialize, Deserialize, Apiv2Schema)]
-pub struct ClusteringOptionPartial {
-    pub routing: Option<ClusteringRoutingOptionPartial>,
-    pub objective: Option<ClusteringRoutingObjectivePartial>,
+#[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct ClusteringOption {
+    pub routing: Option<ClusteringRoutingOption>,
+    pub objective: Option<ClusteringRoutingObjective>,
+    #[doc = "number of clusters to form, at most the number of jobs."]
+    pub number_of_clusters: Option<usize>,
+    #[doc = "max load of a cluster, with the dimensions of `demand`."]
+    pub max_cluster_load: Option<Vec<u64>>,
 }
 
-#[derive(Serialize, Deserialize, Apiv2Schema)]
-pub struct ClusteringRoutingOptionPartial {
+#[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct ClusteringRoutingOption {
     pub mode: Option<String>,
     pub option: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Apiv2Schema)]
-pub struct ClusteringRoutingObjectivePartial {
+#[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct ClusteringRoutingObjective {
     pub travel_cost: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Apiv2Schema)]
-pub struct ClusteringJobPartial {}
+#[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct ClusteringJob {
+    pub id: u64,
+    #[doc = "index of the job in `locations`."]
+    pub location_index: usize,
+    #[doc = "load of the job, with one value per capacity dimension."]
+    pub demand: Option<Vec<u64>>,
+    #[doc = "time window of the job.\n\nFormat: `[start, end]`.\n\nUnit: `unix timestamp in seconds`"]
+    pub time_window: Option<Vec<u64>>,
+    pub skills: Option<Vec<i64>>,
+    pub description: Option<String>,
+}
+
+// the partial names are kept for the gateways that still use them
+pub type ClusteringPostInputPartial = ClusteringPostInput;
+pub type ClusteringOptionPartial = ClusteringOption;
+pub type ClusteringRoutingOptionPartial = ClusteringRoutingOption;
+pub type ClusteringRoutingObjectivePartial = ClusteringRoutingObjective;
+pub type ClusteringJobPartial = ClusteringJob;
+
+impl ClusteringPostInput {
+    pub fn validate(&self) -> Result<()> {
+        if self.locations.is_empty() {
+            bail!("locations is empty");
+        }
+        for (i, location) in self.locations.iter().enumerate() {
+            if let Err(e) = Coord::coord(location) {
+                bail!("locations[{}] is invalid: {}", i, e);
+            }
+        }
+        if self.jobs.is_empty() {
+            bail!("jobs is empty");
+        }
+        let mut ids = HashSet::new();
+        let mut dimensions = None;
+        for job in self.jobs.iter() {
+            if !ids.insert(job.id) {
+                bail!("job {} is duplicated", job.id);
+            }
+            if job.location_index >= self.locations.len() {
+                bail!(
+                    "job {}: location_index {} is out of the {} locations",
+                    job.id,
+                    job.location_index,
+                    self.locations.len()
+                );
+            }
+            if let Some(tw) = job.time_window.as_ref() {
+                if tw.len() != 2 || tw[0] > tw[1] {
+                    bail!(
+                        "job {}: time_window must be [start, end] with start <= end",
+                        job.id
+                    );
+                }
+            }
+            if let Some(demand) = job.demand.as_ref() {
+                match dimensions {
+                    Some(d) if d != demand.len() => bail!(
+                        "job {}: demand has {} dimensions, other jobs have {}",
+                        job.id,
+                        demand.len(),
+                        d
+                    ),
+                    _ => dimensions = Some(demand.len()),
+                }
+            }
+        }
+        if let Some(options) = self.options.as_ref() {
+            if let Some(n) = options.number_of_clusters {
+                if n == 0 || n > self.jobs.len() {
+                    bail!(
+                        "number_of_clusters must be between 1 and the {} jobs",
+                        self.jobs.len()
+                    );
+                }
+            }
+            if let (Some(max), Some(d)) = (options.max_cluster_load.as_ref(), dimensions) {
+                if max.len() != d {
+                    bail!(
+                        "max_cluster_load has {} dimensions, demand has {}",
+                        max.len(),
+                        d
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct ClusteringOutput {
+    pub clusters: Vec<Cluster>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct Cluster {
+    #[doc = "center of the locations of the jobs in the cluster."]
+    pub centroid: Location,
+    pub job_ids: Vec<u64>,
+    #[doc = "sum of the demand of the jobs in the cluster."]
+    pub load: Vec<u64>,
+}
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ConfigKeyValue {
@@ -1836,9 +2434,13 @@ pub struct ConfigKeyValue {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct MaaasAreaConfig {
     pub areas: Vec<ConfigArea>,
+    #[serde(default)]
+    pub repair_polygons: bool,
     #[serde(skip)]
     pub parsed_areas: HashMap<String, Vec<Polygon<f64>>>,
     #[serde(skip)]
+    pub load_reports: HashMap<String, LoadReport>,
+    #[serde(skip)]
     pub inited: bool,
 }
 
@@ -1847,6 +2449,9 @@ impl MaaasAreaConfig {
         if self.inited {
             return;
         }
+        let opts = NormalizeOptions {
+            repair: self.repair_polygons,
+        };
         for area in self.areas.iter() {
             let mut polygons: Vec<Polygon<f64>> = Vec::new();
             for p in area.polygons.iter() {
@@ -1856,7 +2461,12 @@ impl MaaasAreaConfig {
                 }
                 polygons.push(Polygon::<f64>::new(LineString::from(coords), vec![]));
             }
+            let (polygons, report) = normalize_polygons(polygons, &opts);
+            for issue in report.issues.iter() {
+                warn!("maaas area {} has polygon issue {:?}", area.id, issue);
+            }
             self.parsed_areas.insert(area.id.to_owned(), polygons);
+            self.load_reports.insert(area.id.to_owned(), report);
         }
         self.inited = true;
     }
@@ -2010,4 +2620,120 @@ mod tests {
             assert!(r.areas.len() == 1);
         }
     }
+
+    #[test]
+    fn test_straight_line_fallback() {
+        let origin = Coord::new(1.3, 103.8);
+        let destination = Coord::new(1.4, 103.9);
+        let route = Route::straight_line_fallback(
+            &origin,
+            &destination,
+            10.0,
+            polyline::PRECISION_POLYLINE6,
+        );
+        assert!((route.distance - 15_730.0).abs() < 100.0);
+        assert_eq!(route.duration, (route.distance / 10.0).round());
+        let legs = route.legs.as_ref().unwrap();
+        assert_eq!(legs.len(), 1);
+        assert_eq!(legs[0].distance.value, route.distance as i64);
+        let coords = polyline::decode(
+            route.geometry.as_ref().unwrap(),
+            polyline::PRECISION_POLYLINE6,
+        )
+        .unwrap();
+        assert_eq!(coords.len(), 17);
+        assert!(path_length(&[coords[0].clone(), origin]) < 0.5);
+        assert!(path_length(&[coords[16].clone(), destination]) < 0.5);
+    }
+
+    #[test]
+    fn test_typed_inputs() {
+        let input: OptimizationInput = serde_json::from_value(serde_json::json!({
+            "coordinates": "1.3,103.8|1.4,103.9",
+            "source": "first",
+            "destination": "any",
+        }))
+        .unwrap();
+        assert_eq!(input.source, Some(TripSource::First));
+        assert_eq!(input.source_string(), Some("first".to_string()));
+        assert_eq!(input.destination_string(), Some("any".to_string()));
+        assert!(serde_json::from_value::<OptimizationInput>(serde_json::json!({
+            "coordinates": "1.3,103.8",
+            "source": "last",
+        }))
+        .is_err());
+
+        let input: NavigatingInput = serde_json::from_value(serde_json::json!({
+            "route_type": "shortest",
+            "overview": "simplified",
+        }))
+        .unwrap();
+        assert_eq!(input.route_type, Some(RouteType::Shortest));
+        assert_eq!(input.route_type_string(), Some("shortest".to_string()));
+        assert_eq!(input.overview.unwrap().as_str(), "simplified");
+        assert!(serde_json::from_value::<NavigatingInput>(serde_json::json!({
+            "route_type": "shortes",
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn test_clustering_input() {
+        let input: ClusteringPostInput = serde_json::from_value(serde_json::json!({
+            "locations": ["1.3,103.8", "1.29,103.85"],
+            "jobs": [
+                {"id": 1, "location_index": 0, "demand": [2], "time_window": [0, 3600]},
+                {"id": 2, "location_index": 1, "demand": [3], "skills": [1]},
+            ],
+            "options": {"number_of_clusters": 2, "max_cluster_load": [4]},
+        }))
+        .unwrap();
+        assert!(input.validate().is_ok());
+
+        let message = |input: serde_json::Value| {
+            let input: ClusteringPostInput = serde_json::from_value(input).unwrap();
+            input.validate().err().unwrap().to_string()
+        };
+        assert_eq!(
+            message(serde_json::json!({
+                "locations": ["1.3,103.8"],
+                "jobs": [{"id": 1, "location_index": 1}],
+            })),
+            "job 1: location_index 1 is out of the 1 locations"
+        );
+        assert_eq!(
+            message(serde_json::json!({
+                "locations": ["1.3,103.8"],
+                "jobs": [{"id": 1, "location_index": 0, "demand": [1]}, {"id": 2, "location_index": 0, "demand": [1, 2]}],
+            })),
+            "job 2: demand has 2 dimensions, other jobs have 1"
+        );
+        assert_eq!(
+            message(serde_json::json!({
+                "locations": ["1.3,103.8"],
+                "jobs": [{"id": 1, "location_index": 0, "time_window": [10, 0]}],
+            })),
+            "job 1: time_window must be [start, end] with start <= end"
+        );
+    }
+
+    #[test]
+    fn test_route_schema_names() {
+        use paperclip::v2::schema::Apiv2Schema;
+        assert_eq!(Route::name(), Some("Route".to_string()));
+        assert_eq!(ValhallaRoute::name(), Some("ValhallaRoute".to_string()));
+        assert_eq!(Leg::name(), Some("Leg".to_string()));
+        assert_eq!(ValhallaLeg::name(), Some("ValhallaLeg".to_string()));
+
+        // nested schemas are named after the aliases too
+        let schema = DirectionsOutput::raw_schema();
+        let route = schema.properties["routes"].items.as_ref().unwrap();
+        assert_eq!(route.name.as_deref(), Some("Route"));
+        let leg = route.properties["legs"].items.as_ref().unwrap();
+        assert_eq!(leg.name.as_deref(), Some("Leg"));
+        assert!(route.properties.contains_key("road_info"));
+        let schema = ValhallaRoute::raw_schema();
+        let leg = schema.properties["legs"].items.as_ref().unwrap();
+        assert_eq!(leg.name.as_deref(), Some("ValhallaLeg"));
+    }
 }
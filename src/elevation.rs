@@ -0,0 +1,400 @@
+// elevation profiles of routes, for the bike and escooter modes. the geometry of a route is
+//  sampled at regular distances, the elevation of the samples comes from an ElevationProvider
+//  (SRTM tiles on disk or an HTTP elevation service), and the ascent and descent of the route
+//  are summed over the profile
+use crate::coord::{cumulative_distances, Coord, Locatable};
+use crate::def::RouteT;
+use crate::http_client::HttpClient;
+use crate::mdm_io::StoreFuture;
+use crate::polyline;
+use crate::Result;
+use paperclip::actix::Apiv2Schema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+// samples of SRTM tiles without data
+const SRTM_VOID: i16 = -32768;
+// max number of locations per request of HttpElevationProvider
+const MAX_LOCATIONS_PER_REQUEST: usize = 100;
+// max number of samples of a path, the step grows on longer paths
+pub const MAX_PATH_SAMPLES: usize = 2000;
+
+pub trait ElevationProvider: Sync {
+    // elevations returns the elevation of each coordinate in meters, None where it is unknown
+    fn elevations<'a>(&'a self, coords: &'a [Coord]) -> StoreFuture<'a, Vec<Option<f64>>>;
+}
+
+#[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct ElevationPoint {
+    #[doc = "distance along the route.\n\nUnit: `meters`"]
+    pub offset: f64,
+    #[doc = "elevation above sea level.\n\nUnit: `meters`"]
+    pub elevation: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct ElevationProfile {
+    #[doc = "elevation sampled along the route, samples of unknown elevation are skipped."]
+    pub points: Vec<ElevationPoint>,
+    #[doc = "total elevation gained along the route.\n\nUnit: `meters`"]
+    pub ascent: f64,
+    #[doc = "total elevation lost along the route.\n\nUnit: `meters`"]
+    pub descent: f64,
+}
+
+impl ElevationProfile {
+    pub fn from_points(points: Vec<ElevationPoint>) -> ElevationProfile {
+        let (mut ascent, mut descent) = (0.0, 0.0);
+        for w in points.windows(2) {
+            let diff = w[1].elevation - w[0].elevation;
+            if diff > 0.0 {
+                ascent += diff;
+            } else {
+                descent -= diff;
+            }
+        }
+        ElevationProfile {
+            points,
+            ascent,
+            descent,
+        }
+    }
+}
+
+// sample_path returns points of geometry every step_m meters with their distance along it,
+//  from its first to its last point. step_m is widened so that there are at most
+//  MAX_PATH_SAMPLES points
+pub fn sample_path<T: Locatable>(geometry: &[T], step_m: f64) -> Vec<(f64, Coord)> {
+    let offsets = cumulative_distances(geometry);
+    let total = match offsets.last() {
+        Some(v) => *v,
+        None => return vec![],
+    };
+    let step_m = step_m.max(total / (MAX_PATH_SAMPLES - 1) as f64);
+    let mut r = vec![];
+    let mut segment = 0;
+    let mut offset = 0.0;
+    while offset < total && r.len() < MAX_PATH_SAMPLES - 1 {
+        while offsets[segment + 1] < offset {
+            segment += 1;
+        }
+        let (a, b) = (&geometry[segment], &geometry[segment + 1]);
+        let length = offsets[segment + 1] - offsets[segment];
+        let ratio = if length > 0.0 {
+            (offset - offsets[segment]) / length
+        } else {
+            0.0
+        };
+        r.push((
+            offset,
+            Coord::new(
+                a.lat() + (b.lat() - a.lat()) * ratio,
+                a.lng() + (b.lng() - a.lng()) * ratio,
+            ),
+        ));
+        if step_m <= 0.0 {
+            break;
+        }
+        offset += step_m;
+    }
+    let last = &geometry[geometry.len() - 1];
+    r.push((total, Coord::new(last.lat(), last.lng())));
+    r
+}
+
+// profile samples geometry every step_m meters and gets the elevation of the samples
+pub async fn profile<T: Locatable>(
+    provider: &dyn ElevationProvider,
+    geometry: &[T],
+    step_m: f64,
+) -> Result<ElevationProfile> {
+    let (offsets, coords): (Vec<f64>, Vec<Coord>) =
+        sample_path(geometry, step_m).into_iter().unzip();
+    let elevations = provider.elevations(&coords).await?;
+    let points = offsets
+        .into_iter()
+        .zip(elevations)
+        .filter_map(|(offset, elevation)| {
+            Some(ElevationPoint {
+                offset,
+                elevation: elevation?,
+            })
+        })
+        .collect();
+    Ok(ElevationProfile::from_points(points))
+}
+
+// annotate_route sets the elevation profile of route, from its geometry encoded with precision.
+//  routes without geometry are left as is
+pub async fn annotate_route<L>(
+    route: &mut RouteT<L>,
+    provider: &dyn ElevationProvider,
+    step_m: f64,
+    precision: u32,
+) -> Result<()> {
+    let geometry = match route.geometry.as_ref() {
+        Some(v) => polyline::decode(v, precision)?,
+        None => return Ok(()),
+    };
+    route.elevation = Some(profile(provider, &geometry, step_m).await?);
+    Ok(())
+}
+
+// SrtmTile holds the samples of a tile of 1x1 degree, rows from north to south
+struct SrtmTile {
+    size: usize,
+    samples: Vec<i16>,
+}
+
+impl SrtmTile {
+    // parse reads a .hgt file, square grids of big endian samples, 1201 (3 arc seconds) or
+    //  3601 (1 arc second) wide
+    fn parse(data: &[u8]) -> Result<SrtmTile> {
+        let size = ((data.len() / 2) as f64).sqrt() as usize;
+        if size < 2 || size * size * 2 != data.len() {
+            bail!("invalid hgt file of {} bytes", data.len());
+        }
+        let samples = data
+            .chunks(2)
+            .map(|v| i16::from_be_bytes([v[0], v[1]]))
+            .collect();
+        Ok(SrtmTile { size, samples })
+    }
+
+    fn sample(&self, row: usize, col: usize) -> Option<f64> {
+        let v = self.samples[row.min(self.size - 1) * self.size + col.min(self.size - 1)];
+        if v == SRTM_VOID {
+            None
+        } else {
+            Some(v as f64)
+        }
+    }
+
+    // elevation interpolates the samples around the point at (lat, lng) from the south west
+    //  corner of the tile, in degrees
+    fn elevation(&self, lat: f64, lng: f64) -> Option<f64> {
+        let last = (self.size - 1) as f64;
+        let y = (1.0 - lat) * last;
+        let x = lng * last;
+        let (row, col) = (y.floor() as usize, x.floor() as usize);
+        let (dy, dx) = (y - row as f64, x - col as f64);
+        let top = self.sample(row, col)? * (1.0 - dx) + self.sample(row, col + 1)? * dx;
+        let bottom = self.sample(row + 1, col)? * (1.0 - dx) + self.sample(row + 1, col + 1)? * dx;
+        Some(top * (1.0 - dy) + bottom * dy)
+    }
+}
+
+// srtm_tile_name is the name of the tile holding c, e.g. N01E103 for singapore
+fn srtm_tile_name(c: &Coord) -> String {
+    let (lat, lng) = (c.lat.floor() as i32, c.lng.floor() as i32);
+    format!(
+        "{}{:02}{}{:03}",
+        if lat < 0 { 'S' } else { 'N' },
+        lat.abs(),
+        if lng < 0 { 'W' } else { 'E' },
+        lng.abs()
+    )
+}
+
+// SrtmTiles reads the `<name>.hgt` tiles under a local directory, loaded on first use. tiles
+//  missing from the directory give unknown elevations
+pub struct SrtmTiles {
+    pub root: PathBuf,
+    tiles: Mutex<HashMap<String, Option<Arc<SrtmTile>>>>,
+}
+
+impl SrtmTiles {
+    pub fn new<P: Into<PathBuf>>(root: P) -> SrtmTiles {
+        SrtmTiles {
+            root: root.into(),
+            tiles: Mutex::new(HashMap::new()),
+        }
+    }
+
+    async fn tile(&self, name: &str) -> Result<Option<Arc<SrtmTile>>> {
+        if let Some(v) = self.tiles.lock().unwrap().get(name) {
+            return Ok(v.clone());
+        }
+        let path = self.root.join(format!("{}.hgt", name));
+        let tile = match tokio::fs::read(&path).await {
+            Ok(v) => Some(Arc::new(SrtmTile::parse(&v)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                warn!("no srtm tile {:?}", path);
+                None
+            }
+            Err(e) => bail!("failed to read {:?}: {}", path, e),
+        };
+        self.tiles
+            .lock()
+            .unwrap()
+            .insert(name.to_string(), tile.clone());
+        Ok(tile)
+    }
+
+    pub async fn elevation(&self, c: &Coord) -> Result<Option<f64>> {
+        Ok(match self.tile(&srtm_tile_name(c)).await? {
+            Some(tile) => tile.elevation(c.lat - c.lat.floor(), c.lng - c.lng.floor()),
+            None => None,
+        })
+    }
+}
+
+impl ElevationProvider for SrtmTiles {
+    fn elevations<'a>(&'a self, coords: &'a [Coord]) -> StoreFuture<'a, Vec<Option<f64>>> {
+        Box::pin(async move {
+            let mut r = Vec::with_capacity(coords.len());
+            for c in coords {
+                r.push(self.elevation(c).await?);
+            }
+            Ok(r)
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct ElevationResult {
+    elevation: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct ElevationResponse {
+    results: Vec<ElevationResult>,
+}
+
+// HttpElevationProvider queries an elevation service with the API of open-elevation and
+//  opentopodata, `<base_url>?locations=lat,lng|lat,lng`
+pub struct HttpElevationProvider {
+    base_url: String,
+    client: Box<dyn HttpClient + Send>,
+}
+
+impl HttpElevationProvider {
+    pub fn new(base_url: &str) -> HttpElevationProvider {
+        HttpElevationProvider {
+            base_url: base_url.to_string(),
+            client: Box::new(reqwest::Client::new()),
+        }
+    }
+
+    // with_client queries the service with client instead of reqwest
+    pub fn with_client<C: HttpClient + Send + 'static>(
+        mut self,
+        client: C,
+    ) -> HttpElevationProvider {
+        self.client = Box::new(client);
+        self
+    }
+}
+
+impl ElevationProvider for HttpElevationProvider {
+    fn elevations<'a>(&'a self, coords: &'a [Coord]) -> StoreFuture<'a, Vec<Option<f64>>> {
+        Box::pin(async move {
+            let mut r = Vec::with_capacity(coords.len());
+            for chunk in coords.chunks(MAX_LOCATIONS_PER_REQUEST) {
+                let locations: Vec<String> = chunk
+                    .iter()
+                    .map(|c| format!("{:.6},{:.6}", c.lat, c.lng))
+                    .collect();
+                let url = format!("{}?locations={}", self.base_url, locations.join("|"));
+                let resp = self.client.get(&url, &[]).await?;
+                if !resp.is_success() {
+                    bail!("{} returned {}", self.base_url, resp.status);
+                }
+                let resp: ElevationResponse = serde_json::from_slice(&resp.body)?;
+                if resp.results.len() != chunk.len() {
+                    bail!(
+                        "{} returned {} elevations for {} locations",
+                        self.base_url,
+                        resp.results.len(),
+                        chunk.len()
+                    );
+                }
+                r.extend(resp.results.into_iter().map(|v| v.elevation));
+            }
+            Ok(r)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::replay::{request_key, Cassette, Interaction, ReplayHttp};
+
+    // hgt writes a tile of 3x3 samples
+    fn hgt(samples: [i16; 9]) -> Vec<u8> {
+        samples
+            .iter()
+            .flat_map(|v| v.to_be_bytes().to_vec())
+            .collect()
+    }
+
+    #[test]
+    fn test_sample_path() {
+        let geometry = vec![Coord::new(0.0, 0.0), Coord::new(0.0, 0.01)];
+        let r = sample_path(&geometry, 400.0);
+        let offsets: Vec<f64> = r.iter().map(|v| v.0.round()).collect();
+        assert_eq!(offsets, vec![0.0, 400.0, 800.0, 1112.0]);
+        assert!((r[1].1.lng - 0.0036).abs() < 1e-4);
+        assert_eq!(sample_path(&geometry[..1], 400.0).len(), 1);
+        assert!(sample_path::<Coord>(&[], 400.0).is_empty());
+        // tiny steps are widened
+        let r = sample_path(&geometry, 0.001);
+        assert_eq!(r.len(), MAX_PATH_SAMPLES);
+        assert!((r[1].0 - 1112.0 / (MAX_PATH_SAMPLES - 1) as f64).abs() < 0.01);
+    }
+
+    #[actix_rt::test]
+    async fn test_srtm_tiles() {
+        let root = std::env::temp_dir().join(format!("nbroutes-srtm-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        // rows from north to south
+        std::fs::write(
+            root.join("N01E103.hgt"),
+            hgt([20, 20, 20, 10, 10, 10, 0, 0, SRTM_VOID]),
+        )
+        .unwrap();
+        let tiles = SrtmTiles::new(&root);
+        let coords = vec![
+            Coord::new(1.5, 103.25),
+            Coord::new(1.75, 103.25),
+            Coord::new(1.0, 103.0),
+            Coord::new(1.1, 103.9),
+            Coord::new(2.5, 103.5),
+        ];
+        assert_eq!(
+            tiles.elevations(&coords).await.unwrap(),
+            vec![Some(10.0), Some(15.0), Some(0.0), None, None]
+        );
+
+        // north to south across the tile
+        let geometry = vec![Coord::new(1.99, 103.25), Coord::new(1.01, 103.25)];
+        let r = profile(&tiles, &geometry, 20_000.0).await.unwrap();
+        assert_eq!(r.points.len(), 7);
+        assert_eq!(r.ascent, 0.0);
+        assert!((r.descent - 19.6).abs() < 1e-6);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[actix_rt::test]
+    async fn test_http_elevation_provider() {
+        let url = "https://elevation.example.com/v1/srtm90m";
+        let cassette = Arc::new(Cassette::new(vec![Interaction::new(
+            &request_key("GET", url),
+            200,
+            br#"{"results": [{"elevation": 12.5}, {"elevation": null}], "status": "OK"}"#,
+        )]));
+        let provider = HttpElevationProvider::new(url)
+            .with_client(ReplayHttp::new(reqwest::Client::new(), cassette));
+        let coords = vec![Coord::new(1.3, 103.8), Coord::new(1.31, 103.81)];
+        assert_eq!(
+            provider.elevations(&coords).await.unwrap(),
+            vec![Some(12.5), None]
+        );
+        assert!(provider.elevations(&coords[..1]).await.is_err());
+    }
+}
@@ -0,0 +1,238 @@
+// estimated CO2 emissions of routes and matrix elements, for sustainability reporting. the
+//  emissions are the distance times the emission factor of the mode, configured in g/km. the
+//  factors of trucks grow with the weight of the truck above its empty weight. responses are
+//  only annotated when the request enables `emissions`
+use crate::def::{
+    DirectionsInput, IntValue, MatrixInput, MatrixOutput, RouteT, Row, ValhallaDirectionsInput,
+    ValhallaMatrixInput,
+};
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct EmissionFactor {
+    // grams of CO2 per km of the vehicle at its empty weight
+    pub g_per_km: f64,
+    // extra grams per km for each tonne above empty_weight_kg
+    #[serde(default)]
+    pub g_per_tonne_km: f64,
+    #[serde(default)]
+    pub empty_weight_kg: f64,
+}
+
+impl EmissionFactor {
+    // g_per_km_for returns the factor for a truck weighing weight_kg
+    pub fn g_per_km_for(&self, weight_kg: Option<f64>) -> f64 {
+        let extra_kg = weight_kg
+            .map(|v| (v - self.empty_weight_kg).max(0.0))
+            .unwrap_or_default();
+        self.g_per_km + self.g_per_tonne_km * extra_kg / 1000.0
+    }
+}
+
+// TruckSpec is the truck of a request, weight being in kg including trailers and goods
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TruckSpec {
+    pub weight_kg: Option<f64>,
+}
+
+impl TruckSpec {
+    // from_weight reads the truck_weight of requests, 0 or less being no weight
+    pub fn from_weight(truck_weight: Option<i32>) -> TruckSpec {
+        TruckSpec {
+            weight_kg: truck_weight.filter(|v| *v > 0).map(|v| v as f64),
+        }
+    }
+}
+
+// EmissionsInput is a request that may ask for the emissions of its routes or elements
+pub trait EmissionsInput {
+    // emissions is true when the request enables `emissions`
+    fn emissions(&self) -> bool;
+    fn truck(&self) -> TruckSpec;
+}
+
+macro_rules! impl_emissions_input {
+    ($($input:ty),*) => {
+        $(
+            impl EmissionsInput for $input {
+                fn emissions(&self) -> bool {
+                    self.emissions.unwrap_or(false)
+                }
+
+                fn truck(&self) -> TruckSpec {
+                    TruckSpec::from_weight(self.truck_weight)
+                }
+            }
+        )*
+    };
+}
+
+impl_emissions_input!(
+    ValhallaDirectionsInput,
+    DirectionsInput,
+    ValhallaMatrixInput,
+    MatrixInput
+);
+
+// EmissionFactors holds the factor of each mode, modes without a factor falling back to the
+//  "" entry
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct EmissionFactors {
+    #[serde(flatten)]
+    pub modes: HashMap<String, EmissionFactor>,
+}
+
+impl EmissionFactors {
+    pub fn from_yaml(content: &str) -> Result<EmissionFactors> {
+        Ok(serde_yaml::from_str(content)?)
+    }
+
+    pub fn factor(&self, mode: &str) -> Option<&EmissionFactor> {
+        self.modes.get(mode).or_else(|| self.modes.get(""))
+    }
+
+    // estimate returns the grams of CO2 emitted over distance meters, None when the mode has no
+    //  factor
+    pub fn estimate(&self, mode: &str, distance: f64, truck: Option<&TruckSpec>) -> Option<f64> {
+        let factor = self.factor(mode)?;
+        let weight_kg = truck.and_then(|v| v.weight_kg);
+        Some(factor.g_per_km_for(weight_kg) * distance / 1000.0)
+    }
+
+    pub fn estimate_route<L>(
+        &self,
+        route: &RouteT<L>,
+        mode: &str,
+        truck: Option<&TruckSpec>,
+    ) -> Option<f64> {
+        self.estimate(mode, route.distance, truck)
+    }
+
+    // annotate_routes sets the emissions of routes when input enables them
+    pub fn annotate_routes<L>(
+        &self,
+        routes: &mut [RouteT<L>],
+        mode: &str,
+        input: &dyn EmissionsInput,
+    ) {
+        if !input.emissions() {
+            return;
+        }
+        let truck = input.truck();
+        for route in routes.iter_mut() {
+            route.co2_emissions = self.estimate_route(route, mode, Some(&truck));
+        }
+    }
+
+    // estimate_matrix returns the grams of CO2 of each element of rows, elements that could not
+    //  be routed (negative distance) get None
+    pub fn estimate_matrix(
+        &self,
+        rows: &[Row],
+        mode: &str,
+        truck: Option<&TruckSpec>,
+    ) -> Vec<Vec<Option<f64>>> {
+        rows.iter()
+            .map(|row| {
+                row.elements
+                    .iter()
+                    .map(|e| match e.distance.value {
+                        v if v < 0 => None,
+                        v => self.estimate(mode, v as f64, truck),
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    // annotate_matrix sets the emissions of the elements of output, rounded to grams, when input
+    //  enables them
+    pub fn annotate_matrix(
+        &self,
+        output: &mut MatrixOutput,
+        mode: &str,
+        input: &dyn EmissionsInput,
+    ) {
+        if !input.emissions() {
+            return;
+        }
+        let truck = input.truck();
+        let estimates = self.estimate_matrix(&output.rows, mode, Some(&truck));
+        for (row, estimates) in output.rows.iter_mut().zip(estimates) {
+            for (e, estimate) in row.elements.iter_mut().zip(estimates) {
+                e.co2_emissions = estimate.map(|v| IntValue {
+                    value: v.round() as i64,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coord::Coord;
+    use crate::def::Route;
+    use crate::openapi::ApiExample;
+
+    fn factors() -> EmissionFactors {
+        EmissionFactors::from_yaml(
+            r#"
+"": {g_per_km: 170}
+2w: {g_per_km: 90}
+6w: {g_per_km: 600, g_per_tonne_km: 50, empty_weight_kg: 8000}
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_estimate() {
+        let factors = factors();
+        assert_eq!(factors.estimate("2w", 2000.0, None), Some(180.0));
+        assert_eq!(factors.estimate("4w", 1000.0, None), Some(170.0));
+        assert_eq!(factors.estimate("6w", 1000.0, None), Some(600.0));
+        let truck = TruckSpec::from_weight(Some(12000));
+        assert_eq!(factors.estimate("6w", 1000.0, Some(&truck)), Some(800.0));
+        // lighter than empty
+        let truck = TruckSpec::from_weight(Some(5000));
+        assert_eq!(factors.estimate("6w", 1000.0, Some(&truck)), Some(600.0));
+        assert_eq!(TruckSpec::from_weight(Some(0)).weight_kg, None);
+        assert_eq!(
+            EmissionFactors::default().estimate("4w", 1000.0, None),
+            None
+        );
+
+        let route =
+            Route::straight_line_fallback(&Coord::new(0.0, 0.0), &Coord::new(0.0, 0.1), 10.0, 5);
+        let expected = route.distance * 0.17;
+        let mut routes = vec![route];
+        let mut input = DirectionsInput::example();
+        factors.annotate_routes(&mut routes, "4w", &input);
+        assert_eq!(routes[0].co2_emissions, None);
+        input.emissions = Some(true);
+        factors.annotate_routes(&mut routes, "4w", &input);
+        assert_eq!(routes[0].co2_emissions, Some(expected));
+    }
+
+    #[test]
+    fn test_annotate_matrix() {
+        let mut output = MatrixOutput::example();
+        output.rows[0].elements[0].distance.value = -1;
+        let mut input = MatrixInput::example();
+        input.emissions = Some(true);
+        factors().annotate_matrix(&mut output, "2w", &input);
+        assert!(output.rows[0].elements[0].co2_emissions.is_none());
+        // 2116 m at 90 g/km
+        assert_eq!(
+            output.rows[1].elements[0]
+                .co2_emissions
+                .as_ref()
+                .unwrap()
+                .value,
+            190
+        );
+    }
+}
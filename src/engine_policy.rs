@@ -0,0 +1,153 @@
+// selection of the engine serving a request, configured per area, mode and endpoint instead of
+//  being inferred from the names of services. a rule matches when each of its fields is either
+//  "" or equal to the one of the request, and the rule with the most set fields wins, the first
+//  listed one on ties. the context of the rule names the dataset of the engine to use
+use crate::def::Engine;
+use crate::Result;
+use crate::Service;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EngineRule {
+    #[serde(default)]
+    pub area: String,
+    #[serde(default)]
+    pub mode: String,
+    #[serde(default)]
+    pub endpoint: String,
+    pub engine: Engine,
+    pub context: Option<String>,
+}
+
+impl EngineRule {
+    // specificity is None when the rule does not match, the number of set fields otherwise
+    fn specificity(&self, area: &str, mode: &str, endpoint: &str) -> Option<usize> {
+        let mut r = 0;
+        for (field, value) in [
+            (&self.area, area),
+            (&self.mode, mode),
+            (&self.endpoint, endpoint),
+        ] {
+            if field.is_empty() {
+                continue;
+            }
+            if field != value {
+                return None;
+            }
+            r += 1;
+        }
+        Some(r)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct EngineSelection {
+    pub engine: Engine,
+    pub context: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct EnginePolicy {
+    pub rules: Vec<EngineRule>,
+}
+
+impl EnginePolicy {
+    pub fn from_yaml(content: &str) -> Result<EnginePolicy> {
+        Ok(serde_yaml::from_str(content)?)
+    }
+
+    pub fn with_rule(
+        mut self,
+        area: &str,
+        mode: &str,
+        endpoint: &str,
+        engine: Engine,
+        context: Option<&str>,
+    ) -> Self {
+        self.rules.push(EngineRule {
+            area: area.to_string(),
+            mode: mode.to_string(),
+            endpoint: endpoint.to_string(),
+            engine,
+            context: context.map(|c| c.to_string()),
+        });
+        self
+    }
+
+    pub fn resolve(&self, area: &str, mode: &str, endpoint: &str) -> Option<EngineSelection> {
+        let mut best: Option<(usize, &EngineRule)> = None;
+        for rule in self.rules.iter() {
+            if let Some(s) = rule.specificity(area, mode, endpoint) {
+                if best.map(|(b, _)| s > b).unwrap_or(true) {
+                    best = Some((s, rule));
+                }
+            }
+        }
+        best.map(|(_, rule)| EngineSelection {
+            engine: rule.engine.clone(),
+            context: rule.context.clone(),
+        })
+    }
+
+    // resolve_service resolves the engine of the service found by find_service
+    pub fn resolve_service(&self, service: &Service, endpoint: &str) -> Result<EngineSelection> {
+        match self.resolve(&service.area.name, &service.mode, endpoint) {
+            Some(v) => Ok(v),
+            None => bail!(
+                "no engine configured for area {}, mode {} and endpoint {}",
+                service.area.name,
+                service.mode,
+                endpoint
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        let policy = EnginePolicy::from_yaml(
+            r#"
+rules:
+  - engine: osrm
+  - area: singapore
+    engine: valhalla
+    context: sg-202401
+  - area: singapore
+    mode: 4w
+    endpoint: matrix
+    engine: osrm
+    context: sg-4w
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            policy.resolve("singapore", "4w", "matrix"),
+            Some(EngineSelection {
+                engine: Engine::OSRM,
+                context: Some("sg-4w".to_string()),
+            })
+        );
+        assert_eq!(
+            policy.resolve("singapore", "4w", "directions").unwrap(),
+            EngineSelection {
+                engine: Engine::Valhalla,
+                context: Some("sg-202401".to_string()),
+            }
+        );
+        assert_eq!(
+            policy.resolve("jakarta", "2w", "matrix").unwrap().engine,
+            Engine::OSRM
+        );
+
+        let policy = EnginePolicy::default().with_rule("", "2w", "", Engine::Valhalla, None);
+        assert!(policy.resolve("jakarta", "4w", "matrix").is_none());
+        assert_eq!(
+            policy.resolve("jakarta", "2w", "matrix").unwrap().engine,
+            Engine::Valhalla
+        );
+    }
+}
@@ -0,0 +1,497 @@
+use crate::bearings::{encode_bearings, parse_bearings};
+use crate::circuit_breaker::{CircuitBreakers, CircuitPermit};
+use crate::coord::{Coord, Locatable};
+use crate::def::{MatrixInput, RouteType, ValhallaDirectionsInput};
+use crate::geometry_format::GeometryFormats;
+use crate::util::split_positional_list;
+use crate::Result;
+use chrono::prelude::*;
+use serde_json::{json, Map, Value};
+
+// penalty (in seconds) applied on border crossing when cross_border is disabled
+const VALHALLA_COUNTRY_CROSSING_PENALTY: u64 = 3600;
+
+// OsrmTableRequest is the path and query of an osrm table call, without host
+#[derive(Clone, Debug, PartialEq)]
+pub struct OsrmTableRequest {
+    pub path: String,
+    pub query: Vec<(String, String)>,
+}
+
+impl OsrmTableRequest {
+    pub fn url(&self, host: &str) -> String {
+        let query: Vec<String> = self
+            .query
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect();
+        format!(
+            "{}{}?{}",
+            host.trim_end_matches('/'),
+            self.path,
+            query.join("&")
+        )
+    }
+
+    // checked_url fails fast when the breaker of host is open. the outcome of the call is
+    //  reported with the returned permit
+    pub fn checked_url(
+        &self,
+        host: &str,
+        breakers: &CircuitBreakers,
+    ) -> Result<(String, CircuitPermit)> {
+        let permit = breakers.get(host).acquire()?;
+        Ok((self.url(host), permit))
+    }
+}
+
+// DatasetSelection is the outcome of area/mode/ctx resolution for a single engine call
+#[derive(Clone, Debug)]
+pub struct DatasetSelection {
+    // valhalla costing, e.g. auto, truck, motor_scooter
+    pub costing: String,
+    // timezone offset of the area in hours, used to express date_time in local time
+    pub timezone: f64,
+}
+
+pub fn build_valhalla_route_request(
+    input: &ValhallaDirectionsInput,
+    dataset: &DatasetSelection,
+) -> Result<Value> {
+    let mut coords = vec![Coord::coord(input.origin.as_str())?];
+    if let Some(waypoints) = input.waypoints.as_ref() {
+        if !waypoints.trim().is_empty() {
+            coords.extend(Coord::coords(waypoints.as_str())?);
+        }
+    }
+    coords.push(Coord::coord(input.destination.as_str())?);
+
+    let approaches = valhalla_approaches(input, coords.len())?;
+    let mut locations = vec![];
+    for (idx, coord) in coords.iter().enumerate() {
+        let mut location = json!({
+            "lat": coord.lat(),
+            "lon": coord.lng(),
+            "type": "break",
+        });
+        if let Some(side) = approaches[idx] {
+            location["preferred_side"] = json!(side);
+        }
+        locations.push(location);
+    }
+
+    let mut request = json!({
+        "locations": locations,
+        "costing": dataset.costing.as_str(),
+        "costing_options": {
+            dataset.costing.as_str(): valhalla_costing_options(input)?,
+        },
+        "shape_format": input.output_format()?.valhalla_shape_format(),
+        "directions_type": if input.steps.unwrap_or(false) { "instructions" } else { "none" },
+    });
+
+    if let Some(date_time) = valhalla_date_time(input, dataset.timezone)? {
+        request["date_time"] = date_time;
+    }
+
+    let altcount = valhalla_altcount(input);
+    if altcount > 1 {
+        request["alternates"] = json!(altcount - 1);
+    }
+
+    if let Some(session) = input.session.as_ref() {
+        if !session.is_empty() {
+            request["id"] = json!(session);
+        }
+    }
+
+    Ok(request)
+}
+
+fn valhalla_costing_options(input: &ValhallaDirectionsInput) -> Result<Value> {
+    let mut options = Map::new();
+
+    if let Some(truck_size) = input.truck_size.as_ref() {
+        let items: Vec<&str> = truck_size.split(',').collect();
+        if items.len() != 3 {
+            bail!("truck_size should be in format of height,width,length");
+        }
+        // input is in centimeters while valhalla expects meters
+        let mut dimensions = vec![];
+        for item in items {
+            dimensions.push(item.trim().parse::<f64>()? / 100.0);
+        }
+        options.insert("height".to_string(), json!(dimensions[0]));
+        options.insert("width".to_string(), json!(dimensions[1]));
+        options.insert("length".to_string(), json!(dimensions[2]));
+    }
+    if let Some(truck_weight) = input.truck_weight {
+        // input is in kilograms while valhalla expects metric tons
+        options.insert("weight".to_string(), json!(truck_weight as f64 / 1000.0));
+    }
+    if let Some(axle_count) = input.truck_axle_count {
+        options.insert("axle_count".to_string(), json!(axle_count));
+    }
+    if let Some(axle_load) = input.truck_axle_load {
+        options.insert("axle_load".to_string(), json!(axle_load));
+    }
+    if let Some(hazmat_type) = input.hazmat_type.as_ref() {
+        if !hazmat_type.is_empty() {
+            options.insert("hazmat".to_string(), json!(true));
+        }
+    }
+    if input.route_type == Some(RouteType::Shortest) {
+        options.insert("shortest".to_string(), json!(true));
+    }
+    if input.cross_border == Some(false) {
+        options.insert(
+            "country_crossing_penalty".to_string(),
+            json!(VALHALLA_COUNTRY_CROSSING_PENALTY),
+        );
+    }
+
+    if let Some(avoid) = input.avoid.as_ref() {
+        for item in avoid.split('|') {
+            match item.trim() {
+                "" | "none" => {}
+                "toll" => {
+                    options.insert("use_tolls".to_string(), json!(0.0));
+                }
+                "highway" => {
+                    options.insert("use_highways".to_string(), json!(0.0));
+                }
+                "ferry" => {
+                    options.insert("use_ferry".to_string(), json!(0.0));
+                }
+                v => {
+                    warn!(
+                        "build_valhalla_route_request ignores unsupported avoid value: {}",
+                        v
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(Value::Object(options))
+}
+
+fn valhalla_approaches(
+    input: &ValhallaDirectionsInput,
+    count: usize,
+) -> Result<Vec<Option<&'static str>>> {
+    let mut r = vec![None; count];
+    let approaches = match input.approaches.as_ref() {
+        Some(v) if !v.is_empty() => v,
+        _ => return Ok(r),
+    };
+    let items = split_positional_list(approaches, '|');
+    if items.len() != count {
+        bail!(
+            "approaches count {} does not match coordinates count {}",
+            items.len(),
+            count
+        );
+    }
+    for (idx, item) in items.iter().enumerate() {
+        r[idx] = match *item {
+            "" => None,
+            "curb" => Some("same"),
+            "unrestricted" => Some("either"),
+            v => bail!("invalid approach: {}", v),
+        };
+    }
+    Ok(r)
+}
+
+fn valhalla_altcount(input: &ValhallaDirectionsInput) -> i32 {
+    if !input.alternatives.unwrap_or(false) {
+        return 1;
+    }
+    input.altcount.unwrap_or(3)
+}
+
+fn valhalla_date_time(input: &ValhallaDirectionsInput, timezone: f64) -> Result<Option<Value>> {
+    let departure_time = input.departure_time.unwrap_or(0);
+    let arrive_time = input.arrive_time.unwrap_or(0);
+    if departure_time > 0 && arrive_time > 0 {
+        bail!("departure_time conflicts with arrive_time");
+    }
+    // date_time type 1 is depart at, type 2 is arrive by
+    let (date_time_type, ts) = match (departure_time, arrive_time) {
+        (0, 0) => return Ok(None),
+        (0, ts) => (2, ts),
+        (ts, _) => (1, ts),
+    };
+
+    let time_zone = match FixedOffset::east_opt((timezone * 3600.0) as i32) {
+        Some(v) => v,
+        None => bail!("invalid timezone: {}", timezone),
+    };
+    let local_time = match DateTime::from_timestamp(ts, 0) {
+        Some(v) => v.with_timezone(&time_zone),
+        None => bail!("invalid timestamp: {}", ts),
+    };
+
+    Ok(Some(json!({
+        "type": date_time_type,
+        "value": local_time.format("%Y-%m-%dT%H:%M").to_string(),
+    })))
+}
+
+pub fn build_osrm_table_request(input: &MatrixInput) -> Result<OsrmTableRequest> {
+    let origins = Coord::coords(input.origins.as_str())?;
+    let destinations = Coord::coords(input.destinations.as_str())?;
+    let total = origins.len() + destinations.len();
+
+    // osrm expects lng,lat pairs separated by ;
+    let coords: Vec<String> = origins
+        .iter()
+        .chain(destinations.iter())
+        .map(|c| format!("{},{}", c.lng(), c.lat()))
+        .collect();
+    let path = format!("/table/v1/driving/{}", coords.join(";"));
+
+    let sources: Vec<String> = (0..origins.len()).map(|i| i.to_string()).collect();
+    let targets: Vec<String> = (origins.len()..total).map(|i| i.to_string()).collect();
+    let mut query = vec![
+        ("sources".to_string(), sources.join(";")),
+        ("destinations".to_string(), targets.join(";")),
+        ("annotations".to_string(), "duration,distance".to_string()),
+    ];
+
+    if let Some(approaches) = osrm_approaches(input, origins.len(), destinations.len())? {
+        query.push(("approaches".to_string(), approaches.join(";")));
+    }
+
+    if let Some(bearings) = input.bearings.as_ref() {
+        if !bearings.is_empty() {
+            let bearings = parse_bearings(bearings, total)?;
+            query.push(("bearings".to_string(), encode_bearings(&bearings)));
+        }
+    }
+
+    if let Some(avoid) = input.avoid.as_ref() {
+        let excludes: Vec<&str> = avoid
+            .split('|')
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty() && *v != "none")
+            .collect();
+        if !excludes.is_empty() {
+            query.push(("exclude".to_string(), excludes.join(",")));
+        }
+    }
+
+    Ok(OsrmTableRequest { path, query })
+}
+
+// approaches precedence: `approaches` covers all coordinates; otherwise origins use
+//  `origin_approaches` or `origins_approach` and destinations use `destinations_approach`
+fn osrm_approaches(
+    input: &MatrixInput,
+    n_origins: usize,
+    n_destinations: usize,
+) -> Result<Option<Vec<String>>> {
+    if let Some(approaches) = input.approaches.as_ref() {
+        if !approaches.is_empty() {
+            let items = parse_osrm_approaches(approaches)?;
+            if items.len() != n_origins + n_destinations {
+                bail!(
+                    "approaches count {} does not match coordinates count {}",
+                    items.len(),
+                    n_origins + n_destinations
+                );
+            }
+            return Ok(Some(items));
+        }
+    }
+
+    let mut origin_items = vec!["".to_string(); n_origins];
+    let mut destination_items = vec!["".to_string(); n_destinations];
+    let mut found = false;
+    if let Some(approaches) = input.origin_approaches.as_ref() {
+        if !approaches.is_empty() {
+            origin_items = parse_osrm_approaches(approaches)?;
+            if origin_items.len() != n_origins {
+                bail!(
+                    "origin_approaches count {} does not match origins count {}",
+                    origin_items.len(),
+                    n_origins
+                );
+            }
+            found = true;
+        }
+    } else if let Some(approach) = input.origins_approach.as_ref() {
+        origin_items = vec![parse_osrm_approach(approach)?; n_origins];
+        found = true;
+    }
+    if let Some(approach) = input.destinations_approach.as_ref() {
+        destination_items = vec![parse_osrm_approach(approach)?; n_destinations];
+        found = true;
+    }
+
+    if !found {
+        return Ok(None);
+    }
+    origin_items.extend(destination_items);
+    Ok(Some(origin_items))
+}
+
+fn parse_osrm_approaches(input: &str) -> Result<Vec<String>> {
+    split_positional_list(input, '|')
+        .into_iter()
+        .map(parse_osrm_approach)
+        .collect()
+}
+
+fn parse_osrm_approach(input: &str) -> Result<String> {
+    match input.trim() {
+        "" => Ok("".to_string()),
+        "curb" => Ok("curb".to_string()),
+        "unrestricted" => Ok("unrestricted".to_string()),
+        v => bail!("invalid approach: {}", v),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(v: Value) -> ValhallaDirectionsInput {
+        serde_json::from_value(v).unwrap()
+    }
+
+    #[test]
+    fn test_build_valhalla_route_request() {
+        let dataset = DatasetSelection {
+            costing: "truck".to_string(),
+            timezone: 8.0,
+        };
+        let r = build_valhalla_route_request(
+            &input(json!({
+                "origin": "1.3,103.8",
+                "destination": "1.4,103.9",
+                "waypoints": "1.35,103.85",
+                "departure_time": 1600000000,
+                "truck_size": "400,250,1200",
+                "truck_weight": 12000,
+                "avoid": "toll|ferry",
+                "route_type": "shortest",
+                "approaches": "curb||unrestricted",
+                "alternatives": true,
+                "geometry": "polyline6",
+            })),
+            &dataset,
+        )
+        .unwrap();
+
+        assert_eq!(r["costing"], "truck");
+        assert_eq!(r["locations"].as_array().unwrap().len(), 3);
+        assert_eq!(r["locations"][0]["lat"], 1.3);
+        assert_eq!(r["locations"][0]["lon"], 103.8);
+        assert_eq!(r["locations"][0]["preferred_side"], "same");
+        assert!(r["locations"][1].get("preferred_side").is_none());
+        assert_eq!(r["locations"][2]["preferred_side"], "either");
+        assert_eq!(r["date_time"]["type"], 1);
+        assert_eq!(r["date_time"]["value"], "2020-09-13T20:26");
+        assert_eq!(r["alternates"], 2);
+        assert_eq!(r["shape_format"], "polyline6");
+
+        let options = &r["costing_options"]["truck"];
+        assert_eq!(options["height"], 4.0);
+        assert_eq!(options["width"], 2.5);
+        assert_eq!(options["length"], 12.0);
+        assert_eq!(options["weight"], 12.0);
+        assert_eq!(options["use_tolls"], 0.0);
+        assert_eq!(options["use_ferry"], 0.0);
+        assert_eq!(options["shortest"], true);
+    }
+
+    #[test]
+    fn test_build_valhalla_route_request_invalid() {
+        let dataset = DatasetSelection {
+            costing: "auto".to_string(),
+            timezone: 0.0,
+        };
+        assert!(build_valhalla_route_request(
+            &input(json!({
+                "origin": "1.3,103.8",
+                "destination": "1.4,103.9",
+                "departure_time": 1600000000,
+                "arrive_time": 1600000000,
+            })),
+            &dataset,
+        )
+        .is_err());
+        assert!(build_valhalla_route_request(
+            &input(json!({
+                "origin": "1.3,103.8",
+                "destination": "1.4,103.9",
+                "approaches": "curb",
+            })),
+            &dataset,
+        )
+        .is_err());
+    }
+
+    fn matrix_input(v: Value) -> MatrixInput {
+        serde_json::from_value(v).unwrap()
+    }
+
+    #[test]
+    fn test_build_osrm_table_request() {
+        let r = build_osrm_table_request(&matrix_input(json!({
+            "origins": "1.3,103.8|1.31,103.81",
+            "destinations": "1.4,103.9",
+        })))
+        .unwrap();
+        assert_eq!(
+            r.url("http://osrm:5000/"),
+            "http://osrm:5000/table/v1/driving/103.8,1.3;103.81,1.31;103.9,1.4?sources=0;1&destinations=2&annotations=duration,distance"
+        );
+
+        let r = build_osrm_table_request(&matrix_input(json!({
+            "origins": "1.3,103.8|1.31,103.81",
+            "destinations": "1.4,103.9",
+            "approaches": "curb||unrestricted",
+            "bearings": "0,20;;90,45",
+            "avoid": "toll|ferry",
+        })))
+        .unwrap();
+        assert_eq!(
+            r.url("http://osrm:5000"),
+            "http://osrm:5000/table/v1/driving/103.8,1.3;103.81,1.31;103.9,1.4?sources=0;1&destinations=2&annotations=duration,distance&approaches=curb;;unrestricted&bearings=0,20;;90,45&exclude=toll,ferry"
+        );
+
+        let r = build_osrm_table_request(&matrix_input(json!({
+            "origins": "1.3,103.8|1.31,103.81",
+            "destinations": "1.4,103.9|1.41,103.91",
+            "origins_approach": "curb",
+            "destinations_approach": "unrestricted",
+        })))
+        .unwrap();
+        assert_eq!(
+            r.query[3],
+            (
+                "approaches".to_string(),
+                "curb;curb;unrestricted;unrestricted".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_build_osrm_table_request_invalid() {
+        assert!(build_osrm_table_request(&matrix_input(json!({
+            "origins": "1.3,103.8",
+            "destinations": "1.4,103.9",
+            "bearings": "0,20",
+        })))
+        .is_err());
+        assert!(build_osrm_table_request(&matrix_input(json!({
+            "origins": "1.3,103.8",
+            "destinations": "1.4,103.9",
+            "origin_approaches": "curb|curb",
+        })))
+        .is_err());
+    }
+}
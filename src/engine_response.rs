@@ -0,0 +1,1061 @@
+use crate::coord::{Coord, Locatable};
+use crate::def::{
+    Annotation, Coordinate, Element, IntValue, Intersection, Lane, Leg, Location, Maneuver,
+    MatrixOutput, MetaData, Route, Row, Step, ValhallaAnnotation, ValhallaLeg, ValhallaRoute,
+    Waypoint, STATUS_OK,
+};
+use crate::polyline;
+use crate::warnings::{Warning, Warnings};
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+const METERS_PER_KILOMETER: f64 = 1000.0;
+const METERS_PER_MILE: f64 = 1609.344;
+
+// duration/distance value of a matrix element that has no route
+pub const MATRIX_UNREACHABLE_VALUE: i64 = -1;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ValhallaRawResponse {
+    pub trip: ValhallaRawTrip,
+    pub alternates: Option<Vec<ValhallaRawAlternate>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ValhallaRawAlternate {
+    pub trip: ValhallaRawTrip,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ValhallaRawTrip {
+    pub locations: Option<Vec<ValhallaRawLocation>>,
+    pub legs: Vec<ValhallaRawLeg>,
+    pub summary: ValhallaRawSummary,
+    pub units: Option<String>,
+    pub status: Option<i64>,
+    pub status_message: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ValhallaRawLocation {
+    pub lat: f64,
+    pub lon: f64,
+    #[serde(rename = "type")]
+    pub location_type: Option<String>,
+    pub original_index: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ValhallaRawSummary {
+    // in `units` of the trip
+    pub length: f64,
+    // in seconds
+    pub time: f64,
+    pub cost: Option<f64>,
+    pub has_toll: Option<bool>,
+    pub has_highway: Option<bool>,
+    pub has_ferry: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ValhallaRawLeg {
+    pub maneuvers: Option<Vec<ValhallaRawManeuver>>,
+    pub summary: ValhallaRawSummary,
+    // always encoded in polyline6
+    pub shape: String,
+    pub shape_attributes: Option<ValhallaRawShapeAttributes>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ValhallaRawShapeAttributes {
+    // per segment duration in seconds
+    pub time: Option<Vec<f64>>,
+    // per segment length in `units` of the trip
+    pub length: Option<Vec<f64>>,
+    // per segment speed in `units` per hour
+    pub speed: Option<Vec<f64>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ValhallaRawManeuver {
+    #[serde(rename = "type")]
+    pub maneuver_type: i32,
+    pub instruction: Option<String>,
+    pub street_names: Option<Vec<String>>,
+    pub time: f64,
+    pub length: f64,
+    pub cost: Option<f64>,
+    pub begin_shape_index: usize,
+    pub end_shape_index: usize,
+    pub bearing_before: Option<i32>,
+    pub bearing_after: Option<i32>,
+    pub roundabout_exit_count: Option<i32>,
+    pub travel_mode: Option<String>,
+    pub toll: Option<bool>,
+}
+
+// RawStr is a string of a response body, borrowed from the body unless it has escapes
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct RawStr<'a>(#[serde(borrow)] pub Cow<'a, str>);
+
+impl<'a> RawStr<'a> {
+    pub fn as_str(&self) -> &str {
+        self.0.as_ref()
+    }
+}
+
+// the *Ref versions of the raw responses borrow their strings from the response body, fields
+//  unused by the conversions are skipped. the owned versions convert through them
+#[derive(Deserialize, Debug)]
+pub struct ValhallaRawResponseRef<'a> {
+    #[serde(borrow)]
+    pub trip: ValhallaRawTripRef<'a>,
+    #[serde(borrow)]
+    pub alternates: Option<Vec<ValhallaRawAlternateRef<'a>>>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ValhallaRawAlternateRef<'a> {
+    #[serde(borrow)]
+    pub trip: ValhallaRawTripRef<'a>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ValhallaRawTripRef<'a> {
+    #[serde(borrow)]
+    pub legs: Vec<ValhallaRawLegRef<'a>>,
+    pub summary: ValhallaRawSummary,
+    #[serde(borrow)]
+    pub units: Option<RawStr<'a>>,
+    pub status: Option<i64>,
+    #[serde(borrow)]
+    pub status_message: Option<RawStr<'a>>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ValhallaRawLegRef<'a> {
+    #[serde(borrow)]
+    pub maneuvers: Option<Vec<ValhallaRawManeuverRef<'a>>>,
+    pub summary: ValhallaRawSummary,
+    #[serde(borrow)]
+    pub shape: Cow<'a, str>,
+    pub shape_attributes: Option<ValhallaRawShapeAttributes>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ValhallaRawManeuverRef<'a> {
+    #[serde(rename = "type")]
+    pub maneuver_type: i32,
+    #[serde(borrow)]
+    pub instruction: Option<RawStr<'a>>,
+    #[serde(borrow)]
+    pub street_names: Option<Vec<RawStr<'a>>>,
+    pub time: f64,
+    pub length: f64,
+    pub begin_shape_index: usize,
+    pub end_shape_index: usize,
+    pub bearing_before: Option<i32>,
+    pub bearing_after: Option<i32>,
+    pub roundabout_exit_count: Option<i32>,
+    #[serde(borrow)]
+    pub travel_mode: Option<RawStr<'a>>,
+}
+
+fn owned_str(v: Option<String>) -> Option<RawStr<'static>> {
+    v.map(|v| RawStr(Cow::Owned(v)))
+}
+
+// maneuver type and modifier in the osrm style used by our Maneuver
+fn maneuver_type_and_modifier(maneuver_type: i32) -> (&'static str, Option<&'static str>) {
+    match maneuver_type {
+        1 => ("depart", None),
+        2 => ("depart", Some("right")),
+        3 => ("depart", Some("left")),
+        4 => ("arrive", None),
+        5 => ("arrive", Some("right")),
+        6 => ("arrive", Some("left")),
+        7 => ("new name", Some("straight")),
+        8 => ("continue", Some("straight")),
+        9 => ("turn", Some("slight right")),
+        10 => ("turn", Some("right")),
+        11 => ("turn", Some("sharp right")),
+        12 | 13 => ("turn", Some("uturn")),
+        14 => ("turn", Some("sharp left")),
+        15 => ("turn", Some("left")),
+        16 => ("turn", Some("slight left")),
+        17 => ("on ramp", Some("straight")),
+        18 => ("on ramp", Some("right")),
+        19 => ("on ramp", Some("left")),
+        20 => ("off ramp", Some("right")),
+        21 => ("off ramp", Some("left")),
+        22 => ("fork", Some("straight")),
+        23 => ("fork", Some("right")),
+        24 => ("fork", Some("left")),
+        25 => ("merge", Some("straight")),
+        26 => ("roundabout", None),
+        27 => ("exit roundabout", None),
+        28 | 29 => ("notification", None),
+        _ => ("continue", None),
+    }
+}
+
+fn unit_in_meters(units: Option<&str>) -> f64 {
+    match units {
+        Some("miles") | Some("mi") => METERS_PER_MILE,
+        _ => METERS_PER_KILOMETER,
+    }
+}
+
+fn to_location(coord: &Coord) -> Location {
+    Location {
+        latitude: coord.lat(),
+        longitude: coord.lng(),
+    }
+}
+
+fn to_coordinate(coord: &Coord) -> Coordinate {
+    Coordinate {
+        latitude: coord.lat(),
+        longitude: coord.lng(),
+        name: None,
+    }
+}
+
+impl ValhallaRawResponse {
+    // converts the main trip and all alternates into routes, with geometries encoded in `precision`
+    pub fn into_routes(self, precision: u32) -> Result<Vec<ValhallaRoute>> {
+        let mut routes = vec![self.trip.into_route(precision)?];
+        for alternate in self.alternates.unwrap_or_default() {
+            routes.push(alternate.trip.into_route(precision)?);
+        }
+        Ok(routes)
+    }
+}
+
+impl ValhallaRawTrip {
+    pub fn into_route(self, precision: u32) -> Result<ValhallaRoute> {
+        self.into_ref().into_route(precision)
+    }
+
+    fn into_ref(self) -> ValhallaRawTripRef<'static> {
+        ValhallaRawTripRef {
+            legs: self.legs.into_iter().map(|l| l.into_ref()).collect(),
+            summary: self.summary,
+            units: owned_str(self.units),
+            status: self.status,
+            status_message: owned_str(self.status_message),
+        }
+    }
+}
+
+impl ValhallaRawLeg {
+    fn into_ref(self) -> ValhallaRawLegRef<'static> {
+        ValhallaRawLegRef {
+            maneuvers: self
+                .maneuvers
+                .map(|v| v.into_iter().map(|m| m.into_ref()).collect()),
+            summary: self.summary,
+            shape: Cow::Owned(self.shape),
+            shape_attributes: self.shape_attributes,
+        }
+    }
+}
+
+impl ValhallaRawManeuver {
+    fn into_ref(self) -> ValhallaRawManeuverRef<'static> {
+        ValhallaRawManeuverRef {
+            maneuver_type: self.maneuver_type,
+            instruction: owned_str(self.instruction),
+            street_names: self
+                .street_names
+                .map(|v| v.into_iter().map(|n| RawStr(Cow::Owned(n))).collect()),
+            time: self.time,
+            length: self.length,
+            begin_shape_index: self.begin_shape_index,
+            end_shape_index: self.end_shape_index,
+            bearing_before: self.bearing_before,
+            bearing_after: self.bearing_after,
+            roundabout_exit_count: self.roundabout_exit_count,
+            travel_mode: owned_str(self.travel_mode),
+        }
+    }
+}
+
+impl<'a> ValhallaRawResponseRef<'a> {
+    pub fn into_routes(self, precision: u32) -> Result<Vec<ValhallaRoute>> {
+        let mut routes = vec![self.trip.into_route(precision)?];
+        for alternate in self.alternates.unwrap_or_default() {
+            routes.push(alternate.trip.into_route(precision)?);
+        }
+        Ok(routes)
+    }
+}
+
+impl<'a> ValhallaRawTripRef<'a> {
+    pub fn into_route(self, precision: u32) -> Result<ValhallaRoute> {
+        if let Some(status) = self.status {
+            if status != 0 {
+                bail!(
+                    "valhalla trip failed with status {}: {}",
+                    status,
+                    self.status_message
+                        .as_ref()
+                        .map(|v| v.as_str())
+                        .unwrap_or_default()
+                );
+            }
+        }
+        let unit = unit_in_meters(self.units.as_ref().map(|v| v.as_str()));
+
+        let mut route_shape: Vec<Coord> = vec![];
+        let mut legs = vec![];
+        for leg in self.legs.iter() {
+            let shape = polyline::decode(leg.shape.as_ref(), polyline::PRECISION_POLYLINE6)?;
+            legs.push(leg.to_leg(&shape, unit, precision)?);
+            // consecutive legs share the waypoint coordinate
+            let skip = if route_shape.is_empty() { 0 } else { 1 };
+            route_shape.extend(shape.into_iter().skip(skip));
+        }
+
+        Ok(ValhallaRoute {
+            geometry: Some(polyline::encode(&route_shape, precision)),
+            geometry_full: None,
+            distance: self.summary.length * unit,
+            distance_full: None,
+            duration: self.summary.time,
+            weight: self.summary.cost,
+            start_location: route_shape.first().map(to_location),
+            end_location: route_shape.last().map(to_location),
+            legs: Some(legs),
+            raw_duration: None,
+            predicted_duration: None,
+            geojson: None,
+            confidence: None,
+            road_info: None,
+            debug_info: None,
+            geofence_crossings: None,
+            toll_cost: None,
+            co2_emissions: None,
+            elevation: None,
+        })
+    }
+}
+
+impl<'a> ValhallaRawLegRef<'a> {
+    fn to_leg(&self, shape: &[Coord], unit: f64, precision: u32) -> Result<ValhallaLeg> {
+        let mut steps = None;
+        if let Some(maneuvers) = self.maneuvers.as_ref() {
+            let mut r = vec![];
+            for maneuver in maneuvers.iter() {
+                r.push(maneuver.to_step(shape, unit, precision)?);
+            }
+            steps = Some(r);
+        }
+
+        Ok(ValhallaLeg {
+            distance: IntValue {
+                value: (self.summary.length * unit).round() as i64,
+            },
+            duration: IntValue {
+                value: self.summary.time.round() as i64,
+            },
+            raw_duration: None,
+            start_location: shape.first().map(to_location),
+            end_location: shape.last().map(to_location),
+            steps,
+            annotation: self
+                .shape_attributes
+                .as_ref()
+                .map(|attributes| attributes.to_annotation(shape, unit)),
+        })
+    }
+}
+
+impl ValhallaRawShapeAttributes {
+    fn to_annotation(&self, shape: &[Coord], unit: f64) -> ValhallaAnnotation {
+        ValhallaAnnotation {
+            seg_info: vec![],
+            node_info: vec![],
+            duration: self.time.clone().unwrap_or_default(),
+            distance: self
+                .length
+                .as_ref()
+                .map(|v| v.iter().map(|l| l * unit).collect())
+                .unwrap_or_default(),
+            // in lng, lat order
+            node: shape.iter().map(|c| vec![c.lng(), c.lat()]).collect(),
+            // in meters per second
+            speed: self
+                .speed
+                .as_ref()
+                .map(|v| v.iter().map(|s| s * unit / 3600.0).collect())
+                .unwrap_or_default(),
+            metadata: vec![],
+            datasources: vec![],
+        }
+    }
+}
+
+impl<'a> ValhallaRawManeuverRef<'a> {
+    fn to_step(&self, shape: &[Coord], unit: f64, precision: u32) -> Result<Step> {
+        if self.begin_shape_index > self.end_shape_index || self.end_shape_index >= shape.len() {
+            bail!(
+                "invalid maneuver shape index [{}, {}] for shape of {} points",
+                self.begin_shape_index,
+                self.end_shape_index,
+                shape.len()
+            );
+        }
+        let step_shape = &shape[self.begin_shape_index..=self.end_shape_index];
+        let start = &step_shape[0];
+        let end = &step_shape[step_shape.len() - 1];
+        let (maneuver_type, modifier) = maneuver_type_and_modifier(self.maneuver_type);
+        let name = self
+            .street_names
+            .as_ref()
+            .filter(|v| !v.is_empty())
+            .map(|v| v.iter().map(|n| n.as_str()).collect::<Vec<_>>().join("/"));
+
+        Ok(Step {
+            geometry: Some(polyline::encode(step_shape, precision)),
+            start_location: to_location(start),
+            end_location: to_location(end),
+            distance: IntValue {
+                value: (self.length * unit).round() as i64,
+            },
+            duration: IntValue {
+                value: self.time.round() as i64,
+            },
+            maneuver: Some(Maneuver {
+                instruction: self.instruction.as_ref().map(|v| v.as_str().to_string()),
+                voice_instruction: vec![],
+                bearing_before: self.bearing_before.unwrap_or(0),
+                bearing_after: self.bearing_after.unwrap_or(0),
+                coordinate: to_coordinate(start),
+                maneuver_type: maneuver_type.to_string(),
+                modifier: modifier.map(|v| v.to_string()),
+                muted: None,
+                roundabout_count: self.roundabout_exit_count,
+            }),
+            name,
+            intersections: None,
+            geojson: None,
+            reference: None,
+            ffs: None,
+            metadata: None,
+            pronunciation: None,
+            destinations: None,
+            exits: None,
+            mode: self.travel_mode.as_ref().map(|v| v.as_str().to_string()),
+            rotary_name: None,
+            rotary_pronunciation: None,
+            driving_side: None,
+        })
+    }
+}
+
+pub fn parse_valhalla_route_response(body: &str, precision: u32) -> Result<Vec<ValhallaRoute>> {
+    let raw: ValhallaRawResponseRef = serde_json::from_str(body)?;
+    raw.into_routes(precision)
+}
+
+// parse_valhalla_route_response_owned goes through the owned raw response, which allocates
+//  every string of the body
+pub fn parse_valhalla_route_response_owned(
+    body: &str,
+    precision: u32,
+) -> Result<Vec<ValhallaRoute>> {
+    let raw: ValhallaRawResponse = serde_json::from_str(body)?;
+    raw.into_routes(precision)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OsrmRawRouteResponse {
+    pub code: String,
+    pub message: Option<String>,
+    pub routes: Option<Vec<OsrmRawRoute>>,
+    pub waypoints: Option<Vec<OsrmRawWaypoint>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OsrmRawWaypoint {
+    pub name: Option<String>,
+    // in lng, lat order
+    pub location: Vec<f64>,
+    // distance from the input coordinate to the snapped location in meters
+    pub distance: Option<f64>,
+    pub hint: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OsrmRawRoute {
+    // polyline string, or a geojson object when geometries=geojson
+    pub geometry: Option<serde_json::Value>,
+    pub distance: f64,
+    pub duration: f64,
+    pub weight: Option<f64>,
+    pub weight_name: Option<String>,
+    pub legs: Vec<OsrmRawLeg>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OsrmRawLeg {
+    pub distance: f64,
+    pub duration: f64,
+    pub weight: Option<f64>,
+    pub summary: Option<String>,
+    pub steps: Option<Vec<OsrmRawStep>>,
+    pub annotation: Option<OsrmRawAnnotation>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OsrmRawAnnotation {
+    pub duration: Option<Vec<f64>>,
+    pub distance: Option<Vec<f64>>,
+    pub speed: Option<Vec<f64>>,
+    pub weight: Option<Vec<f64>>,
+    pub nodes: Option<Vec<i64>>,
+    pub datasources: Option<Vec<i32>>,
+    pub metadata: Option<MetaData>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OsrmRawStep {
+    pub distance: f64,
+    pub duration: f64,
+    pub weight: Option<f64>,
+    pub geometry: Option<serde_json::Value>,
+    pub name: Option<String>,
+    #[serde(rename = "ref")]
+    pub reference: Option<String>,
+    pub pronunciation: Option<String>,
+    pub destinations: Option<String>,
+    pub exits: Option<String>,
+    pub mode: Option<String>,
+    pub maneuver: OsrmRawManeuver,
+    pub intersections: Option<Vec<OsrmRawIntersection>>,
+    pub rotary_name: Option<String>,
+    pub rotary_pronunciation: Option<String>,
+    pub driving_side: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OsrmRawManeuver {
+    // in lng, lat order
+    pub location: Vec<f64>,
+    pub bearing_before: Option<i32>,
+    pub bearing_after: Option<i32>,
+    #[serde(rename = "type")]
+    pub maneuver_type: String,
+    pub modifier: Option<String>,
+    pub exit: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OsrmRawIntersection {
+    // in lng, lat order
+    pub location: Vec<f64>,
+    pub bearings: Vec<i32>,
+    pub classes: Option<Vec<String>>,
+    pub entry: Vec<bool>,
+    #[serde(rename = "in")]
+    pub intersection_in: Option<i32>,
+    #[serde(rename = "out")]
+    pub intersection_out: Option<i32>,
+    pub lanes: Option<Vec<Lane>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OsrmRawTableResponse {
+    pub code: String,
+    pub message: Option<String>,
+    // null cells are unreachable pairs
+    pub durations: Option<Vec<Vec<Option<f64>>>>,
+    pub distances: Option<Vec<Vec<Option<f64>>>>,
+    pub sources: Option<Vec<OsrmRawWaypoint>>,
+    pub destinations: Option<Vec<OsrmRawWaypoint>>,
+}
+
+fn lng_lat_to_location(v: &[f64]) -> Result<Location> {
+    if v.len() != 2 {
+        bail!("invalid osrm location {:?}", v);
+    }
+    Ok(Location {
+        latitude: v[1],
+        longitude: v[0],
+    })
+}
+
+impl OsrmRawWaypoint {
+    pub fn to_waypoint(&self) -> Result<Waypoint> {
+        Ok(Waypoint {
+            location: lng_lat_to_location(&self.location)?,
+            name: self.name.clone().filter(|v| !v.is_empty()),
+            distance: self.distance,
+            hint: self.hint.clone(),
+        })
+    }
+}
+
+fn lng_lat_to_coordinate(v: &[f64]) -> Result<Coordinate> {
+    let location = lng_lat_to_location(v)?;
+    Ok(Coordinate {
+        latitude: location.latitude,
+        longitude: location.longitude,
+        name: None,
+    })
+}
+
+fn osrm_geometry(geometry: &Option<serde_json::Value>) -> Option<String> {
+    match geometry {
+        Some(serde_json::Value::String(v)) => Some(v.clone()),
+        Some(v) => Some(v.to_string()),
+        None => None,
+    }
+}
+
+fn check_osrm_code(code: &str, message: &Option<String>) -> Result<()> {
+    if code != "Ok" {
+        bail!(
+            "osrm request failed with code {}: {}",
+            code,
+            message.as_deref().unwrap_or("")
+        );
+    }
+    Ok(())
+}
+
+// OsrmRoutes are the routes of an osrm route response and the waypoints they go through
+#[derive(Debug)]
+pub struct OsrmRoutes {
+    pub routes: Vec<Route>,
+    pub waypoints: Vec<Waypoint>,
+}
+
+impl OsrmRawRouteResponse {
+    // converts osrm routes into our routes; leg start/end locations are the snapped waypoints,
+    //  which keep their snapping info
+    pub fn into_routes(self) -> Result<OsrmRoutes> {
+        check_osrm_code(self.code.as_str(), &self.message)?;
+        let mut snapped = vec![];
+        for waypoint in self.waypoints.unwrap_or_default().iter() {
+            snapped.push(waypoint.to_waypoint()?);
+        }
+        let waypoints: Vec<Location> = snapped.iter().map(|w| w.location.clone()).collect();
+
+        let mut routes = vec![];
+        for route in self.routes.unwrap_or_default() {
+            let mut legs = vec![];
+            for (idx, leg) in route.legs.iter().enumerate() {
+                legs.push(leg.to_leg(waypoints.get(idx), waypoints.get(idx + 1))?);
+            }
+            routes.push(Route {
+                geometry: osrm_geometry(&route.geometry),
+                geometry_full: None,
+                distance: route.distance,
+                distance_full: None,
+                duration: route.duration,
+                weight: route.weight,
+                start_location: waypoints.first().cloned(),
+                end_location: waypoints.last().cloned(),
+                legs: Some(legs),
+                raw_duration: None,
+                predicted_duration: None,
+                geojson: None,
+                confidence: None,
+                road_info: None,
+                debug_info: None,
+                geofence_crossings: None,
+                toll_cost: None,
+                co2_emissions: None,
+                elevation: None,
+            });
+        }
+        Ok(OsrmRoutes {
+            routes,
+            waypoints: snapped,
+        })
+    }
+}
+
+impl OsrmRawLeg {
+    fn to_leg(&self, start: Option<&Location>, end: Option<&Location>) -> Result<Leg> {
+        let mut steps = None;
+        if let Some(raw_steps) = self.steps.as_ref() {
+            if !raw_steps.is_empty() {
+                let mut r = vec![];
+                for (idx, step) in raw_steps.iter().enumerate() {
+                    // a step ends where the next maneuver starts
+                    let next = raw_steps.get(idx + 1).map(|s| &s.maneuver.location);
+                    r.push(step.to_step(next)?);
+                }
+                steps = Some(r);
+            }
+        }
+
+        Ok(Leg {
+            distance: IntValue {
+                value: self.distance.round() as i64,
+            },
+            duration: IntValue {
+                value: self.duration.round() as i64,
+            },
+            raw_duration: None,
+            start_location: start.cloned(),
+            end_location: end.cloned(),
+            steps,
+            annotation: self.annotation.as_ref().map(|a| a.to_annotation()),
+        })
+    }
+}
+
+impl OsrmRawAnnotation {
+    fn to_annotation(&self) -> Annotation {
+        Annotation {
+            duration: self.duration.clone().unwrap_or_default(),
+            distance: self.distance.clone().unwrap_or_default(),
+            speed: self.speed.clone().unwrap_or_default(),
+            weight: self.weight.clone().unwrap_or_default(),
+            nodes: self.nodes.clone().unwrap_or_default(),
+            datasources: self.datasources.clone().unwrap_or_default(),
+            metadata: self.metadata.clone(),
+            turn_penalty: vec![],
+        }
+    }
+}
+
+impl OsrmRawStep {
+    fn to_step(&self, next_maneuver_location: Option<&Vec<f64>>) -> Result<Step> {
+        let start_location = lng_lat_to_location(&self.maneuver.location)?;
+        let end_location = match next_maneuver_location {
+            Some(v) => lng_lat_to_location(v)?,
+            None => start_location.clone(),
+        };
+        let mut intersections = None;
+        if let Some(raw_intersections) = self.intersections.as_ref() {
+            let mut r = vec![];
+            for intersection in raw_intersections.iter() {
+                r.push(Intersection {
+                    location: lng_lat_to_coordinate(&intersection.location)?,
+                    bearings: intersection.bearings.clone(),
+                    classes: intersection.classes.clone().unwrap_or_default(),
+                    entry: intersection.entry.clone(),
+                    intersection_in: intersection.intersection_in.unwrap_or(-1),
+                    intersection_out: intersection.intersection_out.unwrap_or(-1),
+                    lanes: intersection.lanes.clone().unwrap_or_default(),
+                });
+            }
+            intersections = Some(r);
+        }
+
+        Ok(Step {
+            geometry: osrm_geometry(&self.geometry),
+            start_location,
+            end_location,
+            distance: IntValue {
+                value: self.distance.round() as i64,
+            },
+            duration: IntValue {
+                value: self.duration.round() as i64,
+            },
+            maneuver: Some(Maneuver {
+                instruction: None,
+                voice_instruction: vec![],
+                bearing_before: self.maneuver.bearing_before.unwrap_or(0),
+                bearing_after: self.maneuver.bearing_after.unwrap_or(0),
+                coordinate: lng_lat_to_coordinate(&self.maneuver.location)?,
+                maneuver_type: self.maneuver.maneuver_type.clone(),
+                modifier: self.maneuver.modifier.clone(),
+                muted: None,
+                roundabout_count: self.maneuver.exit,
+            }),
+            name: self.name.clone().filter(|v| !v.is_empty()),
+            intersections,
+            geojson: None,
+            reference: self.reference.clone(),
+            ffs: None,
+            metadata: None,
+            pronunciation: self.pronunciation.clone(),
+            destinations: self.destinations.clone(),
+            exits: self.exits.clone(),
+            mode: self.mode.clone(),
+            rotary_name: self.rotary_name.clone(),
+            rotary_pronunciation: self.rotary_pronunciation.clone(),
+            driving_side: self.driving_side.clone(),
+        })
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct OsrmRawTableResponseRef<'a> {
+    #[serde(borrow)]
+    pub code: RawStr<'a>,
+    #[serde(borrow)]
+    pub message: Option<RawStr<'a>>,
+    pub durations: Option<Vec<Vec<Option<f64>>>>,
+    pub distances: Option<Vec<Vec<Option<f64>>>>,
+}
+
+impl OsrmRawTableResponse {
+    pub fn into_matrix_output(self) -> Result<MatrixOutput> {
+        OsrmRawTableResponseRef {
+            code: RawStr(Cow::Owned(self.code)),
+            message: owned_str(self.message),
+            durations: self.durations,
+            distances: self.distances,
+        }
+        .into_matrix_output()
+    }
+}
+
+impl<'a> OsrmRawTableResponseRef<'a> {
+    // converts an osrm table into MatrixOutput; null cells become MATRIX_UNREACHABLE_VALUE
+    //  and are reported in warning
+    pub fn into_matrix_output(self) -> Result<MatrixOutput> {
+        check_osrm_code(self.code.as_str(), &self.message.map(|v| v.0.into_owned()))?;
+        let durations = match self.durations {
+            Some(v) => v,
+            None => bail!("osrm table response has no durations"),
+        };
+        let distances = self.distances.unwrap_or_default();
+
+        let mut unreachable = 0;
+        let mut rows = vec![];
+        for (i, duration_row) in durations.iter().enumerate() {
+            let mut elements = vec![];
+            for (j, duration) in duration_row.iter().enumerate() {
+                let distance = distances.get(i).and_then(|r| r.get(j)).cloned().flatten();
+                let (duration, distance) = match (duration, distance) {
+                    (Some(duration), Some(distance)) => {
+                        (duration.round() as i64, distance.round() as i64)
+                    }
+                    // distances are optional in the osrm annotations
+                    (Some(duration), None) if distances.is_empty() => (duration.round() as i64, 0),
+                    _ => {
+                        unreachable += 1;
+                        (MATRIX_UNREACHABLE_VALUE, MATRIX_UNREACHABLE_VALUE)
+                    }
+                };
+                elements.push(Element {
+                    duration: IntValue { value: duration },
+                    distance: IntValue { value: distance },
+                    raw_duration: None,
+                    predicted_duration: None,
+                    co2_emissions: None,
+                });
+            }
+            rows.push(Row { elements });
+        }
+
+        let mut warnings = Warnings::new();
+        if unreachable > 0 {
+            warnings.push(Warning::UnreachablePairs { count: unreachable });
+        }
+
+        Ok(MatrixOutput {
+            status: STATUS_OK.to_string(),
+            warning: warnings.into_legacy(),
+            server_timings: None,
+            rows,
+        })
+    }
+}
+
+pub fn parse_osrm_route_response(body: &str) -> Result<OsrmRoutes> {
+    let raw: OsrmRawRouteResponse = serde_json::from_str(body)?;
+    raw.into_routes()
+}
+
+pub fn parse_osrm_table_response(body: &str) -> Result<MatrixOutput> {
+    let raw: OsrmRawTableResponseRef = serde_json::from_str(body)?;
+    raw.into_matrix_output()
+}
+
+// parse_osrm_table_response_owned goes through the owned raw response, which also
+//  deserializes the sources and destinations
+pub fn parse_osrm_table_response_owned(body: &str) -> Result<MatrixOutput> {
+    let raw: OsrmRawTableResponse = serde_json::from_str(body)?;
+    raw.into_matrix_output()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_valhalla_route_response() {
+        let shape = polyline::encode(
+            &[
+                Coord::new(1.3, 103.8),
+                Coord::new(1.301, 103.8),
+                Coord::new(1.301, 103.801),
+            ],
+            polyline::PRECISION_POLYLINE6,
+        );
+        let body = serde_json::json!({
+            "trip": {
+                "locations": [{"lat": 1.3, "lon": 103.8, "type": "break"}, {"lat": 1.301, "lon": 103.801, "type": "break"}],
+                "legs": [{
+                    "maneuvers": [
+                        {"type": 1, "instruction": "Drive north.", "street_names": ["Main St"], "time": 10.2, "length": 0.111, "begin_shape_index": 0, "end_shape_index": 1, "bearing_after": 0},
+                        {"type": 10, "instruction": "Turn right.", "time": 9.6, "length": 0.111, "begin_shape_index": 1, "end_shape_index": 2, "bearing_before": 0, "bearing_after": 90},
+                        {"type": 4, "instruction": "You have arrived.", "time": 0.0, "length": 0.0, "begin_shape_index": 2, "end_shape_index": 2},
+                    ],
+                    "summary": {"length": 0.222, "time": 19.8},
+                    "shape": shape,
+                    "shape_attributes": {"time": [10.2, 9.6], "length": [0.111, 0.111], "speed": [39.2, 41.6]},
+                }],
+                "summary": {"length": 0.222, "time": 19.8, "cost": 25.0},
+                "units": "kilometers",
+                "status": 0,
+            },
+        })
+        .to_string();
+
+        let routes =
+            parse_valhalla_route_response(body.as_str(), polyline::PRECISION_POLYLINE).unwrap();
+        assert_eq!(routes.len(), 1);
+        let route = &routes[0];
+        assert_eq!(route.distance, 222.0);
+        assert_eq!(route.duration, 19.8);
+        assert_eq!(route.weight, Some(25.0));
+        assert_eq!(
+            polyline::decode(
+                route.geometry.as_ref().unwrap(),
+                polyline::PRECISION_POLYLINE
+            )
+            .unwrap()
+            .len(),
+            3
+        );
+
+        let leg = &route.legs.as_ref().unwrap()[0];
+        assert_eq!(leg.distance.value, 222);
+        assert_eq!(leg.duration.value, 20);
+        let steps = leg.steps.as_ref().unwrap();
+        assert_eq!(steps.len(), 3);
+        assert_eq!(steps[0].name.as_deref(), Some("Main St"));
+        assert_eq!(steps[0].maneuver.as_ref().unwrap().maneuver_type, "depart");
+        assert_eq!(
+            steps[1].maneuver.as_ref().unwrap().modifier.as_deref(),
+            Some("right")
+        );
+        assert_eq!(steps[1].start_location.latitude, 1.301);
+        assert_eq!(steps[2].maneuver.as_ref().unwrap().maneuver_type, "arrive");
+
+        let annotation = leg.annotation.as_ref().unwrap();
+        assert_eq!(annotation.distance, vec![111.0, 111.0]);
+        assert_eq!(annotation.node.len(), 3);
+        assert_eq!(annotation.node[0], vec![103.8, 1.3]);
+
+        let owned =
+            parse_valhalla_route_response_owned(body.as_str(), polyline::PRECISION_POLYLINE)
+                .unwrap();
+        assert_eq!(
+            serde_json::to_value(&owned).unwrap(),
+            serde_json::to_value(&routes).unwrap()
+        );
+        let raw: ValhallaRawResponseRef = serde_json::from_str(body.as_str()).unwrap();
+        let maneuver = &raw.trip.legs[0].maneuvers.as_ref().unwrap()[0];
+        assert!(matches!(
+            maneuver.instruction.as_ref().unwrap().0,
+            Cow::Borrowed("Drive north.")
+        ));
+        assert!(matches!(raw.trip.legs[0].shape, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_parse_valhalla_route_response_invalid() {
+        let body = r#"{"trip": {"legs": [], "summary": {"length": 0, "time": 0}, "status": 442, "status_message": "No path could be found for input"}}"#;
+        assert!(parse_valhalla_route_response(body, polyline::PRECISION_POLYLINE6).is_err());
+    }
+
+    #[test]
+    fn test_parse_osrm_route_response() {
+        let body = serde_json::json!({
+            "code": "Ok",
+            "waypoints": [
+                {"name": "Main St", "location": [103.8, 1.3], "distance": 3.2, "hint": "x"},
+                {"name": "", "location": [103.801, 1.301], "distance": 0.5, "hint": "y"},
+            ],
+            "routes": [{
+                "geometry": "abc",
+                "distance": 222.4,
+                "duration": 19.6,
+                "weight": 21.0,
+                "weight_name": "routability",
+                "legs": [{
+                    "distance": 222.4,
+                    "duration": 19.6,
+                    "weight": 21.0,
+                    "summary": "Main St",
+                    "steps": [
+                        {"distance": 111.2, "duration": 10.0, "geometry": "a", "name": "Main St", "mode": "driving",
+                         "maneuver": {"location": [103.8, 1.3], "bearing_before": 0, "bearing_after": 0, "type": "depart"},
+                         "intersections": [{"location": [103.8, 1.3], "bearings": [0], "entry": [true], "out": 0}]},
+                        {"distance": 111.2, "duration": 9.6, "geometry": "b", "name": "", "mode": "driving",
+                         "maneuver": {"location": [103.8, 1.301], "bearing_before": 0, "bearing_after": 90, "type": "turn", "modifier": "right"},
+                         "intersections": []},
+                    ],
+                    "annotation": {"duration": [10.0, 9.6], "distance": [111.2, 111.2], "nodes": [1, 2, 3]},
+                }],
+            }],
+        })
+        .to_string();
+
+        let OsrmRoutes { routes, waypoints } = parse_osrm_route_response(body.as_str()).unwrap();
+        assert_eq!(routes.len(), 1);
+        assert_eq!(waypoints.len(), 2);
+        assert_eq!(waypoints[0].name.as_deref(), Some("Main St"));
+        assert_eq!(waypoints[0].distance, Some(3.2));
+        assert_eq!(waypoints[0].hint.as_deref(), Some("x"));
+        assert_eq!(waypoints[1].name, None);
+        assert_eq!(waypoints[1].location.latitude, 1.301);
+        let route = &routes[0];
+        assert_eq!(route.geometry.as_deref(), Some("abc"));
+        assert_eq!(route.start_location.as_ref().unwrap().latitude, 1.3);
+        assert_eq!(route.end_location.as_ref().unwrap().longitude, 103.801);
+        let leg = &route.legs.as_ref().unwrap()[0];
+        assert_eq!(leg.distance.value, 222);
+        assert_eq!(leg.annotation.as_ref().unwrap().nodes, vec![1, 2, 3]);
+        let steps = leg.steps.as_ref().unwrap();
+        assert_eq!(steps[0].end_location.latitude, 1.301);
+        assert_eq!(
+            steps[0].intersections.as_ref().unwrap()[0].intersection_in,
+            -1
+        );
+        assert_eq!(steps[1].name, None);
+        assert_eq!(
+            steps[1].maneuver.as_ref().unwrap().modifier.as_deref(),
+            Some("right")
+        );
+
+        assert!(
+            parse_osrm_route_response(r#"{"code": "NoRoute", "message": "Impossible route"}"#)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_parse_osrm_table_response() {
+        let body = r#"{"code": "Ok", "durations": [[0, 10.6], [null, 0]], "distances": [[0, 100.4], [null, 0]]}"#;
+        let output = parse_osrm_table_response(body).unwrap();
+        assert_eq!(output.rows.len(), 2);
+        assert_eq!(output.rows[0].elements[1].duration.value, 11);
+        assert_eq!(output.rows[0].elements[1].distance.value, 100);
+        assert_eq!(
+            output.rows[1].elements[0].duration.value,
+            MATRIX_UNREACHABLE_VALUE
+        );
+        assert_eq!(
+            output.rows[1].elements[0].distance.value,
+            MATRIX_UNREACHABLE_VALUE
+        );
+        assert_eq!(output.warning.as_ref().unwrap().len(), 1);
+        assert_eq!(
+            serde_json::to_value(parse_osrm_table_response_owned(body).unwrap()).unwrap(),
+            serde_json::to_value(output).unwrap()
+        );
+    }
+}
@@ -0,0 +1,104 @@
+// errors of the area, mode, coordinate, token and config functions of this crate, so that
+//  callers can tell a request to fix from a failure of the service. messages are the ones
+//  returned to customers before the error type existed
+use crate::coord::Coord;
+use crate::deadline::DeadlineError;
+use std::error::Error;
+use thiserror::Error;
+
+pub type NbroutesResult<T> = std::result::Result<T, NbroutesError>;
+
+#[derive(Debug, Error)]
+pub enum NbroutesError {
+    // no area contains all the coordinates, coord is the first one outside of the area
+    //  containing most of them if any
+    #[error("{}", area_not_found_message(.coord))]
+    AreaNotFound { coord: Option<Coord> },
+    #[error("invalid mode input")]
+    InvalidMode { mode: String },
+    #[error("option=flexible not supported for this area")]
+    FlexibleNotSupported { area: String },
+    #[error("area not supported")]
+    AreaNotSupported { area: String },
+    #[error("{reason}")]
+    InvalidCoordinate { input: String, reason: String },
+    #[error("failed to load {path}: {source}")]
+    ConfigLoadFailed {
+        path: String,
+        source: Box<dyn Error + Send + Sync>,
+    },
+    #[error("{0}")]
+    JwtError(String),
+    #[error(transparent)]
+    Deadline(#[from] DeadlineError),
+}
+
+fn area_not_found_message(coord: &Option<Coord>) -> String {
+    match coord {
+        Some(c) => format!("({},{})", c.lat, c.lng),
+        None => String::new(),
+    }
+}
+
+impl NbroutesError {
+    // config_load_failed keeps exceeded deadlines as such
+    pub(crate) fn config_load_failed(
+        path: &str,
+        source: Box<dyn Error + Send + Sync>,
+    ) -> NbroutesError {
+        match source.downcast::<DeadlineError>() {
+            Ok(e) => NbroutesError::Deadline(*e),
+            Err(source) => NbroutesError::ConfigLoadFailed {
+                path: path.to_string(),
+                source,
+            },
+        }
+    }
+
+    // is_client_error tells if the request has to be fixed, as opposed to a failure of the
+    //  service
+    pub fn is_client_error(&self) -> bool {
+        matches!(
+            self,
+            NbroutesError::AreaNotFound { .. }
+                | NbroutesError::InvalidMode { .. }
+                | NbroutesError::FlexibleNotSupported { .. }
+                | NbroutesError::AreaNotSupported { .. }
+                | NbroutesError::InvalidCoordinate { .. }
+                | NbroutesError::JwtError(_)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deadline::is_timed_out;
+
+    #[test]
+    fn test_nbroutes_error() {
+        let e = NbroutesError::AreaNotFound {
+            coord: Some(Coord::new(1.3, 103.8)),
+        };
+        assert_eq!(e.to_string(), "(1.3,103.8)");
+        assert!(e.is_client_error());
+        assert_eq!(NbroutesError::AreaNotFound { coord: None }.to_string(), "");
+
+        let e =
+            NbroutesError::config_load_failed("gs://maaas/maaas-cfg.yaml", "no such object".into());
+        assert_eq!(
+            e.to_string(),
+            "failed to load gs://maaas/maaas-cfg.yaml: no such object"
+        );
+        assert!(e.source().is_some());
+        assert!(!e.is_client_error());
+
+        let timed_out = DeadlineError::TimedOut {
+            operation: "gsutil".to_string(),
+        };
+        let e = NbroutesError::config_load_failed("gs://maaas/maaas-cfg.yaml", timed_out.into());
+        assert!(matches!(e, NbroutesError::Deadline(_)));
+        let boxed: Box<dyn Error + Send + Sync> = e.into();
+        assert!(is_timed_out(boxed.as_ref()));
+    }
+}
@@ -0,0 +1,110 @@
+// per stop ETAs of optimized routes as wall clock times. the arrival of solver steps is
+//  expressed in the time reference of the time windows of the request: relative to the start
+//  of the route for windows such as [0, 28800], or unix timestamps for absolute windows, in
+//  which case the start is 0. the service of a stop begins after the waiting for its time
+//  window and the setup
+use crate::def::VRoomRoute;
+use crate::Result;
+use chrono::prelude::*;
+use paperclip::actix::Apiv2Schema;
+use serde::{Deserialize, Serialize};
+
+const LOCAL_TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%:z";
+
+#[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone, PartialEq)]
+pub struct StopEta {
+    #[doc = "type of the step.\n\nValues: `start|job|pickup|delivery|break|end`"]
+    #[serde(rename = "type")]
+    pub step_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[doc = "arrival at the stop.\n\nFormat: `unix timestamp`.\n\nUnit: `seconds`"]
+    pub arrival: i64,
+    #[doc = "arrival at the stop in the local time of the area.\n\nFormat: `YYYY-MM-DDThh:mm:ss±hh:mm`"]
+    pub arrival_local: String,
+    #[doc = "start of the service, after waiting and setup.\n\nFormat: `unix timestamp`.\n\nUnit: `seconds`"]
+    pub service_start: i64,
+    #[doc = "departure from the stop.\n\nFormat: `unix timestamp`.\n\nUnit: `seconds`"]
+    pub departure: i64,
+    #[doc = "departure from the stop in the local time of the area.\n\nFormat: `YYYY-MM-DDThh:mm:ss±hh:mm`"]
+    pub departure_local: String,
+}
+
+fn local_time(ts: i64, time_zone: &FixedOffset) -> Result<String> {
+    match DateTime::from_timestamp(ts, 0) {
+        Some(v) => Ok(v
+            .with_timezone(time_zone)
+            .format(LOCAL_TIME_FORMAT)
+            .to_string()),
+        None => bail!("invalid timestamp: {}", ts),
+    }
+}
+
+// eta_timeline converts the steps of route into wall clock times, start being the timestamp
+//  the time reference of the request counts from, and timezone the offset of the area in hours
+pub fn eta_timeline(route: &VRoomRoute, start: i64, timezone: f64) -> Result<Vec<StopEta>> {
+    let time_zone = match FixedOffset::east_opt((timezone * 3600.0) as i32) {
+        Some(v) => v,
+        None => bail!("invalid timezone: {}", timezone),
+    };
+    let mut etas = vec![];
+    for step in route.steps.iter().flatten() {
+        let arrival = start + step.arrival.unwrap_or(0.0).round() as i64;
+        let service_start =
+            arrival + step.waiting_time.unwrap_or(0) as i64 + step.setup.unwrap_or(0) as i64;
+        let departure = service_start + step.service.unwrap_or(0) as i64;
+        etas.push(StopEta {
+            step_type: step.step_type.clone().unwrap_or_default(),
+            id: step.id,
+            description: step.description.clone(),
+            arrival,
+            arrival_local: local_time(arrival, &time_zone)?,
+            service_start,
+            departure,
+            departure_local: local_time(departure, &time_zone)?,
+        });
+    }
+    Ok(etas)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_eta_timeline() {
+        let route: VRoomRoute = serde_json::from_value(json!({
+            "vehicle": 1,
+            "steps": [
+                {"type": "start", "arrival": 0.0, "duration": 0.0},
+                {"type": "job", "id": 7, "arrival": 900.0, "duration": 900.0, "waiting_time": 300, "setup": 60, "service": 600, "description": "front desk"},
+                {"type": "end", "arrival": 2400.0, "duration": 1500.0},
+            ],
+        }))
+        .unwrap();
+        // 2021-01-01T08:00:00+08:00
+        let etas = eta_timeline(&route, 1609459200, 8.0).unwrap();
+        assert_eq!(etas.len(), 3);
+        assert_eq!(etas[0].arrival_local, "2021-01-01T08:00:00+08:00");
+        let job = &etas[1];
+        assert_eq!(job.id, Some(7));
+        assert_eq!(job.arrival, 1609459200 + 900);
+        assert_eq!(job.service_start, 1609459200 + 1260);
+        assert_eq!(job.departure_local, "2021-01-01T08:31:00+08:00");
+        assert_eq!(
+            serde_json::to_value(&etas[2]).unwrap(),
+            json!({
+                "type": "end",
+                "arrival": 1609461600,
+                "arrival_local": "2021-01-01T08:40:00+08:00",
+                "service_start": 1609461600,
+                "departure": 1609461600,
+                "departure_local": "2021-01-01T08:40:00+08:00",
+            })
+        );
+        assert!(eta_timeline(&route, 0, 30.0).is_err());
+    }
+}
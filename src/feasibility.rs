@@ -0,0 +1,349 @@
+// feasibility pre-check of optimization requests, for the problems that make the solver fail
+//  or leave tasks unassigned and that are cheap to detect upfront: a location index out of the
+//  locations, a skill no vehicle has, a demand no vehicle can carry. vehicles serve a task
+//  when they have all of its skills, as in the solver
+use crate::def::{Job, OptimizationV2PostInput, Shipment, Vehicle};
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum FeasibilityIssue {
+    NoVehicles,
+    LocationIndexOutOfRange {
+        // such as `job 3` or `vehicle 1 start`
+        task: String,
+        index: i64,
+        locations: usize,
+    },
+    // amounts and capacities do not all have the same number of dimensions
+    DimensionMismatch {
+        task: String,
+        dimensions: usize,
+        expected: usize,
+    },
+    SkillUnavailable {
+        task: String,
+        skills: Vec<i64>,
+    },
+    DemandExceedsCapacity {
+        task: String,
+        dimension: usize,
+        demand: u64,
+        // largest capacity of the vehicles having the skills of the task
+        capacity: i64,
+    },
+    // deliveries are loaded at the start of routes, so all of them cannot be carried when they
+    //  exceed the capacity of the whole fleet
+    TotalDeliveryExceedsCapacity {
+        dimension: usize,
+        delivery: u64,
+        capacity: i64,
+    },
+}
+
+impl fmt::Display for FeasibilityIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FeasibilityIssue::NoVehicles => write!(f, "no vehicles are given"),
+            FeasibilityIssue::LocationIndexOutOfRange {
+                task,
+                index,
+                locations,
+            } => write!(
+                f,
+                "{}: location index {} is out of the {} locations",
+                task, index, locations
+            ),
+            FeasibilityIssue::DimensionMismatch {
+                task,
+                dimensions,
+                expected,
+            } => write!(
+                f,
+                "{}: has {} capacity dimensions, expected {}",
+                task, dimensions, expected
+            ),
+            FeasibilityIssue::SkillUnavailable { task, skills } => write!(
+                f,
+                "{}: no vehicle has all of the skills {:?}",
+                task, skills
+            ),
+            FeasibilityIssue::DemandExceedsCapacity {
+                task,
+                dimension,
+                demand,
+                capacity,
+            } => write!(
+                f,
+                "{}: demand {} of dimension {} exceeds the largest capacity {} of the vehicles able to serve it",
+                task, demand, dimension, capacity
+            ),
+            FeasibilityIssue::TotalDeliveryExceedsCapacity {
+                dimension,
+                delivery,
+                capacity,
+            } => write!(
+                f,
+                "total delivery {} of dimension {} exceeds the total capacity {} of the vehicles",
+                delivery, dimension, capacity
+            ),
+        }
+    }
+}
+
+struct Task<'a> {
+    name: String,
+    skills: &'a [i64],
+    // per dimension, the largest amount carried at once
+    demand: Vec<u64>,
+}
+
+fn has_skills(vehicle: &Vehicle, skills: &[i64]) -> bool {
+    let own = vehicle.skills.as_deref().unwrap_or(&[]);
+    skills.iter().all(|s| own.contains(s))
+}
+
+fn job_task(job: &Job) -> Task<'_> {
+    let delivery = job.delivery.as_deref().unwrap_or(&[]);
+    let pickup = job.pickup.as_deref().unwrap_or(&[]);
+    let demand = (0..delivery.len().max(pickup.len()))
+        .map(|d| {
+            delivery
+                .get(d)
+                .copied()
+                .unwrap_or(0)
+                .max(pickup.get(d).copied().unwrap_or(0))
+        })
+        .collect();
+    Task {
+        name: format!("job {}", job.id),
+        skills: job.skills.as_deref().unwrap_or(&[]),
+        demand,
+    }
+}
+
+fn shipment_task(shipment: &Shipment) -> Task<'_> {
+    Task {
+        name: format!("shipment {}", shipment.pickup.id),
+        skills: shipment.skills.as_deref().unwrap_or(&[]),
+        demand: shipment.amount.clone().unwrap_or_default(),
+    }
+}
+
+fn check_index(issues: &mut Vec<FeasibilityIssue>, task: String, index: i64, locations: usize) {
+    if index < 0 || index as usize >= locations {
+        issues.push(FeasibilityIssue::LocationIndexOutOfRange {
+            task,
+            index,
+            locations,
+        });
+    }
+}
+
+// feasibility_check returns the problems of the input that the solver cannot get around, an
+//  empty list does not mean the problem has a solution
+pub fn feasibility_check(input: &OptimizationV2PostInput) -> Vec<FeasibilityIssue> {
+    let mut issues = vec![];
+    let locations = input.locations.location.len();
+    let jobs = input.jobs.as_deref().unwrap_or(&[]);
+    let shipments = input.shipments.as_deref().unwrap_or(&[]);
+
+    if input.vehicles.is_empty() {
+        issues.push(FeasibilityIssue::NoVehicles);
+    }
+    for job in jobs.iter() {
+        check_index(
+            &mut issues,
+            format!("job {}", job.id),
+            job.location_index as i64,
+            locations,
+        );
+    }
+    for shipment in shipments.iter() {
+        for (step, kind) in [
+            (&shipment.pickup, "pickup"),
+            (&shipment.delivery, "delivery"),
+        ] {
+            check_index(
+                &mut issues,
+                format!("shipment {} {}", shipment.pickup.id, kind),
+                step.location_index as i64,
+                locations,
+            );
+        }
+    }
+    for vehicle in input.vehicles.iter() {
+        for (index, kind) in [(vehicle.start_index, "start"), (vehicle.end_index, "end")] {
+            if let Some(index) = index {
+                check_index(
+                    &mut issues,
+                    format!("vehicle {} {}", vehicle.id, kind),
+                    index as i64,
+                    locations,
+                );
+            }
+        }
+    }
+    for depot in input.depots.iter().flatten() {
+        check_index(
+            &mut issues,
+            format!("depot {}", depot.id),
+            depot.location_index as i64,
+            locations,
+        );
+    }
+
+    let tasks: Vec<Task> = jobs
+        .iter()
+        .map(job_task)
+        .chain(shipments.iter().map(shipment_task))
+        .collect();
+
+    // the dimensions of the first vehicle with a capacity are the reference
+    let expected = input
+        .vehicles
+        .iter()
+        .find_map(|v| v.capacity.as_ref().map(|c| c.len()));
+    if let Some(expected) = expected {
+        for vehicle in input.vehicles.iter() {
+            let dimensions = vehicle.capacity.as_ref().map(|c| c.len()).unwrap_or(0);
+            if dimensions != expected {
+                issues.push(FeasibilityIssue::DimensionMismatch {
+                    task: format!("vehicle {}", vehicle.id),
+                    dimensions,
+                    expected,
+                });
+            }
+        }
+        for task in tasks.iter().filter(|t| !t.demand.is_empty()) {
+            if task.demand.len() != expected {
+                issues.push(FeasibilityIssue::DimensionMismatch {
+                    task: task.name.clone(),
+                    dimensions: task.demand.len(),
+                    expected,
+                });
+            }
+        }
+    }
+
+    for task in tasks.iter() {
+        let able: Vec<&Vehicle> = input
+            .vehicles
+            .iter()
+            .filter(|v| has_skills(v, task.skills))
+            .collect();
+        if able.is_empty() {
+            if !input.vehicles.is_empty() {
+                issues.push(FeasibilityIssue::SkillUnavailable {
+                    task: task.name.clone(),
+                    skills: task.skills.to_vec(),
+                });
+            }
+            continue;
+        }
+        // vehicles without capacity are not limited
+        if able.iter().any(|v| v.capacity.is_none()) {
+            continue;
+        }
+        for (dimension, demand) in task.demand.iter().enumerate() {
+            let capacity = able
+                .iter()
+                .filter_map(|v| v.capacity.as_ref().and_then(|c| c.get(dimension)))
+                .copied()
+                .max();
+            // dimensions no vehicle has are already reported as a mismatch
+            let capacity = match capacity {
+                Some(v) => v,
+                None => continue,
+            };
+            if *demand as i64 > capacity {
+                issues.push(FeasibilityIssue::DemandExceedsCapacity {
+                    task: task.name.clone(),
+                    dimension,
+                    demand: *demand,
+                    capacity,
+                });
+            }
+        }
+    }
+
+    if input.vehicles.iter().all(|v| v.capacity.is_some()) {
+        let dimensions = expected.unwrap_or(0);
+        for dimension in 0..dimensions {
+            let delivery: u64 = jobs
+                .iter()
+                .filter_map(|j| j.delivery.as_ref().and_then(|d| d.get(dimension)))
+                .sum();
+            let capacity: i64 = input
+                .vehicles
+                .iter()
+                .filter_map(|v| v.capacity.as_ref().and_then(|c| c.get(dimension)))
+                .sum();
+            if delivery as i64 > capacity {
+                issues.push(FeasibilityIssue::TotalDeliveryExceedsCapacity {
+                    dimension,
+                    delivery,
+                    capacity,
+                });
+            }
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn input(jobs: serde_json::Value, vehicles: serde_json::Value) -> OptimizationV2PostInput {
+        serde_json::from_value(json!({
+            "locations": {"id": 1, "location": ["1.3,103.8", "1.29,103.85"]},
+            "jobs": jobs,
+            "vehicles": vehicles,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_feasible() {
+        let input = input(
+            json!([
+                {"id": 1, "location_index": 0, "delivery": [2], "skills": [7]},
+                {"id": 2, "location_index": 1, "pickup": [3]},
+            ]),
+            json!([
+                {"id": 1, "start_index": 0, "capacity": [4], "skills": [7]},
+                {"id": 2, "capacity": [3]},
+            ]),
+        );
+        assert_eq!(feasibility_check(&input), vec![]);
+    }
+
+    #[test]
+    fn test_issues() {
+        let input = input(
+            json!([
+                {"id": 1, "location_index": 0, "delivery": [5], "skills": [7]},
+                {"id": 2, "location_index": 2, "delivery": [1], "skills": [8]},
+                {"id": 3, "location_index": 1, "delivery": [1, 1]},
+            ]),
+            json!([
+                {"id": 1, "start_index": 0, "capacity": [4], "skills": [7]},
+            ]),
+        );
+        let messages: Vec<String> = feasibility_check(&input)
+            .iter()
+            .map(|i| i.to_string())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "job 2: location index 2 is out of the 2 locations",
+                "job 3: has 2 capacity dimensions, expected 1",
+                "job 1: demand 5 of dimension 0 exceeds the largest capacity 4 of the vehicles able to serve it",
+                "job 2: no vehicle has all of the skills [8]",
+                "total delivery 7 of dimension 0 exceeds the total capacity 4 of the vehicles",
+            ]
+        );
+    }
+}
@@ -0,0 +1,197 @@
+// feature flags of a key, set with the labels of the key in the key server instead of
+//  comparing label strings where the behaviors are gated. a flag is the label `ff.<flag>`,
+//  valued `true`/`false`, a number, any string, or a rollout percentage like `25%` which
+//  enables the flag for a stable quarter of the keys
+use crate::def::KeyServerAuthKey;
+use ring::digest;
+use std::collections::HashMap;
+
+pub const FLAG_LABEL_PREFIX: &str = "ff.";
+
+pub const FLAG_NEW_ETA_MODEL: &str = "new_eta_model";
+pub const FLAG_ALTERNATIVE_RANKING: &str = "alternative_ranking";
+
+// rollouts are evaluated in basis points, so that `0.5%` is honored
+const ROLLOUT_BUCKETS: u64 = 10000;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FeatureFlags {
+    flags: HashMap<String, String>,
+    // key rollouts are evaluated for
+    key_id: String,
+}
+
+impl FeatureFlags {
+    pub fn from_labels(key_id: &str, key: &KeyServerAuthKey) -> FeatureFlags {
+        match key.labels.as_ref() {
+            Some(labels) => FeatureFlags::from_label_map(key_id, labels),
+            None => FeatureFlags::default(),
+        }
+    }
+
+    pub fn from_label_map(key_id: &str, labels: &HashMap<String, String>) -> FeatureFlags {
+        let flags = labels
+            .iter()
+            .filter_map(|(k, v)| {
+                k.strip_prefix(FLAG_LABEL_PREFIX)
+                    .map(|k| (k.to_string(), v.trim().to_string()))
+            })
+            .collect();
+        FeatureFlags {
+            flags,
+            key_id: key_id.to_string(),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.flags.get(name).map(|v| v.as_str())
+    }
+
+    pub fn bool(&self, name: &str, default: bool) -> bool {
+        let v = match self.get(name) {
+            Some(v) => v,
+            None => return default,
+        };
+        if let Some(percent) = v.strip_suffix('%') {
+            return match percent.trim().parse::<f64>() {
+                Ok(percent) => {
+                    let threshold = percent * ROLLOUT_BUCKETS as f64 / 100.0;
+                    (rollout_bucket(name, &self.key_id) as f64) < threshold
+                }
+                Err(_) => invalid(name, v, default),
+            };
+        }
+        match v.to_lowercase().as_str() {
+            "true" | "1" | "on" | "yes" => true,
+            "false" | "0" | "off" | "no" => false,
+            _ => invalid(name, v, default),
+        }
+    }
+
+    pub fn i64(&self, name: &str, default: i64) -> i64 {
+        match self.get(name).map(|v| v.parse()) {
+            Some(Ok(v)) => v,
+            Some(Err(_)) => invalid(name, self.get(name).unwrap_or_default(), default),
+            None => default,
+        }
+    }
+
+    pub fn f64(&self, name: &str, default: f64) -> f64 {
+        match self.get(name).map(|v| v.parse::<f64>()) {
+            Some(Ok(v)) if v.is_finite() => v,
+            Some(_) => invalid(name, self.get(name).unwrap_or_default(), default),
+            None => default,
+        }
+    }
+
+    pub fn str<'a>(&'a self, name: &str, default: &'a str) -> &'a str {
+        self.get(name).unwrap_or(default)
+    }
+
+    pub fn new_eta_model(&self) -> bool {
+        self.bool(FLAG_NEW_ETA_MODEL, false)
+    }
+
+    pub fn alternative_ranking(&self) -> bool {
+        self.bool(FLAG_ALTERNATIVE_RANKING, false)
+    }
+}
+
+fn invalid<T>(name: &str, value: &str, default: T) -> T {
+    warn!(
+        "invalid value {:?} of feature flag {}, default used",
+        value, name
+    );
+    default
+}
+
+// rollout_bucket places key_id in one of ROLLOUT_BUCKETS buckets of the flag. buckets are the
+//  same in every process and independent between flags
+pub fn rollout_bucket(flag: &str, key_id: &str) -> u64 {
+    let hash = digest::digest(&digest::SHA256, format!("{}:{}", flag, key_id).as_bytes());
+    let mut v = [0u8; 8];
+    v.copy_from_slice(&hash.as_ref()[..8]);
+    u64::from_be_bytes(v) % ROLLOUT_BUCKETS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(labels: &[(&str, &str)]) -> KeyServerAuthKey {
+        KeyServerAuthKey {
+            source: None,
+            sku_map: None,
+            labels: Some(
+                labels
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+            ),
+            qps_limit: None,
+        }
+    }
+
+    #[test]
+    fn test_feature_flags() {
+        let flags = FeatureFlags::from_labels(
+            "k1",
+            &key(&[
+                ("ff.new_eta_model", "true"),
+                ("ff.max_alternatives", "3"),
+                ("ff.eta_model", " v2 "),
+                ("ff.broken", "maybe"),
+                ("team", "routing"),
+            ]),
+        );
+        assert!(flags.new_eta_model());
+        assert!(!flags.alternative_ranking());
+        assert_eq!(flags.i64("max_alternatives", 1), 3);
+        assert_eq!(flags.i64("eta_model", 1), 1);
+        assert_eq!(flags.f64("max_alternatives", 0.0), 3.0);
+        assert_eq!(flags.str("eta_model", "v1"), "v2");
+        assert_eq!(flags.str("other", "v1"), "v1");
+        assert!(flags.bool("broken", true));
+        assert!(flags.get("team").is_none());
+        assert_eq!(
+            FeatureFlags::from_labels(
+                "k1",
+                &KeyServerAuthKey {
+                    labels: None,
+                    ..key(&[])
+                }
+            ),
+            FeatureFlags::default()
+        );
+    }
+
+    #[test]
+    fn test_rollout() {
+        assert_eq!(rollout_bucket("a", "key"), rollout_bucket("a", "key"));
+        let rollout = |percent: &str| {
+            (0..1000)
+                .filter(|i| {
+                    FeatureFlags::from_labels(
+                        &format!("key-{}", i),
+                        &key(&[("ff.alternative_ranking", percent)]),
+                    )
+                    .alternative_ranking()
+                })
+                .count()
+        };
+        assert_eq!(rollout("0%"), 0);
+        assert_eq!(rollout("100%"), 1000);
+        let quarter = rollout("25%");
+        assert!(quarter > 200 && quarter < 300, "{}", quarter);
+        // keys enabled at 25% stay enabled at 50%
+        let flags = |id: &str, percent: &str| {
+            FeatureFlags::from_labels(id, &key(&[("ff.alternative_ranking", percent)]))
+        };
+        for i in 0..1000 {
+            let id = format!("key-{}", i);
+            if flags(&id, "25%").alternative_ranking() {
+                assert!(flags(&id, "50%").alternative_ranking());
+            }
+        }
+    }
+}
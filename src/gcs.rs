@@ -0,0 +1,382 @@
+// native client of the GCS JSON API for the configs read from buckets, so that services do
+//  not need the gsutil binary in their images. requests are authorized with OAuth2 access
+//  tokens, obtained with the key file of a service account or from the metadata server (GKE
+//  workload identity, GCE), and cached until shortly before they expire
+use crate::mdm_io::{ObjectStore, RetryPolicy, StoreFuture};
+use crate::signed_url::{pem_decode, split_path, uri_encode};
+use crate::Result;
+use ring::{rand, signature};
+use serde::Deserialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+pub const DEFAULT_ENDPOINT: &str = "https://storage.googleapis.com";
+
+const TOKEN_URI: &str = "https://oauth2.googleapis.com/token";
+const METADATA_TOKEN_URL: &str =
+    "http://metadata.google.internal/computeMetadata/v1/instance/service-accounts/default/token";
+const READ_ONLY_SCOPE: &str = "https://www.googleapis.com/auth/devstorage.read_only";
+// tokens are refreshed this long before they expire
+const TOKEN_EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+
+lazy_static! {
+    static ref DEFAULT_READER: GcsReader =
+        GcsReader::new(GcsAuth::from_env().unwrap_or_else(|e| {
+            warn!(
+                "error loading gcs credentials, using the metadata server: {}",
+                e
+            );
+            GcsAuth::Metadata
+        }));
+}
+
+// default_reader is shared by the config loaders of util, with the credentials of from_env
+pub fn default_reader() -> &'static GcsReader {
+    &DEFAULT_READER
+}
+
+pub enum GcsAuth {
+    // public objects and emulators
+    Anonymous,
+    ServiceAccount {
+        client_email: String,
+        key: Box<signature::RsaKeyPair>,
+        token_uri: String,
+    },
+    // the metadata server of GKE, with workload identity, and GCE
+    Metadata,
+}
+
+#[derive(Deserialize)]
+struct ServiceAccountKey {
+    client_email: String,
+    private_key: String,
+    token_uri: Option<String>,
+}
+
+impl GcsAuth {
+    // from_service_account loads the JSON key file of a service account
+    pub fn from_service_account(json: &str) -> Result<GcsAuth> {
+        let account: ServiceAccountKey = serde_json::from_str(json)?;
+        let key = match signature::RsaKeyPair::from_pkcs8(&pem_decode(&account.private_key)?) {
+            Ok(v) => v,
+            Err(e) => bail!("invalid RSA private key of {}: {}", account.client_email, e),
+        };
+        Ok(GcsAuth::ServiceAccount {
+            client_email: account.client_email,
+            key: Box::new(key),
+            token_uri: account.token_uri.unwrap_or_else(|| TOKEN_URI.to_string()),
+        })
+    }
+
+    // from_env uses the key file at GOOGLE_APPLICATION_CREDENTIALS when set and the metadata
+    //  server otherwise, like the google SDKs
+    pub fn from_env() -> Result<GcsAuth> {
+        match std::env::var("GOOGLE_APPLICATION_CREDENTIALS") {
+            Ok(path) if !path.is_empty() => {
+                GcsAuth::from_service_account(&std::fs::read_to_string(&path)?)
+            }
+            _ => Ok(GcsAuth::Metadata),
+        }
+    }
+}
+
+fn base64_url(data: &[u8]) -> String {
+    base64::encode_config(data, base64::URL_SAFE_NO_PAD)
+}
+
+// jwt_assertion is the JWT signed by a service account to get an access token, `now` in unix
+//  seconds
+fn jwt_assertion(
+    client_email: &str,
+    key: &signature::RsaKeyPair,
+    token_uri: &str,
+    now: u64,
+) -> Result<String> {
+    let header = serde_json::json!({"alg": "RS256", "typ": "JWT"});
+    let claims = serde_json::json!({
+        "iss": client_email,
+        "scope": READ_ONLY_SCOPE,
+        "aud": token_uri,
+        "iat": now,
+        "exp": now + 3600,
+    });
+    let message = format!(
+        "{}.{}",
+        base64_url(&serde_json::to_vec(&header)?),
+        base64_url(&serde_json::to_vec(&claims)?)
+    );
+    let mut signature = vec![0; key.public_modulus_len()];
+    if key
+        .sign(
+            &signature::RSA_PKCS1_SHA256,
+            &rand::SystemRandom::new(),
+            message.as_bytes(),
+            &mut signature,
+        )
+        .is_err()
+    {
+        bail!("error signing token request of {}", client_email);
+    }
+    Ok(format!("{}.{}", message, base64_url(&signature)))
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: u64,
+}
+
+struct CachedToken {
+    value: String,
+    expires_at: Instant,
+}
+
+// failure of an attempt, permanent ones are not retried
+struct ReadError {
+    message: String,
+    permanent: bool,
+}
+
+impl ReadError {
+    fn transient<E: ToString>(e: E) -> ReadError {
+        ReadError {
+            message: e.to_string(),
+            permanent: false,
+        }
+    }
+}
+
+pub struct GcsReader {
+    client: reqwest::Client,
+    auth: GcsAuth,
+    endpoint: String,
+    retry_policy: RetryPolicy,
+    token: Mutex<Option<CachedToken>>,
+}
+
+impl GcsReader {
+    pub fn new(auth: GcsAuth) -> GcsReader {
+        GcsReader {
+            client: reqwest::Client::new(),
+            auth,
+            endpoint: DEFAULT_ENDPOINT.to_string(),
+            retry_policy: RetryPolicy {
+                attempts: 3,
+                backoff: Duration::from_millis(200),
+                max_backoff: Duration::from_secs(2),
+                timeout: Duration::from_secs(30),
+            },
+            token: Mutex::new(None),
+        }
+    }
+
+    // with_endpoint targets another endpoint than storage.googleapis.com, e.g. an emulator
+    pub fn with_endpoint(mut self, endpoint: &str) -> GcsReader {
+        self.endpoint = endpoint.trim_end_matches('/').to_string();
+        self
+    }
+
+    // the timeout of the policy applies to each attempt
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> GcsReader {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn object_url(&self, bucket: &str, object: &str) -> String {
+        format!(
+            "{}/storage/v1/b/{}/o/{}?alt=media",
+            self.endpoint,
+            uri_encode(bucket, false),
+            uri_encode(object, false)
+        )
+    }
+
+    // read returns the content of object, retrying failed attempts but missing objects and
+    //  denied accesses
+    pub async fn read(&self, bucket: &str, object: &str) -> Result<Vec<u8>> {
+        let url = self.object_url(bucket, object);
+        let mut n = 1;
+        loop {
+            let e = match self.attempt(&url).await {
+                Ok(v) => return Ok(v),
+                Err(e) => e,
+            };
+            if e.permanent || n >= self.retry_policy.attempts {
+                bail!(
+                    "error reading gs://{}/{} after {} attempts: {}",
+                    bucket,
+                    object,
+                    n,
+                    e.message
+                );
+            }
+            warn!(
+                "attempt {} to read gs://{}/{} failed: {}",
+                n, bucket, object, e.message
+            );
+            tokio::time::sleep(self.retry_policy.delay(n)).await;
+            n += 1;
+        }
+    }
+
+    // read_path reads a `gs://<bucket>/<object>` path
+    pub async fn read_path(&self, path: &str) -> Result<Vec<u8>> {
+        let (bucket, object) = split_path(path, "gs")?;
+        self.read(bucket, object).await
+    }
+
+    async fn attempt(&self, url: &str) -> std::result::Result<Vec<u8>, ReadError> {
+        let mut request = self.client.get(url).timeout(self.retry_policy.timeout);
+        if let Some(token) = self.token().await.map_err(ReadError::transient)? {
+            request = request.bearer_auth(token);
+        }
+        let response = request.send().await.map_err(ReadError::transient)?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response
+                .bytes()
+                .await
+                .map_err(ReadError::transient)?
+                .to_vec());
+        }
+        if status.as_u16() == 401 {
+            // the token may have been revoked, the next attempt gets a new one
+            *self.token.lock().unwrap() = None;
+        }
+        Err(ReadError {
+            message: format!("status {}", status),
+            permanent: status.is_client_error() && ![401, 408, 429].contains(&status.as_u16()),
+        })
+    }
+
+    // token returns the cached access token, or a new one when it is about to expire
+    async fn token(&self) -> Result<Option<String>> {
+        if let GcsAuth::Anonymous = self.auth {
+            return Ok(None);
+        }
+        if let Some(token) = self.token.lock().unwrap().as_ref() {
+            if Instant::now() + TOKEN_EXPIRY_MARGIN < token.expires_at {
+                return Ok(Some(token.value.clone()));
+            }
+        }
+        let response = self.fetch_token().await?;
+        *self.token.lock().unwrap() = Some(CachedToken {
+            value: response.access_token.clone(),
+            expires_at: Instant::now() + Duration::from_secs(response.expires_in),
+        });
+        Ok(Some(response.access_token))
+    }
+
+    async fn fetch_token(&self) -> Result<TokenResponse> {
+        let request = match &self.auth {
+            GcsAuth::Anonymous => bail!("anonymous access has no token"),
+            GcsAuth::ServiceAccount {
+                client_email,
+                key,
+                token_uri,
+            } => {
+                let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+                let assertion = jwt_assertion(client_email, key, token_uri, now)?;
+                self.client
+                    .post(token_uri)
+                    .header("Content-Type", "application/x-www-form-urlencoded")
+                    .body(format!(
+                        "grant_type={}&assertion={}",
+                        uri_encode("urn:ietf:params:oauth:grant-type:jwt-bearer", false),
+                        assertion
+                    ))
+            }
+            GcsAuth::Metadata => self
+                .client
+                .get(METADATA_TOKEN_URL)
+                .header("Metadata-Flavor", "Google"),
+        };
+        let response = request.timeout(self.retry_policy.timeout).send().await?;
+        if !response.status().is_success() {
+            bail!(
+                "error getting gcs access token: status {}",
+                response.status()
+            );
+        }
+        Ok(serde_json::from_slice(&response.bytes().await?)?)
+    }
+}
+
+// GcsReader is a read only store, so that the loaders can take any ObjectStore
+impl ObjectStore for GcsReader {
+    fn put<'a>(&'a self, path: &'a str, _data: Vec<u8>) -> StoreFuture<'a, ()> {
+        Box::pin(async move { bail!("can not upload {}, GcsReader is read only", path) })
+    }
+
+    fn get<'a>(&'a self, path: &'a str) -> StoreFuture<'a, Vec<u8>> {
+        Box::pin(self.read_path(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::signature::KeyPair;
+
+    #[test]
+    fn test_jwt_assertion() {
+        let pem = include_str!("../tests/fixtures/test_rsa_key.pem");
+        let email = "maaas@project.iam.gserviceaccount.com";
+        let account = serde_json::json!({"client_email": email, "private_key": pem});
+        let auth = GcsAuth::from_service_account(&account.to_string()).unwrap();
+        let (key, token_uri) = match &auth {
+            GcsAuth::ServiceAccount { key, token_uri, .. } => (key, token_uri),
+            _ => panic!("not a service account"),
+        };
+        assert_eq!(token_uri, TOKEN_URI);
+
+        let assertion = jwt_assertion(email, key, token_uri, 1600000000).unwrap();
+        let parts: Vec<&str> = assertion.split('.').collect();
+        assert_eq!(parts.len(), 3);
+        let claims: serde_json::Value = serde_json::from_slice(
+            &base64::decode_config(parts[1], base64::URL_SAFE_NO_PAD).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(claims["iss"], email);
+        assert_eq!(claims["scope"], READ_ONLY_SCOPE);
+        assert_eq!(claims["exp"], 1600003600);
+        signature::UnparsedPublicKey::new(
+            &signature::RSA_PKCS1_2048_8192_SHA256,
+            key.public_key().as_ref(),
+        )
+        .verify(
+            format!("{}.{}", parts[0], parts[1]).as_bytes(),
+            &base64::decode_config(parts[2], base64::URL_SAFE_NO_PAD).unwrap(),
+        )
+        .unwrap();
+
+        assert!(GcsAuth::from_service_account(
+            &serde_json::json!({"client_email": email, "private_key": "not a key"}).to_string()
+        )
+        .is_err());
+    }
+
+    #[actix_rt::test]
+    async fn test_gcs_reader() {
+        let reader = GcsReader::new(GcsAuth::Anonymous)
+            .with_endpoint("http://127.0.0.1:1/")
+            .with_retry_policy(RetryPolicy {
+                attempts: 2,
+                backoff: Duration::from_millis(1),
+                max_backoff: Duration::from_millis(1),
+                timeout: Duration::from_secs(1),
+            });
+        assert_eq!(
+            reader.object_url("maaas", "areas/maaas cfg.yaml"),
+            "http://127.0.0.1:1/storage/v1/b/maaas/o/areas%2Fmaaas%20cfg.yaml?alt=media"
+        );
+        let e = reader.read_path("gs://maaas/maaas-cfg.yaml").await;
+        assert!(e
+            .err()
+            .unwrap()
+            .to_string()
+            .starts_with("error reading gs://maaas/maaas-cfg.yaml after 2 attempts"));
+        assert!(reader.read_path("s3://maaas/maaas-cfg.yaml").await.is_err());
+    }
+}
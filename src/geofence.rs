@@ -0,0 +1,249 @@
+// crossings of named geofences (city limits, restricted zones...) along the geometry of a
+//  route. fences are prepared polygons keyed by their name, like the polygons of the areas, and
+//  the bounding rects of the polygons discard the fences far from the route. each segment of the
+//  geometry is intersected with the rings of the fences it may cross, so that a segment going
+//  through a fence without a vertex inside it still counts
+use crate::coord::{cumulative_distances, Coord, Locatable};
+use crate::def::RouteT;
+use crate::polyline;
+use crate::prepared_polygon::PreparedPolygon;
+use crate::Result;
+use geo::{Point, Rect};
+use paperclip::actix::Apiv2Schema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct Crossing {
+    #[doc = "name of the geofence."]
+    pub fence: String,
+    #[doc = "distance along the route where it enters the geofence, 0 when the route starts inside.\n\nUnit: `meters`"]
+    pub enter_offset_m: f64,
+    #[doc = "distance along the route where it exits the geofence, the length of the route when it ends inside.\n\nUnit: `meters`"]
+    pub exit_offset_m: f64,
+}
+
+fn fence_contains(polygons: &[PreparedPolygon], c: &Coord) -> bool {
+    let p = Point::new(c.lng(), c.lat());
+    polygons.iter().any(|v| v.contains(&p))
+}
+
+fn interpolate(a: &Coord, b: &Coord, ratio: f64) -> Coord {
+    Coord::new(
+        a.lat() + (b.lat() - a.lat()) * ratio,
+        a.lng() + (b.lng() - a.lng()) * ratio,
+    )
+}
+
+// segment_ratio returns the ratio along a-b where it intersects the edge p-q, None when they
+//  do not intersect or are parallel. points are (lng, lat), planar like the containment checks
+fn segment_ratio(a: (f64, f64), b: (f64, f64), p: (f64, f64), q: (f64, f64)) -> Option<f64> {
+    let r = (b.0 - a.0, b.1 - a.1);
+    let s = (q.0 - p.0, q.1 - p.1);
+    let denom = r.0 * s.1 - r.1 * s.0;
+    if denom == 0.0 {
+        return None;
+    }
+    let ap = (p.0 - a.0, p.1 - a.1);
+    let t = (ap.0 * s.1 - ap.1 * s.0) / denom;
+    let u = (ap.0 * r.1 - ap.1 * r.0) / denom;
+    if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) {
+        Some(t)
+    } else {
+        None
+    }
+}
+
+fn rect_overlaps(r: &Rect<f64>, min: &Coord, max: &Coord) -> bool {
+    r.min().x <= max.lng()
+        && r.max().x >= min.lng()
+        && r.min().y <= max.lat()
+        && r.max().y >= min.lat()
+}
+
+// border_ratios returns the sorted ratios along a-b where it meets the rings of the fence,
+//  polygons whose bounding rect misses the segment are skipped
+fn border_ratios(polygons: &[PreparedPolygon], a: &Coord, b: &Coord) -> Vec<f64> {
+    let min = Coord::new(a.lat().min(b.lat()), a.lng().min(b.lng()));
+    let max = Coord::new(a.lat().max(b.lat()), a.lng().max(b.lng()));
+    let mut r = vec![];
+    for polygon in polygons.iter() {
+        if !polygon
+            .bbox()
+            .map(|v| rect_overlaps(v, &min, &max))
+            .unwrap_or(false)
+        {
+            continue;
+        }
+        let polygon = polygon.polygon();
+        for ring in std::iter::once(polygon.exterior()).chain(polygon.interiors()) {
+            for line in ring.lines() {
+                let (p, q) = (line.start, line.end);
+                if let Some(t) = segment_ratio(
+                    (a.lng(), a.lat()),
+                    (b.lng(), b.lat()),
+                    (p.x, p.y),
+                    (q.x, q.y),
+                ) {
+                    r.push(t);
+                }
+            }
+        }
+    }
+    r.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    r.dedup();
+    r
+}
+
+// may_cross is false when no polygon of the fence overlaps the bounding rect of the geometry
+fn may_cross(polygons: &[PreparedPolygon], min: &Coord, max: &Coord) -> bool {
+    polygons
+        .iter()
+        .filter_map(|p| p.bbox())
+        .any(|r| rect_overlaps(r, min, max))
+}
+
+// crossings returns the visits of the geometry to the fences, sorted by the distance at which
+//  the route enters them. a fence entered several times is listed once per visit
+pub fn crossings<T: Locatable>(
+    geometry: &[T],
+    fences: &HashMap<String, Vec<PreparedPolygon>>,
+) -> Vec<Crossing> {
+    let coords: Vec<Coord> = geometry
+        .iter()
+        .map(|c| Coord::new(c.lat(), c.lng()))
+        .collect();
+    if coords.is_empty() {
+        return vec![];
+    }
+    let offsets = cumulative_distances(&coords);
+    let total = offsets[offsets.len() - 1];
+    let mut min = coords[0].clone();
+    let mut max = coords[0].clone();
+    for c in coords.iter() {
+        min = Coord::new(min.lat().min(c.lat()), min.lng().min(c.lng()));
+        max = Coord::new(max.lat().max(c.lat()), max.lng().max(c.lng()));
+    }
+
+    let mut r = vec![];
+    for (name, polygons) in fences.iter() {
+        if !may_cross(polygons, &min, &max) {
+            continue;
+        }
+        let mut inside = fence_contains(polygons, &coords[0]);
+        let mut enter = if inside { Some(0.0) } else { None };
+        for i in 1..coords.len() {
+            let (a, b) = (&coords[i - 1], &coords[i]);
+            let length = offsets[i] - offsets[i - 1];
+            // the segment is on one side of the border between two consecutive ratios
+            let ratios = border_ratios(polygons, a, b);
+            for (k, ratio) in ratios.iter().enumerate() {
+                let next = ratios.get(k + 1).cloned().unwrap_or(1.0);
+                let now_inside = fence_contains(polygons, &interpolate(a, b, (ratio + next) / 2.0));
+                if now_inside == inside {
+                    continue;
+                }
+                let offset = offsets[i - 1] + ratio * length;
+                match enter.take() {
+                    Some(enter_offset_m) => r.push(Crossing {
+                        fence: name.clone(),
+                        enter_offset_m,
+                        exit_offset_m: offset,
+                    }),
+                    None => enter = Some(offset),
+                }
+                inside = now_inside;
+            }
+        }
+        if let Some(enter_offset_m) = enter {
+            r.push(Crossing {
+                fence: name.clone(),
+                enter_offset_m,
+                exit_offset_m: total,
+            });
+        }
+    }
+    r.sort_by(|a, b| {
+        a.enter_offset_m
+            .partial_cmp(&b.enter_offset_m)
+            .unwrap()
+            .then_with(|| a.fence.cmp(&b.fence))
+    });
+    r
+}
+
+// annotate_route sets the geofence crossings of route, from its geometry encoded with
+//  precision. routes without geometry are left as is
+pub fn annotate_route<L>(
+    route: &mut RouteT<L>,
+    fences: &HashMap<String, Vec<PreparedPolygon>>,
+    precision: u32,
+) -> Result<()> {
+    let geometry = match route.geometry.as_ref() {
+        Some(v) => polyline::decode(v, precision)?,
+        None => return Ok(()),
+    };
+    route.geofence_crossings = Some(crossings(&geometry, fences));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coord::path_length;
+    use crate::def::Route;
+    use crate::testkit::{polygons_map, square_polygon};
+
+    #[test]
+    fn test_crossings() {
+        // a route going east along the equator, through 2 fences and ending in a 3rd
+        let geometry: Vec<Coord> = (0..=10).map(|i| Coord::new(0.0, i as f64 * 0.1)).collect();
+        let fences = polygons_map(vec![
+            ("a", vec![square_polygon(&Coord::new(0.0, 0.25), 0.07)]),
+            ("b", vec![square_polygon(&Coord::new(0.0, 0.6), 0.13)]),
+            ("c", vec![square_polygon(&Coord::new(0.0, 1.0), 0.07)]),
+            ("far", vec![square_polygon(&Coord::new(10.0, 10.0), 0.5)]),
+        ]);
+        let r = crossings(&geometry, &fences);
+        let names: Vec<&str> = r.iter().map(|c| c.fence.as_str()).collect();
+        assert_eq!(names, vec!["a", "b", "c"]);
+        // a degree of longitude is ~111.2 km at the equator
+        let km = |d: f64| d * 111.2;
+        for (c, (enter, exit)) in r.iter().zip(vec![(0.18, 0.32), (0.47, 0.73), (0.93, 1.0)]) {
+            assert!(
+                (c.enter_offset_m / 1000.0 - km(enter)).abs() < 0.1,
+                "{:?}",
+                c
+            );
+            assert!((c.exit_offset_m / 1000.0 - km(exit)).abs() < 0.1, "{:?}", c);
+        }
+
+        // ending inside
+        let r = crossings(&geometry[..6], &fences);
+        assert_eq!(r.len(), 2);
+        assert_eq!(r[1].exit_offset_m, path_length(&geometry[..6]));
+        assert!(crossings(&geometry[..1], &fences).is_empty());
+        let inside = vec![Coord::new(0.0, 0.6)];
+        assert_eq!(
+            crossings(&inside, &fences),
+            vec![Crossing {
+                fence: "b".to_string(),
+                enter_offset_m: 0.0,
+                exit_offset_m: 0.0,
+            }]
+        );
+
+        // a single segment going through the fences
+        let segment = vec![geometry[0].clone(), geometry[10].clone()];
+        let r = crossings(&segment, &fences);
+        let names: Vec<&str> = r.iter().map(|c| c.fence.as_str()).collect();
+        assert_eq!(names, vec!["a", "b", "c"]);
+        assert!((r[0].enter_offset_m / 1000.0 - km(0.18)).abs() < 0.1);
+        assert!((r[1].exit_offset_m / 1000.0 - km(0.73)).abs() < 0.1);
+
+        let mut route = Route::straight_line_fallback(&geometry[0], &geometry[10], 10.0, 5);
+        annotate_route(&mut route, &fences, 5).unwrap();
+        assert_eq!(route.geofence_crossings.unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,265 @@
+// GeometryFormat is the canonical encoding of geometries. requests still carry it in several
+//  legacy fields (`geometry`, `geometries`, `geometry_type`, `original_shape_type`) with their
+//  own defaults, the resolvers below map each input to one format so that the output builders
+//  do not have to know about them
+use crate::def::{
+    DirectionsInput, GeometryInput, NavigatingInput, OptimizationInput, PostTripRouteInput,
+    SnapInput, ValhallaDirectionsInput,
+};
+use crate::polyline::{PRECISION_POLYLINE, PRECISION_POLYLINE6};
+use crate::Result;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GeometryFormat {
+    Polyline,
+    Polyline6,
+    GeoJSON,
+}
+
+impl GeometryFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GeometryFormat::Polyline => "polyline",
+            GeometryFormat::Polyline6 => "polyline6",
+            GeometryFormat::GeoJSON => "geojson",
+        }
+    }
+
+    // precision of the encoded polyline, None for geojson
+    pub fn precision(&self) -> Option<u32> {
+        match self {
+            GeometryFormat::Polyline => Some(PRECISION_POLYLINE),
+            GeometryFormat::Polyline6 => Some(PRECISION_POLYLINE6),
+            GeometryFormat::GeoJSON => None,
+        }
+    }
+
+    // name of the format in the shape_format of valhalla
+    pub fn valhalla_shape_format(&self) -> &'static str {
+        match self {
+            GeometryFormat::Polyline => "polyline5",
+            GeometryFormat::Polyline6 => "polyline6",
+            GeometryFormat::GeoJSON => "geojson",
+        }
+    }
+}
+
+impl FromStr for GeometryFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "polyline" | "polyline5" => Ok(GeometryFormat::Polyline),
+            "polyline6" => Ok(GeometryFormat::Polyline6),
+            "geojson" => Ok(GeometryFormat::GeoJSON),
+            _ => Err(format!(
+                "unknown geometry format '{}', expected one of polyline|polyline6|geojson",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for GeometryFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl From<&GeometryInput> for GeometryFormat {
+    fn from(v: &GeometryInput) -> Self {
+        match v {
+            GeometryInput::Polyline => GeometryFormat::Polyline,
+            GeometryInput::Polyline6 => GeometryFormat::Polyline6,
+            GeometryInput::GeoJSON => GeometryFormat::GeoJSON,
+        }
+    }
+}
+
+// resolve returns the format of the first set field, fields are (name, value) in order of
+//  precedence. an empty value counts as unset, an unknown one is an error naming the field
+pub fn resolve(
+    fields: &[(&str, &Option<String>)],
+    default: GeometryFormat,
+) -> Result<GeometryFormat> {
+    for (name, value) in fields.iter() {
+        let value = match value.as_deref().map(|v| v.trim()) {
+            Some(v) if !v.is_empty() => v,
+            _ => continue,
+        };
+        return value
+            .parse()
+            .map_err(|e| format!("invalid {}: {}", name, e).into());
+    }
+    Ok(default)
+}
+
+pub fn from_input(geometry: &Option<GeometryInput>, default: GeometryFormat) -> GeometryFormat {
+    geometry
+        .as_ref()
+        .map(GeometryFormat::from)
+        .unwrap_or(default)
+}
+
+// GeometryFormats gives the output format of an input, with the legacy fields resolved
+pub trait GeometryFormats {
+    fn output_format(&self) -> Result<GeometryFormat>;
+}
+
+// `geometry`, default polyline
+impl GeometryFormats for DirectionsInput {
+    fn output_format(&self) -> Result<GeometryFormat> {
+        Ok(from_input(&self.geometry, GeometryFormat::Polyline))
+    }
+}
+
+// `geometry`, default polyline
+impl GeometryFormats for ValhallaDirectionsInput {
+    fn output_format(&self) -> Result<GeometryFormat> {
+        Ok(from_input(&self.geometry, GeometryFormat::Polyline))
+    }
+}
+
+// `geometry`, default polyline. in the old version `geometry` carried the input geometry
+//  itself, which is not a format name and then falls back to the default
+impl GeometryFormats for NavigatingInput {
+    fn output_format(&self) -> Result<GeometryFormat> {
+        match self.original_shape.as_ref() {
+            Some(_) => resolve(&[("geometry", &self.geometry)], GeometryFormat::Polyline),
+            None => Ok(self
+                .geometry
+                .as_deref()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(GeometryFormat::Polyline)),
+        }
+    }
+}
+
+impl NavigatingInput {
+    // input_shape_format is the format of the input geometry: `original_shape_type` when
+    //  `original_shape` is given (default polyline), otherwise the old version `geometry_type`
+    //  (default polyline6)
+    pub fn input_shape_format(&self) -> Result<GeometryFormat> {
+        match self.original_shape.as_ref() {
+            Some(_) => resolve(
+                &[("original_shape_type", &self.original_shape_type)],
+                GeometryFormat::Polyline,
+            ),
+            None => resolve(
+                &[("geometry_type", &self.geometry_type)],
+                GeometryFormat::Polyline6,
+            ),
+        }
+    }
+}
+
+// `geometries`, default polyline6
+impl GeometryFormats for OptimizationInput {
+    fn output_format(&self) -> Result<GeometryFormat> {
+        resolve(
+            &[("geometries", &self.geometries)],
+            GeometryFormat::Polyline6,
+        )
+    }
+}
+
+// `geometry`, default polyline
+impl GeometryFormats for PostTripRouteInput {
+    fn output_format(&self) -> Result<GeometryFormat> {
+        resolve(&[("geometry", &self.geometry)], GeometryFormat::Polyline)
+    }
+}
+
+// `geometry`, default polyline. snap does not support polyline6
+impl GeometryFormats for SnapInput {
+    fn output_format(&self) -> Result<GeometryFormat> {
+        let format = resolve(&[("geometry", &self.geometry)], GeometryFormat::Polyline)?;
+        if format == GeometryFormat::Polyline6 {
+            bail!(
+                "invalid geometry: polyline6 is not supported by snap, expected polyline|geojson"
+            );
+        }
+        Ok(format)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_resolve() {
+        let unset: Option<String> = None;
+        let empty = Some("".to_string());
+        let polyline5 = Some("Polyline5".to_string());
+        assert_eq!(
+            resolve(&[("a", &unset), ("b", &empty)], GeometryFormat::GeoJSON).unwrap(),
+            GeometryFormat::GeoJSON
+        );
+        assert_eq!(
+            resolve(&[("a", &unset), ("b", &polyline5)], GeometryFormat::GeoJSON).unwrap(),
+            GeometryFormat::Polyline
+        );
+        assert_eq!(
+            resolve(&[("a", &Some("wkt".to_string()))], GeometryFormat::Polyline)
+                .err()
+                .unwrap()
+                .to_string(),
+            "invalid a: unknown geometry format 'wkt', expected one of polyline|polyline6|geojson"
+        );
+        assert_eq!(GeometryFormat::Polyline6.precision(), Some(6));
+        assert_eq!(GeometryFormat::GeoJSON.precision(), None);
+        assert_eq!(
+            GeometryFormat::Polyline.valhalla_shape_format(),
+            "polyline5"
+        );
+    }
+
+    #[test]
+    fn test_input_formats() {
+        let navigating: NavigatingInput = serde_json::from_value(json!({
+            "original_shape": "_p~iF~ps|U",
+            "original_shape_type": "polyline6",
+            "geometry": "geojson",
+        }))
+        .unwrap();
+        assert_eq!(
+            navigating.input_shape_format().unwrap(),
+            GeometryFormat::Polyline6
+        );
+        assert_eq!(navigating.output_format().unwrap(), GeometryFormat::GeoJSON);
+
+        // old version, the input geometry is in `geometry`
+        let navigating: NavigatingInput = serde_json::from_value(json!({
+            "geometry": "_p~iF~ps|U",
+        }))
+        .unwrap();
+        assert_eq!(
+            navigating.input_shape_format().unwrap(),
+            GeometryFormat::Polyline6
+        );
+        assert_eq!(
+            navigating.output_format().unwrap(),
+            GeometryFormat::Polyline
+        );
+
+        let optimization: OptimizationInput = serde_json::from_value(json!({
+            "coordinates": "1.3,103.8|1.29,103.85",
+        }))
+        .unwrap();
+        assert_eq!(
+            optimization.output_format().unwrap(),
+            GeometryFormat::Polyline6
+        );
+
+        let snap: SnapInput = serde_json::from_value(json!({
+            "path": "1.3,103.8|1.29,103.85",
+            "geometry": "polyline6",
+        }))
+        .unwrap();
+        assert!(snap.output_format().is_err());
+    }
+}
@@ -0,0 +1,204 @@
+// hedging of latency sensitive engine calls. the primary call is started right away, when it
+//  is still running after the hedge delay a duplicate call is started and the first of both
+//  to succeed is returned, the other one being dropped (cancelled). a failure of one call
+//  waits for the other one, the primary failing before the delay fails right away as hedging
+//  is not meant to retry errors
+use crate::statsd::{MetricType, RegisterMetricInput, TrackCountInput, TypedTrackInput};
+use crate::Result;
+use std::collections::HashMap;
+use std::future::{poll_fn, Future};
+use std::sync::mpsc::SyncSender;
+use std::task::Poll;
+use std::time::Duration;
+
+pub const METRIC_HEDGED_CALLS: &str = "hedged_calls";
+pub const LABELNAME_OPERATION: &str = "operation";
+pub const LABELNAME_WINNER: &str = "winner";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HedgeWinner {
+    // the primary call answered before the hedge delay
+    NotHedged,
+    Primary,
+    Hedge,
+}
+
+impl HedgeWinner {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HedgeWinner::NotHedged => "not_hedged",
+            HedgeWinner::Primary => "primary",
+            HedgeWinner::Hedge => "hedge",
+        }
+    }
+}
+
+pub struct Hedger {
+    delay: Duration,
+    sender: Option<SyncSender<TypedTrackInput>>,
+}
+
+impl Hedger {
+    pub fn new(delay: Duration) -> Hedger {
+        Hedger {
+            delay,
+            sender: None,
+        }
+    }
+
+    // with_sender tracks the winner of every successful call with the statsd collector
+    pub fn with_sender(mut self, sender: SyncSender<TypedTrackInput>) -> Hedger {
+        self.sender = Some(sender);
+        self
+    }
+
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+
+    // run calls `make` once for the primary call and once more for the hedge if needed
+    pub async fn run<F, Fut, T>(&self, operation: &str, make: F) -> Result<T>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let (v, winner) = self.run_with_winner(make).await?;
+        self.track(operation, winner);
+        Ok(v)
+    }
+
+    pub async fn run_with_winner<F, Fut, T>(&self, make: F) -> Result<(T, HedgeWinner)>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut primary = Box::pin(make());
+        let mut timer = Box::pin(tokio::time::sleep(self.delay));
+        let early = poll_fn(|cx| {
+            if let Poll::Ready(r) = primary.as_mut().poll(cx) {
+                return Poll::Ready(Some(r));
+            }
+            if timer.as_mut().poll(cx).is_ready() {
+                return Poll::Ready(None);
+            }
+            Poll::Pending
+        })
+        .await;
+        if let Some(r) = early {
+            return r.map(|v| (v, HedgeWinner::NotHedged));
+        }
+
+        let mut primary = Some(primary);
+        let mut hedge = Some(Box::pin(make()));
+        let mut last_err = None;
+        poll_fn(|cx| {
+            if let Some(f) = primary.as_mut() {
+                if let Poll::Ready(r) = f.as_mut().poll(cx) {
+                    primary = None;
+                    match r {
+                        Ok(v) => return Poll::Ready(Ok((v, HedgeWinner::Primary))),
+                        Err(e) => last_err = Some(e),
+                    }
+                }
+            }
+            if let Some(f) = hedge.as_mut() {
+                if let Poll::Ready(r) = f.as_mut().poll(cx) {
+                    hedge = None;
+                    match r {
+                        Ok(v) => return Poll::Ready(Ok((v, HedgeWinner::Hedge))),
+                        Err(e) => last_err = Some(e),
+                    }
+                }
+            }
+            if primary.is_none() && hedge.is_none() {
+                if let Some(e) = last_err.take() {
+                    return Poll::Ready(Err(e));
+                }
+            }
+            Poll::Pending
+        })
+        .await
+    }
+
+    fn track(&self, operation: &str, winner: HedgeWinner) {
+        if let Some(sender) = self.sender.as_ref() {
+            let mut labels = HashMap::new();
+            labels.insert(LABELNAME_OPERATION.to_string(), operation.to_string());
+            labels.insert(LABELNAME_WINNER.to_string(), winner.name().to_string());
+            let r = sender.try_send(TypedTrackInput::Counter(TrackCountInput {
+                metric_name: METRIC_HEDGED_CALLS.to_string(),
+                count: 1.0,
+                labels,
+            }));
+            if let Err(e) = r {
+                warn!("failed to track hedged call due to {:?}", e);
+            }
+        }
+    }
+}
+
+pub fn hedge_metric() -> RegisterMetricInput {
+    RegisterMetricInput {
+        metric_type: MetricType::Counter,
+        metric_name: METRIC_HEDGED_CALLS.to_string(),
+        metric_desc: "number of successful hedged calls by winner".to_string(),
+        labels: vec![
+            LABELNAME_OPERATION.to_string(),
+            LABELNAME_WINNER.to_string(),
+        ],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::mpsc::sync_channel;
+
+    // the n-th call made sleeps delays[n] milliseconds then returns n, or fails for n in fails
+    async fn call(calls: &AtomicUsize, delays: &[u64], fails: &[usize]) -> Result<usize> {
+        let n = calls.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(delays[n])).await;
+        if fails.contains(&n) {
+            bail!("call {} failed", n);
+        }
+        Ok(n)
+    }
+
+    #[actix_rt::test]
+    async fn test_hedger() {
+        let (tx, rx) = sync_channel(10);
+        let h = Hedger::new(Duration::from_millis(50)).with_sender(tx);
+
+        let calls = AtomicUsize::new(0);
+        let r = h
+            .run_with_winner(|| call(&calls, &[10], &[]))
+            .await
+            .unwrap();
+        assert_eq!(r, (0, HedgeWinner::NotHedged));
+
+        let calls = AtomicUsize::new(0);
+        let r = h
+            .run_with_winner(|| call(&calls, &[1000, 10], &[]))
+            .await
+            .unwrap();
+        assert_eq!(r, (1, HedgeWinner::Hedge));
+
+        let calls = AtomicUsize::new(0);
+        let r = h
+            .run_with_winner(|| call(&calls, &[100, 10], &[1]))
+            .await
+            .unwrap();
+        assert_eq!(r, (0, HedgeWinner::Primary));
+
+        let calls = AtomicUsize::new(0);
+        let r = h
+            .run("directions", || call(&calls, &[100, 10], &[0, 1]))
+            .await;
+        assert_eq!(r.err().unwrap().to_string(), "call 0 failed");
+        let calls = AtomicUsize::new(0);
+        let r = h.run("directions", || call(&calls, &[10], &[])).await;
+        assert_eq!(r.unwrap(), 0);
+        assert_eq!(rx.try_iter().count(), 1);
+    }
+}
@@ -0,0 +1,65 @@
+// HttpClient is the GET the loaders of remote files go through, so that tests can replace the
+//  network, see replay. reqwest::Client is the client used in production
+use crate::mdm_io::StoreFuture;
+use std::collections::BTreeMap;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    // names are lowercase
+    pub headers: BTreeMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(&name.to_lowercase()).map(|v| v.as_str())
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+pub trait HttpClient: Sync {
+    // get returns the response whatever its status, errors are failures to get one
+    fn get<'a>(
+        &'a self,
+        url: &'a str,
+        headers: &'a [(&'a str, String)],
+    ) -> StoreFuture<'a, HttpResponse>;
+}
+
+impl HttpClient for reqwest::Client {
+    fn get<'a>(
+        &'a self,
+        url: &'a str,
+        headers: &'a [(&'a str, String)],
+    ) -> StoreFuture<'a, HttpResponse> {
+        Box::pin(async move {
+            let mut req = reqwest::Client::get(self, url);
+            for (name, value) in headers {
+                req = req.header(*name, value.as_str());
+            }
+            let resp = req.send().await?;
+            let status = resp.status().as_u16();
+            let headers = resp
+                .headers()
+                .iter()
+                .filter_map(|(k, v)| {
+                    Some((k.as_str().to_lowercase(), v.to_str().ok()?.to_string()))
+                })
+                .collect();
+            let body = resp.bytes().await?.to_vec();
+            Ok(HttpResponse {
+                status,
+                headers,
+                body,
+            })
+        })
+    }
+}
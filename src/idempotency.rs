@@ -0,0 +1,213 @@
+// detection of duplicate optimization job submissions, e.g. retried by clients after a network
+//  error. a submission is identified by the api key of the customer and the hash of its
+//  request, the first submission records the id of its job for the window and duplicates
+//  within the window get that id back instead of creating a new job
+use crate::def::OptimizationPostOutput;
+use crate::response_cache::request_hash;
+use crate::Result;
+#[cfg(feature = "redis")]
+use redis::aio::ConnectionManager;
+#[cfg(feature = "redis")]
+use redis::AsyncCommands;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+pub const DUPLICATE_SUBMISSION_MESSAGE: &str = "duplicate submission, returning the original job";
+
+// claims retried when the recorded submission keeps expiring between SET NX and GET
+#[cfg(feature = "redis")]
+const MAX_CLAIM_ATTEMPTS: usize = 3;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct IdempotencyKey {
+    pub customer: String,
+    pub request_hash: String,
+}
+
+impl IdempotencyKey {
+    // new hashes input without its key, which is the customer part of the idempotency key
+    pub fn new<T: Serialize>(customer: &str, endpoint: &str, input: &T) -> Result<IdempotencyKey> {
+        Ok(IdempotencyKey {
+            customer: customer.to_string(),
+            request_hash: request_hash(endpoint, input, &["key"])?,
+        })
+    }
+
+    pub fn as_string(&self) -> String {
+        format!("{}:{}", self.customer, self.request_hash)
+    }
+}
+
+pub type IdempotencyFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+pub trait IdempotencyStore: Sync {
+    // claim records id for key unless a submission was recorded within the window, returns the
+    //  id of that earlier submission if any
+    fn claim<'a>(
+        &'a self,
+        key: &'a IdempotencyKey,
+        id: &'a str,
+        window: Duration,
+    ) -> IdempotencyFuture<'a, Option<String>>;
+
+    // release forgets key, so that a submission failing after its claim can be retried
+    fn release<'a>(&'a self, key: &'a IdempotencyKey) -> IdempotencyFuture<'a, ()>;
+}
+
+// MemoryIdempotencyStore keeps submissions in memory, for tests and single replica services
+#[derive(Debug, Default)]
+pub struct MemoryIdempotencyStore {
+    // id and expiry by key
+    entries: Mutex<HashMap<IdempotencyKey, (String, Instant)>>,
+}
+
+impl MemoryIdempotencyStore {
+    pub fn new() -> MemoryIdempotencyStore {
+        MemoryIdempotencyStore::default()
+    }
+}
+
+impl IdempotencyStore for MemoryIdempotencyStore {
+    fn claim<'a>(
+        &'a self,
+        key: &'a IdempotencyKey,
+        id: &'a str,
+        window: Duration,
+    ) -> IdempotencyFuture<'a, Option<String>> {
+        Box::pin(async move {
+            let now = Instant::now();
+            let mut entries = self.entries.lock().unwrap();
+            entries.retain(|_, (_, expiry)| *expiry > now);
+            if let Some((original, _)) = entries.get(key) {
+                return Ok(Some(original.clone()));
+            }
+            entries.insert(key.clone(), (id.to_string(), now + window));
+            Ok(None)
+        })
+    }
+
+    fn release<'a>(&'a self, key: &'a IdempotencyKey) -> IdempotencyFuture<'a, ()> {
+        Box::pin(async move {
+            self.entries.lock().unwrap().remove(key);
+            Ok(())
+        })
+    }
+}
+
+// RedisIdempotencyStore shares submissions between replicas through redis
+#[cfg(feature = "redis")]
+#[derive(Clone)]
+pub struct RedisIdempotencyStore {
+    conn: ConnectionManager,
+    prefix: String,
+}
+
+#[cfg(feature = "redis")]
+impl RedisIdempotencyStore {
+    pub fn new(conn: ConnectionManager, prefix: &str) -> RedisIdempotencyStore {
+        RedisIdempotencyStore {
+            conn,
+            prefix: prefix.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "redis")]
+impl IdempotencyStore for RedisIdempotencyStore {
+    fn claim<'a>(
+        &'a self,
+        key: &'a IdempotencyKey,
+        id: &'a str,
+        window: Duration,
+    ) -> IdempotencyFuture<'a, Option<String>> {
+        Box::pin(async move {
+            let key = format!("{}{}", self.prefix, key.as_string());
+            let mut conn = self.conn.clone();
+            for _ in 0..MAX_CLAIM_ATTEMPTS {
+                let set: Option<String> = redis::cmd("SET")
+                    .arg(&key)
+                    .arg(id)
+                    .arg("NX")
+                    .arg("EX")
+                    .arg(window.as_secs().max(1))
+                    .query_async(&mut conn)
+                    .await?;
+                if set.is_some() {
+                    return Ok(None);
+                }
+                let original: Option<String> = conn.get(&key).await?;
+                // None when expired between both commands, the claim is tried again
+                if original.is_some() {
+                    return Ok(original);
+                }
+            }
+            bail!("failed to claim {}", key)
+        })
+    }
+
+    fn release<'a>(&'a self, key: &'a IdempotencyKey) -> IdempotencyFuture<'a, ()> {
+        Box::pin(async move {
+            let key = format!("{}{}", self.prefix, key.as_string());
+            let mut conn = self.conn.clone();
+            conn.del::<_, ()>(key).await?;
+            Ok(())
+        })
+    }
+}
+
+// duplicate_output is the output returned to a duplicate submission of the job `id`
+pub fn duplicate_output(id: &str) -> OptimizationPostOutput {
+    OptimizationPostOutput {
+        id: id.to_string(),
+        message: DUPLICATE_SUBMISSION_MESSAGE.to_string(),
+        status: "Ok".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::def::OptimizationV2PostInput;
+    use serde_json::json;
+
+    fn input(key: &str, vehicles: usize) -> OptimizationV2PostInput {
+        serde_json::from_value(json!({
+            "key": key,
+            "locations": {"id": 1, "location": ["1.3,103.8", "1.31,103.81"]},
+            "jobs": [{"id": 1, "location_index": 1}],
+            "vehicles": (0..vehicles).map(|i| json!({"id": i, "start_index": 0})).collect::<Vec<_>>(),
+        }))
+        .unwrap()
+    }
+
+    #[actix_rt::test]
+    async fn test_memory_idempotency_store() {
+        let store = MemoryIdempotencyStore::new();
+        let window = Duration::from_millis(50);
+        let a = IdempotencyKey::new("k1", "optimization", &input("k1", 1)).unwrap();
+        // same request with another key value in the input
+        let b = IdempotencyKey::new("k1", "optimization", &input("other", 1)).unwrap();
+        let c = IdempotencyKey::new("k1", "optimization", &input("k1", 2)).unwrap();
+        let d = IdempotencyKey::new("k2", "optimization", &input("k2", 1)).unwrap();
+        assert_eq!(a, b);
+
+        assert_eq!(store.claim(&a, "job-1", window).await.unwrap(), None);
+        assert_eq!(
+            store.claim(&b, "job-2", window).await.unwrap(),
+            Some("job-1".to_string())
+        );
+        assert_eq!(store.claim(&c, "job-3", window).await.unwrap(), None);
+        assert_eq!(store.claim(&d, "job-4", window).await.unwrap(), None);
+
+        store.release(&c).await.unwrap();
+        assert_eq!(store.claim(&c, "job-5", window).await.unwrap(), None);
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(store.claim(&a, "job-6", window).await.unwrap(), None);
+        assert_eq!(duplicate_output("job-6").id, "job-6");
+    }
+}
@@ -0,0 +1,366 @@
+use crate::def::{DirectionsOutput, Leg, MatrixOutput, SnapOutput};
+use crate::engine_response::MATRIX_UNREACHABLE_VALUE;
+use crate::statsd::{MetricType, RegisterMetricInput, TrackCountInput, TypedTrackInput};
+use crate::warnings::{Warning, Warnings};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::mpsc::SyncSender;
+
+pub const METRIC_OUTPUT_INTEGRITY_ISSUES: &str = "output_integrity_issues";
+pub const LABELNAME_ISSUE_KIND: &str = "issue_kind";
+pub const LABELNAME_OUTPUT: &str = "output";
+
+// sums of step distances may drift from the leg distance due to per-step rounding
+const DISTANCE_TOLERANCE_METERS: f64 = 5.0;
+const DISTANCE_TOLERANCE_RATIO: f64 = 0.01;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum OutputIssueKind {
+    // geometry is empty or missing while distance > 0
+    EmptyGeometry,
+    // sum of children distances differs from the parent distance
+    DistanceMismatch { expected: f64, actual: f64 },
+    NegativeValue { value: f64 },
+    // number of elements of a matrix row differs from the first row
+    RowLengthMismatch { expected: usize, actual: usize },
+}
+
+impl OutputIssueKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            OutputIssueKind::EmptyGeometry => "empty_geometry",
+            OutputIssueKind::DistanceMismatch { .. } => "distance_mismatch",
+            OutputIssueKind::NegativeValue { .. } => "negative_value",
+            OutputIssueKind::RowLengthMismatch { .. } => "row_length_mismatch",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct OutputIssue {
+    // location of the issue in the output, e.g. routes[0].legs[1]
+    pub path: String,
+    pub kind: OutputIssueKind,
+}
+
+impl fmt::Display for OutputIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            OutputIssueKind::EmptyGeometry => {
+                write!(f, "{}: empty geometry with positive distance", self.path)
+            }
+            OutputIssueKind::DistanceMismatch { expected, actual } => write!(
+                f,
+                "{}: distance {} does not match sum of parts {}",
+                self.path, expected, actual
+            ),
+            OutputIssueKind::NegativeValue { value } => {
+                write!(f, "{}: negative value {}", self.path, value)
+            }
+            OutputIssueKind::RowLengthMismatch { expected, actual } => write!(
+                f,
+                "{}: row has {} elements, expected {}",
+                self.path, actual, expected
+            ),
+        }
+    }
+}
+
+fn distance_matches(expected: f64, actual: f64) -> bool {
+    let diff = (expected - actual).abs();
+    diff <= DISTANCE_TOLERANCE_METERS || diff <= expected.abs() * DISTANCE_TOLERANCE_RATIO
+}
+
+fn is_empty_geometry(geometry: &Option<String>) -> bool {
+    geometry.as_ref().map(|g| g.is_empty()).unwrap_or(true)
+}
+
+fn validate_legs(path: &str, legs: &[Leg], issues: &mut Vec<OutputIssue>) {
+    for (leg_idx, leg) in legs.iter().enumerate() {
+        let leg_path = if path.is_empty() {
+            format!("legs[{}]", leg_idx)
+        } else {
+            format!("{}.legs[{}]", path, leg_idx)
+        };
+        if leg.distance.value < 0 || leg.duration.value < 0 {
+            issues.push(OutputIssue {
+                path: leg_path.clone(),
+                kind: OutputIssueKind::NegativeValue {
+                    value: leg.distance.value.min(leg.duration.value) as f64,
+                },
+            });
+        }
+        let steps = match leg.steps.as_ref() {
+            Some(v) if !v.is_empty() => v,
+            _ => continue,
+        };
+        let steps_distance: i64 = steps.iter().map(|s| s.distance.value).sum();
+        if !distance_matches(leg.distance.value as f64, steps_distance as f64) {
+            issues.push(OutputIssue {
+                path: leg_path.clone(),
+                kind: OutputIssueKind::DistanceMismatch {
+                    expected: leg.distance.value as f64,
+                    actual: steps_distance as f64,
+                },
+            });
+        }
+        for (step_idx, step) in steps.iter().enumerate() {
+            if step.distance.value > 0 && is_empty_geometry(&step.geometry) {
+                issues.push(OutputIssue {
+                    path: format!("{}.steps[{}]", leg_path, step_idx),
+                    kind: OutputIssueKind::EmptyGeometry,
+                });
+            }
+        }
+    }
+}
+
+pub fn validate_output(output: &DirectionsOutput) -> Vec<OutputIssue> {
+    let mut issues = vec![];
+    for (route_idx, route) in output.routes.iter().enumerate() {
+        let path = format!("routes[{}]", route_idx);
+        if route.distance < 0.0 || route.duration < 0.0 {
+            issues.push(OutputIssue {
+                path: path.clone(),
+                kind: OutputIssueKind::NegativeValue {
+                    value: route.distance.min(route.duration),
+                },
+            });
+        }
+        if route.distance > 0.0 && is_empty_geometry(&route.geometry) && route.geojson.is_none() {
+            issues.push(OutputIssue {
+                path: path.clone(),
+                kind: OutputIssueKind::EmptyGeometry,
+            });
+        }
+        let legs = match route.legs.as_ref() {
+            Some(v) if !v.is_empty() => v,
+            _ => continue,
+        };
+        let legs_distance: i64 = legs.iter().map(|l| l.distance.value).sum();
+        if !distance_matches(route.distance, legs_distance as f64) {
+            issues.push(OutputIssue {
+                path: path.clone(),
+                kind: OutputIssueKind::DistanceMismatch {
+                    expected: route.distance,
+                    actual: legs_distance as f64,
+                },
+            });
+        }
+        validate_legs(path.as_str(), legs, &mut issues);
+    }
+    issues
+}
+
+pub fn validate_matrix_output(output: &MatrixOutput) -> Vec<OutputIssue> {
+    let mut issues = vec![];
+    let expected = output.rows.first().map(|r| r.elements.len()).unwrap_or(0);
+    for (row_idx, row) in output.rows.iter().enumerate() {
+        if row.elements.len() != expected {
+            issues.push(OutputIssue {
+                path: format!("rows[{}]", row_idx),
+                kind: OutputIssueKind::RowLengthMismatch {
+                    expected,
+                    actual: row.elements.len(),
+                },
+            });
+        }
+        for (idx, e) in row.elements.iter().enumerate() {
+            for value in [e.duration.value, e.distance.value].iter() {
+                if *value < 0 && *value != MATRIX_UNREACHABLE_VALUE {
+                    issues.push(OutputIssue {
+                        path: format!("rows[{}].elements[{}]", row_idx, idx),
+                        kind: OutputIssueKind::NegativeValue {
+                            value: *value as f64,
+                        },
+                    });
+                }
+            }
+        }
+    }
+    issues
+}
+
+pub fn validate_snap_output(output: &SnapOutput) -> Vec<OutputIssue> {
+    let mut issues = vec![];
+    if let Some(legs) = output.legs.as_ref() {
+        if !legs.is_empty() {
+            let legs_distance: i64 = legs.iter().map(|l| l.distance.value).sum();
+            if !distance_matches(output.distance as f64, legs_distance as f64) {
+                issues.push(OutputIssue {
+                    path: "distance".to_string(),
+                    kind: OutputIssueKind::DistanceMismatch {
+                        expected: output.distance as f64,
+                        actual: legs_distance as f64,
+                    },
+                });
+            }
+            validate_legs("", legs, &mut issues);
+        }
+    }
+    let has_geometry = output
+        .geometry
+        .as_ref()
+        .map(|v| v.iter().any(|g| !is_empty_geometry(g)))
+        .unwrap_or(false);
+    if output.distance > 0 && !has_geometry && output.geojson.is_none() {
+        issues.push(OutputIssue {
+            path: "geometry".to_string(),
+            kind: OutputIssueKind::EmptyGeometry,
+        });
+    }
+    issues
+}
+
+pub fn integrity_metric() -> RegisterMetricInput {
+    RegisterMetricInput {
+        metric_type: MetricType::Counter,
+        metric_name: METRIC_OUTPUT_INTEGRITY_ISSUES.to_string(),
+        metric_desc: "number of integrity issues found in outputs".to_string(),
+        labels: vec![
+            LABELNAME_OUTPUT.to_string(),
+            LABELNAME_ISSUE_KIND.to_string(),
+        ],
+    }
+}
+
+// report_issues turns issues into warning messages and tracks them when a sender is configured
+fn report_issues(
+    output_name: &str,
+    issues: Vec<OutputIssue>,
+    warning: &mut Option<Vec<String>>,
+    sender: Option<&SyncSender<TypedTrackInput>>,
+) {
+    if issues.is_empty() {
+        return;
+    }
+    let mut counts = HashMap::<&'static str, f64>::new();
+    let mut warnings = Warnings::new();
+    for issue in issues.iter() {
+        *counts.entry(issue.kind.name()).or_insert(0.0) += 1.0;
+        warn!("{} integrity issue: {}", output_name, issue);
+        warnings.push(Warning::Integrity(issue.to_string()));
+    }
+    warnings.merge_into(warning);
+    if let Some(sender) = sender {
+        for (kind, count) in counts {
+            let mut labels = HashMap::new();
+            labels.insert(LABELNAME_OUTPUT.to_string(), output_name.to_string());
+            labels.insert(LABELNAME_ISSUE_KIND.to_string(), kind.to_string());
+            let r = sender.try_send(TypedTrackInput::Counter(TrackCountInput {
+                metric_name: METRIC_OUTPUT_INTEGRITY_ISSUES.to_string(),
+                count,
+                labels,
+            }));
+            if let Err(e) = r {
+                warn!("failed to track integrity issues due to {:?}", e);
+            }
+        }
+    }
+}
+
+// check_output runs validate_output when debug is enabled and records issues in warning
+pub fn check_output(
+    output: &mut DirectionsOutput,
+    debug: bool,
+    sender: Option<&SyncSender<TypedTrackInput>>,
+) {
+    if !debug {
+        return;
+    }
+    let issues = validate_output(output);
+    report_issues("directions", issues, &mut output.warning, sender);
+}
+
+pub fn check_matrix_output(
+    output: &mut MatrixOutput,
+    debug: bool,
+    sender: Option<&SyncSender<TypedTrackInput>>,
+) {
+    if !debug {
+        return;
+    }
+    let issues = validate_matrix_output(output);
+    report_issues("matrix", issues, &mut output.warning, sender);
+}
+
+pub fn check_snap_output(
+    output: &mut SnapOutput,
+    debug: bool,
+    sender: Option<&SyncSender<TypedTrackInput>>,
+) {
+    if !debug {
+        return;
+    }
+    let issues = validate_snap_output(output);
+    report_issues("snap", issues, &mut output.warning, sender);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn step(distance: i64, geometry: Option<&str>) -> serde_json::Value {
+        json!({
+            "geometry": geometry,
+            "start_location": {"latitude": 1.0, "longitude": 103.0},
+            "end_location": {"latitude": 1.0, "longitude": 103.0},
+            "distance": {"value": distance},
+            "duration": {"value": 1},
+        })
+    }
+
+    #[test]
+    fn test_validate_output() {
+        let mut output: DirectionsOutput = serde_json::from_value(json!({
+            "status": "Ok",
+            "routes": [{
+                "geometry": "",
+                "distance": 300.0,
+                "duration": 30.0,
+                "legs": [{
+                    "distance": {"value": 300},
+                    "duration": {"value": 30},
+                    "steps": [step(100, Some("abc")), step(100, None)],
+                }],
+            }],
+        }))
+        .unwrap();
+
+        let issues = validate_output(&output);
+        assert_eq!(issues.len(), 3);
+        assert_eq!(issues[0].path, "routes[0]");
+        assert_eq!(issues[0].kind, OutputIssueKind::EmptyGeometry);
+        assert_eq!(issues[1].path, "routes[0].legs[0]");
+        assert_eq!(
+            issues[1].kind,
+            OutputIssueKind::DistanceMismatch {
+                expected: 300.0,
+                actual: 200.0
+            }
+        );
+        assert_eq!(issues[2].path, "routes[0].legs[0].steps[1]");
+
+        check_output(&mut output, false, None);
+        assert!(output.warning.is_none());
+        check_output(&mut output, true, None);
+        assert_eq!(output.warning.unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_validate_matrix_output() {
+        let output: MatrixOutput = serde_json::from_value(json!({
+            "status": "Ok",
+            "rows": [
+                {"elements": [{"duration": {"value": 1}, "distance": {"value": -1}}, {"duration": {"value": -2}, "distance": {"value": 1}}]},
+                {"elements": [{"duration": {"value": 1}, "distance": {"value": 1}}]},
+            ],
+        }))
+        .unwrap();
+        let issues = validate_matrix_output(&output);
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].path, "rows[0].elements[1]");
+        assert_eq!(issues[1].path, "rows[1]");
+    }
+}
@@ -0,0 +1,177 @@
+// interned names of areas and services, which find_area, map_mode and the polygon store hand
+//  out on every request. cloning a Name increments a reference count instead of allocating,
+//  and the same name loaded from different configs shares one allocation. names are
+//  serialized as plain strings
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Borrow;
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::{Arc, Mutex};
+
+lazy_static! {
+    // names come from configs, so the set stays small
+    static ref NAMES: Mutex<HashSet<Arc<str>>> = Mutex::new(HashSet::new());
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Name(Arc<str>);
+
+// intern returns the Name of s, allocated on the first call only
+pub fn intern(s: &str) -> Name {
+    let mut names = NAMES.lock().unwrap();
+    if let Some(v) = names.get(s) {
+        return Name(v.clone());
+    }
+    let v: Arc<str> = Arc::from(s);
+    names.insert(v.clone());
+    Name(v)
+}
+
+impl Name {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    // ptr_eq tells if both names share their allocation
+    pub fn ptr_eq(&self, other: &Name) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Default for Name {
+    fn default() -> Self {
+        intern("")
+    }
+}
+
+impl Deref for Name {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Name {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for Name {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&*self.0, f)
+    }
+}
+
+impl fmt::Debug for Name {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl From<&str> for Name {
+    fn from(s: &str) -> Self {
+        intern(s)
+    }
+}
+
+impl From<String> for Name {
+    fn from(s: String) -> Self {
+        intern(&s)
+    }
+}
+
+impl From<&String> for Name {
+    fn from(s: &String) -> Self {
+        intern(s)
+    }
+}
+
+impl From<Name> for String {
+    fn from(n: Name) -> Self {
+        n.0.to_string()
+    }
+}
+
+impl PartialEq<str> for Name {
+    fn eq(&self, other: &str) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Name {
+    fn eq(&self, other: &&str) -> bool {
+        &*self.0 == *other
+    }
+}
+
+impl PartialEq<String> for Name {
+    fn eq(&self, other: &String) -> bool {
+        &*self.0 == other.as_str()
+    }
+}
+
+impl PartialEq<Name> for str {
+    fn eq(&self, other: &Name) -> bool {
+        self == &*other.0
+    }
+}
+
+impl PartialEq<Name> for &str {
+    fn eq(&self, other: &Name) -> bool {
+        *self == &*other.0
+    }
+}
+
+impl PartialEq<Name> for String {
+    fn eq(&self, other: &Name) -> bool {
+        self.as_str() == &*other.0
+    }
+}
+
+impl Serialize for Name {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Name {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        Ok(intern(&s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_intern() {
+        let a = intern("singapore");
+        let b: Name = "singapore".to_string().into();
+        assert!(a.ptr_eq(&b));
+        assert_eq!(a, "singapore");
+        assert_eq!("singapore".to_string(), a);
+        assert_eq!(format!("{} {:?}", a, a), "singapore \"singapore\"");
+        assert!(!a.ptr_eq(&intern("india")));
+
+        let mut mappings: BTreeMap<String, Name> = BTreeMap::new();
+        mappings.insert("car".to_string(), intern("4w"));
+        let json = serde_json::to_string(&mappings).unwrap();
+        assert_eq!(json, r#"{"car":"4w"}"#);
+        let parsed: BTreeMap<String, Name> = serde_json::from_str(&json).unwrap();
+        assert!(parsed["car"].ptr_eq(&mappings["car"]));
+        let names: HashSet<Name> = vec![a.clone()].into_iter().collect();
+        assert!(names.contains("singapore"));
+    }
+}
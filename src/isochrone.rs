@@ -0,0 +1,274 @@
+// contours of isochrone requests. IsochroneInput (`times`/`distances`, `strokes`, `opacities`)
+//  and ISOChroneValhallaInput (`contours_minutes`/`contours_meters`, `contours_colors`) are
+//  both normalized into a ContourSpec: one metric, values sorted ascending and deduplicated,
+//  with the color and opacity of each value kept aligned with it
+use crate::def::{ISOChroneValhallaInput, IsochroneInput};
+use crate::Result;
+
+// valhalla computes at most 4 contours per request
+pub const MAX_CONTOURS: usize = 4;
+pub const MAX_CONTOUR_MINUTES: u32 = 120;
+pub const MAX_CONTOUR_METERS: u32 = 100_000;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ContourMetric {
+    Minutes,
+    Meters,
+}
+
+impl ContourMetric {
+    pub fn max_value(&self) -> u32 {
+        match self {
+            ContourMetric::Minutes => MAX_CONTOUR_MINUTES,
+            ContourMetric::Meters => MAX_CONTOUR_METERS,
+        }
+    }
+
+    // valhalla_key is the key of the contour value in valhalla requests
+    pub fn valhalla_key(&self) -> &'static str {
+        match self {
+            ContourMetric::Minutes => "time",
+            ContourMetric::Meters => "distance",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Contour {
+    pub value: u32,
+    // hex color without `#`, lowercase
+    pub color: Option<String>,
+    // range: [0, 1], 0 for transparent
+    pub opacity: Option<f64>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContourSpec {
+    pub metric: ContourMetric,
+    pub contours: Vec<Contour>,
+}
+
+// items of contour lists are separated by `,` or `|`, empty items are ignored
+fn split_items(input: &Option<String>) -> Vec<&str> {
+    match input.as_deref() {
+        Some(v) => v
+            .split([',', '|'])
+            .map(|i| i.trim())
+            .filter(|i| !i.is_empty())
+            .collect(),
+        None => vec![],
+    }
+}
+
+fn parse_color(name: &str, item: &str) -> Result<String> {
+    let color = item.trim_start_matches('#');
+    if color.len() != 6 || !color.chars().all(|c| c.is_ascii_hexdigit()) {
+        bail!(
+            "invalid {} '{}', expected a hex color such as ff0000",
+            name,
+            item
+        );
+    }
+    Ok(color.to_lowercase())
+}
+
+fn parse_opacity(name: &str, item: &str) -> Result<f64> {
+    let opacity: f64 = item
+        .parse()
+        .map_err(|_| format!("invalid {} '{}', expected a number", name, item))?;
+    if !(0.0..=1.0).contains(&opacity) {
+        bail!("invalid {} '{}', expected a value in [0, 1]", name, item);
+    }
+    Ok(opacity)
+}
+
+// aligned checks that the list is empty or has one item per contour value
+fn aligned<T>(name: &str, items: Vec<T>, values: &str, count: usize) -> Result<Vec<Option<T>>> {
+    if items.is_empty() {
+        return Ok((0..count).map(|_| None).collect());
+    }
+    if items.len() != count {
+        bail!(
+            "{} has {} items but {} has {}, expected one per contour",
+            name,
+            items.len(),
+            values,
+            count
+        );
+    }
+    Ok(items.into_iter().map(Some).collect())
+}
+
+impl ContourSpec {
+    // parse builds the spec of exactly one of minutes and meters, fields are given as
+    //  (name, value) so that errors refer to the fields of the request
+    pub fn parse(
+        minutes: (&str, &Option<String>),
+        meters: (&str, &Option<String>),
+        colors: (&str, &Option<String>),
+        opacities: (&str, &Option<String>),
+    ) -> Result<ContourSpec> {
+        let (name, metric, items) = match (split_items(minutes.1), split_items(meters.1)) {
+            (m, d) if !m.is_empty() && !d.is_empty() => {
+                bail!("{} and {} cannot be used together", minutes.0, meters.0)
+            }
+            (m, _) if !m.is_empty() => (minutes.0, ContourMetric::Minutes, m),
+            (_, d) if !d.is_empty() => (meters.0, ContourMetric::Meters, d),
+            _ => bail!("one of {} and {} is required", minutes.0, meters.0),
+        };
+        let mut values = vec![];
+        for item in items.iter() {
+            let value: u32 = item
+                .parse()
+                .map_err(|_| format!("invalid {} '{}', expected a whole number", name, item))?;
+            if value == 0 || value > metric.max_value() {
+                bail!(
+                    "invalid {} '{}', expected a value in [1, {}]",
+                    name,
+                    item,
+                    metric.max_value()
+                );
+            }
+            values.push(value);
+        }
+        let parsed_colors = split_items(colors.1)
+            .iter()
+            .map(|c| parse_color(colors.0, c))
+            .collect::<Result<Vec<String>>>()?;
+        let colors = aligned(colors.0, parsed_colors, name, values.len())?;
+        let parsed_opacities = split_items(opacities.1)
+            .iter()
+            .map(|o| parse_opacity(opacities.0, o))
+            .collect::<Result<Vec<f64>>>()?;
+        let opacities = aligned(opacities.0, parsed_opacities, name, values.len())?;
+
+        let mut contours: Vec<Contour> = values
+            .into_iter()
+            .zip(colors)
+            .zip(opacities)
+            .map(|((value, color), opacity)| Contour {
+                value,
+                color,
+                opacity,
+            })
+            .collect();
+        // the first occurrence of a value keeps its color and opacity
+        contours.sort_by_key(|c| c.value);
+        contours.dedup_by_key(|c| c.value);
+        if contours.len() > MAX_CONTOURS {
+            bail!(
+                "{} has {} distinct values, at most {} are supported",
+                name,
+                contours.len(),
+                MAX_CONTOURS
+            );
+        }
+        Ok(ContourSpec { metric, contours })
+    }
+
+    pub fn values(&self) -> Vec<u32> {
+        self.contours.iter().map(|c| c.value).collect()
+    }
+}
+
+// ContourInput gives the normalized contours of an isochrone input
+pub trait ContourInput {
+    fn contour_spec(&self) -> Result<ContourSpec>;
+}
+
+impl ContourInput for IsochroneInput {
+    fn contour_spec(&self) -> Result<ContourSpec> {
+        ContourSpec::parse(
+            ("times", &self.times),
+            ("distances", &self.distances),
+            ("strokes", &self.strokes),
+            ("opacities", &self.opacities),
+        )
+    }
+}
+
+impl ContourInput for ISOChroneValhallaInput {
+    fn contour_spec(&self) -> Result<ContourSpec> {
+        ContourSpec::parse(
+            ("contours_minutes", &self.contours_minutes),
+            ("contours_meters", &self.contours_meters),
+            ("contours_colors", &self.contours_colors),
+            ("opacities", &None),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::openapi::ApiExample;
+    use serde_json::json;
+
+    #[test]
+    fn test_contour_spec() {
+        let input: IsochroneInput = serde_json::from_value(json!({
+            "center": "1.3,103.8",
+            "times": "30|10|30|20",
+            "strokes": "#FF0000|00ff00|0000ff|ffffff",
+            "opacities": "0.5|0.1|0.9|0.2",
+        }))
+        .unwrap();
+        let spec = input.contour_spec().unwrap();
+        assert_eq!(spec.metric, ContourMetric::Minutes);
+        assert_eq!(spec.values(), vec![10, 20, 30]);
+        assert_eq!(
+            spec.contours[2],
+            Contour {
+                value: 30,
+                color: Some("ff0000".to_string()),
+                opacity: Some(0.5),
+            }
+        );
+
+        let input: ISOChroneValhallaInput = serde_json::from_value(json!({
+            "coordinates": "1.3,103.8",
+            "contours_meters": "1000,500",
+        }))
+        .unwrap();
+        let spec = input.contour_spec().unwrap();
+        assert_eq!(spec.metric, ContourMetric::Meters);
+        assert_eq!(spec.values(), vec![500, 1000]);
+        assert_eq!(spec.contours[0].color, None);
+
+        // times are in minutes
+        let spec = IsochroneInput::example().contour_spec().unwrap();
+        assert_eq!(spec.values(), vec![10, 20]);
+    }
+
+    #[test]
+    fn test_invalid_contours() {
+        let message = |input: serde_json::Value| {
+            let input: ISOChroneValhallaInput = serde_json::from_value(input).unwrap();
+            input.contour_spec().err().unwrap().to_string()
+        };
+        assert_eq!(
+            message(
+                json!({"coordinates": "1.3,103.8", "contours_minutes": "10", "contours_meters": "100"})
+            ),
+            "contours_minutes and contours_meters cannot be used together"
+        );
+        assert_eq!(
+            message(json!({"coordinates": "1.3,103.8", "contours_minutes": ""})),
+            "one of contours_minutes and contours_meters is required"
+        );
+        assert_eq!(
+            message(json!({"coordinates": "1.3,103.8", "contours_minutes": "10,200"})),
+            "invalid contours_minutes '200', expected a value in [1, 120]"
+        );
+        assert_eq!(
+            message(
+                json!({"coordinates": "1.3,103.8", "contours_minutes": "10,20", "contours_colors": "ff0000"})
+            ),
+            "contours_colors has 1 items but contours_minutes has 2, expected one per contour"
+        );
+        assert_eq!(
+            message(json!({"coordinates": "1.3,103.8", "contours_minutes": "1,2,3,4,5"})),
+            "contours_minutes has 5 distinct values, at most 4 are supported"
+        );
+    }
+}
@@ -0,0 +1,527 @@
+// queue of long running jobs (optimization, massive matrix chunks) shared by the services
+//  submitting them and the workers running them. a worker claims a queued job for a lease and
+//  keeps it with heartbeats, a running job whose lease expired is claimed again by the next
+//  worker, counting one more attempt. payloads and results are the input and output structs
+//  of the jobs serialized as JSON
+use crate::Result;
+#[cfg(feature = "redis")]
+use redis::aio::ConnectionManager;
+#[cfg(feature = "redis")]
+use redis::{AsyncCommands, Script};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const JOB_KIND_OPTIMIZATION: &str = "optimization";
+pub const JOB_KIND_MDM: &str = "mdm";
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum JobState {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct JobRecord {
+    pub id: String,
+    pub kind: String,
+    pub state: JobState,
+    pub payload: Value,
+    // number of claims, > 1 when a worker lost its lease
+    pub attempts: u32,
+    pub worker: Option<String>,
+    // unix timestamps in milliseconds
+    pub enqueued_at: i64,
+    pub lease_until: Option<i64>,
+    pub result: Option<Value>,
+    pub error: Option<String>,
+}
+
+impl JobRecord {
+    fn new(kind: &str, payload: Value) -> JobRecord {
+        let now = now_millis();
+        JobRecord {
+            id: format!("{:x}-{:08x}", now, JOB_SEQ.fetch_add(1, Ordering::Relaxed)),
+            kind: kind.to_string(),
+            state: JobState::Queued,
+            payload,
+            attempts: 0,
+            worker: None,
+            enqueued_at: now,
+            lease_until: None,
+            result: None,
+            error: None,
+        }
+    }
+
+    pub fn input<T: DeserializeOwned>(&self) -> Result<T> {
+        Ok(serde_json::from_value(self.payload.clone())?)
+    }
+
+    // output is None until the job completed
+    pub fn output<T: DeserializeOwned>(&self) -> Result<Option<T>> {
+        match self.result.as_ref() {
+            Some(v) => Ok(Some(serde_json::from_value(v.clone())?)),
+            None => Ok(None),
+        }
+    }
+
+    fn lease_expired(&self, now: i64) -> bool {
+        self.state == JobState::Running && self.lease_until.map(|l| l <= now).unwrap_or(true)
+    }
+
+    fn claim(&mut self, worker: &str, lease: Duration, now: i64) {
+        self.state = JobState::Running;
+        self.attempts += 1;
+        self.worker = Some(worker.to_string());
+        self.lease_until = Some(now + lease.as_millis() as i64);
+    }
+
+    fn check_worker(&self, worker: &str) -> Result<()> {
+        if self.state != JobState::Running || self.worker.as_deref() != Some(worker) {
+            bail!("job {} is not running on worker {}", self.id, worker);
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, result: std::result::Result<Value, String>) {
+        match result {
+            Ok(v) => {
+                self.state = JobState::Completed;
+                self.result = Some(v);
+            }
+            Err(e) => {
+                self.state = JobState::Failed;
+                self.error = Some(e);
+            }
+        }
+        self.lease_until = None;
+    }
+}
+
+static JOB_SEQ: AtomicU64 = AtomicU64::new(0);
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64
+}
+
+pub type JobFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+pub trait JobQueue: Sync {
+    // enqueue returns the id of the new job
+    fn enqueue<'a>(&'a self, kind: &'a str, payload: Value) -> JobFuture<'a, String>;
+    // claim returns the oldest queued job of kind, or a running one whose lease expired
+    fn claim<'a>(
+        &'a self,
+        kind: &'a str,
+        worker: &'a str,
+        lease: Duration,
+    ) -> JobFuture<'a, Option<JobRecord>>;
+    // heartbeat extends the lease of a job running on worker
+    fn heartbeat<'a>(&'a self, id: &'a str, worker: &'a str, lease: Duration) -> JobFuture<'a, ()>;
+    fn complete<'a>(&'a self, id: &'a str, worker: &'a str, result: Value) -> JobFuture<'a, ()>;
+    fn fail<'a>(&'a self, id: &'a str, worker: &'a str, error: &'a str) -> JobFuture<'a, ()>;
+    // get returns the job to poll its state or fetch its result
+    fn get<'a>(&'a self, id: &'a str) -> JobFuture<'a, Option<JobRecord>>;
+}
+
+// enqueue_input enqueues a job whose payload is input
+pub async fn enqueue_input<T: Serialize>(
+    queue: &dyn JobQueue,
+    kind: &str,
+    input: &T,
+) -> Result<String> {
+    queue.enqueue(kind, serde_json::to_value(input)?).await
+}
+
+// complete_output completes a job with output as result
+pub async fn complete_output<T: Serialize>(
+    queue: &dyn JobQueue,
+    id: &str,
+    worker: &str,
+    output: &T,
+) -> Result<()> {
+    queue
+        .complete(id, worker, serde_json::to_value(output)?)
+        .await
+}
+
+// MemoryJobQueue keeps the jobs in memory, for tests and local runs
+#[derive(Debug, Default)]
+pub struct MemoryJobQueue {
+    jobs: Mutex<HashMap<String, JobRecord>>,
+}
+
+impl MemoryJobQueue {
+    pub fn new() -> MemoryJobQueue {
+        MemoryJobQueue::default()
+    }
+
+    fn update<F>(&self, id: &str, worker: &str, f: F) -> Result<()>
+    where
+        F: FnOnce(&mut JobRecord),
+    {
+        let mut jobs = self.jobs.lock().unwrap();
+        let job = match jobs.get_mut(id) {
+            Some(v) => v,
+            None => bail!("job {} does not exist", id),
+        };
+        job.check_worker(worker)?;
+        f(job);
+        Ok(())
+    }
+}
+
+impl JobQueue for MemoryJobQueue {
+    fn enqueue<'a>(&'a self, kind: &'a str, payload: Value) -> JobFuture<'a, String> {
+        Box::pin(async move {
+            let job = JobRecord::new(kind, payload);
+            let id = job.id.clone();
+            self.jobs.lock().unwrap().insert(id.clone(), job);
+            Ok(id)
+        })
+    }
+
+    fn claim<'a>(
+        &'a self,
+        kind: &'a str,
+        worker: &'a str,
+        lease: Duration,
+    ) -> JobFuture<'a, Option<JobRecord>> {
+        Box::pin(async move {
+            let now = now_millis();
+            let mut jobs = self.jobs.lock().unwrap();
+            let job = jobs
+                .values_mut()
+                .filter(|j| j.kind == kind)
+                .filter(|j| j.state == JobState::Queued || j.lease_expired(now))
+                .min_by(|a, b| (a.enqueued_at, &a.id).cmp(&(b.enqueued_at, &b.id)));
+            Ok(job.map(|j| {
+                j.claim(worker, lease, now);
+                j.clone()
+            }))
+        })
+    }
+
+    fn heartbeat<'a>(&'a self, id: &'a str, worker: &'a str, lease: Duration) -> JobFuture<'a, ()> {
+        Box::pin(async move {
+            self.update(id, worker, |j| {
+                j.lease_until = Some(now_millis() + lease.as_millis() as i64)
+            })
+        })
+    }
+
+    fn complete<'a>(&'a self, id: &'a str, worker: &'a str, result: Value) -> JobFuture<'a, ()> {
+        Box::pin(async move { self.update(id, worker, |j| j.finish(Ok(result))) })
+    }
+
+    fn fail<'a>(&'a self, id: &'a str, worker: &'a str, error: &'a str) -> JobFuture<'a, ()> {
+        Box::pin(async move { self.update(id, worker, |j| j.finish(Err(error.to_string()))) })
+    }
+
+    fn get<'a>(&'a self, id: &'a str) -> JobFuture<'a, Option<JobRecord>> {
+        Box::pin(async move { Ok(self.jobs.lock().unwrap().get(id).cloned()) })
+    }
+}
+
+// claims the first running job whose lease expired, else pops the oldest queued job, and
+//  scores it with its new lease in the running set
+#[cfg(feature = "redis")]
+const CLAIM_SCRIPT: &str = r"
+local id = redis.call('ZRANGEBYSCORE', KEYS[1], '-inf', ARGV[1], 'LIMIT', 0, 1)[1]
+if not id then
+    id = redis.call('LPOP', KEYS[2])
+end
+if not id then
+    return false
+end
+redis.call('ZADD', KEYS[1], ARGV[2], id)
+return id
+";
+
+// stores a job record if it is still the one read, with its lease in the running set (none
+//  when the job is done)
+#[cfg(feature = "redis")]
+const UPDATE_SCRIPT: &str = r"
+if redis.call('GET', KEYS[1]) ~= ARGV[1] then
+    return 0
+end
+redis.call('SET', KEYS[1], ARGV[2])
+if ARGV[4] == '' then
+    redis.call('ZREM', KEYS[2], ARGV[3])
+else
+    redis.call('ZADD', KEYS[2], ARGV[4], ARGV[3])
+end
+return 1
+";
+
+// concurrent writes of a job record retried before giving up
+#[cfg(feature = "redis")]
+const MAX_UPDATE_ATTEMPTS: usize = 10;
+
+// RedisJobQueue stores jobs as JSON at `<prefix>job:<id>`, the ids of queued jobs in the list
+//  `<prefix>queue:<kind>` and the ids of running jobs in the sorted set `<prefix>running:<kind>`
+//  scored by the end of their lease. claims pick the id and lease it in a single script so a
+//  single worker gets a job, records are then written with a compare and set script
+#[cfg(feature = "redis")]
+#[derive(Clone)]
+pub struct RedisJobQueue {
+    conn: ConnectionManager,
+    prefix: String,
+    claim_script: Script,
+    update_script: Script,
+}
+
+#[cfg(feature = "redis")]
+impl RedisJobQueue {
+    pub fn new(conn: ConnectionManager, prefix: &str) -> RedisJobQueue {
+        RedisJobQueue {
+            conn,
+            prefix: prefix.to_string(),
+            claim_script: Script::new(CLAIM_SCRIPT),
+            update_script: Script::new(UPDATE_SCRIPT),
+        }
+    }
+
+    fn job_key(&self, id: &str) -> String {
+        format!("{}job:{}", self.prefix, id)
+    }
+
+    fn running_key(&self, kind: &str) -> String {
+        format!("{}running:{}", self.prefix, kind)
+    }
+
+    // load returns the job and its raw record, the expected value of update_record
+    async fn load(&self, id: &str) -> Result<Option<(JobRecord, String)>> {
+        let mut conn = self.conn.clone();
+        let value: Option<String> = conn.get(self.job_key(id)).await?;
+        match value {
+            Some(v) => Ok(Some((serde_json::from_str(&v)?, v))),
+            None => Ok(None),
+        }
+    }
+
+    // update_record stores job unless its record changed since it was read as `raw`
+    async fn update_record(&self, job: &JobRecord, raw: &str) -> Result<bool> {
+        let mut conn = self.conn.clone();
+        let until = job.lease_until.map(|v| v.to_string()).unwrap_or_default();
+        let updated: i64 = self
+            .update_script
+            .key(self.job_key(&job.id))
+            .key(self.running_key(&job.kind))
+            .arg(raw)
+            .arg(serde_json::to_string(job)?)
+            .arg(&job.id)
+            .arg(until)
+            .invoke_async(&mut conn)
+            .await?;
+        Ok(updated == 1)
+    }
+
+    async fn claim_id(&self, kind: &str, lease: Duration) -> Result<Option<String>> {
+        let mut conn = self.conn.clone();
+        let now = now_millis();
+        let id: Option<String> = self
+            .claim_script
+            .key(self.running_key(kind))
+            .key(format!("{}queue:{}", self.prefix, kind))
+            .arg(now)
+            .arg(now + lease.as_millis() as i64)
+            .invoke_async(&mut conn)
+            .await?;
+        Ok(id)
+    }
+
+    async fn update<F>(&self, id: &str, worker: &str, f: F) -> Result<JobRecord>
+    where
+        F: Fn(&mut JobRecord),
+    {
+        for _ in 0..MAX_UPDATE_ATTEMPTS {
+            let (mut job, raw) = match self.load(id).await? {
+                Some(v) => v,
+                None => bail!("job {} does not exist", id),
+            };
+            job.check_worker(worker)?;
+            f(&mut job);
+            if self.update_record(&job, &raw).await? {
+                return Ok(job);
+            }
+        }
+        bail!("job {} is updated concurrently", id)
+    }
+}
+
+#[cfg(feature = "redis")]
+impl JobQueue for RedisJobQueue {
+    fn enqueue<'a>(&'a self, kind: &'a str, payload: Value) -> JobFuture<'a, String> {
+        Box::pin(async move {
+            let job = JobRecord::new(kind, payload);
+            let queue = format!("{}queue:{}", self.prefix, kind);
+            let mut conn = self.conn.clone();
+            redis::pipe()
+                .atomic()
+                .set(self.job_key(&job.id), serde_json::to_string(&job)?)
+                .ignore()
+                .rpush(queue, &job.id)
+                .ignore()
+                .query_async::<()>(&mut conn)
+                .await?;
+            Ok(job.id)
+        })
+    }
+
+    fn claim<'a>(
+        &'a self,
+        kind: &'a str,
+        worker: &'a str,
+        lease: Duration,
+    ) -> JobFuture<'a, Option<JobRecord>> {
+        Box::pin(async move {
+            let id = match self.claim_id(kind, lease).await? {
+                Some(v) => v,
+                None => return Ok(None),
+            };
+            // the previous worker of an expired lease may still write the record
+            for _ in 0..MAX_UPDATE_ATTEMPTS {
+                let (mut job, raw) = match self.load(&id).await? {
+                    Some(v) => v,
+                    None => bail!("job {} does not exist", id),
+                };
+                if job.state == JobState::Completed || job.state == JobState::Failed {
+                    return Ok(None);
+                }
+                job.claim(worker, lease, now_millis());
+                if self.update_record(&job, &raw).await? {
+                    return Ok(Some(job));
+                }
+            }
+            bail!("job {} is updated concurrently", id)
+        })
+    }
+
+    fn heartbeat<'a>(&'a self, id: &'a str, worker: &'a str, lease: Duration) -> JobFuture<'a, ()> {
+        Box::pin(async move {
+            self.update(id, worker, |j| {
+                j.lease_until = Some(now_millis() + lease.as_millis() as i64)
+            })
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn complete<'a>(&'a self, id: &'a str, worker: &'a str, result: Value) -> JobFuture<'a, ()> {
+        Box::pin(async move {
+            self.update(id, worker, |j| j.finish(Ok(result.clone())))
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn fail<'a>(&'a self, id: &'a str, worker: &'a str, error: &'a str) -> JobFuture<'a, ()> {
+        Box::pin(async move {
+            self.update(id, worker, |j| j.finish(Err(error.to_string())))
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn get<'a>(&'a self, id: &'a str) -> JobFuture<'a, Option<JobRecord>> {
+        Box::pin(async move { Ok(self.load(id).await?.map(|(job, _)| job)) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::def::{MassiveDistanceMatrixInput, MassiveDistanceMatrixOutput};
+    use serde_json::json;
+
+    fn mdm_input(chunk_id: &str) -> MassiveDistanceMatrixInput {
+        serde_json::from_value(json!({
+            "matrix_input": {"origins": "1.3,103.8", "destinations": "1.4,103.9"},
+            "task_id": "t1",
+            "chunk_id": chunk_id,
+        }))
+        .unwrap()
+    }
+
+    #[actix_rt::test]
+    async fn test_memory_job_queue() {
+        let queue = MemoryJobQueue::new();
+        let lease = Duration::from_secs(60);
+        let first = enqueue_input(&queue, JOB_KIND_MDM, &mdm_input("0"))
+            .await
+            .unwrap();
+        let second = enqueue_input(&queue, JOB_KIND_MDM, &mdm_input("1"))
+            .await
+            .unwrap();
+        assert!(queue
+            .claim(JOB_KIND_OPTIMIZATION, "w1", lease)
+            .await
+            .unwrap()
+            .is_none());
+
+        let job = queue
+            .claim(JOB_KIND_MDM, "w1", lease)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(job.id, first);
+        assert_eq!(job.attempts, 1);
+        let input: MassiveDistanceMatrixInput = job.input().unwrap();
+        assert_eq!(input.chunk_id, "0");
+        assert!(queue.heartbeat(&first, "w2", lease).await.is_err());
+        queue.heartbeat(&first, "w1", lease).await.unwrap();
+        complete_output(
+            &queue,
+            &first,
+            "w1",
+            &MassiveDistanceMatrixOutput {
+                status: "Ok".to_string(),
+                warning: None,
+            },
+        )
+        .await
+        .unwrap();
+        let job = queue.get(&first).await.unwrap().unwrap();
+        assert_eq!(job.state, JobState::Completed);
+        let output: MassiveDistanceMatrixOutput = job.output().unwrap().unwrap();
+        assert_eq!(output.status, "Ok");
+
+        // w2 loses its lease, w3 takes the job over
+        let job = queue
+            .claim(JOB_KIND_MDM, "w2", Duration::from_millis(0))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(job.id, second);
+        let job = queue
+            .claim(JOB_KIND_MDM, "w3", lease)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(job.id, second);
+        assert_eq!(job.attempts, 2);
+        assert!(queue.fail(&second, "w2", "too late").await.is_err());
+        queue.fail(&second, "w3", "engine down").await.unwrap();
+        let job = queue.get(&second).await.unwrap().unwrap();
+        assert_eq!(job.state, JobState::Failed);
+        assert_eq!(job.error, Some("engine down".to_string()));
+        assert!(queue
+            .claim(JOB_KIND_MDM, "w1", lease)
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,94 @@
+// JSON Schemas of the public input and output types of def.rs, for partners who do not use
+//  the OpenAPI spec. the types derive schemars::JsonSchema when the json-schema feature is on,
+//  so the schemas follow their serde attributes and doc comments. nested types are listed in
+//  the definitions of each schema, which follows draft 07
+use crate::def::*;
+use crate::Result;
+use schemars::gen::SchemaSettings;
+use schemars::JsonSchema;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+pub const JSON_SCHEMA_DRAFT: &str = "http://json-schema.org/draft-07/schema#";
+
+pub fn schema_of<T: JsonSchema>() -> Result<Value> {
+    let schema = SchemaSettings::draft07()
+        .into_generator()
+        .into_root_schema_for::<T>();
+    Ok(serde_json::to_value(schema)?)
+}
+
+macro_rules! schemas {
+    ($($t:ty),* $(,)?) => {
+        vec![$((stringify!($t), schema_of::<$t>()?)),*]
+    };
+}
+
+// schemas returns the name and the schema of the main request and response types
+pub fn schemas() -> Result<Vec<(&'static str, Value)>> {
+    Ok(schemas![
+        DirectionsInput,
+        DirectionsOutput,
+        MatrixInput,
+        MatrixOutput,
+        MatrixConciseOutput,
+        MassiveDistanceMatrixInput,
+        MassiveDistanceMatrixOutput,
+        MassiveDistanceMatrixStatusInput,
+        MassiveDistanceMatrixStatusOutput,
+        SnapInput,
+        SnapOutput,
+        IsochroneInput,
+        IsochroneOutput,
+        NavigatingInput,
+        NavigatingOutput,
+        GetNearbyInput,
+        GetNearbyOutput,
+        OptimizationPostInput,
+        OptimizationPostOutput,
+        OptimizationGetInput,
+        OptimizationGetOutput,
+        PostTripRouteInput,
+        PostTripRouteOutput,
+        ClusteringPostInput,
+        ClusteringOutput,
+    ])
+}
+
+// generate_schemas writes <Type>.schema.json for every type of schemas() into dir, and
+//  returns the written paths
+pub fn generate_schemas(dir: &Path) -> Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir)?;
+    let mut paths = vec![];
+    for (name, schema) in schemas()? {
+        let path = dir.join(format!("{}.schema.json", name));
+        std::fs::write(&path, serde_json::to_string_pretty(&schema)?)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_schemas() {
+        let dir = std::env::temp_dir().join(format!("nbroutes-schemas-{}", std::process::id()));
+        let paths = generate_schemas(&dir).unwrap();
+        assert_eq!(paths.len(), schemas().unwrap().len());
+
+        let content = std::fs::read_to_string(dir.join("MatrixOutput.schema.json")).unwrap();
+        let schema: Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(schema["$schema"], JSON_SCHEMA_DRAFT);
+        assert_eq!(schema["title"], "MatrixOutput");
+        assert_eq!(schema["type"], "object");
+        // nested types are referenced from the definitions
+        assert_eq!(
+            schema["properties"]["rows"]["items"]["$ref"],
+            "#/definitions/Row"
+        );
+        assert_eq!(schema["definitions"]["Row"]["type"], "object");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
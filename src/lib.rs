@@ -1,25 +1,111 @@
+pub mod alternatives;
+pub mod audit;
+pub mod avoid;
+pub mod balancer;
+pub mod bearings;
+pub mod bounds;
+pub mod circuit_breaker;
+pub mod closures;
+pub mod budget;
+pub mod clustering;
+pub mod config_proto;
 pub mod coord;
+pub mod deadline;
 pub mod def;
+pub mod elevation;
+pub mod emissions;
+pub mod engine_policy;
+pub mod engine_request;
+pub mod engine_response;
+pub mod error;
+pub mod eta;
+pub mod feasibility;
+pub mod feature_flags;
+pub mod gcs;
+pub mod geofence;
+pub mod geometry_format;
+pub mod hedge;
+pub mod http_client;
+pub mod idempotency;
+pub mod integrity;
+pub mod intern;
+pub mod isochrone;
+pub mod jobs;
+#[cfg(feature = "json-schema")]
+pub mod json_schema;
 pub mod jwks;
+pub mod matrix_binary;
+#[cfg(feature = "grpc")]
+pub mod matrix_grpc;
+pub mod matrix_plan;
+pub mod matrix_stream;
+pub mod matrix_view;
+pub mod mdm_chunk;
+pub mod mdm_io;
+pub mod metering;
+pub mod nearby;
+pub mod notify;
+pub mod openapi;
 pub mod osrm_path;
 pub mod poly;
+pub mod polygon_check;
+pub mod polygon_pack;
+pub mod polygon_store;
+pub mod polyline;
+pub mod prepared_polygon;
+pub mod privacy;
 pub mod protos;
+#[cfg(any(test, feature = "testkit"))]
+pub mod replay;
+pub mod request_ctx;
+pub mod response_cache;
+pub mod road_info;
+pub mod setting_check;
+pub mod setting_fetcher;
+pub mod shadow;
+pub mod signed_url;
+pub mod simplify;
+pub mod solution_check;
+pub mod speed_compliance;
 pub mod statsd;
+pub mod stitch;
+pub mod strict;
+#[cfg(any(test, feature = "testkit"))]
+pub mod testkit;
+pub mod time_dependant_cache;
+pub mod timings;
+pub mod toll;
+pub mod travel_mode;
+pub mod trip_order;
+pub mod units;
 pub mod util;
+pub mod vroom;
+pub mod warnings;
 pub mod mdm_status;
 
 use chrono::prelude::*;
+use bounds::{bounding_circles, BoundingCircle};
 use def::{Engine, ValhallaError, OsrmError, AdaptError, EngineError};
 
-use crate::coord::{Coord, Locatable};
+use crate::coord::{nearest_point_on_area, Coord, Locatable};
+use crate::deadline::{check_deadline, run_with_deadline, Deadline};
+use crate::error::{NbroutesError, NbroutesResult};
+use crate::intern::Name;
 use crate::osrm_path::get_data_root;
 use crate::poly::load as load_poly;
+use crate::prepared_polygon::{prepare_polygons, PreparedPolygon};
+use crate::privacy::anonymize_in_area;
+use crate::setting_fetcher::SettingFetcher;
+use crate::simplify::simplify_area_polygons;
+use crate::time_dependant_cache::TimeDependantCache;
+use crate::travel_mode::TravelMode;
 use crate::util::load_maaas_area_config;
-use geo::Polygon;
-use reqwest;
+use futures::stream::{self, StreamExt};
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashMap, HashSet};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use util::Area;
 
 #[macro_use]
@@ -38,6 +124,30 @@ pub fn timestamp() -> i64 {
     now.duration_since(UNIX_EPOCH).unwrap().as_secs() as i64
 }
 
+lazy_static! {
+    static ref INSTANCE_ID: String = {
+        let mut bytes = [0u8; 4];
+        let _ = ring::rand::SecureRandom::fill(&ring::rand::SystemRandom::new(), &mut bytes);
+        let suffix: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        match std::env::var("HOSTNAME") {
+            Ok(host) if !host.is_empty() => {
+                let host: String = host
+                    .chars()
+                    .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+                    .collect();
+                format!("{}-{}", host, suffix)
+            }
+            _ => suffix,
+        }
+    };
+}
+
+// instance_id tells the replicas of a service apart in the names of the objects they write: the
+//  host name (the pod name on kubernetes) and a random suffix drawn at startup
+pub fn instance_id() -> &'static str {
+    INSTANCE_ID.as_str()
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Borders {
     pub area_list: Vec<Area>,
@@ -45,61 +155,216 @@ pub struct Borders {
 
 impl Borders {
     pub async fn populate_time_dependant_setting(&mut self, namespace: &Option<String>) {
-        for area_setting in self.area_list.iter_mut() {
-            if area_setting.time_dependant.is_none() {
-                continue;
+        if let Err(e) = self
+            .populate_time_dependant_setting_with_deadline(namespace, None)
+            .await
+        {
+            warn!("populate_time_dependant_setting fails due to {:?}", e);
+        }
+    }
+
+    // populate_time_dependant_setting_with_deadline stops fetching settings and returns
+    //  a TimedOut error once the deadline is exceeded
+    pub async fn populate_time_dependant_setting_with_deadline(
+        &mut self,
+        namespace: &Option<String>,
+        deadline: Option<&Deadline>,
+    ) -> Result<()> {
+        let cache = TimeDependantCache::new(Duration::from_secs(0));
+        self.populate_time_dependant_setting_with_cache(namespace, &cache, deadline)
+            .await
+    }
+
+    // populate_time_dependant_setting_with_fetcher reads the settings with fetcher instead of
+    //  the public bucket, e.g. from a local directory for on-prem deployments
+    pub async fn populate_time_dependant_setting_with_fetcher(
+        &mut self,
+        namespace: &Option<String>,
+        fetcher: Arc<dyn SettingFetcher + Send>,
+        deadline: Option<&Deadline>,
+    ) -> Result<()> {
+        let cache = TimeDependantCache::new(Duration::from_secs(0)).with_fetcher(fetcher);
+        self.populate_time_dependant_setting_with_cache(namespace, &cache, deadline)
+            .await
+    }
+
+    // populate_time_dependant_setting_with_cache only fetches the settings expired in cache,
+    //  keep the cache to refresh the settings later, see TimeDependantCache::refresh
+    pub async fn populate_time_dependant_setting_with_cache(
+        &mut self,
+        namespace: &Option<String>,
+        cache: &TimeDependantCache,
+        deadline: Option<&Deadline>,
+    ) -> Result<()> {
+        let ns = match namespace.as_ref() {
+            Some(v) => v.as_str(),
+            None => {
+                if self.area_list.iter().any(has_time_dependant) {
+                    warn!(
+                        "populate_time_dependant_setting fails since namespace is not configured"
+                    );
+                }
+                return Ok(());
             }
-            if namespace.is_none() {
-                warn!("populate_time_dependant_setting fails since namespace is not configured");
-                continue;
+        };
+
+        let fetches = time_dependant_fetches(&self.area_list, ns);
+        let results = run_with_deadline(
+            deadline,
+            "time dependant setting fetch",
+            stream::iter(fetches.iter())
+                .map(|f| async move {
+                    let r = cache.get(&f.namespace, &f.filename).await;
+                    (f, r)
+                })
+                .buffer_unordered(TIME_DEPENDANT_FETCH_CONCURRENCY)
+                .collect::<Vec<_>>(),
+        )
+        .await?;
+
+        // settings of areas without any fetched setting are reset to None as before
+        let mut settings: HashMap<(usize, bool), TimeDependantSettings> = HashMap::new();
+        for (f, r) in results {
+            match r {
+                Ok(setting) => {
+                    settings
+                        .entry((f.area, f.flexible))
+                        .or_default()
+                        .entry(f.mode.clone())
+                        .or_default()
+                        .insert(f.ctx.clone(), setting);
+                }
+                Err(e) => warn!(
+                    "populate_time_dependant_setting fails to get setting for filename {} due to {}",
+                    &f.filename, e
+                ),
+            }
+        }
+        for (i, area_setting) in self.area_list.iter_mut().enumerate() {
+            if area_setting.time_dependant.is_some() {
+                area_setting.time_dependant_settings = settings.remove(&(i, false));
             }
+            if let Some(flexible_setting) = area_setting.flexible_setting.as_mut() {
+                if flexible_setting.time_dependant.is_some() {
+                    flexible_setting.time_dependant_settings = settings.remove(&(i, true));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// concurrent fetches of time dependant settings, in flight at once
+const TIME_DEPENDANT_FETCH_CONCURRENCY: usize = 8;
 
-            let ns = namespace.as_ref().unwrap().as_str();
+type TimeDependantSettings = BTreeMap<String, BTreeMap<String, TimeDependantSetting>>;
 
-            let mut area_time_dependant =
-                BTreeMap::<String, BTreeMap<String, TimeDependantSetting>>::new();
-            for (mode, mode_setting) in area_setting.time_dependant.as_ref().unwrap() {
-                let mut mode_time_dependant = BTreeMap::<String, TimeDependantSetting>::new();
+#[derive(Debug)]
+struct TimeDependantFetch {
+    // index of the area in the area list
+    area: usize,
+    flexible: bool,
+    mode: String,
+    ctx: String,
+    namespace: String,
+    filename: String,
+}
 
+fn has_time_dependant(area: &Area) -> bool {
+    area.time_dependant.is_some()
+        || area
+            .flexible_setting
+            .as_ref()
+            .map(|f| f.time_dependant.is_some())
+            .unwrap_or(false)
+}
+
+// time_dependant_fetches lists the settings enabled in the areas. flexible settings live in
+//  their own sub namespace so that they never shadow the regular settings of the same
+//  area/mode/ctx
+fn time_dependant_fetches(areas: &[Area], ns: &str) -> Vec<TimeDependantFetch> {
+    let flexible_ns = format!("{}/flexible", ns);
+    let mut r = vec![];
+    for (i, area) in areas.iter().enumerate() {
+        let flexible = area
+            .flexible_setting
+            .as_ref()
+            .and_then(|f| f.time_dependant.as_ref());
+        let all = vec![
+            (ns, false, area.time_dependant.as_ref()),
+            (flexible_ns.as_str(), true, flexible),
+        ];
+        for (ns, is_flexible, time_dependant) in all {
+            for (mode, mode_setting) in time_dependant.into_iter().flatten() {
                 for (ctx, enabled) in mode_setting {
                     if !enabled {
                         continue;
                     }
-
-                    let mut filename = area_setting.name.to_owned();
+                    let mut filename = area.name.to_string();
                     if ctx.as_str() != "" {
                         filename = filename + "-" + ctx.as_str();
                     }
                     filename = filename + "-" + mode.as_str();
-
-                    let url = format!("https://storage.googleapis.com/static.nextbillion.io/nbroute/time_dependant_setting/{}/{}.yaml?{}", ns, filename.as_str(), timestamp());
-                    let maybe_resp = reqwest::get(url.as_str()).await;
-                    if maybe_resp.is_err() {
-                        warn!("populate_time_dependant_setting fails to get setting for filename {} due to {:?}", &filename, maybe_resp.err().unwrap());
-                        continue;
-                    }
-                    let maybe_body = maybe_resp.unwrap().text().await;
-                    if maybe_body.is_err() {
-                        warn!("populate_time_dependant_setting fails to get setting for filename {} due to {:?}", &filename, maybe_body.err().unwrap());
-                        continue;
-                    }
-                    let body = maybe_body.unwrap();
-                    let maybe_setting = serde_yaml::from_str(&body);
-                    if maybe_setting.is_err() {
-                        warn!("populate_time_dependant_setting fails to get setting for filename {} due to {:?}, contents: {}", &filename, maybe_setting.err().unwrap(), body.as_str());
-                        continue;
-                    }
-                    mode_time_dependant.insert(ctx.clone(), maybe_setting.unwrap());
-                }
-
-                if mode_time_dependant.len() > 0 {
-                    area_time_dependant.insert(mode.clone(), mode_time_dependant);
+                    r.push(TimeDependantFetch {
+                        area: i,
+                        flexible: is_flexible,
+                        mode: mode.clone(),
+                        ctx: ctx.clone(),
+                        namespace: ns.to_string(),
+                        filename,
+                    });
                 }
             }
+        }
+    }
+    r
+}
 
-            if area_time_dependant.len() > 0 {
-                area_setting.time_dependant_settings = Some(area_time_dependant);
-            }
+// SlotTime is a slot boundary in minutes since midnight. configs give it as an hour like `7`
+//  or as a time like `"07:30"`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SlotTime(pub u32);
+
+impl SlotTime {
+    pub fn from_hour(hour: u32) -> SlotTime {
+        SlotTime(hour * 60)
+    }
+
+    pub fn minutes(&self) -> u32 {
+        self.0
+    }
+
+    // parse reads `HH:MM`, up to 24:00
+    pub fn parse(s: &str) -> Option<SlotTime> {
+        let (hour, minute) = s.trim().split_once(':')?;
+        let (hour, minute) = (hour.parse::<u32>().ok()?, minute.parse::<u32>().ok()?);
+        if minute >= 60 || hour * 60 + minute > 24 * 60 {
+            return None;
+        }
+        Some(SlotTime(hour * 60 + minute))
+    }
+}
+
+impl From<u32> for SlotTime {
+    fn from(hour: u32) -> Self {
+        SlotTime::from_hour(hour)
+    }
+}
+
+impl<'de> Deserialize<'de> for SlotTime {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Hour(u32),
+            Time(String),
+        }
+        match Raw::deserialize(deserializer)? {
+            Raw::Hour(v) => Ok(SlotTime::from_hour(v)),
+            Raw::Time(v) => SlotTime::parse(&v)
+                .ok_or_else(|| serde::de::Error::custom(format!("invalid slot time {:?}", v))),
         }
     }
 }
@@ -107,7 +372,23 @@ impl Borders {
 #[derive(Deserialize, Clone, Debug)]
 pub struct DaysAheadSlotSetting {
     pub id: String,
-    pub range: Vec<u32>,
+    #[serde(default)]
+    pub range: Vec<SlotTime>,
+    // minutes since midnight, taking precedence over range when given
+    #[serde(default)]
+    pub range_minutes: Option<Vec<u32>>,
+}
+
+impl DaysAheadSlotSetting {
+    // minutes returns the start and end of the slot in minutes since midnight
+    pub fn minutes(&self) -> Option<(u32, u32)> {
+        match self.range_minutes.as_ref() {
+            Some(v) if v.len() >= 2 => Some((v[0], v[1])),
+            Some(_) => None,
+            None if self.range.len() >= 2 => Some((self.range[0].0, self.range[1].0)),
+            None => None,
+        }
+    }
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -226,8 +507,18 @@ impl TimeDependantSetting {
         let seconds_since_target_day = target_ts_since_today - (days_since_today * 86400);
         let target_day = &days_ahead_setting.days[days_since_today as usize];
         for slot in target_day.slots.iter() {
-            if seconds_since_target_day >= ((slot.range[0] * 3600) as i64)
-                && seconds_since_target_day <= ((slot.range[1] * 3600) as i64)
+            let (start, end) = match slot.minutes() {
+                Some(v) => v,
+                None => {
+                    warn!(
+                        "get_additional_ctx_days_ahead invalid slot range {:?}",
+                        &slot.range
+                    );
+                    continue;
+                }
+            };
+            if seconds_since_target_day >= (start * 60) as i64
+                && seconds_since_target_day <= (end * 60) as i64
             {
                 return Some(target_day.prefix.to_owned() + slot.id.as_str());
             }
@@ -262,14 +553,14 @@ impl TimeDependantSetting {
             target_local_time.day()
         );
         let target_weekday = target_local_time.weekday();
-        let target_hour = target_local_time.hour();
+        let target_minute = target_local_time.hour() * 60 + target_local_time.minute();
         debug!(
             "local time for ts {} is {:?} {} {}, {}",
             ts,
             &target_local_time,
             target_date.as_str(),
             target_weekday.number_from_monday() - 1,
-            target_hour
+            target_minute
         );
 
         for recurring_day in recurring_setting.days.iter() {
@@ -278,17 +569,20 @@ impl TimeDependantSetting {
                     continue;
                 }
                 for slot in recurring_day.slots.iter() {
-                    if slot.range.len() < 2 {
-                        warn!(
-                            "get_additional_ctx_recurring invalid slot range {:?}",
-                            &slot.range
-                        );
+                    let (start, end) = match slot.minutes() {
+                        Some(v) => v,
+                        None => {
+                            warn!(
+                                "get_additional_ctx_recurring invalid slot range {:?}",
+                                &slot.range
+                            );
+                            continue;
+                        }
+                    };
+                    if target_minute < start {
                         continue;
                     }
-                    if target_hour < slot.range[0] {
-                        continue;
-                    }
-                    if target_hour >= slot.range[1] {
+                    if target_minute >= end {
                         continue;
                     }
 
@@ -319,21 +613,63 @@ impl TimeDependantSetting {
 #[derive(Clone, Debug)]
 pub struct Service {
     pub area: Area,
-    pub mode: String,
+    pub mode: Name,
+    // coordinates moved into the area by find_service_with_nudge
+    pub adjustments: Vec<CoordAdjustment>,
+    // indices of the coordinates outside of the area, only with tolerate_outlier
+    pub outside_indices: Vec<usize>,
+    // area of each coordinate, set when some of the outside coordinates are in other areas
+    pub coord_areas: Option<Vec<Option<Name>>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CoordAdjustment {
+    pub index: usize,
+    pub original: Coord,
+    pub adjusted: Coord,
+    pub distance_meters: f64,
 }
 
+// nudged coordinates are moved this far past the border so that they are strictly inside, ~10cm
+const NUDGE_PAST_BORDER_DEGREES: f64 = 0.000001;
+
 pub fn find_area<'a>(
-    mode: &Option<String>,
+    mode: &Option<TravelMode>,
     coords: &Vec<Coord>,
-    polygons: &HashMap<String, Vec<Polygon<f64>>>,
+    polygons: &HashMap<String, Vec<PreparedPolygon>>,
     areas: &'a Vec<Area>,
     tolerate_outlier: bool,
     request_id: Option<&str>,
     is_flexible_request: bool,
-) -> Result<(&'a Area, String, Option<Vec<usize>>)> {
+) -> NbroutesResult<(&'a Area, Name, Option<Vec<usize>>)> {
+    find_area_with_bounds(
+        mode,
+        coords,
+        polygons,
+        None,
+        areas,
+        tolerate_outlier,
+        request_id,
+        is_flexible_request,
+    )
+}
+
+// find_area_with_bounds skips point-in-polygon for coordinates outside of the bounding circle
+//  of an area, bounds are returned by load_polygons_with_bounds
+#[allow(clippy::too_many_arguments)]
+pub fn find_area_with_bounds<'a>(
+    mode: &Option<TravelMode>,
+    coords: &Vec<Coord>,
+    polygons: &HashMap<String, Vec<PreparedPolygon>>,
+    bounds: Option<&HashMap<String, BoundingCircle>>,
+    areas: &'a Vec<Area>,
+    tolerate_outlier: bool,
+    request_id: Option<&str>,
+    is_flexible_request: bool,
+) -> NbroutesResult<(&'a Area, Name, Option<Vec<usize>>)> {
     let mut best_area = None;
     let mut best_coord_index = vec![];
-    let mut mapped_mode: Option<String> = None;
+    let mut mapped_mode: Option<Name> = None;
 
     // the following two vars are used to keep track of the most likely areas
     //  in case there is no perfect match; so we can help user identify what coordinate is invalid
@@ -347,12 +683,14 @@ pub fn find_area<'a>(
             continue;
         }
         let vs = vs.unwrap();
+        let circle = bounds.and_then(|b| b.get(area.name.as_str()));
 
         // coord_index stores the idx of coordinates that are in this area
         let mut coord_index = vec![];
         let mut missing_coords = vec![];
         for (idx, coord) in coords.iter().enumerate() {
-            if coord.is_in_polygons(vs) {
+            let may_contain = circle.map(|c| c.may_contain(coord)).unwrap_or(true);
+            if may_contain && coord.is_in_polygons(vs) {
                 coord_index.push(idx);
                 continue;
             }
@@ -389,7 +727,12 @@ pub fn find_area<'a>(
 
         warn!(
             "some coordinates are not in area {:?}, coords: {:?}, request_id: {:?}",
-            area.name, missing_coords, &request_id
+            area.name,
+            missing_coords
+                .iter()
+                .map(|c| anonymize_in_area(*c, area))
+                .collect::<Vec<_>>(),
+            &request_id
         );
 
         if !tolerate_outlier {
@@ -414,18 +757,15 @@ pub fn find_area<'a>(
         ));
     }
 
-    if best_missing_coords.is_some() {
-        let best_missing_coords = best_missing_coords.unwrap();
-        bail!("({},{})", best_missing_coords.lat(), best_missing_coords.lng());
-    }
-
-    bail!("")
+    Err(NbroutesError::AreaNotFound {
+        coord: best_missing_coords.cloned(),
+    })
 }
 
 pub fn find_service<'a>(
-    mode: &Option<String>,
+    mode: &Option<TravelMode>,
     coords: &'a Vec<Coord>,
-    polygons: &HashMap<String, Vec<Polygon<f64>>>,
+    polygons: &HashMap<String, Vec<PreparedPolygon>>,
     areas: &Vec<Area>,
     tolerate_outlier: bool,
     request_id: Option<&str>,
@@ -434,56 +774,407 @@ pub fn find_service<'a>(
     let (detected_area, mode, coord_index) =
         find_area(mode, coords, polygons, areas, tolerate_outlier, request_id, is_flexible_request)?;
 
-    let r = Service {
+    let mut r = Service {
         area: detected_area.clone(),
         mode: mode,
+        adjustments: vec![],
+        outside_indices: vec![],
+        coord_areas: None,
     };
+    if let Some(coord_index) = coord_index.as_ref() {
+        annotate_outside_coords(&mut r, coords, coord_index, polygons, areas);
+    }
+
+    Ok((r, coord_index))
+}
+
+// annotate_outside_coords records which coordinates are outside of the area of the service,
+//  and the areas they are in if any, so that callers can tell which points to fix
+fn annotate_outside_coords(
+    service: &mut Service,
+    coords: &[Coord],
+    coord_index: &[usize],
+    polygons: &HashMap<String, Vec<PreparedPolygon>>,
+    areas: &[Area],
+) {
+    let inside: HashSet<usize> = coord_index.iter().cloned().collect();
+    let mut coord_areas = Vec::with_capacity(coords.len());
+    let mut in_other_area = false;
+    for (idx, coord) in coords.iter().enumerate() {
+        if inside.contains(&idx) {
+            coord_areas.push(Some(service.area.name.clone()));
+            continue;
+        }
+        service.outside_indices.push(idx);
+        let area = area_of(coord, polygons, None, areas);
+        in_other_area |= area.is_some();
+        coord_areas.push(area.map(|(_, a)| a.name.clone()));
+    }
+    if in_other_area {
+        service.coord_areas = Some(coord_areas);
+    }
+}
+
+// area_of returns the first area of areas containing coord, with its index in areas. like in
+//  find_area_with_bounds, areas whose bounding circle misses coord are skipped
+fn area_of<'a>(
+    coord: &Coord,
+    polygons: &HashMap<String, Vec<PreparedPolygon>>,
+    bounds: Option<&HashMap<String, BoundingCircle>>,
+    areas: &'a [Area],
+) -> Option<(usize, &'a Area)> {
+    areas.iter().enumerate().find(|(_, a)| {
+        let may_contain = bounds
+            .and_then(|b| b.get(a.name.as_str()))
+            .map(|c| c.may_contain(coord))
+            .unwrap_or(true);
+        may_contain
+            && polygons
+                .get(a.name.as_str())
+                .map(|ps| coord.is_in_polygons(ps))
+                .unwrap_or(false)
+    })
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AreaAssignment {
+    pub area: Name,
+    // index of the area in the areas given to assign_areas
+    pub area_index: usize,
+}
+
+// assign_areas labels each coordinate with the area it is in, None when it is in no area.
+//  areas are tried in order like in find_area, so a coordinate in overlapping areas gets the
+//  first one
+pub fn assign_areas(
+    coords: &[Coord],
+    polygons: &HashMap<String, Vec<PreparedPolygon>>,
+    areas: &[Area],
+) -> Vec<Option<AreaAssignment>> {
+    assign_areas_with_bounds(coords, polygons, None, areas)
+}
+
+// assign_areas_with_bounds skips point-in-polygon for coordinates outside of the bounding
+//  circle of an area, bounds are returned by load_polygons_with_bounds
+pub fn assign_areas_with_bounds(
+    coords: &[Coord],
+    polygons: &HashMap<String, Vec<PreparedPolygon>>,
+    bounds: Option<&HashMap<String, BoundingCircle>>,
+    areas: &[Area],
+) -> Vec<Option<AreaAssignment>> {
+    coords
+        .iter()
+        .map(|c| {
+            area_of(c, polygons, bounds, areas).map(|(area_index, a)| AreaAssignment {
+                area: a.name.clone(),
+                area_index,
+            })
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AreaSegment {
+    pub area: Name,
+    // index of the area in the areas given to split_by_area
+    pub area_index: usize,
+    // coordinates of the segment are start..end, the route crosses into the area between
+    //  coordinates start - 1 and start
+    pub start: usize,
+    pub end: usize,
+}
+
+// split_by_area splits an ordered list of coordinates into contiguous segments each fully in
+//  one area, so that cross-border routes can be stitched from a route per segment. a segment
+//  goes on while its area contains the coordinates, even if they are in other areas too, and
+//  fails on the first coordinate in no area
+pub fn split_by_area(
+    coords: &[Coord],
+    polygons: &HashMap<String, Vec<PreparedPolygon>>,
+    areas: &[Area],
+) -> NbroutesResult<Vec<AreaSegment>> {
+    let mut r: Vec<AreaSegment> = vec![];
+    for (idx, coord) in coords.iter().enumerate() {
+        if let Some(segment) = r.last_mut() {
+            let contains = polygons
+                .get(segment.area.as_str())
+                .map(|ps| coord.is_in_polygons(ps))
+                .unwrap_or(false);
+            if contains {
+                segment.end = idx + 1;
+                continue;
+            }
+        }
+        let (area_index, area) = match area_of(coord, polygons, None, areas) {
+            Some(v) => v,
+            None => {
+                return Err(NbroutesError::AreaNotFound {
+                    coord: Some(coord.clone()),
+                })
+            }
+        };
+        r.push(AreaSegment {
+            area: area.name.clone(),
+            area_index,
+            start: idx,
+            end: idx + 1,
+        });
+    }
+    Ok(r)
+}
+
+// nudge_coord moves a coordinate outside of all areas onto the nearest area within max_meters
+fn nudge_coord(
+    coord: &Coord,
+    polygons: &HashMap<String, Vec<PreparedPolygon>>,
+    areas: &[Area],
+    max_meters: f64,
+) -> Option<(Coord, f64)> {
+    let area_polygons: Vec<&Vec<PreparedPolygon>> = areas
+        .iter()
+        .filter_map(|a| polygons.get(a.name.as_str()))
+        .collect();
+    if area_polygons.iter().any(|ps| coord.is_in_polygons(ps)) {
+        return None;
+    }
+    let (nearest, distance, ps) = area_polygons
+        .iter()
+        .filter_map(|ps| nearest_point_on_area(coord, ps).map(|(c, d)| (c, d, *ps)))
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))?;
+    if distance > max_meters {
+        return None;
+    }
+    let (dlat, dlng) = (nearest.lat - coord.lat, nearest.lng - coord.lng);
+    let norm = (dlat * dlat + dlng * dlng).sqrt();
+    if norm <= 0.0 {
+        return None;
+    }
+    let adjusted = Coord::new(
+        nearest.lat + dlat / norm * NUDGE_PAST_BORDER_DEGREES,
+        nearest.lng + dlng / norm * NUDGE_PAST_BORDER_DEGREES,
+    );
+    if !adjusted.is_in_polygons(ps) {
+        return None;
+    }
+    Some((adjusted, distance))
+}
+
+// nudge_coords returns coords with the ones outside of all areas moved onto the nearest area
+//  within max_meters, and the adjustments made
+fn nudge_coords(
+    coords: &[Coord],
+    polygons: &HashMap<String, Vec<PreparedPolygon>>,
+    areas: &[Area],
+    max_meters: f64,
+) -> (Vec<Coord>, Vec<CoordAdjustment>) {
+    let mut adjusted_coords = coords.to_vec();
+    let mut adjustments = vec![];
+    for (idx, coord) in coords.iter().enumerate() {
+        if let Some((adjusted, distance)) = nudge_coord(coord, polygons, areas, max_meters) {
+            adjustments.push(CoordAdjustment {
+                index: idx,
+                original: coord.clone(),
+                adjusted: adjusted.clone(),
+                distance_meters: distance,
+            });
+            adjusted_coords[idx] = adjusted;
+        }
+    }
+    (adjusted_coords, adjustments)
+}
+
+// FallbackPolicy lets find_area_with_fallback accept the coordinates outside of all areas that
+//  are within max_snap_distance_m of an area, e.g. points slightly offshore or over a border
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FallbackPolicy {
+    pub max_snap_distance_m: f64,
+}
+
+// find_area_with_fallback is find_area, coordinates in no area being snapped onto the nearest
+//  area within the distance of fallback. the snapped coordinates are returned with their snap
+//  distance
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn find_area_with_fallback<'a>(
+    mode: &Option<TravelMode>,
+    coords: &[Coord],
+    polygons: &HashMap<String, Vec<PreparedPolygon>>,
+    areas: &'a Vec<Area>,
+    tolerate_outlier: bool,
+    request_id: Option<&str>,
+    is_flexible_request: bool,
+    fallback: &FallbackPolicy,
+) -> NbroutesResult<(&'a Area, Name, Option<Vec<usize>>, Vec<CoordAdjustment>)> {
+    let (adjusted_coords, adjustments) =
+        nudge_coords(coords, polygons, areas, fallback.max_snap_distance_m);
+    let (area, mode, coord_index) = find_area(
+        mode,
+        &adjusted_coords,
+        polygons,
+        areas,
+        tolerate_outlier,
+        request_id,
+        is_flexible_request,
+    )?;
+    Ok((area, mode, coord_index, adjustments))
+}
 
+// find_service_with_nudge moves coordinates that are outside of all areas by at most
+//  max_nudge_meters onto the nearest area before finding the service, e.g. pickups slightly
+//  offshore. moved coordinates are recorded in the adjustments of the service
+#[allow(clippy::too_many_arguments)]
+pub fn find_service_with_nudge(
+    mode: &Option<TravelMode>,
+    coords: &[Coord],
+    polygons: &HashMap<String, Vec<PreparedPolygon>>,
+    areas: &Vec<Area>,
+    tolerate_outlier: bool,
+    request_id: Option<&str>,
+    is_flexible_request: bool,
+    max_nudge_meters: f64,
+) -> Result<(Service, Option<Vec<usize>>)> {
+    let (adjusted_coords, adjustments) = nudge_coords(coords, polygons, areas, max_nudge_meters);
+    let (mut r, coord_index) = find_service(
+        mode,
+        &adjusted_coords,
+        polygons,
+        areas,
+        tolerate_outlier,
+        request_id,
+        is_flexible_request,
+    )?;
+    r.adjustments = adjustments;
     Ok((r, coord_index))
 }
 
-pub fn map_mode(mode: &Option<String>, area: &Area, is_flexible_request: bool) -> Result<String> {
-    let mut default_mode = area.default_service.as_str();
+#[allow(clippy::too_many_arguments)]
+pub fn find_service_with_deadline(
+    mode: &Option<TravelMode>,
+    coords: &Vec<Coord>,
+    polygons: &HashMap<String, Vec<PreparedPolygon>>,
+    areas: &Vec<Area>,
+    tolerate_outlier: bool,
+    request_id: Option<&str>,
+    is_flexible_request: bool,
+    deadline: Option<&Deadline>,
+) -> Result<(Service, Option<Vec<usize>>)> {
+    check_deadline(deadline, "find_service")?;
+    let r = find_service(
+        mode,
+        coords,
+        polygons,
+        areas,
+        tolerate_outlier,
+        request_id,
+        is_flexible_request,
+    )?;
+    check_deadline(deadline, "find_service")?;
+    Ok(r)
+}
+
+// ModeExplanation tells how map_mode got the service of a request, for the debug responses
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ModeExplanation {
+    pub service: Name,
+    // key of the mappings the mode matched, None when the default service was used
+    pub matched_key: Option<String>,
+    // the mode was empty or missing, or named the default service
+    pub used_default: bool,
+    // the mappings and default service are those of the flexible setting of the area
+    pub flexible: bool,
+}
+
+pub fn map_mode(mode: &Option<TravelMode>, area: &Area, is_flexible_request: bool) -> NbroutesResult<Name> {
+    map_mode_explain(mode, area, is_flexible_request).map(|v| v.service)
+}
+
+// map_mode_explain is map_mode, telling which mapping of the area was applied
+pub fn map_mode_explain(
+    mode: &Option<TravelMode>,
+    area: &Area,
+    is_flexible_request: bool,
+) -> NbroutesResult<ModeExplanation> {
+    let mut default_mode = &area.default_service;
     let mut mappings = &area.mappings;
 
     if is_flexible_request {
         if area.flexible_setting.is_none() {
-            bail!("option=flexible not supported for this area")
+            return Err(NbroutesError::FlexibleNotSupported {
+                area: area.name.to_string(),
+            });
         }
         
         let flexible_setting = area.flexible_setting.as_ref().unwrap();
-        default_mode = flexible_setting.default_service.as_str();
+        default_mode = &flexible_setting.default_service;
         mappings = &flexible_setting.mappings;
     }
+    let explanation = |service: &Name, matched_key: Option<&str>| ModeExplanation {
+        service: service.clone(),
+        matched_key: matched_key.map(|v| v.to_string()),
+        used_default: matched_key.is_none(),
+        flexible: is_flexible_request,
+    };
 
-    if mode.is_some() && mode.as_ref().unwrap() != "" {
-        match mappings.get(mode.as_ref().unwrap()) {
-            Some(v) => return Ok(v.clone()),
+    if let Some(mode) = mode.as_ref().filter(|m| !m.is_empty()) {
+        // mappings are keyed by the canonical name, by one of its aliases, or by the raw name
+        //  for custom modes
+        let matched = mode
+            .names()
+            .into_iter()
+            .find_map(|k| mappings.get(k).map(|v| (k, v)));
+        match matched {
+            Some((key, v)) => return Ok(explanation(v, Some(key))),
             _ => {
-                if mode.as_ref().unwrap().as_str() == default_mode {
-                    return Ok(default_mode.to_string());
+                if mode.as_str() == default_mode.as_str() {
+                    return Ok(explanation(default_mode, None));
                 } else {
                     warn!(
-                        "map_mode failed due to unknown mode: {}",
-                        mode.as_ref().unwrap()
+                        "map_mode failed due to unknown mode: {}, known: {:?}",
+                        mode,
+                        mappings.keys().collect::<Vec<_>>()
                     );
-                    bail!("invalid mode input")
+                    return Err(NbroutesError::InvalidMode {
+                        mode: mode.to_string(),
+                    });
                 }
             }
         }
     }
 
     if default_mode == "" {
-        bail!("area not supported")
+        return Err(NbroutesError::AreaNotSupported {
+            area: area.name.to_string(),
+        });
     }
 
-    Ok(default_mode.to_string())
+    Ok(explanation(default_mode, None))
 }
 
+// load_polygons_with_bounds also precomputes the bounding circle of each area for find_area_with_bounds
+pub async fn load_polygons_with_bounds(
+    areas: &HashSet<String>,
+    skip_maaas: bool,
+) -> Option<(HashMap<String, Vec<PreparedPolygon>>, HashMap<String, BoundingCircle>)> {
+    let polygons = load_polygons(areas, skip_maaas).await?;
+    let bounds = bounding_circles(&polygons);
+    Some((polygons, bounds))
+}
+
+// load_area_polygons is load_polygons for areas, the polygons of each area being simplified
+//  with its simplify_tolerance_meters
+pub async fn load_area_polygons(
+    areas: &[Area],
+    skip_maaas: bool,
+) -> Option<HashMap<String, Vec<PreparedPolygon>>> {
+    let names: HashSet<String> = areas.iter().map(|a| a.name.to_string()).collect();
+    let polygons = load_polygons(&names, skip_maaas).await?;
+    Some(simplify_area_polygons(polygons, areas))
+}
+
+// load_polygons returns the polygons of each area prepared for find_area
 pub async fn load_polygons(
     areas: &HashSet<String>,
     skip_maaas: bool,
-) -> Option<HashMap<String, Vec<Polygon<f64>>>> {
+) -> Option<HashMap<String, Vec<PreparedPolygon>>> {
     if areas.len() == 0 {
         return None;
     }
@@ -492,7 +1183,7 @@ pub async fn load_polygons(
         panic!("failed to load area defs from maaas");
     }
     let data_root = get_data_root();
-    let mut polygons = HashMap::<String, Vec<Polygon<f64>>>::new();
+    let mut polygons = HashMap::<String, Vec<PreparedPolygon>>::new();
     for area_name in areas {
         if !skip_maaas {
             let ps = maaas_area_cfg
@@ -500,18 +1191,29 @@ pub async fn load_polygons(
                 .unwrap()
                 .polygons(area_name.as_str());
             if ps.is_some() {
-                polygons.insert(area_name.clone(), ps.unwrap().to_vec());
+                polygons.insert(area_name.clone(), prepare_polygons(ps.unwrap().to_vec()));
                 info!("loaded poly file from maaas-area-cfg for {}", &area_name);
                 continue;
             }
         }
 
-        polygons.insert(
-            area_name.clone(),
+        // borders authored as geojson take precedence over the poly file of the area. a
+        //  corrupted border file only disables its area instead of crashing the process
+        let geojson_path = format!("{}/mojo/borders/{}.geojson", data_root, &area_name);
+        let loaded = if Path::new(&geojson_path).exists() {
+            poly::load_geojson(&geojson_path)
+        } else {
             load_poly(&format!("{}/mojo/borders/{}.poly", data_root, &area_name))
-                .expect(&format!("failed to load poly for {}", &area_name)),
-        );
-        info!("loaded poly file for {}", &area_name);
+        };
+        match loaded {
+            Ok(ps) => {
+                polygons.insert(area_name.clone(), prepare_polygons(ps));
+                info!("loaded border file for {}", &area_name);
+            }
+            Err(e) => {
+                error!("failed to load poly for {}, area skipped: {}", &area_name, e);
+            }
+        }
     }
     Some(polygons)
 }
@@ -521,10 +1223,26 @@ pub fn handle_error_message(
     code: &str,
     message: &str
 ) -> String {
-    match engine_mode_input(engine) {
+    handle_error_message_for(engine, code, message, "", "")
+}
+
+// handle_error_message_for also labels the recorded engine error with area and mode, see
+//  statsd::set_engine_error_sender
+pub fn handle_error_message_for(
+    engine: &str,
+    code: &str,
+    message: &str,
+    area: &str,
+    mode: &str,
+) -> String {
+    let error = match engine_mode_input(engine) {
         Engine::OSRM => error_handle_osrm(code, message),
         Engine::Valhalla => error_handle_valhalla(code, message),
+    };
+    if let Some(sender) = statsd::engine_error_sender() {
+        statsd::record_engine_error(&sender, engine, &error, area, mode);
     }
+    error.to_string()
 }
 
 fn engine_mode_input(engine: &str) -> Engine {
@@ -534,7 +1252,7 @@ fn engine_mode_input(engine: &str) -> Engine {
     }
 }
 
-fn error_handle_valhalla(code: &str, message: &str) -> String{
+fn error_handle_valhalla(code: &str, message: &str) -> AdaptError {
     let error_type = match code {
         "Bad Request" => ValhallaError::BadRequest,
         "Not Implemented" => ValhallaError::NotImplemented,
@@ -556,7 +1274,7 @@ fn error_handle_valhalla(code: &str, message: &str) -> String{
     handle_valhalla_err_message(error_type, message)
 }
 
-fn error_handle_osrm(code: &str, message: &str) -> String{
+fn error_handle_osrm(code: &str, message: &str) -> AdaptError {
     let error_type = match code {
         "TooBig" => OsrmError::TooBig,
         "NotImplemented" => OsrmError::NotImplemented,
@@ -569,12 +1287,12 @@ fn error_handle_osrm(code: &str, message: &str) -> String{
         "NoRoute" => OsrmError::NoRoute,
         _ => OsrmError::UnknownError,
     };
-    handle_osrm_err_message(error_type, message).to_string()
+    handle_osrm_err_message(error_type, message)
 }
 
-fn handle_valhalla_err_message(error_type: ValhallaError, message: &str) -> String{
+fn handle_valhalla_err_message(error_type: ValhallaError, message: &str) -> AdaptError {
     // TODO: @Youzhi specific error
-    let msg = match error_type {
+    match error_type {
         ValhallaError::BadRequest => match adapt_err_message(message){
             EngineError::InputNoPath => AdaptError::OutputRouteFailed,
             _ => AdaptError::OutputUnclassifiedError,
@@ -600,13 +1318,12 @@ fn handle_valhalla_err_message(error_type: ValhallaError, message: &str) -> Stri
         },
         ValhallaError::DistanceExceeded | ValhallaError::PerimeterExceeded | ValhallaError::BreakageDistanceExceeded => AdaptError::OutputTooBig,
         _ => AdaptError::OutputUnclassifiedError,
-    };
-    msg.to_string()
+    }
 }
 
-fn handle_osrm_err_message(error_type: OsrmError, message: &str) -> String{
+fn handle_osrm_err_message(error_type: OsrmError, message: &str) -> AdaptError {
     // TODO: @Youzhi specific error
-    let msg = match error_type {
+    match error_type {
         OsrmError::NoRoute => AdaptError::OutputRouteFailed,
         OsrmError::InvalidOptions => match adapt_err_message(message) {
             EngineError::InputCoordinatesInvalid => AdaptError::OutputCoordinatesInvalid,
@@ -632,8 +1349,7 @@ fn handle_osrm_err_message(error_type: OsrmError, message: &str) -> String{
         },
         OsrmError::NoTrips => def::AdaptError::OutputNoTrips,
         _ => AdaptError::OutputUnclassifiedError,
-    };
-    msg.to_string()
+    }
 }
 
 fn adapt_err_message(message: &str) -> EngineError {
@@ -652,4 +1368,382 @@ fn adapt_err_message(message: &str) -> EngineError {
         "No table found, no valid input node" => EngineError::InputInvalidInputTable,
         _ => EngineError::InputUnknown,
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intern::intern;
+    use crate::testkit::{polygons_map, square_polygon, AreaBuilder};
+
+    #[test]
+    fn test_find_service_with_nudge() {
+        let center = Coord::new(1.3, 103.8);
+        let polygons = polygons_map(vec![("singapore", vec![square_polygon(&center, 0.1)])]);
+        let areas = vec![AreaBuilder::new("singapore", "4w").build()];
+        // ~30m and ~300m east of the area
+        let coords = vec![center.clone(), Coord::new(1.3, 103.90027)];
+        let far = vec![center.clone(), Coord::new(1.3, 103.9027)];
+
+        assert!(find_service(&None, &coords, &polygons, &areas, false, None, false).is_err());
+        let (service, coord_index) =
+            find_service_with_nudge(&None, &coords, &polygons, &areas, false, None, false, 50.0)
+                .unwrap();
+        assert_eq!(service.area.name, "singapore");
+        assert_eq!(coord_index, None);
+        assert_eq!(service.adjustments.len(), 1);
+        let adjustment = &service.adjustments[0];
+        assert_eq!(adjustment.index, 1);
+        assert_eq!(adjustment.original, coords[1]);
+        assert!(adjustment.adjusted.lng < 103.9);
+        assert!((adjustment.distance_meters - 30.0).abs() < 1.0);
+
+        assert!(
+            find_service_with_nudge(&None, &far, &polygons, &areas, false, None, false, 50.0)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_find_area_with_fallback() {
+        let center = Coord::new(1.3, 103.8);
+        let polygons = polygons_map(vec![
+            ("singapore", vec![square_polygon(&center, 0.1)]),
+            ("johor", vec![square_polygon(&Coord::new(1.6, 103.8), 0.1)]),
+        ]);
+        let areas = vec![
+            AreaBuilder::new("singapore", "4w").build(),
+            AreaBuilder::new("johor", "4w").build(),
+        ];
+        let policy = FallbackPolicy {
+            max_snap_distance_m: 500.0,
+        };
+        // ~300m south of singapore, then ~30m north of johor
+        let coords = vec![Coord::new(1.1973, 103.8), Coord::new(1.70027, 103.8)];
+        let (area, mode, coord_index, adjustments) = find_area_with_fallback(
+            &None,
+            &coords[..1],
+            &polygons,
+            &areas,
+            false,
+            None,
+            false,
+            &policy,
+        )
+        .unwrap();
+        assert_eq!(area.name, "singapore");
+        assert_eq!(mode, "4w");
+        assert_eq!(coord_index, None);
+        assert_eq!(adjustments.len(), 1);
+        assert!((adjustments[0].distance_meters - 300.0).abs() < 5.0);
+
+        let (area, _, _, adjustments) = find_area_with_fallback(
+            &None,
+            &coords[1..],
+            &polygons,
+            &areas,
+            false,
+            None,
+            false,
+            &policy,
+        )
+        .unwrap();
+        assert_eq!(area.name, "johor");
+        assert!((adjustments[0].distance_meters - 30.0).abs() < 1.0);
+
+        let strict = FallbackPolicy {
+            max_snap_distance_m: 100.0,
+        };
+        match find_area_with_fallback(
+            &None, &coords, &polygons, &areas, false, None, false, &strict,
+        ) {
+            Err(NbroutesError::AreaNotFound { coord }) => {
+                assert_eq!(coord, Some(coords[0].clone()))
+            }
+            r => panic!("unexpected {:?}", r.map(|v| v.0.name.clone())),
+        }
+    }
+
+    #[test]
+    fn test_find_service_outside_coords() {
+        let center = Coord::new(1.3, 103.8);
+        let polygons = polygons_map(vec![
+            ("singapore", vec![square_polygon(&center, 0.1)]),
+            ("johor", vec![square_polygon(&Coord::new(1.6, 103.8), 0.1)]),
+        ]);
+        let areas = vec![
+            AreaBuilder::new("singapore", "4w").build(),
+            AreaBuilder::new("johor", "4w").build(),
+        ];
+        let coords = vec![
+            center.clone(),
+            Coord::new(5.0, 103.8),
+            Coord::new(1.31, 103.81),
+        ];
+        let (service, coord_index) =
+            find_service(&None, &coords, &polygons, &areas, true, None, false).unwrap();
+        assert_eq!(coord_index, Some(vec![0, 2]));
+        assert_eq!(service.outside_indices, vec![1]);
+        assert_eq!(service.coord_areas, None);
+
+        let coords = vec![
+            center.clone(),
+            Coord::new(1.6, 103.8),
+            Coord::new(1.31, 103.81),
+            Coord::new(5.0, 103.8),
+        ];
+        let (service, _) =
+            find_service(&None, &coords, &polygons, &areas, true, None, false).unwrap();
+        assert_eq!(service.area.name, "singapore");
+        assert_eq!(service.outside_indices, vec![1, 3]);
+        assert_eq!(
+            service.coord_areas,
+            Some(vec![
+                Some(intern("singapore")),
+                Some(intern("johor")),
+                Some(intern("singapore")),
+                None
+            ])
+        );
+
+        let (service, coord_index) =
+            find_service(&None, &coords[..1].to_vec(), &polygons, &areas, true, None, false)
+                .unwrap();
+        assert_eq!(coord_index, None);
+        assert!(service.outside_indices.is_empty());
+    }
+
+    #[test]
+    fn test_assign_areas() {
+        let center = Coord::new(1.3, 103.8);
+        let polygons = polygons_map(vec![
+            ("singapore", vec![square_polygon(&center, 0.1)]),
+            ("johor", vec![square_polygon(&Coord::new(1.6, 103.8), 0.25)]),
+        ]);
+        let areas = vec![
+            AreaBuilder::new("singapore", "4w").build(),
+            AreaBuilder::new("johor", "4w").build(),
+        ];
+        // the third coordinate is in both areas
+        let coords = vec![
+            Coord::new(1.31, 103.81),
+            Coord::new(1.6, 103.8),
+            Coord::new(1.38, 103.8),
+            Coord::new(5.0, 103.8),
+        ];
+        let assignment = |area: &str, area_index: usize| {
+            Some(AreaAssignment {
+                area: intern(area),
+                area_index,
+            })
+        };
+        assert_eq!(
+            assign_areas(&coords, &polygons, &areas),
+            vec![
+                assignment("singapore", 0),
+                assignment("johor", 1),
+                assignment("singapore", 0),
+                None
+            ]
+        );
+        assert!(assign_areas(&[], &polygons, &areas).is_empty());
+
+        let bounds = bounding_circles(&polygons);
+        assert_eq!(
+            assign_areas_with_bounds(&coords, &polygons, Some(&bounds), &areas),
+            assign_areas(&coords, &polygons, &areas)
+        );
+    }
+
+    #[test]
+    fn test_split_by_area() {
+        let center = Coord::new(1.3, 103.8);
+        let polygons = polygons_map(vec![
+            ("singapore", vec![square_polygon(&center, 0.1)]),
+            ("johor", vec![square_polygon(&Coord::new(1.6, 103.8), 0.25)]),
+        ]);
+        let areas = vec![
+            AreaBuilder::new("singapore", "4w").build(),
+            AreaBuilder::new("johor", "4w").build(),
+        ];
+        let segment = |area: &str, area_index: usize, start: usize, end: usize| AreaSegment {
+            area: intern(area),
+            area_index,
+            start,
+            end,
+        };
+        // singapore to johor and back, the third and fifth coordinates are in both areas
+        let coords = vec![
+            Coord::new(1.31, 103.81),
+            Coord::new(1.32, 103.8),
+            Coord::new(1.38, 103.8),
+            Coord::new(1.6, 103.8),
+            Coord::new(1.38, 103.8),
+            Coord::new(1.3, 103.8),
+        ];
+        assert_eq!(
+            split_by_area(&coords, &polygons, &areas).unwrap(),
+            vec![
+                segment("singapore", 0, 0, 3),
+                segment("johor", 1, 3, 5),
+                segment("singapore", 0, 5, 6),
+            ]
+        );
+        assert!(split_by_area(&[], &polygons, &areas).unwrap().is_empty());
+        let outside = vec![center.clone(), Coord::new(5.0, 103.8)];
+        match split_by_area(&outside, &polygons, &areas) {
+            Err(NbroutesError::AreaNotFound { coord }) => {
+                assert_eq!(coord, Some(outside[1].clone()))
+            }
+            r => panic!("unexpected {:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_map_mode() {
+        let area = AreaBuilder::new("singapore", "4w")
+            .mapping("car", "4w")
+            .mapping("van", "6w")
+            .build();
+        let mode = |m: &str| Some(TravelMode::parse(m));
+        assert_eq!(map_mode(&None, &area, false).unwrap(), "4w");
+        assert_eq!(map_mode(&mode(""), &area, false).unwrap(), "4w");
+        assert_eq!(map_mode(&mode("CAR"), &area, false).unwrap(), "4w");
+        // custom modes of an area are matched by their raw name
+        assert_eq!(map_mode(&mode("van"), &area, false).unwrap(), "6w");
+        assert_eq!(map_mode(&mode("4W"), &area, false).unwrap(), "4w");
+        assert!(map_mode(&mode("cra"), &area, false).is_err());
+    }
+
+    #[test]
+    fn test_map_mode_explain() {
+        let area = AreaBuilder::new("singapore", "4w")
+            .mapping("car", "4w")
+            .mapping("van", "6w")
+            .build();
+        let mode = |m: &str| Some(TravelMode::parse(m));
+        let r = map_mode_explain(&mode("van"), &area, false).unwrap();
+        assert_eq!(r.service, "6w");
+        assert_eq!(r.matched_key.as_deref(), Some("van"));
+        assert!(!r.used_default);
+        assert!(!r.flexible);
+        let r = map_mode_explain(&None, &area, false).unwrap();
+        assert_eq!(r.service, "4w");
+        assert_eq!(r.matched_key, None);
+        assert!(r.used_default);
+        assert!(map_mode_explain(&mode("van"), &area, true).is_err());
+
+        // mappings keyed by an alias of the mode
+        let area = AreaBuilder::new("singapore", "4w")
+            .mapping("bicycle", "2w")
+            .mapping("e-scooter", "2w-scooter")
+            .build();
+        let r = map_mode_explain(&mode("bike"), &area, false).unwrap();
+        assert_eq!(r.service, "2w");
+        assert_eq!(r.matched_key.as_deref(), Some("bicycle"));
+        let r = map_mode_explain(&mode("E_Scooter"), &area, false).unwrap();
+        assert_eq!(r.service, "2w-scooter");
+
+        let area = AreaBuilder::new("singapore", "4w")
+            .flexible("4w-flex", &[("truck", "6w-flex")])
+            .build();
+        let r = map_mode_explain(&mode("truck"), &area, true).unwrap();
+        assert_eq!(r.service, "6w-flex");
+        assert_eq!(r.matched_key.as_deref(), Some("truck"));
+        assert!(r.flexible);
+        let r = map_mode_explain(&None, &area, true).unwrap();
+        assert_eq!(r.service, "4w-flex");
+        assert!(r.used_default && r.flexible);
+    }
+
+    #[test]
+    fn test_handle_error_message_records_engine_error() {
+        let (tx, rx) = std::sync::mpsc::sync_channel(10);
+        statsd::set_engine_error_sender(tx);
+        let msg = handle_error_message_for(
+            "valhalla",
+            "DistanceExceeded",
+            "Path distance exceeds the max distance limit",
+            "singapore",
+            "car",
+        );
+        assert_eq!(msg, AdaptError::OutputTooBig.to_string());
+        match rx.try_recv().unwrap() {
+            statsd::TypedTrackInput::Counter(c) => {
+                assert_eq!(c.metric_name, statsd::METRIC_ENGINE_ERRORS);
+                assert_eq!(c.labels[statsd::LABELNAME_ADAPT_ERROR], "OutputTooBig");
+                assert_eq!(c.labels[statsd::LABELNAME_ENGINE], "valhalla");
+                assert_eq!(c.labels[statsd::LABELNAME_AREA], "singapore");
+                assert_eq!(c.labels[statsd::LABELNAME_MODE], "car");
+            }
+            _ => panic!("engine error is not a counter"),
+        }
+    }
+
+    #[test]
+    fn test_time_dependant_fetches() {
+        let areas = vec![
+            AreaBuilder::new("india", "4w").build(),
+            AreaBuilder::new("singapore", "4w")
+                .time_dependant("4w", "")
+                .time_dependant("2w", "taxi")
+                .flexible("4w", &[])
+                .flexible_time_dependant("4w", "")
+                .build(),
+        ];
+        let fetches = time_dependant_fetches(&areas, "prod");
+        let filenames: Vec<(&str, bool)> = fetches
+            .iter()
+            .map(|f| (f.filename.as_str(), f.flexible))
+            .collect();
+        assert_eq!(
+            filenames,
+            vec![
+                ("singapore-taxi-2w", false),
+                ("singapore-4w", false),
+                ("singapore-4w", true)
+            ]
+        );
+        assert!(fetches.iter().all(|f| f.area == 1));
+        assert_eq!(fetches[1].namespace, "prod");
+        assert_eq!(fetches[2].namespace, "prod/flexible");
+        assert!(!has_time_dependant(&areas[0]));
+        assert!(has_time_dependant(&areas[1]));
+    }
+
+    #[test]
+    fn test_minute_slots() {
+        let setting: TimeDependantSetting = serde_yaml::from_str(
+            "
+setting_type: recurring
+recurring_setting:
+  timezone: 0
+  days:
+    - name: weekday
+      prefix: wd
+      days:
+        - day_type: weekday
+          weekday_value: [0, 1, 2, 3, 4]
+      slots:
+        - id: early
+          range: [6, '07:30']
+        - id: peak
+          range: ['07:30', '09:00']
+        - id: evening
+          range_minutes: [1050, 1200]
+",
+        )
+        .unwrap();
+        // monday 2021-01-04 00:00 utc
+        let monday = 1609718400;
+        let at = |h: i64, m: i64| setting.get_additional_ctx(monday + h * 3600 + m * 60);
+        assert_eq!(at(7, 29), Some("wdearly".to_string()));
+        assert_eq!(at(7, 30), Some("wdpeak".to_string()));
+        assert_eq!(at(9, 0), None);
+        assert_eq!(at(17, 30), Some("wdevening".to_string()));
+
+        assert_eq!(SlotTime::parse("24:00"), Some(SlotTime(1440)));
+        assert_eq!(SlotTime::parse("07:60"), None);
+        assert!(serde_yaml::from_str::<DaysAheadSlotSetting>("{id: x, range: ['7h', 8]}").is_err());
+    }
+}
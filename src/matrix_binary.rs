@@ -0,0 +1,357 @@
+// binary matrix framing written by MatrixOutput::binary_encode, and its conversions to the
+//  MatrixOutputPB protobuf message. all numbers are little endian u32.
+//
+// version 1 layout, the one written by default:
+//   header:  rows, cols
+//   body:    rows * cols elements in row major order, each element being duration, distance
+// version 2 layout, written by MatrixOutput::binary_encode_version:
+//   header:  BINARY_MATRIX_MAGIC, version, rows, cols
+//   body:    as in version 1
+// the magic can not be the row count of a version 1 matrix, whose body would not fit in memory,
+//  so binary_decode reads both layouts.
+// unreachable pairs (MATRIX_UNREACHABLE_VALUE) are written as u32::MAX in the binary format and
+//  as u64::MAX in MatrixOutputPB. debug fields are not part of either format
+use crate::def::{Element, IntValue, MatrixOutput, Row, STATUS_OK};
+use crate::engine_response::MATRIX_UNREACHABLE_VALUE;
+use crate::protos::{
+    MatrixOutputPB, MatrixOutputPB_IntValue, MatrixOutputPB_MatrixElement, MatrixOutputPB_MatrixRow,
+};
+use crate::Result;
+use byteorder::{ByteOrder, LittleEndian};
+
+pub const BINARY_MATRIX_MAGIC: u32 = u32::MAX;
+pub const BINARY_MATRIX_ELEMENT_SIZE: usize = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BinaryMatrixVersion {
+    V1,
+    V2,
+}
+
+impl BinaryMatrixVersion {
+    pub fn number(&self) -> u32 {
+        match self {
+            BinaryMatrixVersion::V1 => 1,
+            BinaryMatrixVersion::V2 => 2,
+        }
+    }
+
+    pub fn header_size(&self) -> usize {
+        match self {
+            BinaryMatrixVersion::V1 => 8,
+            BinaryMatrixVersion::V2 => 16,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BinaryMatrixHeader {
+    pub version: BinaryMatrixVersion,
+    pub rows: u32,
+    pub cols: u32,
+}
+
+impl BinaryMatrixHeader {
+    pub fn encode(&self) -> Vec<u8> {
+        let numbers = match self.version {
+            BinaryMatrixVersion::V1 => vec![self.rows, self.cols],
+            BinaryMatrixVersion::V2 => vec![
+                BINARY_MATRIX_MAGIC,
+                self.version.number(),
+                self.rows,
+                self.cols,
+            ],
+        };
+        let mut bytes = vec![0; self.version.header_size()];
+        LittleEndian::write_u32_into(&numbers, &mut bytes);
+        bytes
+    }
+
+    pub fn decode(data: &[u8]) -> Result<BinaryMatrixHeader> {
+        let v1_size = BinaryMatrixVersion::V1.header_size();
+        if data.len() < v1_size {
+            bail!(
+                "binary matrix is too short for its header: {} bytes",
+                data.len()
+            );
+        }
+        let first = LittleEndian::read_u32(&data[0..4]);
+        // a version 1 matrix of u32::MAX rows has no columns and nothing after its header
+        if first != BINARY_MATRIX_MAGIC || data.len() == v1_size {
+            return Ok(BinaryMatrixHeader {
+                version: BinaryMatrixVersion::V1,
+                rows: first,
+                cols: LittleEndian::read_u32(&data[4..8]),
+            });
+        }
+        if data.len() < BinaryMatrixVersion::V2.header_size() {
+            bail!(
+                "binary matrix is too short for its header: {} bytes",
+                data.len()
+            );
+        }
+        let version = LittleEndian::read_u32(&data[4..8]);
+        if version != BinaryMatrixVersion::V2.number() {
+            bail!("binary matrix version {} is not supported", version);
+        }
+        Ok(BinaryMatrixHeader {
+            version: BinaryMatrixVersion::V2,
+            rows: LittleEndian::read_u32(&data[8..12]),
+            cols: LittleEndian::read_u32(&data[12..16]),
+        })
+    }
+
+    // body_size is the expected number of bytes after the header
+    pub fn body_size(&self) -> usize {
+        self.rows as usize * self.cols as usize * BINARY_MATRIX_ELEMENT_SIZE
+    }
+}
+
+// to_binary_value maps a value to its u32 representation, negative values are unreachable
+pub fn to_binary_value(value: i64) -> u32 {
+    if value < 0 || value >= u32::MAX as i64 {
+        return u32::MAX;
+    }
+    value as u32
+}
+
+pub fn from_binary_value(value: u32) -> i64 {
+    if value == u32::MAX {
+        return MATRIX_UNREACHABLE_VALUE;
+    }
+    value as i64
+}
+
+pub fn to_pb_value(value: i64) -> u64 {
+    if value < 0 {
+        return u64::MAX;
+    }
+    value as u64
+}
+
+pub fn from_pb_value(value: u64) -> i64 {
+    if value > i64::MAX as u64 {
+        return MATRIX_UNREACHABLE_VALUE;
+    }
+    value as i64
+}
+
+fn element(duration: i64, distance: i64) -> Element {
+    Element {
+        duration: IntValue { value: duration },
+        distance: IntValue { value: distance },
+        raw_duration: None,
+        predicted_duration: None,
+        co2_emissions: None,
+    }
+}
+
+// binary_decode parses both versions of MatrixOutput::binary_encode, status is always Ok since
+//  the binary format only carries successful results
+pub fn binary_decode(data: &[u8]) -> Result<MatrixOutput> {
+    let header = BinaryMatrixHeader::decode(data)?;
+    let body = &data[header.version.header_size()..];
+    if body.len() != header.body_size() {
+        bail!(
+            "binary matrix of {}x{} needs {} bytes, got {}",
+            header.rows,
+            header.cols,
+            header.body_size(),
+            body.len()
+        );
+    }
+    let read = |offset: usize| from_binary_value(LittleEndian::read_u32(&body[offset..offset + 4]));
+    let cols = header.cols as usize;
+    let rows = (0..header.rows as usize)
+        .map(|r| Row {
+            elements: (0..cols)
+                .map(|c| {
+                    let offset = (r * cols + c) * BINARY_MATRIX_ELEMENT_SIZE;
+                    element(read(offset), read(offset + 4))
+                })
+                .collect(),
+        })
+        .collect();
+    Ok(MatrixOutput {
+        status: STATUS_OK.to_string(),
+        warning: None,
+        server_timings: None,
+        rows,
+    })
+}
+
+fn int_value_to_pb(value: i64) -> MatrixOutputPB_IntValue {
+    let mut r = MatrixOutputPB_IntValue::new();
+    r.set_value(to_pb_value(value));
+    r
+}
+
+pub fn row_to_pb(row: &Row) -> MatrixOutputPB_MatrixRow {
+    let mut r = MatrixOutputPB_MatrixRow::new();
+    for e in row.elements.iter() {
+        let mut pb_element = MatrixOutputPB_MatrixElement::new();
+        pb_element.set_duration(int_value_to_pb(e.duration.value));
+        pb_element.set_distance(int_value_to_pb(e.distance.value));
+        r.mut_elements().push(pb_element);
+    }
+    r
+}
+
+pub fn row_from_pb(pb: &MatrixOutputPB_MatrixRow) -> Row {
+    Row {
+        elements: pb
+            .get_elements()
+            .iter()
+            .map(|e| {
+                element(
+                    from_pb_value(e.get_duration().get_value()),
+                    from_pb_value(e.get_distance().get_value()),
+                )
+            })
+            .collect(),
+    }
+}
+
+pub fn to_pb(output: &MatrixOutput) -> MatrixOutputPB {
+    let mut r = MatrixOutputPB::new();
+    r.set_status(output.status.clone());
+    r.set_rows(output.rows.iter().map(row_to_pb).collect());
+    r
+}
+
+pub fn from_pb(pb: &MatrixOutputPB) -> MatrixOutput {
+    MatrixOutput {
+        status: pb.get_status().to_string(),
+        warning: None,
+        server_timings: None,
+        rows: pb.get_rows().iter().map(row_from_pb).collect(),
+    }
+}
+
+pub fn binary_to_pb(data: &[u8]) -> Result<MatrixOutputPB> {
+    Ok(to_pb(&binary_decode(data)?))
+}
+
+pub fn pb_to_binary(pb: &MatrixOutputPB) -> Vec<u8> {
+    from_pb(pb).binary_encode()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::Rng;
+    use protobuf::Message;
+
+    fn values(output: &MatrixOutput) -> Vec<Vec<(i64, i64)>> {
+        output
+            .rows
+            .iter()
+            .map(|r| {
+                r.elements
+                    .iter()
+                    .map(|e| (e.duration.value, e.distance.value))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn random_output(rng: &mut Rng, rows: usize, cols: usize) -> MatrixOutput {
+        MatrixOutput {
+            status: STATUS_OK.to_string(),
+            warning: None,
+            server_timings: None,
+            rows: (0..rows)
+                .map(|_| Row {
+                    elements: (0..cols)
+                        .map(|_| {
+                            if rng.next_f64() < 0.1 {
+                                return element(MATRIX_UNREACHABLE_VALUE, MATRIX_UNREACHABLE_VALUE);
+                            }
+                            element(
+                                rng.range_usize(0, 100_000) as i64,
+                                rng.range_usize(0, 5_000_000) as i64,
+                            )
+                        })
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_binary_layout() {
+        let output = MatrixOutput {
+            status: STATUS_OK.to_string(),
+            warning: None,
+            server_timings: None,
+            rows: vec![Row {
+                elements: vec![element(1, 2), element(MATRIX_UNREACHABLE_VALUE, 258)],
+            }],
+        };
+        let body = vec![
+            1, 0, 0, 0, 2, 0, 0, 0, // element 0
+            255, 255, 255, 255, 2, 1, 0, 0, // element 1
+        ];
+        // the layout written before the format had versions
+        let v1: Vec<u8> = vec![1, 0, 0, 0, 2, 0, 0, 0]
+            .into_iter()
+            .chain(body.clone())
+            .collect();
+        assert_eq!(output.binary_encode(), v1);
+        assert_eq!(values(&binary_decode(&v1).unwrap()), values(&output));
+
+        let v2: Vec<u8> = vec![255, 255, 255, 255, 2, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0]
+            .into_iter()
+            .chain(body)
+            .collect();
+        assert_eq!(output.binary_encode_version(BinaryMatrixVersion::V2), v2);
+        assert_eq!(values(&binary_decode(&v2).unwrap()), values(&output));
+
+        // a version 1 matrix of 2 rows is not taken for version 2
+        let two_rows = MatrixOutput {
+            rows: vec![output.rows[0].clone(), output.rows[0].clone()],
+            ..output.clone()
+        };
+        let decoded = binary_decode(&two_rows.binary_encode()).unwrap();
+        assert_eq!(values(&decoded), values(&two_rows));
+        assert_eq!(
+            BinaryMatrixHeader::decode(&[255, 255, 255, 255, 0, 0, 0, 0]).unwrap(),
+            BinaryMatrixHeader {
+                version: BinaryMatrixVersion::V1,
+                rows: u32::MAX,
+                cols: 0,
+            }
+        );
+
+        assert!(binary_decode(&[1, 0, 0]).is_err());
+        assert!(binary_decode(&v1[..20]).is_err());
+        assert!(binary_decode(&v2[..12]).is_err());
+        let mut v3 = v2.clone();
+        v3[4] = 3;
+        assert!(binary_decode(&v3).is_err());
+    }
+
+    #[test]
+    fn test_cross_format_equivalence() {
+        let mut rng = Rng::new(3);
+        for _ in 0..50 {
+            let rows = rng.range_usize(1, 8);
+            let cols = rng.range_usize(0, 8);
+            let output = random_output(&mut rng, rows, cols);
+
+            let binary = output.binary_encode();
+            let from_binary = binary_decode(&binary).unwrap();
+            assert_eq!(values(&from_binary), values(&output));
+            let v2 = output.binary_encode_version(BinaryMatrixVersion::V2);
+            assert_eq!(values(&binary_decode(&v2).unwrap()), values(&output));
+
+            let pb_bytes = to_pb(&output).write_to_bytes().unwrap();
+            let pb = MatrixOutputPB::parse_from_bytes(&pb_bytes).unwrap();
+            assert_eq!(values(&from_pb(&pb)), values(&output));
+
+            // both directions between the two formats
+            assert_eq!(pb_to_binary(&pb), binary);
+            assert_eq!(binary_to_pb(&binary).unwrap(), pb);
+        }
+    }
+}
@@ -0,0 +1,631 @@
+// the MatrixRowStream service of pb/protos.proto: MDM workers push the rows of a chunk as they
+//  are computed and the aggregator assembles them. RowChunker and RowAssembler build and check
+//  the messages, MatrixRowStreamClient and MatrixRowStreamServer carry them over gRPC with
+//  tonic. the messages are the rust-protobuf ones of protos.rs, encoded by ProtobufCodec
+use crate::def::{MatrixOutput, Row, STATUS_OK};
+use crate::matrix_binary::{row_from_pb, row_to_pb};
+use crate::protos::{MatrixRowChunk, PushRowsAck, ResumePoint, ResumeRequest};
+use crate::Result;
+use bytes::{Buf, BufMut};
+use futures::{Stream, StreamExt};
+use protobuf::Message;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use tonic::codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder};
+use tonic::codegen::{empty_body, http, Body, BoxFuture, Service, StdError};
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Request, Response, Status, Streaming};
+
+const SERVICE_NAME: &str = "matrix.MatrixRowStream";
+const PUSH_ROWS_PATH: &str = "/matrix.MatrixRowStream/PushRows";
+const GET_RESUME_POINT_PATH: &str = "/matrix.MatrixRowStream/GetResumePoint";
+
+// RowChunker splits rows computed by a worker into stream messages
+pub struct RowChunker {
+    task_id: String,
+    chunk_id: String,
+    rows_per_message: usize,
+    total_rows: u32,
+    next_row: u32,
+    next_sequence: u64,
+}
+
+impl RowChunker {
+    pub fn new(task_id: &str, chunk_id: &str, total_rows: u32, rows_per_message: usize) -> Self {
+        RowChunker {
+            task_id: task_id.to_string(),
+            chunk_id: chunk_id.to_string(),
+            rows_per_message: rows_per_message.max(1),
+            total_rows,
+            next_row: 0,
+            next_sequence: 0,
+        }
+    }
+
+    // resume_from skips the rows the aggregator already has, after a reconnection
+    pub fn resume_from(&mut self, point: &ResumePoint) {
+        self.next_row = point.get_next_row().min(self.total_rows);
+        self.next_sequence = point.get_next_sequence();
+    }
+
+    pub fn next_row(&self) -> u32 {
+        self.next_row
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.next_row >= self.total_rows
+    }
+
+    // push turns the rows starting at first_row into messages, rows before next_row are
+    //  dropped so that a worker can recompute from any earlier row after resuming
+    pub fn push(&mut self, first_row: u32, rows: &[Row]) -> Result<Vec<MatrixRowChunk>> {
+        if first_row > self.next_row {
+            bail!(
+                "rows must be pushed in order, expected row {}, got {}",
+                self.next_row,
+                first_row
+            );
+        }
+        let skip = ((self.next_row - first_row) as usize).min(rows.len());
+        if first_row as usize + rows.len() > self.total_rows as usize {
+            bail!(
+                "rows {}..{} are out of the {} rows of the chunk",
+                first_row,
+                first_row as usize + rows.len(),
+                self.total_rows
+            );
+        }
+        let mut messages = vec![];
+        for rows in rows[skip..].chunks(self.rows_per_message) {
+            let mut m = MatrixRowChunk::new();
+            m.set_task_id(self.task_id.clone());
+            m.set_chunk_id(self.chunk_id.clone());
+            m.set_sequence(self.next_sequence);
+            m.set_first_row(self.next_row);
+            m.set_total_rows(self.total_rows);
+            m.set_rows(rows.iter().map(row_to_pb).collect());
+            self.next_sequence += 1;
+            self.next_row += rows.len() as u32;
+            m.set_last(self.is_done());
+            messages.push(m);
+        }
+        Ok(messages)
+    }
+}
+
+// RowAssembler collects the rows of a chunk on the aggregator side. messages must arrive in
+//  sequence order, rows already received are ignored so that resumed streams can overlap
+pub struct RowAssembler {
+    task_id: String,
+    chunk_id: String,
+    total_rows: Option<u32>,
+    rows: Vec<Row>,
+    next_sequence: u64,
+}
+
+impl RowAssembler {
+    pub fn new(task_id: &str, chunk_id: &str) -> Self {
+        RowAssembler {
+            task_id: task_id.to_string(),
+            chunk_id: chunk_id.to_string(),
+            total_rows: None,
+            rows: vec![],
+            next_sequence: 0,
+        }
+    }
+
+    fn check(&mut self, m: &MatrixRowChunk) -> Result<()> {
+        if m.get_task_id() != self.task_id || m.get_chunk_id() != self.chunk_id {
+            bail!(
+                "message of {}/{} sent to the stream of {}/{}",
+                m.get_task_id(),
+                m.get_chunk_id(),
+                self.task_id,
+                self.chunk_id
+            );
+        }
+        if m.get_sequence() != self.next_sequence {
+            bail!(
+                "expected sequence {}, got {}",
+                self.next_sequence,
+                m.get_sequence()
+            );
+        }
+        if m.get_first_row() as usize > self.rows.len() {
+            bail!("missing rows {}..{}", self.rows.len(), m.get_first_row());
+        }
+        match self.total_rows {
+            Some(v) if v != m.get_total_rows() => {
+                bail!("total rows changed from {} to {}", v, m.get_total_rows())
+            }
+            _ => self.total_rows = Some(m.get_total_rows()),
+        }
+        let end = m.get_first_row() as usize + m.get_rows().len();
+        if end > m.get_total_rows() as usize {
+            bail!(
+                "rows {}..{} are out of the {} rows of the chunk",
+                m.get_first_row(),
+                end,
+                m.get_total_rows()
+            );
+        }
+        Ok(())
+    }
+
+    // accept adds the rows of a message, the returned ack carries the error if any
+    pub fn accept(&mut self, m: &MatrixRowChunk) -> PushRowsAck {
+        let mut ack = PushRowsAck::new();
+        ack.set_task_id(self.task_id.clone());
+        ack.set_chunk_id(self.chunk_id.clone());
+        match self.check(m) {
+            Ok(_) => {
+                let skip = self.rows.len() - m.get_first_row() as usize;
+                self.rows
+                    .extend(m.get_rows().iter().skip(skip).map(row_from_pb));
+                self.next_sequence += 1;
+            }
+            Err(e) => ack.set_error(e.to_string()),
+        }
+        ack.set_next_row(self.rows.len() as u32);
+        ack
+    }
+
+    pub fn resume_point(&self) -> ResumePoint {
+        let mut r = ResumePoint::new();
+        r.set_next_row(self.rows.len() as u32);
+        r.set_next_sequence(self.next_sequence);
+        r
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.total_rows
+            .map(|v| self.rows.len() == v as usize)
+            .unwrap_or(false)
+    }
+
+    pub fn finish(self) -> Result<MatrixOutput> {
+        if !self.is_complete() {
+            bail!(
+                "chunk {}/{} is incomplete: {} rows of {:?}",
+                self.task_id,
+                self.chunk_id,
+                self.rows.len(),
+                self.total_rows
+            );
+        }
+        Ok(MatrixOutput {
+            status: STATUS_OK.to_string(),
+            warning: None,
+            server_timings: None,
+            rows: self.rows,
+        })
+    }
+}
+
+// ProtobufCodec encodes rust-protobuf messages for tonic, which only comes with a prost codec
+pub struct ProtobufCodec<E, D>(PhantomData<(E, D)>);
+
+impl<E, D> Default for ProtobufCodec<E, D> {
+    fn default() -> Self {
+        ProtobufCodec(PhantomData)
+    }
+}
+
+pub struct ProtobufEncoder<T>(PhantomData<T>);
+
+pub struct ProtobufDecoder<T>(PhantomData<T>);
+
+impl<E: Message, D: Message> Codec for ProtobufCodec<E, D> {
+    type Encode = E;
+    type Decode = D;
+    type Encoder = ProtobufEncoder<E>;
+    type Decoder = ProtobufDecoder<D>;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        ProtobufEncoder(PhantomData)
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        ProtobufDecoder(PhantomData)
+    }
+}
+
+impl<T: Message> Encoder for ProtobufEncoder<T> {
+    type Item = T;
+    type Error = Status;
+
+    fn encode(&mut self, item: T, dst: &mut EncodeBuf<'_>) -> std::result::Result<(), Status> {
+        let data = item
+            .write_to_bytes()
+            .map_err(|e| Status::internal(e.to_string()))?;
+        dst.put_slice(&data);
+        Ok(())
+    }
+}
+
+impl<T: Message> Decoder for ProtobufDecoder<T> {
+    type Item = T;
+    type Error = Status;
+
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> std::result::Result<Option<T>, Status> {
+        let data = src.copy_to_bytes(src.remaining());
+        T::parse_from_bytes(&data)
+            .map(Some)
+            .map_err(|e| Status::invalid_argument(e.to_string()))
+    }
+}
+
+// MatrixRowStreamClient is the worker side of the stream
+#[derive(Clone)]
+pub struct MatrixRowStreamClient {
+    inner: tonic::client::Grpc<Channel>,
+}
+
+impl MatrixRowStreamClient {
+    pub fn new(channel: Channel) -> MatrixRowStreamClient {
+        MatrixRowStreamClient {
+            inner: tonic::client::Grpc::new(channel),
+        }
+    }
+
+    // connect connects to the aggregator at dst, e.g. `http://aggregator:50051`
+    pub async fn connect(dst: &str) -> Result<MatrixRowStreamClient> {
+        let channel = Endpoint::from_shared(dst.to_string())?.connect().await?;
+        Ok(MatrixRowStreamClient::new(channel))
+    }
+
+    // push_rows streams the messages of a RowChunker, the ack tells which row the aggregator
+    //  expects next and carries the error which stopped the stream if any
+    pub async fn push_rows<S>(&mut self, messages: S) -> Result<PushRowsAck>
+    where
+        S: Stream<Item = MatrixRowChunk> + Send + 'static,
+    {
+        self.inner
+            .ready()
+            .await
+            .map_err(|e| Status::unknown(e.to_string()))?;
+        let r = self
+            .inner
+            .client_streaming(
+                Request::new(messages),
+                http::uri::PathAndQuery::from_static(PUSH_ROWS_PATH),
+                ProtobufCodec::default(),
+            )
+            .await?;
+        Ok(r.into_inner())
+    }
+
+    // get_resume_point returns where a worker resumes the chunk after a reconnection
+    pub async fn get_resume_point(&mut self, task_id: &str, chunk_id: &str) -> Result<ResumePoint> {
+        let mut req = ResumeRequest::new();
+        req.set_task_id(task_id.to_string());
+        req.set_chunk_id(chunk_id.to_string());
+        self.inner
+            .ready()
+            .await
+            .map_err(|e| Status::unknown(e.to_string()))?;
+        let r = self
+            .inner
+            .unary(
+                Request::new(req),
+                http::uri::PathAndQuery::from_static(GET_RESUME_POINT_PATH),
+                ProtobufCodec::default(),
+            )
+            .await?;
+        Ok(r.into_inner())
+    }
+}
+
+pub type GrpcFuture<T> = Pin<Box<dyn Future<Output = std::result::Result<T, Status>> + Send>>;
+
+// MatrixRowStream is the aggregator side of the stream, served by MatrixRowStreamServer
+pub trait MatrixRowStream: Send + Sync + 'static {
+    fn push_rows(&self, messages: Streaming<MatrixRowChunk>) -> GrpcFuture<PushRowsAck>;
+    fn get_resume_point(&self, req: ResumeRequest) -> GrpcFuture<ResumePoint>;
+}
+
+// AssemblerService assembles the chunks pushed by the workers with a RowAssembler each
+#[derive(Clone, Default)]
+pub struct AssemblerService {
+    assemblers: Arc<Mutex<HashMap<(String, String), RowAssembler>>>,
+}
+
+impl AssemblerService {
+    pub fn new() -> AssemblerService {
+        AssemblerService::default()
+    }
+
+    pub fn is_complete(&self, task_id: &str, chunk_id: &str) -> bool {
+        self.assemblers
+            .lock()
+            .unwrap()
+            .get(&(task_id.to_string(), chunk_id.to_string()))
+            .map(|v| v.is_complete())
+            .unwrap_or(false)
+    }
+
+    // finish returns the rows of a complete chunk and forgets it
+    pub fn finish(&self, task_id: &str, chunk_id: &str) -> Result<MatrixOutput> {
+        let key = (task_id.to_string(), chunk_id.to_string());
+        let mut assemblers = self.assemblers.lock().unwrap();
+        match assemblers.get(&key).map(|v| v.is_complete()) {
+            Some(true) => assemblers.remove(&key).unwrap().finish(),
+            Some(false) => bail!("chunk {}/{} is incomplete", task_id, chunk_id),
+            None => bail!("chunk {}/{} is unknown", task_id, chunk_id),
+        }
+    }
+
+    fn accept(&self, m: &MatrixRowChunk) -> PushRowsAck {
+        let key = (m.get_task_id().to_string(), m.get_chunk_id().to_string());
+        self.assemblers
+            .lock()
+            .unwrap()
+            .entry(key)
+            .or_insert_with(|| RowAssembler::new(m.get_task_id(), m.get_chunk_id()))
+            .accept(m)
+    }
+}
+
+impl MatrixRowStream for AssemblerService {
+    fn push_rows(&self, mut messages: Streaming<MatrixRowChunk>) -> GrpcFuture<PushRowsAck> {
+        let service = self.clone();
+        Box::pin(async move {
+            let mut ack = PushRowsAck::new();
+            while let Some(m) = messages.next().await {
+                ack = service.accept(&m?);
+                // the worker resumes from the resume point
+                if ack.has_error() {
+                    break;
+                }
+            }
+            Ok(ack)
+        })
+    }
+
+    fn get_resume_point(&self, req: ResumeRequest) -> GrpcFuture<ResumePoint> {
+        let key = (
+            req.get_task_id().to_string(),
+            req.get_chunk_id().to_string(),
+        );
+        let point = self
+            .assemblers
+            .lock()
+            .unwrap()
+            .get(&key)
+            .map(|v| v.resume_point())
+            .unwrap_or_default();
+        Box::pin(async move { Ok(point) })
+    }
+}
+
+// MatrixRowStreamServer serves a MatrixRowStream, e.g. with
+//  `tonic::transport::Server::builder().add_service(MatrixRowStreamServer::new(service))`
+pub struct MatrixRowStreamServer<T> {
+    inner: Arc<T>,
+}
+
+impl<T: MatrixRowStream> MatrixRowStreamServer<T> {
+    pub fn new(inner: T) -> MatrixRowStreamServer<T> {
+        MatrixRowStreamServer {
+            inner: Arc::new(inner),
+        }
+    }
+}
+
+impl<T> Clone for MatrixRowStreamServer<T> {
+    fn clone(&self) -> Self {
+        MatrixRowStreamServer {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T: MatrixRowStream> tonic::server::NamedService for MatrixRowStreamServer<T> {
+    const NAME: &'static str = SERVICE_NAME;
+}
+
+struct PushRowsSvc<T>(Arc<T>);
+
+impl<T: MatrixRowStream> tonic::server::ClientStreamingService<MatrixRowChunk> for PushRowsSvc<T> {
+    type Response = PushRowsAck;
+    type Future = BoxFuture<Response<PushRowsAck>, Status>;
+
+    fn call(&mut self, request: Request<Streaming<MatrixRowChunk>>) -> Self::Future {
+        let f = self.0.push_rows(request.into_inner());
+        Box::pin(async move { f.await.map(Response::new) })
+    }
+}
+
+struct GetResumePointSvc<T>(Arc<T>);
+
+impl<T: MatrixRowStream> tonic::server::UnaryService<ResumeRequest> for GetResumePointSvc<T> {
+    type Response = ResumePoint;
+    type Future = BoxFuture<Response<ResumePoint>, Status>;
+
+    fn call(&mut self, request: Request<ResumeRequest>) -> Self::Future {
+        let f = self.0.get_resume_point(request.into_inner());
+        Box::pin(async move { f.await.map(Response::new) })
+    }
+}
+
+impl<T, B> Service<http::Request<B>> for MatrixRowStreamServer<T>
+where
+    T: MatrixRowStream,
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    type Response = http::Response<tonic::body::BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Infallible>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<std::result::Result<(), Infallible>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        let inner = self.inner.clone();
+        match req.uri().path() {
+            PUSH_ROWS_PATH => Box::pin(async move {
+                let mut grpc = tonic::server::Grpc::new(ProtobufCodec::default());
+                Ok(grpc.client_streaming(PushRowsSvc(inner), req).await)
+            }),
+            GET_RESUME_POINT_PATH => Box::pin(async move {
+                let mut grpc = tonic::server::Grpc::new(ProtobufCodec::default());
+                Ok(grpc.unary(GetResumePointSvc(inner), req).await)
+            }),
+            _ => Box::pin(async move {
+                // grpc-status 12 is UNIMPLEMENTED
+                Ok(http::Response::builder()
+                    .status(200)
+                    .header("grpc-status", "12")
+                    .header("content-type", "application/grpc")
+                    .body(empty_body())
+                    .unwrap())
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::def::{Element, IntValue};
+    use tonic::transport::Server;
+
+    fn rows(first: usize, n: usize) -> Vec<Row> {
+        (first..first + n)
+            .map(|i| Row {
+                elements: vec![Element {
+                    duration: IntValue { value: i as i64 },
+                    distance: IntValue {
+                        value: i as i64 * 10,
+                    },
+                    raw_duration: None,
+                    predicted_duration: None,
+                    co2_emissions: None,
+                }],
+            })
+            .collect()
+    }
+
+    fn durations(output: &MatrixOutput) -> Vec<i64> {
+        output
+            .rows
+            .iter()
+            .map(|r| r.elements[0].duration.value)
+            .collect()
+    }
+
+    #[test]
+    fn test_stream_with_resume() {
+        let mut chunker = RowChunker::new("task", "0", 10, 3);
+        let mut assembler = RowAssembler::new("task", "0");
+
+        let messages = chunker.push(0, &rows(0, 4)).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[1].get_first_row(), 3);
+        assert!(chunker.push(5, &rows(5, 1)).is_err());
+        for m in messages.iter() {
+            assert!(!assembler.accept(m).has_error());
+        }
+        // the message is lost with the connection
+        chunker.push(4, &rows(4, 2)).unwrap();
+
+        // the new worker resumes from what the aggregator has, and recomputes from row 3
+        let mut chunker = RowChunker::new("task", "0", 10, 3);
+        chunker.resume_from(&assembler.resume_point());
+        assert_eq!(chunker.next_row(), 4);
+        let messages = chunker.push(3, &rows(3, 7)).unwrap();
+        assert_eq!(messages[0].get_sequence(), 2);
+        assert_eq!(messages[0].get_first_row(), 4);
+        assert!(messages.last().unwrap().get_last());
+        assert!(chunker.is_done());
+
+        // a replayed message is rejected
+        assert!(assembler.accept(&messages[1]).has_error());
+        for m in messages.iter() {
+            let ack = assembler.accept(m);
+            assert!(!ack.has_error(), "{}", ack.get_error());
+        }
+        assert!(assembler.is_complete());
+        assert_eq!(
+            durations(&assembler.finish().unwrap()),
+            (0..10).collect::<Vec<i64>>()
+        );
+    }
+
+    #[test]
+    fn test_assembler_errors() {
+        let mut chunker = RowChunker::new("task", "1", 2, 1);
+        let messages = chunker.push(0, &rows(0, 2)).unwrap();
+
+        let mut assembler = RowAssembler::new("task", "0");
+        assert!(assembler.accept(&messages[0]).has_error());
+
+        let mut assembler = RowAssembler::new("task", "1");
+        let ack = assembler.accept(&messages[1]);
+        assert_eq!(ack.get_error(), "expected sequence 0, got 1");
+        assert_eq!(ack.get_next_row(), 0);
+        assert!(!assembler.accept(&messages[0]).has_error());
+        assert!(!assembler.is_complete());
+        assert!(assembler.finish().is_err());
+    }
+
+    #[actix_rt::test]
+    async fn test_grpc_stream() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let incoming = futures::stream::unfold(listener, |l| async move {
+            Some((l.accept().await.map(|(s, _)| s), l))
+        });
+        let service = AssemblerService::new();
+        tokio::spawn(
+            Server::builder()
+                .add_service(MatrixRowStreamServer::new(service.clone()))
+                .serve_with_incoming(incoming),
+        );
+
+        let mut client = MatrixRowStreamClient::connect(&format!("http://{}", addr))
+            .await
+            .unwrap();
+        let mut chunker = RowChunker::new("task", "0", 10, 3);
+        let messages = chunker.push(0, &rows(0, 4)).unwrap();
+        let ack = client
+            .push_rows(futures::stream::iter(messages))
+            .await
+            .unwrap();
+        assert!(!ack.has_error());
+        assert_eq!(ack.get_next_row(), 4);
+        assert!(!service.is_complete("task", "0"));
+
+        // a new worker resumes where the aggregator stopped
+        let point = client.get_resume_point("task", "0").await.unwrap();
+        assert_eq!(point.get_next_row(), 4);
+        let mut chunker = RowChunker::new("task", "0", 10, 3);
+        chunker.resume_from(&point);
+        let mut messages = chunker.push(3, &rows(3, 7)).unwrap();
+        let replayed = messages[0].clone();
+        messages.insert(1, replayed);
+        let ack = client
+            .push_rows(futures::stream::iter(messages.clone()))
+            .await
+            .unwrap();
+        assert_eq!(ack.get_error(), "expected sequence 3, got 2");
+        let ack = client
+            .push_rows(futures::stream::iter(messages.into_iter().skip(2)))
+            .await
+            .unwrap();
+        assert!(!ack.has_error(), "{}", ack.get_error());
+        assert!(service.is_complete("task", "0"));
+        assert_eq!(
+            durations(&service.finish("task", "0").unwrap()),
+            (0..10).collect::<Vec<i64>>()
+        );
+        assert!(service.finish("task", "0").is_err());
+    }
+}
@@ -0,0 +1,209 @@
+// plan of the matrix elements an optimization request needs. the solver only travels from the
+//  start of a vehicle to tasks or to its own end, between tasks, and from tasks to the end of
+//  a vehicle, so the other elements of the full N×N matrix over `locations` are never read.
+//  the plan also compacts the used locations: the solver matrix is built over them, and
+//  location indexes of the input are mapped to their position in it
+use crate::def::{OptimizationV2PostInput, Vehicle};
+use crate::Result;
+use std::collections::{BTreeSet, HashMap};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MatrixPlan {
+    // location index of the input at each index of the solver matrix
+    pub locations: Vec<usize>,
+    // (origin, destination) of the elements to compute, as indexes of the solver matrix
+    pub pairs: BTreeSet<(usize, usize)>,
+}
+
+fn location_index(index: i64, count: usize, task: &str) -> Result<usize> {
+    if index < 0 || index as usize >= count {
+        bail!(
+            "{}: location index {} is out of the {} locations",
+            task,
+            index,
+            count
+        );
+    }
+    Ok(index as usize)
+}
+
+// ends of a vehicle as (starts, ends), a vehicle with a depot starts and ends there, and one
+//  without start, end or depot may use any depot
+fn vehicle_ends(
+    vehicle: &Vehicle,
+    depots: &HashMap<u64, usize>,
+    count: usize,
+) -> Result<(Vec<usize>, Vec<usize>)> {
+    let task = format!("vehicle {}", vehicle.id);
+    let mut starts = vec![];
+    let mut ends = vec![];
+    if let Some(i) = vehicle.start_index {
+        starts.push(location_index(i as i64, count, &task)?);
+    }
+    if let Some(i) = vehicle.end_index {
+        ends.push(location_index(i as i64, count, &task)?);
+    }
+    if let Some(depot) = vehicle.depot {
+        let i = match depots.get(&depot) {
+            Some(i) => *i,
+            None => bail!("{}: depot {} does not exist", task, depot),
+        };
+        if starts.is_empty() {
+            starts.push(i);
+        }
+        if ends.is_empty() {
+            ends.push(i);
+        }
+    } else if starts.is_empty() && ends.is_empty() {
+        let mut all: Vec<usize> = depots.values().copied().collect();
+        all.sort_unstable();
+        all.dedup();
+        starts = all.clone();
+        ends = all;
+    }
+    Ok((starts, ends))
+}
+
+impl MatrixPlan {
+    pub fn new(input: &OptimizationV2PostInput) -> Result<MatrixPlan> {
+        let count = input.locations.location.len();
+        let mut depots = HashMap::new();
+        for depot in input.depots.iter().flatten() {
+            let i = location_index(
+                depot.location_index as i64,
+                count,
+                &format!("depot {}", depot.id),
+            )?;
+            depots.insert(depot.id, i);
+        }
+        let mut tasks = BTreeSet::new();
+        for job in input.jobs.iter().flatten() {
+            tasks.insert(location_index(
+                job.location_index as i64,
+                count,
+                &format!("job {}", job.id),
+            )?);
+        }
+        for shipment in input.shipments.iter().flatten() {
+            for step in [&shipment.pickup, &shipment.delivery] {
+                tasks.insert(location_index(
+                    step.location_index as i64,
+                    count,
+                    &format!("shipment {}", shipment.pickup.id),
+                )?);
+            }
+        }
+
+        // pairs over location indexes of the input first
+        let mut pairs = BTreeSet::new();
+        for from in tasks.iter() {
+            for to in tasks.iter() {
+                pairs.insert((*from, *to));
+            }
+        }
+        for vehicle in input.vehicles.iter() {
+            let (starts, ends) = vehicle_ends(vehicle, &depots, count)?;
+            for start in starts.iter() {
+                tasks.iter().for_each(|t| {
+                    pairs.insert((*start, *t));
+                });
+                ends.iter().for_each(|e| {
+                    pairs.insert((*start, *e));
+                });
+            }
+            for end in ends.iter() {
+                tasks.iter().for_each(|t| {
+                    pairs.insert((*t, *end));
+                });
+            }
+        }
+
+        let used: BTreeSet<usize> = pairs.iter().flat_map(|(a, b)| vec![*a, *b]).collect();
+        let locations: Vec<usize> = used.into_iter().collect();
+        let compact: HashMap<usize, usize> =
+            locations.iter().enumerate().map(|(i, l)| (*l, i)).collect();
+        // the diagonal is always 0
+        let pairs = pairs
+            .into_iter()
+            .filter(|(a, b)| a != b)
+            .map(|(a, b)| (compact[&a], compact[&b]))
+            .collect();
+        Ok(MatrixPlan { locations, pairs })
+    }
+
+    // index of a location of the input in the solver matrix
+    pub fn matrix_index(&self, location_index: usize) -> Option<usize> {
+        self.locations.binary_search(&location_index).ok()
+    }
+
+    // origins and destinations of the plan, as indexes of the solver matrix. a rectangular
+    //  matrix call over them covers every pair
+    pub fn origins(&self) -> Vec<usize> {
+        let set: BTreeSet<usize> = self.pairs.iter().map(|(a, _)| *a).collect();
+        set.into_iter().collect()
+    }
+
+    pub fn destinations(&self) -> Vec<usize> {
+        let set: BTreeSet<usize> = self.pairs.iter().map(|(_, b)| *b).collect();
+        set.into_iter().collect()
+    }
+
+    // assemble builds the square solver matrix from the computed elements, the elements that
+    //  are not part of the plan are never read by the solver and are left at 0
+    pub fn assemble<F: Fn(usize, usize) -> u64>(&self, value: F) -> Vec<Vec<u64>> {
+        let n = self.locations.len();
+        let mut matrix = vec![vec![0; n]; n];
+        for (a, b) in self.pairs.iter() {
+            matrix[*a][*b] = value(*a, *b);
+        }
+        matrix
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_matrix_plan() {
+        let input: OptimizationV2PostInput = serde_json::from_value(json!({
+            "locations": {"id": 1, "location": ["1.30,103.80", "1.31,103.81", "1.32,103.82", "1.33,103.83", "1.34,103.84"]},
+            "jobs": [
+                {"id": 1, "location_index": 1},
+                {"id": 2, "location_index": 3},
+            ],
+            "vehicles": [
+                {"id": 1, "start_index": 0},
+                {"id": 2, "depot": 7},
+            ],
+            "depots": [{"id": 7, "location_index": 4}],
+        }))
+        .unwrap();
+        let plan = MatrixPlan::new(&input).unwrap();
+        // location 2 is not used
+        assert_eq!(plan.locations, vec![0, 1, 3, 4]);
+        assert_eq!(plan.matrix_index(3), Some(2));
+        assert_eq!(plan.matrix_index(2), None);
+        let expected: BTreeSet<(usize, usize)> = vec![
+            (0, 1),
+            (0, 2),
+            (1, 2),
+            (2, 1),
+            (3, 1),
+            (3, 2),
+            (1, 3),
+            (2, 3),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(plan.pairs, expected);
+        assert_eq!(plan.origins(), vec![0, 1, 2, 3]);
+        assert_eq!(plan.destinations(), vec![1, 2, 3]);
+
+        let matrix = plan.assemble(|a, b| (plan.locations[a] * 10 + plan.locations[b]) as u64);
+        assert_eq!(matrix[0][2], 3);
+        assert_eq!(matrix[3][1], 41);
+        assert_eq!(matrix[1][0], 0);
+    }
+}
@@ -0,0 +1,182 @@
+use crate::def::{MatrixOutput, Row, ServerTiming};
+use crate::Result;
+use serde::Serialize;
+use std::io;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+// rows are flushed to async writers in chunks of about this size
+const ASYNC_CHUNK_SIZE: usize = 64 * 1024;
+
+// write_header and write_footer write the fields before and after `rows`, output is identical
+//  to serde_json for MatrixOutput
+fn write_header<W: io::Write>(
+    status: &str,
+    warning: &Option<Vec<String>>,
+    w: &mut W,
+) -> Result<()> {
+    w.write_all(b"{\"status\":")?;
+    serde_json::to_writer(&mut *w, status)?;
+    if let Some(warning) = warning {
+        w.write_all(b",\"warning\":")?;
+        serde_json::to_writer(&mut *w, warning)?;
+    }
+    w.write_all(b",\"rows\":[")?;
+    Ok(())
+}
+
+fn write_footer<W: io::Write>(server_timings: &Option<Vec<ServerTiming>>, w: &mut W) -> Result<()> {
+    w.write_all(b"]")?;
+    if let Some(server_timings) = server_timings {
+        w.write_all(b",\"server_timings\":")?;
+        serde_json::to_writer(&mut *w, server_timings)?;
+    }
+    w.write_all(b"}")?;
+    Ok(())
+}
+
+fn write_row<W: io::Write, R: Serialize>(idx: usize, row: &R, w: &mut W) -> Result<()> {
+    if idx > 0 {
+        w.write_all(b",")?;
+    }
+    serde_json::to_writer(&mut *w, row)?;
+    Ok(())
+}
+
+// write_matrix_rows serializes a MatrixOutput json row by row, rows may be produced lazily
+pub fn write_matrix_rows<W, I, R>(
+    status: &str,
+    warning: &Option<Vec<String>>,
+    rows: I,
+    server_timings: &Option<Vec<ServerTiming>>,
+    mut w: W,
+) -> Result<()>
+where
+    W: io::Write,
+    I: IntoIterator<Item = R>,
+    R: std::borrow::Borrow<Row>,
+{
+    write_header(status, warning, &mut w)?;
+    for (idx, row) in rows.into_iter().enumerate() {
+        write_row(idx, row.borrow(), &mut w)?;
+    }
+    write_footer(server_timings, &mut w)?;
+    w.flush()?;
+    Ok(())
+}
+
+pub fn write_matrix_output<W: io::Write>(output: &MatrixOutput, w: W) -> Result<()> {
+    write_matrix_rows(
+        output.status.as_str(),
+        &output.warning,
+        output.rows.iter(),
+        &output.server_timings,
+        w,
+    )
+}
+
+pub async fn write_matrix_rows_async<W, I, R>(
+    status: &str,
+    warning: &Option<Vec<String>>,
+    rows: I,
+    server_timings: &Option<Vec<ServerTiming>>,
+    w: &mut W,
+) -> Result<()>
+where
+    W: AsyncWrite + Unpin,
+    I: IntoIterator<Item = R>,
+    R: std::borrow::Borrow<Row>,
+{
+    let mut buf = Vec::with_capacity(ASYNC_CHUNK_SIZE * 2);
+    write_header(status, warning, &mut buf)?;
+    for (idx, row) in rows.into_iter().enumerate() {
+        write_row(idx, row.borrow(), &mut buf)?;
+        if buf.len() >= ASYNC_CHUNK_SIZE {
+            w.write_all(&buf).await?;
+            buf.clear();
+        }
+    }
+    write_footer(server_timings, &mut buf)?;
+    w.write_all(&buf).await?;
+    w.flush().await?;
+    Ok(())
+}
+
+pub async fn write_matrix_output_async<W: AsyncWrite + Unpin>(
+    output: &MatrixOutput,
+    w: &mut W,
+) -> Result<()> {
+    write_matrix_rows_async(
+        output.status.as_str(),
+        &output.warning,
+        output.rows.iter(),
+        &output.server_timings,
+        w,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn output(
+        warning: Option<Vec<String>>,
+        server_timings: Option<Vec<ServerTiming>>,
+    ) -> MatrixOutput {
+        let mut o: MatrixOutput = serde_json::from_value(json!({
+            "status": "Ok",
+            "rows": [
+                {"elements": [{"duration": {"value": 1}, "distance": {"value": 10}}, {"duration": {"value": 2}, "distance": {"value": 20}}]},
+                {"elements": [{"duration": {"value": 3}, "distance": {"value": 30}, "raw_duration": {"value": 4}}, {"duration": {"value": 0}, "distance": {"value": 0}}]},
+                {"elements": [{"duration": {"value": 5}, "distance": {"value": 50}}, {"duration": {"value": 6}, "distance": {"value": 60}}]},
+            ],
+        }))
+        .unwrap();
+        o.warning = warning;
+        o.server_timings = server_timings;
+        o
+    }
+
+    fn timings() -> Vec<ServerTiming> {
+        vec![
+            ServerTiming {
+                name: "engine".to_string(),
+                duration_ms: 12.5,
+            },
+            ServerTiming {
+                name: "post_processing".to_string(),
+                duration_ms: 0.25,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_write_matrix_output() {
+        for warning in [None, Some(vec!["w \"1\"".to_string()])] {
+            for server_timings in [None, Some(timings())] {
+                let o = output(warning.clone(), server_timings);
+                let mut buf = vec![];
+                write_matrix_output(&o, &mut buf).unwrap();
+                assert_eq!(buf, serde_json::to_vec(&o).unwrap());
+            }
+        }
+
+        let o = output(None, Some(timings()));
+        let page = o.page(1, 5);
+        assert_eq!(page.rows_offset, 1);
+        assert_eq!(page.total_rows, 3);
+        assert_eq!(page.rows.len(), 2);
+        assert_eq!(page.rows[0].elements[0].duration.value, 3);
+        assert_eq!(page.server_timings, Some(timings()));
+        assert!(o.page(10, 5).rows.is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn test_write_matrix_output_async() {
+        let o = output(Some(vec!["warning".to_string()]), Some(timings()));
+        let mut buf: Vec<u8> = vec![];
+        write_matrix_output_async(&o, &mut buf).await.unwrap();
+        assert_eq!(buf, serde_json::to_vec(&o).unwrap());
+    }
+}
@@ -0,0 +1,353 @@
+use crate::def::{
+    Element, IntValue, MatrixConciseOutput, MatrixInput, MatrixOutput, Row, STATUS_OK,
+};
+use crate::engine_response::MATRIX_UNREACHABLE_VALUE;
+use serde::Serialize;
+
+pub const SYMMETRIC_WARNING: &str =
+    "symmetric approximation used, values below the diagonal mirror the ones above it";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MatrixFormat {
+    Full,
+    Concise,
+}
+
+impl MatrixFormat {
+    // parse returns Full for anything but `concise`
+    pub fn parse(format: Option<&str>) -> MatrixFormat {
+        match format.map(|f| f.trim().to_lowercase()) {
+            Some(f) if f == "concise" => MatrixFormat::Concise,
+            _ => MatrixFormat::Full,
+        }
+    }
+}
+
+// MatrixData is the internal representation of a matrix, values are stored row major
+//  and only rounded when rendered
+#[derive(Clone, Debug)]
+pub struct MatrixData {
+    pub status: String,
+    pub warning: Option<Vec<String>>,
+    pub origins: usize,
+    pub destinations: usize,
+    // None for unreachable pairs
+    pub durations: Vec<Option<f64>>,
+    pub distances: Vec<Option<f64>>,
+    pub raw_durations: Option<Vec<Option<f64>>>,
+    pub predicted_durations: Option<Vec<Option<f64>>>,
+}
+
+// round_value rounds a value to the nearest integer, unreachable pairs become MATRIX_UNREACHABLE_VALUE
+pub fn round_value(value: Option<f64>) -> i64 {
+    match value {
+        Some(v) if v.is_finite() && v >= 0.0 => v.round() as i64,
+        _ => MATRIX_UNREACHABLE_VALUE,
+    }
+}
+
+fn unround_value(value: i64) -> Option<f64> {
+    if value < 0 {
+        None
+    } else {
+        Some(value as f64)
+    }
+}
+
+impl MatrixData {
+    pub fn new(origins: usize, destinations: usize) -> MatrixData {
+        MatrixData {
+            status: STATUS_OK.to_string(),
+            warning: None,
+            origins,
+            destinations,
+            durations: vec![None; origins * destinations],
+            distances: vec![None; origins * destinations],
+            raw_durations: None,
+            predicted_durations: None,
+        }
+    }
+
+    fn element(&self, idx: usize, include_debug: bool) -> Element {
+        let debug_value = |values: &Option<Vec<Option<f64>>>| match values {
+            Some(v) if include_debug => Some(IntValue {
+                value: round_value(v[idx]),
+            }),
+            _ => None,
+        };
+        Element {
+            duration: IntValue {
+                value: round_value(self.durations[idx]),
+            },
+            distance: IntValue {
+                value: round_value(self.distances[idx]),
+            },
+            raw_duration: debug_value(&self.raw_durations),
+            predicted_duration: debug_value(&self.predicted_durations),
+            co2_emissions: None,
+        }
+    }
+
+    pub fn to_output(&self, include_debug: bool) -> MatrixOutput {
+        let rows = (0..self.origins)
+            .map(|o| Row {
+                elements: (0..self.destinations)
+                    .map(|d| self.element(o * self.destinations + d, include_debug))
+                    .collect(),
+            })
+            .collect();
+        MatrixOutput {
+            status: self.status.clone(),
+            warning: self.warning.clone(),
+            server_timings: None,
+            rows,
+        }
+    }
+
+    pub fn to_concise_output(&self) -> MatrixConciseOutput {
+        let rows = (0..self.origins)
+            .map(|o| {
+                (0..self.destinations)
+                    .map(|d| {
+                        let idx = o * self.destinations + d;
+                        vec![
+                            round_value(self.durations[idx]),
+                            round_value(self.distances[idx]),
+                        ]
+                    })
+                    .collect()
+            })
+            .collect();
+        MatrixConciseOutput {
+            status: self.status.clone(),
+            rows,
+            warning: self.warning.clone(),
+        }
+    }
+}
+
+// symmetric_eligible tells if only the upper triangle of the matrix of input needs computing:
+//  origins and destinations are the same, the mode has no turn penalties and nothing makes the
+//  direction of travel matter
+pub fn symmetric_eligible(input: &MatrixInput) -> bool {
+    let directional = [
+        &input.approaches,
+        &input.origin_approaches,
+        &input.origins_approach,
+        &input.destinations_approach,
+        &input.bearings,
+    ];
+    input
+        .mode
+        .as_ref()
+        .map(|m| m.is_symmetric())
+        .unwrap_or(false)
+        && input.origins.trim() == input.destinations.trim()
+        && directional
+            .iter()
+            .all(|v| v.as_deref().map(|s| s.trim().is_empty()).unwrap_or(true))
+}
+
+// upper_triangle lists the (origin, destination) pairs to compute for a symmetric n×n matrix,
+//  the diagonal included
+pub fn upper_triangle(n: usize) -> Vec<(usize, usize)> {
+    (0..n).flat_map(|o| (o..n).map(move |d| (o, d))).collect()
+}
+
+impl MatrixData {
+    // mirror_triangle copies the upper triangle of a square matrix below the diagonal and
+    //  records the approximation in the warnings, other matrices are left as is
+    pub fn mirror_triangle(&mut self) {
+        if self.origins != self.destinations {
+            return;
+        }
+        let n = self.origins;
+        let mirror = |values: &mut Vec<Option<f64>>| {
+            for o in 0..n {
+                for d in 0..o {
+                    values[o * n + d] = values[d * n + o];
+                }
+            }
+        };
+        mirror(&mut self.durations);
+        mirror(&mut self.distances);
+        if let Some(v) = self.raw_durations.as_mut() {
+            mirror(v);
+        }
+        if let Some(v) = self.predicted_durations.as_mut() {
+            mirror(v);
+        }
+        let warning = self.warning.get_or_insert_with(Vec::new);
+        if !warning.iter().any(|w| w == SYMMETRIC_WARNING) {
+            warning.push(SYMMETRIC_WARNING.to_string());
+        }
+    }
+}
+
+// expand_symmetric mirrors the computed upper triangle of output into the full matrix
+pub fn expand_symmetric(output: &MatrixOutput) -> MatrixOutput {
+    let mut data = MatrixData::from(output);
+    data.mirror_triangle();
+    let include_debug = data.raw_durations.is_some() || data.predicted_durations.is_some();
+    data.to_output(include_debug)
+}
+
+impl From<&MatrixOutput> for MatrixData {
+    fn from(output: &MatrixOutput) -> Self {
+        let origins = output.rows.len();
+        let destinations = output.rows.first().map(|r| r.elements.len()).unwrap_or(0);
+        let mut data = MatrixData::new(origins, destinations);
+        data.status = output.status.clone();
+        data.warning = output.warning.clone();
+        let elements = || {
+            output
+                .rows
+                .iter()
+                .flat_map(|r| r.elements.iter().take(destinations))
+        };
+        let has_raw = elements().any(|e| e.raw_duration.is_some());
+        let has_predicted = elements().any(|e| e.predicted_duration.is_some());
+        if has_raw {
+            data.raw_durations = Some(vec![None; origins * destinations]);
+        }
+        if has_predicted {
+            data.predicted_durations = Some(vec![None; origins * destinations]);
+        }
+        for (o, row) in output.rows.iter().enumerate() {
+            for (d, e) in row.elements.iter().take(destinations).enumerate() {
+                let idx = o * destinations + d;
+                data.durations[idx] = unround_value(e.duration.value);
+                data.distances[idx] = unround_value(e.distance.value);
+                if let (Some(v), Some(raw)) = (data.raw_durations.as_mut(), e.raw_duration.as_ref())
+                {
+                    v[idx] = unround_value(raw.value);
+                }
+                if let (Some(v), Some(predicted)) = (
+                    data.predicted_durations.as_mut(),
+                    e.predicted_duration.as_ref(),
+                ) {
+                    v[idx] = unround_value(predicted.value);
+                }
+            }
+        }
+        data
+    }
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum MatrixView {
+    Full(MatrixOutput),
+    Concise(MatrixConciseOutput),
+}
+
+impl MatrixView {
+    // render builds the representation requested by the client, debug fields are only
+    //  kept for the full format when include_debug is set
+    pub fn render(data: &MatrixData, format: MatrixFormat, include_debug: bool) -> MatrixView {
+        match format {
+            MatrixFormat::Full => MatrixView::Full(data.to_output(include_debug)),
+            MatrixFormat::Concise => MatrixView::Concise(data.to_concise_output()),
+        }
+    }
+
+    pub fn strip_debug(&mut self) {
+        if let MatrixView::Full(output) = self {
+            strip_debug_fields(output);
+        }
+    }
+}
+
+// strip_debug_fields removes raw/predicted durations, used for external keys
+pub fn strip_debug_fields(output: &mut MatrixOutput) {
+    for row in output.rows.iter_mut() {
+        for e in row.elements.iter_mut() {
+            e.raw_duration = None;
+            e.predicted_duration = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data() -> MatrixData {
+        let mut data = MatrixData::new(2, 2);
+        data.durations = vec![Some(0.0), Some(10.4), Some(10.6), None];
+        data.distances = vec![Some(0.0), Some(100.5), Some(99.4), None];
+        data.raw_durations = Some(vec![Some(0.0), Some(8.0), Some(9.0), None]);
+        data
+    }
+
+    #[test]
+    fn test_render() {
+        let data = data();
+        let full = match MatrixView::render(&data, MatrixFormat::Full, true) {
+            MatrixView::Full(o) => o,
+            _ => panic!("unexpected view"),
+        };
+        assert_eq!(full.rows[0].elements[1].duration.value, 10);
+        assert_eq!(full.rows[0].elements[1].distance.value, 101);
+        assert_eq!(
+            full.rows[0].elements[1]
+                .raw_duration
+                .as_ref()
+                .unwrap()
+                .value,
+            8
+        );
+        assert!(full.rows[0].elements[1].predicted_duration.is_none());
+        assert_eq!(
+            full.rows[1].elements[1].duration.value,
+            MATRIX_UNREACHABLE_VALUE
+        );
+
+        let concise = match MatrixView::render(&data, MatrixFormat::parse(Some("Concise")), true) {
+            MatrixView::Concise(o) => o,
+            _ => panic!("unexpected view"),
+        };
+        assert_eq!(concise.rows[1][0], vec![11, 99]);
+        assert_eq!(concise.rows[1][1], vec![-1, -1]);
+
+        // round trip through the full output keeps the rounded values
+        let back = MatrixData::from(&full).to_concise_output();
+        assert_eq!(back.rows, concise.rows);
+
+        let mut view = MatrixView::render(&data, MatrixFormat::Full, true);
+        view.strip_debug();
+        let json = serde_json::to_value(&view).unwrap();
+        assert!(json["rows"][0]["elements"][1].get("raw_duration").is_none());
+        let json =
+            serde_json::to_value(MatrixView::render(&data, MatrixFormat::Full, false)).unwrap();
+        assert!(json["rows"][0]["elements"][1].get("raw_duration").is_none());
+    }
+
+    #[test]
+    fn test_symmetric() {
+        let input: MatrixInput = serde_json::from_value(serde_json::json!({
+            "origins": "1.30,103.80|1.31,103.81",
+            "destinations": "1.30,103.80|1.31,103.81",
+            "mode": "bicycle",
+        }))
+        .unwrap();
+        assert!(symmetric_eligible(&input));
+        let mut car = input.clone();
+        car.mode = Some("car".into());
+        assert!(!symmetric_eligible(&car));
+        let mut bearings = input.clone();
+        bearings.bearings = Some("0,90|".to_string());
+        assert!(!symmetric_eligible(&bearings));
+
+        assert_eq!(upper_triangle(2), vec![(0, 0), (0, 1), (1, 1)]);
+
+        let mut data = MatrixData::new(2, 2);
+        data.durations = vec![Some(0.0), Some(10.0), None, Some(0.0)];
+        data.distances = vec![Some(0.0), Some(100.0), None, Some(0.0)];
+        let full = expand_symmetric(&data.to_output(false));
+        assert_eq!(full.rows[1].elements[0].duration.value, 10);
+        assert_eq!(full.rows[1].elements[0].distance.value, 100);
+        assert_eq!(full.warning, Some(vec![SYMMETRIC_WARNING.to_string()]));
+        assert!(full.rows[1].elements[0].raw_duration.is_none());
+    }
+}
@@ -0,0 +1,295 @@
+// sharding of massive matrix requests. plan_chunks splits origins×destinations into blocks of
+//  at most max_elements pairs and max_side coordinates on each side, keeping blocks of similar
+//  sizes. each block becomes a MassiveDistanceMatrixInput of its own, whose output is placed
+//  back at its offsets in the final grid
+use crate::def::{MassiveDistanceMatrixInput, MatrixInput, MatrixOutput};
+use crate::matrix_view::MatrixData;
+use crate::util::split_positional_list;
+use crate::Result;
+use std::ops::Range;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChunkSpec {
+    pub origin_range: Range<usize>,
+    pub dest_range: Range<usize>,
+    pub chunk_id: String,
+}
+
+impl ChunkSpec {
+    pub fn elements(&self) -> usize {
+        self.origin_range.len() * self.dest_range.len()
+    }
+}
+
+// ranges splits 0..n into count ranges whose lengths differ by one at most
+fn ranges(n: usize, count: usize) -> Vec<Range<usize>> {
+    let (size, extra) = (n / count, n % count);
+    let mut start = 0;
+    (0..count)
+        .map(|i| {
+            let end = start + size + if i < extra { 1 } else { 0 };
+            let r = start..end;
+            start = end;
+            r
+        })
+        .collect()
+}
+
+// plan_chunks returns the chunks in row major order, chunk ids being their positions. limits
+//  below 1 are taken as 1
+pub fn plan_chunks(
+    n_origins: usize,
+    n_destinations: usize,
+    max_elements: usize,
+    max_side: usize,
+) -> Vec<ChunkSpec> {
+    if n_origins == 0 || n_destinations == 0 {
+        return vec![];
+    }
+    let max_elements = max_elements.max(1);
+    let max_side = max_side.max(1);
+    let dest_side = n_destinations.min(max_side).min(max_elements);
+    let dest_count = n_destinations.div_ceil(dest_side);
+    // the widest chunk after balancing, which bounds the height of chunks
+    let dest_width = n_destinations.div_ceil(dest_count);
+    let origin_side = n_origins.min(max_side).min(max_elements / dest_width);
+    let origin_count = n_origins.div_ceil(origin_side);
+
+    let dest_ranges = ranges(n_destinations, dest_count);
+    let mut chunks = vec![];
+    for origin_range in ranges(n_origins, origin_count) {
+        for dest_range in dest_ranges.iter() {
+            chunks.push(ChunkSpec {
+                origin_range: origin_range.clone(),
+                dest_range: dest_range.clone(),
+                chunk_id: chunks.len().to_string(),
+            });
+        }
+    }
+    chunks
+}
+
+// per coordinate field of the input, cut to the coordinates of a chunk
+struct Sliced<'a> {
+    separator: char,
+    items: Vec<&'a str>,
+}
+
+impl<'a> Sliced<'a> {
+    fn new(
+        name: &str,
+        value: &'a Option<String>,
+        separator: char,
+        expected: usize,
+    ) -> Result<Option<Sliced<'a>>> {
+        let value = match value.as_deref().filter(|v| !v.trim().is_empty()) {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+        let items = split_positional_list(value, separator);
+        if items.len() != expected {
+            bail!(
+                "{} count {} does not match coordinates count {}",
+                name,
+                items.len(),
+                expected
+            );
+        }
+        Ok(Some(Sliced { separator, items }))
+    }
+
+    fn slice(&self, ranges: &[Range<usize>]) -> String {
+        let items: Vec<&str> = ranges
+            .iter()
+            .flat_map(|r| self.items[r.clone()].iter().copied())
+            .collect();
+        items.join(&self.separator.to_string())
+    }
+}
+
+// slice_input builds the inputs of chunks over input. `approaches` and `bearings` cover origins
+//  then destinations, `origin_approaches` the origins only, other fields are kept as is
+pub fn slice_input(
+    input: &MatrixInput,
+    chunks: &[ChunkSpec],
+    task_id: &str,
+    gcs_path: &Option<String>,
+) -> Result<Vec<MassiveDistanceMatrixInput>> {
+    let origins = split_positional_list(&input.origins, '|');
+    let destinations = split_positional_list(&input.destinations, '|');
+    let (n_origins, n_destinations) = (origins.len(), destinations.len());
+    let total = n_origins + n_destinations;
+    let approaches = Sliced::new("approaches", &input.approaches, '|', total)?;
+    let origin_approaches = Sliced::new(
+        "origin_approaches",
+        &input.origin_approaches,
+        '|',
+        n_origins,
+    )?;
+    let bearings = Sliced::new("bearings", &input.bearings, ';', total)?;
+
+    let mut r = vec![];
+    for chunk in chunks.iter() {
+        if chunk.origin_range.end > n_origins || chunk.dest_range.end > n_destinations {
+            bail!(
+                "chunk {} is out of the {}x{} matrix",
+                chunk.chunk_id,
+                n_origins,
+                n_destinations
+            );
+        }
+        let both = [
+            chunk.origin_range.clone(),
+            chunk.dest_range.start + n_origins..chunk.dest_range.end + n_origins,
+        ];
+        let mut matrix_input = input.clone();
+        matrix_input.origins = origins[chunk.origin_range.clone()].join("|");
+        matrix_input.destinations = destinations[chunk.dest_range.clone()].join("|");
+        if let Some(v) = approaches.as_ref() {
+            matrix_input.approaches = Some(v.slice(&both));
+        }
+        if let Some(v) = origin_approaches.as_ref() {
+            matrix_input.origin_approaches = Some(v.slice(&both[..1]));
+        }
+        if let Some(v) = bearings.as_ref() {
+            matrix_input.bearings = Some(v.slice(&both));
+        }
+        r.push(MassiveDistanceMatrixInput {
+            matrix_input,
+            task_id: task_id.to_string(),
+            chunk_id: chunk.chunk_id.clone(),
+            gcs_path: gcs_path.clone(),
+        });
+    }
+    Ok(r)
+}
+
+// place_chunk copies the output of chunk into grid at the offsets of the chunk, warnings of the
+//  chunk are added to the ones of the grid
+pub fn place_chunk(grid: &mut MatrixData, chunk: &ChunkSpec, output: &MatrixOutput) -> Result<()> {
+    if chunk.origin_range.end > grid.origins || chunk.dest_range.end > grid.destinations {
+        bail!(
+            "chunk {} is out of the {}x{} matrix",
+            chunk.chunk_id,
+            grid.origins,
+            grid.destinations
+        );
+    }
+    let data = MatrixData::from(output);
+    if data.origins != chunk.origin_range.len() || data.destinations != chunk.dest_range.len() {
+        bail!(
+            "output of chunk {} is {}x{} instead of {}x{}",
+            chunk.chunk_id,
+            data.origins,
+            data.destinations,
+            chunk.origin_range.len(),
+            chunk.dest_range.len()
+        );
+    }
+    let width = grid.destinations;
+    let place = |to: &mut Vec<Option<f64>>, from: &[Option<f64>]| {
+        for (o, origin) in chunk.origin_range.clone().enumerate() {
+            let at = origin * width + chunk.dest_range.start;
+            let row = o * data.destinations;
+            to[at..at + data.destinations].copy_from_slice(&from[row..row + data.destinations]);
+        }
+    };
+    let size = grid.origins * grid.destinations;
+    place(&mut grid.durations, &data.durations);
+    place(&mut grid.distances, &data.distances);
+    if let Some(v) = data.raw_durations.as_ref() {
+        place(grid.raw_durations.get_or_insert(vec![None; size]), v);
+    }
+    if let Some(v) = data.predicted_durations.as_ref() {
+        place(grid.predicted_durations.get_or_insert(vec![None; size]), v);
+    }
+    for w in data.warning.iter().flatten() {
+        let warning = grid.warning.get_or_insert_with(Vec::new);
+        if !warning.contains(w) {
+            warning.push(w.clone());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_plan_chunks() {
+        let chunks = plan_chunks(5, 7, 12, 4);
+        // destinations in 2 chunks of 4 and 3, origins in 2 of 3 and 2
+        assert_eq!(chunks.len(), 4);
+        assert_eq!(chunks[0].origin_range, 0..3);
+        assert_eq!(chunks[0].dest_range, 0..4);
+        assert_eq!(chunks[3].origin_range, 3..5);
+        assert_eq!(chunks[3].dest_range, 4..7);
+        assert_eq!(chunks[3].chunk_id, "3");
+        assert!(chunks.iter().all(|c| c.elements() <= 12));
+        assert_eq!(chunks.iter().map(|c| c.elements()).sum::<usize>(), 35);
+
+        assert_eq!(plan_chunks(3, 3, 100, 100).len(), 1);
+        assert_eq!(plan_chunks(2, 3, 0, 0).len(), 6);
+        assert!(plan_chunks(0, 3, 10, 10).is_empty());
+    }
+
+    #[test]
+    fn test_slice_and_place() {
+        let input: MatrixInput = serde_json::from_value(json!({
+            "origins": "1.1,103.1|1.2,103.2|1.3,103.3",
+            "destinations": "1.4,103.4|1.5,103.5",
+            "mode": "car",
+            "approaches": "curb|unrestricted|curb||curb",
+            "bearings": "0,20;;90,45;;180,10",
+        }))
+        .unwrap();
+        // one origin by chunk
+        let chunks = plan_chunks(3, 2, 2, 2);
+        assert_eq!(chunks.len(), 3);
+        let inputs = slice_input(&input, &chunks, "task", &Some("gs://b/p".to_string())).unwrap();
+        let last = &inputs[2].matrix_input;
+        assert_eq!(last.origins, "1.3,103.3");
+        assert_eq!(last.destinations, "1.4,103.4|1.5,103.5");
+        assert_eq!(last.approaches.as_deref(), Some("curb||curb"));
+        assert_eq!(last.bearings.as_deref(), Some("90,45;;180,10"));
+        assert_eq!(inputs[2].chunk_id, "2");
+        assert_eq!(inputs[2].gcs_path.as_deref(), Some("gs://b/p"));
+
+        let chunks = plan_chunks(3, 2, 2, 1);
+        assert_eq!(chunks.len(), 6);
+        assert_eq!(
+            slice_input(&input, &chunks, "task", &None).unwrap()[5]
+                .matrix_input
+                .approaches
+                .as_deref(),
+            Some("curb|curb")
+        );
+
+        let mut bad = input.clone();
+        bad.bearings = Some("0,20".to_string());
+        assert!(slice_input(&bad, &chunks, "task", &None).is_err());
+
+        let mut grid = MatrixData::new(3, 2);
+        for chunk in chunks.iter() {
+            let mut data = MatrixData::new(chunk.origin_range.len(), 1);
+            for (i, o) in chunk.origin_range.clone().enumerate() {
+                data.durations[i] = Some((o * 10 + chunk.dest_range.start) as f64);
+                data.distances[i] = Some(1.0);
+            }
+            data.warning = Some(vec!["slow".to_string()]);
+            place_chunk(&mut grid, chunk, &data.to_output(false)).unwrap();
+        }
+        assert_eq!(
+            grid.durations,
+            vec![0.0, 1.0, 10.0, 11.0, 20.0, 21.0]
+                .into_iter()
+                .map(Some)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(grid.warning, Some(vec!["slow".to_string()]));
+        let wrong = MatrixData::new(2, 2).to_output(false);
+        assert!(place_chunk(&mut grid, &chunks[3], &wrong).is_err());
+    }
+}
@@ -0,0 +1,442 @@
+// upload and download of the outputs of massive matrix chunks. the output of chunk c of task t
+//  is stored at `<gcs_path>/<t>/<c>.<format>`, next to a `.crc32` object holding the CRC-32 of
+//  its bytes in hex, which is written last so that readers never accept a partial upload.
+//  the binary format only carries the values of the matrix, status and warnings are lost
+use crate::def::{MatrixOutput, SignedUrl};
+use crate::matrix_binary::binary_decode;
+use crate::signed_url::UrlSigner;
+use crate::Result;
+use async_process::Command;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+pub const CHECKSUM_EXTENSION: &str = "crc32";
+
+pub type StoreFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+// ObjectStore reads and writes whole objects by path
+pub trait ObjectStore: Sync {
+    fn put<'a>(&'a self, path: &'a str, data: Vec<u8>) -> StoreFuture<'a, ()>;
+    fn get<'a>(&'a self, path: &'a str) -> StoreFuture<'a, Vec<u8>>;
+
+    // url_signer signs the URLs shared with customers, None when objects can not be shared
+    fn url_signer(&self) -> Option<&dyn UrlSigner> {
+        None
+    }
+}
+
+// GsutilStore goes through the gsutil command, like the config loaders of util built with the
+//  gsutil feature
+#[derive(Clone, Debug, Default)]
+pub struct GsutilStore;
+
+static UPLOAD_SEQ: AtomicU64 = AtomicU64::new(0);
+
+impl ObjectStore for GsutilStore {
+    fn put<'a>(&'a self, path: &'a str, data: Vec<u8>) -> StoreFuture<'a, ()> {
+        Box::pin(async move {
+            let file = std::env::temp_dir().join(format!(
+                "nbroutes-upload-{}-{}",
+                std::process::id(),
+                UPLOAD_SEQ.fetch_add(1, Ordering::Relaxed)
+            ));
+            std::fs::write(&file, &data)?;
+            let output = Command::new("gsutil")
+                .arg("-q")
+                .arg("cp")
+                .arg(&file)
+                .arg(path)
+                .output()
+                .await;
+            let _ = std::fs::remove_file(&file);
+            let output = output?;
+            if !output.status.success() {
+                bail!(
+                    "error uploading {} using gsutil: {}",
+                    path,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+            Ok(())
+        })
+    }
+
+    fn get<'a>(&'a self, path: &'a str) -> StoreFuture<'a, Vec<u8>> {
+        Box::pin(async move {
+            let output = Command::new("gsutil").arg("cat").arg(path).output().await?;
+            if !output.status.success() {
+                bail!(
+                    "error downloading {} using gsutil: {}",
+                    path,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+            Ok(output.stdout)
+        })
+    }
+}
+
+// MemoryStore keeps the objects in memory, for tests and local runs
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    objects: Mutex<HashMap<String, Vec<u8>>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> MemoryStore {
+        MemoryStore::default()
+    }
+}
+
+impl ObjectStore for MemoryStore {
+    fn put<'a>(&'a self, path: &'a str, data: Vec<u8>) -> StoreFuture<'a, ()> {
+        Box::pin(async move {
+            self.objects.lock().unwrap().insert(path.to_string(), data);
+            Ok(())
+        })
+    }
+
+    fn get<'a>(&'a self, path: &'a str) -> StoreFuture<'a, Vec<u8>> {
+        Box::pin(async move {
+            match self.objects.lock().unwrap().get(path) {
+                Some(v) => Ok(v.clone()),
+                None => bail!("object {} does not exist", path),
+            }
+        })
+    }
+}
+
+// SigningStore adds the signing of URLs to store
+pub struct SigningStore<S: ObjectStore> {
+    pub store: S,
+    pub signer: Box<dyn UrlSigner>,
+}
+
+impl<S: ObjectStore> ObjectStore for SigningStore<S> {
+    fn put<'a>(&'a self, path: &'a str, data: Vec<u8>) -> StoreFuture<'a, ()> {
+        self.store.put(path, data)
+    }
+
+    fn get<'a>(&'a self, path: &'a str) -> StoreFuture<'a, Vec<u8>> {
+        self.store.get(path)
+    }
+
+    fn url_signer(&self) -> Option<&dyn UrlSigner> {
+        Some(self.signer.as_ref())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChunkFormat {
+    Binary,
+    Json,
+}
+
+impl ChunkFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ChunkFormat::Binary => "bin",
+            ChunkFormat::Json => "json",
+        }
+    }
+
+    pub fn encode(&self, output: &MatrixOutput) -> Result<Vec<u8>> {
+        match self {
+            ChunkFormat::Binary => Ok(output.binary_encode()),
+            ChunkFormat::Json => Ok(serde_json::to_vec(output)?),
+        }
+    }
+
+    pub fn decode(&self, data: &[u8]) -> Result<MatrixOutput> {
+        match self {
+            ChunkFormat::Binary => binary_decode(data),
+            ChunkFormat::Json => Ok(serde_json::from_slice(data)?),
+        }
+    }
+}
+
+// attempts are made backoff, 2*backoff, 4*backoff... apart, up to max_backoff. the policy is
+//  shared by the remote calls of the crate (object stores, GCS reads, job callbacks), timeout
+//  being the one of each attempt of HTTP calls
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RetryPolicy {
+    pub attempts: u32,
+    pub backoff: Duration,
+    pub max_backoff: Duration,
+    pub timeout: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            attempts: 3,
+            backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(60),
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    // delay returns the backoff before the attempt following attempt (starting at 1)
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.backoff
+            .checked_mul(factor)
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff)
+    }
+
+    async fn run<'a, T, F>(&self, operation: &str, f: F) -> Result<T>
+    where
+        F: Fn() -> StoreFuture<'a, T>,
+    {
+        let mut attempt = 1;
+        loop {
+            match f().await {
+                Ok(v) => return Ok(v),
+                Err(e) if attempt >= self.attempts.max(1) => {
+                    bail!("{} failed after {} attempts: {}", operation, attempt, e)
+                }
+                Err(e) => {
+                    warn!("{} failed, attempt {}: {}", operation, attempt, e);
+                    tokio::time::sleep(self.delay(attempt)).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+}
+
+pub fn chunk_path(gcs_path: &str, task: &str, chunk: &str, format: ChunkFormat) -> String {
+    format!(
+        "{}/{}/{}.{}",
+        gcs_path.trim_end_matches('/'),
+        task,
+        chunk,
+        format.extension()
+    )
+}
+
+// crc32 is the IEEE CRC-32, as computed by gsutil and zlib
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for b in data.iter() {
+        crc ^= *b as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+pub async fn upload_chunk_output(
+    store: &dyn ObjectStore,
+    retry: &RetryPolicy,
+    gcs_path: &str,
+    task: &str,
+    chunk: &str,
+    output: &MatrixOutput,
+    format: ChunkFormat,
+) -> Result<()> {
+    let path = chunk_path(gcs_path, task, chunk, format);
+    let checksum_path = format!("{}.{}", path, CHECKSUM_EXTENSION);
+    let data = format.encode(output)?;
+    let checksum = format!("{:08x}", crc32(&data));
+    retry
+        .run(&format!("upload of {}", path), || {
+            store.put(&path, data.clone())
+        })
+        .await?;
+    retry
+        .run(&format!("upload of {}", checksum_path), || {
+            store.put(&checksum_path, checksum.clone().into_bytes())
+        })
+        .await
+}
+
+// download_chunk_output retries when the checksum does not match, as the output may be
+//  downloaded while being overwritten
+pub async fn download_chunk_output(
+    store: &dyn ObjectStore,
+    retry: &RetryPolicy,
+    gcs_path: &str,
+    task: &str,
+    chunk: &str,
+    format: ChunkFormat,
+) -> Result<MatrixOutput> {
+    let path = chunk_path(gcs_path, task, chunk, format);
+    let checksum_path = format!("{}.{}", path, CHECKSUM_EXTENSION);
+    let data = retry
+        .run(&format!("download of {}", path), || {
+            Box::pin(async {
+                let expected = store.get(&checksum_path).await?;
+                let expected = String::from_utf8_lossy(&expected).trim().to_lowercase();
+                let data = store.get(&path).await?;
+                let actual = format!("{:08x}", crc32(&data));
+                if actual != expected {
+                    bail!(
+                        "checksum {} does not match the expected {}",
+                        actual,
+                        expected
+                    );
+                }
+                Ok(data)
+            })
+        })
+        .await?;
+    format.decode(&data)
+}
+
+// chunk_url returns an URL to download the output of a chunk, valid for ttl, for the
+//  result_url of MassiveDistanceMatrixStatusOutput
+pub fn chunk_url(
+    store: &dyn ObjectStore,
+    gcs_path: &str,
+    task: &str,
+    chunk: &str,
+    format: ChunkFormat,
+    ttl: Duration,
+) -> Result<SignedUrl> {
+    let signer = match store.url_signer() {
+        Some(v) => v,
+        None => bail!("the store of {} can not sign URLs", gcs_path),
+    };
+    let now = chrono::Utc::now();
+    Ok(SignedUrl {
+        url: signer.sign_url(&chunk_path(gcs_path, task, chunk, format), ttl, now)?,
+        expires_at: now.timestamp() + ttl.as_secs() as i64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::def::{Element, IntValue, Row, STATUS_OK};
+    use crate::signed_url::S3Signer;
+
+    // fails the first puts
+    struct FlakyStore {
+        inner: MemoryStore,
+        failures: AtomicU64,
+    }
+
+    impl ObjectStore for FlakyStore {
+        fn put<'a>(&'a self, path: &'a str, data: Vec<u8>) -> StoreFuture<'a, ()> {
+            if self.failures.load(Ordering::SeqCst) > 0 {
+                self.failures.fetch_sub(1, Ordering::SeqCst);
+                return Box::pin(async { bail!("unavailable") });
+            }
+            self.inner.put(path, data)
+        }
+
+        fn get<'a>(&'a self, path: &'a str) -> StoreFuture<'a, Vec<u8>> {
+            self.inner.get(path)
+        }
+    }
+
+    fn output() -> MatrixOutput {
+        let element = |v| Element {
+            duration: IntValue { value: v },
+            distance: IntValue {
+                value: if v < 0 { v } else { v * 10 },
+            },
+            raw_duration: None,
+            predicted_duration: None,
+            co2_emissions: None,
+        };
+        MatrixOutput {
+            status: STATUS_OK.to_string(),
+            warning: None,
+            server_timings: None,
+            rows: vec![Row {
+                elements: vec![element(0), element(12), element(-1)],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_chunk_url() {
+        let ttl = Duration::from_secs(3600);
+        assert!(chunk_url(
+            &MemoryStore::new(),
+            "s3://b",
+            "t",
+            "0",
+            ChunkFormat::Json,
+            ttl
+        )
+        .is_err());
+        let store = SigningStore {
+            store: MemoryStore::new(),
+            signer: Box::new(S3Signer::new("AKID", "secret", "eu-west-1")),
+        };
+        let url = chunk_url(&store, "s3://b/mdm", "t", "0", ChunkFormat::Json, ttl).unwrap();
+        assert!(url
+            .url
+            .starts_with("https://b.s3.eu-west-1.amazonaws.com/mdm/t/0.json?"));
+        assert!(url.url.contains("X-Amz-Expires=3600"));
+        assert!(url.expires_at > chrono::Utc::now().timestamp());
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[actix_rt::test]
+    async fn test_upload_download() {
+        let retry = RetryPolicy {
+            attempts: 3,
+            backoff: Duration::from_millis(1),
+            ..Default::default()
+        };
+        let store = FlakyStore {
+            inner: MemoryStore::new(),
+            failures: AtomicU64::new(2),
+        };
+        for format in [ChunkFormat::Binary, ChunkFormat::Json] {
+            upload_chunk_output(&store, &retry, "gs://b/mdm/", "t", "3", &output(), format)
+                .await
+                .unwrap();
+            let back = download_chunk_output(&store, &retry, "gs://b/mdm", "t", "3", format)
+                .await
+                .unwrap();
+            assert_eq!(
+                serde_json::to_value(&back).unwrap(),
+                serde_json::to_value(output()).unwrap()
+            );
+        }
+        assert!(store.inner.get("gs://b/mdm/t/3.bin.crc32").await.is_ok());
+
+        store
+            .inner
+            .put("gs://b/mdm/t/3.json", b"{}".to_vec())
+            .await
+            .unwrap();
+        let err = download_chunk_output(&store, &retry, "gs://b/mdm", "t", "3", ChunkFormat::Json)
+            .await
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("does not match"));
+
+        store.failures.store(5, Ordering::SeqCst);
+        assert!(upload_chunk_output(
+            &store,
+            &retry,
+            "gs://b/mdm",
+            "t",
+            "4",
+            &output(),
+            ChunkFormat::Json
+        )
+        .await
+        .is_err());
+    }
+}
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use crate::def::{MassiveDistanceMatrixStatus, MassiveDistanceMatrixStatusEnum};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 lazy_static! {
     static ref STATUS: Arc<Mutex<HashMap<String, MassiveDistanceMatrixStatus>>> = Arc::new(Mutex::new(HashMap::new()));
@@ -29,15 +29,83 @@ pub fn get_status(task_id: String, chunk_id:String) -> MassiveDistanceMatrixStat
         message: "".to_string(),
         start_time: 0,
         output: None,
+        attempts: 0,
+        last_error: None,
+        next_retry_at: None,
     }
 }
 
-pub fn set_status(task_id: String, chunk_id:String, status: MassiveDistanceMatrixStatus)  {
+// set_status keeps the retry bookkeeping of the chunk when status has none, so that
+//  re-submitted chunks still count their previous attempts
+pub fn set_status(task_id: String, chunk_id:String, mut status: MassiveDistanceMatrixStatus)  {
     let key = uniq_key(task_id.clone(), chunk_id.clone());
-    STATUS.lock().unwrap().insert(key, status);
+    let mut m = STATUS.lock().unwrap();
+    if status.attempts == 0 {
+        if let Some(previous) = m.get(&key) {
+            status.attempts = previous.attempts;
+            status.last_error = previous.last_error.clone();
+            status.next_retry_at = previous.next_retry_at;
+        }
+    }
+    m.insert(key, status);
     return
 }
 
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64
+}
+
+// mark_retry records a failed attempt of the chunk, its message becomes last_error and it can
+//  be submitted again after backoff
+pub fn mark_retry(task_id: String, chunk_id:String, backoff: Duration) -> MassiveDistanceMatrixStatus {
+    let key = uniq_key(task_id.clone(), chunk_id.clone());
+    let mut m = STATUS.lock().unwrap();
+    let status = m.entry(key).or_insert_with(|| MassiveDistanceMatrixStatus{
+        task_id,
+        chunk_id,
+        status: MassiveDistanceMatrixStatusEnum::Failed,
+        message: "".to_string(),
+        start_time: now_millis(),
+        output: None,
+        attempts: 0,
+        last_error: None,
+        next_retry_at: None,
+    });
+    status.status = MassiveDistanceMatrixStatusEnum::Failed;
+    status.attempts += 1;
+    if !status.message.is_empty() {
+        status.last_error = Some(status.message.clone());
+    }
+    status.next_retry_at = Some(now_millis() + backoff.as_millis() as i64);
+    status.clone()
+}
+
+// retry_due lists the failed chunks below max_attempts whose backoff is over
+pub fn retry_due(max_attempts: u32) -> Vec<MassiveDistanceMatrixStatus> {
+    let now = now_millis();
+    STATUS.lock().unwrap().values()
+        .filter(|s| matches!(s.status, MassiveDistanceMatrixStatusEnum::Failed))
+        .filter(|s| s.attempts < max_attempts && s.next_retry_at.unwrap_or(0) <= now)
+        .cloned()
+        .collect()
+}
+
+// dead_letters lists the failed chunks of task_id, all tasks when None, which reached
+//  max_attempts and must not be submitted again
+pub fn dead_letters(task_id: Option<&str>, max_attempts: u32) -> Vec<MassiveDistanceMatrixStatus> {
+    let mut r: Vec<MassiveDistanceMatrixStatus> = STATUS.lock().unwrap().values()
+        .filter(|s| task_id.map(|t| t == s.task_id).unwrap_or(true))
+        .filter(|s| matches!(s.status, MassiveDistanceMatrixStatusEnum::Failed))
+        .filter(|s| s.attempts >= max_attempts)
+        .cloned()
+        .collect();
+    r.sort_by(|a, b| (&a.task_id, &a.chunk_id).cmp(&(&b.task_id, &b.chunk_id)));
+    r
+}
+
 pub fn evict(){
     let now_time = SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -65,4 +133,45 @@ pub fn parse_uniq_key(key: String) -> (String, String){
     return (items[0].to_string(), items[1].to_string());
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
 
+    fn failed(task_id: &str, chunk_id: &str, message: &str) -> MassiveDistanceMatrixStatus {
+        let mut status = get_status(task_id.to_string(), chunk_id.to_string());
+        status.status = MassiveDistanceMatrixStatusEnum::Failed;
+        status.message = message.to_string();
+        status
+    }
+
+    #[test]
+    fn test_mark_retry() {
+        let task = "test_mark_retry";
+        set_status(task.to_string(), "0".to_string(), failed(task, "0", "engine timeout"));
+        let status = mark_retry(task.to_string(), "0".to_string(), Duration::from_secs(60));
+        assert_eq!(status.attempts, 1);
+        assert_eq!(status.last_error.as_deref(), Some("engine timeout"));
+        assert!(status.next_retry_at.unwrap() > now_millis());
+        // not due before the backoff
+        assert!(retry_due(3).iter().all(|s| s.task_id != task));
+
+        // re-submitting keeps the attempts
+        let mut running = get_status(task.to_string(), "0".to_string());
+        running.status = MassiveDistanceMatrixStatusEnum::Running;
+        running.attempts = 0;
+        set_status(task.to_string(), "0".to_string(), running);
+        set_status(task.to_string(), "0".to_string(), failed(task, "0", "out of memory"));
+        let status = mark_retry(task.to_string(), "0".to_string(), Duration::from_secs(0));
+        assert_eq!(status.attempts, 2);
+        assert_eq!(status.last_error.as_deref(), Some("out of memory"));
+        assert!(retry_due(3).iter().any(|s| s.task_id == task));
+
+        set_status(task.to_string(), "1".to_string(), failed(task, "1", "bad input"));
+        mark_retry(task.to_string(), "1".to_string(), Duration::from_secs(0));
+        let dead = dead_letters(Some(task), 2);
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].chunk_id, "0");
+        assert!(retry_due(2).iter().all(|s| s.task_id != task || s.chunk_id == "1"));
+        assert_eq!(dead_letters(Some(task), 1).len(), 2);
+    }
+}
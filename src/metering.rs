@@ -0,0 +1,234 @@
+// metering of the usage of api keys. usage is billed, so it must not be lost and it can not
+//  be tracked with a key_id label of the prometheus metrics (one series per key). a Meter
+//  buffers the usage records and flushes them in batches to a durable sink, a batch the sink
+//  failed to write is kept for the next flush. the metric only counts usage by api, mode and sku
+use crate::mdm_io::ObjectStore;
+use crate::statsd::{self, TypedTrackInput};
+use crate::{instance_id, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::SyncSender;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UsageRecord {
+    pub key_id: String,
+    pub api: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    // None when the key has no sku for api
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sku: Option<i64>,
+    pub count: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    // unix timestamp in milliseconds
+    pub timestamp: i64,
+}
+
+impl UsageRecord {
+    pub fn new(key_id: &str, api: &str, count: f64) -> UsageRecord {
+        UsageRecord {
+            key_id: key_id.to_string(),
+            api: api.to_string(),
+            mode: None,
+            sku: None,
+            count,
+            request_id: None,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as i64,
+        }
+    }
+}
+
+pub type MeteringFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+pub trait UsageSink: Sync {
+    // write stores records durably, an error means that none of them may have been stored
+    fn write<'a>(&'a self, records: &'a [UsageRecord]) -> MeteringFuture<'a, ()>;
+}
+
+static BATCH_SEQ: AtomicU64 = AtomicU64::new(0);
+
+// ObjectStoreUsageSink writes each batch to a store, e.g. gcs, as json lines at
+//  `<prefix>/<yyyy-mm-dd>/<timestamp>-<instance>-<seq>.jsonl`
+pub struct ObjectStoreUsageSink<S: ObjectStore> {
+    pub store: S,
+    pub prefix: String,
+    pub instance: String,
+}
+
+impl<S: ObjectStore> ObjectStoreUsageSink<S> {
+    pub fn new(store: S, prefix: &str) -> ObjectStoreUsageSink<S> {
+        ObjectStoreUsageSink {
+            store,
+            prefix: prefix.trim_end_matches('/').to_string(),
+            instance: instance_id().to_string(),
+        }
+    }
+
+    // with_instance replaces the generated instance id, e.g. by a stable replica name
+    pub fn with_instance(mut self, instance: &str) -> ObjectStoreUsageSink<S> {
+        self.instance = instance.to_string();
+        self
+    }
+
+    pub fn batch_path(&self, timestamp: i64) -> String {
+        format!(
+            "{}/{}/{}-{}-{}.jsonl",
+            self.prefix,
+            DateTime::<Utc>::from(UNIX_EPOCH + Duration::from_millis(timestamp.max(0) as u64))
+                .format("%Y-%m-%d"),
+            timestamp,
+            self.instance,
+            BATCH_SEQ.fetch_add(1, Ordering::SeqCst)
+        )
+    }
+}
+
+impl<S: ObjectStore> UsageSink for ObjectStoreUsageSink<S> {
+    fn write<'a>(&'a self, records: &'a [UsageRecord]) -> MeteringFuture<'a, ()> {
+        Box::pin(async move {
+            let timestamp = match records.first() {
+                Some(v) => v.timestamp,
+                None => return Ok(()),
+            };
+            let mut data = vec![];
+            for record in records.iter() {
+                data.extend(serde_json::to_vec(record)?);
+                data.push(b'\n');
+            }
+            self.store.put(&self.batch_path(timestamp), data).await
+        })
+    }
+}
+
+// Meter buffers the usage records of a service until flush, which services call periodically
+//  and before exiting
+pub struct Meter {
+    sink: Box<dyn UsageSink + Send>,
+    pending: Mutex<Vec<UsageRecord>>,
+    sender: Option<SyncSender<TypedTrackInput>>,
+}
+
+impl Meter {
+    pub fn new(sink: Box<dyn UsageSink + Send>) -> Meter {
+        Meter {
+            sink,
+            pending: Mutex::new(vec![]),
+            sender: None,
+        }
+    }
+
+    // with_sender also counts the usage with the statsd collector, without the key
+    pub fn with_sender(mut self, sender: SyncSender<TypedTrackInput>) -> Meter {
+        self.sender = Some(sender);
+        self
+    }
+
+    pub fn record(&self, record: UsageRecord) {
+        if let Some(sender) = self.sender.as_ref() {
+            statsd::record_metered_usage(
+                sender,
+                &record.api,
+                record.mode.as_deref().unwrap_or_default(),
+                record.sku,
+                record.count,
+            );
+        }
+        self.pending.lock().unwrap().push(record);
+    }
+
+    pub fn pending(&self) -> usize {
+        self.pending.lock().unwrap().len()
+    }
+
+    // flush writes the pending records and returns their number. when the sink fails, the
+    //  records are kept ahead of the ones recorded meanwhile
+    pub async fn flush(&self) -> Result<usize> {
+        let batch = std::mem::take(&mut *self.pending.lock().unwrap());
+        if batch.is_empty() {
+            return Ok(0);
+        }
+        if let Err(e) = self.sink.write(&batch).await {
+            let mut pending = self.pending.lock().unwrap();
+            let recorded = std::mem::replace(&mut *pending, batch);
+            pending.extend(recorded);
+            bail!("failed to write {} usage records: {}", pending.len(), e);
+        }
+        Ok(batch.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mdm_io::MemoryStore;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::mpsc::sync_channel;
+    use std::sync::Arc;
+
+    struct FlakySink {
+        inner: Arc<ObjectStoreUsageSink<MemoryStore>>,
+        down: Arc<AtomicBool>,
+    }
+
+    impl UsageSink for FlakySink {
+        fn write<'a>(&'a self, records: &'a [UsageRecord]) -> MeteringFuture<'a, ()> {
+            Box::pin(async move {
+                if self.down.load(Ordering::SeqCst) {
+                    bail!("store unavailable");
+                }
+                self.inner.write(records).await
+            })
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_meter() {
+        let sink = Arc::new(
+            ObjectStoreUsageSink::new(MemoryStore::new(), "gs://usage/").with_instance("pod-0"),
+        );
+        let down = Arc::new(AtomicBool::new(true));
+        let (tx, rx) = sync_channel(10);
+        let meter = Meter::new(Box::new(FlakySink {
+            inner: sink.clone(),
+            down: down.clone(),
+        }))
+        .with_sender(tx);
+        let record = |key_id: &str, count| UsageRecord {
+            timestamp: 1600000000000,
+            ..UsageRecord::new(key_id, "directions", count)
+        };
+
+        meter.record(record("k1", 1.0));
+        assert!(meter.flush().await.is_err());
+        meter.record(record("k2", 2.0));
+        assert_eq!(meter.pending(), 2);
+
+        if let TypedTrackInput::Counter(c) = rx.try_recv().unwrap() {
+            assert!(!c.labels.contains_key("key_id"));
+            assert_eq!(c.labels[statsd::LABELNAME_API], "directions");
+        }
+
+        down.store(false, Ordering::SeqCst);
+        let seq = BATCH_SEQ.load(Ordering::SeqCst);
+        assert_eq!(meter.flush().await.unwrap(), 2);
+        assert_eq!(meter.flush().await.unwrap(), 0);
+
+        let path = format!("gs://usage/2020-09-13/1600000000000-pod-0-{}.jsonl", seq);
+        let data = sink.store.get(&path).await.unwrap();
+        let records: Vec<UsageRecord> = String::from_utf8(data)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(records, vec![record("k1", 1.0), record("k2", 2.0)]);
+    }
+}
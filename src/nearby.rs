@@ -0,0 +1,178 @@
+// results of nearby searches. the engine returns them in its own order, possibly with the same
+//  result more than once, so they are deduplicated, filtered and ranked here before being cut
+//  to maxcount. candidates can be prefiltered on their straight line distance before the
+//  matrix call, since it is a lower bound of the traveling distance
+use crate::coord::Locatable;
+use crate::def::{GetNearbyInput, GetNearbyOutput, NearbyResult};
+use crate::util::straight_distance;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+pub const DEFAULT_SEARCH_RADIUS: i64 = 10000;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum NearbyRank {
+    // eta, then distance, then id
+    #[default]
+    Eta,
+    // distance, then eta, then id
+    Distance,
+}
+
+impl NearbyRank {
+    pub fn compare(&self, a: &NearbyResult, b: &NearbyResult) -> Ordering {
+        let primary = match self {
+            NearbyRank::Eta => a.eta.cmp(&b.eta).then(a.distance.cmp(&b.distance)),
+            NearbyRank::Distance => a.distance.cmp(&b.distance).then(a.eta.cmp(&b.eta)),
+        };
+        primary.then_with(|| a.id.cmp(&b.id))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct NearbyFilter {
+    // seconds
+    pub max_eta: Option<u64>,
+    // meters
+    pub max_distance: Option<u64>,
+}
+
+impl NearbyFilter {
+    pub fn accepts(&self, result: &NearbyResult) -> bool {
+        self.max_eta.map(|v| result.eta <= v).unwrap_or(true)
+            && self
+                .max_distance
+                .map(|v| result.distance <= v)
+                .unwrap_or(true)
+    }
+}
+
+pub fn rank(results: &mut [NearbyResult], by: NearbyRank) {
+    results.sort_by(|a, b| by.compare(a, b));
+}
+
+// dedup_by_id keeps the first result of each id
+pub fn dedup_by_id(results: &mut Vec<NearbyResult>) {
+    let mut seen = HashSet::new();
+    results.retain(|r| seen.insert(r.id.clone()));
+}
+
+// finalize ranks, deduplicates and filters the results of the output, keeping at most maxCount.
+//  ranking first makes the best of duplicated results the one kept
+pub fn finalize(output: &mut GetNearbyOutput, by: NearbyRank, filter: &NearbyFilter) {
+    rank(&mut output.results, by);
+    dedup_by_id(&mut output.results);
+    output.results.retain(|r| filter.accepts(r));
+    output.results.truncate(output.maxCount);
+}
+
+// filter_of is the filter implied by the input, results beyond searchradius are dropped
+pub fn filter_of(input: &GetNearbyInput) -> NearbyFilter {
+    NearbyFilter {
+        max_eta: None,
+        max_distance: Some(input.searchradius.unwrap_or(DEFAULT_SEARCH_RADIUS).max(0) as u64),
+    }
+}
+
+// prefilter returns the indexes of the candidates within max_distance meters of origin in a
+//  straight line, nearest first. limit caps the count, which is an approximation when ranking
+//  by eta since the nearest candidates are not always the fastest to reach
+pub fn prefilter<O: Locatable, T: Locatable>(
+    origin: &O,
+    candidates: &[T],
+    max_distance: f64,
+    limit: Option<usize>,
+) -> Vec<usize> {
+    let mut within: Vec<(usize, f64)> = candidates
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            (
+                i,
+                straight_distance(origin.lat(), origin.lng(), c.lat(), c.lng()),
+            )
+        })
+        .filter(|(_, d)| *d <= max_distance)
+        .collect();
+    within.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal));
+    if let Some(limit) = limit {
+        within.truncate(limit);
+    }
+    within.into_iter().map(|(i, _)| i).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coord::Coord;
+    use crate::def::Location;
+
+    fn result(id: &str, eta: u64, distance: u64) -> NearbyResult {
+        NearbyResult {
+            id: id.to_string(),
+            location: Location {
+                latitude: 1.3,
+                longitude: 103.8,
+            },
+            eta,
+            distance,
+        }
+    }
+
+    fn ids(results: &[NearbyResult]) -> Vec<&str> {
+        results.iter().map(|r| r.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_finalize() {
+        let mut output = GetNearbyOutput {
+            status: "Ok".to_string(),
+            msg: None,
+            currentLocation: Location {
+                latitude: 1.3,
+                longitude: 103.8,
+            },
+            searchRadius: 10000,
+            maxCount: 3,
+            serviceType: "4w".to_string(),
+            results: vec![
+                result("c", 60, 500),
+                result("a", 60, 400),
+                result("b", 30, 900),
+                result("a", 20, 300),
+                result("d", 10, 20000),
+                result("e", 100, 100),
+            ],
+        };
+        let filter = NearbyFilter {
+            max_eta: None,
+            max_distance: Some(10000),
+        };
+        finalize(&mut output, NearbyRank::Eta, &filter);
+        assert_eq!(ids(&output.results), vec!["a", "b", "c"]);
+        assert_eq!(output.results[0].eta, 20);
+
+        let mut results = vec![
+            result("b", 30, 900),
+            result("a", 60, 900),
+            result("e", 100, 100),
+        ];
+        rank(&mut results, NearbyRank::Distance);
+        assert_eq!(ids(&results), vec!["e", "b", "a"]);
+    }
+
+    #[test]
+    fn test_prefilter() {
+        let origin = Coord::new(1.3, 103.8);
+        let candidates = vec![
+            Coord::new(1.4, 103.8),
+            Coord::new(1.301, 103.8),
+            Coord::new(1.31, 103.8),
+        ];
+        assert_eq!(prefilter(&origin, &candidates, 5000.0, None), vec![1, 2]);
+        assert_eq!(
+            prefilter(&origin, &candidates, 50000.0, Some(2)),
+            vec![1, 2]
+        );
+    }
+}
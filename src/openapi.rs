@@ -0,0 +1,324 @@
+// OpenAPI definitions of the request and response types of def.rs, with the examples and the
+//  conventions of the doc strings applied, so that services no longer patch the spec by hand.
+//  paragraphs of a field description starting with `Regex:` become its pattern, and
+//  `Default: `value`` its default when the value matches the field type
+use crate::def::*;
+use paperclip::v2::schema::Apiv2Schema;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+const REGEX_PREFIX: &str = "Regex:";
+const DEFAULT_PREFIX: &str = "Default:";
+
+// ApiExample provides the example of a type shown in the spec
+pub trait ApiExample: Serialize + Sized {
+    fn example() -> Self;
+}
+
+// from_example builds an example from its JSON form, so that examples only list the fields
+//  worth showing
+fn from_example<T: DeserializeOwned>(value: Value) -> T {
+    serde_json::from_value(value).expect("invalid example")
+}
+
+fn remove_nulls(value: &mut Value) {
+    match value {
+        Value::Object(m) => {
+            m.retain(|_, v| !v.is_null());
+            m.values_mut().for_each(remove_nulls);
+        }
+        Value::Array(v) => v.iter_mut().for_each(remove_nulls),
+        _ => {}
+    }
+}
+
+// example_value is the example of T as it is sent on the wire, without unset fields
+pub fn example_value<T: ApiExample>() -> Value {
+    let mut value = serde_json::to_value(T::example()).unwrap_or(Value::Null);
+    remove_nulls(&mut value);
+    value
+}
+
+fn matches_type(value: &Value, data_type: &str) -> bool {
+    match data_type {
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        _ => false,
+    }
+}
+
+// parse_default reads a default made of a single quoted value, such as `false` or `"\"\""`
+fn parse_default(paragraph: &str) -> Option<Value> {
+    let rest = paragraph.strip_prefix(DEFAULT_PREFIX)?.trim();
+    let quoted = rest.strip_prefix('`')?.strip_suffix('`')?;
+    if quoted.contains('`') {
+        return None;
+    }
+    Some(serde_json::from_str(quoted).unwrap_or_else(|_| json!(quoted)))
+}
+
+fn enrich_property(schema: &mut Map<String, Value>) {
+    let description = match schema.get("description").and_then(|v| v.as_str()) {
+        Some(v) => v.to_string(),
+        None => return,
+    };
+    for paragraph in description.split("\n\n").map(|p| p.trim()) {
+        if let Some(pattern) = paragraph.strip_prefix(REGEX_PREFIX) {
+            schema
+                .entry("pattern")
+                .or_insert_with(|| json!(pattern.trim()));
+        }
+        if let Some(default) = parse_default(paragraph) {
+            let data_type = schema.get("type").and_then(|v| v.as_str()).unwrap_or("");
+            if matches_type(&default, data_type) {
+                schema.entry("default").or_insert(default);
+            }
+        }
+    }
+}
+
+// enrich applies the doc string conventions to every property of the schema, recursively
+pub fn enrich(schema: &mut Value) {
+    if let Value::Object(m) = schema {
+        enrich_property(m);
+        if let Some(Value::Object(properties)) = m.get_mut("properties") {
+            properties.values_mut().for_each(enrich);
+        }
+        if let Some(items) = m.get_mut("items") {
+            enrich(items);
+        }
+    }
+}
+
+// definition returns the enriched schema of T with its example, nested types are inlined
+pub fn definition<T: Apiv2Schema + ApiExample>() -> Value {
+    let mut raw = T::raw_schema();
+    raw.remove_refs();
+    let mut schema = serde_json::to_value(raw).unwrap_or(Value::Null);
+    enrich(&mut schema);
+    if let Value::Object(m) = &mut schema {
+        if !T::description().is_empty() {
+            m.insert("description".to_string(), json!(T::description()));
+        }
+        m.insert("example".to_string(), example_value::<T>());
+    }
+    schema
+}
+
+macro_rules! definitions {
+    ($($t:ty),* $(,)?) => {{
+        let mut definitions = Map::new();
+        $(definitions.insert(stringify!($t).to_string(), definition::<$t>());)*
+        definitions
+    }};
+}
+
+// spec_fragment returns the definitions of the types of this crate, to be merged into the
+//  spec served by the services
+pub fn spec_fragment() -> Value {
+    json!({
+        "definitions": definitions![
+            DirectionsInput,
+            MatrixInput,
+            MatrixOutput,
+            MassiveDistanceMatrixInput,
+            MassiveDistanceMatrixOutput,
+            MassiveDistanceMatrixStatusInput,
+            MassiveDistanceMatrixStatusOutput,
+            SnapInput,
+            IsochroneInput,
+            GetNearbyInput,
+        ],
+    })
+}
+
+impl ApiExample for DirectionsInput {
+    fn example() -> Self {
+        from_example(json!({
+            "origin": "1.310185,103.847313",
+            "destination": "1.289691,103.851311",
+            "waypoints": "1.300436,103.854991",
+            "mode": "4w",
+            "departure_time": 1609459200,
+            "steps": true,
+            "key": "<your-api-key>",
+        }))
+    }
+}
+
+impl ApiExample for MatrixInput {
+    fn example() -> Self {
+        from_example(json!({
+            "origins": "1.310185,103.847313|1.300436,103.854991",
+            "destinations": "1.289691,103.851311",
+            "mode": "4w",
+            "departure_time": 1609459200,
+            "key": "<your-api-key>",
+        }))
+    }
+}
+
+fn example_element(duration: i64, distance: i64) -> Element {
+    Element {
+        duration: IntValue { value: duration },
+        distance: IntValue { value: distance },
+        raw_duration: None,
+        predicted_duration: None,
+        co2_emissions: None,
+    }
+}
+
+impl ApiExample for MatrixOutput {
+    fn example() -> Self {
+        MatrixOutput {
+            status: STATUS_OK.to_string(),
+            warning: None,
+            server_timings: None,
+            rows: vec![
+                Row {
+                    elements: vec![example_element(521, 3204)],
+                },
+                Row {
+                    elements: vec![example_element(407, 2116)],
+                },
+            ],
+        }
+    }
+}
+
+impl ApiExample for MassiveDistanceMatrixInput {
+    fn example() -> Self {
+        MassiveDistanceMatrixInput {
+            matrix_input: MatrixInput::example(),
+            task_id: "2f6c7e1a".to_string(),
+            chunk_id: "0".to_string(),
+            gcs_path: None,
+        }
+    }
+}
+
+impl ApiExample for MassiveDistanceMatrixOutput {
+    fn example() -> Self {
+        MassiveDistanceMatrixOutput {
+            status: STATUS_OK.to_string(),
+            warning: None,
+        }
+    }
+}
+
+impl ApiExample for MassiveDistanceMatrixStatusInput {
+    fn example() -> Self {
+        MassiveDistanceMatrixStatusInput {
+            task_id: "2f6c7e1a".to_string(),
+            chunk_id: "0".to_string(),
+            key: Some("<your-api-key>".to_string()),
+        }
+    }
+}
+
+impl ApiExample for MassiveDistanceMatrixStatusOutput {
+    fn example() -> Self {
+        MassiveDistanceMatrixStatusOutput {
+            status: MassiveDistanceMatrixStatus {
+                task_id: "2f6c7e1a".to_string(),
+                chunk_id: "0".to_string(),
+                status: MassiveDistanceMatrixStatusEnum::Finish,
+                message: "".to_string(),
+                output: Some(MatrixOutput::example()),
+                start_time: 0,
+                attempts: 0,
+                last_error: None,
+                next_retry_at: None,
+            },
+            result_url: None,
+        }
+    }
+}
+
+impl ApiExample for SnapInput {
+    fn example() -> Self {
+        from_example(json!({
+            "path": "1.310185,103.847313|1.310441,103.847961|1.310738,103.848503",
+            "timestamps": "1609459200|1609459205|1609459210",
+            "radiuses": "10|10|10",
+            "mode": "4w",
+            "key": "<your-api-key>",
+        }))
+    }
+}
+
+impl ApiExample for IsochroneInput {
+    fn example() -> Self {
+        from_example(json!({
+            "center": "1.310185,103.847313",
+            "times": "10|20",
+            "mode": "4w",
+            "key": "<your-api-key>",
+        }))
+    }
+}
+
+impl ApiExample for GetNearbyInput {
+    fn example() -> Self {
+        from_example(json!({
+            "currentlocation": "1.310185,103.847313",
+            "servicetype": "4w",
+            "searchradius": 5000,
+            "maxcount": 5,
+            "key": "<your-api-key>",
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spec_fragment() {
+        let fragment = spec_fragment();
+        let definitions = fragment["definitions"].as_object().unwrap();
+        for (name, schema) in definitions.iter() {
+            assert!(schema["example"].is_object(), "{} has no example", name);
+        }
+
+        let directions = &definitions["DirectionsInput"];
+        assert_eq!(directions["example"]["mode"], "4w");
+        assert!(directions["example"].get("session").is_none());
+        let origin = &directions["properties"]["origin"];
+        assert_eq!(origin["pattern"], "^[\\d\\.\\-]+,[\\d\\.\\-]+$");
+        let properties = &directions["properties"];
+        assert_eq!(properties["steps"]["default"], false);
+        assert_eq!(properties["departure_time"]["default"], 0);
+        assert_eq!(properties["mode"]["default"], "");
+        assert_eq!(properties["geometry"]["default"], "polyline");
+        // defaults that are not a single value are left in the description only
+        assert!(properties["altcount"].get("default").is_none());
+
+        // nested types are enriched too
+        let matrix = &definitions["MassiveDistanceMatrixInput"];
+        assert_eq!(
+            matrix["properties"]["matrix_input"]["properties"]["origins"]["pattern"],
+            "^[\\d\\.\\-]+,[\\d\\.\\-]+(\\|[\\d\\.\\-]+,[\\d\\.\\-]+)*$"
+        );
+        // a default of the wrong type is ignored
+        let snap = &definitions["SnapInput"];
+        assert!(snap["properties"]["waypoints"].get("default").is_none());
+    }
+
+    #[test]
+    fn test_examples_round_trip() {
+        let example = example_value::<MassiveDistanceMatrixStatusOutput>();
+        assert_eq!(example["status"]["status"], "Finish");
+        assert_eq!(
+            example["status"]["output"]["rows"][0]["elements"][0]["duration"]["value"],
+            521
+        );
+        let input: DirectionsInput =
+            serde_json::from_value(example_value::<DirectionsInput>()).unwrap();
+        assert_eq!(input.waypoints.unwrap(), "1.300436,103.854991");
+    }
+}
@@ -1,18 +1,92 @@
-use geo::{LineString, Polygon};
+use crate::polygon_check::{normalize_polygons, LoadReport, NormalizeOptions};
+use geo::algorithm::contains::Contains;
+use geo::{LineString, Point, Polygon};
+use serde_json::Value;
+use std::error::Error;
+use std::fmt;
 use std::fs;
 
-pub fn load(path: &str) -> Result<Vec<Polygon<f64>>, std::io::Error> {
+#[derive(Debug)]
+pub enum PolyError {
+    IoError(std::io::Error),
+    // `line` starts from 1
+    ParseError { line: usize, token: String },
+    HttpError(reqwest::Error),
+    JsonError(serde_json::Error),
+    // the json is valid but not the GeoJSON geometry at path
+    GeoJsonError { path: String },
+}
+
+impl fmt::Display for PolyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PolyError::IoError(e) => write!(f, "failed to read poly file: {}", e),
+            PolyError::ParseError { line, token } => {
+                write!(f, "invalid number {:?} at line {}", token, line)
+            }
+            PolyError::HttpError(e) => write!(f, "failed to fetch polygons: {}", e),
+            PolyError::JsonError(e) => write!(f, "failed to parse geojson: {}", e),
+            PolyError::GeoJsonError { path } => write!(f, "invalid geojson geometry at {}", path),
+        }
+    }
+}
+
+impl Error for PolyError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PolyError::IoError(e) => Some(e),
+            PolyError::HttpError(e) => Some(e),
+            PolyError::JsonError(e) => Some(e),
+            PolyError::ParseError { .. } | PolyError::GeoJsonError { .. } => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for PolyError {
+    fn from(e: std::io::Error) -> Self {
+        PolyError::IoError(e)
+    }
+}
+
+impl From<reqwest::Error> for PolyError {
+    fn from(e: reqwest::Error) -> Self {
+        PolyError::HttpError(e)
+    }
+}
+
+impl From<serde_json::Error> for PolyError {
+    fn from(e: serde_json::Error) -> Self {
+        PolyError::JsonError(e)
+    }
+}
+
+pub fn load(path: &str) -> Result<Vec<Polygon<f64>>, PolyError> {
+    let (polygons, report) = load_with_report(path, &NormalizeOptions::default())?;
+    for issue in report.issues.iter() {
+        warn!("poly file {} has issue {:?}", path, issue);
+    }
+    Ok(polygons)
+}
+
+// load_with_report loads and normalizes the polygons of a poly file, see normalize_polygons
+pub fn load_with_report(
+    path: &str,
+    opts: &NormalizeOptions,
+) -> Result<(Vec<Polygon<f64>>, LoadReport), PolyError> {
     debug!("loading poly from path: {}", path);
-    let contents = fs::read_to_string(path).expect("Something went wrong reading the file");
-    _load(&contents)
+    let contents = fs::read_to_string(path)?;
+    Ok(normalize_polygons(_load(&contents)?, opts))
 }
 
-fn _load(contents: &str) -> Result<Vec<Polygon<f64>>, std::io::Error> {
+// _load parses the sections of a poly file. sections named with a `!` prefix are excluded
+//  from the area, they become interior rings of the polygons they are in
+fn _load(contents: &str) -> Result<Vec<Polygon<f64>>, PolyError> {
     let lines = contents.lines();
     let mut mode = 0;
     let mut coords: Vec<(f64, f64)> = Vec::new();
-    let mut polygons: Vec<Polygon<f64>> = Vec::new();
-    for line in lines {
+    let mut hole = false;
+    let mut rings: Vec<(LineString<f64>, bool)> = Vec::new();
+    for (line_idx, line) in lines.enumerate() {
         let trimed = line.trim_end();
         let replaced = trimed.replace("\t", " ");
         let swt = replaced.starts_with(" ");
@@ -21,6 +95,9 @@ fn _load(contents: &str) -> Result<Vec<Polygon<f64>>, std::io::Error> {
                 if swt {
                     //begin area
                     mode = 1;
+                } else if !trimed.is_empty() && trimed != "END" {
+                    // file or section name
+                    hole = trimed.starts_with('!');
                 }
             }
             1 => {
@@ -28,32 +105,258 @@ fn _load(contents: &str) -> Result<Vec<Polygon<f64>>, std::io::Error> {
                     let items: Vec<&str> = replaced.trim().split_whitespace().collect();
                     if items.len() == 2 {
                         let coord = (
-                            items[0].parse::<f64>().unwrap(),
-                            items[1].parse::<f64>().unwrap(),
+                            parse_number(items[0], line_idx + 1)?,
+                            parse_number(items[1], line_idx + 1)?,
                         );
                         coords.push(coord);
                     }
                 }
                 if trimed == "END" {
                     mode = 0;
-                    polygons.push(Polygon::<f64>::new(LineString::from(coords), vec![]));
+                    rings.push((LineString::from(coords), hole));
                     coords = vec![];
+                    hole = false;
                 }
             }
             _ => {}
         }
     }
+    Ok(assemble_rings(rings))
+}
+
+// assemble_rings adds each excluded ring to the polygons containing any of its points, excluded
+//  rings outside of all polygons exclude nothing and are dropped
+fn assemble_rings(rings: Vec<(LineString<f64>, bool)>) -> Vec<Polygon<f64>> {
+    let (holes, exteriors): (Vec<_>, Vec<_>) = rings.into_iter().partition(|(_, hole)| *hole);
+    exteriors
+        .into_iter()
+        .map(|(exterior, _)| {
+            let outer = Polygon::new(exterior, vec![]);
+            let interiors = holes
+                .iter()
+                .filter(|(h, _)| h.0.iter().any(|c| outer.contains(&Point::from(*c))))
+                .map(|(h, _)| h.clone())
+                .collect();
+            let (exterior, _) = outer.into_inner();
+            Polygon::new(exterior, interiors)
+        })
+        .collect()
+}
+
+// load_geojson loads and normalizes the Polygon and MultiPolygon geometries of a GeoJSON file,
+//  bare or in features. other geometries are skipped
+pub fn load_geojson(path: &str) -> Result<Vec<Polygon<f64>>, PolyError> {
+    debug!("loading geojson from path: {}", path);
+    let contents = fs::read_to_string(path)?;
+    Ok(normalize_geojson(path, parse_geojson(&contents)?))
+}
+
+// load_geojson_url is load_geojson for a file served over http
+pub async fn load_geojson_url(url: &str) -> Result<Vec<Polygon<f64>>, PolyError> {
+    debug!("loading geojson from url: {}", url);
+    let contents = reqwest::get(url).await?.error_for_status()?.text().await?;
+    Ok(normalize_geojson(url, parse_geojson(&contents)?))
+}
+
+fn normalize_geojson(path: &str, polygons: Vec<Polygon<f64>>) -> Vec<Polygon<f64>> {
+    let (polygons, report) = normalize_polygons(polygons, &NormalizeOptions::default());
+    for issue in report.issues.iter() {
+        warn!("geojson file {} has issue {:?}", path, issue);
+    }
+    polygons
+}
+
+// parse_geojson returns the polygons of a GeoJSON document as they are
+pub fn parse_geojson(contents: &str) -> Result<Vec<Polygon<f64>>, PolyError> {
+    let v: Value = serde_json::from_str(contents)?;
+    let mut polygons = vec![];
+    geojson_polygons(&v, "$", &mut polygons)?;
     Ok(polygons)
 }
 
+fn geojson_polygons(
+    v: &Value,
+    path: &str,
+    polygons: &mut Vec<Polygon<f64>>,
+) -> Result<(), PolyError> {
+    match v["type"].as_str() {
+        Some("FeatureCollection") => {
+            for (i, feature) in v["features"].as_array().into_iter().flatten().enumerate() {
+                geojson_polygons(feature, &format!("{}.features[{}]", path, i), polygons)?;
+            }
+        }
+        Some("Feature") => {
+            geojson_polygons(&v["geometry"], &format!("{}.geometry", path), polygons)?
+        }
+        Some("GeometryCollection") => {
+            for (i, geometry) in v["geometries"].as_array().into_iter().flatten().enumerate() {
+                geojson_polygons(geometry, &format!("{}.geometries[{}]", path, i), polygons)?;
+            }
+        }
+        Some("Polygon") => polygons.push(geojson_polygon(&v["coordinates"], path)?),
+        Some("MultiPolygon") => {
+            for (i, p) in v["coordinates"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .enumerate()
+            {
+                polygons.push(geojson_polygon(p, &format!("{}.coordinates[{}]", path, i))?);
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+// geojson_polygon reads the rings of a polygon, the first one is the exterior and the others
+//  are holes
+fn geojson_polygon(v: &Value, path: &str) -> Result<Polygon<f64>, PolyError> {
+    let invalid = || PolyError::GeoJsonError {
+        path: path.to_string(),
+    };
+    let mut rings = vec![];
+    for ring in v.as_array().ok_or_else(invalid)? {
+        let mut coords = vec![];
+        for p in ring.as_array().ok_or_else(invalid)? {
+            match (
+                p.get(0).and_then(|v| v.as_f64()),
+                p.get(1).and_then(|v| v.as_f64()),
+            ) {
+                (Some(x), Some(y)) => coords.push((x, y)),
+                _ => return Err(invalid()),
+            }
+        }
+        rings.push(LineString::from(coords));
+    }
+    if rings.is_empty() {
+        return Err(invalid());
+    }
+    let exterior = rings.remove(0);
+    Ok(Polygon::new(exterior, rings))
+}
+
+fn parse_number(token: &str, line: usize) -> Result<f64, PolyError> {
+    match token.parse::<f64>() {
+        Ok(v) if v.is_finite() => Ok(v),
+        _ => Err(PolyError::ParseError {
+            line,
+            token: token.to_string(),
+        }),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::time::Instant;
 
     use crate::coord::{Coord, Locatable};
+    use crate::prepared_polygon::prepare_polygons;
+    use crate::testkit::{poly_string, random_coords, square_poly_string, square_polygon};
+    use proptest::prelude::*;
 
     use super::*;
 
+    #[test]
+    fn test_load_square() {
+        let center = Coord::new(1.3, 103.8);
+        let polygons = _load(&square_poly_string("singapore", &center, 0.5)).unwrap();
+        assert_eq!(polygons.len(), 1);
+        for c in random_coords(&center, 1.0, 200, 5) {
+            let inside =
+                (c.lat() - center.lat()).abs() < 0.5 && (c.lng() - center.lng()).abs() < 0.5;
+            assert_eq!(c.is_in_polygons(&prepare_polygons(polygons.clone())), inside);
+        }
+
+        match _load("x\n1\n  1.0  2.0\n  1.0  abc\nEND\nEND") {
+            Err(PolyError::ParseError { line, token }) => {
+                assert_eq!(line, 4);
+                assert_eq!(token, "abc");
+            }
+            r => panic!("unexpected result {:?}", r),
+        }
+        match load("/nonexistent/border.poly") {
+            Err(PolyError::IoError(e)) => assert_eq!(e.kind(), std::io::ErrorKind::NotFound),
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_load_holes() {
+        let center = Coord::new(1.3, 103.8);
+        let contents = poly_string(
+            "singapore",
+            &[
+                ("1", square_polygon(&center, 1.0).exterior()),
+                ("!2", square_polygon(&center, 0.3).exterior()),
+                // outside of the area, excludes nothing
+                (
+                    "!3",
+                    square_polygon(&Coord::new(10.0, 110.0), 0.3).exterior(),
+                ),
+            ],
+        );
+        let polygons = _load(&contents).unwrap();
+        assert_eq!(polygons.len(), 1);
+        assert_eq!(polygons[0].interiors().len(), 1);
+        let prepared = prepare_polygons(polygons);
+        assert!(!center.is_in_polygons(&prepared));
+        assert!(Coord::new(1.3, 104.5).is_in_polygons(&prepared));
+        assert!(!Coord::new(1.3, 105.0).is_in_polygons(&prepared));
+    }
+
+    #[test]
+    fn test_parse_geojson() {
+        let center = Coord::new(1.3, 103.8);
+        let ring = |c: &Coord, size: f64| {
+            serde_json::to_value(
+                square_polygon(c, size)
+                    .exterior()
+                    .points_iter()
+                    .map(|p| vec![p.x(), p.y()])
+                    .collect::<Vec<Vec<f64>>>(),
+            )
+            .unwrap()
+        };
+        let collection = serde_json::json!({
+            "type": "FeatureCollection",
+            "features": [
+                {"type": "Feature", "geometry": {"type": "Polygon", "coordinates": [ring(&center, 1.0), ring(&center, 0.3)]}},
+                {"type": "Feature", "geometry": {"type": "MultiPolygon", "coordinates": [[ring(&Coord::new(10.0, 110.0), 0.3)], [ring(&Coord::new(20.0, 120.0), 0.3)]]}},
+                {"type": "Feature", "geometry": {"type": "Point", "coordinates": [103.8, 1.3]}},
+            ],
+        });
+        let polygons = parse_geojson(&collection.to_string()).unwrap();
+        assert_eq!(polygons.len(), 3);
+        assert_eq!(polygons[0].interiors().len(), 1);
+        let prepared = prepare_polygons(polygons);
+        assert!(!center.is_in_polygons(&prepared));
+        assert!(Coord::new(1.3, 104.5).is_in_polygons(&prepared));
+        assert!(Coord::new(20.0, 120.0).is_in_polygons(&prepared));
+
+        let dir = std::env::temp_dir().join(format!("poly-geojson-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("singapore.geojson");
+        fs::write(&path, collection.to_string()).unwrap();
+        assert_eq!(load_geojson(path.to_str().unwrap()).unwrap().len(), 3);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let invalid = r#"{"type": "MultiPolygon", "coordinates": [[[[103.8, "x"]]]]}"#;
+        match parse_geojson(invalid) {
+            Err(PolyError::GeoJsonError { path }) => assert_eq!(path, "$.coordinates[0]"),
+            r => panic!("unexpected result {:?}", r),
+        }
+        assert!(matches!(parse_geojson("{"), Err(PolyError::JsonError(_))));
+    }
+
+    proptest! {
+        #[test]
+        fn test_load_fuzz(input in "[1.END \t\nx-]{0,60}") {
+            // must never panic
+            let _ = _load(input.as_str());
+        }
+    }
+
     #[test]
     fn test_load() {
         let usa_content = "usa-0 \n
@@ -533,7 +836,7 @@ END";
         let now = Instant::now();
         let coord = Coord::coord("24.2050449,77.1064213").unwrap();
         //let coord = Coord::coord("41.577330,-105.691938").unwrap();
-        let ok = coord.is_in_polygons(&polygons);
+        let ok = coord.is_in_polygons(&prepare_polygons(polygons));
         println!("elapsed: {} us", now.elapsed().as_micros());
         assert!(ok)
     }
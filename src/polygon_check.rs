@@ -0,0 +1,391 @@
+use geo::{Coordinate, LineString, Polygon};
+use std::collections::{HashMap, HashSet};
+
+// max number of splits applied to a single ring when repairing self-intersections
+const MAX_REPAIR_SPLITS: usize = 100;
+
+#[derive(Clone, Debug, Default)]
+pub struct NormalizeOptions {
+    // split self-intersecting exterior rings into simple rings
+    pub repair: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RingKind {
+    Exterior,
+    Interior(usize),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PolygonIssueKind {
+    // ring orientation was reversed, exteriors are counter-clockwise and interiors clockwise
+    Reoriented,
+    // ring has less than 3 distinct points and was dropped
+    Degenerate,
+    // segments `a` and `b` of the ring intersect
+    SelfIntersection { a: usize, b: usize },
+    // self-intersecting ring was split into `parts` rings
+    Repaired { parts: usize },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PolygonIssue {
+    // index of the polygon in the loaded file/config
+    pub polygon: usize,
+    pub ring: RingKind,
+    pub kind: PolygonIssueKind,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct LoadReport {
+    pub issues: Vec<PolygonIssue>,
+}
+
+impl LoadReport {
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    // has_unrepaired_intersections tells if `contains` may be unreliable for these polygons
+    pub fn has_unrepaired_intersections(&self) -> bool {
+        let repaired: HashSet<(usize, &RingKind)> = self
+            .issues
+            .iter()
+            .filter(|i| matches!(i.kind, PolygonIssueKind::Repaired { .. }))
+            .map(|i| (i.polygon, &i.ring))
+            .collect();
+        self.issues.iter().any(|i| {
+            matches!(i.kind, PolygonIssueKind::SelfIntersection { .. })
+                && !repaired.contains(&(i.polygon, &i.ring))
+        })
+    }
+}
+
+type Point = (f64, f64);
+
+// open ring without consecutive duplicates, the closing point is removed
+fn open_ring(ring: &LineString<f64>) -> Vec<Point> {
+    let mut r: Vec<Point> = vec![];
+    for c in ring.0.iter() {
+        if r.last().map(|p| p.0 == c.x && p.1 == c.y).unwrap_or(false) {
+            continue;
+        }
+        r.push((c.x, c.y));
+    }
+    if r.len() > 1 && r.first() == r.last() {
+        r.pop();
+    }
+    r
+}
+
+fn close_ring(points: &[Point]) -> LineString<f64> {
+    let mut coords: Vec<Coordinate<f64>> = points
+        .iter()
+        .map(|p| Coordinate { x: p.0, y: p.1 })
+        .collect();
+    if let Some(first) = coords.first().cloned() {
+        coords.push(first);
+    }
+    LineString(coords)
+}
+
+// signed area of an open ring, positive for counter-clockwise
+fn signed_area(points: &[Point]) -> f64 {
+    let n = points.len();
+    (0..n)
+        .map(|i| {
+            let (a, b) = (points[i], points[(i + 1) % n]);
+            a.0 * b.1 - b.0 * a.1
+        })
+        .sum::<f64>()
+        / 2.0
+}
+
+fn orientation(a: Point, b: Point, c: Point) -> f64 {
+    (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)
+}
+
+fn on_segment(a: Point, b: Point, p: Point) -> bool {
+    p.0 >= a.0.min(b.0) && p.0 <= a.0.max(b.0) && p.1 >= a.1.min(b.1) && p.1 <= a.1.max(b.1)
+}
+
+fn segments_intersect(a1: Point, a2: Point, b1: Point, b2: Point) -> bool {
+    let d1 = orientation(b1, b2, a1);
+    let d2 = orientation(b1, b2, a2);
+    let d3 = orientation(a1, a2, b1);
+    let d4 = orientation(a1, a2, b2);
+    if ((d1 > 0.0 && d2 < 0.0) || (d1 < 0.0 && d2 > 0.0))
+        && ((d3 > 0.0 && d4 < 0.0) || (d3 < 0.0 && d4 > 0.0))
+    {
+        return true;
+    }
+    (d1 == 0.0 && on_segment(b1, b2, a1))
+        || (d2 == 0.0 && on_segment(b1, b2, a2))
+        || (d3 == 0.0 && on_segment(a1, a2, b1))
+        || (d4 == 0.0 && on_segment(a1, a2, b2))
+}
+
+fn intersection_point(a1: Point, a2: Point, b1: Point, b2: Point) -> Point {
+    let denom = (a2.0 - a1.0) * (b2.1 - b1.1) - (a2.1 - a1.1) * (b2.0 - b1.0);
+    if denom == 0.0 {
+        // collinear overlap, any shared point works
+        return if on_segment(a1, a2, b1) { b1 } else { b2 };
+    }
+    let t = ((b1.0 - a1.0) * (b2.1 - b1.1) - (b1.1 - a1.1) * (b2.0 - b1.0)) / denom;
+    (a1.0 + t * (a2.0 - a1.0), a1.1 + t * (a2.1 - a1.1))
+}
+
+// find_self_intersections returns pairs of intersecting non adjacent segments,
+//  segment i goes from points[i] to points[i + 1]. segments are bucketed in a grid so
+//  that dense rings don't need a full pairwise check
+pub(crate) fn find_self_intersections(points: &[Point]) -> Vec<(usize, usize)> {
+    let n = points.len();
+    if n < 4 {
+        return vec![];
+    }
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (f64::MAX, f64::MAX, f64::MIN, f64::MIN);
+    for p in points {
+        min_x = min_x.min(p.0);
+        min_y = min_y.min(p.1);
+        max_x = max_x.max(p.0);
+        max_y = max_y.max(p.1);
+    }
+    let cells_per_side = ((n as f64).sqrt().ceil() as usize).max(1);
+    let cell_w = ((max_x - min_x) / cells_per_side as f64).max(f64::EPSILON);
+    let cell_h = ((max_y - min_y) / cells_per_side as f64).max(f64::EPSILON);
+    let cell = |v: f64, min: f64, size: f64| (((v - min) / size) as usize).min(cells_per_side - 1);
+
+    let mut grid: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+    for i in 0..n {
+        let (a, b) = (points[i], points[(i + 1) % n]);
+        let (x0, x1) = (
+            cell(a.0.min(b.0), min_x, cell_w),
+            cell(a.0.max(b.0), min_x, cell_w),
+        );
+        let (y0, y1) = (
+            cell(a.1.min(b.1), min_y, cell_h),
+            cell(a.1.max(b.1), min_y, cell_h),
+        );
+        for x in x0..=x1 {
+            for y in y0..=y1 {
+                grid.entry((x, y)).or_default().push(i);
+            }
+        }
+    }
+
+    let mut found = HashSet::new();
+    for segments in grid.values() {
+        for (idx, &i) in segments.iter().enumerate() {
+            for &j in segments[idx + 1..].iter() {
+                let (i, j) = (i.min(j), i.max(j));
+                // adjacent segments share an endpoint
+                if j == i + 1 || (i == 0 && j == n - 1) || found.contains(&(i, j)) {
+                    continue;
+                }
+                if segments_intersect(points[i], points[i + 1], points[j], points[(j + 1) % n]) {
+                    found.insert((i, j));
+                }
+            }
+        }
+    }
+    let mut r: Vec<(usize, usize)> = found.into_iter().collect();
+    r.sort_unstable();
+    r
+}
+
+// split_ring splits a ring at the intersection of segments i and j (i < j) into two loops
+fn split_ring(points: &[Point], i: usize, j: usize) -> (Vec<Point>, Vec<Point>) {
+    let n = points.len();
+    let p = intersection_point(points[i], points[i + 1], points[j], points[(j + 1) % n]);
+    let mut first: Vec<Point> = points[..=i].to_vec();
+    first.push(p);
+    first.extend_from_slice(&points[j + 1..]);
+    let mut second = vec![p];
+    second.extend_from_slice(&points[i + 1..=j]);
+    (dedup_ring(first), dedup_ring(second))
+}
+
+fn dedup_ring(points: Vec<Point>) -> Vec<Point> {
+    let mut r: Vec<Point> = vec![];
+    for p in points {
+        if r.last() != Some(&p) {
+            r.push(p);
+        }
+    }
+    if r.len() > 1 && r.first() == r.last() {
+        r.pop();
+    }
+    r
+}
+
+// repair_ring splits a self-intersecting ring until every part is simple
+fn repair_ring(points: Vec<Point>) -> Vec<Vec<Point>> {
+    let mut done = vec![];
+    let mut todo = vec![points];
+    let mut splits = 0;
+    while let Some(ring) = todo.pop() {
+        if ring.len() < 3 {
+            continue;
+        }
+        match find_self_intersections(&ring).first() {
+            Some(&(i, j)) if splits < MAX_REPAIR_SPLITS => {
+                splits += 1;
+                let (a, b) = split_ring(&ring, i, j);
+                todo.push(a);
+                todo.push(b);
+            }
+            _ => done.push(ring),
+        }
+    }
+    done.into_iter()
+        .filter(|r| r.len() >= 3 && signed_area(r) != 0.0)
+        .collect()
+}
+
+fn oriented(mut points: Vec<Point>, ccw: bool) -> (Vec<Point>, bool) {
+    let area = signed_area(&points);
+    if (ccw && area < 0.0) || (!ccw && area > 0.0) {
+        points.reverse();
+        return (points, true);
+    }
+    (points, false)
+}
+
+// normalize_polygons orients rings (exterior counter-clockwise, interiors clockwise),
+//  drops degenerate rings and reports or repairs self-intersections
+pub fn normalize_polygons(
+    polygons: Vec<Polygon<f64>>,
+    opts: &NormalizeOptions,
+) -> (Vec<Polygon<f64>>, LoadReport) {
+    let mut report = LoadReport::default();
+    let mut r = vec![];
+    for (idx, polygon) in polygons.into_iter().enumerate() {
+        let mut issue = |ring: RingKind, kind: PolygonIssueKind| {
+            report.issues.push(PolygonIssue {
+                polygon: idx,
+                ring,
+                kind,
+            })
+        };
+        let (exterior, interiors) = polygon.into_inner();
+
+        let mut holes = vec![];
+        for (hole_idx, interior) in interiors.iter().enumerate() {
+            let points = open_ring(interior);
+            if points.len() < 3 {
+                issue(RingKind::Interior(hole_idx), PolygonIssueKind::Degenerate);
+                continue;
+            }
+            for (a, b) in find_self_intersections(&points) {
+                issue(
+                    RingKind::Interior(hole_idx),
+                    PolygonIssueKind::SelfIntersection { a, b },
+                );
+            }
+            let (points, reoriented) = oriented(points, false);
+            if reoriented {
+                issue(RingKind::Interior(hole_idx), PolygonIssueKind::Reoriented);
+            }
+            holes.push(close_ring(&points));
+        }
+
+        let points = open_ring(&exterior);
+        if points.len() < 3 {
+            issue(RingKind::Exterior, PolygonIssueKind::Degenerate);
+            continue;
+        }
+        let intersections = find_self_intersections(&points);
+        for (a, b) in intersections.iter() {
+            issue(
+                RingKind::Exterior,
+                PolygonIssueKind::SelfIntersection { a: *a, b: *b },
+            );
+        }
+        if !intersections.is_empty() && opts.repair {
+            let parts = repair_ring(points);
+            issue(
+                RingKind::Exterior,
+                PolygonIssueKind::Repaired { parts: parts.len() },
+            );
+            // holes can't be assigned reliably to the parts, keep them on the first one
+            for (part_idx, part) in parts.into_iter().enumerate() {
+                let (part, _) = oriented(part, true);
+                let part_holes = if part_idx == 0 {
+                    std::mem::take(&mut holes)
+                } else {
+                    vec![]
+                };
+                r.push(Polygon::new(close_ring(&part), part_holes));
+            }
+            continue;
+        }
+        let (points, reoriented) = oriented(points, true);
+        if reoriented {
+            issue(RingKind::Exterior, PolygonIssueKind::Reoriented);
+        }
+        r.push(Polygon::new(close_ring(&points), holes));
+    }
+    (r, report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coord::{Coord, Locatable};
+    use crate::prepared_polygon::prepare_polygons;
+
+    fn polygon(points: &[(f64, f64)]) -> Polygon<f64> {
+        Polygon::new(LineString::from(points.to_vec()), vec![])
+    }
+
+    #[test]
+    fn test_normalize_polygons() {
+        // clockwise square
+        let square = polygon(&[(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0)]);
+        // figure 8, crossing at (1, 1)
+        let bowtie = polygon(&[(0.0, 0.0), (2.0, 2.0), (2.0, 0.0), (0.0, 2.0)]);
+        let line = polygon(&[(0.0, 0.0), (1.0, 1.0)]);
+
+        let (polygons, report) = normalize_polygons(
+            vec![square.clone(), bowtie.clone(), line],
+            &NormalizeOptions::default(),
+        );
+        assert_eq!(polygons.len(), 2);
+        assert!(signed_area(&open_ring(polygons[0].exterior())) > 0.0);
+        assert_eq!(
+            report.issues,
+            vec![
+                PolygonIssue {
+                    polygon: 0,
+                    ring: RingKind::Exterior,
+                    kind: PolygonIssueKind::Reoriented
+                },
+                PolygonIssue {
+                    polygon: 1,
+                    ring: RingKind::Exterior,
+                    kind: PolygonIssueKind::SelfIntersection { a: 0, b: 2 }
+                },
+                PolygonIssue {
+                    polygon: 2,
+                    ring: RingKind::Exterior,
+                    kind: PolygonIssueKind::Degenerate
+                },
+            ]
+        );
+        assert!(report.has_unrepaired_intersections());
+
+        let (polygons, report) =
+            normalize_polygons(vec![bowtie], &NormalizeOptions { repair: true });
+        assert_eq!(polygons.len(), 2);
+        assert!(!report.has_unrepaired_intersections());
+        for p in polygons.iter() {
+            assert!(find_self_intersections(&open_ring(p.exterior())).is_empty());
+            assert!(signed_area(&open_ring(p.exterior())) > 0.0);
+        }
+        // both lobes of the figure 8 are still covered
+        let polygons = prepare_polygons(polygons);
+        assert!(Coord::new(1.0, 0.5).is_in_polygons(&polygons));
+        assert!(Coord::new(1.0, 1.5).is_in_polygons(&polygons));
+        assert!(!Coord::new(0.5, 1.0).is_in_polygons(&polygons));
+    }
+}
@@ -0,0 +1,567 @@
+// packed polygon datasets, a compact binary format of the borders of many areas compiled
+//  from .poly and GeoJSON files, which can be memory-mapped and queried in place instead of
+//  deserializing hundreds of MB of polygons in every process.
+//
+// version 1 layout, all numbers are little endian:
+//   header:   magic "NBPG", then u32 version, areas, polygons, rings, points, names size, 0
+//   areas:    per area sorted by name, u32 name offset, name length, first polygon, polygons,
+//             then its bounding rect as f64 min x, min y, max x, max y
+//   polygons: per polygon, u32 first ring, rings (exterior first), then its bounding rect
+//   rings:    per ring, u32 first point, points
+//   points:   per point, f64 x (longitude), y (latitude)
+//   names:    utf-8 area names
+use crate::coord::Locatable;
+use crate::intern::Name;
+use crate::util::Area;
+use crate::Result;
+use byteorder::{ByteOrder, LittleEndian, WriteBytesExt};
+use geo::prelude::BoundingRect;
+use geo::{Coordinate, LineString, Polygon, Rect};
+use std::collections::HashMap;
+use std::fs;
+
+pub const POLYGON_PACK_MAGIC: &[u8; 4] = b"NBPG";
+pub const POLYGON_PACK_VERSION: u32 = 1;
+
+const HEADER_SIZE: usize = 32;
+const AREA_SIZE: usize = 48;
+const POLYGON_SIZE: usize = 40;
+const RING_SIZE: usize = 8;
+const POINT_SIZE: usize = 16;
+
+fn write_rect(buf: &mut Vec<u8>, rect: Option<Rect<f64>>) {
+    // empty polygons get an inverted rect, which contains nothing
+    let (min, max) = match rect {
+        Some(r) => (r.min(), r.max()),
+        None => (
+            Coordinate {
+                x: f64::INFINITY,
+                y: f64::INFINITY,
+            },
+            Coordinate {
+                x: f64::NEG_INFINITY,
+                y: f64::NEG_INFINITY,
+            },
+        ),
+    };
+    for v in [min.x, min.y, max.x, max.y] {
+        buf.write_f64::<LittleEndian>(v).unwrap();
+    }
+}
+
+fn merge_rects(a: Option<Rect<f64>>, b: Option<Rect<f64>>) -> Option<Rect<f64>> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(Rect::new(
+            Coordinate {
+                x: a.min().x.min(b.min().x),
+                y: a.min().y.min(b.min().y),
+            },
+            Coordinate {
+                x: a.max().x.max(b.max().x),
+                y: a.max().y.max(b.max().y),
+            },
+        )),
+        (a, None) => a,
+        (None, b) => b,
+    }
+}
+
+// build packs the polygons of each area
+pub fn build(areas: &HashMap<String, Vec<Polygon<f64>>>) -> Vec<u8> {
+    let mut names: Vec<&String> = areas.keys().collect();
+    names.sort();
+
+    let mut area_table = vec![];
+    let mut polygon_table = vec![];
+    let mut ring_table = vec![];
+    let mut points = vec![];
+    let mut name_bytes = vec![];
+    let (mut polygon_count, mut ring_count, mut point_count) = (0u32, 0u32, 0u32);
+    for name in names.iter() {
+        let polygons = &areas[*name];
+        area_table
+            .write_u32::<LittleEndian>(name_bytes.len() as u32)
+            .unwrap();
+        area_table
+            .write_u32::<LittleEndian>(name.len() as u32)
+            .unwrap();
+        area_table.write_u32::<LittleEndian>(polygon_count).unwrap();
+        area_table
+            .write_u32::<LittleEndian>(polygons.len() as u32)
+            .unwrap();
+        name_bytes.extend_from_slice(name.as_bytes());
+
+        let mut area_rect = None;
+        for polygon in polygons.iter() {
+            let rect = polygon.bounding_rect();
+            area_rect = merge_rects(area_rect, rect);
+            let rings: Vec<&LineString<f64>> = std::iter::once(polygon.exterior())
+                .chain(polygon.interiors().iter())
+                .collect();
+            polygon_table.write_u32::<LittleEndian>(ring_count).unwrap();
+            polygon_table
+                .write_u32::<LittleEndian>(rings.len() as u32)
+                .unwrap();
+            write_rect(&mut polygon_table, rect);
+            for ring in rings {
+                ring_table.write_u32::<LittleEndian>(point_count).unwrap();
+                ring_table
+                    .write_u32::<LittleEndian>(ring.0.len() as u32)
+                    .unwrap();
+                for c in ring.0.iter() {
+                    points.write_f64::<LittleEndian>(c.x).unwrap();
+                    points.write_f64::<LittleEndian>(c.y).unwrap();
+                }
+                point_count += ring.0.len() as u32;
+                ring_count += 1;
+            }
+            polygon_count += 1;
+        }
+        write_rect(&mut area_table, area_rect);
+    }
+
+    let mut buf = Vec::with_capacity(
+        HEADER_SIZE
+            + area_table.len()
+            + polygon_table.len()
+            + ring_table.len()
+            + points.len()
+            + name_bytes.len(),
+    );
+    buf.extend_from_slice(POLYGON_PACK_MAGIC);
+    for v in [
+        POLYGON_PACK_VERSION,
+        names.len() as u32,
+        polygon_count,
+        ring_count,
+        point_count,
+        name_bytes.len() as u32,
+        0,
+    ] {
+        buf.write_u32::<LittleEndian>(v).unwrap();
+    }
+    buf.extend_from_slice(&area_table);
+    buf.extend_from_slice(&polygon_table);
+    buf.extend_from_slice(&ring_table);
+    buf.extend_from_slice(&points);
+    buf.extend_from_slice(&name_bytes);
+    buf
+}
+
+// PackedPolygons queries a packed dataset in place, data being e.g. a Vec<u8> or a
+//  memory-mapped file. the tables are checked when it is created, points are read on demand
+pub struct PackedPolygons<B: AsRef<[u8]>> {
+    data: B,
+    areas: usize,
+    polygons_offset: usize,
+    polygons: usize,
+    rings_offset: usize,
+    rings: usize,
+    points_offset: usize,
+    points: usize,
+    names_offset: usize,
+}
+
+impl<B: AsRef<[u8]>> PackedPolygons<B> {
+    pub fn new(data: B) -> Result<PackedPolygons<B>> {
+        let bytes = data.as_ref();
+        if bytes.len() < HEADER_SIZE || &bytes[0..4] != POLYGON_PACK_MAGIC {
+            bail!("not a packed polygon dataset");
+        }
+        let header: Vec<usize> = (0..6)
+            .map(|i| LittleEndian::read_u32(&bytes[4 + i * 4..8 + i * 4]) as usize)
+            .collect();
+        if header[0] != POLYGON_PACK_VERSION as usize {
+            bail!("unsupported packed polygon dataset version {}", header[0]);
+        }
+        let (areas, polygons, rings, points, names_size) =
+            (header[1], header[2], header[3], header[4], header[5]);
+        let polygons_offset = HEADER_SIZE + areas * AREA_SIZE;
+        let rings_offset = polygons_offset + polygons * POLYGON_SIZE;
+        let points_offset = rings_offset + rings * RING_SIZE;
+        let names_offset = points_offset + points * POINT_SIZE;
+        if bytes.len() != names_offset + names_size {
+            bail!(
+                "packed polygon dataset has {} bytes, expected {}",
+                bytes.len(),
+                names_offset + names_size
+            );
+        }
+        let r = PackedPolygons {
+            data,
+            areas,
+            polygons_offset,
+            polygons,
+            rings_offset,
+            rings,
+            points_offset,
+            points,
+            names_offset,
+        };
+        r.check(names_size)?;
+        Ok(r)
+    }
+
+    // check makes sure that queries stay within the tables
+    fn check(&self, names_size: usize) -> Result<()> {
+        for i in 0..self.areas {
+            let (name_offset, name_len) = self.pair(HEADER_SIZE + i * AREA_SIZE);
+            if name_offset + name_len > names_size {
+                bail!("name of packed area {} is out of bounds", i);
+            }
+            let start = self.names_offset + name_offset;
+            if std::str::from_utf8(&self.data.as_ref()[start..start + name_len]).is_err() {
+                bail!("name of packed area {} is not valid utf-8", i);
+            }
+            let (first, count) = self.pair(HEADER_SIZE + i * AREA_SIZE + 8);
+            if first + count > self.polygons {
+                bail!("polygons of packed area {} are out of bounds", i);
+            }
+        }
+        for i in 0..self.polygons {
+            let (first, count) = self.pair(self.polygons_offset + i * POLYGON_SIZE);
+            if first + count > self.rings {
+                bail!("rings of packed polygon {} are out of bounds", i);
+            }
+        }
+        for i in 0..self.rings {
+            let (first, count) = self.pair(self.rings_offset + i * RING_SIZE);
+            if first + count > self.points {
+                bail!("points of packed ring {} are out of bounds", i);
+            }
+        }
+        Ok(())
+    }
+
+    fn pair(&self, offset: usize) -> (usize, usize) {
+        let bytes = self.data.as_ref();
+        (
+            LittleEndian::read_u32(&bytes[offset..offset + 4]) as usize,
+            LittleEndian::read_u32(&bytes[offset + 4..offset + 8]) as usize,
+        )
+    }
+
+    fn rect(&self, offset: usize) -> [f64; 4] {
+        let mut r = [0.0; 4];
+        LittleEndian::read_f64_into(&self.data.as_ref()[offset..offset + 32], &mut r);
+        r
+    }
+
+    fn point(&self, i: usize) -> (f64, f64) {
+        let offset = self.points_offset + i * POINT_SIZE;
+        let bytes = self.data.as_ref();
+        (
+            LittleEndian::read_f64(&bytes[offset..offset + 8]),
+            LittleEndian::read_f64(&bytes[offset + 8..offset + 16]),
+        )
+    }
+
+    fn area_name(&self, i: usize) -> &str {
+        let (offset, len) = self.pair(HEADER_SIZE + i * AREA_SIZE);
+        let start = self.names_offset + offset;
+        // checked by new
+        std::str::from_utf8(&self.data.as_ref()[start..start + len]).unwrap_or_default()
+    }
+
+    pub fn area_count(&self) -> usize {
+        self.areas
+    }
+
+    // area_names returns the names of the areas, sorted
+    pub fn area_names(&self) -> Vec<&str> {
+        (0..self.areas).map(|i| self.area_name(i)).collect()
+    }
+
+    fn area_index(&self, name: &str) -> Option<usize> {
+        let (mut lo, mut hi) = (0, self.areas);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            match self.area_name(mid).cmp(name) {
+                std::cmp::Ordering::Equal => return Some(mid),
+                std::cmp::Ordering::Less => lo = mid + 1,
+                std::cmp::Ordering::Greater => hi = mid,
+            }
+        }
+        None
+    }
+
+    fn area_polygons(&self, name: &str) -> Option<std::ops::Range<usize>> {
+        let i = self.area_index(name)?;
+        let (first, count) = self.pair(HEADER_SIZE + i * AREA_SIZE + 8);
+        Some(first..first + count)
+    }
+
+    // bounding_rect of the polygons of an area, None for unknown and empty areas
+    pub fn bounding_rect(&self, name: &str) -> Option<Rect<f64>> {
+        let i = self.area_index(name)?;
+        let r = self.rect(HEADER_SIZE + i * AREA_SIZE + 16);
+        if r[0] > r[2] {
+            return None;
+        }
+        Some(Rect::new(
+            Coordinate { x: r[0], y: r[1] },
+            Coordinate { x: r[2], y: r[3] },
+        ))
+    }
+
+    // ring_crosses tells if a ray from (x, y) crosses the ring an odd number of times
+    fn ring_crosses(&self, ring: usize, x: f64, y: f64) -> bool {
+        let (first, count) = self.pair(self.rings_offset + ring * RING_SIZE);
+        if count == 0 {
+            return false;
+        }
+        let mut inside = false;
+        let mut prev = self.point(first + count - 1);
+        for i in first..first + count {
+            let cur = self.point(i);
+            if (cur.1 > y) != (prev.1 > y)
+                && x < (prev.0 - cur.0) * (y - cur.1) / (prev.1 - cur.1) + cur.0
+            {
+                inside = !inside;
+            }
+            prev = cur;
+        }
+        inside
+    }
+
+    fn polygon_contains(&self, polygon: usize, x: f64, y: f64) -> bool {
+        let offset = self.polygons_offset + polygon * POLYGON_SIZE;
+        let r = self.rect(offset + 8);
+        if x < r[0] || y < r[1] || x > r[2] || y > r[3] {
+            return false;
+        }
+        // even-odd over the exterior and the holes
+        let (first, count) = self.pair(offset);
+        (first..first + count).fold(false, |inside, ring| inside ^ self.ring_crosses(ring, x, y))
+    }
+
+    pub fn contains<T: Locatable>(&self, area: &str, c: &T) -> bool {
+        let (x, y) = (c.lng(), c.lat());
+        match self.area_polygons(area) {
+            Some(mut range) => range.any(|p| self.polygon_contains(p, x, y)),
+            None => false,
+        }
+    }
+
+    // area_of returns the first of areas whose polygons contain the coordinate, like
+    //  PolygonStore::area_of
+    pub fn area_of<T: Locatable>(&self, c: &T, areas: &[Area]) -> Option<Name> {
+        areas
+            .iter()
+            .find(|a| self.contains(a.name.as_str(), c))
+            .map(|a| a.name.clone())
+    }
+
+    // polygons deserializes the polygons of an area
+    pub fn polygons(&self, area: &str) -> Option<Vec<Polygon<f64>>> {
+        let range = self.area_polygons(area)?;
+        Some(range.map(|p| self.polygon(p)).collect())
+    }
+
+    fn polygon(&self, polygon: usize) -> Polygon<f64> {
+        let (first, count) = self.pair(self.polygons_offset + polygon * POLYGON_SIZE);
+        let mut rings: Vec<LineString<f64>> = (first..first + count)
+            .map(|ring| {
+                let (first, count) = self.pair(self.rings_offset + ring * RING_SIZE);
+                LineString::from(
+                    (first..first + count)
+                        .map(|i| self.point(i))
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect();
+        if rings.is_empty() {
+            return Polygon::new(LineString(vec![]), vec![]);
+        }
+        let exterior = rings.remove(0);
+        Polygon::new(exterior, rings)
+    }
+
+    // to_polygons deserializes the whole dataset
+    pub fn to_polygons(&self) -> HashMap<String, Vec<Polygon<f64>>> {
+        self.area_names()
+            .into_iter()
+            .filter_map(|name| Some((name.to_string(), self.polygons(name)?)))
+            .collect()
+    }
+}
+
+impl PackedPolygons<Vec<u8>> {
+    // read loads a packed dataset in memory
+    pub fn read(path: &str) -> Result<PackedPolygons<Vec<u8>>> {
+        PackedPolygons::new(fs::read(path)?)
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl PackedPolygons<memmap2::Mmap> {
+    // open memory-maps a packed dataset, the file must not be modified while it is mapped
+    pub fn open(path: &str) -> Result<PackedPolygons<memmap2::Mmap>> {
+        let file = fs::File::open(path)?;
+        // safety: datasets are written once by convert and only read afterwards
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        PackedPolygons::new(map)
+    }
+}
+
+// load_source loads a .poly file, or a GeoJSON file for the .json and .geojson extensions
+pub fn load_source(path: &str) -> Result<Vec<Polygon<f64>>> {
+    if path.ends_with(".json") || path.ends_with(".geojson") {
+        return Ok(crate::poly::load_geojson(path)?);
+    }
+    Ok(crate::poly::load(path)?)
+}
+
+// convert packs the polygons of (area, source file) pairs into output
+pub fn convert(inputs: &[(String, String)], output: &str) -> Result<()> {
+    let mut areas: HashMap<String, Vec<Polygon<f64>>> = HashMap::new();
+    for (area, path) in inputs.iter() {
+        areas
+            .entry(area.clone())
+            .or_default()
+            .extend(load_source(path)?);
+    }
+    let data = build(&areas);
+    fs::write(output, &data)?;
+    info!(
+        "packed {} areas into {} ({} bytes)",
+        areas.len(),
+        output,
+        data.len()
+    );
+    Ok(())
+}
+
+// convert_args runs convert with command line arguments `<output> <area>=<path>...`, so that
+//  a binary only has to pass std::env::args().skip(1)
+pub fn convert_args(args: &[String]) -> Result<()> {
+    let (output, inputs) = match args.split_first() {
+        Some((output, inputs)) if !inputs.is_empty() => (output, inputs),
+        _ => bail!("usage: <output> <area>=<path>..."),
+    };
+    let mut pairs = vec![];
+    for input in inputs.iter() {
+        match input.split_once('=') {
+            Some((area, path)) if !area.is_empty() && !path.is_empty() => {
+                pairs.push((area.to_string(), path.to_string()))
+            }
+            _ => bail!("invalid input {}, expected <area>=<path>", input),
+        }
+    }
+    convert(&pairs, output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coord::Coord;
+    use crate::intern::intern;
+    use crate::testkit::{random_coords, ring_polygon, square_polygon, AreaBuilder};
+    use geo::algorithm::contains::Contains;
+    use geo::Point;
+
+    fn dataset() -> HashMap<String, Vec<Polygon<f64>>> {
+        let mut areas = HashMap::new();
+        areas.insert(
+            "ring".to_string(),
+            vec![ring_polygon(&Coord::new(1.3, 103.8), 1.0, 0.5)],
+        );
+        areas.insert(
+            "squares".to_string(),
+            vec![
+                square_polygon(&Coord::new(1.3, 103.8), 0.2),
+                square_polygon(&Coord::new(3.0, 106.0), 0.5),
+            ],
+        );
+        areas.insert("empty".to_string(), vec![]);
+        areas
+    }
+
+    #[test]
+    fn test_packed_polygons() {
+        let areas = dataset();
+        let packed = PackedPolygons::new(build(&areas)).unwrap();
+        assert_eq!(packed.area_names(), vec!["empty", "ring", "squares"]);
+        assert_eq!(packed.to_polygons(), areas);
+        assert_eq!(packed.polygons("missing"), None);
+        assert_eq!(packed.bounding_rect("empty"), None);
+        let rect = packed.bounding_rect("squares").unwrap();
+        assert_eq!((rect.min().x, rect.max().y), (103.6, 3.5));
+
+        for c in random_coords(&Coord::new(2.0, 104.5), 2.5, 5000, 9) {
+            let p = Point::new(c.lng, c.lat);
+            for (name, polygons) in areas.iter() {
+                assert_eq!(
+                    packed.contains(name, &c),
+                    polygons.iter().any(|polygon| polygon.contains(&p)),
+                    "{} {:?}",
+                    name,
+                    c
+                );
+            }
+        }
+        let selected = vec![
+            AreaBuilder::new("squares", "4w").build(),
+            AreaBuilder::new("ring", "4w").build(),
+        ];
+        assert_eq!(
+            packed.area_of(&Coord::new(1.3, 103.8), &selected),
+            Some(intern("squares"))
+        );
+        assert_eq!(
+            packed.area_of(&Coord::new(1.3, 104.55), &selected),
+            Some(intern("ring"))
+        );
+        assert_eq!(
+            packed.area_of(&Coord::new(1.3, 103.8 + 0.3), &selected),
+            None
+        );
+
+        let mut data = build(&areas);
+        assert!(PackedPolygons::new(&data[..data.len() - 1]).is_err());
+        data[0] = b'X';
+        assert!(PackedPolygons::new(data).is_err());
+    }
+
+    #[test]
+    fn test_convert() {
+        let dir = std::env::temp_dir().join(format!("nbroutes-pack-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let geojson = dir.join("squares.geojson");
+        let collection = serde_json::json!({
+            "type": "FeatureCollection",
+            "features": [
+                {"type": "Feature", "properties": {}, "geometry": {
+                    "type": "MultiPolygon",
+                    "coordinates": [
+                        [[[103.6, 1.1], [104.0, 1.1], [104.0, 1.5], [103.6, 1.5], [103.6, 1.1]]],
+                        [[[105.5, 2.5], [106.5, 2.5], [106.5, 3.5], [105.5, 3.5], [105.5, 2.5]]],
+                    ],
+                }},
+                {"type": "Feature", "properties": {}, "geometry": {"type": "Point", "coordinates": [103.8, 1.3]}},
+            ],
+        });
+        fs::write(&geojson, collection.to_string()).unwrap();
+        let output = dir.join("areas.nbpg");
+        let output = output.to_str().unwrap();
+
+        convert_args(&[
+            output.to_string(),
+            format!("squares={}", geojson.to_str().unwrap()),
+        ])
+        .unwrap();
+        let packed = PackedPolygons::read(output).unwrap();
+        assert_eq!(packed.area_names(), vec!["squares"]);
+        assert_eq!(packed.polygons("squares").unwrap().len(), 2);
+        assert!(packed.contains("squares", &Coord::new(3.0, 106.0)));
+        #[cfg(feature = "mmap")]
+        {
+            let mapped = PackedPolygons::open(output).unwrap();
+            assert_eq!(mapped.to_polygons(), packed.to_polygons());
+        }
+
+        assert!(convert_args(&[output.to_string()]).is_err());
+        assert!(convert_args(&[output.to_string(), "squares".to_string()]).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,421 @@
+use crate::coord::Locatable;
+use crate::intern::Name;
+use crate::prepared_polygon::{unprepare_polygons, PreparedPolygon};
+use crate::simplify::{build_area_polygons, AreaPolygons};
+use crate::statsd::{MetricType, RegisterMetricInput, TrackCountInput, TypedTrackInput};
+use crate::util::Area;
+use geo::{LineString, Polygon};
+use lru_cache::LruCache;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::SyncSender;
+use std::sync::Mutex;
+
+pub const METRIC_AREA_CACHE_LOOKUPS: &str = "area_cache_lookups";
+pub const LABELNAME_CACHE_RESULT: &str = "result";
+
+// 4 decimals is ~11m at the equator
+pub const DEFAULT_AREA_CACHE_PRECISION: u32 = 4;
+
+// coordinates per task of areas_of_parallel, small enough for idle threads to steal work from
+//  busy ones and large enough to keep the overhead of tasks negligible
+pub const DEFAULT_PARALLEL_CHUNK_SIZE: usize = 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct CacheKey {
+    lat: i64,
+    lng: i64,
+    precision: u32,
+}
+
+impl CacheKey {
+    fn new<T: Locatable>(c: &T, precision: u32) -> CacheKey {
+        let factor = 10_f64.powi(precision as i32);
+        CacheKey {
+            lat: (c.lat() * factor).round() as i64,
+            lng: (c.lng() * factor).round() as i64,
+            precision,
+        }
+    }
+}
+
+// PolygonStore holds the polygons of all areas and optionally caches the area of recently
+//  looked up coordinates. coordinates are quantized to the cache precision, so points closer
+//  than that to a border may get the area of their neighbours
+pub struct PolygonStore {
+    polygons: HashMap<String, AreaPolygons>,
+    // None means no area contains the coordinate
+    cache: Option<Mutex<LruCache<CacheKey, Option<Name>>>>,
+    cache_precision: u32,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    sender: Option<SyncSender<TypedTrackInput>>,
+}
+
+impl PolygonStore {
+    pub fn new(polygons: HashMap<String, AreaPolygons>) -> PolygonStore {
+        PolygonStore {
+            polygons,
+            cache: None,
+            cache_precision: DEFAULT_AREA_CACHE_PRECISION,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            sender: None,
+        }
+    }
+
+    // load builds the store from loaded polygons, applying the simplification of each area
+    pub fn load(areas: &[Area], polygons: HashMap<String, Vec<PreparedPolygon>>) -> PolygonStore {
+        PolygonStore::new(build_area_polygons(areas, unprepare_polygons(polygons)))
+    }
+
+    // with_cache enables the area cache, a capacity of 0 disables it
+    pub fn with_cache(mut self, capacity: usize, precision: u32) -> PolygonStore {
+        self.cache = if capacity == 0 {
+            None
+        } else {
+            Some(Mutex::new(LruCache::new(capacity)))
+        };
+        self.cache_precision = precision;
+        self
+    }
+
+    // with_sender tracks cache hits and misses with the statsd collector
+    pub fn with_sender(mut self, sender: SyncSender<TypedTrackInput>) -> PolygonStore {
+        self.sender = Some(sender);
+        self
+    }
+
+    pub fn get(&self, area: &str) -> Option<&AreaPolygons> {
+        self.polygons.get(area)
+    }
+
+    pub fn contains<T: Locatable>(&self, area: &str, c: &T) -> bool {
+        self.polygons
+            .get(area)
+            .map(|p| p.contains(c))
+            .unwrap_or(false)
+    }
+
+    // area_of returns the first of areas whose polygons contain the coordinate.
+    //  cached results assume areas is the same list on every call
+    pub fn area_of<T: Locatable>(&self, c: &T, areas: &[Area]) -> Option<Name> {
+        let cache = match self.cache.as_ref() {
+            Some(v) => v,
+            None => return self.lookup(c, areas),
+        };
+        let key = CacheKey::new(c, self.cache_precision);
+        if let Some(v) = cache.lock().unwrap().get_mut(&key) {
+            let v = v.clone();
+            self.track(true);
+            return v;
+        }
+        self.track(false);
+        // containment runs without holding the lock
+        let v = self.lookup(c, areas);
+        cache.lock().unwrap().insert(key, v.clone());
+        v
+    }
+
+    // areas_of returns the area of each coordinate like area_of. batches skip the area cache,
+    //  massive inputs would evict the coordinates of single lookups
+    pub fn areas_of<T: Locatable>(&self, coords: &[T], areas: &[Area]) -> Vec<Option<Name>> {
+        coords.iter().map(|c| self.lookup(c, areas)).collect()
+    }
+
+    // areas_of_parallel is areas_of on the rayon thread pool, coordinates are split in chunks
+    //  of chunk_size which idle threads steal from busy ones
+    #[cfg(feature = "parallel")]
+    pub fn areas_of_parallel<T: Locatable + Sync>(
+        &self,
+        coords: &[T],
+        areas: &[Area],
+        chunk_size: usize,
+    ) -> Vec<Option<Name>> {
+        coords
+            .par_chunks(chunk_size.max(1))
+            .flat_map_iter(|chunk| chunk.iter().map(move |c| self.lookup(c, areas)))
+            .collect()
+    }
+
+    fn lookup<T: Locatable>(&self, c: &T, areas: &[Area]) -> Option<Name> {
+        areas
+            .iter()
+            .find(|a| self.contains(a.name.as_str(), c))
+            .map(|a| a.name.clone())
+    }
+
+    fn track(&self, hit: bool) {
+        let (counter, result) = if hit {
+            (&self.hits, "hit")
+        } else {
+            (&self.misses, "miss")
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        if let Some(sender) = self.sender.as_ref() {
+            let mut labels = HashMap::new();
+            labels.insert(LABELNAME_CACHE_RESULT.to_string(), result.to_string());
+            let r = sender.try_send(TypedTrackInput::Counter(TrackCountInput {
+                metric_name: METRIC_AREA_CACHE_LOOKUPS.to_string(),
+                count: 1.0,
+                labels,
+            }));
+            if let Err(e) = r {
+                warn!("failed to track area cache lookup due to {:?}", e);
+            }
+        }
+    }
+
+    // reload replaces the polygons and invalidates the cache
+    pub fn reload(&mut self, polygons: HashMap<String, AreaPolygons>) {
+        self.polygons = polygons;
+        self.invalidate();
+    }
+
+    pub fn invalidate(&self) {
+        if let Some(cache) = self.cache.as_ref() {
+            cache.lock().unwrap().clear();
+        }
+    }
+
+    // cache_stats returns the number of cache hits and misses
+    pub fn cache_stats(&self) -> (u64, u64) {
+        (
+            self.hits.load(Ordering::Relaxed),
+            self.misses.load(Ordering::Relaxed),
+        )
+    }
+}
+
+fn ring_coordinates(ring: &LineString<f64>) -> Value {
+    Value::Array(ring.0.iter().map(|c| json!([c.x, c.y])).collect())
+}
+
+fn ring_wkt(ring: &LineString<f64>) -> String {
+    let points: Vec<String> = ring.0.iter().map(|c| format!("{} {}", c.x, c.y)).collect();
+    format!("({})", points.join(", "))
+}
+
+// area_feature is a MultiPolygon feature with the area name in its properties
+fn area_feature(name: &str, polygons: &[Polygon<f64>]) -> Value {
+    let coordinates: Vec<Value> = polygons
+        .iter()
+        .map(|p| {
+            let mut rings = vec![ring_coordinates(p.exterior())];
+            rings.extend(p.interiors().iter().map(ring_coordinates));
+            Value::Array(rings)
+        })
+        .collect();
+    json!({
+        "type": "Feature",
+        "geometry": {
+            "type": "MultiPolygon",
+            "coordinates": coordinates,
+        },
+        "properties": {
+            "area": name,
+        },
+    })
+}
+
+// polygons_wkt formats polygons as a WKT MULTIPOLYGON
+pub fn polygons_wkt(polygons: &[Polygon<f64>]) -> String {
+    if polygons.is_empty() {
+        return "MULTIPOLYGON EMPTY".to_string();
+    }
+    let parts: Vec<String> = polygons
+        .iter()
+        .map(|p| {
+            let mut rings = vec![ring_wkt(p.exterior())];
+            rings.extend(p.interiors().iter().map(ring_wkt));
+            format!("({})", rings.join(", "))
+        })
+        .collect();
+    format!("MULTIPOLYGON ({})", parts.join(", "))
+}
+
+impl PolygonStore {
+    // export_area_geojson returns a FeatureCollection with the polygons loaded for the area,
+    //  as they are before simplification
+    pub fn export_area_geojson(&self, area: &str) -> Option<Value> {
+        let polygons = self.polygons.get(area)?;
+        Some(json!({
+            "type": "FeatureCollection",
+            "features": [area_feature(area, &polygons.original)],
+        }))
+    }
+
+    // export_all_geojson returns a FeatureCollection with one feature per area, sorted by name
+    pub fn export_all_geojson(&self) -> Value {
+        let mut names: Vec<&String> = self.polygons.keys().collect();
+        names.sort();
+        let features: Vec<Value> = names
+            .into_iter()
+            .map(|name| area_feature(name, &self.polygons[name].original))
+            .collect();
+        json!({
+            "type": "FeatureCollection",
+            "features": features,
+        })
+    }
+
+    pub fn export_area_wkt(&self, area: &str) -> Option<String> {
+        self.polygons.get(area).map(|p| polygons_wkt(&p.original))
+    }
+}
+
+pub fn area_cache_metric() -> RegisterMetricInput {
+    RegisterMetricInput {
+        metric_type: MetricType::Counter,
+        metric_name: METRIC_AREA_CACHE_LOOKUPS.to_string(),
+        metric_desc: "number of area cache lookups".to_string(),
+        labels: vec![LABELNAME_CACHE_RESULT.to_string()],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coord::Coord;
+    use crate::intern::intern;
+    use crate::testkit::{polygons_map, random_coords, ring_polygon, square_polygon, AreaBuilder};
+    use std::sync::mpsc::sync_channel;
+
+    #[test]
+    fn test_area_cache() {
+        let center = Coord::new(1.3, 103.8);
+        let areas = vec![
+            AreaBuilder::new("small", "4w").build(),
+            AreaBuilder::new("large", "4w").build(),
+        ];
+        let polygons = polygons_map(vec![
+            ("small", vec![square_polygon(&center, 0.1)]),
+            ("large", vec![square_polygon(&center, 1.0)]),
+        ]);
+        let (tx, rx) = sync_channel(10);
+        let mut store = PolygonStore::load(&areas, polygons)
+            .with_cache(2, DEFAULT_AREA_CACHE_PRECISION)
+            .with_sender(tx);
+
+        assert_eq!(store.area_of(&center, &areas), Some(intern("small")));
+        // same quantized coordinate
+        assert_eq!(
+            store.area_of(&Coord::new(1.30001, 103.80001), &areas),
+            Some(intern("small"))
+        );
+        assert_eq!(
+            store.area_of(&Coord::new(1.8, 103.8), &areas),
+            Some(intern("large"))
+        );
+        assert_eq!(store.area_of(&Coord::new(5.0, 103.8), &areas), None);
+        assert_eq!(store.area_of(&Coord::new(5.0, 103.8), &areas), None);
+        assert_eq!(store.cache_stats(), (2, 3));
+        let tracked: Vec<String> = rx
+            .try_iter()
+            .map(|m| match m {
+                TypedTrackInput::Counter(c) => c.labels[LABELNAME_CACHE_RESULT].clone(),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(tracked, vec!["miss", "hit", "miss", "miss", "hit"]);
+
+        // reloaded polygons are used right away
+        store.reload(build_area_polygons(
+            &areas,
+            unprepare_polygons(polygons_map(vec![(
+                "large",
+                vec![square_polygon(&center, 1.0)],
+            )])),
+        ));
+        assert_eq!(store.area_of(&center, &areas), Some(intern("large")));
+        assert_eq!(store.cache_stats(), (2, 4));
+    }
+
+    #[test]
+    fn test_areas_of() {
+        let center = Coord::new(1.3, 103.8);
+        let areas = vec![
+            AreaBuilder::new("ring", "4w").build(),
+            AreaBuilder::new("square", "4w").build(),
+        ];
+        let store = PolygonStore::load(
+            &areas,
+            polygons_map(vec![
+                ("ring", vec![ring_polygon(&center, 1.0, 0.5)]),
+                ("square", vec![square_polygon(&center, 0.2)]),
+            ]),
+        )
+        .with_cache(100, DEFAULT_AREA_CACHE_PRECISION);
+        let coords = random_coords(&center, 1.5, 5000, 5);
+        let expected: Vec<Option<Name>> = coords.iter().map(|c| store.lookup(c, &areas)).collect();
+        assert_eq!(store.areas_of(&coords, &areas), expected);
+        assert!(expected.iter().any(|a| a.as_deref() == Some("ring")));
+        assert!(expected.iter().any(|a| a.as_deref() == Some("square")));
+        assert!(expected.iter().any(|a| a.is_none()));
+        assert_eq!(store.cache_stats(), (0, 0));
+
+        #[cfg(feature = "parallel")]
+        for chunk_size in [0, 7, DEFAULT_PARALLEL_CHUNK_SIZE] {
+            assert_eq!(
+                store.areas_of_parallel(&coords, &areas, chunk_size),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_export() {
+        let center = Coord::new(1.0, 100.0);
+        let areas = vec![
+            AreaBuilder::new("ring", "4w").build(),
+            AreaBuilder::new("empty", "4w").build(),
+        ];
+        let store = PolygonStore::load(
+            &areas,
+            polygons_map(vec![
+                ("ring", vec![ring_polygon(&center, 1.0, 0.5)]),
+                ("empty", vec![]),
+            ]),
+        );
+        assert_eq!(store.export_area_geojson("missing"), None);
+
+        let geojson = store.export_area_geojson("ring").unwrap();
+        assert_eq!(geojson["type"], "FeatureCollection");
+        let feature = &geojson["features"][0];
+        assert_eq!(feature["properties"]["area"], "ring");
+        assert_eq!(feature["geometry"]["type"], "MultiPolygon");
+        assert_eq!(
+            feature["geometry"]["coordinates"][0][0][0],
+            json!([99.0, 0.0])
+        );
+        assert_eq!(
+            feature["geometry"]["coordinates"][0][1][2],
+            json!([100.5, 1.5])
+        );
+
+        let all = store.export_all_geojson();
+        let names: Vec<&Value> = all["features"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| &f["properties"]["area"])
+            .collect();
+        assert_eq!(names, vec!["empty", "ring"]);
+
+        assert_eq!(
+            store.export_area_wkt("empty").unwrap(),
+            "MULTIPOLYGON EMPTY"
+        );
+        let square = PolygonStore::load(
+            &areas,
+            polygons_map(vec![("square", vec![square_polygon(&center, 1.0)])]),
+        );
+        assert_eq!(
+            square.export_area_wkt("square").unwrap(),
+            "MULTIPOLYGON (((99 0, 101 0, 101 2, 99 2, 99 0)))"
+        );
+    }
+}
@@ -0,0 +1,102 @@
+use crate::coord::{Coord, Locatable};
+use crate::Result;
+
+pub const PRECISION_POLYLINE: u32 = 5;
+pub const PRECISION_POLYLINE6: u32 = 6;
+
+// decode an encoded polyline (google polyline algorithm) into coordinates
+pub fn decode(input: &str, precision: u32) -> Result<Vec<Coord>> {
+    let factor = 10_f64.powi(precision as i32);
+    let bytes = input.as_bytes();
+    let mut r = vec![];
+    let mut idx = 0;
+    let mut lat: i64 = 0;
+    let mut lng: i64 = 0;
+    while idx < bytes.len() {
+        let (delta_lat, next) = decode_value(bytes, idx)?;
+        let (delta_lng, next) = decode_value(bytes, next)?;
+        idx = next;
+        lat += delta_lat;
+        lng += delta_lng;
+        r.push(Coord::new(lat as f64 / factor, lng as f64 / factor));
+    }
+    Ok(r)
+}
+
+fn decode_value(bytes: &[u8], mut idx: usize) -> Result<(i64, usize)> {
+    let mut result: i64 = 0;
+    let mut shift = 0;
+    loop {
+        if idx >= bytes.len() {
+            bail!("invalid polyline: unexpected end of input");
+        }
+        if bytes[idx] < 63 || shift > 60 {
+            bail!("invalid polyline: bad character at {}", idx);
+        }
+        let b = (bytes[idx] - 63) as i64;
+        idx += 1;
+        result |= (b & 0x1f) << shift;
+        shift += 5;
+        if b < 0x20 {
+            break;
+        }
+    }
+    let value = if result & 1 != 0 {
+        !(result >> 1)
+    } else {
+        result >> 1
+    };
+    Ok((value, idx))
+}
+
+// encode coordinates into a polyline (google polyline algorithm)
+pub fn encode<T: Locatable>(coords: &[T], precision: u32) -> String {
+    let factor = 10_f64.powi(precision as i32);
+    let mut r = String::new();
+    let mut prev_lat: i64 = 0;
+    let mut prev_lng: i64 = 0;
+    for coord in coords {
+        let lat = (coord.lat() * factor).round() as i64;
+        let lng = (coord.lng() * factor).round() as i64;
+        encode_value(lat - prev_lat, &mut r);
+        encode_value(lng - prev_lng, &mut r);
+        prev_lat = lat;
+        prev_lng = lng;
+    }
+    r
+}
+
+fn encode_value(value: i64, output: &mut String) {
+    let mut v = if value < 0 { !(value << 1) } else { value << 1 };
+    while v >= 0x20 {
+        output.push((((v & 0x1f) | 0x20) as u8 + 63) as char);
+        v >>= 5;
+    }
+    output.push((v as u8 + 63) as char);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_encode() {
+        // example from the google polyline algorithm documentation
+        let encoded = "_p~iF~ps|U_ulLnnqC_mqNvxq`@";
+        let coords = decode(encoded, PRECISION_POLYLINE).unwrap();
+        assert_eq!(coords.len(), 3);
+        assert_eq!(coords[0].lat(), 38.5);
+        assert_eq!(coords[0].lng(), -120.2);
+        assert_eq!(coords[2].lat(), 43.252);
+        assert_eq!(coords[2].lng(), -126.453);
+        assert_eq!(encode(&coords, PRECISION_POLYLINE), encoded);
+
+        let encoded6 = encode(&coords, PRECISION_POLYLINE6);
+        let coords6 = decode(encoded6.as_str(), PRECISION_POLYLINE6).unwrap();
+        assert_eq!(coords6[1].lat(), 40.7);
+        assert_eq!(coords6[1].lng(), -120.95);
+
+        assert!(decode("_p~iF~ps|U_ulL", PRECISION_POLYLINE).is_err());
+        assert!(decode("", PRECISION_POLYLINE).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,156 @@
+// polygons prepared for repeated point-in-polygon checks. the bounding rect is computed once
+//  when the polygon is loaded instead of on every check, and the convex hull can be cached as
+//  a second cheaper filter for polygons with many vertices. dense polygons can also be
+//  simplified, the polygon itself then only being checked near the simplified boundary
+use crate::simplify::{near_boundary, simplify_polygon, tolerance_to_degrees};
+use geo::algorithm::contains::Contains;
+use geo::algorithm::convex_hull::ConvexHull;
+use geo::prelude::BoundingRect;
+use geo::{Point, Polygon, Rect};
+use std::borrow::Borrow;
+use std::collections::HashMap;
+
+#[derive(Clone, Debug)]
+pub struct PreparedPolygon {
+    polygon: Polygon<f64>,
+    // None for polygons without points, which contain nothing
+    bbox: Option<Rect<f64>>,
+    hull: Option<Polygon<f64>>,
+    simplified: Option<Polygon<f64>>,
+    // max distance between the simplified and the original boundaries, in degrees
+    tolerance_degrees: f64,
+}
+
+impl PreparedPolygon {
+    pub fn new(polygon: Polygon<f64>) -> PreparedPolygon {
+        PreparedPolygon {
+            bbox: polygon.bounding_rect(),
+            polygon,
+            hull: None,
+            simplified: None,
+            tolerance_degrees: 0.0,
+        }
+    }
+
+    // with_hull also caches the convex hull, worth it when the polygon has many more vertices
+    //  than its hull
+    pub fn with_hull(mut self) -> PreparedPolygon {
+        self.hull = Some(self.polygon.convex_hull());
+        self
+    }
+
+    // with_simplified also keeps the polygon simplified with tolerance_meters, points farther
+    //  than the tolerance from its boundary are checked against it instead of the polygon
+    pub fn with_simplified(mut self, tolerance_meters: f64) -> PreparedPolygon {
+        if tolerance_meters <= 0.0 {
+            return self;
+        }
+        let tolerance = tolerance_to_degrees(std::slice::from_ref(&self.polygon), tolerance_meters);
+        self.simplified = Some(simplify_polygon(&self.polygon, tolerance));
+        self.tolerance_degrees = tolerance;
+        self
+    }
+
+    pub fn polygon(&self) -> &Polygon<f64> {
+        &self.polygon
+    }
+
+    pub fn bbox(&self) -> Option<&Rect<f64>> {
+        self.bbox.as_ref()
+    }
+
+    pub fn simplified(&self) -> Option<&Polygon<f64>> {
+        self.simplified.as_ref()
+    }
+
+    pub fn into_inner(self) -> Polygon<f64> {
+        self.polygon
+    }
+
+    pub fn contains(&self, p: &Point<f64>) -> bool {
+        let bbox = match self.bbox.as_ref() {
+            Some(v) => v,
+            None => return false,
+        };
+        if p.x() < bbox.min().x
+            || p.x() > bbox.max().x
+            || p.y() < bbox.min().y
+            || p.y() > bbox.max().y
+        {
+            return false;
+        }
+        if let Some(hull) = self.hull.as_ref() {
+            if !hull.contains(p) {
+                return false;
+            }
+        }
+        if let Some(simplified) = self.simplified.as_ref() {
+            if !near_boundary(simplified, p, self.tolerance_degrees) {
+                return simplified.contains(p);
+            }
+        }
+        self.polygon.contains(p)
+    }
+}
+
+impl From<Polygon<f64>> for PreparedPolygon {
+    fn from(polygon: Polygon<f64>) -> Self {
+        PreparedPolygon::new(polygon)
+    }
+}
+
+impl Borrow<Polygon<f64>> for PreparedPolygon {
+    fn borrow(&self) -> &Polygon<f64> {
+        &self.polygon
+    }
+}
+
+pub fn prepare_polygons(polygons: Vec<Polygon<f64>>) -> Vec<PreparedPolygon> {
+    polygons.into_iter().map(PreparedPolygon::new).collect()
+}
+
+// unprepare_polygons returns the raw polygons of each area, e.g. for PolygonStore
+pub fn unprepare_polygons(
+    polygons: HashMap<String, Vec<PreparedPolygon>>,
+) -> HashMap<String, Vec<Polygon<f64>>> {
+    polygons
+        .into_iter()
+        .map(|(name, ps)| (name, ps.into_iter().map(|p| p.into_inner()).collect()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coord::Coord;
+    use crate::testkit::{random_coords, ring_polygon, square_polygon};
+    use geo::LineString;
+
+    #[test]
+    fn test_prepared_polygon() {
+        let center = Coord::new(60.0, 10.0);
+        let polygons = vec![
+            ring_polygon(&center, 1.0, 0.5),
+            square_polygon(&Coord::new(62.0, 12.0), 0.2),
+        ];
+        let prepared = prepare_polygons(polygons.clone());
+        let with_hull: Vec<PreparedPolygon> = polygons
+            .iter()
+            .cloned()
+            .map(|p| PreparedPolygon::new(p).with_hull())
+            .collect();
+        assert_eq!(prepared[1].bbox(), polygons[1].bounding_rect().as_ref());
+
+        for c in random_coords(&Coord::new(61.0, 11.0), 2.0, 5000, 7) {
+            let p = Point::new(c.lng, c.lat);
+            for (i, polygon) in polygons.iter().enumerate() {
+                assert_eq!(prepared[i].contains(&p), polygon.contains(&p));
+                assert_eq!(with_hull[i].contains(&p), polygon.contains(&p));
+            }
+        }
+
+        let empty = PreparedPolygon::new(Polygon::new(LineString(vec![]), vec![]));
+        assert!(empty.bbox().is_none());
+        assert!(!empty.contains(&Point::new(10.0, 60.0)));
+    }
+}
@@ -0,0 +1,223 @@
+use crate::coord::Locatable;
+use crate::def::*;
+use crate::util::Area;
+use std::fmt;
+
+// set to `true` to log raw coordinates, only meant for debugging environments
+pub const ENV_LOG_RAW_COORDS: &str = "NBROUTES_LOG_RAW_COORDS";
+// number of decimals kept when logging coordinates
+pub const ENV_LOG_COORD_PRECISION: &str = "NBROUTES_LOG_COORD_PRECISION";
+// 2 decimals is ~1km at the equator
+pub const DEFAULT_LOG_COORD_PRECISION: u32 = 2;
+
+lazy_static! {
+    static ref LOG_RAW_COORDS: bool = std::env::var(ENV_LOG_RAW_COORDS)
+        .map(|v| v == "true" || v == "1")
+        .unwrap_or(false);
+    static ref LOG_COORD_PRECISION: u32 = std::env::var(ENV_LOG_COORD_PRECISION)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_LOG_COORD_PRECISION);
+}
+
+// truncate drops the decimals beyond precision, rounding towards zero so that the
+//  logged value never points to a more precise location than the original
+pub fn truncate(value: f64, precision: u32) -> f64 {
+    let factor = 10_f64.powi(precision as i32);
+    (value * factor).trunc() / factor
+}
+
+// AnonymizedCoord formats a coordinate for logging
+pub struct AnonymizedCoord {
+    lat: f64,
+    lng: f64,
+    precision: Option<u32>,
+}
+
+impl fmt::Display for AnonymizedCoord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.precision {
+            None => write!(f, "{},{}", self.lat, self.lng),
+            Some(p) => write!(
+                f,
+                "{:.*},{:.*}",
+                p as usize,
+                truncate(self.lat, p),
+                p as usize,
+                truncate(self.lng, p)
+            ),
+        }
+    }
+}
+
+impl fmt::Debug for AnonymizedCoord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({})", self)
+    }
+}
+
+fn anonymize_with<T: Locatable>(c: &T, precision: u32, raw: bool) -> AnonymizedCoord {
+    AnonymizedCoord {
+        lat: c.lat(),
+        lng: c.lng(),
+        precision: if raw { None } else { Some(precision) },
+    }
+}
+
+// anonymize truncates a coordinate to the configured log precision
+pub fn anonymize<T: Locatable>(c: &T) -> AnonymizedCoord {
+    anonymize_with(c, *LOG_COORD_PRECISION, *LOG_RAW_COORDS)
+}
+
+// anonymize_in_area uses the log precision of the area when configured
+pub fn anonymize_in_area<T: Locatable>(c: &T, area: &Area) -> AnonymizedCoord {
+    anonymize_with(
+        c,
+        area.log_coord_precision.unwrap_or(*LOG_COORD_PRECISION),
+        *LOG_RAW_COORDS,
+    )
+}
+
+pub fn anonymize_all<T: Locatable>(coords: &[T]) -> Vec<AnonymizedCoord> {
+    coords.iter().map(anonymize).collect()
+}
+
+pub fn anonymize_all_in_area<T: Locatable>(coords: &[T], area: &Area) -> Vec<AnonymizedCoord> {
+    coords.iter().map(|c| anonymize_in_area(c, area)).collect()
+}
+
+// api keys are masked down to their first characters, enough to tell them apart in logs
+pub const KEY_VISIBLE_CHARS: usize = 4;
+const KEY_MASK: &str = "****";
+
+pub fn mask_key(key: &str) -> String {
+    if key.chars().count() <= KEY_VISIBLE_CHARS {
+        return KEY_MASK.to_string();
+    }
+    let visible: String = key.chars().take(KEY_VISIBLE_CHARS).collect();
+    format!("{}{}", visible, KEY_MASK)
+}
+
+// MaskedKey formats an optional api key with mask_key, for Debug implementations
+pub struct MaskedKey<'a>(pub &'a Option<String>);
+
+impl<'a> fmt::Debug for MaskedKey<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.as_deref().map(mask_key).fmt(f)
+    }
+}
+
+// Sensitive is implemented by inputs carrying credentials, strip_sensitive masks them before
+//  the input is logged or tracked
+pub trait Sensitive {
+    fn strip_sensitive(&mut self);
+}
+
+pub fn strip_sensitive<T: Sensitive>(input: &mut T) {
+    input.strip_sensitive()
+}
+
+macro_rules! impl_sensitive_key {
+    ($($t:ty),* $(,)?) => {
+        $(impl Sensitive for $t {
+            fn strip_sensitive(&mut self) {
+                self.key = self.key.as_deref().map(mask_key);
+            }
+        })*
+    };
+}
+
+impl_sensitive_key!(
+    ISOChroneValhallaInput,
+    KeyInput,
+    UpdateRRTSimpleInput,
+    UpdateRRTSegmentInput,
+    UpdateRRTDimensionInput,
+    UpdateRRTFixedSpeedInput,
+    OptimizationInput,
+    NavigatingInput,
+    ValhallaDirectionsInput,
+    DirectionsInput,
+    PostTripRouteInput,
+    OptimizationPostInput,
+    OptimizationV2PostInput,
+    OptimizationGetInput,
+    GetNearbyInput,
+    ValhallaMatrixInput,
+    MatrixInput,
+    MassiveDistanceMatrixStatusInput,
+    IsochroneInput,
+    SnapInput,
+    ClusteringPostInput,
+);
+
+impl Sensitive for MassiveDistanceMatrixInput {
+    fn strip_sensitive(&mut self) {
+        self.matrix_input.strip_sensitive();
+    }
+}
+
+impl Sensitive for ValhallaMassiveDistanceMatrixInput {
+    fn strip_sensitive(&mut self) {
+        self.matrix_input.strip_sensitive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coord::Coord;
+    use crate::testkit::AreaBuilder;
+
+    #[test]
+    fn test_anonymize() {
+        let c = Coord::new(1.234567, -103.987654);
+        assert_eq!(anonymize_with(&c, 2, false).to_string(), "1.23,-103.98");
+        assert_eq!(anonymize_with(&c, 0, false).to_string(), "1,-103");
+        assert_eq!(
+            anonymize_with(&c, 2, true).to_string(),
+            "1.234567,-103.987654"
+        );
+        assert_eq!(
+            format!("{:?}", vec![anonymize_with(&c, 3, false)]),
+            "[(1.234,-103.987)]"
+        );
+
+        let mut area = AreaBuilder::new("singapore", "4w")
+            .log_coord_precision(1)
+            .build();
+        if !*LOG_RAW_COORDS {
+            assert_eq!(anonymize_in_area(&c, &area).to_string(), "1.2,-103.9");
+            area.log_coord_precision = None;
+            assert_eq!(
+                anonymize_in_area(&c, &area).to_string(),
+                anonymize(&c).to_string()
+            );
+        }
+    }
+
+    #[test]
+    fn test_mask_key() {
+        assert_eq!(mask_key("abcdef123456"), "abcd****");
+        assert_eq!(mask_key("abcd"), "****");
+        assert_eq!(mask_key(""), "****");
+
+        let mut input: MatrixInput = serde_json::from_value(serde_json::json!({
+            "origins": "1.3,103.8",
+            "destinations": "1.29,103.85",
+            "key": "abcdef123456",
+        }))
+        .unwrap();
+        strip_sensitive(&mut input);
+        assert_eq!(input.key.as_deref(), Some("abcd****"));
+
+        let input: ISOChroneValhallaInput = serde_json::from_value(serde_json::json!({
+            "coordinates": "1.3,103.8",
+            "key": "abcdef123456",
+        }))
+        .unwrap();
+        let formatted = format!("{:?}", input);
+        assert!(formatted.contains("key: Some(\"abcd****\")"), "{}", formatted);
+        assert!(!formatted.contains("abcdef123456"));
+    }
+}
@@ -806,6 +806,5215 @@ impl ::protobuf::reflect::ProtobufValue for MatrixOutputPB_MatrixRow {
     }
 }
 
+#[derive(PartialEq,Clone,Default)]
+pub struct StringListPB {
+    // message fields
+    pub values: ::protobuf::RepeatedField<::std::string::String>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a StringListPB {
+    fn default() -> &'a StringListPB {
+        <StringListPB as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl StringListPB {
+    pub fn new() -> StringListPB {
+        ::std::default::Default::default()
+    }
+
+    // repeated string values = 1;
+
+
+    pub fn get_values(&self) -> &[::std::string::String] {
+        &self.values
+    }
+    pub fn clear_values(&mut self) {
+        self.values.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_values(&mut self, v: ::protobuf::RepeatedField<::std::string::String>) {
+        self.values = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_values(&mut self) -> &mut ::protobuf::RepeatedField<::std::string::String> {
+        &mut self.values
+    }
+
+    // Take field
+    pub fn take_values(&mut self) -> ::protobuf::RepeatedField<::std::string::String> {
+        ::std::mem::replace(&mut self.values, ::protobuf::RepeatedField::new())
+    }
+}
+
+impl ::protobuf::Message for StringListPB {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_repeated_string_into(wire_type, is, &mut self.values)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        for value in &self.values {
+            my_size += ::protobuf::rt::string_size(1, &value);
+        };
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        for v in &self.values {
+            os.write_string(1, &v)?;
+        };
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> StringListPB {
+        StringListPB::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "values",
+                |m: &StringListPB| { &m.values },
+                |m: &mut StringListPB| { &mut m.values },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<StringListPB>(
+                "StringListPB",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static StringListPB {
+        static instance: ::protobuf::rt::LazyV2<StringListPB> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(StringListPB::new)
+    }
+}
+
+impl ::protobuf::Clear for StringListPB {
+    fn clear(&mut self) {
+        self.values.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for StringListPB {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for StringListPB {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct UInt32ListPB {
+    // message fields
+    pub values: ::std::vec::Vec<u32>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a UInt32ListPB {
+    fn default() -> &'a UInt32ListPB {
+        <UInt32ListPB as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl UInt32ListPB {
+    pub fn new() -> UInt32ListPB {
+        ::std::default::Default::default()
+    }
+
+    // repeated uint32 values = 1;
+
+
+    pub fn get_values(&self) -> &[u32] {
+        &self.values
+    }
+    pub fn clear_values(&mut self) {
+        self.values.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_values(&mut self, v: ::std::vec::Vec<u32>) {
+        self.values = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_values(&mut self) -> &mut ::std::vec::Vec<u32> {
+        &mut self.values
+    }
+
+    // Take field
+    pub fn take_values(&mut self) -> ::std::vec::Vec<u32> {
+        ::std::mem::replace(&mut self.values, ::std::vec::Vec::new())
+    }
+}
+
+impl ::protobuf::Message for UInt32ListPB {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_repeated_uint32_into(wire_type, is, &mut self.values)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        for value in &self.values {
+            my_size += ::protobuf::rt::value_size(1, *value, ::protobuf::wire_format::WireTypeVarint);
+        };
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        for v in &self.values {
+            os.write_uint32(1, *v)?;
+        };
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> UInt32ListPB {
+        UInt32ListPB::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_vec_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "values",
+                |m: &UInt32ListPB| { &m.values },
+                |m: &mut UInt32ListPB| { &mut m.values },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<UInt32ListPB>(
+                "UInt32ListPB",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static UInt32ListPB {
+        static instance: ::protobuf::rt::LazyV2<UInt32ListPB> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(UInt32ListPB::new)
+    }
+}
+
+impl ::protobuf::Clear for UInt32ListPB {
+    fn clear(&mut self) {
+        self.values.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for UInt32ListPB {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for UInt32ListPB {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct StringListMapPB {
+    // message fields
+    pub values: ::std::collections::HashMap<::std::string::String, StringListPB>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a StringListMapPB {
+    fn default() -> &'a StringListMapPB {
+        <StringListMapPB as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl StringListMapPB {
+    pub fn new() -> StringListMapPB {
+        ::std::default::Default::default()
+    }
+
+    // repeated .matrix.StringListMapPB.values_MapEntry values = 1;
+
+
+    pub fn get_values(&self) -> &::std::collections::HashMap<::std::string::String, StringListPB> {
+        &self.values
+    }
+    pub fn clear_values(&mut self) {
+        self.values.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_values(&mut self, v: ::std::collections::HashMap<::std::string::String, StringListPB>) {
+        self.values = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_values(&mut self) -> &mut ::std::collections::HashMap<::std::string::String, StringListPB> {
+        &mut self.values
+    }
+
+    // Take field
+    pub fn take_values(&mut self) -> ::std::collections::HashMap<::std::string::String, StringListPB> {
+        ::std::mem::replace(&mut self.values, ::std::collections::HashMap::new())
+    }
+}
+
+impl ::protobuf::Message for StringListMapPB {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_map_into::<::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeMessage<StringListPB>>(wire_type, is, &mut self.values)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        my_size += ::protobuf::rt::compute_map_size::<::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeMessage<StringListPB>>(1, &self.values);
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        ::protobuf::rt::write_map_with_cached_sizes::<::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeMessage<StringListPB>>(1, &self.values, os)?;
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> StringListMapPB {
+        StringListMapPB::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_map_accessor::<_, ::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeMessage<StringListPB>>(
+                "values",
+                |m: &StringListMapPB| { &m.values },
+                |m: &mut StringListMapPB| { &mut m.values },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<StringListMapPB>(
+                "StringListMapPB",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static StringListMapPB {
+        static instance: ::protobuf::rt::LazyV2<StringListMapPB> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(StringListMapPB::new)
+    }
+}
+
+impl ::protobuf::Clear for StringListMapPB {
+    fn clear(&mut self) {
+        self.values.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for StringListMapPB {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for StringListMapPB {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct BoolMapPB {
+    // message fields
+    pub values: ::std::collections::HashMap<::std::string::String, bool>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a BoolMapPB {
+    fn default() -> &'a BoolMapPB {
+        <BoolMapPB as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl BoolMapPB {
+    pub fn new() -> BoolMapPB {
+        ::std::default::Default::default()
+    }
+
+    // repeated .matrix.BoolMapPB.values_MapEntry values = 1;
+
+
+    pub fn get_values(&self) -> &::std::collections::HashMap<::std::string::String, bool> {
+        &self.values
+    }
+    pub fn clear_values(&mut self) {
+        self.values.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_values(&mut self, v: ::std::collections::HashMap<::std::string::String, bool>) {
+        self.values = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_values(&mut self) -> &mut ::std::collections::HashMap<::std::string::String, bool> {
+        &mut self.values
+    }
+
+    // Take field
+    pub fn take_values(&mut self) -> ::std::collections::HashMap<::std::string::String, bool> {
+        ::std::mem::replace(&mut self.values, ::std::collections::HashMap::new())
+    }
+}
+
+impl ::protobuf::Message for BoolMapPB {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_map_into::<::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeBool>(wire_type, is, &mut self.values)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        my_size += ::protobuf::rt::compute_map_size::<::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeBool>(1, &self.values);
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        ::protobuf::rt::write_map_with_cached_sizes::<::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeBool>(1, &self.values, os)?;
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> BoolMapPB {
+        BoolMapPB::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_map_accessor::<_, ::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeBool>(
+                "values",
+                |m: &BoolMapPB| { &m.values },
+                |m: &mut BoolMapPB| { &mut m.values },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<BoolMapPB>(
+                "BoolMapPB",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static BoolMapPB {
+        static instance: ::protobuf::rt::LazyV2<BoolMapPB> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(BoolMapPB::new)
+    }
+}
+
+impl ::protobuf::Clear for BoolMapPB {
+    fn clear(&mut self) {
+        self.values.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for BoolMapPB {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for BoolMapPB {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct ModeBoolMapPB {
+    // message fields
+    pub values: ::std::collections::HashMap<::std::string::String, BoolMapPB>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a ModeBoolMapPB {
+    fn default() -> &'a ModeBoolMapPB {
+        <ModeBoolMapPB as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl ModeBoolMapPB {
+    pub fn new() -> ModeBoolMapPB {
+        ::std::default::Default::default()
+    }
+
+    // repeated .matrix.ModeBoolMapPB.values_MapEntry values = 1;
+
+
+    pub fn get_values(&self) -> &::std::collections::HashMap<::std::string::String, BoolMapPB> {
+        &self.values
+    }
+    pub fn clear_values(&mut self) {
+        self.values.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_values(&mut self, v: ::std::collections::HashMap<::std::string::String, BoolMapPB>) {
+        self.values = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_values(&mut self) -> &mut ::std::collections::HashMap<::std::string::String, BoolMapPB> {
+        &mut self.values
+    }
+
+    // Take field
+    pub fn take_values(&mut self) -> ::std::collections::HashMap<::std::string::String, BoolMapPB> {
+        ::std::mem::replace(&mut self.values, ::std::collections::HashMap::new())
+    }
+}
+
+impl ::protobuf::Message for ModeBoolMapPB {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_map_into::<::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeMessage<BoolMapPB>>(wire_type, is, &mut self.values)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        my_size += ::protobuf::rt::compute_map_size::<::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeMessage<BoolMapPB>>(1, &self.values);
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        ::protobuf::rt::write_map_with_cached_sizes::<::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeMessage<BoolMapPB>>(1, &self.values, os)?;
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> ModeBoolMapPB {
+        ModeBoolMapPB::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_map_accessor::<_, ::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeMessage<BoolMapPB>>(
+                "values",
+                |m: &ModeBoolMapPB| { &m.values },
+                |m: &mut ModeBoolMapPB| { &mut m.values },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<ModeBoolMapPB>(
+                "ModeBoolMapPB",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static ModeBoolMapPB {
+        static instance: ::protobuf::rt::LazyV2<ModeBoolMapPB> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(ModeBoolMapPB::new)
+    }
+}
+
+impl ::protobuf::Clear for ModeBoolMapPB {
+    fn clear(&mut self) {
+        self.values.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for ModeBoolMapPB {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for ModeBoolMapPB {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct SettingMapPB {
+    // message fields
+    pub values: ::std::collections::HashMap<::std::string::String, TimeDependantSettingPB>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a SettingMapPB {
+    fn default() -> &'a SettingMapPB {
+        <SettingMapPB as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl SettingMapPB {
+    pub fn new() -> SettingMapPB {
+        ::std::default::Default::default()
+    }
+
+    // repeated .matrix.SettingMapPB.values_MapEntry values = 1;
+
+
+    pub fn get_values(&self) -> &::std::collections::HashMap<::std::string::String, TimeDependantSettingPB> {
+        &self.values
+    }
+    pub fn clear_values(&mut self) {
+        self.values.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_values(&mut self, v: ::std::collections::HashMap<::std::string::String, TimeDependantSettingPB>) {
+        self.values = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_values(&mut self) -> &mut ::std::collections::HashMap<::std::string::String, TimeDependantSettingPB> {
+        &mut self.values
+    }
+
+    // Take field
+    pub fn take_values(&mut self) -> ::std::collections::HashMap<::std::string::String, TimeDependantSettingPB> {
+        ::std::mem::replace(&mut self.values, ::std::collections::HashMap::new())
+    }
+}
+
+impl ::protobuf::Message for SettingMapPB {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_map_into::<::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeMessage<TimeDependantSettingPB>>(wire_type, is, &mut self.values)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        my_size += ::protobuf::rt::compute_map_size::<::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeMessage<TimeDependantSettingPB>>(1, &self.values);
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        ::protobuf::rt::write_map_with_cached_sizes::<::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeMessage<TimeDependantSettingPB>>(1, &self.values, os)?;
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> SettingMapPB {
+        SettingMapPB::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_map_accessor::<_, ::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeMessage<TimeDependantSettingPB>>(
+                "values",
+                |m: &SettingMapPB| { &m.values },
+                |m: &mut SettingMapPB| { &mut m.values },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<SettingMapPB>(
+                "SettingMapPB",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static SettingMapPB {
+        static instance: ::protobuf::rt::LazyV2<SettingMapPB> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(SettingMapPB::new)
+    }
+}
+
+impl ::protobuf::Clear for SettingMapPB {
+    fn clear(&mut self) {
+        self.values.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for SettingMapPB {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for SettingMapPB {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct ModeSettingMapPB {
+    // message fields
+    pub values: ::std::collections::HashMap<::std::string::String, SettingMapPB>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a ModeSettingMapPB {
+    fn default() -> &'a ModeSettingMapPB {
+        <ModeSettingMapPB as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl ModeSettingMapPB {
+    pub fn new() -> ModeSettingMapPB {
+        ::std::default::Default::default()
+    }
+
+    // repeated .matrix.ModeSettingMapPB.values_MapEntry values = 1;
+
+
+    pub fn get_values(&self) -> &::std::collections::HashMap<::std::string::String, SettingMapPB> {
+        &self.values
+    }
+    pub fn clear_values(&mut self) {
+        self.values.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_values(&mut self, v: ::std::collections::HashMap<::std::string::String, SettingMapPB>) {
+        self.values = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_values(&mut self) -> &mut ::std::collections::HashMap<::std::string::String, SettingMapPB> {
+        &mut self.values
+    }
+
+    // Take field
+    pub fn take_values(&mut self) -> ::std::collections::HashMap<::std::string::String, SettingMapPB> {
+        ::std::mem::replace(&mut self.values, ::std::collections::HashMap::new())
+    }
+}
+
+impl ::protobuf::Message for ModeSettingMapPB {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_map_into::<::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeMessage<SettingMapPB>>(wire_type, is, &mut self.values)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        my_size += ::protobuf::rt::compute_map_size::<::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeMessage<SettingMapPB>>(1, &self.values);
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        ::protobuf::rt::write_map_with_cached_sizes::<::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeMessage<SettingMapPB>>(1, &self.values, os)?;
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> ModeSettingMapPB {
+        ModeSettingMapPB::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_map_accessor::<_, ::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeMessage<SettingMapPB>>(
+                "values",
+                |m: &ModeSettingMapPB| { &m.values },
+                |m: &mut ModeSettingMapPB| { &mut m.values },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<ModeSettingMapPB>(
+                "ModeSettingMapPB",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static ModeSettingMapPB {
+        static instance: ::protobuf::rt::LazyV2<ModeSettingMapPB> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(ModeSettingMapPB::new)
+    }
+}
+
+impl ::protobuf::Clear for ModeSettingMapPB {
+    fn clear(&mut self) {
+        self.values.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for ModeSettingMapPB {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for ModeSettingMapPB {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct DaysAheadSlotSettingPB {
+    // message fields
+    id: ::protobuf::SingularField<::std::string::String>,
+    pub range: ::std::vec::Vec<u32>,
+    pub range_minutes: ::std::vec::Vec<u32>,
+    pub range_times: ::std::vec::Vec<u32>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a DaysAheadSlotSettingPB {
+    fn default() -> &'a DaysAheadSlotSettingPB {
+        <DaysAheadSlotSettingPB as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl DaysAheadSlotSettingPB {
+    pub fn new() -> DaysAheadSlotSettingPB {
+        ::std::default::Default::default()
+    }
+
+    // optional string id = 1;
+
+
+    pub fn get_id(&self) -> &str {
+        match self.id.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
+    pub fn clear_id(&mut self) {
+        self.id.clear();
+    }
+
+    pub fn has_id(&self) -> bool {
+        self.id.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_id(&mut self, v: ::std::string::String) {
+        self.id = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_id(&mut self) -> &mut ::std::string::String {
+        if self.id.is_none() {
+            self.id.set_default();
+        }
+        self.id.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_id(&mut self) -> ::std::string::String {
+        self.id.take().unwrap_or_else(|| ::std::string::String::new())
+    }
+
+    // repeated uint32 range = 2;
+
+
+    pub fn get_range(&self) -> &[u32] {
+        &self.range
+    }
+    pub fn clear_range(&mut self) {
+        self.range.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_range(&mut self, v: ::std::vec::Vec<u32>) {
+        self.range = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_range(&mut self) -> &mut ::std::vec::Vec<u32> {
+        &mut self.range
+    }
+
+    // Take field
+    pub fn take_range(&mut self) -> ::std::vec::Vec<u32> {
+        ::std::mem::replace(&mut self.range, ::std::vec::Vec::new())
+    }
+
+    // repeated uint32 range_minutes = 3;
+
+
+    pub fn get_range_minutes(&self) -> &[u32] {
+        &self.range_minutes
+    }
+    pub fn clear_range_minutes(&mut self) {
+        self.range_minutes.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_range_minutes(&mut self, v: ::std::vec::Vec<u32>) {
+        self.range_minutes = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_range_minutes(&mut self) -> &mut ::std::vec::Vec<u32> {
+        &mut self.range_minutes
+    }
+
+    // Take field
+    pub fn take_range_minutes(&mut self) -> ::std::vec::Vec<u32> {
+        ::std::mem::replace(&mut self.range_minutes, ::std::vec::Vec::new())
+    }
+
+    // repeated uint32 range_times = 4;
+
+
+    pub fn get_range_times(&self) -> &[u32] {
+        &self.range_times
+    }
+    pub fn clear_range_times(&mut self) {
+        self.range_times.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_range_times(&mut self, v: ::std::vec::Vec<u32>) {
+        self.range_times = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_range_times(&mut self) -> &mut ::std::vec::Vec<u32> {
+        &mut self.range_times
+    }
+
+    // Take field
+    pub fn take_range_times(&mut self) -> ::std::vec::Vec<u32> {
+        ::std::mem::replace(&mut self.range_times, ::std::vec::Vec::new())
+    }
+}
+
+impl ::protobuf::Message for DaysAheadSlotSettingPB {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.id)?;
+                },
+                2 => {
+                    ::protobuf::rt::read_repeated_uint32_into(wire_type, is, &mut self.range)?;
+                },
+                3 => {
+                    ::protobuf::rt::read_repeated_uint32_into(wire_type, is, &mut self.range_minutes)?;
+                },
+                4 => {
+                    ::protobuf::rt::read_repeated_uint32_into(wire_type, is, &mut self.range_times)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let Some(ref v) = self.id.as_ref() {
+            my_size += ::protobuf::rt::string_size(1, &v);
+        }
+        for value in &self.range {
+            my_size += ::protobuf::rt::value_size(2, *value, ::protobuf::wire_format::WireTypeVarint);
+        };
+        for value in &self.range_minutes {
+            my_size += ::protobuf::rt::value_size(3, *value, ::protobuf::wire_format::WireTypeVarint);
+        };
+        for value in &self.range_times {
+            my_size += ::protobuf::rt::value_size(4, *value, ::protobuf::wire_format::WireTypeVarint);
+        };
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let Some(ref v) = self.id.as_ref() {
+            os.write_string(1, &v)?;
+        }
+        for v in &self.range {
+            os.write_uint32(2, *v)?;
+        };
+        for v in &self.range_minutes {
+            os.write_uint32(3, *v)?;
+        };
+        for v in &self.range_times {
+            os.write_uint32(4, *v)?;
+        };
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> DaysAheadSlotSettingPB {
+        DaysAheadSlotSettingPB::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_singular_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "id",
+                |m: &DaysAheadSlotSettingPB| { &m.id },
+                |m: &mut DaysAheadSlotSettingPB| { &mut m.id },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_vec_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "range",
+                |m: &DaysAheadSlotSettingPB| { &m.range },
+                |m: &mut DaysAheadSlotSettingPB| { &mut m.range },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_vec_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "range_minutes",
+                |m: &DaysAheadSlotSettingPB| { &m.range_minutes },
+                |m: &mut DaysAheadSlotSettingPB| { &mut m.range_minutes },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_vec_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "range_times",
+                |m: &DaysAheadSlotSettingPB| { &m.range_times },
+                |m: &mut DaysAheadSlotSettingPB| { &mut m.range_times },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<DaysAheadSlotSettingPB>(
+                "DaysAheadSlotSettingPB",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static DaysAheadSlotSettingPB {
+        static instance: ::protobuf::rt::LazyV2<DaysAheadSlotSettingPB> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(DaysAheadSlotSettingPB::new)
+    }
+}
+
+impl ::protobuf::Clear for DaysAheadSlotSettingPB {
+    fn clear(&mut self) {
+        self.id.clear();
+        self.range.clear();
+        self.range_minutes.clear();
+        self.range_times.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for DaysAheadSlotSettingPB {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for DaysAheadSlotSettingPB {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct DaysAheadDaySettingPB {
+    // message fields
+    prefix: ::protobuf::SingularField<::std::string::String>,
+    pub slots: ::protobuf::RepeatedField<DaysAheadSlotSettingPB>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a DaysAheadDaySettingPB {
+    fn default() -> &'a DaysAheadDaySettingPB {
+        <DaysAheadDaySettingPB as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl DaysAheadDaySettingPB {
+    pub fn new() -> DaysAheadDaySettingPB {
+        ::std::default::Default::default()
+    }
+
+    // optional string prefix = 1;
+
+
+    pub fn get_prefix(&self) -> &str {
+        match self.prefix.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
+    pub fn clear_prefix(&mut self) {
+        self.prefix.clear();
+    }
+
+    pub fn has_prefix(&self) -> bool {
+        self.prefix.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_prefix(&mut self, v: ::std::string::String) {
+        self.prefix = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_prefix(&mut self) -> &mut ::std::string::String {
+        if self.prefix.is_none() {
+            self.prefix.set_default();
+        }
+        self.prefix.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_prefix(&mut self) -> ::std::string::String {
+        self.prefix.take().unwrap_or_else(|| ::std::string::String::new())
+    }
+
+    // repeated .matrix.DaysAheadSlotSettingPB slots = 2;
+
+
+    pub fn get_slots(&self) -> &[DaysAheadSlotSettingPB] {
+        &self.slots
+    }
+    pub fn clear_slots(&mut self) {
+        self.slots.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_slots(&mut self, v: ::protobuf::RepeatedField<DaysAheadSlotSettingPB>) {
+        self.slots = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_slots(&mut self) -> &mut ::protobuf::RepeatedField<DaysAheadSlotSettingPB> {
+        &mut self.slots
+    }
+
+    // Take field
+    pub fn take_slots(&mut self) -> ::protobuf::RepeatedField<DaysAheadSlotSettingPB> {
+        ::std::mem::replace(&mut self.slots, ::protobuf::RepeatedField::new())
+    }
+}
+
+impl ::protobuf::Message for DaysAheadDaySettingPB {
+    fn is_initialized(&self) -> bool {
+        for v in &self.slots {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.prefix)?;
+                },
+                2 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.slots)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let Some(ref v) = self.prefix.as_ref() {
+            my_size += ::protobuf::rt::string_size(1, &v);
+        }
+        for value in &self.slots {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let Some(ref v) = self.prefix.as_ref() {
+            os.write_string(1, &v)?;
+        }
+        for v in &self.slots {
+            os.write_tag(2, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> DaysAheadDaySettingPB {
+        DaysAheadDaySettingPB::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_singular_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "prefix",
+                |m: &DaysAheadDaySettingPB| { &m.prefix },
+                |m: &mut DaysAheadDaySettingPB| { &mut m.prefix },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<DaysAheadSlotSettingPB>>(
+                "slots",
+                |m: &DaysAheadDaySettingPB| { &m.slots },
+                |m: &mut DaysAheadDaySettingPB| { &mut m.slots },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<DaysAheadDaySettingPB>(
+                "DaysAheadDaySettingPB",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static DaysAheadDaySettingPB {
+        static instance: ::protobuf::rt::LazyV2<DaysAheadDaySettingPB> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(DaysAheadDaySettingPB::new)
+    }
+}
+
+impl ::protobuf::Clear for DaysAheadDaySettingPB {
+    fn clear(&mut self) {
+        self.prefix.clear();
+        self.slots.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for DaysAheadDaySettingPB {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for DaysAheadDaySettingPB {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct DaysAheadSettingPB {
+    // message fields
+    timezone: ::std::option::Option<f64>,
+    pub days: ::protobuf::RepeatedField<DaysAheadDaySettingPB>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a DaysAheadSettingPB {
+    fn default() -> &'a DaysAheadSettingPB {
+        <DaysAheadSettingPB as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl DaysAheadSettingPB {
+    pub fn new() -> DaysAheadSettingPB {
+        ::std::default::Default::default()
+    }
+
+    // optional double timezone = 1;
+
+
+    pub fn get_timezone(&self) -> f64 {
+        self.timezone.unwrap_or(0.)
+    }
+    pub fn clear_timezone(&mut self) {
+        self.timezone = ::std::option::Option::None;
+    }
+
+    pub fn has_timezone(&self) -> bool {
+        self.timezone.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_timezone(&mut self, v: f64) {
+        self.timezone = ::std::option::Option::Some(v);
+    }
+
+    // repeated .matrix.DaysAheadDaySettingPB days = 2;
+
+
+    pub fn get_days(&self) -> &[DaysAheadDaySettingPB] {
+        &self.days
+    }
+    pub fn clear_days(&mut self) {
+        self.days.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_days(&mut self, v: ::protobuf::RepeatedField<DaysAheadDaySettingPB>) {
+        self.days = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_days(&mut self) -> &mut ::protobuf::RepeatedField<DaysAheadDaySettingPB> {
+        &mut self.days
+    }
+
+    // Take field
+    pub fn take_days(&mut self) -> ::protobuf::RepeatedField<DaysAheadDaySettingPB> {
+        ::std::mem::replace(&mut self.days, ::protobuf::RepeatedField::new())
+    }
+}
+
+impl ::protobuf::Message for DaysAheadSettingPB {
+    fn is_initialized(&self) -> bool {
+        for v in &self.days {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeFixed64 {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_double()?;
+                    self.timezone = ::std::option::Option::Some(tmp);
+                },
+                2 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.days)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let Some(v) = self.timezone {
+            my_size += 9;
+        }
+        for value in &self.days {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let Some(v) = self.timezone {
+            os.write_double(1, v)?;
+        }
+        for v in &self.days {
+            os.write_tag(2, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> DaysAheadSettingPB {
+        DaysAheadSettingPB::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeDouble>(
+                "timezone",
+                |m: &DaysAheadSettingPB| { &m.timezone },
+                |m: &mut DaysAheadSettingPB| { &mut m.timezone },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<DaysAheadDaySettingPB>>(
+                "days",
+                |m: &DaysAheadSettingPB| { &m.days },
+                |m: &mut DaysAheadSettingPB| { &mut m.days },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<DaysAheadSettingPB>(
+                "DaysAheadSettingPB",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static DaysAheadSettingPB {
+        static instance: ::protobuf::rt::LazyV2<DaysAheadSettingPB> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(DaysAheadSettingPB::new)
+    }
+}
+
+impl ::protobuf::Clear for DaysAheadSettingPB {
+    fn clear(&mut self) {
+        self.timezone = ::std::option::Option::None;
+        self.days.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for DaysAheadSettingPB {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for DaysAheadSettingPB {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct RecurringDayDefinitionPB {
+    // message fields
+    day_type: ::protobuf::SingularField<::std::string::String>,
+    pub date_value: ::protobuf::SingularPtrField<StringListPB>,
+    pub weekday_value: ::protobuf::SingularPtrField<UInt32ListPB>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a RecurringDayDefinitionPB {
+    fn default() -> &'a RecurringDayDefinitionPB {
+        <RecurringDayDefinitionPB as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl RecurringDayDefinitionPB {
+    pub fn new() -> RecurringDayDefinitionPB {
+        ::std::default::Default::default()
+    }
+
+    // optional string day_type = 1;
+
+
+    pub fn get_day_type(&self) -> &str {
+        match self.day_type.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
+    pub fn clear_day_type(&mut self) {
+        self.day_type.clear();
+    }
+
+    pub fn has_day_type(&self) -> bool {
+        self.day_type.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_day_type(&mut self, v: ::std::string::String) {
+        self.day_type = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_day_type(&mut self) -> &mut ::std::string::String {
+        if self.day_type.is_none() {
+            self.day_type.set_default();
+        }
+        self.day_type.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_day_type(&mut self) -> ::std::string::String {
+        self.day_type.take().unwrap_or_else(|| ::std::string::String::new())
+    }
+
+    // optional .matrix.StringListPB date_value = 2;
+
+
+    pub fn get_date_value(&self) -> &StringListPB {
+        self.date_value.as_ref().unwrap_or_else(|| <StringListPB as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_date_value(&mut self) {
+        self.date_value.clear();
+    }
+
+    pub fn has_date_value(&self) -> bool {
+        self.date_value.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_date_value(&mut self, v: StringListPB) {
+        self.date_value = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_date_value(&mut self) -> &mut StringListPB {
+        if self.date_value.is_none() {
+            self.date_value.set_default();
+        }
+        self.date_value.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_date_value(&mut self) -> StringListPB {
+        self.date_value.take().unwrap_or_else(|| StringListPB::new())
+    }
+
+    // optional .matrix.UInt32ListPB weekday_value = 3;
+
+
+    pub fn get_weekday_value(&self) -> &UInt32ListPB {
+        self.weekday_value.as_ref().unwrap_or_else(|| <UInt32ListPB as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_weekday_value(&mut self) {
+        self.weekday_value.clear();
+    }
+
+    pub fn has_weekday_value(&self) -> bool {
+        self.weekday_value.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_weekday_value(&mut self, v: UInt32ListPB) {
+        self.weekday_value = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_weekday_value(&mut self) -> &mut UInt32ListPB {
+        if self.weekday_value.is_none() {
+            self.weekday_value.set_default();
+        }
+        self.weekday_value.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_weekday_value(&mut self) -> UInt32ListPB {
+        self.weekday_value.take().unwrap_or_else(|| UInt32ListPB::new())
+    }
+}
+
+impl ::protobuf::Message for RecurringDayDefinitionPB {
+    fn is_initialized(&self) -> bool {
+        for v in &self.date_value {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.weekday_value {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.day_type)?;
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.date_value)?;
+                },
+                3 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.weekday_value)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let Some(ref v) = self.day_type.as_ref() {
+            my_size += ::protobuf::rt::string_size(1, &v);
+        }
+        if let Some(ref v) = self.date_value.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        if let Some(ref v) = self.weekday_value.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let Some(ref v) = self.day_type.as_ref() {
+            os.write_string(1, &v)?;
+        }
+        if let Some(ref v) = self.date_value.as_ref() {
+            os.write_tag(2, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        if let Some(ref v) = self.weekday_value.as_ref() {
+            os.write_tag(3, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> RecurringDayDefinitionPB {
+        RecurringDayDefinitionPB::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_singular_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "day_type",
+                |m: &RecurringDayDefinitionPB| { &m.day_type },
+                |m: &mut RecurringDayDefinitionPB| { &mut m.day_type },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<StringListPB>>(
+                "date_value",
+                |m: &RecurringDayDefinitionPB| { &m.date_value },
+                |m: &mut RecurringDayDefinitionPB| { &mut m.date_value },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<UInt32ListPB>>(
+                "weekday_value",
+                |m: &RecurringDayDefinitionPB| { &m.weekday_value },
+                |m: &mut RecurringDayDefinitionPB| { &mut m.weekday_value },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<RecurringDayDefinitionPB>(
+                "RecurringDayDefinitionPB",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static RecurringDayDefinitionPB {
+        static instance: ::protobuf::rt::LazyV2<RecurringDayDefinitionPB> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(RecurringDayDefinitionPB::new)
+    }
+}
+
+impl ::protobuf::Clear for RecurringDayDefinitionPB {
+    fn clear(&mut self) {
+        self.day_type.clear();
+        self.date_value.clear();
+        self.weekday_value.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for RecurringDayDefinitionPB {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for RecurringDayDefinitionPB {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct RecurringDaySettingPB {
+    // message fields
+    name: ::protobuf::SingularField<::std::string::String>,
+    prefix: ::protobuf::SingularField<::std::string::String>,
+    pub days: ::protobuf::RepeatedField<RecurringDayDefinitionPB>,
+    pub slots: ::protobuf::RepeatedField<DaysAheadSlotSettingPB>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a RecurringDaySettingPB {
+    fn default() -> &'a RecurringDaySettingPB {
+        <RecurringDaySettingPB as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl RecurringDaySettingPB {
+    pub fn new() -> RecurringDaySettingPB {
+        ::std::default::Default::default()
+    }
+
+    // optional string name = 1;
+
+
+    pub fn get_name(&self) -> &str {
+        match self.name.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
+    pub fn clear_name(&mut self) {
+        self.name.clear();
+    }
+
+    pub fn has_name(&self) -> bool {
+        self.name.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_name(&mut self, v: ::std::string::String) {
+        self.name = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_name(&mut self) -> &mut ::std::string::String {
+        if self.name.is_none() {
+            self.name.set_default();
+        }
+        self.name.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_name(&mut self) -> ::std::string::String {
+        self.name.take().unwrap_or_else(|| ::std::string::String::new())
+    }
+
+    // optional string prefix = 2;
+
+
+    pub fn get_prefix(&self) -> &str {
+        match self.prefix.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
+    pub fn clear_prefix(&mut self) {
+        self.prefix.clear();
+    }
+
+    pub fn has_prefix(&self) -> bool {
+        self.prefix.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_prefix(&mut self, v: ::std::string::String) {
+        self.prefix = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_prefix(&mut self) -> &mut ::std::string::String {
+        if self.prefix.is_none() {
+            self.prefix.set_default();
+        }
+        self.prefix.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_prefix(&mut self) -> ::std::string::String {
+        self.prefix.take().unwrap_or_else(|| ::std::string::String::new())
+    }
+
+    // repeated .matrix.RecurringDayDefinitionPB days = 3;
+
+
+    pub fn get_days(&self) -> &[RecurringDayDefinitionPB] {
+        &self.days
+    }
+    pub fn clear_days(&mut self) {
+        self.days.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_days(&mut self, v: ::protobuf::RepeatedField<RecurringDayDefinitionPB>) {
+        self.days = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_days(&mut self) -> &mut ::protobuf::RepeatedField<RecurringDayDefinitionPB> {
+        &mut self.days
+    }
+
+    // Take field
+    pub fn take_days(&mut self) -> ::protobuf::RepeatedField<RecurringDayDefinitionPB> {
+        ::std::mem::replace(&mut self.days, ::protobuf::RepeatedField::new())
+    }
+
+    // repeated .matrix.DaysAheadSlotSettingPB slots = 4;
+
+
+    pub fn get_slots(&self) -> &[DaysAheadSlotSettingPB] {
+        &self.slots
+    }
+    pub fn clear_slots(&mut self) {
+        self.slots.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_slots(&mut self, v: ::protobuf::RepeatedField<DaysAheadSlotSettingPB>) {
+        self.slots = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_slots(&mut self) -> &mut ::protobuf::RepeatedField<DaysAheadSlotSettingPB> {
+        &mut self.slots
+    }
+
+    // Take field
+    pub fn take_slots(&mut self) -> ::protobuf::RepeatedField<DaysAheadSlotSettingPB> {
+        ::std::mem::replace(&mut self.slots, ::protobuf::RepeatedField::new())
+    }
+}
+
+impl ::protobuf::Message for RecurringDaySettingPB {
+    fn is_initialized(&self) -> bool {
+        for v in &self.days {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.slots {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.name)?;
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.prefix)?;
+                },
+                3 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.days)?;
+                },
+                4 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.slots)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let Some(ref v) = self.name.as_ref() {
+            my_size += ::protobuf::rt::string_size(1, &v);
+        }
+        if let Some(ref v) = self.prefix.as_ref() {
+            my_size += ::protobuf::rt::string_size(2, &v);
+        }
+        for value in &self.days {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        for value in &self.slots {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let Some(ref v) = self.name.as_ref() {
+            os.write_string(1, &v)?;
+        }
+        if let Some(ref v) = self.prefix.as_ref() {
+            os.write_string(2, &v)?;
+        }
+        for v in &self.days {
+            os.write_tag(3, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        for v in &self.slots {
+            os.write_tag(4, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> RecurringDaySettingPB {
+        RecurringDaySettingPB::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_singular_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "name",
+                |m: &RecurringDaySettingPB| { &m.name },
+                |m: &mut RecurringDaySettingPB| { &mut m.name },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "prefix",
+                |m: &RecurringDaySettingPB| { &m.prefix },
+                |m: &mut RecurringDaySettingPB| { &mut m.prefix },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<RecurringDayDefinitionPB>>(
+                "days",
+                |m: &RecurringDaySettingPB| { &m.days },
+                |m: &mut RecurringDaySettingPB| { &mut m.days },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<DaysAheadSlotSettingPB>>(
+                "slots",
+                |m: &RecurringDaySettingPB| { &m.slots },
+                |m: &mut RecurringDaySettingPB| { &mut m.slots },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<RecurringDaySettingPB>(
+                "RecurringDaySettingPB",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static RecurringDaySettingPB {
+        static instance: ::protobuf::rt::LazyV2<RecurringDaySettingPB> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(RecurringDaySettingPB::new)
+    }
+}
+
+impl ::protobuf::Clear for RecurringDaySettingPB {
+    fn clear(&mut self) {
+        self.name.clear();
+        self.prefix.clear();
+        self.days.clear();
+        self.slots.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for RecurringDaySettingPB {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for RecurringDaySettingPB {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct RecurringSettingPB {
+    // message fields
+    timezone: ::std::option::Option<f64>,
+    pub days: ::protobuf::RepeatedField<RecurringDaySettingPB>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a RecurringSettingPB {
+    fn default() -> &'a RecurringSettingPB {
+        <RecurringSettingPB as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl RecurringSettingPB {
+    pub fn new() -> RecurringSettingPB {
+        ::std::default::Default::default()
+    }
+
+    // optional double timezone = 1;
+
+
+    pub fn get_timezone(&self) -> f64 {
+        self.timezone.unwrap_or(0.)
+    }
+    pub fn clear_timezone(&mut self) {
+        self.timezone = ::std::option::Option::None;
+    }
+
+    pub fn has_timezone(&self) -> bool {
+        self.timezone.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_timezone(&mut self, v: f64) {
+        self.timezone = ::std::option::Option::Some(v);
+    }
+
+    // repeated .matrix.RecurringDaySettingPB days = 2;
+
+
+    pub fn get_days(&self) -> &[RecurringDaySettingPB] {
+        &self.days
+    }
+    pub fn clear_days(&mut self) {
+        self.days.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_days(&mut self, v: ::protobuf::RepeatedField<RecurringDaySettingPB>) {
+        self.days = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_days(&mut self) -> &mut ::protobuf::RepeatedField<RecurringDaySettingPB> {
+        &mut self.days
+    }
+
+    // Take field
+    pub fn take_days(&mut self) -> ::protobuf::RepeatedField<RecurringDaySettingPB> {
+        ::std::mem::replace(&mut self.days, ::protobuf::RepeatedField::new())
+    }
+}
+
+impl ::protobuf::Message for RecurringSettingPB {
+    fn is_initialized(&self) -> bool {
+        for v in &self.days {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeFixed64 {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_double()?;
+                    self.timezone = ::std::option::Option::Some(tmp);
+                },
+                2 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.days)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let Some(v) = self.timezone {
+            my_size += 9;
+        }
+        for value in &self.days {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let Some(v) = self.timezone {
+            os.write_double(1, v)?;
+        }
+        for v in &self.days {
+            os.write_tag(2, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> RecurringSettingPB {
+        RecurringSettingPB::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeDouble>(
+                "timezone",
+                |m: &RecurringSettingPB| { &m.timezone },
+                |m: &mut RecurringSettingPB| { &mut m.timezone },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<RecurringDaySettingPB>>(
+                "days",
+                |m: &RecurringSettingPB| { &m.days },
+                |m: &mut RecurringSettingPB| { &mut m.days },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<RecurringSettingPB>(
+                "RecurringSettingPB",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static RecurringSettingPB {
+        static instance: ::protobuf::rt::LazyV2<RecurringSettingPB> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(RecurringSettingPB::new)
+    }
+}
+
+impl ::protobuf::Clear for RecurringSettingPB {
+    fn clear(&mut self) {
+        self.timezone = ::std::option::Option::None;
+        self.days.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for RecurringSettingPB {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for RecurringSettingPB {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct TimeDependantSettingPB {
+    // message fields
+    setting_type: ::protobuf::SingularField<::std::string::String>,
+    pub days_ahead_setting: ::protobuf::SingularPtrField<DaysAheadSettingPB>,
+    pub recurring_setting: ::protobuf::SingularPtrField<RecurringSettingPB>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a TimeDependantSettingPB {
+    fn default() -> &'a TimeDependantSettingPB {
+        <TimeDependantSettingPB as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl TimeDependantSettingPB {
+    pub fn new() -> TimeDependantSettingPB {
+        ::std::default::Default::default()
+    }
+
+    // optional string setting_type = 1;
+
+
+    pub fn get_setting_type(&self) -> &str {
+        match self.setting_type.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
+    pub fn clear_setting_type(&mut self) {
+        self.setting_type.clear();
+    }
+
+    pub fn has_setting_type(&self) -> bool {
+        self.setting_type.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_setting_type(&mut self, v: ::std::string::String) {
+        self.setting_type = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_setting_type(&mut self) -> &mut ::std::string::String {
+        if self.setting_type.is_none() {
+            self.setting_type.set_default();
+        }
+        self.setting_type.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_setting_type(&mut self) -> ::std::string::String {
+        self.setting_type.take().unwrap_or_else(|| ::std::string::String::new())
+    }
+
+    // optional .matrix.DaysAheadSettingPB days_ahead_setting = 2;
+
+
+    pub fn get_days_ahead_setting(&self) -> &DaysAheadSettingPB {
+        self.days_ahead_setting.as_ref().unwrap_or_else(|| <DaysAheadSettingPB as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_days_ahead_setting(&mut self) {
+        self.days_ahead_setting.clear();
+    }
+
+    pub fn has_days_ahead_setting(&self) -> bool {
+        self.days_ahead_setting.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_days_ahead_setting(&mut self, v: DaysAheadSettingPB) {
+        self.days_ahead_setting = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_days_ahead_setting(&mut self) -> &mut DaysAheadSettingPB {
+        if self.days_ahead_setting.is_none() {
+            self.days_ahead_setting.set_default();
+        }
+        self.days_ahead_setting.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_days_ahead_setting(&mut self) -> DaysAheadSettingPB {
+        self.days_ahead_setting.take().unwrap_or_else(|| DaysAheadSettingPB::new())
+    }
+
+    // optional .matrix.RecurringSettingPB recurring_setting = 3;
+
+
+    pub fn get_recurring_setting(&self) -> &RecurringSettingPB {
+        self.recurring_setting.as_ref().unwrap_or_else(|| <RecurringSettingPB as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_recurring_setting(&mut self) {
+        self.recurring_setting.clear();
+    }
+
+    pub fn has_recurring_setting(&self) -> bool {
+        self.recurring_setting.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_recurring_setting(&mut self, v: RecurringSettingPB) {
+        self.recurring_setting = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_recurring_setting(&mut self) -> &mut RecurringSettingPB {
+        if self.recurring_setting.is_none() {
+            self.recurring_setting.set_default();
+        }
+        self.recurring_setting.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_recurring_setting(&mut self) -> RecurringSettingPB {
+        self.recurring_setting.take().unwrap_or_else(|| RecurringSettingPB::new())
+    }
+}
+
+impl ::protobuf::Message for TimeDependantSettingPB {
+    fn is_initialized(&self) -> bool {
+        for v in &self.days_ahead_setting {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.recurring_setting {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.setting_type)?;
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.days_ahead_setting)?;
+                },
+                3 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.recurring_setting)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let Some(ref v) = self.setting_type.as_ref() {
+            my_size += ::protobuf::rt::string_size(1, &v);
+        }
+        if let Some(ref v) = self.days_ahead_setting.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        if let Some(ref v) = self.recurring_setting.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let Some(ref v) = self.setting_type.as_ref() {
+            os.write_string(1, &v)?;
+        }
+        if let Some(ref v) = self.days_ahead_setting.as_ref() {
+            os.write_tag(2, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        if let Some(ref v) = self.recurring_setting.as_ref() {
+            os.write_tag(3, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> TimeDependantSettingPB {
+        TimeDependantSettingPB::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_singular_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "setting_type",
+                |m: &TimeDependantSettingPB| { &m.setting_type },
+                |m: &mut TimeDependantSettingPB| { &mut m.setting_type },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<DaysAheadSettingPB>>(
+                "days_ahead_setting",
+                |m: &TimeDependantSettingPB| { &m.days_ahead_setting },
+                |m: &mut TimeDependantSettingPB| { &mut m.days_ahead_setting },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<RecurringSettingPB>>(
+                "recurring_setting",
+                |m: &TimeDependantSettingPB| { &m.recurring_setting },
+                |m: &mut TimeDependantSettingPB| { &mut m.recurring_setting },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<TimeDependantSettingPB>(
+                "TimeDependantSettingPB",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static TimeDependantSettingPB {
+        static instance: ::protobuf::rt::LazyV2<TimeDependantSettingPB> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(TimeDependantSettingPB::new)
+    }
+}
+
+impl ::protobuf::Clear for TimeDependantSettingPB {
+    fn clear(&mut self) {
+        self.setting_type.clear();
+        self.days_ahead_setting.clear();
+        self.recurring_setting.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for TimeDependantSettingPB {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for TimeDependantSettingPB {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct AreaFlexiblePB {
+    // message fields
+    default_service: ::protobuf::SingularField<::std::string::String>,
+    pub mappings: ::std::collections::HashMap<::std::string::String, ::std::string::String>,
+    pub allowed_context: ::protobuf::SingularPtrField<StringListMapPB>,
+    pub time_dependant: ::protobuf::SingularPtrField<ModeBoolMapPB>,
+    pub time_dependant_settings: ::protobuf::SingularPtrField<ModeSettingMapPB>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a AreaFlexiblePB {
+    fn default() -> &'a AreaFlexiblePB {
+        <AreaFlexiblePB as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl AreaFlexiblePB {
+    pub fn new() -> AreaFlexiblePB {
+        ::std::default::Default::default()
+    }
+
+    // optional string default_service = 1;
+
+
+    pub fn get_default_service(&self) -> &str {
+        match self.default_service.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
+    pub fn clear_default_service(&mut self) {
+        self.default_service.clear();
+    }
+
+    pub fn has_default_service(&self) -> bool {
+        self.default_service.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_default_service(&mut self, v: ::std::string::String) {
+        self.default_service = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_default_service(&mut self) -> &mut ::std::string::String {
+        if self.default_service.is_none() {
+            self.default_service.set_default();
+        }
+        self.default_service.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_default_service(&mut self) -> ::std::string::String {
+        self.default_service.take().unwrap_or_else(|| ::std::string::String::new())
+    }
+
+    // repeated .matrix.AreaFlexiblePB.mappings_MapEntry mappings = 2;
+
+
+    pub fn get_mappings(&self) -> &::std::collections::HashMap<::std::string::String, ::std::string::String> {
+        &self.mappings
+    }
+    pub fn clear_mappings(&mut self) {
+        self.mappings.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_mappings(&mut self, v: ::std::collections::HashMap<::std::string::String, ::std::string::String>) {
+        self.mappings = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_mappings(&mut self) -> &mut ::std::collections::HashMap<::std::string::String, ::std::string::String> {
+        &mut self.mappings
+    }
+
+    // Take field
+    pub fn take_mappings(&mut self) -> ::std::collections::HashMap<::std::string::String, ::std::string::String> {
+        ::std::mem::replace(&mut self.mappings, ::std::collections::HashMap::new())
+    }
+
+    // optional .matrix.StringListMapPB allowed_context = 3;
+
+
+    pub fn get_allowed_context(&self) -> &StringListMapPB {
+        self.allowed_context.as_ref().unwrap_or_else(|| <StringListMapPB as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_allowed_context(&mut self) {
+        self.allowed_context.clear();
+    }
+
+    pub fn has_allowed_context(&self) -> bool {
+        self.allowed_context.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_allowed_context(&mut self, v: StringListMapPB) {
+        self.allowed_context = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_allowed_context(&mut self) -> &mut StringListMapPB {
+        if self.allowed_context.is_none() {
+            self.allowed_context.set_default();
+        }
+        self.allowed_context.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_allowed_context(&mut self) -> StringListMapPB {
+        self.allowed_context.take().unwrap_or_else(|| StringListMapPB::new())
+    }
+
+    // optional .matrix.ModeBoolMapPB time_dependant = 4;
+
+
+    pub fn get_time_dependant(&self) -> &ModeBoolMapPB {
+        self.time_dependant.as_ref().unwrap_or_else(|| <ModeBoolMapPB as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_time_dependant(&mut self) {
+        self.time_dependant.clear();
+    }
+
+    pub fn has_time_dependant(&self) -> bool {
+        self.time_dependant.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_time_dependant(&mut self, v: ModeBoolMapPB) {
+        self.time_dependant = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_time_dependant(&mut self) -> &mut ModeBoolMapPB {
+        if self.time_dependant.is_none() {
+            self.time_dependant.set_default();
+        }
+        self.time_dependant.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_time_dependant(&mut self) -> ModeBoolMapPB {
+        self.time_dependant.take().unwrap_or_else(|| ModeBoolMapPB::new())
+    }
+
+    // optional .matrix.ModeSettingMapPB time_dependant_settings = 5;
+
+
+    pub fn get_time_dependant_settings(&self) -> &ModeSettingMapPB {
+        self.time_dependant_settings.as_ref().unwrap_or_else(|| <ModeSettingMapPB as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_time_dependant_settings(&mut self) {
+        self.time_dependant_settings.clear();
+    }
+
+    pub fn has_time_dependant_settings(&self) -> bool {
+        self.time_dependant_settings.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_time_dependant_settings(&mut self, v: ModeSettingMapPB) {
+        self.time_dependant_settings = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_time_dependant_settings(&mut self) -> &mut ModeSettingMapPB {
+        if self.time_dependant_settings.is_none() {
+            self.time_dependant_settings.set_default();
+        }
+        self.time_dependant_settings.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_time_dependant_settings(&mut self) -> ModeSettingMapPB {
+        self.time_dependant_settings.take().unwrap_or_else(|| ModeSettingMapPB::new())
+    }
+}
+
+impl ::protobuf::Message for AreaFlexiblePB {
+    fn is_initialized(&self) -> bool {
+        for v in &self.allowed_context {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.time_dependant {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.time_dependant_settings {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.default_service)?;
+                },
+                2 => {
+                    ::protobuf::rt::read_map_into::<::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeString>(wire_type, is, &mut self.mappings)?;
+                },
+                3 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.allowed_context)?;
+                },
+                4 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.time_dependant)?;
+                },
+                5 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.time_dependant_settings)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let Some(ref v) = self.default_service.as_ref() {
+            my_size += ::protobuf::rt::string_size(1, &v);
+        }
+        my_size += ::protobuf::rt::compute_map_size::<::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeString>(2, &self.mappings);
+        if let Some(ref v) = self.allowed_context.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        if let Some(ref v) = self.time_dependant.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        if let Some(ref v) = self.time_dependant_settings.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let Some(ref v) = self.default_service.as_ref() {
+            os.write_string(1, &v)?;
+        }
+        ::protobuf::rt::write_map_with_cached_sizes::<::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeString>(2, &self.mappings, os)?;
+        if let Some(ref v) = self.allowed_context.as_ref() {
+            os.write_tag(3, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        if let Some(ref v) = self.time_dependant.as_ref() {
+            os.write_tag(4, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        if let Some(ref v) = self.time_dependant_settings.as_ref() {
+            os.write_tag(5, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> AreaFlexiblePB {
+        AreaFlexiblePB::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_singular_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "default_service",
+                |m: &AreaFlexiblePB| { &m.default_service },
+                |m: &mut AreaFlexiblePB| { &mut m.default_service },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_map_accessor::<_, ::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeString>(
+                "mappings",
+                |m: &AreaFlexiblePB| { &m.mappings },
+                |m: &mut AreaFlexiblePB| { &mut m.mappings },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<StringListMapPB>>(
+                "allowed_context",
+                |m: &AreaFlexiblePB| { &m.allowed_context },
+                |m: &mut AreaFlexiblePB| { &mut m.allowed_context },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<ModeBoolMapPB>>(
+                "time_dependant",
+                |m: &AreaFlexiblePB| { &m.time_dependant },
+                |m: &mut AreaFlexiblePB| { &mut m.time_dependant },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<ModeSettingMapPB>>(
+                "time_dependant_settings",
+                |m: &AreaFlexiblePB| { &m.time_dependant_settings },
+                |m: &mut AreaFlexiblePB| { &mut m.time_dependant_settings },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<AreaFlexiblePB>(
+                "AreaFlexiblePB",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static AreaFlexiblePB {
+        static instance: ::protobuf::rt::LazyV2<AreaFlexiblePB> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(AreaFlexiblePB::new)
+    }
+}
+
+impl ::protobuf::Clear for AreaFlexiblePB {
+    fn clear(&mut self) {
+        self.default_service.clear();
+        self.mappings.clear();
+        self.allowed_context.clear();
+        self.time_dependant.clear();
+        self.time_dependant_settings.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for AreaFlexiblePB {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for AreaFlexiblePB {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct AreaPB {
+    // message fields
+    name: ::protobuf::SingularField<::std::string::String>,
+    default_service: ::protobuf::SingularField<::std::string::String>,
+    pub mappings: ::std::collections::HashMap<::std::string::String, ::std::string::String>,
+    pub allowed_context: ::protobuf::SingularPtrField<StringListMapPB>,
+    pub time_dependant: ::protobuf::SingularPtrField<ModeBoolMapPB>,
+    pub flexible: ::protobuf::SingularPtrField<ModeBoolMapPB>,
+    pub time_dependant_settings: ::protobuf::SingularPtrField<ModeSettingMapPB>,
+    pub flexible_setting: ::protobuf::SingularPtrField<AreaFlexiblePB>,
+    log_coord_precision: ::std::option::Option<u32>,
+    simplify_tolerance_meters: ::std::option::Option<f64>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a AreaPB {
+    fn default() -> &'a AreaPB {
+        <AreaPB as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl AreaPB {
+    pub fn new() -> AreaPB {
+        ::std::default::Default::default()
+    }
+
+    // optional string name = 1;
+
+
+    pub fn get_name(&self) -> &str {
+        match self.name.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
+    pub fn clear_name(&mut self) {
+        self.name.clear();
+    }
+
+    pub fn has_name(&self) -> bool {
+        self.name.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_name(&mut self, v: ::std::string::String) {
+        self.name = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_name(&mut self) -> &mut ::std::string::String {
+        if self.name.is_none() {
+            self.name.set_default();
+        }
+        self.name.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_name(&mut self) -> ::std::string::String {
+        self.name.take().unwrap_or_else(|| ::std::string::String::new())
+    }
+
+    // optional string default_service = 2;
+
+
+    pub fn get_default_service(&self) -> &str {
+        match self.default_service.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
+    pub fn clear_default_service(&mut self) {
+        self.default_service.clear();
+    }
+
+    pub fn has_default_service(&self) -> bool {
+        self.default_service.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_default_service(&mut self, v: ::std::string::String) {
+        self.default_service = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_default_service(&mut self) -> &mut ::std::string::String {
+        if self.default_service.is_none() {
+            self.default_service.set_default();
+        }
+        self.default_service.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_default_service(&mut self) -> ::std::string::String {
+        self.default_service.take().unwrap_or_else(|| ::std::string::String::new())
+    }
+
+    // repeated .matrix.AreaPB.mappings_MapEntry mappings = 3;
+
+
+    pub fn get_mappings(&self) -> &::std::collections::HashMap<::std::string::String, ::std::string::String> {
+        &self.mappings
+    }
+    pub fn clear_mappings(&mut self) {
+        self.mappings.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_mappings(&mut self, v: ::std::collections::HashMap<::std::string::String, ::std::string::String>) {
+        self.mappings = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_mappings(&mut self) -> &mut ::std::collections::HashMap<::std::string::String, ::std::string::String> {
+        &mut self.mappings
+    }
+
+    // Take field
+    pub fn take_mappings(&mut self) -> ::std::collections::HashMap<::std::string::String, ::std::string::String> {
+        ::std::mem::replace(&mut self.mappings, ::std::collections::HashMap::new())
+    }
+
+    // optional .matrix.StringListMapPB allowed_context = 4;
+
+
+    pub fn get_allowed_context(&self) -> &StringListMapPB {
+        self.allowed_context.as_ref().unwrap_or_else(|| <StringListMapPB as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_allowed_context(&mut self) {
+        self.allowed_context.clear();
+    }
+
+    pub fn has_allowed_context(&self) -> bool {
+        self.allowed_context.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_allowed_context(&mut self, v: StringListMapPB) {
+        self.allowed_context = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_allowed_context(&mut self) -> &mut StringListMapPB {
+        if self.allowed_context.is_none() {
+            self.allowed_context.set_default();
+        }
+        self.allowed_context.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_allowed_context(&mut self) -> StringListMapPB {
+        self.allowed_context.take().unwrap_or_else(|| StringListMapPB::new())
+    }
+
+    // optional .matrix.ModeBoolMapPB time_dependant = 5;
+
+
+    pub fn get_time_dependant(&self) -> &ModeBoolMapPB {
+        self.time_dependant.as_ref().unwrap_or_else(|| <ModeBoolMapPB as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_time_dependant(&mut self) {
+        self.time_dependant.clear();
+    }
+
+    pub fn has_time_dependant(&self) -> bool {
+        self.time_dependant.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_time_dependant(&mut self, v: ModeBoolMapPB) {
+        self.time_dependant = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_time_dependant(&mut self) -> &mut ModeBoolMapPB {
+        if self.time_dependant.is_none() {
+            self.time_dependant.set_default();
+        }
+        self.time_dependant.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_time_dependant(&mut self) -> ModeBoolMapPB {
+        self.time_dependant.take().unwrap_or_else(|| ModeBoolMapPB::new())
+    }
+
+    // optional .matrix.ModeBoolMapPB flexible = 6;
+
+
+    pub fn get_flexible(&self) -> &ModeBoolMapPB {
+        self.flexible.as_ref().unwrap_or_else(|| <ModeBoolMapPB as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_flexible(&mut self) {
+        self.flexible.clear();
+    }
+
+    pub fn has_flexible(&self) -> bool {
+        self.flexible.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_flexible(&mut self, v: ModeBoolMapPB) {
+        self.flexible = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_flexible(&mut self) -> &mut ModeBoolMapPB {
+        if self.flexible.is_none() {
+            self.flexible.set_default();
+        }
+        self.flexible.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_flexible(&mut self) -> ModeBoolMapPB {
+        self.flexible.take().unwrap_or_else(|| ModeBoolMapPB::new())
+    }
+
+    // optional .matrix.ModeSettingMapPB time_dependant_settings = 7;
+
+
+    pub fn get_time_dependant_settings(&self) -> &ModeSettingMapPB {
+        self.time_dependant_settings.as_ref().unwrap_or_else(|| <ModeSettingMapPB as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_time_dependant_settings(&mut self) {
+        self.time_dependant_settings.clear();
+    }
+
+    pub fn has_time_dependant_settings(&self) -> bool {
+        self.time_dependant_settings.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_time_dependant_settings(&mut self, v: ModeSettingMapPB) {
+        self.time_dependant_settings = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_time_dependant_settings(&mut self) -> &mut ModeSettingMapPB {
+        if self.time_dependant_settings.is_none() {
+            self.time_dependant_settings.set_default();
+        }
+        self.time_dependant_settings.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_time_dependant_settings(&mut self) -> ModeSettingMapPB {
+        self.time_dependant_settings.take().unwrap_or_else(|| ModeSettingMapPB::new())
+    }
+
+    // optional .matrix.AreaFlexiblePB flexible_setting = 8;
+
+
+    pub fn get_flexible_setting(&self) -> &AreaFlexiblePB {
+        self.flexible_setting.as_ref().unwrap_or_else(|| <AreaFlexiblePB as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_flexible_setting(&mut self) {
+        self.flexible_setting.clear();
+    }
+
+    pub fn has_flexible_setting(&self) -> bool {
+        self.flexible_setting.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_flexible_setting(&mut self, v: AreaFlexiblePB) {
+        self.flexible_setting = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_flexible_setting(&mut self) -> &mut AreaFlexiblePB {
+        if self.flexible_setting.is_none() {
+            self.flexible_setting.set_default();
+        }
+        self.flexible_setting.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_flexible_setting(&mut self) -> AreaFlexiblePB {
+        self.flexible_setting.take().unwrap_or_else(|| AreaFlexiblePB::new())
+    }
+
+    // optional uint32 log_coord_precision = 9;
+
+
+    pub fn get_log_coord_precision(&self) -> u32 {
+        self.log_coord_precision.unwrap_or(0)
+    }
+    pub fn clear_log_coord_precision(&mut self) {
+        self.log_coord_precision = ::std::option::Option::None;
+    }
+
+    pub fn has_log_coord_precision(&self) -> bool {
+        self.log_coord_precision.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_log_coord_precision(&mut self, v: u32) {
+        self.log_coord_precision = ::std::option::Option::Some(v);
+    }
+
+    // optional double simplify_tolerance_meters = 10;
+
+
+    pub fn get_simplify_tolerance_meters(&self) -> f64 {
+        self.simplify_tolerance_meters.unwrap_or(0.)
+    }
+    pub fn clear_simplify_tolerance_meters(&mut self) {
+        self.simplify_tolerance_meters = ::std::option::Option::None;
+    }
+
+    pub fn has_simplify_tolerance_meters(&self) -> bool {
+        self.simplify_tolerance_meters.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_simplify_tolerance_meters(&mut self, v: f64) {
+        self.simplify_tolerance_meters = ::std::option::Option::Some(v);
+    }
+}
+
+impl ::protobuf::Message for AreaPB {
+    fn is_initialized(&self) -> bool {
+        for v in &self.allowed_context {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.time_dependant {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.flexible {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.time_dependant_settings {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.flexible_setting {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.name)?;
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.default_service)?;
+                },
+                3 => {
+                    ::protobuf::rt::read_map_into::<::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeString>(wire_type, is, &mut self.mappings)?;
+                },
+                4 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.allowed_context)?;
+                },
+                5 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.time_dependant)?;
+                },
+                6 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.flexible)?;
+                },
+                7 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.time_dependant_settings)?;
+                },
+                8 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.flexible_setting)?;
+                },
+                9 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.log_coord_precision = ::std::option::Option::Some(tmp);
+                },
+                10 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeFixed64 {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_double()?;
+                    self.simplify_tolerance_meters = ::std::option::Option::Some(tmp);
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let Some(ref v) = self.name.as_ref() {
+            my_size += ::protobuf::rt::string_size(1, &v);
+        }
+        if let Some(ref v) = self.default_service.as_ref() {
+            my_size += ::protobuf::rt::string_size(2, &v);
+        }
+        my_size += ::protobuf::rt::compute_map_size::<::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeString>(3, &self.mappings);
+        if let Some(ref v) = self.allowed_context.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        if let Some(ref v) = self.time_dependant.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        if let Some(ref v) = self.flexible.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        if let Some(ref v) = self.time_dependant_settings.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        if let Some(ref v) = self.flexible_setting.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        if let Some(v) = self.log_coord_precision {
+            my_size += ::protobuf::rt::value_size(9, v, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if let Some(v) = self.simplify_tolerance_meters {
+            my_size += 9;
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let Some(ref v) = self.name.as_ref() {
+            os.write_string(1, &v)?;
+        }
+        if let Some(ref v) = self.default_service.as_ref() {
+            os.write_string(2, &v)?;
+        }
+        ::protobuf::rt::write_map_with_cached_sizes::<::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeString>(3, &self.mappings, os)?;
+        if let Some(ref v) = self.allowed_context.as_ref() {
+            os.write_tag(4, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        if let Some(ref v) = self.time_dependant.as_ref() {
+            os.write_tag(5, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        if let Some(ref v) = self.flexible.as_ref() {
+            os.write_tag(6, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        if let Some(ref v) = self.time_dependant_settings.as_ref() {
+            os.write_tag(7, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        if let Some(ref v) = self.flexible_setting.as_ref() {
+            os.write_tag(8, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        if let Some(v) = self.log_coord_precision {
+            os.write_uint32(9, v)?;
+        }
+        if let Some(v) = self.simplify_tolerance_meters {
+            os.write_double(10, v)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> AreaPB {
+        AreaPB::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_singular_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "name",
+                |m: &AreaPB| { &m.name },
+                |m: &mut AreaPB| { &mut m.name },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "default_service",
+                |m: &AreaPB| { &m.default_service },
+                |m: &mut AreaPB| { &mut m.default_service },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_map_accessor::<_, ::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeString>(
+                "mappings",
+                |m: &AreaPB| { &m.mappings },
+                |m: &mut AreaPB| { &mut m.mappings },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<StringListMapPB>>(
+                "allowed_context",
+                |m: &AreaPB| { &m.allowed_context },
+                |m: &mut AreaPB| { &mut m.allowed_context },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<ModeBoolMapPB>>(
+                "time_dependant",
+                |m: &AreaPB| { &m.time_dependant },
+                |m: &mut AreaPB| { &mut m.time_dependant },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<ModeBoolMapPB>>(
+                "flexible",
+                |m: &AreaPB| { &m.flexible },
+                |m: &mut AreaPB| { &mut m.flexible },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<ModeSettingMapPB>>(
+                "time_dependant_settings",
+                |m: &AreaPB| { &m.time_dependant_settings },
+                |m: &mut AreaPB| { &mut m.time_dependant_settings },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<AreaFlexiblePB>>(
+                "flexible_setting",
+                |m: &AreaPB| { &m.flexible_setting },
+                |m: &mut AreaPB| { &mut m.flexible_setting },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "log_coord_precision",
+                |m: &AreaPB| { &m.log_coord_precision },
+                |m: &mut AreaPB| { &mut m.log_coord_precision },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeDouble>(
+                "simplify_tolerance_meters",
+                |m: &AreaPB| { &m.simplify_tolerance_meters },
+                |m: &mut AreaPB| { &mut m.simplify_tolerance_meters },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<AreaPB>(
+                "AreaPB",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static AreaPB {
+        static instance: ::protobuf::rt::LazyV2<AreaPB> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(AreaPB::new)
+    }
+}
+
+impl ::protobuf::Clear for AreaPB {
+    fn clear(&mut self) {
+        self.name.clear();
+        self.default_service.clear();
+        self.mappings.clear();
+        self.allowed_context.clear();
+        self.time_dependant.clear();
+        self.flexible.clear();
+        self.time_dependant_settings.clear();
+        self.flexible_setting.clear();
+        self.log_coord_precision = ::std::option::Option::None;
+        self.simplify_tolerance_meters = ::std::option::Option::None;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for AreaPB {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for AreaPB {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct BordersPB {
+    // message fields
+    pub area_list: ::protobuf::RepeatedField<AreaPB>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a BordersPB {
+    fn default() -> &'a BordersPB {
+        <BordersPB as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl BordersPB {
+    pub fn new() -> BordersPB {
+        ::std::default::Default::default()
+    }
+
+    // repeated .matrix.AreaPB area_list = 1;
+
+
+    pub fn get_area_list(&self) -> &[AreaPB] {
+        &self.area_list
+    }
+    pub fn clear_area_list(&mut self) {
+        self.area_list.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_area_list(&mut self, v: ::protobuf::RepeatedField<AreaPB>) {
+        self.area_list = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_area_list(&mut self) -> &mut ::protobuf::RepeatedField<AreaPB> {
+        &mut self.area_list
+    }
+
+    // Take field
+    pub fn take_area_list(&mut self) -> ::protobuf::RepeatedField<AreaPB> {
+        ::std::mem::replace(&mut self.area_list, ::protobuf::RepeatedField::new())
+    }
+}
+
+impl ::protobuf::Message for BordersPB {
+    fn is_initialized(&self) -> bool {
+        for v in &self.area_list {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.area_list)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        for value in &self.area_list {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        for v in &self.area_list {
+            os.write_tag(1, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> BordersPB {
+        BordersPB::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<AreaPB>>(
+                "area_list",
+                |m: &BordersPB| { &m.area_list },
+                |m: &mut BordersPB| { &mut m.area_list },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<BordersPB>(
+                "BordersPB",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static BordersPB {
+        static instance: ::protobuf::rt::LazyV2<BordersPB> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(BordersPB::new)
+    }
+}
+
+impl ::protobuf::Clear for BordersPB {
+    fn clear(&mut self) {
+        self.area_list.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for BordersPB {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for BordersPB {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct MatrixRowChunk {
+    // message fields
+    task_id: ::protobuf::SingularField<::std::string::String>,
+    chunk_id: ::protobuf::SingularField<::std::string::String>,
+    sequence: ::std::option::Option<u64>,
+    first_row: ::std::option::Option<u32>,
+    pub rows: ::protobuf::RepeatedField<MatrixOutputPB_MatrixRow>,
+    total_rows: ::std::option::Option<u32>,
+    last: ::std::option::Option<bool>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a MatrixRowChunk {
+    fn default() -> &'a MatrixRowChunk {
+        <MatrixRowChunk as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl MatrixRowChunk {
+    pub fn new() -> MatrixRowChunk {
+        ::std::default::Default::default()
+    }
+
+    // optional string task_id = 1;
+
+
+    pub fn get_task_id(&self) -> &str {
+        match self.task_id.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
+    pub fn clear_task_id(&mut self) {
+        self.task_id.clear();
+    }
+
+    pub fn has_task_id(&self) -> bool {
+        self.task_id.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_task_id(&mut self, v: ::std::string::String) {
+        self.task_id = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_task_id(&mut self) -> &mut ::std::string::String {
+        if self.task_id.is_none() {
+            self.task_id.set_default();
+        }
+        self.task_id.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_task_id(&mut self) -> ::std::string::String {
+        self.task_id.take().unwrap_or_else(|| ::std::string::String::new())
+    }
+
+    // optional string chunk_id = 2;
+
+
+    pub fn get_chunk_id(&self) -> &str {
+        match self.chunk_id.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
+    pub fn clear_chunk_id(&mut self) {
+        self.chunk_id.clear();
+    }
+
+    pub fn has_chunk_id(&self) -> bool {
+        self.chunk_id.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_chunk_id(&mut self, v: ::std::string::String) {
+        self.chunk_id = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_chunk_id(&mut self) -> &mut ::std::string::String {
+        if self.chunk_id.is_none() {
+            self.chunk_id.set_default();
+        }
+        self.chunk_id.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_chunk_id(&mut self) -> ::std::string::String {
+        self.chunk_id.take().unwrap_or_else(|| ::std::string::String::new())
+    }
+
+    // optional uint64 sequence = 3;
+
+
+    pub fn get_sequence(&self) -> u64 {
+        self.sequence.unwrap_or(0)
+    }
+    pub fn clear_sequence(&mut self) {
+        self.sequence = ::std::option::Option::None;
+    }
+
+    pub fn has_sequence(&self) -> bool {
+        self.sequence.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_sequence(&mut self, v: u64) {
+        self.sequence = ::std::option::Option::Some(v);
+    }
+
+    // optional uint32 first_row = 4;
+
+
+    pub fn get_first_row(&self) -> u32 {
+        self.first_row.unwrap_or(0)
+    }
+    pub fn clear_first_row(&mut self) {
+        self.first_row = ::std::option::Option::None;
+    }
+
+    pub fn has_first_row(&self) -> bool {
+        self.first_row.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_first_row(&mut self, v: u32) {
+        self.first_row = ::std::option::Option::Some(v);
+    }
+
+    // repeated .matrix.MatrixOutputPB.MatrixRow rows = 5;
+
+
+    pub fn get_rows(&self) -> &[MatrixOutputPB_MatrixRow] {
+        &self.rows
+    }
+    pub fn clear_rows(&mut self) {
+        self.rows.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_rows(&mut self, v: ::protobuf::RepeatedField<MatrixOutputPB_MatrixRow>) {
+        self.rows = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_rows(&mut self) -> &mut ::protobuf::RepeatedField<MatrixOutputPB_MatrixRow> {
+        &mut self.rows
+    }
+
+    // Take field
+    pub fn take_rows(&mut self) -> ::protobuf::RepeatedField<MatrixOutputPB_MatrixRow> {
+        ::std::mem::replace(&mut self.rows, ::protobuf::RepeatedField::new())
+    }
+
+    // optional uint32 total_rows = 6;
+
+
+    pub fn get_total_rows(&self) -> u32 {
+        self.total_rows.unwrap_or(0)
+    }
+    pub fn clear_total_rows(&mut self) {
+        self.total_rows = ::std::option::Option::None;
+    }
+
+    pub fn has_total_rows(&self) -> bool {
+        self.total_rows.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_total_rows(&mut self, v: u32) {
+        self.total_rows = ::std::option::Option::Some(v);
+    }
+
+    // optional bool last = 7;
+
+
+    pub fn get_last(&self) -> bool {
+        self.last.unwrap_or(false)
+    }
+    pub fn clear_last(&mut self) {
+        self.last = ::std::option::Option::None;
+    }
+
+    pub fn has_last(&self) -> bool {
+        self.last.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_last(&mut self, v: bool) {
+        self.last = ::std::option::Option::Some(v);
+    }
+}
+
+impl ::protobuf::Message for MatrixRowChunk {
+    fn is_initialized(&self) -> bool {
+        for v in &self.rows {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.task_id)?;
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.chunk_id)?;
+                },
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.sequence = ::std::option::Option::Some(tmp);
+                },
+                4 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.first_row = ::std::option::Option::Some(tmp);
+                },
+                5 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.rows)?;
+                },
+                6 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.total_rows = ::std::option::Option::Some(tmp);
+                },
+                7 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_bool()?;
+                    self.last = ::std::option::Option::Some(tmp);
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let Some(ref v) = self.task_id.as_ref() {
+            my_size += ::protobuf::rt::string_size(1, &v);
+        }
+        if let Some(ref v) = self.chunk_id.as_ref() {
+            my_size += ::protobuf::rt::string_size(2, &v);
+        }
+        if let Some(v) = self.sequence {
+            my_size += ::protobuf::rt::value_size(3, v, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if let Some(v) = self.first_row {
+            my_size += ::protobuf::rt::value_size(4, v, ::protobuf::wire_format::WireTypeVarint);
+        }
+        for value in &self.rows {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        if let Some(v) = self.total_rows {
+            my_size += ::protobuf::rt::value_size(6, v, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if let Some(v) = self.last {
+            my_size += 2;
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let Some(ref v) = self.task_id.as_ref() {
+            os.write_string(1, &v)?;
+        }
+        if let Some(ref v) = self.chunk_id.as_ref() {
+            os.write_string(2, &v)?;
+        }
+        if let Some(v) = self.sequence {
+            os.write_uint64(3, v)?;
+        }
+        if let Some(v) = self.first_row {
+            os.write_uint32(4, v)?;
+        }
+        for v in &self.rows {
+            os.write_tag(5, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        if let Some(v) = self.total_rows {
+            os.write_uint32(6, v)?;
+        }
+        if let Some(v) = self.last {
+            os.write_bool(7, v)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> MatrixRowChunk {
+        MatrixRowChunk::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_singular_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "task_id",
+                |m: &MatrixRowChunk| { &m.task_id },
+                |m: &mut MatrixRowChunk| { &mut m.task_id },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "chunk_id",
+                |m: &MatrixRowChunk| { &m.chunk_id },
+                |m: &mut MatrixRowChunk| { &mut m.chunk_id },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "sequence",
+                |m: &MatrixRowChunk| { &m.sequence },
+                |m: &mut MatrixRowChunk| { &mut m.sequence },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "first_row",
+                |m: &MatrixRowChunk| { &m.first_row },
+                |m: &mut MatrixRowChunk| { &mut m.first_row },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<MatrixOutputPB_MatrixRow>>(
+                "rows",
+                |m: &MatrixRowChunk| { &m.rows },
+                |m: &mut MatrixRowChunk| { &mut m.rows },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "total_rows",
+                |m: &MatrixRowChunk| { &m.total_rows },
+                |m: &mut MatrixRowChunk| { &mut m.total_rows },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeBool>(
+                "last",
+                |m: &MatrixRowChunk| { &m.last },
+                |m: &mut MatrixRowChunk| { &mut m.last },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<MatrixRowChunk>(
+                "MatrixRowChunk",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static MatrixRowChunk {
+        static instance: ::protobuf::rt::LazyV2<MatrixRowChunk> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(MatrixRowChunk::new)
+    }
+}
+
+impl ::protobuf::Clear for MatrixRowChunk {
+    fn clear(&mut self) {
+        self.task_id.clear();
+        self.chunk_id.clear();
+        self.sequence = ::std::option::Option::None;
+        self.first_row = ::std::option::Option::None;
+        self.rows.clear();
+        self.total_rows = ::std::option::Option::None;
+        self.last = ::std::option::Option::None;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for MatrixRowChunk {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for MatrixRowChunk {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct PushRowsAck {
+    // message fields
+    task_id: ::protobuf::SingularField<::std::string::String>,
+    chunk_id: ::protobuf::SingularField<::std::string::String>,
+    next_row: ::std::option::Option<u32>,
+    error: ::protobuf::SingularField<::std::string::String>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a PushRowsAck {
+    fn default() -> &'a PushRowsAck {
+        <PushRowsAck as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl PushRowsAck {
+    pub fn new() -> PushRowsAck {
+        ::std::default::Default::default()
+    }
+
+    // optional string task_id = 1;
+
+
+    pub fn get_task_id(&self) -> &str {
+        match self.task_id.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
+    pub fn clear_task_id(&mut self) {
+        self.task_id.clear();
+    }
+
+    pub fn has_task_id(&self) -> bool {
+        self.task_id.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_task_id(&mut self, v: ::std::string::String) {
+        self.task_id = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_task_id(&mut self) -> &mut ::std::string::String {
+        if self.task_id.is_none() {
+            self.task_id.set_default();
+        }
+        self.task_id.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_task_id(&mut self) -> ::std::string::String {
+        self.task_id.take().unwrap_or_else(|| ::std::string::String::new())
+    }
+
+    // optional string chunk_id = 2;
+
+
+    pub fn get_chunk_id(&self) -> &str {
+        match self.chunk_id.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
+    pub fn clear_chunk_id(&mut self) {
+        self.chunk_id.clear();
+    }
+
+    pub fn has_chunk_id(&self) -> bool {
+        self.chunk_id.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_chunk_id(&mut self, v: ::std::string::String) {
+        self.chunk_id = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_chunk_id(&mut self) -> &mut ::std::string::String {
+        if self.chunk_id.is_none() {
+            self.chunk_id.set_default();
+        }
+        self.chunk_id.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_chunk_id(&mut self) -> ::std::string::String {
+        self.chunk_id.take().unwrap_or_else(|| ::std::string::String::new())
+    }
+
+    // optional uint32 next_row = 3;
+
+
+    pub fn get_next_row(&self) -> u32 {
+        self.next_row.unwrap_or(0)
+    }
+    pub fn clear_next_row(&mut self) {
+        self.next_row = ::std::option::Option::None;
+    }
+
+    pub fn has_next_row(&self) -> bool {
+        self.next_row.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_next_row(&mut self, v: u32) {
+        self.next_row = ::std::option::Option::Some(v);
+    }
+
+    // optional string error = 4;
+
+
+    pub fn get_error(&self) -> &str {
+        match self.error.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
+    pub fn clear_error(&mut self) {
+        self.error.clear();
+    }
+
+    pub fn has_error(&self) -> bool {
+        self.error.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_error(&mut self, v: ::std::string::String) {
+        self.error = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_error(&mut self) -> &mut ::std::string::String {
+        if self.error.is_none() {
+            self.error.set_default();
+        }
+        self.error.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_error(&mut self) -> ::std::string::String {
+        self.error.take().unwrap_or_else(|| ::std::string::String::new())
+    }
+}
+
+impl ::protobuf::Message for PushRowsAck {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.task_id)?;
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.chunk_id)?;
+                },
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.next_row = ::std::option::Option::Some(tmp);
+                },
+                4 => {
+                    ::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.error)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let Some(ref v) = self.task_id.as_ref() {
+            my_size += ::protobuf::rt::string_size(1, &v);
+        }
+        if let Some(ref v) = self.chunk_id.as_ref() {
+            my_size += ::protobuf::rt::string_size(2, &v);
+        }
+        if let Some(v) = self.next_row {
+            my_size += ::protobuf::rt::value_size(3, v, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if let Some(ref v) = self.error.as_ref() {
+            my_size += ::protobuf::rt::string_size(4, &v);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let Some(ref v) = self.task_id.as_ref() {
+            os.write_string(1, &v)?;
+        }
+        if let Some(ref v) = self.chunk_id.as_ref() {
+            os.write_string(2, &v)?;
+        }
+        if let Some(v) = self.next_row {
+            os.write_uint32(3, v)?;
+        }
+        if let Some(ref v) = self.error.as_ref() {
+            os.write_string(4, &v)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> PushRowsAck {
+        PushRowsAck::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_singular_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "task_id",
+                |m: &PushRowsAck| { &m.task_id },
+                |m: &mut PushRowsAck| { &mut m.task_id },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "chunk_id",
+                |m: &PushRowsAck| { &m.chunk_id },
+                |m: &mut PushRowsAck| { &mut m.chunk_id },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "next_row",
+                |m: &PushRowsAck| { &m.next_row },
+                |m: &mut PushRowsAck| { &mut m.next_row },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "error",
+                |m: &PushRowsAck| { &m.error },
+                |m: &mut PushRowsAck| { &mut m.error },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<PushRowsAck>(
+                "PushRowsAck",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static PushRowsAck {
+        static instance: ::protobuf::rt::LazyV2<PushRowsAck> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(PushRowsAck::new)
+    }
+}
+
+impl ::protobuf::Clear for PushRowsAck {
+    fn clear(&mut self) {
+        self.task_id.clear();
+        self.chunk_id.clear();
+        self.next_row = ::std::option::Option::None;
+        self.error.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for PushRowsAck {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for PushRowsAck {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct ResumeRequest {
+    // message fields
+    task_id: ::protobuf::SingularField<::std::string::String>,
+    chunk_id: ::protobuf::SingularField<::std::string::String>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a ResumeRequest {
+    fn default() -> &'a ResumeRequest {
+        <ResumeRequest as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl ResumeRequest {
+    pub fn new() -> ResumeRequest {
+        ::std::default::Default::default()
+    }
+
+    // optional string task_id = 1;
+
+
+    pub fn get_task_id(&self) -> &str {
+        match self.task_id.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
+    pub fn clear_task_id(&mut self) {
+        self.task_id.clear();
+    }
+
+    pub fn has_task_id(&self) -> bool {
+        self.task_id.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_task_id(&mut self, v: ::std::string::String) {
+        self.task_id = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_task_id(&mut self) -> &mut ::std::string::String {
+        if self.task_id.is_none() {
+            self.task_id.set_default();
+        }
+        self.task_id.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_task_id(&mut self) -> ::std::string::String {
+        self.task_id.take().unwrap_or_else(|| ::std::string::String::new())
+    }
+
+    // optional string chunk_id = 2;
+
+
+    pub fn get_chunk_id(&self) -> &str {
+        match self.chunk_id.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
+    pub fn clear_chunk_id(&mut self) {
+        self.chunk_id.clear();
+    }
+
+    pub fn has_chunk_id(&self) -> bool {
+        self.chunk_id.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_chunk_id(&mut self, v: ::std::string::String) {
+        self.chunk_id = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_chunk_id(&mut self) -> &mut ::std::string::String {
+        if self.chunk_id.is_none() {
+            self.chunk_id.set_default();
+        }
+        self.chunk_id.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_chunk_id(&mut self) -> ::std::string::String {
+        self.chunk_id.take().unwrap_or_else(|| ::std::string::String::new())
+    }
+}
+
+impl ::protobuf::Message for ResumeRequest {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.task_id)?;
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.chunk_id)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let Some(ref v) = self.task_id.as_ref() {
+            my_size += ::protobuf::rt::string_size(1, &v);
+        }
+        if let Some(ref v) = self.chunk_id.as_ref() {
+            my_size += ::protobuf::rt::string_size(2, &v);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let Some(ref v) = self.task_id.as_ref() {
+            os.write_string(1, &v)?;
+        }
+        if let Some(ref v) = self.chunk_id.as_ref() {
+            os.write_string(2, &v)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> ResumeRequest {
+        ResumeRequest::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_singular_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "task_id",
+                |m: &ResumeRequest| { &m.task_id },
+                |m: &mut ResumeRequest| { &mut m.task_id },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "chunk_id",
+                |m: &ResumeRequest| { &m.chunk_id },
+                |m: &mut ResumeRequest| { &mut m.chunk_id },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<ResumeRequest>(
+                "ResumeRequest",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static ResumeRequest {
+        static instance: ::protobuf::rt::LazyV2<ResumeRequest> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(ResumeRequest::new)
+    }
+}
+
+impl ::protobuf::Clear for ResumeRequest {
+    fn clear(&mut self) {
+        self.task_id.clear();
+        self.chunk_id.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for ResumeRequest {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for ResumeRequest {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct ResumePoint {
+    // message fields
+    next_row: ::std::option::Option<u32>,
+    next_sequence: ::std::option::Option<u64>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a ResumePoint {
+    fn default() -> &'a ResumePoint {
+        <ResumePoint as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl ResumePoint {
+    pub fn new() -> ResumePoint {
+        ::std::default::Default::default()
+    }
+
+    // optional uint32 next_row = 1;
+
+
+    pub fn get_next_row(&self) -> u32 {
+        self.next_row.unwrap_or(0)
+    }
+    pub fn clear_next_row(&mut self) {
+        self.next_row = ::std::option::Option::None;
+    }
+
+    pub fn has_next_row(&self) -> bool {
+        self.next_row.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_next_row(&mut self, v: u32) {
+        self.next_row = ::std::option::Option::Some(v);
+    }
+
+    // optional uint64 next_sequence = 2;
+
+
+    pub fn get_next_sequence(&self) -> u64 {
+        self.next_sequence.unwrap_or(0)
+    }
+    pub fn clear_next_sequence(&mut self) {
+        self.next_sequence = ::std::option::Option::None;
+    }
+
+    pub fn has_next_sequence(&self) -> bool {
+        self.next_sequence.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_next_sequence(&mut self, v: u64) {
+        self.next_sequence = ::std::option::Option::Some(v);
+    }
+}
+
+impl ::protobuf::Message for ResumePoint {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.next_row = ::std::option::Option::Some(tmp);
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.next_sequence = ::std::option::Option::Some(tmp);
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let Some(v) = self.next_row {
+            my_size += ::protobuf::rt::value_size(1, v, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if let Some(v) = self.next_sequence {
+            my_size += ::protobuf::rt::value_size(2, v, ::protobuf::wire_format::WireTypeVarint);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let Some(v) = self.next_row {
+            os.write_uint32(1, v)?;
+        }
+        if let Some(v) = self.next_sequence {
+            os.write_uint64(2, v)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> ResumePoint {
+        ResumePoint::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "next_row",
+                |m: &ResumePoint| { &m.next_row },
+                |m: &mut ResumePoint| { &mut m.next_row },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "next_sequence",
+                |m: &ResumePoint| { &m.next_sequence },
+                |m: &mut ResumePoint| { &mut m.next_sequence },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<ResumePoint>(
+                "ResumePoint",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static ResumePoint {
+        static instance: ::protobuf::rt::LazyV2<ResumePoint> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(ResumePoint::new)
+    }
+}
+
+impl ::protobuf::Clear for ResumePoint {
+    fn clear(&mut self) {
+        self.next_row = ::std::option::Option::None;
+        self.next_sequence = ::std::option::Option::None;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for ResumePoint {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for ResumePoint {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
 static file_descriptor_proto_data: &'static [u8] = b"\
     \n\x0cprotos.proto\x12\x06matrix\"\xef\x02\n\x0eMatrixOutputPB\x12\x18\n\
     \x06status\x18\x01\x20\x02(\tR\x06statusB\0\x126\n\x04rows\x18\x02\x20\
@@ -815,7 +6024,88 @@ static file_descriptor_proto_data: &'static [u8] = b"\
     x.MatrixOutputPB.IntValueR\x08durationB\0\x12=\n\x08distance\x18\x02\x20\
     \x02(\x0b2\x1f.matrix.MatrixOutputPB.IntValueR\x08distanceB\0:\0\x1aQ\n\
     \tMatrixRow\x12B\n\x08elements\x18\x01\x20\x03(\x0b2$.matrix.MatrixOutpu\
-    tPB.MatrixElementR\x08elementsB\0:\0:\0B\0b\x06proto2\
+    tPB.MatrixElementR\x08elementsB\0:\0:\0\"*\n\x0cStringListPB\x12\x18\n\
+    \x06values\x18\x01\x20\x03(\tR\x06valuesB\0:\0\"*\n\x0cUInt32ListPB\x12\
+    \x18\n\x06values\x18\x01\x20\x03(\rR\x06valuesB\0:\0\"\xa7\x01\n\x0fStri\
+    ngListMapPB\x12A\n\x06values\x18\x01\x20\x03(\x0b2'.matrix.StringListMap\
+    PB.values_MapEntryR\x06valuesB\0\x1aO\n\x0fvalues_MapEntry\x12\x0e\n\x03\
+    key\x18\x01(\tR\x03key\x12(\n\x05value\x18\x02(\x0b2\x14.matrix.StringLi\
+    stPBR\x05value:\x028\x01:\0\"\x85\x01\n\tBoolMapPB\x12;\n\x06values\x18\
+    \x01\x20\x03(\x0b2!.matrix.BoolMapPB.values_MapEntryR\x06valuesB\0\x1a9\
+    \n\x0fvalues_MapEntry\x12\x0e\n\x03key\x18\x01(\tR\x03key\x12\x12\n\x05v\
+    alue\x18\x02(\x08R\x05value:\x028\x01:\0\"\xa0\x01\n\rModeBoolMapPB\x12?\
+    \n\x06values\x18\x01\x20\x03(\x0b2%.matrix.ModeBoolMapPB.values_MapEntry\
+    R\x06valuesB\0\x1aL\n\x0fvalues_MapEntry\x12\x0e\n\x03key\x18\x01(\tR\
+    \x03key\x12%\n\x05value\x18\x02(\x0b2\x11.matrix.BoolMapPBR\x05value:\
+    \x028\x01:\0\"\xab\x01\n\x0cSettingMapPB\x12>\n\x06values\x18\x01\x20\
+    \x03(\x0b2$.matrix.SettingMapPB.values_MapEntryR\x06valuesB\0\x1aY\n\x0f\
+    values_MapEntry\x12\x0e\n\x03key\x18\x01(\tR\x03key\x122\n\x05value\x18\
+    \x02(\x0b2\x1e.matrix.TimeDependantSettingPBR\x05value:\x028\x01:\0\"\
+    \xa9\x01\n\x10ModeSettingMapPB\x12B\n\x06values\x18\x01\x20\x03(\x0b2(.m\
+    atrix.ModeSettingMapPB.values_MapEntryR\x06valuesB\0\x1aO\n\x0fvalues_Ma\
+    pEntry\x12\x0e\n\x03key\x18\x01(\tR\x03key\x12(\n\x05value\x18\x02(\x0b2\
+    \x14.matrix.SettingMapPBR\x05value:\x028\x01:\0\"\x8e\x01\n\x16DaysAhead\
+    SlotSettingPB\x12\x10\n\x02id\x18\x01\x20\x01(\tR\x02idB\0\x12\x16\n\x05\
+    range\x18\x02\x20\x03(\rR\x05rangeB\0\x12%\n\rrange_minutes\x18\x03\x20\
+    \x03(\rR\x0crangeMinutesB\0\x12!\n\x0brange_times\x18\x04\x20\x03(\rR\nr\
+    angeTimesB\0:\0\"k\n\x15DaysAheadDaySettingPB\x12\x18\n\x06prefix\x18\
+    \x01\x20\x01(\tR\x06prefixB\0\x126\n\x05slots\x18\x02\x20\x03(\x0b2\x1e.\
+    matrix.DaysAheadSlotSettingPBR\x05slotsB\0:\0\"i\n\x12DaysAheadSettingPB\
+    \x12\x1c\n\x08timezone\x18\x01\x20\x01(\x01R\x08timezoneB\0\x123\n\x04da\
+    ys\x18\x02\x20\x03(\x0b2\x1d.matrix.DaysAheadDaySettingPBR\x04daysB\0:\0\
+    \"\xad\x01\n\x18RecurringDayDefinitionPB\x12\x1b\n\x08day_type\x18\x01\
+    \x20\x01(\tR\x07dayTypeB\0\x125\n\ndate_value\x18\x02\x20\x01(\x0b2\x14.\
+    matrix.StringListPBR\tdateValueB\0\x12;\n\rweekday_value\x18\x03\x20\x01\
+    (\x0b2\x14.matrix.UInt32ListPBR\x0cweekdayValueB\0:\0\"\xb9\x01\n\x15Rec\
+    urringDaySettingPB\x12\x14\n\x04name\x18\x01\x20\x01(\tR\x04nameB\0\x12\
+    \x18\n\x06prefix\x18\x02\x20\x01(\tR\x06prefixB\0\x126\n\x04days\x18\x03\
+    \x20\x03(\x0b2\x20.matrix.RecurringDayDefinitionPBR\x04daysB\0\x126\n\
+    \x05slots\x18\x04\x20\x03(\x0b2\x1e.matrix.DaysAheadSlotSettingPBR\x05sl\
+    otsB\0:\0\"i\n\x12RecurringSettingPB\x12\x1c\n\x08timezone\x18\x01\x20\
+    \x01(\x01R\x08timezoneB\0\x123\n\x04days\x18\x02\x20\x03(\x0b2\x1d.matri\
+    x.RecurringDaySettingPBR\x04daysB\0:\0\"\xd6\x01\n\x16TimeDependantSetti\
+    ngPB\x12#\n\x0csetting_type\x18\x01\x20\x01(\tR\x0bsettingTypeB\0\x12J\n\
+    \x12days_ahead_setting\x18\x02\x20\x01(\x0b2\x1a.matrix.DaysAheadSetting\
+    PBR\x10daysAheadSettingB\0\x12I\n\x11recurring_setting\x18\x03\x20\x01(\
+    \x0b2\x1a.matrix.RecurringSettingPBR\x10recurringSettingB\0:\0\"\x9a\x03\
+    \n\x0eAreaFlexiblePB\x12)\n\x0fdefault_service\x18\x01\x20\x01(\tR\x0ede\
+    faultServiceB\0\x12F\n\x08mappings\x18\x02\x20\x03(\x0b2(.matrix.AreaFle\
+    xiblePB.mappings_MapEntryR\x08mappingsB\0\x12B\n\x0fallowed_context\x18\
+    \x03\x20\x01(\x0b2\x17.matrix.StringListMapPBR\x0eallowedContextB\0\x12>\
+    \n\x0etime_dependant\x18\x04\x20\x01(\x0b2\x15.matrix.ModeBoolMapPBR\rti\
+    meDependantB\0\x12R\n\x17time_dependant_settings\x18\x05\x20\x01(\x0b2\
+    \x18.matrix.ModeSettingMapPBR\x15timeDependantSettingsB\0\x1a;\n\x11mapp\
+    ings_MapEntry\x12\x0e\n\x03key\x18\x01(\tR\x03key\x12\x12\n\x05value\x18\
+    \x02(\tR\x05value:\x028\x01:\0\"\x8a\x05\n\x06AreaPB\x12\x14\n\x04name\
+    \x18\x01\x20\x01(\tR\x04nameB\0\x12)\n\x0fdefault_service\x18\x02\x20\
+    \x01(\tR\x0edefaultServiceB\0\x12>\n\x08mappings\x18\x03\x20\x03(\x0b2\
+    \x20.matrix.AreaPB.mappings_MapEntryR\x08mappingsB\0\x12B\n\x0fallowed_c\
+    ontext\x18\x04\x20\x01(\x0b2\x17.matrix.StringListMapPBR\x0eallowedConte\
+    xtB\0\x12>\n\x0etime_dependant\x18\x05\x20\x01(\x0b2\x15.matrix.ModeBool\
+    MapPBR\rtimeDependantB\0\x123\n\x08flexible\x18\x06\x20\x01(\x0b2\x15.ma\
+    trix.ModeBoolMapPBR\x08flexibleB\0\x12R\n\x17time_dependant_settings\x18\
+    \x07\x20\x01(\x0b2\x18.matrix.ModeSettingMapPBR\x15timeDependantSettings\
+    B\0\x12C\n\x10flexible_setting\x18\x08\x20\x01(\x0b2\x16.matrix.AreaFlex\
+    iblePBR\x0fflexibleSettingB\0\x120\n\x13log_coord_precision\x18\t\x20\
+    \x01(\rR\x11logCoordPrecisionB\0\x12<\n\x19simplify_tolerance_meters\x18\
+    \n\x20\x01(\x01R\x17simplifyToleranceMetersB\0\x1a;\n\x11mappings_MapEnt\
+    ry\x12\x0e\n\x03key\x18\x01(\tR\x03key\x12\x12\n\x05value\x18\x02(\tR\
+    \x05value:\x028\x01:\0\"<\n\tBordersPB\x12-\n\tarea_list\x18\x01\x20\x03\
+    (\x0b2\x0e.matrix.AreaPBR\x08areaListB\0:\0\"\xf6\x01\n\x0eMatrixRowChun\
+    k\x12\x19\n\x07task_id\x18\x01\x20\x01(\tR\x06taskIdB\0\x12\x1b\n\x08chu\
+    nk_id\x18\x02\x20\x01(\tR\x07chunkIdB\0\x12\x1c\n\x08sequence\x18\x03\
+    \x20\x01(\x04R\x08sequenceB\0\x12\x1d\n\tfirst_row\x18\x04\x20\x01(\rR\
+    \x08firstRowB\0\x126\n\x04rows\x18\x05\x20\x03(\x0b2\x20.matrix.MatrixOu\
+    tputPB.MatrixRowR\x04rowsB\0\x12\x1f\n\ntotal_rows\x18\x06\x20\x01(\rR\t\
+    totalRowsB\0\x12\x14\n\x04last\x18\x07\x20\x01(\x08R\x04lastB\0:\0\"|\n\
+    \x0bPushRowsAck\x12\x19\n\x07task_id\x18\x01\x20\x01(\tR\x06taskIdB\0\
+    \x12\x1b\n\x08chunk_id\x18\x02\x20\x01(\tR\x07chunkIdB\0\x12\x1b\n\x08ne\
+    xt_row\x18\x03\x20\x01(\rR\x07nextRowB\0\x12\x16\n\x05error\x18\x04\x20\
+    \x01(\tR\x05errorB\0:\0\"I\n\rResumeRequest\x12\x19\n\x07task_id\x18\x01\
+    \x20\x01(\tR\x06taskIdB\0\x12\x1b\n\x08chunk_id\x18\x02\x20\x01(\tR\x07c\
+    hunkIdB\0:\0\"S\n\x0bResumePoint\x12\x1b\n\x08next_row\x18\x01\x20\x01(\
+    \rR\x07nextRowB\0\x12%\n\rnext_sequence\x18\x02\x20\x01(\x04R\x0cnextSeq\
+    uenceB\0:\0B\0b\x06proto2\
 ";
 
 static file_descriptor_proto_lazy: ::protobuf::rt::LazyV2<::protobuf::descriptor::FileDescriptorProto> = ::protobuf::rt::LazyV2::INIT;
@@ -0,0 +1,346 @@
+// record and replay of the remote files read by the loaders, so that their tests run without
+//  the network. a Cassette holds the requests of a test with their responses, in
+//  tests/cassettes/<name>.json. tests replay their cassette, set NBROUTES_RECORD_CASSETTES to
+//  run them against the real stores instead and record the cassettes again, see
+//  tests/cassettes/README.md
+use crate::http_client::{HttpClient, HttpResponse};
+use crate::mdm_io::{ObjectStore, StoreFuture};
+use crate::signed_url::UrlSigner;
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+pub const ENV_RECORD_CASSETTES: &str = "NBROUTES_RECORD_CASSETTES";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReplayMode {
+    Replay,
+    Record,
+}
+
+impl ReplayMode {
+    pub fn from_env() -> ReplayMode {
+        match std::env::var(ENV_RECORD_CASSETTES) {
+            Ok(_) => ReplayMode::Record,
+            Err(_) => ReplayMode::Replay,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    // `<method> <url or path>`, urls without their query, see request_key
+    pub request: String,
+    pub status: u16,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    // utf-8 bodies are kept as is, others in base64
+    #[serde(default)]
+    pub body: String,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub base64: bool,
+}
+
+fn is_false(v: &bool) -> bool {
+    !*v
+}
+
+impl Interaction {
+    pub fn new(request: &str, status: u16, body: &[u8]) -> Interaction {
+        let (body, base64) = match std::str::from_utf8(body) {
+            Ok(v) => (v.to_string(), false),
+            Err(_) => (base64::encode(body), true),
+        };
+        Interaction {
+            request: request.to_string(),
+            status,
+            headers: BTreeMap::new(),
+            body,
+            base64,
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Interaction {
+        self.headers.insert(name.to_lowercase(), value.to_string());
+        self
+    }
+
+    pub fn body(&self) -> Result<Vec<u8>> {
+        if self.base64 {
+            return Ok(base64::decode(&self.body)?);
+        }
+        Ok(self.body.as_bytes().to_vec())
+    }
+}
+
+// request_key drops the query of urls, which holds cache busters like the timestamp of the
+//  time dependant settings
+pub fn request_key(method: &str, url: &str) -> String {
+    format!("{} {}", method, url.split('?').next().unwrap_or_default())
+}
+
+pub struct Cassette {
+    mode: ReplayMode,
+    // None for the cassettes of a single test, which are never saved
+    path: Option<PathBuf>,
+    interactions: Mutex<Vec<Interaction>>,
+    // number of interactions replayed per request
+    played: Mutex<HashMap<String, usize>>,
+}
+
+impl Cassette {
+    // new replays interactions
+    pub fn new(interactions: Vec<Interaction>) -> Cassette {
+        Cassette {
+            mode: ReplayMode::Replay,
+            path: None,
+            interactions: Mutex::new(interactions),
+            played: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // recording records the interactions in memory
+    pub fn recording() -> Cassette {
+        Cassette {
+            mode: ReplayMode::Record,
+            ..Cassette::new(vec![])
+        }
+    }
+
+    // load reads tests/cassettes/<name>.json, or starts recording it when
+    //  NBROUTES_RECORD_CASSETTES is set
+    pub fn load(name: &str) -> Result<Cassette> {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("cassettes")
+            .join(format!("{}.json", name));
+        let mode = ReplayMode::from_env();
+        let interactions = match mode {
+            ReplayMode::Record => vec![],
+            ReplayMode::Replay => match std::fs::read_to_string(&path) {
+                Ok(v) => serde_json::from_str(&v)?,
+                Err(e) => bail!("failed to read cassette {:?}: {}", path, e),
+            },
+        };
+        Ok(Cassette {
+            mode,
+            path: Some(path),
+            ..Cassette::new(interactions)
+        })
+    }
+
+    pub fn mode(&self) -> ReplayMode {
+        self.mode
+    }
+
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.interactions.lock().unwrap().clone()
+    }
+
+    pub fn record(&self, interaction: Interaction) {
+        self.interactions.lock().unwrap().push(interaction);
+    }
+
+    // replay returns the interactions recorded for request in turn, the last one once they
+    //  have all been played, so that a revalidation can replay a 304 after a 200
+    pub fn replay(&self, request: &str) -> Result<Interaction> {
+        let interactions = self.interactions.lock().unwrap();
+        let recorded: Vec<&Interaction> = interactions
+            .iter()
+            .filter(|i| i.request == request)
+            .collect();
+        if recorded.is_empty() {
+            bail!("no interaction recorded for {}", request);
+        }
+        let mut played = self.played.lock().unwrap();
+        let n = played.entry(request.to_string()).or_insert(0);
+        let interaction = recorded[(*n).min(recorded.len() - 1)].clone();
+        *n += 1;
+        Ok(interaction)
+    }
+
+    // save writes the recorded interactions to the file of the cassette, cassettes replayed
+    //  are left as is
+    pub fn save(&self) -> Result<()> {
+        let path = match (self.mode, self.path.as_ref()) {
+            (ReplayMode::Record, Some(path)) => path,
+            _ => return Ok(()),
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(&*self.interactions.lock().unwrap())?;
+        std::fs::write(path, json + "\n")?;
+        Ok(())
+    }
+}
+
+// ReplayStore replays the objects of store from cassette, or records them
+pub struct ReplayStore<S: ObjectStore> {
+    pub store: S,
+    pub cassette: Arc<Cassette>,
+}
+
+impl<S: ObjectStore> ReplayStore<S> {
+    pub fn new(store: S, cassette: Arc<Cassette>) -> ReplayStore<S> {
+        ReplayStore { store, cassette }
+    }
+}
+
+impl<S: ObjectStore> ObjectStore for ReplayStore<S> {
+    fn put<'a>(&'a self, path: &'a str, data: Vec<u8>) -> StoreFuture<'a, ()> {
+        Box::pin(async move {
+            let request = format!("PUT {}", path);
+            match self.cassette.mode() {
+                ReplayMode::Record => {
+                    let r = self.store.put(path, data).await;
+                    let interaction = match r.as_ref() {
+                        Ok(_) => Interaction::new(&request, 200, &[]),
+                        Err(e) => Interaction::new(&request, 500, e.to_string().as_bytes()),
+                    };
+                    self.cassette.record(interaction);
+                    r
+                }
+                ReplayMode::Replay => {
+                    let interaction = self.cassette.replay(&request)?;
+                    if interaction.status != 200 {
+                        bail!("error uploading {}: {}", path, interaction.body);
+                    }
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    fn get<'a>(&'a self, path: &'a str) -> StoreFuture<'a, Vec<u8>> {
+        Box::pin(async move {
+            let request = format!("GET {}", path);
+            match self.cassette.mode() {
+                ReplayMode::Record => {
+                    let r = self.store.get(path).await;
+                    let interaction = match r.as_ref() {
+                        Ok(v) => Interaction::new(&request, 200, v),
+                        Err(e) => Interaction::new(&request, 404, e.to_string().as_bytes()),
+                    };
+                    self.cassette.record(interaction);
+                    r
+                }
+                ReplayMode::Replay => {
+                    let interaction = self.cassette.replay(&request)?;
+                    if interaction.status != 200 {
+                        bail!("error downloading {}: {}", path, interaction.body);
+                    }
+                    interaction.body()
+                }
+            }
+        })
+    }
+
+    fn url_signer(&self) -> Option<&dyn UrlSigner> {
+        self.store.url_signer()
+    }
+}
+
+// ReplayHttp replays the responses of client from cassette, or records them
+pub struct ReplayHttp<C: HttpClient> {
+    pub client: C,
+    pub cassette: Arc<Cassette>,
+}
+
+impl<C: HttpClient> ReplayHttp<C> {
+    pub fn new(client: C, cassette: Arc<Cassette>) -> ReplayHttp<C> {
+        ReplayHttp { client, cassette }
+    }
+}
+
+impl<C: HttpClient> HttpClient for ReplayHttp<C> {
+    fn get<'a>(
+        &'a self,
+        url: &'a str,
+        headers: &'a [(&'a str, String)],
+    ) -> StoreFuture<'a, HttpResponse> {
+        Box::pin(async move {
+            let request = request_key("GET", url);
+            match self.cassette.mode() {
+                ReplayMode::Record => {
+                    let resp = self.client.get(url, headers).await?;
+                    let mut interaction = Interaction::new(&request, resp.status, &resp.body);
+                    interaction.headers = resp.headers.clone();
+                    self.cassette.record(interaction);
+                    Ok(resp)
+                }
+                ReplayMode::Replay => {
+                    let interaction = self.cassette.replay(&request)?;
+                    Ok(HttpResponse {
+                        status: interaction.status,
+                        body: interaction.body()?,
+                        headers: interaction.headers,
+                    })
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mdm_io::MemoryStore;
+
+    #[actix_rt::test]
+    async fn test_record_and_replay() {
+        let memory = MemoryStore::new();
+        memory.put("gs://a/b.yaml", b"a: 1".to_vec()).await.unwrap();
+        memory.put("gs://a/c.bin", vec![0xff, 0x00]).await.unwrap();
+
+        let recording = Arc::new(Cassette::recording());
+        let store = ReplayStore::new(memory, recording.clone());
+        assert_eq!(store.get("gs://a/b.yaml").await.unwrap(), b"a: 1");
+        assert_eq!(store.get("gs://a/c.bin").await.unwrap(), vec![0xff, 0x00]);
+        assert!(store.get("gs://a/missing").await.is_err());
+        store.put("gs://a/d", b"d".to_vec()).await.unwrap();
+
+        let interactions = recording.interactions();
+        assert_eq!(interactions.len(), 4);
+        assert!(interactions[1].base64);
+        assert_eq!(interactions[2].status, 404);
+        // saved and loaded as json
+        let json = serde_json::to_string(&interactions).unwrap();
+        let interactions: Vec<Interaction> = serde_json::from_str(&json).unwrap();
+
+        // replayed without the objects
+        let store = ReplayStore::new(MemoryStore::new(), Arc::new(Cassette::new(interactions)));
+        assert_eq!(store.get("gs://a/b.yaml").await.unwrap(), b"a: 1");
+        assert_eq!(store.get("gs://a/c.bin").await.unwrap(), vec![0xff, 0x00]);
+        assert!(store.get("gs://a/missing").await.is_err());
+        assert!(store.get("gs://a/other").await.is_err());
+        store.put("gs://a/d", b"d".to_vec()).await.unwrap();
+        assert!(store.put("gs://a/e", b"e".to_vec()).await.is_err());
+    }
+
+    #[actix_rt::test]
+    async fn test_replay_http() {
+        let cassette = Cassette::new(vec![
+            Interaction::new("GET http://example.com/a.yaml", 200, b"a").with_header("ETag", "v1"),
+            Interaction::new("GET http://example.com/a.yaml", 304, &[]),
+        ]);
+        let client = ReplayHttp::new(reqwest::Client::new(), Arc::new(cassette));
+        let resp = client
+            .get("http://example.com/a.yaml?1", &[])
+            .await
+            .unwrap();
+        assert_eq!(resp.status, 200);
+        assert_eq!(resp.header("etag"), Some("v1"));
+        assert_eq!(resp.text(), "a");
+        for _ in 0..2 {
+            let resp = client
+                .get("http://example.com/a.yaml?2", &[])
+                .await
+                .unwrap();
+            assert_eq!(resp.status, 304);
+        }
+        assert!(client.get("http://example.com/b.yaml", &[]).await.is_err());
+    }
+}
@@ -0,0 +1,226 @@
+// RequestCtx carries the data every step of a request needs, so that find_service, ctx
+//  resolution, sku resolution and metering see the same mode, key, time and deadline instead
+//  of the ad hoc arguments each of them took
+use crate::coord::Coord;
+use crate::deadline::Deadline;
+use crate::def::KeyServerAuthKey;
+use crate::feature_flags::FeatureFlags;
+use crate::metering::{Meter, UsageRecord};
+use crate::prepared_polygon::PreparedPolygon;
+use crate::travel_mode::TravelMode;
+use crate::util::Area;
+use crate::{find_service_with_deadline, timestamp, Result, Service};
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, Default)]
+pub struct RequestCtx {
+    pub request_id: Option<String>,
+    pub key_id: Option<String>,
+    pub mode: Option<TravelMode>,
+    // departure time of the request in unix seconds, now when not given
+    pub ts: Option<i64>,
+    pub flexible: bool,
+    pub deadline: Option<Deadline>,
+    // labels of the key, see FeatureFlags
+    pub labels: HashMap<String, String>,
+}
+
+impl RequestCtx {
+    pub fn new() -> RequestCtx {
+        RequestCtx::default()
+    }
+
+    pub fn with_request_id(mut self, request_id: &str) -> RequestCtx {
+        self.request_id = Some(request_id.to_string());
+        self
+    }
+
+    // with_key sets the id and the labels of the key of the request
+    pub fn with_key(mut self, key_id: &str, key: &KeyServerAuthKey) -> RequestCtx {
+        self.key_id = Some(key_id.to_string());
+        self.labels = key.labels.clone().unwrap_or_default();
+        self
+    }
+
+    pub fn with_mode(mut self, mode: Option<TravelMode>) -> RequestCtx {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_ts(mut self, ts: i64) -> RequestCtx {
+        self.ts = Some(ts);
+        self
+    }
+
+    pub fn with_flexible(mut self, flexible: bool) -> RequestCtx {
+        self.flexible = flexible;
+        self
+    }
+
+    pub fn with_deadline(mut self, deadline: Deadline) -> RequestCtx {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    pub fn request_id(&self) -> Option<&str> {
+        self.request_id.as_deref()
+    }
+
+    pub fn deadline(&self) -> Option<&Deadline> {
+        self.deadline.as_ref()
+    }
+
+    pub fn ts_or_now(&self) -> i64 {
+        self.ts.unwrap_or_else(timestamp)
+    }
+
+    // feature_flags are the flags of the key of the request, none without a key
+    pub fn feature_flags(&self) -> FeatureFlags {
+        match self.key_id.as_ref() {
+            Some(key_id) => FeatureFlags::from_label_map(key_id, &self.labels),
+            None => FeatureFlags::default(),
+        }
+    }
+}
+
+// find_service_for is find_service_with_deadline with the mode, request id, flexibility and
+//  deadline of req
+pub fn find_service_for(
+    req: &RequestCtx,
+    coords: &Vec<Coord>,
+    polygons: &HashMap<String, Vec<PreparedPolygon>>,
+    areas: &Vec<Area>,
+    tolerate_outlier: bool,
+) -> Result<(Service, Option<Vec<usize>>)> {
+    find_service_with_deadline(
+        &req.mode,
+        coords,
+        polygons,
+        areas,
+        tolerate_outlier,
+        req.request_id(),
+        req.flexible,
+        req.deadline(),
+    )
+}
+
+// additional_ctx_for resolves the time dependant ctx of the service mode at the time of req
+pub fn additional_ctx_for(
+    req: &RequestCtx,
+    area: &Area,
+    mode: &str,
+    ctx: &str,
+) -> Result<Option<String>> {
+    area.get_additional_ctx_with_deadline(mode, ctx, req.ts_or_now(), req.flexible, req.deadline())
+}
+
+// resolve_sku returns the sku of api for the key, the sku of `<api>-<mode>` taking precedence
+//  over the one of api when the request has a mode
+pub fn resolve_sku(req: &RequestCtx, key: &KeyServerAuthKey, api: &str) -> Option<i64> {
+    let sku_map = key.sku_map.as_ref()?;
+    if let Some(mode) = req.mode.as_ref().filter(|m| !m.is_empty()) {
+        if let Some(v) = sku_map.get(&format!("{}-{}", api, mode.as_str())) {
+            return Some(v.sku_id);
+        }
+    }
+    sku_map.get(api).map(|v| v.sku_id)
+}
+
+// record_usage meters count units of api for the key of req
+pub fn record_usage(meter: &Meter, req: &RequestCtx, api: &str, sku: Option<i64>, count: f64) {
+    meter.record(UsageRecord {
+        mode: req.mode.as_ref().map(|m| m.as_str().to_string()),
+        sku,
+        request_id: req.request_id.clone(),
+        ..UsageRecord::new(req.key_id.as_deref().unwrap_or_default(), api, count)
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::def::KeySKUSetting;
+    use crate::metering::{MeteringFuture, UsageSink};
+    use crate::statsd::{self, TypedTrackInput};
+    use crate::testkit::{polygons_map, square_polygon, AreaBuilder};
+    use std::sync::mpsc::sync_channel;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    struct CaptureSink(Arc<Mutex<Vec<UsageRecord>>>);
+
+    impl UsageSink for CaptureSink {
+        fn write<'a>(&'a self, records: &'a [UsageRecord]) -> MeteringFuture<'a, ()> {
+            self.0.lock().unwrap().extend_from_slice(records);
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_request_ctx() {
+        let mut key = KeyServerAuthKey {
+            source: None,
+            sku_map: None,
+            labels: Some(
+                vec![("ff.new_eta_model".to_string(), "true".to_string())]
+                    .into_iter()
+                    .collect(),
+            ),
+            qps_limit: None,
+        };
+        let req = RequestCtx::new()
+            .with_request_id("r1")
+            .with_key("k1", &key)
+            .with_mode(Some(TravelMode::Truck))
+            .with_ts(1_600_000_000)
+            .with_deadline(Deadline::after(Duration::from_secs(5)));
+        assert!(req.feature_flags().new_eta_model());
+        assert_eq!(req.ts_or_now(), 1_600_000_000);
+
+        let center = Coord::new(1.3, 103.8);
+        let polygons = polygons_map(vec![("singapore", vec![square_polygon(&center, 0.1)])]);
+        let areas = vec![AreaBuilder::new("singapore", "4w")
+            .mapping("truck", "6w")
+            .build()];
+        let (service, _) =
+            find_service_for(&req, &vec![center.clone()], &polygons, &areas, false).unwrap();
+        assert_eq!(service.mode, "6w");
+        assert_eq!(
+            additional_ctx_for(&req, &service.area, "6w", "").unwrap(),
+            None
+        );
+
+        assert_eq!(resolve_sku(&req, &key, "directions"), None);
+        key.sku_map = Some(
+            vec![
+                ("directions".to_string(), KeySKUSetting { sku_id: 1 }),
+                ("directions-truck".to_string(), KeySKUSetting { sku_id: 2 }),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        assert_eq!(resolve_sku(&req, &key, "directions"), Some(2));
+        let car = req.clone().with_mode(Some(TravelMode::Car));
+        assert_eq!(resolve_sku(&car, &key, "directions"), Some(1));
+
+        let (sender, receiver) = sync_channel(1);
+        let records = Arc::new(Mutex::new(vec![]));
+        let meter = Meter::new(Box::new(CaptureSink(records.clone()))).with_sender(sender);
+        record_usage(&meter, &req, "directions", Some(2), 3.0);
+        assert_eq!(meter.flush().await.unwrap(), 1);
+        let record = records.lock().unwrap()[0].clone();
+        assert_eq!(record.key_id, "k1");
+        assert_eq!(record.request_id.as_deref(), Some("r1"));
+        assert_eq!(record.sku, Some(2));
+        match receiver.recv().unwrap() {
+            TypedTrackInput::Counter(c) => {
+                assert_eq!(c.metric_name, statsd::METRIC_METERED_USAGE);
+                assert_eq!(c.count, 3.0);
+                assert!(!c.labels.contains_key("key_id"));
+                assert_eq!(c.labels[statsd::LABELNAME_SKU], "2");
+                assert_eq!(c.labels[statsd::LABELNAME_MODE], "truck");
+            }
+            _ => panic!("usage is not a counter"),
+        }
+    }
+}
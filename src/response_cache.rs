@@ -0,0 +1,442 @@
+// caching of serialized outputs by the canonical hash of their request. each endpoint has its
+//  own TTL and max entry size, endpoints without a policy are not cached. entries live in an
+//  in-memory LRU, optionally backed by a remote cache shared between the replicas of a service.
+//  requests with a departure or arrival time depend on live traffic and should bypass the cache
+use crate::statsd::{MetricType, RegisterMetricInput, TrackCountInput, TypedTrackInput};
+use crate::Result;
+use lru_cache::LruCache;
+#[cfg(feature = "redis")]
+use redis::aio::ConnectionManager;
+#[cfg(feature = "redis")]
+use redis::AsyncCommands;
+use ring::digest;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::mpsc::SyncSender;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+pub const METRIC_RESPONSE_CACHE_LOOKUPS: &str = "response_cache_lookups";
+pub const LABELNAME_ENDPOINT: &str = "endpoint";
+pub const LABELNAME_CACHE_RESULT: &str = "result";
+
+// fields of the inputs that do not change the output
+pub const DEFAULT_IGNORED_FIELDS: [&str; 2] = ["key", "session"];
+const TIME_FIELDS: [&str; 2] = ["departure_time", "arrival_time"];
+
+// request_hash is the hex SHA-256 of the endpoint and the input serialized as JSON, with null
+//  and ignored fields dropped. object keys are sorted by serde_json, so the hash does not
+//  depend on the order of the query parameters
+pub fn request_hash<T: Serialize>(endpoint: &str, input: &T, ignored: &[&str]) -> Result<String> {
+    let mut value = serde_json::to_value(input)?;
+    if let Value::Object(fields) = &mut value {
+        fields.retain(|k, v| !v.is_null() && !ignored.contains(&k.as_str()));
+    }
+    let canonical = format!("{}\n{}", endpoint, serde_json::to_string(&value)?);
+    let hash = digest::digest(&digest::SHA256, canonical.as_bytes());
+    Ok(hash.as_ref().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+// is_time_sensitive tells if the input asks for a departure or arrival time
+pub fn is_time_sensitive<T: Serialize>(input: &T) -> bool {
+    let value = match serde_json::to_value(input) {
+        Ok(v) => v,
+        Err(_) => return true,
+    };
+    TIME_FIELDS.iter().any(|f| match value.get(f) {
+        None | Some(Value::Null) => false,
+        Some(v) => v.as_i64() != Some(0),
+    })
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct EndpointCachePolicy {
+    pub ttl: Duration,
+    // larger outputs are not cached
+    pub max_entry_bytes: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CacheResult {
+    Hit,
+    Miss,
+    // found but older than the TTL
+    Stale,
+    Bypass,
+}
+
+impl CacheResult {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CacheResult::Hit => "hit",
+            CacheResult::Miss => "miss",
+            CacheResult::Stale => "stale",
+            CacheResult::Bypass => "bypass",
+        }
+    }
+}
+
+pub type RemoteCacheFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+// RemoteCache stores entries shared between replicas, expiring them after ttl
+pub trait RemoteCache: Sync {
+    fn get<'a>(&'a self, key: &'a str) -> RemoteCacheFuture<'a, Option<Vec<u8>>>;
+    fn set<'a>(&'a self, key: &'a str, data: Vec<u8>, ttl: Duration) -> RemoteCacheFuture<'a, ()>;
+}
+
+// redis_connect opens a connection to the redis server of url, reconnecting after failures.
+//  the connection is multiplexed, its clones can be shared by all the redis stores of a service
+#[cfg(feature = "redis")]
+pub async fn redis_connect(url: &str) -> Result<ConnectionManager> {
+    let client = redis::Client::open(url)?;
+    Ok(ConnectionManager::new(client).await?)
+}
+
+// RedisCache stores the entries as binary strings expiring with SETEX
+#[cfg(feature = "redis")]
+#[derive(Clone)]
+pub struct RedisCache {
+    conn: ConnectionManager,
+    prefix: String,
+}
+
+#[cfg(feature = "redis")]
+impl RedisCache {
+    pub fn new(conn: ConnectionManager, prefix: &str) -> RedisCache {
+        RedisCache {
+            conn,
+            prefix: prefix.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "redis")]
+impl RemoteCache for RedisCache {
+    fn get<'a>(&'a self, key: &'a str) -> RemoteCacheFuture<'a, Option<Vec<u8>>> {
+        Box::pin(async move {
+            let key = format!("{}{}", self.prefix, key);
+            let mut conn = self.conn.clone();
+            Ok(conn.get(key).await?)
+        })
+    }
+
+    fn set<'a>(&'a self, key: &'a str, data: Vec<u8>, ttl: Duration) -> RemoteCacheFuture<'a, ()> {
+        Box::pin(async move {
+            let key = format!("{}{}", self.prefix, key);
+            let mut conn = self.conn.clone();
+            conn.set_ex::<_, _, ()>(key, data, ttl.as_secs().max(1))
+                .await?;
+            Ok(())
+        })
+    }
+}
+
+struct Entry {
+    data: Vec<u8>,
+    stored_at: Instant,
+}
+
+pub struct ResponseCache {
+    policies: HashMap<String, EndpointCachePolicy>,
+    entries: Mutex<LruCache<String, Entry>>,
+    remote: Option<Box<dyn RemoteCache + Send>>,
+    sender: Option<SyncSender<TypedTrackInput>>,
+}
+
+impl ResponseCache {
+    pub fn new(capacity: usize) -> ResponseCache {
+        ResponseCache {
+            policies: HashMap::new(),
+            entries: Mutex::new(LruCache::new(capacity)),
+            remote: None,
+            sender: None,
+        }
+    }
+
+    pub fn with_policy(mut self, endpoint: &str, policy: EndpointCachePolicy) -> ResponseCache {
+        self.policies.insert(endpoint.to_string(), policy);
+        self
+    }
+
+    pub fn with_remote(mut self, remote: Box<dyn RemoteCache + Send>) -> ResponseCache {
+        self.remote = Some(remote);
+        self
+    }
+
+    // with_sender tracks lookups with the statsd collector
+    pub fn with_sender(mut self, sender: SyncSender<TypedTrackInput>) -> ResponseCache {
+        self.sender = Some(sender);
+        self
+    }
+
+    // get returns the output stored for the request hash of endpoint. errors of the remote
+    //  cache are logged and count as misses
+    pub async fn get(&self, endpoint: &str, hash: &str, bypass: bool) -> Option<Vec<u8>> {
+        let (data, result) = self.lookup(endpoint, hash, bypass).await;
+        self.track(endpoint, result);
+        data
+    }
+
+    pub async fn get_json<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        hash: &str,
+        bypass: bool,
+    ) -> Option<T> {
+        let data = self.get(endpoint, hash, bypass).await?;
+        match serde_json::from_slice(&data) {
+            Ok(v) => Some(v),
+            Err(e) => {
+                warn!("failed to decode cached {} output due to {:?}", endpoint, e);
+                None
+            }
+        }
+    }
+
+    // put stores the output if its endpoint has a policy and it fits the max entry size
+    pub async fn put(&self, endpoint: &str, hash: &str, data: Vec<u8>) {
+        let policy = match self.policies.get(endpoint) {
+            Some(v) if data.len() <= v.max_entry_bytes => v,
+            _ => return,
+        };
+        let key = entry_key(endpoint, hash);
+        if let Some(remote) = self.remote.as_ref() {
+            if let Err(e) = remote.set(&key, data.clone(), policy.ttl).await {
+                warn!("failed to store {} in remote cache due to {:?}", key, e);
+            }
+        }
+        self.entries.lock().unwrap().insert(
+            key,
+            Entry {
+                data,
+                stored_at: Instant::now(),
+            },
+        );
+    }
+
+    pub async fn put_json<T: Serialize>(&self, endpoint: &str, hash: &str, output: &T) {
+        match serde_json::to_vec(output) {
+            Ok(data) => self.put(endpoint, hash, data).await,
+            Err(e) => warn!("failed to encode {} output due to {:?}", endpoint, e),
+        }
+    }
+
+    async fn lookup(
+        &self,
+        endpoint: &str,
+        hash: &str,
+        bypass: bool,
+    ) -> (Option<Vec<u8>>, CacheResult) {
+        let policy = match self.policies.get(endpoint) {
+            Some(v) if !bypass => v,
+            _ => return (None, CacheResult::Bypass),
+        };
+        let key = entry_key(endpoint, hash);
+        let mut result = CacheResult::Miss;
+        {
+            let mut entries = self.entries.lock().unwrap();
+            if let Some(entry) = entries.get_mut(&key) {
+                if entry.stored_at.elapsed() < policy.ttl {
+                    return (Some(entry.data.clone()), CacheResult::Hit);
+                }
+                result = CacheResult::Stale;
+            }
+            if result == CacheResult::Stale {
+                entries.remove(&key);
+            }
+        }
+        let remote = match self.remote.as_ref() {
+            Some(v) => v,
+            None => return (None, result),
+        };
+        match remote.get(&key).await {
+            Ok(Some(data)) => {
+                // the remote TTL is not known, the entry gets a full local one
+                self.entries.lock().unwrap().insert(
+                    key,
+                    Entry {
+                        data: data.clone(),
+                        stored_at: Instant::now(),
+                    },
+                );
+                (Some(data), CacheResult::Hit)
+            }
+            Ok(None) => (None, result),
+            Err(e) => {
+                warn!("failed to get {} from remote cache due to {:?}", key, e);
+                (None, result)
+            }
+        }
+    }
+
+    fn track(&self, endpoint: &str, result: CacheResult) {
+        if let Some(sender) = self.sender.as_ref() {
+            let mut labels = HashMap::new();
+            labels.insert(LABELNAME_ENDPOINT.to_string(), endpoint.to_string());
+            labels.insert(
+                LABELNAME_CACHE_RESULT.to_string(),
+                result.name().to_string(),
+            );
+            let r = sender.try_send(TypedTrackInput::Counter(TrackCountInput {
+                metric_name: METRIC_RESPONSE_CACHE_LOOKUPS.to_string(),
+                count: 1.0,
+                labels,
+            }));
+            if let Err(e) = r {
+                warn!("failed to track response cache lookup due to {:?}", e);
+            }
+        }
+    }
+}
+
+fn entry_key(endpoint: &str, hash: &str) -> String {
+    format!("{}:{}", endpoint, hash)
+}
+
+pub fn response_cache_metric() -> RegisterMetricInput {
+    RegisterMetricInput {
+        metric_type: MetricType::Counter,
+        metric_name: METRIC_RESPONSE_CACHE_LOOKUPS.to_string(),
+        metric_desc: "number of response cache lookups by result".to_string(),
+        labels: vec![
+            LABELNAME_ENDPOINT.to_string(),
+            LABELNAME_CACHE_RESULT.to_string(),
+        ],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::def::MatrixInput;
+    use serde_json::json;
+    use std::sync::mpsc::sync_channel;
+
+    fn matrix_input(v: Value) -> MatrixInput {
+        serde_json::from_value(v).unwrap()
+    }
+
+    #[derive(Default)]
+    struct MemoryRemote {
+        entries: Mutex<HashMap<String, Vec<u8>>>,
+    }
+
+    impl RemoteCache for MemoryRemote {
+        fn get<'a>(&'a self, key: &'a str) -> RemoteCacheFuture<'a, Option<Vec<u8>>> {
+            Box::pin(async move { Ok(self.entries.lock().unwrap().get(key).cloned()) })
+        }
+
+        fn set<'a>(
+            &'a self,
+            key: &'a str,
+            data: Vec<u8>,
+            _ttl: Duration,
+        ) -> RemoteCacheFuture<'a, ()> {
+            Box::pin(async move {
+                self.entries.lock().unwrap().insert(key.to_string(), data);
+                Ok(())
+            })
+        }
+    }
+
+    #[test]
+    fn test_request_hash() {
+        let a = matrix_input(json!({
+            "origins": "1.3,103.8",
+            "destinations": "1.4,103.9",
+            "key": "k1",
+        }));
+        let b = matrix_input(json!({
+            "destinations": "1.4,103.9",
+            "origins": "1.3,103.8",
+            "key": "k2",
+        }));
+        let hash = request_hash("matrix", &a, &DEFAULT_IGNORED_FIELDS).unwrap();
+        assert_eq!(hash.len(), 64);
+        assert_eq!(
+            hash,
+            request_hash("matrix", &b, &DEFAULT_IGNORED_FIELDS).unwrap()
+        );
+        assert_ne!(hash, request_hash("matrix", &a, &[]).unwrap());
+        assert_ne!(
+            hash,
+            request_hash("directions", &a, &DEFAULT_IGNORED_FIELDS).unwrap()
+        );
+
+        assert!(!is_time_sensitive(&a));
+        let c = matrix_input(json!({
+            "origins": "1.3,103.8",
+            "destinations": "1.4,103.9",
+            "departure_time": 1600000000,
+        }));
+        assert!(is_time_sensitive(&c));
+    }
+
+    #[actix_rt::test]
+    async fn test_response_cache() {
+        let (tx, rx) = sync_channel(10);
+        let policy = EndpointCachePolicy {
+            ttl: Duration::from_millis(50),
+            max_entry_bytes: 16,
+        };
+        let cache = ResponseCache::new(10)
+            .with_policy("matrix", policy.clone())
+            .with_sender(tx);
+
+        assert!(cache.get("matrix", "h1", false).await.is_none());
+        cache.put("matrix", "h1", b"output".to_vec()).await;
+        cache.put("matrix", "h2", vec![0; 17]).await;
+        cache.put("directions", "h1", b"output".to_vec()).await;
+        assert_eq!(
+            cache.get("matrix", "h1", false).await,
+            Some(b"output".to_vec())
+        );
+        assert!(cache.get("matrix", "h1", true).await.is_none());
+        assert!(cache.get("matrix", "h2", false).await.is_none());
+        assert!(cache.get("directions", "h1", false).await.is_none());
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(cache.get("matrix", "h1", false).await.is_none());
+
+        let results: Vec<String> = rx
+            .try_iter()
+            .map(|t| match t {
+                TypedTrackInput::Counter(c) => c.labels[LABELNAME_CACHE_RESULT].clone(),
+                _ => "".to_string(),
+            })
+            .collect();
+        assert_eq!(
+            results,
+            vec!["miss", "hit", "bypass", "miss", "bypass", "stale"]
+        );
+
+        // entries of a replica are found by the others through the remote cache
+        let remote = std::sync::Arc::new(MemoryRemote::default());
+        struct Shared(std::sync::Arc<MemoryRemote>);
+        impl RemoteCache for Shared {
+            fn get<'a>(&'a self, key: &'a str) -> RemoteCacheFuture<'a, Option<Vec<u8>>> {
+                self.0.get(key)
+            }
+            fn set<'a>(
+                &'a self,
+                key: &'a str,
+                data: Vec<u8>,
+                ttl: Duration,
+            ) -> RemoteCacheFuture<'a, ()> {
+                self.0.set(key, data, ttl)
+            }
+        }
+        let a = ResponseCache::new(10)
+            .with_policy("matrix", policy.clone())
+            .with_remote(Box::new(Shared(remote.clone())));
+        let b = ResponseCache::new(10)
+            .with_policy("matrix", policy)
+            .with_remote(Box::new(Shared(remote)));
+        a.put_json("matrix", "h1", &json!({"status": "Ok"})).await;
+        let v: Value = b.get_json("matrix", "h1", false).await.unwrap();
+        assert_eq!(v["status"], "Ok");
+    }
+}
@@ -0,0 +1,224 @@
+// road_info of requests lists the road info objects to include in the response, and the
+//  responses of the engine are filtered down to them. shared by the directions, navigation and
+//  snap paths
+use crate::def::{
+    ProctorRoute, RoadInfo, SnapNodeInfo, SnapOutput, ValhallaDirectionsOutput, ValhallaRoute,
+    ValhallaSnapOutput,
+};
+use crate::Result;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RoadInfoKind {
+    MaxSpeed,
+}
+
+pub const ROAD_INFO_KINDS: &[RoadInfoKind] = &[RoadInfoKind::MaxSpeed];
+
+impl RoadInfoKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RoadInfoKind::MaxSpeed => "max_speed",
+        }
+    }
+}
+
+impl FromStr for RoadInfoKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        ROAD_INFO_KINDS
+            .iter()
+            .find(|k| k.as_str() == s)
+            .copied()
+            .ok_or_else(|| {
+                let known: Vec<&str> = ROAD_INFO_KINDS.iter().map(|k| k.as_str()).collect();
+                format!(
+                    "unknown road_info type '{}', expected one of {}",
+                    s,
+                    known.join("|")
+                )
+            })
+    }
+}
+
+impl fmt::Display for RoadInfoKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+// parse_road_info parses the road_info of a request, types are separated by `|` or `,`.
+//  an unset or empty road_info requests nothing
+pub fn parse_road_info(input: &Option<String>) -> Result<Vec<RoadInfoKind>> {
+    let mut kinds = vec![];
+    let input = match input.as_deref() {
+        Some(v) => v,
+        None => return Ok(kinds),
+    };
+    for item in input.split(['|', ',']) {
+        let item = item.trim();
+        if item.is_empty() {
+            continue;
+        }
+        let kind: RoadInfoKind = item.parse()?;
+        if !kinds.contains(&kind) {
+            kinds.push(kind);
+        }
+    }
+    Ok(kinds)
+}
+
+// RoadInfoFilter strips the road info objects that were not requested
+pub trait RoadInfoFilter {
+    fn filter_road_info(&mut self, kinds: &[RoadInfoKind]);
+}
+
+impl RoadInfoFilter for RoadInfo {
+    fn filter_road_info(&mut self, kinds: &[RoadInfoKind]) {
+        if !kinds.contains(&RoadInfoKind::MaxSpeed) {
+            self.max_speed = None;
+        }
+    }
+}
+
+impl RoadInfoFilter for SnapNodeInfo {
+    fn filter_road_info(&mut self, kinds: &[RoadInfoKind]) {
+        if !kinds.contains(&RoadInfoKind::MaxSpeed) {
+            self.max_speed = None;
+        }
+    }
+}
+
+// blocks left without any object are dropped
+impl RoadInfoFilter for Option<RoadInfo> {
+    fn filter_road_info(&mut self, kinds: &[RoadInfoKind]) {
+        if let Some(v) = self.as_mut() {
+            v.filter_road_info(kinds);
+            if v.max_speed.is_none() {
+                *self = None;
+            }
+        }
+    }
+}
+
+impl RoadInfoFilter for Option<SnapNodeInfo> {
+    fn filter_road_info(&mut self, kinds: &[RoadInfoKind]) {
+        if let Some(v) = self.as_mut() {
+            v.filter_road_info(kinds);
+            if v.max_speed.is_none() {
+                *self = None;
+            }
+        }
+    }
+}
+
+// per point blocks of snap keep their position, so only the whole list is dropped
+impl<T> RoadInfoFilter for Option<Vec<Option<T>>>
+where
+    Option<T>: RoadInfoFilter,
+{
+    fn filter_road_info(&mut self, kinds: &[RoadInfoKind]) {
+        if let Some(v) = self.as_mut() {
+            v.iter_mut().for_each(|i| i.filter_road_info(kinds));
+            if v.iter().all(|i| i.is_none()) {
+                *self = None;
+            }
+        }
+    }
+}
+
+impl RoadInfoFilter for ValhallaRoute {
+    fn filter_road_info(&mut self, kinds: &[RoadInfoKind]) {
+        self.road_info.filter_road_info(kinds);
+    }
+}
+
+impl RoadInfoFilter for ProctorRoute {
+    fn filter_road_info(&mut self, kinds: &[RoadInfoKind]) {
+        self.road_info.filter_road_info(kinds);
+    }
+}
+
+impl RoadInfoFilter for ValhallaDirectionsOutput {
+    fn filter_road_info(&mut self, kinds: &[RoadInfoKind]) {
+        self.routes
+            .iter_mut()
+            .for_each(|r| r.filter_road_info(kinds));
+    }
+}
+
+impl RoadInfoFilter for ValhallaSnapOutput {
+    fn filter_road_info(&mut self, kinds: &[RoadInfoKind]) {
+        self.road_info.filter_road_info(kinds);
+        self.snap_node_info.filter_road_info(kinds);
+        if let Some(routes) = self.routes.as_mut() {
+            routes.iter_mut().for_each(|r| r.filter_road_info(kinds));
+        }
+    }
+}
+
+impl RoadInfoFilter for SnapOutput {
+    fn filter_road_info(&mut self, kinds: &[RoadInfoKind]) {
+        self.road_info.filter_road_info(kinds);
+        self.snap_node_info.filter_road_info(kinds);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::def::{RoadSegInfo, SnapNodeInfoItem};
+
+    fn road_info() -> RoadInfo {
+        RoadInfo {
+            max_speed: Some(vec![RoadSegInfo {
+                offset: 0,
+                length: 3,
+                value: 50.0,
+            }]),
+        }
+    }
+
+    #[test]
+    fn test_parse_road_info() {
+        assert_eq!(parse_road_info(&None).unwrap(), vec![]);
+        assert_eq!(parse_road_info(&Some("".to_string())).unwrap(), vec![]);
+        assert_eq!(
+            parse_road_info(&Some("max_speed|max_speed,".to_string())).unwrap(),
+            vec![RoadInfoKind::MaxSpeed]
+        );
+        assert_eq!(
+            parse_road_info(&Some("max_speed|tolls".to_string()))
+                .err()
+                .unwrap()
+                .to_string(),
+            "unknown road_info type 'tolls', expected one of max_speed"
+        );
+    }
+
+    #[test]
+    fn test_filter_road_info() {
+        let mut info = Some(road_info());
+        info.filter_road_info(&[RoadInfoKind::MaxSpeed]);
+        assert!(info.is_some());
+        info.filter_road_info(&[]);
+        assert!(info.is_none());
+
+        let mut per_point = Some(vec![Some(road_info()), None]);
+        per_point.filter_road_info(&[RoadInfoKind::MaxSpeed]);
+        assert_eq!(per_point.as_ref().unwrap().len(), 2);
+        per_point.filter_road_info(&[]);
+        assert!(per_point.is_none());
+
+        let mut nodes = Some(vec![Some(SnapNodeInfo {
+            max_speed: Some(vec![SnapNodeInfoItem {
+                index: 1,
+                value: 60.0,
+            }]),
+        })]);
+        nodes.filter_road_info(&[]);
+        assert!(nodes.is_none());
+    }
+}
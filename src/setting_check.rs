@@ -0,0 +1,401 @@
+// validation of the time dependant settings, for the mistakes that make the lookups return
+//  no ctx at runtime without any error: reversed or overlapping slots, weekdays out of 0..6,
+//  dates not written the way they are compared (`2021/1/4`, not zero padded), a setting_type
+//  without its setting. deploy pipelines can reject the configs with problems before rollout
+use crate::{
+    has_time_dependant, Borders, DaysAheadSlotSetting, RecurringDayDefinition, TimeDependantSetting,
+};
+use std::fmt;
+
+// bounds of the UTC offsets in use, in hours
+const MIN_TIMEZONE: f64 = -12.0;
+const MAX_TIMEZONE: f64 = 14.0;
+const MINUTES_PER_DAY: u32 = 24 * 60;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SettingProblemKind {
+    UnknownSettingType(String),
+    // the setting of setting_type is not given
+    MissingSetting,
+    InvalidTimezone(f64),
+    NoDays,
+    // the slot has less than 2 bounds
+    InvalidRange,
+    ReversedRange { start: u32, end: u32 },
+    RangeBeyondDay { end: u32 },
+    // the slot overlaps the slot `other` of the same day, the first one wins
+    OverlappingSlots { other: String },
+    DuplicateSlotId(String),
+    UnknownDayType(String),
+    // the date_value or weekday_value of the day_type is not given
+    MissingDayValue,
+    WeekdayOutOfRange(u32),
+    InvalidDate(String),
+    // the setting is enabled in the area but was not loaded
+    NotLoaded,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SettingProblem {
+    // location of the problem in the setting, like `recurring_setting.days[0].slots[1]`
+    pub path: String,
+    pub kind: SettingProblemKind,
+}
+
+impl fmt::Display for SettingProblemKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SettingProblemKind::UnknownSettingType(v) => write!(f, "unknown setting_type {:?}", v),
+            SettingProblemKind::MissingSetting => write!(f, "setting is missing"),
+            SettingProblemKind::InvalidTimezone(v) => write!(f, "invalid timezone {}", v),
+            SettingProblemKind::NoDays => write!(f, "no days"),
+            SettingProblemKind::InvalidRange => write!(f, "range needs a start and an end"),
+            SettingProblemKind::ReversedRange { start, end } => {
+                write!(f, "range starts at {} after it ends at {}", start, end)
+            }
+            SettingProblemKind::RangeBeyondDay { end } => {
+                write!(f, "range ends at {}, after the end of the day", end)
+            }
+            SettingProblemKind::OverlappingSlots { other } => {
+                write!(f, "slot overlaps slot {:?}", other)
+            }
+            SettingProblemKind::DuplicateSlotId(v) => write!(f, "slot id {:?} is duplicated", v),
+            SettingProblemKind::UnknownDayType(v) => write!(f, "unknown day_type {:?}", v),
+            SettingProblemKind::MissingDayValue => write!(f, "value of the day_type is missing"),
+            SettingProblemKind::WeekdayOutOfRange(v) => {
+                write!(f, "weekday {} is out of 0 (monday) to 6 (sunday)", v)
+            }
+            SettingProblemKind::InvalidDate(v) => {
+                write!(f, "date {:?} is not like 2021/1/4", v)
+            }
+            SettingProblemKind::NotLoaded => write!(f, "setting is not loaded"),
+        }
+    }
+}
+
+impl fmt::Display for SettingProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.kind)
+    }
+}
+
+// SettingReport lists the problems of the setting of a mode and ctx of an area
+#[derive(Clone, Debug, PartialEq)]
+pub struct SettingReport {
+    pub area: String,
+    pub flexible: bool,
+    pub mode: String,
+    pub ctx: String,
+    pub problems: Vec<SettingProblem>,
+}
+
+struct Problems(Vec<SettingProblem>);
+
+impl Problems {
+    fn push(&mut self, path: &str, kind: SettingProblemKind) {
+        self.0.push(SettingProblem {
+            path: path.to_string(),
+            kind,
+        });
+    }
+
+    fn check_timezone(&mut self, path: &str, timezone: f64) {
+        if !(MIN_TIMEZONE..=MAX_TIMEZONE).contains(&timezone) {
+            self.push(
+                &format!("{}.timezone", path),
+                SettingProblemKind::InvalidTimezone(timezone),
+            );
+        }
+    }
+
+    fn check_slots(&mut self, path: &str, slots: &[DaysAheadSlotSetting]) {
+        let mut ranges: Vec<(usize, u32, u32)> = vec![];
+        for (i, slot) in slots.iter().enumerate() {
+            let slot_path = format!("{}.slots[{}]", path, i);
+            if slots[..i].iter().any(|s| s.id == slot.id) {
+                self.push(
+                    &slot_path,
+                    SettingProblemKind::DuplicateSlotId(slot.id.clone()),
+                );
+            }
+            let (start, end) = match slot.minutes() {
+                Some(v) => v,
+                None => {
+                    self.push(&slot_path, SettingProblemKind::InvalidRange);
+                    continue;
+                }
+            };
+            if start >= end {
+                self.push(&slot_path, SettingProblemKind::ReversedRange { start, end });
+                continue;
+            }
+            if end > MINUTES_PER_DAY {
+                self.push(&slot_path, SettingProblemKind::RangeBeyondDay { end });
+            }
+            if let Some(&(other, _, _)) = ranges.iter().find(|r| start < r.2 && r.1 < end) {
+                self.push(
+                    &slot_path,
+                    SettingProblemKind::OverlappingSlots {
+                        other: slots[other].id.clone(),
+                    },
+                );
+            }
+            ranges.push((i, start, end));
+        }
+    }
+
+    fn check_day(&mut self, path: &str, day: &RecurringDayDefinition) {
+        match day.day_type.as_str() {
+            "date" => match day.date_value.as_ref() {
+                Some(dates) => {
+                    for (i, date) in dates.iter().enumerate() {
+                        if !is_valid_date(date) {
+                            self.push(
+                                &format!("{}.date_value[{}]", path, i),
+                                SettingProblemKind::InvalidDate(date.clone()),
+                            );
+                        }
+                    }
+                }
+                None => self.push(path, SettingProblemKind::MissingDayValue),
+            },
+            "weekday" => match day.weekday_value.as_ref() {
+                Some(weekdays) => {
+                    for (i, weekday) in weekdays.iter().enumerate() {
+                        if *weekday > 6 {
+                            self.push(
+                                &format!("{}.weekday_value[{}]", path, i),
+                                SettingProblemKind::WeekdayOutOfRange(*weekday),
+                            );
+                        }
+                    }
+                }
+                None => self.push(path, SettingProblemKind::MissingDayValue),
+            },
+            v => self.push(path, SettingProblemKind::UnknownDayType(v.to_string())),
+        }
+    }
+}
+
+// is_valid_date checks that date is written as the recurring lookup formats dates, `y/m/d`
+//  without zero padding
+fn is_valid_date(date: &str) -> bool {
+    let parts: Vec<&str> = date.split('/').collect();
+    if parts.len() != 3 {
+        return false;
+    }
+    let values: Vec<u32> = match parts.iter().map(|v| v.parse::<u32>()).collect() {
+        Ok(v) => v,
+        Err(_) => return false,
+    };
+    if format!("{}/{}/{}", values[0], values[1], values[2]) != date {
+        return false;
+    }
+    chrono::NaiveDate::from_ymd_opt(values[0] as i32, values[1], values[2]).is_some()
+}
+
+impl TimeDependantSetting {
+    // validate returns the problems of the setting, empty when it is valid
+    pub fn validate(&self) -> Vec<SettingProblem> {
+        let mut problems = Problems(vec![]);
+        match self.setting_type.as_str() {
+            "days_ahead" => match self.days_ahead_setting.as_ref() {
+                Some(setting) => {
+                    let path = "days_ahead_setting";
+                    problems.check_timezone(path, setting.timezone);
+                    if setting.days.is_empty() {
+                        problems.push(path, SettingProblemKind::NoDays);
+                    }
+                    for (i, day) in setting.days.iter().enumerate() {
+                        problems.check_slots(&format!("{}.days[{}]", path, i), &day.slots);
+                    }
+                }
+                None => problems.push("days_ahead_setting", SettingProblemKind::MissingSetting),
+            },
+            "recurring" => match self.recurring_setting.as_ref() {
+                Some(setting) => {
+                    let path = "recurring_setting";
+                    problems.check_timezone(path, setting.timezone);
+                    if setting.days.is_empty() {
+                        problems.push(path, SettingProblemKind::NoDays);
+                    }
+                    for (i, recurring_day) in setting.days.iter().enumerate() {
+                        let path = format!("{}.days[{}]", path, i);
+                        if recurring_day.days.is_empty() {
+                            problems.push(&path, SettingProblemKind::NoDays);
+                        }
+                        for (j, day) in recurring_day.days.iter().enumerate() {
+                            problems.check_day(&format!("{}.days[{}]", path, j), day);
+                        }
+                        problems.check_slots(&path, &recurring_day.slots);
+                    }
+                }
+                None => problems.push("recurring_setting", SettingProblemKind::MissingSetting),
+            },
+            v => problems.push(
+                "setting_type",
+                SettingProblemKind::UnknownSettingType(v.to_string()),
+            ),
+        }
+        problems.0
+    }
+}
+
+impl Borders {
+    // validate_settings validates the time dependant settings enabled in the areas, once
+    //  populated. only the settings with problems are reported
+    pub fn validate_settings(&self) -> Vec<SettingReport> {
+        let mut r = vec![];
+        for area in self.area_list.iter().filter(|a| has_time_dependant(a)) {
+            let flexible = area.flexible_setting.as_ref();
+            let all = vec![
+                (
+                    false,
+                    area.time_dependant.as_ref(),
+                    area.time_dependant_settings.as_ref(),
+                ),
+                (
+                    true,
+                    flexible.and_then(|f| f.time_dependant.as_ref()),
+                    flexible.and_then(|f| f.time_dependant_settings.as_ref()),
+                ),
+            ];
+            for (is_flexible, enabled, settings) in all {
+                for (mode, mode_setting) in enabled.into_iter().flatten() {
+                    for (ctx, on) in mode_setting {
+                        if !on {
+                            continue;
+                        }
+                        let problems = match settings.and_then(|s| s.get(mode)?.get(ctx)) {
+                            Some(setting) => setting.validate(),
+                            None => vec![SettingProblem {
+                                path: String::new(),
+                                kind: SettingProblemKind::NotLoaded,
+                            }],
+                        };
+                        if !problems.is_empty() {
+                            r.push(SettingReport {
+                                area: area.name.to_string(),
+                                flexible: is_flexible,
+                                mode: mode.clone(),
+                                ctx: ctx.clone(),
+                                problems,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        r
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::AreaBuilder;
+
+    fn setting(yaml: &str) -> TimeDependantSetting {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_validate() {
+        let valid = setting(
+            "
+setting_type: recurring
+recurring_setting:
+  timezone: 8
+  days:
+    - name: weekday
+      prefix: wd
+      days:
+        - day_type: weekday
+          weekday_value: [0, 1, 2, 3, 4]
+        - day_type: date
+          date_value: ['2021/1/4']
+      slots:
+        - id: peak
+          range: ['07:30', '09:30']
+        - id: evening
+          range: [17, 20]
+",
+        );
+        assert_eq!(valid.validate(), vec![]);
+
+        let broken = setting(
+            "
+setting_type: recurring
+recurring_setting:
+  timezone: 20
+  days:
+    - name: weekday
+      prefix: wd
+      days:
+        - day_type: weekday
+          weekday_value: [0, 7]
+        - day_type: date
+          date_value: ['2021/01/04']
+        - day_type: holiday
+      slots:
+        - id: peak
+          range: ['09:30', '07:30']
+        - id: evening
+          range: [17, 20]
+        - id: late
+          range: [19, 22]
+        - id: late
+          range_minutes: [1320]
+",
+        );
+        let problems: Vec<String> = broken.validate().iter().map(|p| p.to_string()).collect();
+        assert_eq!(
+            problems,
+            vec![
+                "recurring_setting.timezone: invalid timezone 20",
+                "recurring_setting.days[0].days[0].weekday_value[1]: weekday 7 is out of 0 (monday) to 6 (sunday)",
+                "recurring_setting.days[0].days[1].date_value[0]: date \"2021/01/04\" is not like 2021/1/4",
+                "recurring_setting.days[0].days[2]: unknown day_type \"holiday\"",
+                "recurring_setting.days[0].slots[0]: range starts at 570 after it ends at 450",
+                "recurring_setting.days[0].slots[2]: slot overlaps slot \"evening\"",
+                "recurring_setting.days[0].slots[3]: slot id \"late\" is duplicated",
+                "recurring_setting.days[0].slots[3]: range needs a start and an end",
+            ]
+        );
+        assert_eq!(
+            setting("setting_type: days_ahead").validate()[0].kind,
+            SettingProblemKind::MissingSetting
+        );
+    }
+
+    #[test]
+    fn test_validate_settings() {
+        let mut borders = Borders {
+            area_list: vec![
+                AreaBuilder::new("india", "4w").build(),
+                AreaBuilder::new("singapore", "4w")
+                    .time_dependant("4w", "")
+                    .time_dependant("4w", "taxi")
+                    .build(),
+            ],
+        };
+        let settings = vec![(
+            "4w".to_string(),
+            vec![("".to_string(), setting("setting_type: weekly"))]
+                .into_iter()
+                .collect(),
+        )];
+        borders.area_list[1].time_dependant_settings = Some(settings.into_iter().collect());
+        let reports = borders.validate_settings();
+        assert_eq!(reports.len(), 2);
+        assert_eq!(
+            reports[0].problems[0].kind,
+            SettingProblemKind::UnknownSettingType("weekly".to_string())
+        );
+        assert_eq!(
+            (reports[1].ctx.as_str(), reports[1].flexible),
+            ("taxi", false)
+        );
+        assert_eq!(reports[1].problems[0].kind, SettingProblemKind::NotLoaded);
+    }
+}
@@ -0,0 +1,199 @@
+// SettingFetcher reads the time dependant setting files, `<namespace>/<filename>.yaml` under
+//  the root of the settings, so that they can be served from the public bucket over HTTP
+//  (the default), read from a private bucket, or from a local directory in tests and on-prem
+//  deployments
+use crate::http_client::HttpClient;
+use crate::mdm_io::{ObjectStore, StoreFuture};
+use crate::timestamp;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+pub const DEFAULT_SETTING_BASE_URL: &str =
+    "https://storage.googleapis.com/static.nextbillion.io/nbroute/time_dependant_setting";
+
+pub trait SettingFetcher: Sync {
+    // fetch returns the contents of the setting file
+    fn fetch<'a>(&'a self, namespace: &'a str, filename: &'a str) -> StoreFuture<'a, String>;
+}
+
+fn setting_path(root: &str, namespace: &str, filename: &str) -> String {
+    format!(
+        "{}/{}/{}.yaml",
+        root.trim_end_matches('/'),
+        namespace,
+        filename
+    )
+}
+
+struct Validated {
+    etag: Option<String>,
+    last_modified: Option<String>,
+    body: String,
+}
+
+// HttpSettingFetcher gets the files under base_url. files are revalidated with their ETag or
+//  Last-Modified date, so that unchanged files are not downloaded again
+pub struct HttpSettingFetcher {
+    base_url: String,
+    client: Box<dyn HttpClient + Send>,
+    validated: Mutex<HashMap<String, Validated>>,
+}
+
+impl Default for HttpSettingFetcher {
+    fn default() -> Self {
+        HttpSettingFetcher::new(DEFAULT_SETTING_BASE_URL)
+    }
+}
+
+impl HttpSettingFetcher {
+    pub fn new(base_url: &str) -> HttpSettingFetcher {
+        HttpSettingFetcher {
+            base_url: base_url.to_string(),
+            client: Box::new(reqwest::Client::new()),
+            validated: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // with_client gets the files with client instead of reqwest
+    pub fn with_client<C: HttpClient + Send + 'static>(mut self, client: C) -> HttpSettingFetcher {
+        self.client = Box::new(client);
+        self
+    }
+}
+
+impl SettingFetcher for HttpSettingFetcher {
+    fn fetch<'a>(&'a self, namespace: &'a str, filename: &'a str) -> StoreFuture<'a, String> {
+        Box::pin(async move {
+            let url = setting_path(&self.base_url, namespace, filename);
+            let mut headers = vec![];
+            if let Some(v) = self.validated.lock().unwrap().get(&url) {
+                if let Some(etag) = v.etag.as_ref() {
+                    headers.push(("if-none-match", etag.clone()));
+                }
+                if let Some(last_modified) = v.last_modified.as_ref() {
+                    headers.push(("if-modified-since", last_modified.clone()));
+                }
+            }
+            // the timestamp bypasses the caches in front of the bucket
+            let resp = self
+                .client
+                .get(&format!("{}?{}", url, timestamp()), &headers)
+                .await?;
+            if resp.status == 304 {
+                if let Some(v) = self.validated.lock().unwrap().get(&url) {
+                    return Ok(v.body.clone());
+                }
+                bail!("{} is not modified but not cached", url);
+            }
+            if !resp.is_success() {
+                bail!("{} returned {}", url, resp.status);
+            }
+            let body = resp.text();
+            let etag = resp.header("etag").map(|v| v.to_string());
+            let last_modified = resp.header("last-modified").map(|v| v.to_string());
+            if etag.is_some() || last_modified.is_some() {
+                self.validated.lock().unwrap().insert(
+                    url,
+                    Validated {
+                        etag,
+                        last_modified,
+                        body: body.clone(),
+                    },
+                );
+            }
+            Ok(body)
+        })
+    }
+}
+
+// StoreSettingFetcher reads the files under root in store, e.g. a `gs://` path with the
+//  GcsReader for buckets that are not public
+pub struct StoreSettingFetcher<S: ObjectStore> {
+    pub store: S,
+    pub root: String,
+}
+
+impl<S: ObjectStore> StoreSettingFetcher<S> {
+    pub fn new(store: S, root: &str) -> StoreSettingFetcher<S> {
+        StoreSettingFetcher {
+            store,
+            root: root.to_string(),
+        }
+    }
+}
+
+impl<S: ObjectStore> SettingFetcher for StoreSettingFetcher<S> {
+    fn fetch<'a>(&'a self, namespace: &'a str, filename: &'a str) -> StoreFuture<'a, String> {
+        Box::pin(async move {
+            let data = self
+                .store
+                .get(&setting_path(&self.root, namespace, filename))
+                .await?;
+            Ok(String::from_utf8(data)?)
+        })
+    }
+}
+
+// DirSettingFetcher reads the files under a local directory
+#[derive(Clone, Debug)]
+pub struct DirSettingFetcher {
+    pub root: PathBuf,
+}
+
+impl DirSettingFetcher {
+    pub fn new<P: Into<PathBuf>>(root: P) -> DirSettingFetcher {
+        DirSettingFetcher { root: root.into() }
+    }
+}
+
+impl SettingFetcher for DirSettingFetcher {
+    fn fetch<'a>(&'a self, namespace: &'a str, filename: &'a str) -> StoreFuture<'a, String> {
+        Box::pin(async move {
+            let path = self.root.join(namespace).join(format!("{}.yaml", filename));
+            match std::fs::read_to_string(&path) {
+                Ok(v) => Ok(v),
+                Err(e) => bail!("failed to read {:?}: {}", path, e),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mdm_io::MemoryStore;
+
+    #[actix_rt::test]
+    async fn test_setting_fetchers() {
+        let store = MemoryStore::new();
+        store
+            .put(
+                "gs://settings/prod/sg-4w.yaml",
+                b"setting_type: recurring".to_vec(),
+            )
+            .await
+            .unwrap();
+        let fetcher = StoreSettingFetcher::new(store, "gs://settings/");
+        assert_eq!(
+            fetcher.fetch("prod", "sg-4w").await.unwrap(),
+            "setting_type: recurring"
+        );
+        assert!(fetcher.fetch("prod", "sg-2w").await.is_err());
+
+        let root = std::env::temp_dir().join(format!("nbroutes-settings-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("prod/flexible")).unwrap();
+        std::fs::write(
+            root.join("prod/flexible/sg-4w.yaml"),
+            "setting_type: days_ahead",
+        )
+        .unwrap();
+        let fetcher = DirSettingFetcher::new(&root);
+        assert_eq!(
+            fetcher.fetch("prod/flexible", "sg-4w").await.unwrap(),
+            "setting_type: days_ahead"
+        );
+        assert!(fetcher.fetch("prod", "sg-4w").await.is_err());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
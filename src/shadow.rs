@@ -0,0 +1,368 @@
+// comparison of the outputs of a primary and a shadow engine for the same request, used when
+//  migrating an area between engines on live traffic. comparisons only borrow the outputs so
+//  that the primary response is returned untouched, deviations are reported as metrics and
+//  log events
+use crate::alternatives::geometry_overlap;
+use crate::def::{DirectionsOutput, MatrixOutput};
+use crate::engine_response::MATRIX_UNREACHABLE_VALUE;
+use crate::statsd::{
+    MetricType, RegisterMetricInput, TrackCountInput, TrackHistogramInput, TypedTrackInput,
+};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::mpsc::SyncSender;
+
+pub const METRIC_SHADOW_DURATION_DELTA: &str = "shadow_duration_delta";
+pub const METRIC_SHADOW_GEOMETRY_OVERLAP: &str = "shadow_geometry_overlap";
+pub const METRIC_SHADOW_MISMATCHES: &str = "shadow_mismatches";
+pub const LABELNAME_AREA: &str = "area";
+pub const LABELNAME_ENDPOINT: &str = "endpoint";
+pub const LABELNAME_MISMATCH_KIND: &str = "mismatch_kind";
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ShadowMismatch {
+    Status {
+        primary: String,
+        shadow: String,
+    },
+    // number of routes, or rows and columns of a matrix
+    Shape {
+        primary: (usize, usize),
+        shadow: (usize, usize),
+    },
+    // matrix element reachable with only one of the engines
+    Reachability {
+        row: usize,
+        col: usize,
+    },
+}
+
+impl ShadowMismatch {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ShadowMismatch::Status { .. } => "status",
+            ShadowMismatch::Shape { .. } => "shape",
+            ShadowMismatch::Reachability { .. } => "reachability",
+        }
+    }
+}
+
+impl fmt::Display for ShadowMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShadowMismatch::Status { primary, shadow } => {
+                write!(f, "status {} vs {}", primary, shadow)
+            }
+            ShadowMismatch::Shape { primary, shadow } => write!(
+                f,
+                "shape {}x{} vs {}x{}",
+                primary.0, primary.1, shadow.0, shadow.1
+            ),
+            ShadowMismatch::Reachability { row, col } => {
+                write!(f, "reachability differs at rows[{}].elements[{}]", row, col)
+            }
+        }
+    }
+}
+
+// DeltaStats summarizes relative duration deltas, percentiles are of the absolute deltas
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DeltaStats {
+    pub count: usize,
+    // signed, > 0 when the shadow engine is slower on average
+    pub mean: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub max: f64,
+}
+
+impl DeltaStats {
+    pub fn new(deltas: &[f64]) -> DeltaStats {
+        if deltas.is_empty() {
+            return DeltaStats::default();
+        }
+        let mut abs: Vec<f64> = deltas.iter().map(|d| d.abs()).collect();
+        abs.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        // nearest rank
+        let percentile = |p: f64| abs[((p * abs.len() as f64).ceil() as usize).max(1) - 1];
+        DeltaStats {
+            count: deltas.len(),
+            mean: deltas.iter().sum::<f64>() / deltas.len() as f64,
+            p50: percentile(0.5),
+            p90: percentile(0.9),
+            max: abs[abs.len() - 1],
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ShadowComparison {
+    // (shadow - primary) / primary for each compared route or matrix element
+    pub duration_deltas: Vec<f64>,
+    pub duration_stats: DeltaStats,
+    // ratio of the length of the first primary route shared with the first shadow route
+    pub geometry_overlap: Option<f64>,
+    pub mismatches: Vec<ShadowMismatch>,
+}
+
+// relative delta, None when the primary duration gives no meaningful ratio
+fn relative_delta(primary: f64, shadow: f64) -> Option<f64> {
+    if primary > 0.0 {
+        Some((shadow - primary) / primary)
+    } else if shadow == primary {
+        Some(0.0)
+    } else {
+        None
+    }
+}
+
+fn status_mismatch(primary: &str, shadow: &str) -> Option<ShadowMismatch> {
+    if primary == shadow {
+        return None;
+    }
+    Some(ShadowMismatch::Status {
+        primary: primary.to_string(),
+        shadow: shadow.to_string(),
+    })
+}
+
+// compare_directions compares the routes of both outputs pairwise by index. precision is the
+//  one of the encoded route geometries
+pub fn compare_directions(
+    primary: &DirectionsOutput,
+    shadow: &DirectionsOutput,
+    precision: u32,
+) -> ShadowComparison {
+    let mut r = ShadowComparison::default();
+    if let Some(m) = status_mismatch(&primary.status, &shadow.status) {
+        r.mismatches.push(m);
+        return r;
+    }
+    if primary.routes.len() != shadow.routes.len() {
+        r.mismatches.push(ShadowMismatch::Shape {
+            primary: (primary.routes.len(), 1),
+            shadow: (shadow.routes.len(), 1),
+        });
+    }
+    for (p, s) in primary.routes.iter().zip(shadow.routes.iter()) {
+        if let Some(d) = relative_delta(p.duration, s.duration) {
+            r.duration_deltas.push(d);
+        }
+    }
+    if let (Some(p), Some(s)) = (primary.routes.first(), shadow.routes.first()) {
+        if p.geometry.is_some() && s.geometry.is_some() {
+            r.geometry_overlap = Some(geometry_overlap(p, s, precision));
+        }
+    }
+    r.duration_stats = DeltaStats::new(&r.duration_deltas);
+    r
+}
+
+fn matrix_shape(output: &MatrixOutput) -> (usize, usize) {
+    (
+        output.rows.len(),
+        output.rows.first().map(|r| r.elements.len()).unwrap_or(0),
+    )
+}
+
+// compare_matrix compares the durations of the elements present in both outputs
+pub fn compare_matrix(primary: &MatrixOutput, shadow: &MatrixOutput) -> ShadowComparison {
+    let mut r = ShadowComparison::default();
+    if let Some(m) = status_mismatch(&primary.status, &shadow.status) {
+        r.mismatches.push(m);
+        return r;
+    }
+    let (primary_shape, shadow_shape) = (matrix_shape(primary), matrix_shape(shadow));
+    if primary_shape != shadow_shape {
+        r.mismatches.push(ShadowMismatch::Shape {
+            primary: primary_shape,
+            shadow: shadow_shape,
+        });
+    }
+    for (row, (p, s)) in primary.rows.iter().zip(shadow.rows.iter()).enumerate() {
+        for (col, (pe, se)) in p.elements.iter().zip(s.elements.iter()).enumerate() {
+            let p_unreachable = pe.duration.value == MATRIX_UNREACHABLE_VALUE;
+            let s_unreachable = se.duration.value == MATRIX_UNREACHABLE_VALUE;
+            if p_unreachable != s_unreachable {
+                r.mismatches.push(ShadowMismatch::Reachability { row, col });
+                continue;
+            }
+            if p_unreachable {
+                continue;
+            }
+            if let Some(d) = relative_delta(pe.duration.value as f64, se.duration.value as f64) {
+                r.duration_deltas.push(d);
+            }
+        }
+    }
+    r.duration_stats = DeltaStats::new(&r.duration_deltas);
+    r
+}
+
+pub fn shadow_metrics() -> Vec<RegisterMetricInput> {
+    let labels = vec![LABELNAME_AREA.to_string(), LABELNAME_ENDPOINT.to_string()];
+    vec![
+        RegisterMetricInput {
+            metric_type: MetricType::Histogram,
+            metric_name: METRIC_SHADOW_DURATION_DELTA.to_string(),
+            metric_desc:
+                "median absolute relative duration delta between shadow and primary engines"
+                    .to_string(),
+            labels: labels.clone(),
+        },
+        RegisterMetricInput {
+            metric_type: MetricType::Histogram,
+            metric_name: METRIC_SHADOW_GEOMETRY_OVERLAP.to_string(),
+            metric_desc: "ratio of the primary route shared with the shadow route".to_string(),
+            labels: labels.clone(),
+        },
+        RegisterMetricInput {
+            metric_type: MetricType::Counter,
+            metric_name: METRIC_SHADOW_MISMATCHES.to_string(),
+            metric_desc: "number of mismatches between shadow and primary outputs".to_string(),
+            labels: vec![
+                LABELNAME_AREA.to_string(),
+                LABELNAME_ENDPOINT.to_string(),
+                LABELNAME_MISMATCH_KIND.to_string(),
+            ],
+        },
+    ]
+}
+
+// report logs the comparison and tracks it when a sender is configured. one sample of each
+//  histogram is tracked per comparison, so that large matrices do not flood the collector
+pub fn report(
+    area: &str,
+    endpoint: &str,
+    comparison: &ShadowComparison,
+    sender: Option<&SyncSender<TypedTrackInput>>,
+) {
+    info!(
+        "shadow {} {}: {} deltas, mean {:.3}, p50 {:.3}, p90 {:.3}, max {:.3}, overlap {:?}",
+        area,
+        endpoint,
+        comparison.duration_stats.count,
+        comparison.duration_stats.mean,
+        comparison.duration_stats.p50,
+        comparison.duration_stats.p90,
+        comparison.duration_stats.max,
+        comparison.geometry_overlap,
+    );
+    for m in comparison.mismatches.iter() {
+        warn!("shadow {} {} mismatch: {}", area, endpoint, m);
+    }
+    let sender = match sender {
+        Some(v) => v,
+        None => return,
+    };
+    let labels = || {
+        let mut labels = HashMap::new();
+        labels.insert(LABELNAME_AREA.to_string(), area.to_string());
+        labels.insert(LABELNAME_ENDPOINT.to_string(), endpoint.to_string());
+        labels
+    };
+    let mut msgs = vec![];
+    if comparison.duration_stats.count > 0 {
+        msgs.push(TypedTrackInput::Histogram(TrackHistogramInput {
+            metric_name: METRIC_SHADOW_DURATION_DELTA.to_string(),
+            value: comparison.duration_stats.p50,
+            labels: labels(),
+        }));
+    }
+    if let Some(overlap) = comparison.geometry_overlap {
+        msgs.push(TypedTrackInput::Histogram(TrackHistogramInput {
+            metric_name: METRIC_SHADOW_GEOMETRY_OVERLAP.to_string(),
+            value: overlap,
+            labels: labels(),
+        }));
+    }
+    let mut counts = HashMap::<&'static str, f64>::new();
+    for m in comparison.mismatches.iter() {
+        *counts.entry(m.name()).or_insert(0.0) += 1.0;
+    }
+    for (kind, count) in counts {
+        let mut labels = labels();
+        labels.insert(LABELNAME_MISMATCH_KIND.to_string(), kind.to_string());
+        msgs.push(TypedTrackInput::Counter(TrackCountInput {
+            metric_name: METRIC_SHADOW_MISMATCHES.to_string(),
+            count,
+            labels,
+        }));
+    }
+    for msg in msgs {
+        if let Err(e) = sender.try_send(msg) {
+            warn!("failed to track shadow comparison due to {:?}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coord::Coord;
+    use crate::polyline;
+    use serde_json::json;
+    use std::sync::mpsc::sync_channel;
+
+    fn directions(durations: &[f64], coords: &[(f64, f64)]) -> DirectionsOutput {
+        let coords: Vec<Coord> = coords
+            .iter()
+            .map(|(lat, lng)| Coord::new(*lat, *lng))
+            .collect();
+        let geometry = polyline::encode(&coords, polyline::PRECISION_POLYLINE6);
+        let routes: Vec<serde_json::Value> = durations
+            .iter()
+            .map(|d| json!({"geometry": geometry, "distance": 1000.0, "duration": d}))
+            .collect();
+        serde_json::from_value(json!({"status": "Ok", "routes": routes})).unwrap()
+    }
+
+    #[test]
+    fn test_compare_directions() {
+        let primary = directions(&[100.0, 200.0], &[(1.0, 103.0), (1.0, 103.01)]);
+        let shadow = directions(&[110.0], &[(1.0, 103.0), (1.0, 103.01)]);
+        let r = compare_directions(&primary, &shadow, polyline::PRECISION_POLYLINE6);
+        assert_eq!(r.duration_deltas.len(), 1);
+        assert!((r.duration_stats.mean - 0.1).abs() < 1e-9);
+        assert_eq!(r.geometry_overlap, Some(1.0));
+        assert_eq!(
+            r.mismatches,
+            vec![ShadowMismatch::Shape {
+                primary: (2, 1),
+                shadow: (1, 1)
+            }]
+        );
+
+        let (tx, rx) = sync_channel(10);
+        report("singapore", "directions", &r, Some(&tx));
+        assert_eq!(rx.try_iter().count(), 3);
+    }
+
+    #[test]
+    fn test_compare_matrix() {
+        let matrix = |values: &[&[i64]]| -> MatrixOutput {
+            let rows: Vec<serde_json::Value> = values
+                .iter()
+                .map(|row| {
+                    let elements: Vec<serde_json::Value> = row
+                        .iter()
+                        .map(|v| json!({"duration": {"value": v}, "distance": {"value": 1}}))
+                        .collect();
+                    json!({ "elements": elements })
+                })
+                .collect();
+            serde_json::from_value(json!({"status": "Ok", "rows": rows})).unwrap()
+        };
+        let primary = matrix(&[&[0, 100], &[200, -1]]);
+        let shadow = matrix(&[&[0, 150], &[-1, -1]]);
+        let r = compare_matrix(&primary, &shadow);
+        assert_eq!(r.duration_deltas, vec![0.0, 0.5]);
+        assert_eq!(r.duration_stats.p50, 0.0);
+        assert_eq!(r.duration_stats.p90, 0.5);
+        assert_eq!(
+            r.mismatches,
+            vec![ShadowMismatch::Reachability { row: 1, col: 0 }]
+        );
+        assert!(r.geometry_overlap.is_none());
+    }
+}
@@ -0,0 +1,270 @@
+use crate::coord::Locatable;
+use crate::prepared_polygon::PreparedPolygon;
+use crate::util::Area;
+use geo::algorithm::euclidean_distance::EuclideanDistance;
+use geo::prelude::{BoundingRect, Contains};
+use geo::simplify::Simplify;
+use geo::{Point, Polygon, Rect};
+use std::collections::HashMap;
+
+// length of a degree of latitude
+const METERS_PER_DEGREE: f64 = 111_320.0;
+
+// AreaPolygons keeps simplified polygons for fast point-in-polygon checks and the original
+//  ones for an exact second pass on points close to the simplified boundary
+#[derive(Clone, Debug)]
+pub struct AreaPolygons {
+    pub original: Vec<Polygon<f64>>,
+    // None when simplification is disabled
+    pub simplified: Option<Vec<Polygon<f64>>>,
+    // max distance between the simplified and the original boundaries, in degrees
+    pub tolerance_degrees: f64,
+    bounding_rects: Vec<Option<Rect<f64>>>,
+}
+
+// tolerance_to_degrees converts meters into degrees at the highest latitude of the polygons,
+//  where a degree of longitude is the shortest
+pub(crate) fn tolerance_to_degrees(polygons: &[Polygon<f64>], tolerance_meters: f64) -> f64 {
+    let max_lat = polygons
+        .iter()
+        .flat_map(|p| p.exterior().0.iter())
+        .map(|c| c.y.abs())
+        .fold(0.0_f64, f64::max)
+        .min(89.0);
+    tolerance_meters / (METERS_PER_DEGREE * max_lat.to_radians().cos())
+}
+
+// simplify_polygon simplifies the boundaries with the Ramer–Douglas–Peucker algorithm, a
+//  polygon which would collapse is kept as is
+pub(crate) fn simplify_polygon(polygon: &Polygon<f64>, tolerance_degrees: f64) -> Polygon<f64> {
+    let s = polygon.simplify(&tolerance_degrees);
+    if s.exterior().0.len() < 4 {
+        return polygon.clone();
+    }
+    s
+}
+
+pub(crate) fn near_boundary(polygon: &Polygon<f64>, p: &Point<f64>, tolerance: f64) -> bool {
+    polygon.exterior().euclidean_distance(p) <= tolerance
+        || polygon
+            .interiors()
+            .iter()
+            .any(|i| i.euclidean_distance(p) <= tolerance)
+}
+
+impl AreaPolygons {
+    pub fn new(original: Vec<Polygon<f64>>) -> AreaPolygons {
+        let bounding_rects = original.iter().map(|p| p.bounding_rect()).collect();
+        AreaPolygons {
+            original,
+            simplified: None,
+            tolerance_degrees: 0.0,
+            bounding_rects,
+        }
+    }
+
+    // simplified simplifies the boundaries, see simplify_polygon
+    pub fn simplified(original: Vec<Polygon<f64>>, tolerance_meters: f64) -> AreaPolygons {
+        let mut r = AreaPolygons::new(original);
+        if tolerance_meters <= 0.0 {
+            return r;
+        }
+        let tolerance = tolerance_to_degrees(&r.original, tolerance_meters);
+        let simplified = r
+            .original
+            .iter()
+            .map(|p| simplify_polygon(p, tolerance))
+            .collect();
+        r.simplified = Some(simplified);
+        r.tolerance_degrees = tolerance;
+        r
+    }
+
+    pub fn vertex_count(&self) -> (usize, usize) {
+        let count = |ps: &[Polygon<f64>]| -> usize {
+            ps.iter()
+                .map(|p| {
+                    p.exterior().0.len() + p.interiors().iter().map(|i| i.0.len()).sum::<usize>()
+                })
+                .sum()
+        };
+        (
+            count(&self.original),
+            self.simplified.as_ref().map(|s| count(s)).unwrap_or(0),
+        )
+    }
+
+    pub fn contains<T: Locatable>(&self, c: &T) -> bool {
+        let p = Point::<f64>::new(c.lng(), c.lat());
+        let simplified = match self.simplified.as_ref() {
+            Some(v) => v,
+            None => return self.original_contains(&p),
+        };
+        for (idx, polygon) in simplified.iter().enumerate() {
+            // points within the tolerance of the simplified boundary may be classified
+            //  differently than with the original polygon
+            if near_boundary(polygon, &p, self.tolerance_degrees) {
+                if self.original[idx].contains(&p) {
+                    return true;
+                }
+                continue;
+            }
+            if polygon.contains(&p) {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn original_contains(&self, p: &Point<f64>) -> bool {
+        for (idx, polygon) in self.original.iter().enumerate() {
+            if let Some(rect) = self.bounding_rects[idx] {
+                if p.x() < rect.min().x
+                    || p.x() > rect.max().x
+                    || p.y() < rect.min().y
+                    || p.y() > rect.max().y
+                {
+                    continue;
+                }
+            }
+            if polygon.contains(p) {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+// build_area_polygons applies the simplification configured for each area at load time
+pub fn build_area_polygons(
+    areas: &[Area],
+    polygons: HashMap<String, Vec<Polygon<f64>>>,
+) -> HashMap<String, AreaPolygons> {
+    let tolerances: HashMap<&str, f64> = areas
+        .iter()
+        .filter_map(|a| a.simplify_tolerance_meters.map(|t| (a.name.as_str(), t)))
+        .collect();
+    polygons
+        .into_iter()
+        .map(|(name, ps)| {
+            let area_polygons = match tolerances.get(name.as_str()) {
+                Some(t) => {
+                    let r = AreaPolygons::simplified(ps, *t);
+                    let (original, simplified) = r.vertex_count();
+                    info!(
+                        "simplified polygons of {} from {} to {} vertices",
+                        &name, original, simplified
+                    );
+                    r
+                }
+                None => AreaPolygons::new(ps),
+            };
+            (name, area_polygons)
+        })
+        .collect()
+}
+
+// simplify_area_polygons simplifies the prepared polygons of the areas configured with a
+//  simplify_tolerance_meters, for find_area
+pub fn simplify_area_polygons(
+    polygons: HashMap<String, Vec<PreparedPolygon>>,
+    areas: &[Area],
+) -> HashMap<String, Vec<PreparedPolygon>> {
+    let tolerances: HashMap<&str, f64> = areas
+        .iter()
+        .filter_map(|a| a.simplify_tolerance_meters.map(|t| (a.name.as_str(), t)))
+        .collect();
+    polygons
+        .into_iter()
+        .map(|(name, ps)| match tolerances.get(name.as_str()) {
+            Some(t) => {
+                let ps: Vec<PreparedPolygon> =
+                    ps.into_iter().map(|p| p.with_simplified(*t)).collect();
+                info!(
+                    "simplified polygons of {} with a tolerance of {}m",
+                    &name, t
+                );
+                (name, ps)
+            }
+            None => (name, ps),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coord::Coord;
+    use crate::prepared_polygon::prepare_polygons;
+    use crate::testkit::{random_coords, AreaBuilder};
+    use geo::LineString;
+
+    // dense circle-ish polygon with a jagged border
+    fn dense_polygon(center: &Coord, radius: f64, n: usize) -> Polygon<f64> {
+        let coords: Vec<(f64, f64)> = (0..n)
+            .map(|i| {
+                let angle = i as f64 / n as f64 * std::f64::consts::PI * 2.0;
+                let r = radius * (1.0 + 0.001 * (i % 2) as f64);
+                (
+                    center.lng() + r * angle.cos(),
+                    center.lat() + r * angle.sin(),
+                )
+            })
+            .collect();
+        Polygon::new(LineString::from(coords), vec![])
+    }
+
+    #[test]
+    fn test_simplified_contains() {
+        let center = Coord::new(1.3, 103.8);
+        let polygon = dense_polygon(&center, 0.5, 20000);
+        let exact = AreaPolygons::new(vec![polygon.clone()]);
+
+        let areas = vec![AreaBuilder::new("singapore", "4w")
+            .simplify_tolerance_meters(100.0)
+            .build()];
+        let mut polygons = HashMap::new();
+        polygons.insert("singapore".to_string(), vec![polygon]);
+        let built = build_area_polygons(&areas, polygons);
+        let simplified = built.get("singapore").unwrap();
+
+        let (original_count, simplified_count) = simplified.vertex_count();
+        assert_eq!(original_count, 20001);
+        assert!(simplified_count * 10 < original_count);
+
+        // same results as the original polygon, including points close to the border
+        for c in random_coords(&center, 0.51, 2000, 9) {
+            assert_eq!(simplified.contains(&c), exact.contains(&c));
+        }
+        for c in random_coords(&Coord::new(1.3, 104.3), 0.002, 2000, 10) {
+            assert_eq!(simplified.contains(&c), exact.contains(&c));
+        }
+
+        // prepared polygons used by find_area
+        let mut prepared = HashMap::new();
+        prepared.insert(
+            "singapore".to_string(),
+            prepare_polygons(exact.original.clone()),
+        );
+        prepared.insert(
+            "other".to_string(),
+            prepare_polygons(exact.original.clone()),
+        );
+        let prepared = simplify_area_polygons(prepared, &areas);
+        assert!(prepared["singapore"][0].simplified().is_some());
+        assert!(prepared["other"][0].simplified().is_none());
+        let simplified_count = prepared["singapore"][0]
+            .simplified()
+            .unwrap()
+            .exterior()
+            .0
+            .len();
+        assert!(simplified_count * 10 < original_count);
+        let coords = random_coords(&center, 0.51, 2000, 11)
+            .into_iter()
+            .chain(random_coords(&Coord::new(1.3, 104.3), 0.002, 2000, 12));
+        for c in coords {
+            assert_eq!(c.is_in_polygons(&prepared["singapore"]), exact.contains(&c));
+        }
+    }
+}
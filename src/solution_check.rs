@@ -0,0 +1,273 @@
+// post-check of the solutions of the solver against the constraints of the request. the
+//  arrival at each step is recomputed from the cumulated travel durations of the route, the
+//  waiting for time windows and the setup and service of the previous steps, then compared
+//  with `max_visit_lateness` for tasks and `max_vehicle_overtime` for the end of vehicles.
+//  unset constraints allow no lateness or overtime at all
+use crate::def::{OptimizationV2PostInput, VRoomResult, VRoomRoute, Violation};
+use std::collections::HashMap;
+
+pub const CAUSE_VISIT_LATENESS: &str = "max_visit_lateness";
+pub const CAUSE_VEHICLE_OVERTIME: &str = "max_vehicle_overtime";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ViolationKind {
+    VisitLateness,
+    VehicleOvertime,
+}
+
+impl ViolationKind {
+    pub fn cause(&self) -> &'static str {
+        match self {
+            ViolationKind::VisitLateness => CAUSE_VISIT_LATENESS,
+            ViolationKind::VehicleOvertime => CAUSE_VEHICLE_OVERTIME,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SolutionViolation {
+    pub kind: ViolationKind,
+    pub vehicle: Option<u64>,
+    // index of the step in the route
+    pub step: usize,
+    // id of the task of the step, None for the end of the route
+    pub task: Option<u64>,
+    // lateness or overtime beyond what is allowed, in seconds
+    pub duration: f64,
+}
+
+impl SolutionViolation {
+    pub fn to_violation(&self) -> Violation {
+        Violation {
+            cause: Some(self.kind.cause().to_string()),
+            duration: Some(self.duration),
+        }
+    }
+}
+
+// time windows of the tasks and breaks, by step type and id
+fn time_windows(input: &OptimizationV2PostInput) -> HashMap<(String, u64), Vec<(f64, f64)>> {
+    let mut r = HashMap::new();
+    let windows = |tw: &Vec<Vec<u64>>| -> Vec<(f64, f64)> {
+        tw.iter()
+            .filter(|w| w.len() == 2)
+            .map(|w| (w[0] as f64, w[1] as f64))
+            .collect()
+    };
+    for job in input.jobs.iter().flatten() {
+        if let Some(tw) = job.time_windows.as_ref() {
+            r.insert(("job".to_string(), job.id), windows(tw));
+        }
+    }
+    for shipment in input.shipments.iter().flatten() {
+        for (kind, step) in [
+            ("pickup", &shipment.pickup),
+            ("delivery", &shipment.delivery),
+        ] {
+            if let Some(tw) = step.time_windows.as_ref() {
+                r.insert((kind.to_string(), step.id), windows(tw));
+            }
+        }
+    }
+    for vehicle in input.vehicles.iter() {
+        for b in vehicle
+            .breaks
+            .iter()
+            .flatten()
+            .chain(vehicle.r#break.iter())
+        {
+            let tw = b
+                .time_windows
+                .iter()
+                .filter(|w| w.len() == 2)
+                .map(|w| (w[0] as f64, w[1] as f64))
+                .collect();
+            r.insert(("break".to_string(), b.id), tw);
+        }
+    }
+    r
+}
+
+// start of service and lateness when arriving at arrival, the service starts in the first
+//  window not over yet, and is late past the end of the last window
+fn service_start(arrival: f64, windows: &[(f64, f64)]) -> (f64, f64) {
+    match windows.iter().find(|w| w.1 >= arrival) {
+        Some(w) => (arrival.max(w.0), 0.0),
+        None => {
+            let last = windows.iter().map(|w| w.1).fold(f64::MIN, f64::max);
+            if windows.is_empty() {
+                (arrival, 0.0)
+            } else {
+                (arrival, arrival - last)
+            }
+        }
+    }
+}
+
+fn verify_route(
+    route: &VRoomRoute,
+    input: &OptimizationV2PostInput,
+    windows: &HashMap<(String, u64), Vec<(f64, f64)>>,
+    violations: &mut Vec<SolutionViolation>,
+) {
+    let constraint = input.options.as_ref().and_then(|o| o.constraint.as_ref());
+    let max_lateness = constraint.and_then(|c| c.max_visit_lateness).unwrap_or(0) as f64;
+    let max_overtime = constraint.and_then(|c| c.max_vehicle_overtime).unwrap_or(0) as f64;
+    let vehicle = route
+        .vehicle
+        .and_then(|id| input.vehicles.iter().find(|v| v.id == id));
+
+    let mut time: Option<f64> = None;
+    let mut travelled = 0.0;
+    for (i, step) in route.steps.iter().flatten().enumerate() {
+        let cumulated = step.duration.unwrap_or(travelled);
+        let arrival = match time {
+            Some(t) => t + (cumulated - travelled),
+            None => step.arrival.unwrap_or(0.0),
+        };
+        travelled = cumulated;
+        let step_type = step.step_type.as_deref().unwrap_or("");
+        let task_windows = step
+            .id
+            .and_then(|id| windows.get(&(step_type.to_string(), id)))
+            .map(|w| w.as_slice())
+            .unwrap_or(&[]);
+        let (start, lateness) = service_start(arrival, task_windows);
+        if step_type != "break" && lateness > max_lateness {
+            violations.push(SolutionViolation {
+                kind: ViolationKind::VisitLateness,
+                vehicle: route.vehicle,
+                step: i,
+                task: step.id,
+                duration: lateness - max_lateness,
+            });
+        }
+        if step_type == "end" {
+            let end = vehicle
+                .and_then(|v| v.time_window.as_ref())
+                .and_then(|tw| tw.get(1));
+            if let Some(end) = end {
+                let overtime = arrival - end;
+                if overtime > max_overtime {
+                    violations.push(SolutionViolation {
+                        kind: ViolationKind::VehicleOvertime,
+                        vehicle: route.vehicle,
+                        step: i,
+                        task: None,
+                        duration: overtime - max_overtime,
+                    });
+                }
+            }
+        }
+        time = Some(start + step.setup.unwrap_or(0) as f64 + step.service.unwrap_or(0) as f64);
+    }
+}
+
+// verify_solution returns the violations of the constraints of input by the routes of result
+pub fn verify_solution(
+    result: &VRoomResult,
+    input: &OptimizationV2PostInput,
+) -> Vec<SolutionViolation> {
+    let windows = time_windows(input);
+    let mut violations = vec![];
+    for route in result.routes.iter().flatten() {
+        verify_route(route, input, &windows, &mut violations);
+    }
+    violations
+}
+
+// annotate adds the violations to the steps and routes of result, the way the solver reports
+//  its own
+pub fn annotate(result: &mut VRoomResult, violations: &[SolutionViolation]) {
+    for route in result.routes.iter_mut().flatten() {
+        let vehicle = route.vehicle;
+        for v in violations.iter().filter(|v| v.vehicle == vehicle) {
+            if let Some(step) = route.steps.as_mut().and_then(|s| s.get_mut(v.step)) {
+                step.violations
+                    .get_or_insert_with(Vec::new)
+                    .push(v.to_violation());
+            }
+            route
+                .violations
+                .get_or_insert_with(Vec::new)
+                .push(v.to_violation());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn input(constraint: serde_json::Value) -> OptimizationV2PostInput {
+        serde_json::from_value(json!({
+            "locations": {"id": 1, "location": ["1.30,103.80", "1.31,103.81"]},
+            "jobs": [
+                {"id": 1, "location_index": 1, "service": 600, "time_windows": [[0, 1000]]},
+                {"id": 2, "location_index": 1, "time_windows": [[0, 1500]]},
+            ],
+            "vehicles": [{"id": 1, "start_index": 0, "time_window": [0.0, 2000.0]}],
+            "options": {"constraint": constraint},
+        }))
+        .unwrap()
+    }
+
+    fn result() -> VRoomResult {
+        serde_json::from_value(json!({
+            "code": 0,
+            "routes": [{
+                "vehicle": 1,
+                "steps": [
+                    {"type": "start", "arrival": 0.0, "duration": 0.0},
+                    // reported on time, but 900s of travel from the start and a service of
+                    //  600s make job 2 arrive at 1600
+                    {"type": "job", "id": 1, "arrival": 900.0, "duration": 900.0, "service": 600},
+                    {"type": "job", "id": 2, "arrival": 1400.0, "duration": 1000.0},
+                    {"type": "end", "arrival": 1500.0, "duration": 1500.0},
+                ],
+            }],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_verify_solution() {
+        let violations = verify_solution(&result(), &input(json!(null)));
+        assert_eq!(
+            violations,
+            vec![
+                SolutionViolation {
+                    kind: ViolationKind::VisitLateness,
+                    vehicle: Some(1),
+                    step: 2,
+                    task: Some(2),
+                    duration: 100.0,
+                },
+                SolutionViolation {
+                    kind: ViolationKind::VehicleOvertime,
+                    vehicle: Some(1),
+                    step: 3,
+                    task: None,
+                    duration: 100.0,
+                },
+            ]
+        );
+
+        let allowed = input(json!({"max_visit_lateness": 100, "max_vehicle_overtime": 60}));
+        let violations = verify_solution(&result(), &allowed);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].kind, ViolationKind::VehicleOvertime);
+        assert_eq!(violations[0].duration, 40.0);
+
+        let mut result = result();
+        annotate(&mut result, &violations);
+        let route = &result.routes.as_ref().unwrap()[0];
+        let step = &route.steps.as_ref().unwrap()[3];
+        assert_eq!(
+            step.violations.as_ref().unwrap()[0].cause.as_deref(),
+            Some(CAUSE_VEHICLE_OVERTIME)
+        );
+        assert_eq!(route.violations.as_ref().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,245 @@
+// speed limit compliance of snapped traces, for post-trip analysis. the snapped points are
+//  placed along the snapped geometry, the speed between two consecutive points is the distance
+//  along the geometry over the time between their timestamps, and it is compared with the max
+//  speed of the nodes of the geometry (SnapNodeInfo), which applies from its node to the node
+//  of the next value
+use crate::coord::{cumulative_distances, project_on_path, Coord, Locatable};
+use crate::def::{SnapNodeInfoItem, SnapOutput};
+use crate::polyline;
+use crate::Result;
+use paperclip::actix::Apiv2Schema;
+use serde::{Deserialize, Serialize};
+
+// snapped points farther than this from a geometry belong to another one
+const MAX_SNAP_DISTANCE_METERS: f64 = 5.0;
+// tolerance when joining consecutive violations
+const JOIN_EPSILON_METERS: f64 = 0.01;
+
+#[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct SpeedViolation {
+    #[doc = "distance along the geometry where the violation starts.\n\nUnit: `meters`"]
+    pub start_offset: f64,
+    #[doc = "distance along the geometry where the violation ends.\n\nUnit: `meters`"]
+    pub end_offset: f64,
+    #[doc = "average speed observed over the violation.\n\nUnit: `km/h`"]
+    pub observed_speed: f64,
+    #[doc = "max speed of the road.\n\nUnit: `km/h`"]
+    pub max_speed: f64,
+}
+
+// limit_ranges converts the max speed of nodes into (start offset, end offset, max speed),
+//  unknown limits (0 or less) are skipped
+fn limit_ranges(offsets: &[f64], max_speed: &[SnapNodeInfoItem]) -> Vec<(f64, f64, f64)> {
+    let total = offsets.last().cloned().unwrap_or_default();
+    let offset_of = |index: u64| offsets.get(index as usize).cloned().unwrap_or(total);
+    let mut r = vec![];
+    for (i, item) in max_speed.iter().enumerate() {
+        let end = max_speed
+            .get(i + 1)
+            .map(|next| offset_of(next.index))
+            .unwrap_or(total);
+        let start = offset_of(item.index);
+        if item.value > 0.0 && end > start {
+            r.push((start, end, item.value));
+        }
+    }
+    r
+}
+
+// speed_violations returns the ranges of geometry where the trace went faster than the max
+//  speed plus tolerance_kmh. trace holds the snapped points with their unix timestamps, in
+//  order
+pub fn speed_violations<T: Locatable, C: Locatable>(
+    geometry: &[T],
+    max_speed: &[SnapNodeInfoItem],
+    trace: &[(C, i64)],
+    tolerance_kmh: f64,
+) -> Vec<SpeedViolation> {
+    let offsets = cumulative_distances(geometry);
+    let limits = limit_ranges(&offsets, max_speed);
+    let mut segment = 0;
+    let mut placed = vec![];
+    for (c, ts) in trace.iter() {
+        if let Some((i, ratio, _)) = project_on_path(geometry, c, segment) {
+            segment = i;
+            placed.push((offsets[i] + ratio * (offsets[i + 1] - offsets[i]), *ts));
+        }
+    }
+
+    // violations with the seconds spent in them, to average the speed of joined ones
+    let mut r: Vec<(SpeedViolation, f64)> = vec![];
+    for w in placed.windows(2) {
+        let ((from, t0), (to, t1)) = (w[0], w[1]);
+        if t1 <= t0 || to <= from {
+            continue;
+        }
+        let speed = (to - from) / (t1 - t0) as f64 * 3.6;
+        for &(start, end, limit) in limits.iter() {
+            let (start, end) = (start.max(from), end.min(to));
+            if end <= start || speed <= limit + tolerance_kmh {
+                continue;
+            }
+            let seconds = (end - start) / speed * 3.6;
+            match r.last_mut() {
+                Some((v, s))
+                    if v.max_speed == limit
+                        && (v.end_offset - start).abs() < JOIN_EPSILON_METERS =>
+                {
+                    *s += seconds;
+                    v.end_offset = end;
+                    v.observed_speed = (v.end_offset - v.start_offset) / *s * 3.6;
+                }
+                _ => r.push((
+                    SpeedViolation {
+                        start_offset: start,
+                        end_offset: end,
+                        observed_speed: speed,
+                        max_speed: limit,
+                    },
+                    seconds,
+                )),
+            }
+        }
+    }
+    r.into_iter().map(|(v, _)| v).collect()
+}
+
+// annotate_snap_output sets the speed violations of each geometry of output, timestamps being
+//  those of the input points the snapped points refer to by their original index. geometries
+//  without max speed get None
+pub fn annotate_snap_output(
+    output: &mut SnapOutput,
+    timestamps: &[i64],
+    precision: u32,
+    tolerance_kmh: f64,
+) -> Result<()> {
+    let geometries = match output.geometry.as_ref() {
+        Some(v) => v,
+        None => return Ok(()),
+    };
+    let mut r = vec![];
+    for (g, geometry) in geometries.iter().enumerate() {
+        let max_speed = output
+            .snap_node_info
+            .as_ref()
+            .and_then(|v| v.get(g))
+            .and_then(|v| v.as_ref())
+            .and_then(|v| v.max_speed.as_ref());
+        let (geometry, max_speed) = match (geometry, max_speed) {
+            (Some(geometry), Some(max_speed)) => {
+                (polyline::decode(geometry, precision)?, max_speed)
+            }
+            _ => {
+                r.push(None);
+                continue;
+            }
+        };
+        let trace: Vec<(Coord, i64)> = output
+            .snapped_points
+            .iter()
+            .filter_map(|p| {
+                let ts = *timestamps.get(p.original_index as usize)?;
+                let (_, _, distance) = project_on_path(&geometry, &p.location, 0)?;
+                if distance > MAX_SNAP_DISTANCE_METERS {
+                    return None;
+                }
+                Some((Coord::new(p.location.lat(), p.location.lng()), ts))
+            })
+            .collect();
+        r.push(Some(speed_violations(
+            &geometry,
+            max_speed,
+            &trace,
+            tolerance_kmh,
+        )));
+    }
+    output.speed_violations = Some(r);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coord::path_length;
+    use crate::def::{Location, SnapNodeInfo, SnappedPoint};
+
+    fn item(index: u64, value: f64) -> SnapNodeInfoItem {
+        SnapNodeInfoItem { index, value }
+    }
+
+    #[test]
+    fn test_speed_violations() {
+        // 11 nodes ~1112 m apart along the equator, 50 km/h on the first half, 100 after
+        let geometry: Vec<Coord> = (0..=10).map(|i| Coord::new(0.0, i as f64 * 0.01)).collect();
+        let max_speed = vec![item(0, 50.0), item(5, 100.0)];
+        let step = path_length(&geometry) / 10.0;
+        // 1 node per minute is ~67 km/h, over the limit of the first half only
+        let trace: Vec<(Coord, i64)> = geometry
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (c.clone(), i as i64 * 60))
+            .collect();
+        let r = speed_violations(&geometry, &max_speed, &trace, 0.0);
+        assert_eq!(r.len(), 1);
+        assert!(r[0].start_offset.abs() < 1e-6);
+        assert!((r[0].end_offset - 5.0 * step).abs() < 1e-6);
+        assert!((r[0].observed_speed - step * 60.0 / 1000.0).abs() < 1e-6);
+        assert_eq!(r[0].max_speed, 50.0);
+        // tolerated
+        assert!(speed_violations(&geometry, &max_speed, &trace, 20.0).is_empty());
+        // unknown limits are skipped
+        assert!(speed_violations(&geometry, &[item(0, 0.0)], &trace, 0.0).is_empty());
+
+        // 3 points, the second half driven at 2 nodes per minute
+        let trace = vec![
+            (geometry[0].clone(), 0),
+            (geometry[5].clone(), 600),
+            (geometry[10].clone(), 750),
+        ];
+        let r = speed_violations(&geometry, &max_speed, &trace, 0.0);
+        assert_eq!(r.len(), 1);
+        assert_eq!(r[0].max_speed, 100.0);
+        assert!((r[0].start_offset - 5.0 * step).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_annotate_snap_output() {
+        let geometry: Vec<Coord> = (0..=10).map(|i| Coord::new(0.0, i as f64 * 0.01)).collect();
+        let point = |i: usize| SnappedPoint {
+            location: Location {
+                latitude: geometry[i].lat(),
+                longitude: geometry[i].lng(),
+            },
+            original_index: i as u64,
+            distance: 0.0,
+            name: String::new(),
+            bearing: 0.0,
+        };
+        let mut output: SnapOutput = serde_json::from_value(serde_json::json!({
+            "status": "Ok",
+            "snappedPoints": [],
+            "distance": 0,
+        }))
+        .unwrap();
+        output.snapped_points = vec![point(0), point(10)];
+        output.geometry = Some(vec![Some(polyline::encode(&geometry, 5)), None]);
+        output.snap_node_info = Some(vec![
+            Some(SnapNodeInfo {
+                max_speed: Some(vec![item(0, 30.0)]),
+            }),
+            None,
+        ]);
+        annotate_snap_output(
+            &mut output,
+            &[0, 60, 60, 60, 60, 60, 60, 60, 60, 60, 600],
+            5,
+            0.0,
+        )
+        .unwrap();
+        let r = output.speed_violations.unwrap();
+        assert_eq!(r.len(), 2);
+        assert_eq!(r[0].as_ref().unwrap().len(), 1);
+        assert!(r[1].is_none());
+    }
+}
@@ -1,13 +1,28 @@
+use crate::def::AdaptError;
 use prometheus::core::Collector;
 use prometheus::{unregister, CounterVec, Encoder, GaugeVec, HistogramVec, TextEncoder};
 use std::collections::HashMap;
 use std::sync::mpsc::{sync_channel, SyncSender};
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
 use std::thread;
 
 pub const LABELNAME_APPNAME: &str = "appname";
 pub const LABELNAME_SINK_TO: &str = "sink_to";
 
+pub const METRIC_ENGINE_ERRORS: &str = "engine_errors_total";
+pub const LABELNAME_ADAPT_ERROR: &str = "adapt_error";
+pub const LABELNAME_ENGINE: &str = "engine";
+pub const LABELNAME_AREA: &str = "area";
+pub const LABELNAME_MODE: &str = "mode";
+
+pub const METRIC_METERED_USAGE: &str = "metered_usage_total";
+pub const LABELNAME_API: &str = "api";
+pub const LABELNAME_SKU: &str = "sku";
+
+lazy_static! {
+    static ref ENGINE_ERROR_SENDER: Mutex<Option<SyncSender<TypedTrackInput>>> = Mutex::new(None);
+}
+
 pub enum MetricType {
     Counter,
     Histogram,
@@ -270,6 +285,91 @@ impl StatsdCollector {
         label_values
     }
 }
+
+// standard_metrics are the metrics tracked by the crate itself, to be registered by every
+//  service next to its own ones
+pub fn standard_metrics() -> Vec<RegisterMetricInput> {
+    vec![
+        RegisterMetricInput {
+            metric_type: MetricType::Counter,
+            metric_name: METRIC_ENGINE_ERRORS.to_string(),
+            metric_desc: "number of engine errors by adapted error".to_string(),
+            labels: vec![
+                LABELNAME_ADAPT_ERROR.to_string(),
+                LABELNAME_ENGINE.to_string(),
+                LABELNAME_AREA.to_string(),
+                LABELNAME_MODE.to_string(),
+            ],
+        },
+        RegisterMetricInput {
+            metric_type: MetricType::Counter,
+            metric_name: METRIC_METERED_USAGE.to_string(),
+            metric_desc: "units of usage metered by api and sku".to_string(),
+            labels: vec![
+                LABELNAME_API.to_string(),
+                LABELNAME_MODE.to_string(),
+                LABELNAME_SKU.to_string(),
+            ],
+        },
+    ]
+}
+
+// set_engine_error_sender makes handle_error_message record the errors it adapts
+pub fn set_engine_error_sender(sender: SyncSender<TypedTrackInput>) {
+    *ENGINE_ERROR_SENDER.lock().unwrap() = Some(sender);
+}
+
+pub(crate) fn engine_error_sender() -> Option<SyncSender<TypedTrackInput>> {
+    ENGINE_ERROR_SENDER.lock().unwrap().clone()
+}
+
+pub fn record_engine_error(
+    sender: &SyncSender<TypedTrackInput>,
+    engine: &str,
+    error: &AdaptError,
+    area: &str,
+    mode: &str,
+) {
+    let mut labels = HashMap::new();
+    labels.insert(LABELNAME_ADAPT_ERROR.to_string(), format!("{:?}", error));
+    labels.insert(LABELNAME_ENGINE.to_string(), engine.to_string());
+    labels.insert(LABELNAME_AREA.to_string(), area.to_string());
+    labels.insert(LABELNAME_MODE.to_string(), mode.to_string());
+    let r = sender.try_send(TypedTrackInput::Counter(TrackCountInput {
+        metric_name: METRIC_ENGINE_ERRORS.to_string(),
+        count: 1.0,
+        labels,
+    }));
+    if let Err(e) = r {
+        warn!("failed to track engine error due to {:?}", e);
+    }
+}
+
+// record_metered_usage tracks count units of api, sku is empty when the key has none for api.
+//  the usage of each key is metered by metering::Meter
+pub fn record_metered_usage(
+    sender: &SyncSender<TypedTrackInput>,
+    api: &str,
+    mode: &str,
+    sku: Option<i64>,
+    count: f64,
+) {
+    let mut labels = HashMap::new();
+    labels.insert(LABELNAME_API.to_string(), api.to_string());
+    labels.insert(LABELNAME_MODE.to_string(), mode.to_string());
+    labels.insert(
+        LABELNAME_SKU.to_string(),
+        sku.map(|v| v.to_string()).unwrap_or_default(),
+    );
+    let r = sender.try_send(TypedTrackInput::Counter(TrackCountInput {
+        metric_name: METRIC_METERED_USAGE.to_string(),
+        count,
+        labels,
+    }));
+    if let Err(e) = r {
+        warn!("failed to track metered usage due to {:?}", e);
+    }
+}
 //
 // #[cfg(test)]
 // mod test {
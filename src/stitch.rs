@@ -0,0 +1,95 @@
+// stitching of the per leg geometries of optimization trips into the geometry of the trip.
+//  consecutive legs share their endpoint, which is kept once. leg_offsets gives the index of
+//  the first point of each leg in the trip, so that the geometry offsets of the steps of leg i
+//  become leg_offsets[i] + offset in the trip
+use crate::coord::Coord;
+use crate::def::{GeoJSONFeature, GeoJSONLineString, GeoJSONObject, GeoJSONType};
+use crate::polyline;
+use crate::Result;
+
+#[derive(Clone, Debug)]
+pub struct StitchedGeometry {
+    // encoded with the precision of the legs
+    pub geometry: String,
+    pub geojson: GeoJSONFeature,
+    pub leg_offsets: Vec<usize>,
+}
+
+// same point once encoded with precision
+fn same_point(a: &Coord, b: &Coord, factor: f64) -> bool {
+    (a.lat * factor).round() == (b.lat * factor).round()
+        && (a.lng * factor).round() == (b.lng * factor).round()
+}
+
+pub fn geojson_line(coords: &[Coord]) -> GeoJSONFeature {
+    GeoJSONFeature {
+        geojson_type: GeoJSONType::Feature,
+        geometry: GeoJSONObject::LineString(GeoJSONLineString {
+            geojson_type: GeoJSONType::LineString,
+            coordinates: coords.iter().map(|c| vec![c.lng, c.lat]).collect(),
+        }),
+        properties: None,
+    }
+}
+
+pub fn stitch_geometries(legs: &[String], precision: u32) -> Result<StitchedGeometry> {
+    let factor = 10_f64.powi(precision as i32);
+    let mut coords: Vec<Coord> = vec![];
+    let mut leg_offsets = vec![];
+    for (i, leg) in legs.iter().enumerate() {
+        let points = polyline::decode(leg, precision)
+            .map_err(|e| format!("geometry of leg {} is invalid: {}", i, e))?;
+        let shared = match (coords.last(), points.first()) {
+            (Some(last), Some(first)) => same_point(last, first, factor),
+            _ => false,
+        };
+        if shared || points.is_empty() {
+            leg_offsets.push(coords.len().saturating_sub(1));
+            coords.extend(points.into_iter().skip(1));
+        } else {
+            leg_offsets.push(coords.len());
+            coords.extend(points);
+        }
+    }
+    Ok(StitchedGeometry {
+        geometry: polyline::encode(&coords, precision),
+        geojson: geojson_line(&coords),
+        leg_offsets,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polyline::PRECISION_POLYLINE6;
+
+    #[test]
+    fn test_stitch_geometries() {
+        let a = Coord::new(1.3, 103.8);
+        let b = Coord::new(1.31, 103.81);
+        let c = Coord::new(1.32, 103.82);
+        let d = Coord::new(1.33, 103.83);
+        let legs = vec![
+            polyline::encode(&[a.clone(), b.clone()], PRECISION_POLYLINE6),
+            polyline::encode(&[b.clone(), c.clone()], PRECISION_POLYLINE6),
+            // does not start where the previous leg ends
+            polyline::encode(&[d.clone(), a.clone()], PRECISION_POLYLINE6),
+        ];
+        let stitched = stitch_geometries(&legs, PRECISION_POLYLINE6).unwrap();
+        let expected = vec![a.clone(), b, c, d, a];
+        assert_eq!(
+            stitched.geometry,
+            polyline::encode(&expected, PRECISION_POLYLINE6)
+        );
+        assert_eq!(stitched.leg_offsets, vec![0, 1, 3]);
+        match stitched.geojson.geometry {
+            GeoJSONObject::LineString(line) => {
+                assert_eq!(line.coordinates.len(), 5);
+                assert_eq!(line.coordinates[0], vec![103.8, 1.3]);
+            }
+            _ => panic!("expected a line string"),
+        }
+
+        assert!(stitch_geometries(&["_p~iF~ps|U".to_string(), "?".to_string()], 5).is_err());
+    }
+}
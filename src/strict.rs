@@ -0,0 +1,184 @@
+// strict parsing of request bodies: in strict mode, fields that are not part of the input type
+//  are reported instead of being ignored, with the closest known field as a suggestion so
+//  that typos such as `departure_tme` are easy to spot. the known fields are taken from the
+//  Apiv2Schema of the type, which follows the serde renames
+use crate::Result;
+use paperclip::v2::schema::Apiv2Schema;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum InputMode {
+    // unknown fields are ignored
+    #[default]
+    Lenient,
+    // unknown fields are rejected
+    Strict,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnknownField {
+    // path of the field in the input, such as `matrix_input.origns`
+    pub path: String,
+    pub suggestion: Option<String>,
+}
+
+impl fmt::Display for UnknownField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown field `{}`", self.path)?;
+        if let Some(s) = self.suggestion.as_ref() {
+            write!(f, ", did you mean `{}`?", s)?;
+        }
+        Ok(())
+    }
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + if ca == *cb { 0 } else { 1 };
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+// suggest returns the known field closest to name, if it is close enough to be a typo
+fn suggest<'a>(name: &str, known: impl Iterator<Item = &'a String>) -> Option<String> {
+    let max_distance = (name.chars().count() / 3).max(2);
+    known
+        .map(|k| (edit_distance(name, k), k))
+        .filter(|(d, _)| *d <= max_distance)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, k)| k.clone())
+}
+
+fn join(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
+
+fn collect_unknown(value: &Value, schema: &Value, path: &str, unknown: &mut Vec<UnknownField>) {
+    match value {
+        Value::Object(fields) => {
+            // maps and free form objects accept any field
+            let properties = match schema.get("properties").and_then(|p| p.as_object()) {
+                Some(v) if schema.get("additionalProperties").is_none() => v,
+                _ => return,
+            };
+            for (name, v) in fields.iter() {
+                match properties.get(name) {
+                    Some(s) => collect_unknown(v, s, &join(path, name), unknown),
+                    None => unknown.push(UnknownField {
+                        path: join(path, name),
+                        suggestion: suggest(name, properties.keys()),
+                    }),
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(s) = schema.get("items") {
+                for (i, v) in items.iter().enumerate() {
+                    collect_unknown(v, s, &format!("{}[{}]", path, i), unknown);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+// unknown_fields returns the fields of value that T does not have, nested objects included
+pub fn unknown_fields<T: Apiv2Schema>(value: &Value) -> Vec<UnknownField> {
+    let mut raw = T::raw_schema();
+    raw.remove_refs();
+    let schema = serde_json::to_value(raw).unwrap_or(Value::Null);
+    let mut unknown = vec![];
+    collect_unknown(value, &schema, "", &mut unknown);
+    unknown
+}
+
+// parse_input deserializes a request body, rejecting unknown fields in strict mode
+pub fn parse_input<T: DeserializeOwned + Apiv2Schema>(value: Value, mode: InputMode) -> Result<T> {
+    if mode == InputMode::Strict {
+        let unknown = unknown_fields::<T>(&value);
+        if !unknown.is_empty() {
+            let messages: Vec<String> = unknown.iter().map(|u| u.to_string()).collect();
+            bail!("{}", messages.join("; "));
+        }
+    }
+    Ok(serde_json::from_value(value)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::def::{DirectionsInput, MassiveDistanceMatrixInput, MatrixOutput};
+    use serde_json::json;
+
+    #[test]
+    fn test_unknown_fields() {
+        let input = json!({
+            "origin": "1.3,103.8",
+            "destination": "1.29,103.85",
+            "departure_tme": 1609459200,
+            "zzz": true,
+        });
+        let unknown = unknown_fields::<DirectionsInput>(&input);
+        assert_eq!(
+            unknown,
+            vec![
+                UnknownField {
+                    path: "departure_tme".to_string(),
+                    suggestion: Some("departure_time".to_string()),
+                },
+                UnknownField {
+                    path: "zzz".to_string(),
+                    suggestion: None,
+                },
+            ]
+        );
+        assert_eq!(
+            unknown[0].to_string(),
+            "unknown field `departure_tme`, did you mean `departure_time`?"
+        );
+
+        // lenient mode keeps the current behaviour
+        assert!(parse_input::<DirectionsInput>(input.clone(), InputMode::Lenient).is_ok());
+        let e = parse_input::<DirectionsInput>(input, InputMode::Strict)
+            .err()
+            .unwrap();
+        assert_eq!(
+            e.to_string(),
+            "unknown field `departure_tme`, did you mean `departure_time`?; unknown field `zzz`"
+        );
+    }
+
+    #[test]
+    fn test_nested_unknown_fields() {
+        let input = json!({
+            "matrix_input": {"origns": "1.3,103.8", "destinations": "1.29,103.85"},
+            "task_id": "t",
+            "chunk_id": "0",
+        });
+        let unknown = unknown_fields::<MassiveDistanceMatrixInput>(&input);
+        assert_eq!(unknown.len(), 1);
+        assert_eq!(unknown[0].path, "matrix_input.origns");
+        assert_eq!(unknown[0].suggestion.as_deref(), Some("origins"));
+
+        let output = json!({
+            "status": "Ok",
+            "rows": [{"elements": [{"duration": {"value": 1}, "distance": {"valu": 2}}]}],
+        });
+        let unknown = unknown_fields::<MatrixOutput>(&output);
+        assert_eq!(unknown[0].path, "rows[0].elements[0].distance.valu");
+        assert!(parse_input::<MatrixOutput>(output, InputMode::Strict).is_err());
+    }
+}
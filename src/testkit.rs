@@ -0,0 +1,322 @@
+// builders and fixtures for tests, enabled by the `testkit` feature
+use crate::coord::{Coord, Locatable};
+use crate::def::{ConfigArea, ConfigCoord, ConfigPolygon, MaaasAreaConfig};
+use crate::prepared_polygon::{prepare_polygons, PreparedPolygon};
+use crate::util::{Area, AreaFlexible};
+use geo::{LineString, Polygon};
+use std::collections::{BTreeMap, HashMap};
+
+fn square_ring(center: &Coord, half_size: f64) -> Vec<(f64, f64)> {
+    let (lat, lng) = (center.lat(), center.lng());
+    vec![
+        (lng - half_size, lat - half_size),
+        (lng + half_size, lat - half_size),
+        (lng + half_size, lat + half_size),
+        (lng - half_size, lat + half_size),
+        (lng - half_size, lat - half_size),
+    ]
+}
+
+// square_polygon returns a square of 2 * half_size degrees around center
+pub fn square_polygon(center: &Coord, half_size: f64) -> Polygon<f64> {
+    Polygon::new(LineString::from(square_ring(center, half_size)), vec![])
+}
+
+// ring_polygon returns a square around center with a square hole of inner_half_size
+pub fn ring_polygon(center: &Coord, outer_half_size: f64, inner_half_size: f64) -> Polygon<f64> {
+    Polygon::new(
+        LineString::from(square_ring(center, outer_half_size)),
+        vec![LineString::from(square_ring(center, inner_half_size))],
+    )
+}
+
+// circle_polygon returns a polygon of n vertices on a circle of radius degrees, for dense borders
+pub fn circle_polygon(center: &Coord, radius: f64, n: usize) -> Polygon<f64> {
+    let mut coords: Vec<(f64, f64)> = (0..n)
+        .map(|i| {
+            let angle = i as f64 / n as f64 * std::f64::consts::PI * 2.0;
+            (
+                center.lng() + radius * angle.cos(),
+                center.lat() + radius * angle.sin(),
+            )
+        })
+        .collect();
+    coords.push(coords[0]);
+    Polygon::new(LineString::from(coords), vec![])
+}
+
+// square_poly_string returns the content of a .poly file with a square around center
+pub fn square_poly_string(name: &str, center: &Coord, half_size: f64) -> String {
+    poly_string(
+        name,
+        &[("1", &LineString::from(square_ring(center, half_size)))],
+    )
+}
+
+// poly_string returns the content of a .poly file with a section per (name, ring), sections
+//  named with a `!` prefix being excluded from the area
+pub fn poly_string(name: &str, sections: &[(&str, &LineString<f64>)]) -> String {
+    let mut r = format!("{}\n", name);
+    for (section, ring) in sections.iter() {
+        r.push_str(&format!("{}\n", section));
+        for c in ring.0.iter() {
+            r.push_str(&format!("   {}   {}\n", c.x, c.y));
+        }
+        r.push_str("END\n");
+    }
+    r.push_str("END\n");
+    r
+}
+
+// polygons_map builds the area name => polygons map expected by find_service
+pub fn polygons_map(
+    areas: Vec<(&str, Vec<Polygon<f64>>)>,
+) -> HashMap<String, Vec<PreparedPolygon>> {
+    areas
+        .into_iter()
+        .map(|(name, polygons)| (name.to_string(), prepare_polygons(polygons)))
+        .collect()
+}
+
+// grid_coords returns rows * cols coordinates spaced by step degrees, starting at origin
+pub fn grid_coords(origin: &Coord, rows: usize, cols: usize, step: f64) -> Vec<Coord> {
+    let mut r = Vec::with_capacity(rows * cols);
+    for i in 0..rows {
+        for j in 0..cols {
+            r.push(Coord::new(
+                origin.lat() + i as f64 * step,
+                origin.lng() + j as f64 * step,
+            ));
+        }
+    }
+    r
+}
+
+// Rng is a small seeded generator (xorshift64*) for fixtures,
+//  the same seed always generates the same sequence
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed.max(1) }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    // next_f64 returns a value in [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    // range_f64 returns a value in [low, high)
+    pub fn range_f64(&mut self, low: f64, high: f64) -> f64 {
+        low + self.next_f64() * (high - low)
+    }
+
+    // range_usize returns a value in [low, high)
+    pub fn range_usize(&mut self, low: usize, high: usize) -> usize {
+        low + (self.next_u64() % (high - low).max(1) as u64) as usize
+    }
+}
+
+// random_coords returns n coordinates within half_size degrees of center,
+//  the same seed always generates the same coordinates
+pub fn random_coords(center: &Coord, half_size: f64, n: usize, seed: u64) -> Vec<Coord> {
+    let mut rng = Rng::new(seed);
+    (0..n)
+        .map(|_| {
+            let lat = center.lat() + rng.range_f64(-half_size, half_size);
+            let lng = center.lng() + rng.range_f64(-half_size, half_size);
+            Coord::new(lat, lng)
+        })
+        .collect()
+}
+
+pub struct AreaBuilder {
+    area: Area,
+}
+
+impl AreaBuilder {
+    pub fn new(name: &str, default_service: &str) -> AreaBuilder {
+        AreaBuilder {
+            area: Area {
+                name: name.into(),
+                default_service: default_service.into(),
+                mappings: BTreeMap::new(),
+                allowed_context: None,
+                time_dependant: None,
+                flexible: None,
+                time_dependant_settings: None,
+                flexible_setting: None,
+                log_coord_precision: None,
+                simplify_tolerance_meters: None,
+            },
+        }
+    }
+
+    pub fn mapping(mut self, mode: &str, service: &str) -> AreaBuilder {
+        self.area.mappings.insert(mode.to_string(), service.into());
+        self
+    }
+
+    pub fn allowed_context(mut self, service: &str, ctx: &str) -> AreaBuilder {
+        self.area
+            .allowed_context
+            .get_or_insert_with(BTreeMap::new)
+            .entry(service.to_string())
+            .or_default()
+            .push(ctx.to_string());
+        self
+    }
+
+    pub fn time_dependant(mut self, service: &str, ctx: &str) -> AreaBuilder {
+        self.area
+            .time_dependant
+            .get_or_insert_with(BTreeMap::new)
+            .entry(service.to_string())
+            .or_default()
+            .insert(ctx.to_string(), true);
+        self
+    }
+
+    pub fn flexible(mut self, default_service: &str, mappings: &[(&str, &str)]) -> AreaBuilder {
+        self.area.flexible_setting = Some(AreaFlexible {
+            default_service: default_service.into(),
+            mappings: mappings
+                .iter()
+                .map(|(k, v)| (k.to_string(), (*v).into()))
+                .collect(),
+            allowed_context: None,
+            time_dependant: None,
+            time_dependant_settings: None,
+        });
+        self
+    }
+
+    pub fn flexible_allowed_context(mut self, service: &str, ctx: &str) -> AreaBuilder {
+        if let Some(flexible) = self.area.flexible_setting.as_mut() {
+            flexible
+                .allowed_context
+                .get_or_insert_with(BTreeMap::new)
+                .entry(service.to_string())
+                .or_default()
+                .push(ctx.to_string());
+        }
+        self
+    }
+
+    pub fn flexible_time_dependant(mut self, service: &str, ctx: &str) -> AreaBuilder {
+        if let Some(flexible) = self.area.flexible_setting.as_mut() {
+            flexible
+                .time_dependant
+                .get_or_insert_with(BTreeMap::new)
+                .entry(service.to_string())
+                .or_default()
+                .insert(ctx.to_string(), true);
+        }
+        self
+    }
+
+    pub fn log_coord_precision(mut self, precision: u32) -> AreaBuilder {
+        self.area.log_coord_precision = Some(precision);
+        self
+    }
+
+    pub fn simplify_tolerance_meters(mut self, tolerance: f64) -> AreaBuilder {
+        self.area.simplify_tolerance_meters = Some(tolerance);
+        self
+    }
+
+    pub fn build(self) -> Area {
+        self.area
+    }
+}
+
+// example_maaas_area_config returns a config with a square area around center
+pub fn example_maaas_area_config(name: &str, center: &Coord, half_size: f64) -> MaaasAreaConfig {
+    MaaasAreaConfig {
+        areas: vec![ConfigArea {
+            id: name.to_string(),
+            polygons: vec![ConfigPolygon {
+                name: format!("{}-0", name),
+                coords: square_ring(center, half_size)
+                    .into_iter()
+                    .map(|(lng, lat)| ConfigCoord { lat, lng })
+                    .collect(),
+            }],
+        }],
+        repair_polygons: false,
+        parsed_areas: HashMap::new(),
+        load_reports: HashMap::new(),
+        inited: false,
+    }
+}
+
+pub const EXAMPLE_MAAAS_CONFIG: &str = "clusters:
+  - id: sg
+    address: http://sg.example.com
+    nbroutes:
+      - singapore-4w
+    location:
+      lat: 1.3
+      lng: 103.8
+  - id: us
+    address: http://us.example.com
+    nbroutes:
+      - usa-4w
+    location:
+      lat: 40.7
+      lng: -74.0
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::def::MaaasConfig;
+    use crate::find_service;
+
+    #[test]
+    fn test_testkit() {
+        let center = Coord::new(1.3, 103.8);
+        let polygons = polygons_map(vec![
+            ("singapore", vec![square_polygon(&center, 0.5)]),
+            ("ring", vec![ring_polygon(&center, 2.0, 1.0)]),
+        ]);
+        let areas = vec![
+            AreaBuilder::new("singapore", "4w")
+                .mapping("car", "4w")
+                .build(),
+            AreaBuilder::new("ring", "2w").build(),
+        ];
+
+        let coords = random_coords(&center, 0.4, 20, 7);
+        assert_eq!(coords.len(), 20);
+        assert_eq!(random_coords(&center, 0.4, 20, 7)[3].lat(), coords[3].lat());
+        let (service, _) =
+            find_service(&None, &coords, &polygons, &areas, false, None, false).unwrap();
+        assert_eq!(service.area.name, "singapore");
+        assert_eq!(service.mode, "4w");
+
+        // inside the ring but outside of the hole
+        let coords = grid_coords(&Coord::new(2.5, 104.5), 2, 2, 0.1);
+        let (service, _) =
+            find_service(&None, &coords, &polygons, &areas, false, None, false).unwrap();
+        assert_eq!(service.area.name, "ring");
+
+        let mut cfg = example_maaas_area_config("singapore", &center, 0.5);
+        assert!(cfg.polygons("singapore").unwrap()[0]
+            .exterior()
+            .points_iter()
+            .any(|p| p.x() == 103.3));
+
+        let maaas: MaaasConfig = serde_yaml::from_str(EXAMPLE_MAAAS_CONFIG).unwrap();
+        assert!(maaas.lookup("sg", "singapore-4w").unwrap().local);
+    }
+}
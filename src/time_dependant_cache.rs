@@ -0,0 +1,230 @@
+// time dependant settings kept between populations of the borders, so that slot plans can be
+//  refreshed on a timer without reloading the borders or restarting the pods. settings younger
+//  than the ttl are not fetched again, older ones are fetched again through the SettingFetcher
+//  (the HTTP one revalidates them with their ETag or Last-Modified date), and a setting that
+//  fails to refresh keeps its last value
+use crate::deadline::Deadline;
+use crate::setting_fetcher::{HttpSettingFetcher, SettingFetcher};
+use crate::{Borders, Result, TimeDependantSetting};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_TIME_DEPENDANT_TTL: Duration = Duration::from_secs(300);
+
+struct CacheEntry {
+    setting: TimeDependantSetting,
+    // None once expired
+    fetched_at: Option<Instant>,
+}
+
+// TimeDependantCache is keyed by the `<namespace>/<filename>` of the setting files
+pub struct TimeDependantCache {
+    fetcher: Arc<dyn SettingFetcher + Send>,
+    ttl: Duration,
+    entries: Mutex<HashMap<String, CacheEntry>>,
+}
+
+impl Default for TimeDependantCache {
+    fn default() -> Self {
+        TimeDependantCache::new(DEFAULT_TIME_DEPENDANT_TTL)
+    }
+}
+
+impl TimeDependantCache {
+    pub fn new(ttl: Duration) -> TimeDependantCache {
+        TimeDependantCache {
+            fetcher: Arc::new(HttpSettingFetcher::default()),
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // with_fetcher reads the settings with fetcher instead of the public bucket
+    pub fn with_fetcher(mut self, fetcher: Arc<dyn SettingFetcher + Send>) -> TimeDependantCache {
+        self.fetcher = fetcher;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // expire makes the next refresh fetch every setting whatever its age
+    pub fn expire(&self) {
+        for entry in self.entries.lock().unwrap().values_mut() {
+            entry.fetched_at = None;
+        }
+    }
+
+    // refresh populates the time dependant settings of borders again, see
+    //  Borders::populate_time_dependant_setting_with_cache
+    pub async fn refresh(
+        &self,
+        borders: &mut Borders,
+        namespace: &Option<String>,
+        deadline: Option<&Deadline>,
+    ) -> Result<()> {
+        borders
+            .populate_time_dependant_setting_with_cache(namespace, self, deadline)
+            .await
+    }
+
+    // get returns the setting of filename in namespace, fetched only once expired
+    pub async fn get(&self, namespace: &str, filename: &str) -> Result<TimeDependantSetting> {
+        let key = format!("{}/{}", namespace, filename);
+        if let Some(e) = self.entries.lock().unwrap().get(&key) {
+            if e.fetched_at.is_some_and(|t| t.elapsed() < self.ttl) {
+                return Ok(e.setting.clone());
+            }
+        }
+        match self.fetch(namespace, filename).await {
+            Ok(setting) => {
+                self.entries.lock().unwrap().insert(
+                    key,
+                    CacheEntry {
+                        setting: setting.clone(),
+                        fetched_at: Some(Instant::now()),
+                    },
+                );
+                Ok(setting)
+            }
+            Err(e) => match self.entries.lock().unwrap().get(&key) {
+                Some(entry) => {
+                    warn!("failed to refresh {}, last setting kept: {}", key, e);
+                    Ok(entry.setting.clone())
+                }
+                None => Err(e),
+            },
+        }
+    }
+
+    async fn fetch(&self, namespace: &str, filename: &str) -> Result<TimeDependantSetting> {
+        let body = self.fetcher.fetch(namespace, filename).await?;
+        match serde_yaml::from_str(&body) {
+            Ok(v) => Ok(v),
+            Err(e) => bail!("{:?}, contents: {}", e, body),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::replay::{Cassette, ReplayHttp};
+    use crate::setting_fetcher::DEFAULT_SETTING_BASE_URL;
+    use crate::testkit::AreaBuilder;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    // serve answers requests with the setting and an ETag, or 304 when the ETag is sent back.
+    //  requests for other paths fail
+    fn serve(requests: Arc<AtomicUsize>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut lines = vec![];
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    lines.push(line.to_lowercase());
+                }
+                requests.fetch_add(1, Ordering::SeqCst);
+                let body = "setting_type: recurring\n";
+                let resp = if !lines[0].contains("/setting.yaml") {
+                    "HTTP/1.1 500 Internal Server Error\r\ncontent-length: 0\r\n\r\n".to_string()
+                } else if lines.iter().any(|l| l.starts_with("if-none-match: \"v1\"")) {
+                    "HTTP/1.1 304 Not Modified\r\netag: \"v1\"\r\n\r\n".to_string()
+                } else {
+                    format!(
+                        "HTTP/1.1 200 OK\r\netag: \"v1\"\r\ncontent-length: {}\r\n\r\n{}",
+                        body.len(),
+                        body
+                    )
+                };
+                stream.write_all(resp.as_bytes()).unwrap();
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    #[actix_rt::test]
+    async fn test_time_dependant_cache() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let base = serve(requests.clone());
+        let cache = TimeDependantCache::new(Duration::from_secs(60))
+            .with_fetcher(Arc::new(HttpSettingFetcher::new(&base)));
+
+        assert_eq!(
+            cache.get("ns", "setting").await.unwrap().setting_type,
+            "recurring"
+        );
+        assert_eq!(
+            cache.get("ns", "setting").await.unwrap().setting_type,
+            "recurring"
+        );
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        // revalidated with the ETag once expired
+        cache.expire();
+        assert_eq!(
+            cache.get("ns", "setting").await.unwrap().setting_type,
+            "recurring"
+        );
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+        assert_eq!(cache.len(), 1);
+
+        assert!(cache.get("ns", "missing").await.is_err());
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn test_time_dependant_cassette() {
+        let cassette = Arc::new(Cassette::load("time_dependant").unwrap());
+        let fetcher = HttpSettingFetcher::new(DEFAULT_SETTING_BASE_URL)
+            .with_client(ReplayHttp::new(reqwest::Client::new(), cassette.clone()));
+        let cache = TimeDependantCache::new(Duration::from_secs(0)).with_fetcher(Arc::new(fetcher));
+        let mut borders = Borders {
+            area_list: vec![AreaBuilder::new("singapore", "4w")
+                .time_dependant("4w", "")
+                .flexible("4w", &[])
+                .flexible_time_dependant("4w", "")
+                .build()],
+        };
+        let namespace = Some("test".to_string());
+        cache.refresh(&mut borders, &namespace, None).await.unwrap();
+        let area = &borders.area_list[0];
+        assert_eq!(
+            area.time_dependant_setting("4w", "", false)
+                .unwrap()
+                .setting_type,
+            "recurring"
+        );
+        // the flexible setting does not exist
+        assert!(area
+            .flexible_setting
+            .as_ref()
+            .unwrap()
+            .time_dependant_settings
+            .is_none());
+
+        // revalidated, the 304 keeps the setting
+        cache.refresh(&mut borders, &namespace, None).await.unwrap();
+        assert!(borders.area_list[0]
+            .time_dependant_setting("4w", "", false)
+            .is_some());
+        cassette.save().unwrap();
+    }
+}
@@ -0,0 +1,97 @@
+// timings of the stages of a request (area lookup, engine call, post processing...), returned
+//  in the server_timings field of outputs for debug requests and usable as the value of a
+//  Server-Timing header. spans are recorded when the guard returned by `span` is dropped
+use crate::def::ServerTiming;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Default)]
+pub struct Timings {
+    spans: Mutex<Vec<ServerTiming>>,
+}
+
+impl Timings {
+    pub fn new() -> Timings {
+        Timings::default()
+    }
+
+    // span measures the time until the returned guard is dropped
+    pub fn span<'a>(&'a self, name: &str) -> Span<'a> {
+        Span {
+            timings: self,
+            name: name.to_string(),
+            start: Instant::now(),
+        }
+    }
+
+    pub fn record(&self, name: &str, duration: Duration) {
+        self.spans.lock().unwrap().push(ServerTiming {
+            name: name.to_string(),
+            duration_ms: (duration.as_secs_f64() * 1_000_000.0).round() / 1000.0,
+        });
+    }
+
+    // spans in the order they ended
+    pub fn spans(&self) -> Vec<ServerTiming> {
+        self.spans.lock().unwrap().clone()
+    }
+
+    // output is the value of the server_timings field of outputs, only set for debug requests
+    pub fn output(&self, debug: bool) -> Option<Vec<ServerTiming>> {
+        if !debug {
+            return None;
+        }
+        Some(self.spans())
+    }
+
+    // header is the value of a Server-Timing header, e.g. `area_lookup;dur=1.2, engine;dur=35`
+    pub fn header(&self) -> String {
+        self.spans()
+            .iter()
+            .map(|s| format!("{};dur={}", s.name, s.duration_ms))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+pub struct Span<'a> {
+    timings: &'a Timings,
+    name: String,
+    start: Instant,
+}
+
+impl<'a> Drop for Span<'a> {
+    fn drop(&mut self) {
+        self.timings.record(&self.name, self.start.elapsed());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timings() {
+        let timings = Timings::new();
+        {
+            let _area = timings.span("area_lookup");
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        let engine = timings.span("engine");
+        timings.record("post_processing", Duration::from_micros(1500));
+        drop(engine);
+
+        let spans = timings.spans();
+        let names: Vec<&str> = spans.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["area_lookup", "post_processing", "engine"]);
+        assert!(spans[0].duration_ms >= 5.0);
+        assert_eq!(spans[1].duration_ms, 1.5);
+        assert!(timings.header().starts_with(&format!(
+            "area_lookup;dur={}, post_processing;dur=1.5, engine;dur=",
+            spans[0].duration_ms
+        )));
+
+        assert_eq!(timings.output(false), None);
+        assert_eq!(timings.output(true).unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,254 @@
+// estimated toll cost of routes. each area has a toll table listing its gantries, charged when
+//  the geometry of a route passes by them, and its tolled ways, charged when the edges of the
+//  route (debug_info) use them. prices are given by vehicle class, and the price of each crossing
+//  goes through a TollPricing so that services can apply their own rules (time of day, caps...)
+use crate::coord::{cumulative_distances, project_on_path, Coord, Locatable};
+use crate::def::{EdgeInfo, RouteT};
+use crate::polyline;
+use crate::Result;
+use paperclip::actix::Apiv2Schema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// a gantry is crossed by the routes passing within this distance
+const GANTRY_RADIUS_METERS: f64 = 30.0;
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct TollGantry {
+    pub id: String,
+    pub location: Coord,
+    // vehicle class => price
+    pub prices: HashMap<String, f64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct TollWay {
+    pub way_id: i64,
+    // vehicle class => price
+    pub prices: HashMap<String, f64>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct TollTable {
+    pub currency: String,
+    #[serde(default)]
+    pub gantries: Vec<TollGantry>,
+    #[serde(default)]
+    pub ways: Vec<TollWay>,
+}
+
+// TollTables holds the toll table of each area
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TollTables {
+    // area => toll table
+    #[serde(flatten)]
+    pub areas: HashMap<String, TollTable>,
+}
+
+impl TollTables {
+    pub fn from_yaml(content: &str) -> Result<TollTables> {
+        Ok(serde_yaml::from_str(content)?)
+    }
+
+    pub fn get(&self, area: &str) -> Option<&TollTable> {
+        self.areas.get(area)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TollPoint {
+    Gantry(String),
+    Way(i64),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TollCrossing {
+    pub point: TollPoint,
+    // distance along the route where the toll is crossed, in meters
+    pub offset_m: f64,
+    // vehicle class => price, from the toll table
+    pub prices: HashMap<String, f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Apiv2Schema, Clone, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct TollCost {
+    #[doc = "estimated toll cost of the route."]
+    pub amount: f64,
+    #[doc = "currency of the amount, e.g. `SGD`."]
+    pub currency: String,
+    #[doc = "number of tolled gantries and roads the route goes through."]
+    pub crossings: usize,
+}
+
+pub trait TollPricing: Sync {
+    // price returns the price of crossing for vehicle_class, None when it is free
+    fn price(&self, crossing: &TollCrossing, vehicle_class: &str) -> Option<f64>;
+}
+
+// TablePricing charges the prices of the toll table
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TablePricing;
+
+impl TollPricing for TablePricing {
+    fn price(&self, crossing: &TollCrossing, vehicle_class: &str) -> Option<f64> {
+        crossing.prices.get(vehicle_class).cloned()
+    }
+}
+
+impl<F: Fn(&TollCrossing, &str) -> Option<f64> + Sync> TollPricing for F {
+    fn price(&self, crossing: &TollCrossing, vehicle_class: &str) -> Option<f64> {
+        self(crossing, vehicle_class)
+    }
+}
+
+// toll_crossings returns the tolls of table crossed by the route, sorted by offset. a gantry
+//  is charged once per route, a tolled way once each time the route enters it. ways are only
+//  detected with the edges of the route
+pub fn toll_crossings<T: Locatable>(
+    table: &TollTable,
+    geometry: &[T],
+    edges: Option<&[EdgeInfo]>,
+) -> Vec<TollCrossing> {
+    let mut r = vec![];
+    let offsets = cumulative_distances(geometry);
+    for gantry in table.gantries.iter() {
+        if let Some((i, ratio, distance)) = project_on_path(geometry, &gantry.location, 0) {
+            if distance <= GANTRY_RADIUS_METERS {
+                r.push(TollCrossing {
+                    point: TollPoint::Gantry(gantry.id.clone()),
+                    offset_m: offsets[i] + ratio * (offsets[i + 1] - offsets[i]),
+                    prices: gantry.prices.clone(),
+                });
+            }
+        }
+    }
+
+    let ways: HashMap<i64, &TollWay> = table.ways.iter().map(|w| (w.way_id, w)).collect();
+    let mut offset = 0.0;
+    let mut previous = None;
+    for edge in edges.unwrap_or_default() {
+        if previous != Some(edge.way_id) {
+            if let Some(way) = ways.get(&edge.way_id) {
+                r.push(TollCrossing {
+                    point: TollPoint::Way(way.way_id),
+                    offset_m: offset,
+                    prices: way.prices.clone(),
+                });
+            }
+        }
+        previous = Some(edge.way_id);
+        offset += edge.length as f64;
+    }
+    r.sort_by(|a, b| a.offset_m.partial_cmp(&b.offset_m).unwrap());
+    r
+}
+
+// toll_cost sums the prices of crossings for vehicle_class
+pub fn toll_cost(
+    table: &TollTable,
+    crossings: &[TollCrossing],
+    vehicle_class: &str,
+    pricing: &dyn TollPricing,
+) -> TollCost {
+    let prices: Vec<f64> = crossings
+        .iter()
+        .filter_map(|c| pricing.price(c, vehicle_class))
+        .collect();
+    TollCost {
+        amount: prices.iter().sum(),
+        currency: table.currency.clone(),
+        crossings: prices.len(),
+    }
+}
+
+// annotate_route sets the toll cost of route, from its geometry encoded with precision and the
+//  edges of its debug info. routes without geometry are left as is
+pub fn annotate_route<L>(
+    route: &mut RouteT<L>,
+    table: &TollTable,
+    vehicle_class: &str,
+    pricing: &dyn TollPricing,
+    precision: u32,
+) -> Result<()> {
+    let geometry = match route.geometry.as_ref() {
+        Some(v) => polyline::decode(v, precision)?,
+        None => return Ok(()),
+    };
+    let edges = route.debug_info.as_ref().map(|v| v.edge_info.as_slice());
+    let crossings = toll_crossings(table, &geometry, edges);
+    route.toll_cost = Some(toll_cost(table, &crossings, vehicle_class, pricing));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::def::{DebugInfo, Route};
+
+    fn tables() -> TollTables {
+        TollTables::from_yaml(
+            r#"
+singapore:
+  currency: SGD
+  gantries:
+    - id: g1
+      location: {lat: 0.0001, lng: 0.05}
+      prices: {car: 1.5, truck: 3.0}
+    - id: far
+      location: {lat: 0.1, lng: 0.05}
+      prices: {car: 1.5}
+  ways:
+    - way_id: 42
+      prices: {car: 2.0}
+"#,
+        )
+        .unwrap()
+    }
+
+    fn edge(way_id: i64, length: i64) -> EdgeInfo {
+        EdgeInfo {
+            way_id,
+            length,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_toll_crossings() {
+        let tables = tables();
+        let table = tables.get("singapore").unwrap();
+        assert!(tables.get("jakarta").is_none());
+        let geometry = vec![Coord::new(0.0, 0.0), Coord::new(0.0, 0.1)];
+        let edges = vec![edge(1, 100), edge(42, 100), edge(42, 100), edge(2, 100)];
+        let r = toll_crossings(table, &geometry, Some(&edges));
+        assert_eq!(
+            r.iter().map(|c| c.point.clone()).collect::<Vec<_>>(),
+            vec![TollPoint::Way(42), TollPoint::Gantry("g1".to_string())]
+        );
+        assert_eq!(r[0].offset_m, 100.0);
+
+        let cost = toll_cost(table, &r, "car", &TablePricing);
+        assert_eq!(cost.amount, 3.5);
+        assert_eq!(cost.currency, "SGD");
+        assert_eq!(cost.crossings, 2);
+        // the way has no price for trucks
+        assert_eq!(toll_cost(table, &r, "truck", &TablePricing).crossings, 1);
+        let half = |c: &TollCrossing, class: &str| TablePricing.price(c, class).map(|v| v / 2.0);
+        assert_eq!(toll_cost(table, &r, "car", &half).amount, 1.75);
+    }
+
+    #[test]
+    fn test_annotate_route() {
+        let tables = tables();
+        let table = tables.get("singapore").unwrap();
+        let mut route =
+            Route::straight_line_fallback(&Coord::new(0.0, 0.0), &Coord::new(0.0, 0.1), 10.0, 5);
+        route.debug_info = Some(DebugInfo {
+            node_info: vec![],
+            edge_info: vec![edge(42, 100)],
+        });
+        annotate_route(&mut route, table, "car", &TablePricing, 5).unwrap();
+        assert_eq!(route.toll_cost.unwrap().amount, 3.5);
+    }
+}
@@ -0,0 +1,199 @@
+// TravelMode is the `mode` of requests. known modes are matched case insensitively and with a
+//  few aliases, anything else is kept as Unknown so that areas can still map custom modes
+use paperclip::v2::models::DataType;
+use paperclip::v2::schema::TypedData;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::convert::Infallible;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TravelMode {
+    Car,
+    Auto,
+    Bike,
+    Escooter,
+    Truck,
+    TwoWheeler,
+    ThreeWheeler,
+    FourWheeler,
+    SixWheeler,
+    Unknown(String),
+}
+
+const ALIASES: &[(&str, TravelMode)] = &[
+    ("car", TravelMode::Car),
+    ("auto", TravelMode::Auto),
+    ("bike", TravelMode::Bike),
+    ("bicycle", TravelMode::Bike),
+    ("escooter", TravelMode::Escooter),
+    ("e-scooter", TravelMode::Escooter),
+    ("e_scooter", TravelMode::Escooter),
+    ("truck", TravelMode::Truck),
+    ("2w", TravelMode::TwoWheeler),
+    ("3w", TravelMode::ThreeWheeler),
+    ("4w", TravelMode::FourWheeler),
+    ("6w", TravelMode::SixWheeler),
+];
+
+impl TravelMode {
+    // as_str is the canonical name of the mode, Unknown keeps the input as is
+    pub fn as_str(&self) -> &str {
+        match self {
+            TravelMode::Car => "car",
+            TravelMode::Auto => "auto",
+            TravelMode::Bike => "bike",
+            TravelMode::Escooter => "escooter",
+            TravelMode::Truck => "truck",
+            TravelMode::TwoWheeler => "2w",
+            TravelMode::ThreeWheeler => "3w",
+            TravelMode::FourWheeler => "4w",
+            TravelMode::SixWheeler => "6w",
+            TravelMode::Unknown(v) => v.as_str(),
+        }
+    }
+
+    // names returns the names the mode is parsed from, the canonical one first. areas may key
+    //  their mappings by any of them, e.g. `bicycle` or `e-scooter`
+    pub fn names(&self) -> Vec<&str> {
+        let mut r = vec![self.as_str()];
+        r.extend(
+            ALIASES
+                .iter()
+                .filter(|(alias, mode)| mode == self && *alias != self.as_str())
+                .map(|(alias, _)| *alias),
+        );
+        r
+    }
+
+    pub fn is_known(&self) -> bool {
+        !matches!(self, TravelMode::Unknown(_))
+    }
+
+    // is_empty is true for an empty mode, which requests use for the default mode of the area
+    pub fn is_empty(&self) -> bool {
+        self.as_str().is_empty()
+    }
+
+    // is_symmetric is true for the modes routed without turn penalties, for which the duration
+    //  from a to b is close enough to the one from b to a
+    pub fn is_symmetric(&self) -> bool {
+        matches!(self, TravelMode::Bike | TravelMode::Escooter)
+    }
+
+    pub fn parse(input: &str) -> TravelMode {
+        let lowercase = input.trim().to_lowercase();
+        ALIASES
+            .iter()
+            .find(|(alias, _)| *alias == lowercase)
+            .map(|(_, mode)| mode.clone())
+            .unwrap_or_else(|| TravelMode::Unknown(input.to_string()))
+    }
+}
+
+impl FromStr for TravelMode {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(TravelMode::parse(s))
+    }
+}
+
+impl From<&str> for TravelMode {
+    fn from(s: &str) -> Self {
+        TravelMode::parse(s)
+    }
+}
+
+impl fmt::Display for TravelMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl Serialize for TravelMode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for TravelMode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(TravelMode::parse(&String::deserialize(deserializer)?))
+    }
+}
+
+// modes are plain strings in the spec
+impl TypedData for TravelMode {
+    fn data_type() -> DataType {
+        DataType::String
+    }
+}
+
+#[cfg(feature = "json-schema")]
+impl schemars::JsonSchema for TravelMode {
+    fn schema_name() -> String {
+        "TravelMode".to_string()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        String::json_schema(gen)
+    }
+}
+
+// parse_mode converts the mode of callers still holding a string, empty means unset
+pub fn parse_mode(mode: &Option<String>) -> Option<TravelMode> {
+    mode.as_deref()
+        .filter(|m| !m.is_empty())
+        .map(TravelMode::parse)
+}
+
+pub fn mode_string(mode: &Option<TravelMode>) -> Option<String> {
+    mode.as_ref().map(|m| m.as_str().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::def::MatrixInput;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(TravelMode::parse("4W"), TravelMode::FourWheeler);
+        assert_eq!(TravelMode::parse(" Car "), TravelMode::Car);
+        assert_eq!(TravelMode::parse("e-scooter").as_str(), "escooter");
+        assert_eq!(
+            TravelMode::Escooter.names(),
+            vec!["escooter", "e-scooter", "e_scooter"]
+        );
+        assert_eq!(TravelMode::parse("van").names(), vec!["van"]);
+        let typo = TravelMode::parse("bikee");
+        assert!(!typo.is_known());
+        assert_eq!(typo.to_string(), "bikee");
+        assert_eq!(parse_mode(&Some("".to_string())), None);
+        assert_eq!(
+            parse_mode(&Some("2w".to_string())),
+            Some(TravelMode::TwoWheeler)
+        );
+        assert_eq!(
+            mode_string(&Some(TravelMode::Truck)),
+            Some("truck".to_string())
+        );
+    }
+
+    #[test]
+    fn test_serde() {
+        let input: MatrixInput = serde_json::from_value(json!({
+            "origins": "1.3,103.8",
+            "destinations": "1.29,103.85",
+            "mode": "Bicycle",
+        }))
+        .unwrap();
+        assert_eq!(input.mode, Some(TravelMode::Bike));
+        assert_eq!(serde_json::to_value(&input).unwrap()["mode"], "bike");
+        let unknown: TravelMode = serde_json::from_value(json!("4w-flexible")).unwrap();
+        assert_eq!(unknown, TravelMode::Unknown("4w-flexible".to_string()));
+        assert!(serde_json::from_value::<TravelMode>(json!(4)).is_err());
+    }
+}
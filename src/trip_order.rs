@@ -0,0 +1,169 @@
+// visiting order of the waypoints of trip optimizations, following `source`, `destination` and
+//  `roundtrip` of OptimizationInput. a roundtrip is a cycle, which is rotated to start at the
+//  first coordinate or to end at the last one. other trips are paths whose ends must already
+//  match, and a path with neither end fixed is not supported
+use crate::def::{OptimizationInput, OptimizationWaypoint, TripDestination, TripSource};
+use crate::Result;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TripOptions {
+    pub source: TripSource,
+    pub destination: TripDestination,
+    pub roundtrip: bool,
+}
+
+impl Default for TripOptions {
+    fn default() -> Self {
+        TripOptions {
+            source: TripSource::First,
+            destination: TripDestination::Any,
+            roundtrip: true,
+        }
+    }
+}
+
+impl TripOptions {
+    pub fn new(
+        source: TripSource,
+        destination: TripDestination,
+        roundtrip: bool,
+    ) -> Result<TripOptions> {
+        if !roundtrip && source == TripSource::Any && destination == TripDestination::Any {
+            bail!("source=any and destination=any are not supported with roundtrip=false");
+        }
+        Ok(TripOptions {
+            source,
+            destination,
+            roundtrip,
+        })
+    }
+
+    pub fn from_input(input: &OptimizationInput) -> Result<TripOptions> {
+        let default = TripOptions::default();
+        TripOptions::new(
+            input.source.unwrap_or(default.source),
+            input.destination.unwrap_or(default.destination),
+            input.roundtrip.unwrap_or(default.roundtrip),
+        )
+    }
+}
+
+// order_waypoints checks that order, the indexes of the coordinates in visiting order returned
+//  by the solver, is a permutation of them, and rotates roundtrips to honor the options
+pub fn order_waypoints(order: &[usize], options: &TripOptions) -> Result<Vec<usize>> {
+    let n = order.len();
+    let mut seen = vec![false; n];
+    for i in order.iter() {
+        if *i >= n || seen[*i] {
+            bail!(
+                "waypoint order {:?} is not a permutation of the coordinates",
+                order
+            );
+        }
+        seen[*i] = true;
+    }
+    if n == 0 {
+        return Ok(vec![]);
+    }
+    let first = options.source == TripSource::First;
+    let last = options.destination == TripDestination::Last;
+
+    if !options.roundtrip {
+        if first && order[0] != 0 {
+            bail!(
+                "trip starts at waypoint {} instead of the first one",
+                order[0]
+            );
+        }
+        if last && order[n - 1] != n - 1 {
+            bail!(
+                "trip ends at waypoint {} instead of the last one",
+                order[n - 1]
+            );
+        }
+        return Ok(order.to_vec());
+    }
+
+    let mut r = order.to_vec();
+    if first {
+        let at = r.iter().position(|i| *i == 0).unwrap_or(0);
+        r.rotate_left(at);
+    } else if last {
+        let at = r.iter().position(|i| *i == n - 1).unwrap_or(0);
+        r.rotate_left((at + 1) % n);
+    }
+    if first && last && r[n - 1] != n - 1 {
+        bail!(
+            "the first and the last waypoints are not next to each other in the trip {:?}",
+            order
+        );
+    }
+    Ok(r)
+}
+
+// fix_waypoints sets the fields of waypoints, given in the order of the coordinates, from the
+//  visiting order of a single trip
+pub fn fix_waypoints(waypoints: &mut [OptimizationWaypoint], order: &[usize]) {
+    for (position, i) in order.iter().enumerate() {
+        if let Some(w) = waypoints.get_mut(*i) {
+            w.trips_index = 0;
+            w.waypoint_index = position as i64;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::def::Location;
+
+    #[test]
+    fn test_order_waypoints() {
+        let roundtrip = TripOptions::default();
+        assert_eq!(
+            order_waypoints(&[2, 0, 3, 1], &roundtrip).unwrap(),
+            vec![0, 3, 1, 2]
+        );
+        let to_last = TripOptions::new(TripSource::Any, TripDestination::Last, true).unwrap();
+        assert_eq!(
+            order_waypoints(&[2, 3, 0, 1], &to_last).unwrap(),
+            vec![0, 1, 2, 3]
+        );
+        let both = TripOptions::new(TripSource::First, TripDestination::Last, true).unwrap();
+        assert!(order_waypoints(&[0, 3, 1, 2], &both).is_err());
+
+        let path = TripOptions::new(TripSource::Any, TripDestination::Last, false).unwrap();
+        assert_eq!(
+            order_waypoints(&[1, 0, 2, 3], &path).unwrap(),
+            vec![1, 0, 2, 3]
+        );
+        assert_eq!(
+            order_waypoints(&[0, 3, 1, 2], &path)
+                .err()
+                .unwrap()
+                .to_string(),
+            "trip ends at waypoint 2 instead of the last one"
+        );
+        assert!(TripOptions::new(TripSource::Any, TripDestination::Any, false).is_err());
+        assert!(order_waypoints(&[0, 0, 1], &roundtrip).is_err());
+    }
+
+    #[test]
+    fn test_fix_waypoints() {
+        let mut waypoints: Vec<OptimizationWaypoint> = (0..3)
+            .map(|i| OptimizationWaypoint {
+                name: format!("w{}", i),
+                location: Location {
+                    latitude: 1.3,
+                    longitude: 103.8,
+                },
+                trips_index: 1,
+                waypoint_index: -1,
+            })
+            .collect();
+        fix_waypoints(&mut waypoints, &[0, 2, 1]);
+        let indexes: Vec<i64> = waypoints.iter().map(|w| w.waypoint_index).collect();
+        assert_eq!(indexes, vec![0, 2, 1]);
+        assert!(waypoints.iter().all(|w| w.trips_index == 0));
+    }
+}
@@ -0,0 +1,173 @@
+// unit systems of the distances told to users. engines always return meters, UnitSystem is
+//  resolved per request from an explicit `units` param, the country of the route or the
+//  region of `lang`, and the helpers below convert voice instructions and format distances
+use crate::def::{LegT, RouteT, VoiceInstruction};
+
+pub const METERS_PER_MILE: f64 = 1609.344;
+pub const METERS_PER_FOOT: f64 = 0.3048;
+
+// countries signing road distances in miles, as alpha-2 and alpha-3 codes
+const IMPERIAL_COUNTRIES: &[&str] = &["us", "usa", "gb", "gbr", "uk", "lr", "lbr", "mm", "mmr"];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UnitSystem {
+    Metric,
+    Imperial,
+}
+
+impl UnitSystem {
+    // as_str is the value of VoiceInstruction.unit
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UnitSystem::Metric => "metric",
+            UnitSystem::Imperial => "imperial",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<UnitSystem> {
+        match s.trim().to_lowercase().as_str() {
+            "metric" | "km" => Some(UnitSystem::Metric),
+            "imperial" | "mi" | "miles" => Some(UnitSystem::Imperial),
+            _ => None,
+        }
+    }
+
+    pub fn of_country(country_code: &str) -> UnitSystem {
+        if IMPERIAL_COUNTRIES.contains(&country_code.trim().to_lowercase().as_str()) {
+            return UnitSystem::Imperial;
+        }
+        UnitSystem::Metric
+    }
+
+    // resolve picks the explicit param if valid, then the country code, then the region of
+    //  lang, e.g. `en-US` or `en_GB`. metric otherwise
+    pub fn resolve(
+        explicit: Option<&str>,
+        lang: Option<&str>,
+        country_code: Option<&str>,
+    ) -> UnitSystem {
+        if let Some(v) = explicit.and_then(UnitSystem::parse) {
+            return v;
+        }
+        if let Some(v) = country_code.filter(|v| !v.trim().is_empty()) {
+            return UnitSystem::of_country(v);
+        }
+        match lang.and_then(|v| v.split(&['-', '_'][..]).nth(1)) {
+            Some(region) => UnitSystem::of_country(region),
+            None => UnitSystem::Metric,
+        }
+    }
+
+    // distance_along_geometry converts meters to the unit of voice instructions, feet for
+    //  imperial
+    pub fn distance_along_geometry(&self, meters: f64) -> i32 {
+        match self {
+            UnitSystem::Metric => meters.round() as i32,
+            UnitSystem::Imperial => (meters / METERS_PER_FOOT).round() as i32,
+        }
+    }
+
+    // format_distance renders meters the way instructions tell them: meters rounded to 10
+    //  below 1 km, feet rounded to 50 below 0.1 mi, and one decimal above
+    pub fn format_distance(&self, meters: f64) -> String {
+        match self {
+            UnitSystem::Metric if meters < 1000.0 => {
+                format!("{} m", ((meters / 10.0).round() * 10.0) as i64)
+            }
+            UnitSystem::Metric => format!("{:.1} km", meters / 1000.0),
+            UnitSystem::Imperial if meters < 0.1 * METERS_PER_MILE => {
+                let feet = meters / METERS_PER_FOOT;
+                format!("{} ft", ((feet / 50.0).round() * 50.0) as i64)
+            }
+            UnitSystem::Imperial => format!("{:.1} mi", meters / METERS_PER_MILE),
+        }
+    }
+}
+
+// localize_voice_instructions converts the distances of instructions to unit, instructions
+//  without a known unit are in meters
+pub fn localize_voice_instructions(instructions: &mut [VoiceInstruction], unit: UnitSystem) {
+    for v in instructions.iter_mut() {
+        let from = UnitSystem::parse(&v.unit).unwrap_or(UnitSystem::Metric);
+        if from != unit {
+            let meters = match from {
+                UnitSystem::Metric => v.distance_along_geometry as f64,
+                UnitSystem::Imperial => v.distance_along_geometry as f64 * METERS_PER_FOOT,
+            };
+            v.distance_along_geometry = unit.distance_along_geometry(meters);
+        }
+        v.unit = unit.as_str().to_string();
+    }
+}
+
+// localize_route converts the voice instructions of all the steps of route
+pub fn localize_route<A>(route: &mut RouteT<LegT<A>>, unit: UnitSystem) {
+    for leg in route.legs.iter_mut().flatten() {
+        for step in leg.steps.iter_mut().flatten() {
+            if let Some(maneuver) = step.maneuver.as_mut() {
+                localize_voice_instructions(&mut maneuver.voice_instruction, unit);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        assert_eq!(UnitSystem::resolve(None, None, None), UnitSystem::Metric);
+        assert_eq!(
+            UnitSystem::resolve(None, Some("en-US"), None),
+            UnitSystem::Imperial
+        );
+        assert_eq!(
+            UnitSystem::resolve(None, Some("en_GB"), Some("sgp")),
+            UnitSystem::Metric
+        );
+        assert_eq!(
+            UnitSystem::resolve(Some("metric"), Some("en-US"), Some("USA")),
+            UnitSystem::Metric
+        );
+        assert_eq!(
+            UnitSystem::resolve(Some("parsecs"), None, Some("gb")),
+            UnitSystem::Imperial
+        );
+        assert_eq!(
+            UnitSystem::resolve(None, Some("en"), None),
+            UnitSystem::Metric
+        );
+    }
+
+    #[test]
+    fn test_localize_voice_instructions() {
+        assert_eq!(UnitSystem::Metric.format_distance(234.0), "230 m");
+        assert_eq!(UnitSystem::Metric.format_distance(1260.0), "1.3 km");
+        assert_eq!(UnitSystem::Imperial.format_distance(100.0), "350 ft");
+        assert_eq!(UnitSystem::Imperial.format_distance(2414.0), "1.5 mi");
+
+        let mut instructions = vec![
+            VoiceInstruction {
+                distance_along_geometry: 1000,
+                unit: "metric".to_string(),
+                instruction: "turn left".to_string(),
+            },
+            VoiceInstruction {
+                distance_along_geometry: 500,
+                unit: "imperial".to_string(),
+                instruction: "turn right".to_string(),
+            },
+        ];
+        localize_voice_instructions(&mut instructions, UnitSystem::Imperial);
+        assert_eq!(instructions[0].distance_along_geometry, 3281);
+        assert_eq!(instructions[0].unit, "imperial");
+        assert_eq!(instructions[1].distance_along_geometry, 500);
+        localize_voice_instructions(&mut instructions, UnitSystem::Imperial);
+        assert_eq!(instructions[0].distance_along_geometry, 3281);
+        localize_voice_instructions(&mut instructions, UnitSystem::Metric);
+        assert_eq!(instructions[0].distance_along_geometry, 1000);
+        assert_eq!(instructions[1].distance_along_geometry, 152);
+        assert_eq!(instructions[1].unit, "metric");
+    }
+}
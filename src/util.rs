@@ -1,17 +1,67 @@
+use crate::deadline::{check_deadline, run_with_deadline, Deadline};
 use crate::def::{MaaasAreaConfig, MaaasConfig};
+use crate::error::{NbroutesError, NbroutesResult};
+#[cfg(not(feature = "gsutil"))]
+use crate::gcs::default_reader;
+use crate::intern::Name;
+use crate::mdm_io::ObjectStore;
 use crate::{Result, TimeDependantSetting};
 use async_process::Command;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use std::ops::Range;
 use std::str::FromStr;
 use std::string::ToString;
 
-const EARTH_RADIUS_METER: f64 = 6373000.0_f64;
+pub(crate) const EARTH_RADIUS_METER: f64 = 6373000.0_f64;
+
+const MAAAS_AREA_CONFIG_PATH: &str = "gs://maaas/maaas-area-cfg.yaml";
+
+// split_list_spans returns the byte ranges of the items of a `|` separated list, items are
+//  trimmed and empty items at both ends (e.g. trailing separators) are ignored. empty items
+//  in the middle are rejected. lists with one item per coordinate go through
+//  split_positional_list instead
+pub(crate) fn split_list_spans(input: &str) -> Result<Vec<Range<usize>>> {
+    let mut spans = vec![];
+    let mut start = 0;
+    for item in input.split('|') {
+        let trimmed = item.trim_start();
+        let item_start = start + item.len() - trimmed.len();
+        spans.push(item_start..item_start + trimmed.trim_end().len());
+        start += item.len() + 1;
+    }
+    while spans.last().map(|span| span.is_empty()).unwrap_or(false) {
+        spans.pop();
+    }
+    let start = spans.iter().take_while(|span| span.is_empty()).count();
+    let spans = spans.split_off(start);
+    if spans.is_empty() {
+        bail!("empty list");
+    }
+    if let Some(idx) = spans.iter().position(|span| span.is_empty()) {
+        bail!("empty item at index {}", idx);
+    }
+    Ok(spans)
+}
+
+// split_positional_list splits a list with one item per coordinate, e.g. approaches or
+//  bearings. every item is kept trimmed, an empty one (e.g. `curb||unrestricted`) leaving its
+//  coordinate unconstrained
+pub(crate) fn split_positional_list(input: &str, separator: char) -> Vec<&str> {
+    input.split(separator).map(|item| item.trim()).collect()
+}
+
+// split_list splits a `|` separated list with the rules of split_list_spans
+pub(crate) fn split_list(input: &str) -> Result<Vec<&str>> {
+    Ok(split_list_spans(input)?
+        .into_iter()
+        .map(|span| &input[span])
+        .collect())
+}
 
 pub fn parse_list<T: FromStr>(input: &str) -> Result<Vec<T>> {
     let mut r: Vec<T> = Vec::new();
-    let items = input.split("|");
-    for item in items {
+    for item in split_list(input)? {
         match item.parse::<T>() {
             Ok(v) => {
                 r.push(v);
@@ -27,28 +77,120 @@ pub fn encode_list<T: ToString>(input: Vec<T>) -> String {
     x.join("|")
 }
 
-pub async fn gsutil(input: &str) -> Result<String> {
-    let output = Command::new("gsutil").arg("cat").arg(input).output().await;
+pub async fn gsutil(input: &str) -> NbroutesResult<String> {
+    gsutil_with_deadline(input, None).await
+}
+
+pub async fn gsutil_with_deadline(
+    input: &str,
+    deadline: Option<&Deadline>,
+) -> NbroutesResult<String> {
+    let output = run_with_deadline(
+        deadline,
+        "gsutil",
+        Command::new("gsutil").arg("cat").arg(input).output(),
+    )
+    .await
+    .map_err(|e| NbroutesError::config_load_failed(input, e))?;
     if output.is_err() {
         warn!("error cat {:?} using gsutil: {:?}", input, output.err());
-        bail!("error loading file using gsutil");
+        return Err(NbroutesError::config_load_failed(
+            input,
+            "error loading file using gsutil".into(),
+        ));
     }
     let output = output.unwrap();
-    Ok(std::str::from_utf8(&output.stdout)?.to_owned())
+    match std::str::from_utf8(&output.stdout) {
+        Ok(v) => Ok(v.to_owned()),
+        Err(e) => Err(NbroutesError::config_load_failed(input, e.into())),
+    }
 }
 
-pub async fn load_maaas_config(path: Option<String>) -> Result<MaaasConfig> {
-    let mut real_path = "gs://maaas/maaas-cfg.yaml";
-    if path.is_some() {
-        real_path = path.as_ref().unwrap().as_str();
+// read_gcs_with_deadline returns the content of a `gs://` file with the native GCS client, or
+//  with the gsutil command when built with the gsutil feature
+pub async fn read_gcs_with_deadline(
+    path: &str,
+    deadline: Option<&Deadline>,
+) -> NbroutesResult<String> {
+    #[cfg(feature = "gsutil")]
+    {
+        gsutil_with_deadline(path, deadline).await
+    }
+    #[cfg(not(feature = "gsutil"))]
+    {
+        read_store_with_deadline(default_reader(), path, deadline).await
     }
-    Ok(serde_yaml::from_str(&gsutil(real_path).await?)?)
 }
 
-pub async fn load_maaas_area_config() -> Result<MaaasAreaConfig> {
-    Ok(serde_yaml::from_str(
-        &gsutil("gs://maaas/maaas-area-cfg.yaml").await?,
-    )?)
+// read_store_with_deadline returns the content of the file at path in store
+pub async fn read_store_with_deadline(
+    store: &dyn ObjectStore,
+    path: &str,
+    deadline: Option<&Deadline>,
+) -> NbroutesResult<String> {
+    let data = run_with_deadline(deadline, "store read", store.get(path))
+        .await
+        .map_err(|e| NbroutesError::config_load_failed(path, e))?
+        .map_err(|e| NbroutesError::config_load_failed(path, e))?;
+    String::from_utf8(data).map_err(|e| NbroutesError::config_load_failed(path, e.into()))
+}
+
+pub async fn load_maaas_config(path: Option<String>) -> NbroutesResult<MaaasConfig> {
+    load_maaas_config_with_deadline(path, None).await
+}
+
+pub async fn load_maaas_config_with_deadline(
+    path: Option<String>,
+    deadline: Option<&Deadline>,
+) -> NbroutesResult<MaaasConfig> {
+    let real_path = maaas_config_path(&path);
+    parse_maaas_config(
+        real_path,
+        &read_gcs_with_deadline(real_path, deadline).await?,
+    )
+}
+
+// load_maaas_config_with_store loads the config from store instead of GCS
+pub async fn load_maaas_config_with_store(
+    store: &dyn ObjectStore,
+    path: Option<String>,
+    deadline: Option<&Deadline>,
+) -> NbroutesResult<MaaasConfig> {
+    let real_path = maaas_config_path(&path);
+    parse_maaas_config(
+        real_path,
+        &read_store_with_deadline(store, real_path, deadline).await?,
+    )
+}
+
+fn maaas_config_path(path: &Option<String>) -> &str {
+    path.as_deref().unwrap_or("gs://maaas/maaas-cfg.yaml")
+}
+
+fn parse_maaas_config(path: &str, contents: &str) -> NbroutesResult<MaaasConfig> {
+    serde_yaml::from_str(contents).map_err(|e| NbroutesError::config_load_failed(path, e.into()))
+}
+
+pub async fn load_maaas_area_config() -> NbroutesResult<MaaasAreaConfig> {
+    load_maaas_area_config_with_deadline(None).await
+}
+
+pub async fn load_maaas_area_config_with_deadline(
+    deadline: Option<&Deadline>,
+) -> NbroutesResult<MaaasAreaConfig> {
+    let path = MAAAS_AREA_CONFIG_PATH;
+    serde_yaml::from_str(&read_gcs_with_deadline(path, deadline).await?)
+        .map_err(|e| NbroutesError::config_load_failed(path, e.into()))
+}
+
+// load_maaas_area_config_with_store loads the area config from store instead of GCS
+pub async fn load_maaas_area_config_with_store(
+    store: &dyn ObjectStore,
+    deadline: Option<&Deadline>,
+) -> NbroutesResult<MaaasAreaConfig> {
+    let path = MAAAS_AREA_CONFIG_PATH;
+    serde_yaml::from_str(&read_store_with_deadline(store, path, deadline).await?)
+        .map_err(|e| NbroutesError::config_load_failed(path, e.into()))
 }
 
 pub(crate) fn straight_distance(lat1: f64, lng1: f64, lat2: f64, lng2: f64) -> f64 {
@@ -67,9 +209,9 @@ pub(crate) fn straight_distance(lat1: f64, lng1: f64, lat2: f64, lng2: f64) -> f
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Area {
-    pub name: String,
-    pub default_service: String,
-    pub mappings: BTreeMap<String, String>,
+    pub name: Name,
+    pub default_service: Name,
+    pub mappings: BTreeMap<String, Name>,
     pub allowed_context: Option<BTreeMap<String, Vec<String>>>,
     pub time_dependant: Option<BTreeMap<String, BTreeMap<String, bool>>>,
     // deprecated
@@ -77,14 +219,93 @@ pub struct Area {
     #[serde(skip_deserializing, skip_serializing)]
     pub time_dependant_settings: Option<BTreeMap<String, BTreeMap<String, TimeDependantSetting>>>,
     pub flexible_setting: Option<AreaFlexible>,
+    // number of decimals kept when logging coordinates of this area
+    pub log_coord_precision: Option<u32>,
+    // polygons are simplified at load time with this tolerance, in meters
+    pub simplify_tolerance_meters: Option<f64>,
 }
 
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct AreaFlexible {
-    pub default_service: String,
-    pub mappings: BTreeMap<String, String>,
+    pub default_service: Name,
+    pub mappings: BTreeMap<String, Name>,
     pub allowed_context: Option<BTreeMap<String, Vec<String>>>,
+    pub time_dependant: Option<BTreeMap<String, BTreeMap<String, bool>>>,
+    #[serde(skip_deserializing, skip_serializing)]
+    pub time_dependant_settings: Option<BTreeMap<String, BTreeMap<String, TimeDependantSetting>>>,
+}
+
+impl Area {
+    // for flexible requests the flexible allowed_context takes precedence when configured,
+    //  otherwise the area level one applies. empty ctx is always allowed
+    pub fn is_context_allowed(&self, mode: &str, ctx: &str, is_flexible_request: bool) -> bool {
+        if ctx.is_empty() {
+            return true;
+        }
+        let mut allowed_context = self.allowed_context.as_ref();
+        if is_flexible_request {
+            if let Some(flexible_allowed_context) = self
+                .flexible_setting
+                .as_ref()
+                .and_then(|f| f.allowed_context.as_ref())
+            {
+                allowed_context = Some(flexible_allowed_context);
+            }
+        }
+        match allowed_context.and_then(|m| m.get(mode)) {
+            Some(ctxs) => ctxs.iter().any(|c| c == ctx),
+            None => false,
+        }
+    }
+
+    // for flexible requests the flexible time dependant setting of the same mode/ctx wins,
+    //  falling back to the area level setting so peak-hour datasets are not skipped
+    pub fn time_dependant_setting(
+        &self,
+        mode: &str,
+        ctx: &str,
+        is_flexible_request: bool,
+    ) -> Option<&TimeDependantSetting> {
+        if is_flexible_request {
+            let flexible = self
+                .flexible_setting
+                .as_ref()
+                .and_then(|f| f.time_dependant_settings.as_ref())
+                .and_then(|s| s.get(mode))
+                .and_then(|s| s.get(ctx));
+            if flexible.is_some() {
+                return flexible;
+            }
+        }
+        self.time_dependant_settings
+            .as_ref()
+            .and_then(|s| s.get(mode))
+            .and_then(|s| s.get(ctx))
+    }
+
+    pub fn get_additional_ctx(
+        &self,
+        mode: &str,
+        ctx: &str,
+        ts: i64,
+        is_flexible_request: bool,
+    ) -> Option<String> {
+        self.time_dependant_setting(mode, ctx, is_flexible_request)?
+            .get_additional_ctx(ts)
+    }
+
+    pub fn get_additional_ctx_with_deadline(
+        &self,
+        mode: &str,
+        ctx: &str,
+        ts: i64,
+        is_flexible_request: bool,
+        deadline: Option<&Deadline>,
+    ) -> Result<Option<String>> {
+        check_deadline(deadline, "ctx resolution")?;
+        Ok(self.get_additional_ctx(mode, ctx, ts, is_flexible_request))
+    }
 }
 
 //uncomment following testcase to ensure gsutil function works as expected
@@ -102,3 +323,129 @@ mod tests {
     }
 }
 */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gcs::{GcsAuth, GcsReader};
+    use crate::replay::{Cassette, ReplayStore};
+    use crate::testkit::AreaBuilder;
+    use proptest::prelude::*;
+    use std::sync::Arc;
+
+    proptest! {
+        #[test]
+        fn test_parse_list_round_trip(
+            values in prop::collection::vec(any::<i64>(), 1..20),
+            spaced: bool,
+            wrapped: bool,
+        ) {
+            let mut input = encode_list(values.clone());
+            // whitespace and separators at both ends should not change the result
+            if spaced {
+                input = input.replace("|", "\t| ");
+            }
+            if wrapped {
+                input = format!("|{}|", input);
+            }
+            prop_assert_eq!(parse_list::<i64>(input.as_str()).unwrap(), values);
+        }
+
+        #[test]
+        fn test_parse_float_list_round_trip(values in prop::collection::vec(-1e6f64..1e6, 1..20)) {
+            let input = encode_list(values.clone());
+            prop_assert_eq!(parse_list::<f64>(input.as_str()).unwrap(), values);
+        }
+
+        #[test]
+        fn test_parse_list_fuzz(input in "[07+| \tx-]{0,20}") {
+            if let Ok(values) = parse_list::<i32>(input.as_str()) {
+                prop_assert!(!values.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_split_list() {
+        assert!(parse_list::<i64>("1||2").is_err());
+        assert!(parse_list::<i64>("").is_err());
+        assert!(parse_list::<i64>(" | ").is_err());
+        assert_eq!(
+            split_positional_list("curb|| unrestricted|", '|'),
+            vec!["curb", "", "unrestricted", ""]
+        );
+    }
+
+    fn area() -> Area {
+        let mut area = AreaBuilder::new("singapore", "4w")
+            .mapping("car", "4w")
+            .allowed_context("4w", "peak")
+            .time_dependant("4w", "peak")
+            .flexible("4w-flexible", &[("car", "4w-flexible")])
+            .flexible_allowed_context("4w-flexible", "flex")
+            .flexible_time_dependant("4w-flexible", "flex")
+            .build();
+        let setting: TimeDependantSetting =
+            serde_yaml::from_str("setting_type: recurring").unwrap();
+        let mut flexible_setting = setting.clone();
+        flexible_setting.setting_type = "days-ahead".to_string();
+
+        let mut settings = BTreeMap::new();
+        settings.insert("4w".to_string(), BTreeMap::new());
+        settings
+            .get_mut("4w")
+            .unwrap()
+            .insert("peak".to_string(), setting);
+        area.time_dependant_settings = Some(settings.clone());
+
+        settings
+            .get_mut("4w")
+            .unwrap()
+            .insert("peak".to_string(), flexible_setting);
+        area.flexible_setting.as_mut().unwrap().time_dependant_settings = Some(settings);
+        area
+    }
+
+    #[test]
+    fn test_flexible_allowed_context() {
+        let area = area();
+        assert!(area.is_context_allowed("4w", "", false));
+        assert!(area.is_context_allowed("4w", "peak", false));
+        assert!(!area.is_context_allowed("4w", "flex", false));
+        assert!(area.is_context_allowed("4w-flexible", "flex", true));
+        assert!(!area.is_context_allowed("4w", "peak", true));
+    }
+
+    #[test]
+    fn test_flexible_time_dependant_precedence() {
+        let area = area();
+        let s = area.time_dependant_setting("4w", "peak", false).unwrap();
+        assert_eq!(s.setting_type, "recurring");
+        let s = area.time_dependant_setting("4w", "peak", true).unwrap();
+        assert_eq!(s.setting_type, "days-ahead");
+        assert!(area.time_dependant_setting("4w", "off", true).is_none());
+
+        let mut area = area;
+        area.flexible_setting.as_mut().unwrap().time_dependant_settings = None;
+        let s = area.time_dependant_setting("4w", "peak", true).unwrap();
+        assert_eq!(s.setting_type, "recurring");
+    }
+
+    #[actix_rt::test]
+    async fn test_maaas_cassette() {
+        let cassette = Arc::new(Cassette::load("maaas").unwrap());
+        let reader = GcsReader::new(GcsAuth::from_env().unwrap_or(GcsAuth::Anonymous));
+        let store = ReplayStore::new(reader, cassette.clone());
+
+        let cfg = load_maaas_config_with_store(&store, None, None).await.unwrap();
+        assert!(cfg.lookup("sg", "singapore-4w").unwrap().local);
+        let mut area_cfg = load_maaas_area_config_with_store(&store, None).await.unwrap();
+        area_cfg.init();
+        assert!(area_cfg.parsed_areas.contains_key("singapore"));
+        let missing = Some("gs://maaas/missing.yaml".to_string());
+        assert!(load_maaas_config_with_store(&store, missing, None)
+            .await
+            .is_err());
+        cassette.save().unwrap();
+    }
+}
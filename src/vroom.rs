@@ -0,0 +1,195 @@
+// conversion of optimization requests into the input document of the VROOM solver. location
+//  indexes are mapped to the custom matrices, which are either the full N×N matrix over
+//  `locations` or the compact matrix of a MatrixPlan. vehicles with a depot start and end at
+//  the depot unless their own start or end is given
+use crate::def::{Break, Job, OptimizationV2PostInput, Shipment, ShipmentStep, Vehicle};
+use crate::matrix_plan::MatrixPlan;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+// profile of the matrices when the request has no routing mode
+pub const DEFAULT_VROOM_PROFILE: &str = "car";
+
+#[derive(Clone, Debug, Default)]
+pub struct VroomMatrices {
+    // None when the matrices are the full N×N matrix over the locations of the request
+    pub plan: Option<MatrixPlan>,
+    pub durations: Vec<Vec<u64>>,
+    pub distances: Option<Vec<Vec<u64>>>,
+    pub costs: Option<Vec<Vec<u64>>>,
+}
+
+impl VroomMatrices {
+    fn index(&self, location_index: u64) -> u64 {
+        match self.plan.as_ref() {
+            Some(p) => p
+                .matrix_index(location_index as usize)
+                .map(|i| i as u64)
+                .unwrap_or(location_index),
+            None => location_index,
+        }
+    }
+}
+
+// set inserts the value when it is some
+fn set<T: Serialize>(object: &mut Map<String, Value>, key: &str, value: &Option<T>) {
+    if let Some(v) = value.as_ref() {
+        object.insert(key.to_string(), json!(v));
+    }
+}
+
+fn object(value: Value) -> Map<String, Value> {
+    match value {
+        Value::Object(m) => m,
+        _ => Map::new(),
+    }
+}
+
+fn vroom_break(b: &Break) -> Value {
+    let mut r = object(json!({"id": b.id, "time_windows": b.time_windows}));
+    set(&mut r, "service", &b.service);
+    set(&mut r, "description", &b.description);
+    Value::Object(r)
+}
+
+fn vroom_vehicle(
+    v: &Vehicle,
+    profile: &str,
+    depots: &HashMap<u64, u64>,
+    matrices: &VroomMatrices,
+) -> Value {
+    let depot = v.depot.and_then(|d| depots.get(&d)).copied();
+    let mut r = object(json!({"id": v.id, "profile": profile}));
+    set(
+        &mut r,
+        "start_index",
+        &v.start_index.or(depot).map(|i| matrices.index(i)),
+    );
+    set(
+        &mut r,
+        "end_index",
+        &v.end_index.or(depot).map(|i| matrices.index(i)),
+    );
+    set(&mut r, "capacity", &v.capacity);
+    set(&mut r, "skills", &v.skills);
+    set(
+        &mut r,
+        "time_window",
+        &v.time_window
+            .as_ref()
+            .map(|tw| tw.iter().map(|t| *t as u64).collect::<Vec<u64>>()),
+    );
+    let breaks: Vec<Value> = v
+        .breaks
+        .iter()
+        .flatten()
+        .chain(v.r#break.iter())
+        .map(vroom_break)
+        .collect();
+    if !breaks.is_empty() {
+        r.insert("breaks".to_string(), json!(breaks));
+    }
+    set(&mut r, "max_tasks", &v.max_tasks);
+    set(
+        &mut r,
+        "costs",
+        &v.costs
+            .as_ref()
+            .and_then(|c| c.fixed)
+            .map(|f| json!({ "fixed": f })),
+    );
+    set(&mut r, "description", &v.description);
+    Value::Object(r)
+}
+
+fn vroom_job(j: &Job, matrices: &VroomMatrices) -> Value {
+    let mut r = object(json!({
+        "id": j.id,
+        "location_index": matrices.index(j.location_index.max(0) as u64),
+    }));
+    set(&mut r, "setup", &j.setup);
+    set(&mut r, "service", &j.service);
+    set(&mut r, "delivery", &j.delivery);
+    set(&mut r, "pickup", &j.pickup);
+    set(&mut r, "skills", &j.skills);
+    set(&mut r, "priority", &j.priority);
+    set(&mut r, "time_windows", &j.time_windows);
+    set(&mut r, "description", &j.description);
+    Value::Object(r)
+}
+
+fn vroom_shipment_step(s: &ShipmentStep, matrices: &VroomMatrices) -> Value {
+    let mut r = object(json!({
+        "id": s.id,
+        "location_index": matrices.index(s.location_index),
+    }));
+    set(&mut r, "service", &s.service);
+    set(&mut r, "time_windows", &s.time_windows);
+    set(&mut r, "description", &s.description);
+    Value::Object(r)
+}
+
+fn vroom_shipment(s: &Shipment, matrices: &VroomMatrices) -> Value {
+    let mut r = object(json!({
+        "pickup": vroom_shipment_step(&s.pickup, matrices),
+        "delivery": vroom_shipment_step(&s.delivery, matrices),
+    }));
+    set(&mut r, "amount", &s.amount);
+    set(&mut r, "skills", &s.skills);
+    set(&mut r, "priority", &s.priority);
+    Value::Object(r)
+}
+
+pub fn vroom_profile(input: &OptimizationV2PostInput) -> String {
+    input
+        .options
+        .as_ref()
+        .and_then(|o| o.routing.as_ref())
+        .and_then(|r| r.mode.clone())
+        .filter(|m| !m.is_empty())
+        .unwrap_or_else(|| DEFAULT_VROOM_PROFILE.to_string())
+}
+
+pub fn to_vroom_document(input: &OptimizationV2PostInput, matrices: &VroomMatrices) -> Value {
+    let profile = vroom_profile(input);
+    let depots: HashMap<u64, u64> = input
+        .depots
+        .iter()
+        .flatten()
+        .map(|d| (d.id, d.location_index))
+        .collect();
+    let mut document = object(json!({
+        "vehicles": input
+            .vehicles
+            .iter()
+            .map(|v| vroom_vehicle(v, &profile, &depots, matrices))
+            .collect::<Vec<Value>>(),
+    }));
+    if let Some(jobs) = input.jobs.as_ref() {
+        document.insert(
+            "jobs".to_string(),
+            json!(jobs
+                .iter()
+                .map(|j| vroom_job(j, matrices))
+                .collect::<Vec<Value>>()),
+        );
+    }
+    if let Some(shipments) = input.shipments.as_ref() {
+        document.insert(
+            "shipments".to_string(),
+            json!(shipments
+                .iter()
+                .map(|s| vroom_shipment(s, matrices))
+                .collect::<Vec<Value>>()),
+        );
+    }
+    let mut profile_matrices = object(json!({ "durations": matrices.durations }));
+    set(&mut profile_matrices, "distances", &matrices.distances);
+    set(&mut profile_matrices, "costs", &matrices.costs);
+    document.insert(
+        "matrices".to_string(),
+        json!({ profile: Value::Object(profile_matrices) }),
+    );
+    Value::Object(document)
+}
@@ -0,0 +1,219 @@
+// warnings collected while building a response. loops that warn per coordinate, per chunk or
+//  per issue push into Warnings instead of the warning field of the output: identical messages
+//  are counted instead of repeated and the number of distinct messages is capped, so that the
+//  memory held stays bounded. the legacy Vec<String> of the outputs is produced at the end
+use crate::closures::ClosureWarning;
+use serde::{Serialize, Serializer};
+use std::fmt;
+
+pub const DEFAULT_MAX_WARNINGS: usize = 20;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Warning {
+    Message(String),
+    // an issue found by the integrity checks
+    Integrity(String),
+    UnreachablePairs { count: usize },
+    // the coordinate at index was moved by meters to be routable
+    CoordinateSnapped { index: usize, meters: f64 },
+    SymmetricApproximation,
+    // the route goes through a closure while it is active
+    Closure(ClosureWarning),
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Warning::Message(v) => f.write_str(v),
+            Warning::Integrity(v) => write!(f, "integrity: {}", v),
+            Warning::UnreachablePairs { count } => {
+                write!(f, "{} origin/destination pairs are unreachable", count)
+            }
+            Warning::CoordinateSnapped { index, meters } => write!(
+                f,
+                "coordinate {} was snapped {:.0} meters away",
+                index, meters
+            ),
+            Warning::SymmetricApproximation => f.write_str(crate::matrix_view::SYMMETRIC_WARNING),
+            Warning::Closure(v) => v.fmt(f),
+        }
+    }
+}
+
+impl From<String> for Warning {
+    fn from(v: String) -> Self {
+        Warning::Message(v)
+    }
+}
+
+impl From<&str> for Warning {
+    fn from(v: &str) -> Self {
+        Warning::Message(v.to_string())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Warnings {
+    max: usize,
+    // distinct warnings in the order they were first pushed, with their counts
+    entries: Vec<(Warning, usize)>,
+    // warnings pushed once max distinct ones were kept
+    omitted: usize,
+}
+
+impl Default for Warnings {
+    fn default() -> Self {
+        Warnings::new()
+    }
+}
+
+impl Warnings {
+    pub fn new() -> Warnings {
+        Warnings::with_max(DEFAULT_MAX_WARNINGS)
+    }
+
+    pub fn with_max(max: usize) -> Warnings {
+        Warnings {
+            max,
+            entries: vec![],
+            omitted: 0,
+        }
+    }
+
+    // from_legacy starts from the warning field of an output
+    pub fn from_legacy(warning: Option<Vec<String>>) -> Warnings {
+        let mut r = Warnings::new();
+        r.extend(warning.into_iter().flatten().map(Warning::Message));
+        r
+    }
+
+    pub fn push<W: Into<Warning>>(&mut self, warning: W) {
+        let warning = warning.into();
+        if let Some(entry) = self.entries.iter_mut().find(|(w, _)| *w == warning) {
+            entry.1 += 1;
+        } else if self.entries.len() < self.max {
+            self.entries.push((warning, 1));
+        } else {
+            self.omitted += 1;
+        }
+    }
+
+    pub fn extend<W: Into<Warning>, I: IntoIterator<Item = W>>(&mut self, warnings: I) {
+        for w in warnings {
+            self.push(w);
+        }
+    }
+
+    // merge adds the warnings of other, with their counts
+    pub fn merge(&mut self, other: Warnings) {
+        for (warning, count) in other.entries {
+            if let Some(entry) = self.entries.iter_mut().find(|(w, _)| *w == warning) {
+                entry.1 += count;
+            } else if self.entries.len() < self.max {
+                self.entries.push((warning, count));
+            } else {
+                self.omitted += count;
+            }
+        }
+        self.omitted += other.omitted;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty() && self.omitted == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Warning, usize)> {
+        self.entries.iter().map(|(w, count)| (w, *count))
+    }
+
+    pub fn omitted(&self) -> usize {
+        self.omitted
+    }
+
+    // to_vec returns the messages as in the warning field of the outputs, repeated messages get
+    //  a count suffix and a last message tells how many were omitted
+    pub fn to_vec(&self) -> Vec<String> {
+        let mut r: Vec<String> = self
+            .entries
+            .iter()
+            .map(|(w, count)| match count {
+                1 => w.to_string(),
+                _ => format!("{} (x{})", w, count),
+            })
+            .collect();
+        if self.omitted > 0 {
+            r.push(format!("{} more warnings omitted", self.omitted));
+        }
+        r
+    }
+
+    pub fn into_legacy(self) -> Option<Vec<String>> {
+        if self.is_empty() {
+            return None;
+        }
+        Some(self.to_vec())
+    }
+
+    // merge_into appends the messages to the warning field of an output
+    pub fn merge_into(self, warning: &mut Option<Vec<String>>) {
+        if self.is_empty() {
+            return;
+        }
+        warning.get_or_insert_with(Vec::new).extend(self.to_vec());
+    }
+}
+
+impl Serialize for Warnings {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_vec().serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_warnings() {
+        let mut w = Warnings::with_max(3);
+        assert!(w.is_empty());
+        assert_eq!(w.clone().into_legacy(), None);
+
+        for i in 0..1000 {
+            w.push(Warning::CoordinateSnapped {
+                index: i % 2,
+                meters: 12.0,
+            });
+        }
+        w.push("no traffic data");
+        w.push(Warning::UnreachablePairs { count: 4 });
+        w.push(Warning::UnreachablePairs { count: 5 });
+        w.push("no traffic data");
+        assert_eq!(w.iter().count(), 3);
+        assert_eq!(w.omitted(), 2);
+        assert_eq!(
+            w.to_vec(),
+            vec![
+                "coordinate 0 was snapped 12 meters away (x500)",
+                "coordinate 1 was snapped 12 meters away (x500)",
+                "no traffic data (x2)",
+                "2 more warnings omitted",
+            ]
+        );
+        assert_eq!(
+            serde_json::to_value(&w).unwrap(),
+            serde_json::json!(w.to_vec())
+        );
+
+        let mut other = Warnings::from_legacy(Some(vec!["no traffic data".to_string()]));
+        other.push(Warning::Integrity("routes[0]: empty geometry".to_string()));
+        w.merge(other);
+        assert_eq!(w.to_vec()[2], "no traffic data (x3)");
+        assert_eq!(w.omitted(), 3);
+
+        let mut legacy = Some(vec!["existing".to_string()]);
+        w.merge_into(&mut legacy);
+        assert_eq!(legacy.as_ref().unwrap().len(), 5);
+        assert_eq!(legacy.unwrap()[0], "existing");
+    }
+}
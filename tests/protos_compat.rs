@@ -0,0 +1,205 @@
+// wire compatibility of the generated protobuf code, see pb/golden/README.md
+use nbroutes_util::protos::*;
+use protobuf::{Message, RepeatedField};
+use std::path::PathBuf;
+
+// set to regenerate the golden files after an intended wire change
+const ENV_UPDATE_GOLDEN: &str = "NBROUTES_UPDATE_GOLDEN";
+
+// field 999, which no message uses, as a varint with value 150
+const UNKNOWN_FIELD: [u8; 4] = [0xb8, 0x3e, 0x96, 0x01];
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("pb")
+        .join("golden")
+        .join(format!("{}.bin", name))
+}
+
+// check_golden makes sure that msg is encoded exactly as the committed golden file, and that
+//  the golden file still decodes to msg
+fn check_golden<M: Message + PartialEq>(name: &str, msg: &M) {
+    let encoded = msg.write_to_bytes().unwrap();
+    let path = golden_path(name);
+    if std::env::var(ENV_UPDATE_GOLDEN).is_ok() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, &encoded).unwrap();
+    }
+    let golden =
+        std::fs::read(&path).unwrap_or_else(|e| panic!("missing golden file {:?}: {}", path, e));
+    assert_eq!(
+        M::parse_from_bytes(&golden).unwrap(),
+        *msg,
+        "{} golden file decodes differently",
+        name
+    );
+    assert_eq!(encoded, golden, "{} is encoded differently", name);
+
+    // fields added by newer versions survive a round-trip through this version
+    let mut with_unknown = golden.clone();
+    with_unknown.extend_from_slice(&UNKNOWN_FIELD);
+    let decoded = M::parse_from_bytes(&with_unknown).unwrap();
+    assert!(decoded.get_unknown_fields().iter().next().is_some());
+    assert_eq!(decoded.write_to_bytes().unwrap(), with_unknown);
+}
+
+fn string_list(values: &[&str]) -> StringListPB {
+    let mut r = StringListPB::new();
+    r.set_values(RepeatedField::from_vec(
+        values.iter().map(|v| v.to_string()).collect(),
+    ));
+    r
+}
+
+fn slot() -> DaysAheadSlotSettingPB {
+    let mut r = DaysAheadSlotSettingPB::new();
+    r.set_id("morning".to_string());
+    r.set_range(vec![6, 10]);
+    r
+}
+
+fn matrix_output() -> MatrixOutputPB {
+    let mut element = MatrixOutputPB_MatrixElement::new();
+    element.mut_duration().set_value(120);
+    element.mut_distance().set_value(1500);
+    let mut row = MatrixOutputPB_MatrixRow::new();
+    row.mut_elements().push(element);
+    let mut r = MatrixOutputPB::new();
+    r.set_status("Ok".to_string());
+    r.mut_rows().push(row);
+    r
+}
+
+fn time_dependant_setting() -> TimeDependantSettingPB {
+    let mut day = DaysAheadDaySettingPB::new();
+    day.set_prefix("d0".to_string());
+    day.mut_slots().push(slot());
+    let mut days_ahead = DaysAheadSettingPB::new();
+    days_ahead.set_timezone(8.0);
+    days_ahead.mut_days().push(day);
+
+    let mut definition = RecurringDayDefinitionPB::new();
+    definition.set_day_type("weekday".to_string());
+    definition.mut_weekday_value().set_values(vec![5, 6]);
+    definition.set_date_value(string_list(&["2021-01-01"]));
+    let mut recurring_day = RecurringDaySettingPB::new();
+    recurring_day.set_name("weekend".to_string());
+    recurring_day.set_prefix("we".to_string());
+    recurring_day.mut_days().push(definition);
+    recurring_day.mut_slots().push(slot());
+    let mut recurring = RecurringSettingPB::new();
+    recurring.set_timezone(-5.5);
+    recurring.mut_days().push(recurring_day);
+
+    let mut r = TimeDependantSettingPB::new();
+    r.set_setting_type("recurring".to_string());
+    r.set_days_ahead_setting(days_ahead);
+    r.set_recurring_setting(recurring);
+    r
+}
+
+// maps have a single entry so that the encoding does not depend on the iteration order
+fn string_list_map() -> StringListMapPB {
+    let mut r = StringListMapPB::new();
+    r.mut_values()
+        .insert("4w".to_string(), string_list(&["truck"]));
+    r
+}
+
+fn bool_map() -> BoolMapPB {
+    let mut r = BoolMapPB::new();
+    r.mut_values().insert("".to_string(), true);
+    r
+}
+
+fn mode_bool_map() -> ModeBoolMapPB {
+    let mut r = ModeBoolMapPB::new();
+    r.mut_values().insert("4w".to_string(), bool_map());
+    r
+}
+
+fn setting_map() -> SettingMapPB {
+    let mut r = SettingMapPB::new();
+    r.mut_values()
+        .insert("".to_string(), time_dependant_setting());
+    r
+}
+
+fn mode_setting_map() -> ModeSettingMapPB {
+    let mut r = ModeSettingMapPB::new();
+    r.mut_values().insert("4w".to_string(), setting_map());
+    r
+}
+
+fn area_flexible() -> AreaFlexiblePB {
+    let mut r = AreaFlexiblePB::new();
+    r.set_default_service("4w".to_string());
+    r.mut_mappings()
+        .insert("bike".to_string(), "2w".to_string());
+    r.set_allowed_context(string_list_map());
+    r.set_time_dependant(mode_bool_map());
+    r.set_time_dependant_settings(mode_setting_map());
+    r
+}
+
+fn area() -> AreaPB {
+    let mut r = AreaPB::new();
+    r.set_name("singapore".to_string());
+    r.set_default_service("4w".to_string());
+    r.mut_mappings().insert("car".to_string(), "4w".to_string());
+    r.set_allowed_context(string_list_map());
+    r.set_time_dependant(mode_bool_map());
+    r.set_flexible(mode_bool_map());
+    r.set_time_dependant_settings(mode_setting_map());
+    r.set_flexible_setting(area_flexible());
+    r.set_log_coord_precision(3);
+    r.set_simplify_tolerance_meters(25.0);
+    r
+}
+
+#[test]
+fn test_golden_files() {
+    check_golden("MatrixOutputPB", &matrix_output());
+    check_golden("StringListPB", &string_list(&["a", "b"]));
+    let mut uint32_list = UInt32ListPB::new();
+    uint32_list.set_values(vec![1, 300]);
+    check_golden("UInt32ListPB", &uint32_list);
+    check_golden("StringListMapPB", &string_list_map());
+    check_golden("BoolMapPB", &bool_map());
+    check_golden("ModeBoolMapPB", &mode_bool_map());
+    check_golden("SettingMapPB", &setting_map());
+    check_golden("ModeSettingMapPB", &mode_setting_map());
+    check_golden("DaysAheadSlotSettingPB", &slot());
+    let setting = time_dependant_setting();
+    check_golden("DaysAheadSettingPB", setting.get_days_ahead_setting());
+    check_golden(
+        "DaysAheadDaySettingPB",
+        &setting.get_days_ahead_setting().get_days()[0],
+    );
+    check_golden("RecurringSettingPB", setting.get_recurring_setting());
+    check_golden(
+        "RecurringDaySettingPB",
+        &setting.get_recurring_setting().get_days()[0],
+    );
+    check_golden(
+        "RecurringDayDefinitionPB",
+        &setting.get_recurring_setting().get_days()[0].get_days()[0],
+    );
+    check_golden("TimeDependantSettingPB", &setting);
+    check_golden("AreaFlexiblePB", &area_flexible());
+    check_golden("AreaPB", &area());
+    let mut borders = BordersPB::new();
+    borders.mut_area_list().push(area());
+    check_golden("BordersPB", &borders);
+}
+
+#[test]
+fn test_unset_optional_fields() {
+    // messages written by older versions lack newer fields, they decode to the defaults
+    let mut area = AreaPB::new();
+    area.set_name("singapore".to_string());
+    let decoded = AreaPB::parse_from_bytes(&area.write_to_bytes().unwrap()).unwrap();
+    assert!(!decoded.has_log_coord_precision());
+    assert!(!decoded.has_flexible_setting());
+    assert_eq!(decoded.get_default_service(), "");
+}
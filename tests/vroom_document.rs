@@ -0,0 +1,91 @@
+// golden files of the VROOM documents built from optimization requests, see
+//  tests/golden/README.md
+use nbroutes_util::def::OptimizationV2PostInput;
+use nbroutes_util::matrix_plan::MatrixPlan;
+use nbroutes_util::vroom::{to_vroom_document, VroomMatrices};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+// set to regenerate the golden files after an intended change of the documents
+const ENV_UPDATE_GOLDEN: &str = "NBROUTES_UPDATE_GOLDEN";
+
+fn check_golden(name: &str, document: &Value) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{}.json", name));
+    if std::env::var(ENV_UPDATE_GOLDEN).is_ok() {
+        let mut content = serde_json::to_string_pretty(document).unwrap();
+        content.push('\n');
+        std::fs::write(&path, content).unwrap();
+    }
+    let golden = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("missing golden file {:?}: {}", path, e));
+    let golden: Value = serde_json::from_str(&golden).unwrap();
+    assert_eq!(*document, golden, "{} is built differently", name);
+}
+
+fn input() -> OptimizationV2PostInput {
+    serde_json::from_value(json!({
+        "key": "secret",
+        "description": "golden",
+        "locations": {"id": 1, "location": ["1.30,103.80", "1.31,103.81", "1.32,103.82", "1.33,103.83", "1.34,103.84"]},
+        "jobs": [
+            {"id": 1, "location_index": 1, "service": 300, "delivery": [2], "skills": [7], "time_windows": [[0, 3600]]},
+            {"id": 2, "location_index": 3, "pickup": [1], "priority": 10, "setup": 60, "description": "back door"},
+        ],
+        "shipments": [{
+            "pickup": {"id": 3, "location_index": 1, "service": 120},
+            "delivery": {"id": 4, "location_index": 3, "time_windows": [[600, 7200]]},
+            "amount": [1],
+        }],
+        "vehicles": [
+            {
+                "id": 1,
+                "start_index": 0,
+                "capacity": [4],
+                "skills": [7],
+                "time_window": [0.0, 28800.0],
+                "break": {"id": 5, "time_windows": [[14400, 16200]], "service": 1800},
+                "costs": {"fixed": 100},
+            },
+            {"id": 2, "depot": 7, "capacity": [3], "max_tasks": 5, "description": "van"},
+        ],
+        "depots": [{"id": 7, "location_index": 4}],
+        "options": {"routing": {"mode": "truck"}},
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_full_matrix_document() {
+    let n = 5;
+    let durations = (0..n)
+        .map(|a| (0..n).map(|b| (a * 10 + b) as u64).collect())
+        .collect();
+    let matrices = VroomMatrices {
+        plan: None,
+        durations,
+        distances: None,
+        costs: None,
+    };
+    check_golden("vroom_full_matrix", &to_vroom_document(&input(), &matrices));
+}
+
+#[test]
+fn test_planned_matrix_document() {
+    let input = input();
+    let plan = MatrixPlan::new(&input).unwrap();
+    let durations = plan.assemble(|a, b| (a * 10 + b) as u64);
+    let distances = plan.assemble(|a, b| (a * 1000 + b * 100) as u64);
+    let matrices = VroomMatrices {
+        plan: Some(plan),
+        durations,
+        distances: Some(distances),
+        costs: None,
+    };
+    check_golden(
+        "vroom_planned_matrix",
+        &to_vroom_document(&input, &matrices),
+    );
+}